@@ -0,0 +1,177 @@
+//! Listes d'URLs à télécharger en lot: texte au format `--input-file` d'aria2, ou JSON.
+//!
+//! Texte: une entrée par ligne (lignes vides et `#` ignorées); plusieurs URLs séparées par des
+//! tabulations servent le même fichier (miroirs). Les lignes indentées qui suivent portent les
+//! options de l'entrée: `out=<nom>`, `dir=<dossier>`, `checksum=<algo>=<hex>` et
+//! `priority=low|normal|high`.
+//! JSON: tableau de chaînes ou d'objets `{ "url", "out", "dir", "mirrors", "checksum", "priority" }`.
+//!
+//! Chaque entrée devient une `DownloadTask`; les entrées invalides (schéma non géré, URL
+//! illisible) sont ignorées avec un avertissement.
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use super::checksum::{ExpectedHash, HashAlgorithm};
+use super::naming::{filename_from_url, sanitize_filename};
+use super::queue::Priority;
+use super::types::DownloadTask;
+
+/// Entrée d'une liste, avant construction de la tâche
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct UrlListEntry {
+    pub url: String,
+    #[serde(default)]
+    pub mirrors: Vec<String>,
+    /// Nom du fichier de sortie (sinon déduit de l'URL)
+    #[serde(default)]
+    pub out: Option<String>,
+    /// Dossier de sortie (sinon celui de l'import)
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
+    /// `<algo>=<hex>`, comme l'option `checksum` d'aria2 (ex: `sha-256=…`)
+    #[serde(default)]
+    pub checksum: Option<String>,
+    /// Rang dans la file (sinon celui de l'import)
+    #[serde(default)]
+    pub priority: Option<Priority>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonEntry {
+    Url(String),
+    Entry(UrlListEntry),
+}
+
+impl UrlListEntry {
+    /// Tâche vers `dir` (ou le dossier de l'entrée), de rang `priority` sauf si l'entrée fixe le
+    /// sien; erreur si l'URL n'est pas exploitable
+    pub fn to_task(&self, dir: &Path, chunk_size: u64, priority: Priority) -> Result<DownloadTask> {
+        let name = match &self.out {
+            // `out` peut contenir des dossiers: seul le dernier composant est gardé
+            Some(out) => Path::new(out).file_name().map(|n| sanitize_filename(&n.to_string_lossy())),
+            None => filename_from_url(&self.url),
+        };
+        let name = name.unwrap_or_else(|| sanitize_filename(self.url.rsplit('/').next().unwrap_or_default()));
+        let mut builder = DownloadTask::builder(self.url.clone())
+            .with_output(self.dir.as_deref().unwrap_or(dir).join(name))
+            .with_chunk_size(chunk_size)
+            .with_mirrors(self.mirrors.iter().cloned())
+            .with_priority(self.priority.unwrap_or(priority));
+        if let Some(checksum) = &self.checksum {
+            let (algorithm, hex) = checksum.split_once('=').context("checksum attendu au format <algo>=<hex>")?;
+            let algorithm = HashAlgorithm::parse(algorithm).with_context(|| format!("Algorithme inconnu: {algorithm}"))?;
+            builder = builder.with_hash(ExpectedHash::new(algorithm, hex));
+        }
+        builder.build()
+    }
+}
+
+/// Analyse une liste au format texte (aria2)
+fn parse_text(content: &str) -> Vec<UrlListEntry> {
+    let mut entries: Vec<UrlListEntry> = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indented = line.starts_with([' ', '\t']);
+        match entries.last_mut() {
+            Some(entry) if indented => match trimmed.split_once('=') {
+                Some(("out", value)) => entry.out = Some(value.trim().to_string()),
+                Some(("dir", value)) => entry.dir = Some(PathBuf::from(value.trim())),
+                Some(("checksum", value)) => entry.checksum = Some(value.trim().to_string()),
+                Some(("priority", value)) => match value.trim() {
+                    "low" => entry.priority = Some(Priority::Low),
+                    "normal" => entry.priority = Some(Priority::Normal),
+                    "high" => entry.priority = Some(Priority::High),
+                    other => tracing::warn!(priority = other, "Priorité de liste d'URLs inconnue"),
+                },
+                _ => tracing::debug!(option = trimmed, "Option de liste d'URLs ignorée"),
+            },
+            _ => {
+                let mut urls = trimmed.split('\t').map(str::trim).filter(|u| !u.is_empty()).map(str::to_string);
+                let url = urls.next().unwrap_or_default();
+                entries.push(UrlListEntry { url, mirrors: urls.collect(), ..Default::default() });
+            }
+        }
+    }
+    entries
+}
+
+/// Entrées d'une liste d'URLs, texte ou JSON (reconnu à son `[` initial)
+pub fn parse_url_list(content: &str) -> Result<Vec<UrlListEntry>> {
+    if !content.trim_start().starts_with('[') {
+        return Ok(parse_text(content));
+    }
+    let entries: Vec<JsonEntry> = serde_json::from_str(content).context("Liste d'URLs JSON invalide")?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| match entry {
+            JsonEntry::Url(url) => UrlListEntry { url, ..Default::default() },
+            JsonEntry::Entry(entry) => entry,
+        })
+        .collect())
+}
+
+/// Lit `path` et produit une tâche par entrée exploitable, écrite dans `dir` et de rang
+/// `priority` par défaut
+pub fn load_url_list(path: &Path, dir: &Path, chunk_size: u64, priority: Priority) -> Result<Vec<DownloadTask>> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("Lire {}", path.display()))?;
+    let mut tasks = Vec::new();
+    for entry in parse_url_list(&content)? {
+        match entry.to_task(dir, chunk_size, priority) {
+            Ok(task) => tasks.push(task),
+            Err(e) => tracing::warn!(url = %entry.url, error = %e, "Entrée de liste d'URLs ignorée"),
+        }
+    }
+    Ok(tasks)
+}
+
+/// Le chemin désigne‑t‑il une liste d'URLs (`.txt`, `.list`, `.json`)?
+pub fn is_url_list_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| ["txt", "list", "json"].iter().any(|ext| e.eq_ignore_ascii_case(ext)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_text_and_json_lists() {
+        let text = concat!(
+            "# épisodes\n",
+            "https://cdn.example.com/ep1.mkv\thttps://mirror.example.net/ep1.mkv\n",
+            "  out=Episode 1.mkv\n",
+            "  checksum=sha-256=ABCDEF\n",
+            "  priority=high\n",
+            "\n",
+            "ftp://ftp.example.org/pub/notes.txt\n",
+            "\tdir=/srv/notes\n",
+            "magnet:?xt=urn:btih:abc\n",
+        );
+        let entries = parse_url_list(text).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].mirrors, vec!["https://mirror.example.net/ep1.mkv"]);
+        assert_eq!(entries[0].out.as_deref(), Some("Episode 1.mkv"));
+
+        let task = entries[0].to_task(Path::new("/tmp/dl"), 1024, Priority::Low).unwrap();
+        assert_eq!(task.output(), Path::new("/tmp/dl/Episode 1.mkv"));
+        assert_eq!(task.hashes(), [ExpectedHash::new(HashAlgorithm::Sha256, "abcdef")]);
+        // Priorité de l'entrée, sinon celle de l'import
+        assert_eq!(task.priority(), Priority::High);
+        let notes = entries[1].to_task(Path::new("/tmp/dl"), 1024, Priority::Low).unwrap();
+        assert_eq!((notes.output(), notes.priority()), (Path::new("/srv/notes/notes.txt"), Priority::Low));
+        // Schéma non géré par le gestionnaire
+        assert!(entries[2].to_task(Path::new("/tmp/dl"), 1024, Priority::Normal).is_err());
+
+        let json = r#"["https://example.com/a.zip", {"url": "https://example.com/b", "out": "../b.iso", "priority": "low"}]"#;
+        let entries = parse_url_list(json).unwrap();
+        assert_eq!(entries[0].url, "https://example.com/a.zip");
+        let task = entries[1].to_task(Path::new("/tmp/dl"), 1024, Priority::High).unwrap();
+        assert_eq!((task.output(), task.priority()), (Path::new("/tmp/dl/b.iso"), Priority::Low));
+        assert!(parse_url_list("[1, 2]").is_err());
+    }
+}
@@ -0,0 +1,227 @@
+//! Contrôles d'intégrité d'un fichier téléchargé: taille finale et empreintes attendues
+//! (Metalink, fichiers d'empreinte).
+//!
+//! Sans empreinte fournie, le gestionnaire peut chercher un fichier voisin `<url>.sha256` ou
+//! `<url>.md5` (`[checksum] fetch_sidecar = true`): le résultat (`Integrity`) est rapporté sans
+//! faire échouer le téléchargement.
+//!
+//! Seule l'empreinte la plus forte fournie est recalculée: un fichier de plusieurs Go n'est
+//! relu qu'une fois, sur le pool bloquant de tokio. Une taille incorrecte est signalée par
+//! l'erreur typée `TruncatedDownload` (retrouvable par `downcast`).
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use md5::Md5;
+use super::error::{DownloadError, DownloadErrorKind};
+
+/// Taille des blocs relus pour le calcul
+const HASH_BLOCK: usize = 1024 * 1024;
+/// Fichiers d'empreinte cherchés à côté de la ressource, du plus au moins fiable
+pub(crate) const SIDECAR_EXTENSIONS: [(&str, HashAlgorithm); 2] = [("sha256", HashAlgorithm::Sha256), ("md5", HashAlgorithm::Md5)];
+/// Taille maximale lue d'un fichier d'empreinte (une page d'erreur n'en est pas un)
+pub(crate) const MAX_SIDECAR_LEN: usize = 64 * 1024;
+
+/// Algorithmes reconnus, du plus faible au plus fort
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// Nom d'algorithme tel qu'écrit dans Metalink (`sha-256`) ou ailleurs (`SHA256`, `md5`)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "md5" => Some(Self::Md5),
+            "sha1" => Some(Self::Sha1),
+            "sha256" => Some(Self::Sha256),
+            "sha512" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    /// Longueur de l'empreinte en hexadécimal
+    fn hex_len(self) -> usize {
+        match self {
+            Self::Md5 => 32,
+            Self::Sha1 => 40,
+            Self::Sha256 => 64,
+            Self::Sha512 => 128,
+        }
+    }
+}
+
+/// Empreinte attendue (hexadécimal en minuscules)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedHash {
+    pub algorithm: HashAlgorithm,
+    pub hex: String,
+}
+
+impl ExpectedHash {
+    pub fn new(algorithm: HashAlgorithm, hex: &str) -> Self {
+        Self { algorithm, hex: hex.trim().to_ascii_lowercase() }
+    }
+}
+
+/// Fichier final dont la taille ne correspond pas à celle annoncée par le serveur
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruncatedDownload {
+    pub path: PathBuf,
+    /// Taille annoncée (`Content-Length`, `Content-Range`, SIZE...)
+    pub expected: u64,
+    /// Octets effectivement présents (ou reçus)
+    pub actual: u64,
+}
+
+impl fmt::Display for TruncatedDownload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Téléchargement incomplet: {} octets sur {} attendus pour {}",
+            self.actual,
+            self.expected,
+            self.path.display()
+        )
+    }
+}
+
+impl std::error::Error for TruncatedDownload {}
+
+/// Résultat de la vérification par fichier d'empreinte voisin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Integrity {
+    Verified(HashAlgorithm),
+    Corrupt(HashAlgorithm),
+}
+
+/// Lit `[checksum] fetch_sidecar` (désactivé par défaut)
+pub fn sidecar_checksums_from_config(config: &super::AppConfig) -> bool {
+    config.checksum.as_ref().and_then(|c| c.fetch_sidecar).unwrap_or(false)
+}
+
+/// Empreinte de `file_name` dans un fichier au format `sha256sum`/`md5sum` (`<hex>  <nom>`,
+/// `<hex> *<nom>`) ou réduit à l'empreinte seule. Si plusieurs lignes sont présentes, seule
+/// celle qui nomme `file_name` est retenue.
+pub fn parse_sidecar(content: &str, file_name: &str, algorithm: HashAlgorithm) -> Option<ExpectedHash> {
+    let mut entries = Vec::new();
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+        let (hex, name) = match line.split_once(char::is_whitespace) {
+            Some((hex, name)) => (hex, Some(name.trim().trim_start_matches('*'))),
+            None => (line, None),
+        };
+        if hex.len() == algorithm.hex_len() && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            entries.push((hex, name));
+        }
+    }
+    let named = entries.iter().find(|(_, name)| name.is_some_and(|n| n.rsplit('/').next() == Some(file_name)));
+    let (hex, _) = match named {
+        Some(entry) => *entry,
+        None if entries.len() == 1 => entries[0],
+        None => return None,
+    };
+    Some(ExpectedHash::new(algorithm, hex))
+}
+
+fn digest_file<D: Digest>(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = D::new();
+    let mut buf = vec![0u8; HASH_BLOCK];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// Empreinte hexadécimale de `path` selon `algorithm` (bloquant)
+pub fn hash_file(path: &Path, algorithm: HashAlgorithm) -> io::Result<String> {
+    match algorithm {
+        HashAlgorithm::Md5 => digest_file::<Md5>(path),
+        HashAlgorithm::Sha1 => digest_file::<Sha1>(path),
+        HashAlgorithm::Sha256 => digest_file::<Sha256>(path),
+        HashAlgorithm::Sha512 => digest_file::<Sha512>(path),
+    }
+}
+
+/// Empreinte de `path` recalculée sur le pool bloquant
+async fn hash_file_async(path: &Path, algorithm: HashAlgorithm) -> Result<String> {
+    let owned: PathBuf = path.to_path_buf();
+    tokio::task::spawn_blocking(move || hash_file(&owned, algorithm))
+        .await?
+        .with_context(|| format!("Calcul de l'empreinte de {}", path.display()))
+}
+
+/// `path` correspond‑il à `expected`?
+pub async fn matches_hash(path: &Path, expected: &ExpectedHash) -> Result<bool> {
+    Ok(hash_file_async(path, expected.algorithm).await? == expected.hex)
+}
+
+/// Compare `path` à la plus forte des empreintes fournies (aucune = rien à vérifier)
+pub async fn verify_file(path: &Path, hashes: &[ExpectedHash]) -> Result<()> {
+    let Some(expected) = hashes.iter().max_by_key(|h| h.algorithm).cloned() else {
+        return Ok(());
+    };
+    let actual = hash_file_async(path, expected.algorithm).await?;
+    if actual != expected.hex {
+        let message = format!("Empreinte {:?} invalide pour {}: attendu {}, obtenu {}", expected.algorithm, path.display(), expected.hex, actual);
+        return Err(DownloadError::new(DownloadErrorKind::Verification, message).into());
+    }
+    tracing::info!(file = %path.display(), algorithm = ?expected.algorithm, "Empreinte vérifiée");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_verify_uses_strongest_hash() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("abc.txt");
+        std::fs::write(&path, b"abc").unwrap();
+
+        assert_eq!(HashAlgorithm::parse("SHA-256"), Some(HashAlgorithm::Sha256));
+        assert_eq!(hash_file(&path, HashAlgorithm::Md5).unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+        let sha256 = ExpectedHash::new(
+            HashAlgorithm::Sha256,
+            "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD",
+        );
+        verify_file(&path, std::slice::from_ref(&sha256)).await.unwrap();
+
+        // Une empreinte MD5 fausse est ignorée au profit de SHA-256; l'inverse échoue
+        let wrong_md5 = ExpectedHash::new(HashAlgorithm::Md5, "00");
+        verify_file(&path, &[wrong_md5, sha256]).await.unwrap();
+        let wrong_sha512 = ExpectedHash::new(HashAlgorithm::Sha512, "00");
+        assert!(verify_file(&path, &[wrong_sha512]).await.is_err());
+    }
+
+    #[test]
+    fn test_parse_sidecar_formats() {
+        let md5 = "900150983cd24fb0d6963f7d28e17f72";
+        let expected = Some(ExpectedHash::new(HashAlgorithm::Md5, md5));
+        assert_eq!(parse_sidecar(&format!("{md5}\n"), "abc.txt", HashAlgorithm::Md5), expected);
+        assert_eq!(parse_sidecar(&format!("{}  abc.txt", md5.to_uppercase()), "abc.txt", HashAlgorithm::Md5), expected);
+
+        // Liste multi‑fichiers: seule la ligne du fichier compte
+        let listing = format!("{}  other.txt\n{md5} *dist/abc.txt\n", "0".repeat(32));
+        assert_eq!(parse_sidecar(&listing, "abc.txt", HashAlgorithm::Md5), expected);
+        assert_eq!(parse_sidecar(&listing, "missing.txt", HashAlgorithm::Md5), None);
+        // Page HTML ou empreinte d'un autre algorithme
+        assert_eq!(parse_sidecar("<html>404</html>", "abc.txt", HashAlgorithm::Md5), None);
+        assert_eq!(parse_sidecar(md5, "abc.txt", HashAlgorithm::Sha256), None);
+    }
+}
@@ -0,0 +1,185 @@
+//! Cookies partagés par les clients HTTP de l'application et conservés entre deux lancements.
+//!
+//! `CookieJar::global()` sert de `cookie_provider` au `DownloadManager`, à l'extraction de liens,
+//! à la GUI et au scraper FZTV: une session ouverte par le scraper (ou importée depuis le
+//! sniffer, ou depuis le navigateur qui passe une vérification anti‑robot, via `add_cookie`)
+//! accompagne donc les téléchargements qui l'exigent. Le contenu est réécrit dans
+//! `[cookies] file` (`cookies.json` par défaut) dès qu'un serveur en dépose un, cookies de
+//! session compris; `persist = false` garde le stockage en mémoire.
+//!
+//! Un en‑tête `Cookie` explicite (`RequestOptions::cookies`) reste prioritaire sur le stockage.
+use std::fs::{self, File};
+use std::io::{self, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use cookie_store::{CookieStore, RawCookie};
+use reqwest::header::HeaderValue;
+use url::Url;
+
+const DEFAULT_COOKIE_FILE: &str = "cookies.json";
+static GLOBAL: OnceLock<CookieJar> = OnceLock::new();
+
+#[derive(Debug)]
+struct Inner {
+    store: Mutex<CookieStore>,
+    /// Fichier de sauvegarde (`None` = mémoire seulement)
+    path: Option<PathBuf>,
+}
+
+/// Stockage de cookies partagé (clonage bon marché), utilisable comme `cookie_provider` reqwest
+#[derive(Debug, Clone)]
+pub struct CookieJar(Arc<Inner>);
+
+impl CookieJar {
+    /// Stockage vide, jamais écrit sur disque
+    pub fn in_memory() -> Self {
+        Self(Arc::new(Inner { store: Mutex::new(CookieStore::default()), path: None }))
+    }
+
+    /// Stockage relu depuis `path` (vide si le fichier est absent ou illisible) et sauvegardé à
+    /// chaque cookie reçu
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let store = match File::open(&path) {
+            Ok(file) => CookieStore::load_json_all(BufReader::new(file)).unwrap_or_else(|e| {
+                tracing::warn!(file = %path.display(), error = %e, "Fichier de cookies illisible, ignoré");
+                CookieStore::default()
+            }),
+            Err(_) => CookieStore::default(),
+        };
+        Self(Arc::new(Inner { store: Mutex::new(store), path: Some(path) }))
+    }
+
+    /// Lit `[cookies] persist` (activé par défaut) et `[cookies] file`
+    pub fn from_config(config: &super::AppConfig) -> Self {
+        let cookies = config.cookies.as_ref();
+        if !cookies.and_then(|c| c.persist).unwrap_or(true) {
+            return Self::in_memory();
+        }
+        Self::load(cookies.and_then(|c| c.file.clone()).unwrap_or_else(|| PathBuf::from(DEFAULT_COOKIE_FILE)))
+    }
+
+    /// Stockage de l'application, initialisé depuis scrapes.toml au premier appel
+    pub fn global() -> Self {
+        GLOBAL.get_or_init(|| Self::from_config(&super::load_config())).clone()
+    }
+
+    fn store(&self) -> std::sync::MutexGuard<'_, CookieStore> {
+        self.0.store.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Ajoute un cookie au format `Set-Cookie` obtenu pour `url` (scraper, navigateur du
+    /// sniffer, vérification anti‑robot); `false` s'il est invalide ou refusé pour ce domaine
+    pub fn add_cookie(&self, set_cookie: &str, url: &Url) -> bool {
+        let inserted = self.store().parse(set_cookie, url).is_ok();
+        if inserted {
+            self.persist();
+        }
+        inserted
+    }
+
+    /// Valeur de l'en‑tête `Cookie` envoyée à `url` (`name=value; ...`)
+    pub fn header_for(&self, url: &Url) -> Option<String> {
+        let header = self
+            .store()
+            .get_request_values(url)
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ");
+        (!header.is_empty()).then_some(header)
+    }
+
+    /// Oublie tous les cookies (fichier compris)
+    pub fn clear(&self) {
+        self.store().clear();
+        self.persist();
+    }
+
+    /// Réécrit le fichier de sauvegarde; une erreur n'interrompt pas les requêtes en cours
+    fn persist(&self) {
+        let Some(path) = &self.0.path else { return };
+        if let Err(e) = self.save_to(path) {
+            tracing::warn!(file = %path.display(), error = %e, "Sauvegarde des cookies impossible");
+        }
+    }
+
+    /// Écrit via un fichier temporaire renommé: une coupure ne laisse jamais un JSON tronqué
+    fn save_to(&self, path: &Path) -> io::Result<()> {
+        let mut content = Vec::new();
+        self.store()
+            .save_incl_expired_and_nonpersistent_json(&mut content)
+            .map_err(io::Error::other)?;
+        let tmp = path.with_extension("json.tmp");
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // Les cookies valent des identifiants: lisibles par l'utilisateur seul
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options.open(&tmp)?.write_all(&content)?;
+        fs::rename(&tmp, path)
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies: Vec<RawCookie<'static>> = cookie_headers
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| RawCookie::parse(value.to_string()).ok())
+            .collect();
+        if cookies.is_empty() {
+            return;
+        }
+        tracing::debug!(url = %url, count = cookies.len(), "Cookies reçus");
+        self.store().store_response_cookies(cookies.into_iter(), url);
+        self.persist();
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        self.header_for(url).and_then(|header| HeaderValue::from_str(&header).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::cookie::CookieStore as _;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_cookies_survive_reload() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("cookies.json");
+        let site = Url::parse("https://files.example.com/get/1").unwrap();
+
+        let jar = CookieJar::load(&path);
+        let headers = [
+            HeaderValue::from_static("session=abc; Path=/"),
+            HeaderValue::from_static("pref=dark; Max-Age=3600"),
+        ];
+        jar.set_cookies(&mut headers.iter(), &site);
+        assert!(jar.add_cookie("cf_clearance=xyz; Domain=example.com", &site));
+        assert!(!jar.add_cookie("other=1; Domain=other.org", &site));
+
+        // Relu au prochain lancement, cookies de session compris
+        let reloaded = CookieJar::load(&path);
+        let header = reloaded.header_for(&site).unwrap();
+        for cookie in ["session=abc", "pref=dark", "cf_clearance=xyz"] {
+            assert!(header.contains(cookie), "{header}");
+        }
+        assert_eq!(reloaded.header_for(&Url::parse("https://other.org/").unwrap()), None);
+        assert!(CookieJar::in_memory().header_for(&site).is_none());
+    }
+
+    #[test]
+    fn test_clear_forgets_saved_cookies() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("cookies.json");
+        let site = Url::parse("https://files.example.com/").unwrap();
+        let jar = CookieJar::load(&path);
+        assert!(jar.add_cookie("session=abc", &site));
+
+        jar.clear();
+        assert_eq!(jar.header_for(&site), None);
+        assert_eq!(CookieJar::load(&path).header_for(&site), None);
+    }
+}
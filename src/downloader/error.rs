@@ -0,0 +1,111 @@
+//! Catégories d'erreurs de téléchargement.
+//!
+//! Le téléchargeur renvoie des `anyhow::Error` dont la chaîne conserve les erreurs typées
+//! (`DiskFull`, `FileTooLarge`, `TruncatedDownload`, `Throttled`, erreurs reqwest et d'E/S).
+//! `DownloadErrorKind::of` y retrouve la catégorie utile à l'appelant: réessayer plus tard
+//! (réseau), libérer de la place, revenir en ligne... Les échecs sans type dédié (plage ignorée
+//! par le serveur, mode hors ligne, vérification multi‑sources ou d'empreinte) sont signalés
+//! par `DownloadError`, qui porte directement sa catégorie.
+use std::fmt;
+use std::io;
+use super::checksum::TruncatedDownload;
+use super::retry::Throttled;
+use super::space::{DiskFull, FileTooLarge};
+
+/// Nature d'un échec de téléchargement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadErrorKind {
+    /// Connexion, délai dépassé, statut HTTP en erreur ou serveur saturé
+    Network,
+    /// Le serveur ignore `Range` alors que la reprise l'exige
+    RangeUnsupported,
+    /// Espace disque insuffisant (`DiskFull`)
+    DiskFull,
+    /// Fichier au‑delà de la taille maximale autorisée (`FileTooLarge`)
+    TooLarge,
+    /// Interrompu par le mode hors ligne
+    Cancelled,
+    /// Taille finale, empreinte ou contrôle multi‑sources incorrects
+    Verification,
+    /// Lecture ou écriture locale
+    Io,
+    /// Autre cause (tâche invalide, format inconnu...)
+    Other,
+}
+
+impl DownloadErrorKind {
+    /// Catégorie de la première cause reconnue dans la chaîne de `err`
+    pub fn of(err: &anyhow::Error) -> Self {
+        err.chain().find_map(Self::of_cause).unwrap_or(Self::Other)
+    }
+
+    fn of_cause(cause: &(dyn std::error::Error + 'static)) -> Option<Self> {
+        if let Some(e) = cause.downcast_ref::<DownloadError>() {
+            return Some(e.kind);
+        }
+        if cause.is::<DiskFull>() {
+            return Some(Self::DiskFull);
+        }
+        if cause.is::<FileTooLarge>() {
+            return Some(Self::TooLarge);
+        }
+        if cause.is::<TruncatedDownload>() {
+            return Some(Self::Verification);
+        }
+        if cause.is::<Throttled>() || cause.is::<reqwest::Error>() {
+            return Some(Self::Network);
+        }
+        let e = cause.downcast_ref::<io::Error>()?;
+        Some(match e.kind() {
+            io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::BrokenPipe => Self::Network,
+            _ => Self::Io,
+        })
+    }
+}
+
+/// Échec d'une catégorie donnée, sans type plus précis
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadError {
+    pub kind: DownloadErrorKind,
+    message: String,
+}
+
+impl DownloadError {
+    pub fn new(kind: DownloadErrorKind, message: impl Into<String>) -> Self {
+        Self { kind, message: message.into() }
+    }
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for DownloadError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_kind_found_through_context() {
+        let cancelled = anyhow::Error::new(DownloadError::new(DownloadErrorKind::Cancelled, "hors ligne")).context("Téléchargement");
+        assert_eq!(DownloadErrorKind::of(&cancelled), DownloadErrorKind::Cancelled);
+        assert_eq!(cancelled.root_cause().to_string(), "hors ligne");
+
+        let reset: anyhow::Result<()> = Err(io::Error::from(io::ErrorKind::ConnectionReset).into());
+        assert_eq!(DownloadErrorKind::of(&reset.context("segment 2").unwrap_err()), DownloadErrorKind::Network);
+        assert_eq!(DownloadErrorKind::of(&io::Error::from(io::ErrorKind::PermissionDenied).into()), DownloadErrorKind::Io);
+        let full = DiskFull { path: "a.bin".into(), available: 1, needed: 2 };
+        assert_eq!(DownloadErrorKind::of(&full.into()), DownloadErrorKind::DiskFull);
+        assert_eq!(DownloadErrorKind::of(&anyhow::anyhow!("autre")), DownloadErrorKind::Other);
+    }
+}
@@ -0,0 +1,155 @@
+//! Bus d'événements du cycle de vie des téléchargements.
+//!
+//! Chaque `DownloadManager` publie sur un canal `broadcast` (`DownloadManager::subscribe`) les
+//! étapes de ses tâches: acceptation, démarrage, segments terminés, progression, vérification,
+//! fin, échec ou annulation. GUI, journalisation (`DownloadEvent::trace`), notifications et
+//! futur serveur RPC s'y abonnent chacun de leur côté au lieu de relayer la progression à la
+//! main; plusieurs gestionnaires peuvent partager un même bus via `with_events`, comme ceux de
+//! la GUI avec `DownloadEvents::global()`.
+//!
+//! Un abonné trop lent perd les événements les plus anciens (`RecvError::Lagged`) sans jamais
+//! ralentir le téléchargement. Sans abonné, aucun événement n'est construit.
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tokio::sync::broadcast;
+use super::checksum::Integrity;
+use super::error::DownloadErrorKind;
+use super::progress::ProgressEvent;
+use super::types::DownloadStats;
+
+/// Événements conservés pour un abonné en retard avant de perdre les plus anciens
+const CAPACITY: usize = 1024;
+
+static GLOBAL: OnceLock<DownloadEvents> = OnceLock::new();
+
+/// Étape du cycle de vie d'un téléchargement
+#[derive(Debug, Clone)]
+pub enum DownloadEventKind {
+    /// Tâche acceptée par `start`, avant toute requête
+    Queued,
+    /// Métadonnées connues, transfert lancé vers `output` (nom final après politique de collision)
+    Started { total_size: u64, output: PathBuf },
+    /// Segment `index` terminé après `bytes` octets reçus (index 0 en requête unique)
+    ChunkDone { index: usize, bytes: u64 },
+    /// Octets reçus ou attente imposée par le serveur
+    Progress(ProgressEvent),
+    /// Tous les octets reçus: contrôle de taille, empreintes puis renommage du `.partial`
+    Merging,
+    /// Empreinte contrôlée (attendue par la tâche ou fichier voisin)
+    Verified(Integrity),
+    /// Fichier final en place (ou conservé tel quel, `DownloadStats::skipped`)
+    Completed(DownloadStats),
+    /// Échec, avec sa catégorie et la chaîne d'erreurs complète
+    Failed { kind: DownloadErrorKind, message: String },
+    /// Interruption par le mode hors ligne
+    Cancelled,
+}
+
+/// Événement publié pour la tâche `url` → `output` (sortie demandée)
+#[derive(Debug, Clone)]
+pub struct DownloadEvent {
+    pub url: String,
+    pub output: PathBuf,
+    pub kind: DownloadEventKind,
+}
+
+impl DownloadEvent {
+    /// Journalise l'étape (`debug`, la progression en `trace`)
+    pub fn trace(&self) {
+        let (url, output) = (self.url.as_str(), self.output.display());
+        match &self.kind {
+            DownloadEventKind::Queued => tracing::debug!(url, %output, "Tâche acceptée"),
+            DownloadEventKind::Started { total_size, output: target } => {
+                tracing::debug!(url, %output, target = %target.display(), total_size, "Transfert lancé")
+            }
+            DownloadEventKind::ChunkDone { index, bytes } => tracing::debug!(url, index, bytes, "Segment terminé"),
+            DownloadEventKind::Progress(progress) => tracing::trace!(url, downloaded = progress.total_downloaded, "Progression"),
+            DownloadEventKind::Merging => tracing::debug!(url, %output, "Finalisation du fichier"),
+            DownloadEventKind::Verified(integrity) => tracing::debug!(url, ?integrity, "Empreinte contrôlée"),
+            DownloadEventKind::Completed(stats) => {
+                tracing::debug!(url, %output, elapsed = ?stats.total_elapsed, skipped = stats.skipped, "Téléchargement terminé")
+            }
+            DownloadEventKind::Failed { kind, message } => tracing::debug!(url, ?kind, message, "Téléchargement échoué"),
+            DownloadEventKind::Cancelled => tracing::debug!(url, "Téléchargement annulé"),
+        }
+    }
+}
+
+/// Émetteur partagé (clonage bon marché) des événements de téléchargement
+#[derive(Debug, Clone)]
+pub struct DownloadEvents(broadcast::Sender<DownloadEvent>);
+
+impl Default for DownloadEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DownloadEvents {
+    pub fn new() -> Self {
+        Self(broadcast::channel(CAPACITY).0)
+    }
+
+    /// Bus partagé de l'application
+    pub fn global() -> Self {
+        GLOBAL.get_or_init(Self::new).clone()
+    }
+
+    /// Nouvel abonné; il reçoit les événements publiés à partir de maintenant
+    pub fn subscribe(&self) -> broadcast::Receiver<DownloadEvent> {
+        self.0.subscribe()
+    }
+
+    /// Émetteur lié à une tâche
+    pub(crate) fn for_task(&self, url: &str, output: &std::path::Path) -> TaskEvents {
+        TaskEvents { bus: self.clone(), url: url.to_string(), output: output.to_path_buf() }
+    }
+}
+
+/// Émetteur des événements d'une tâche donnée
+#[derive(Debug, Clone)]
+pub(crate) struct TaskEvents {
+    bus: DownloadEvents,
+    url: String,
+    output: PathBuf,
+}
+
+impl TaskEvents {
+    /// Vrai si au moins un abonné écoute
+    pub(crate) fn is_listened(&self) -> bool {
+        self.bus.0.receiver_count() > 0
+    }
+
+    pub(crate) fn emit(&self, kind: DownloadEventKind) {
+        if !self.is_listened() {
+            return;
+        }
+        // Dernier abonné parti entre-temps: l'événement est simplement perdu
+        let _ = self.bus.0.send(DownloadEvent { url: self.url.clone(), output: self.output.clone(), kind });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscribers_receive_events_in_order() {
+        let bus = DownloadEvents::new();
+        let task = bus.for_task("https://example.com/a.bin", std::path::Path::new("a.bin"));
+        // Sans abonné, rien n'est publié ni conservé
+        task.emit(DownloadEventKind::Queued);
+
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        task.emit(DownloadEventKind::Merging);
+        task.emit(DownloadEventKind::Cancelled);
+        for rx in [&mut first, &mut second] {
+            let event = rx.recv().await.unwrap();
+            assert_eq!((event.url.as_str(), event.output.to_str()), ("https://example.com/a.bin", Some("a.bin")));
+            assert!(matches!(event.kind, DownloadEventKind::Merging));
+            assert!(matches!(rx.recv().await.unwrap().kind, DownloadEventKind::Cancelled));
+        }
+        assert!(first.try_recv().is_err());
+    }
+}
@@ -0,0 +1,280 @@
+//! Client FTP minimal (mode passif, binaire) pour le téléchargement par plages.
+//!
+//! Chaque plage ouvre sa propre session: `REST <offset>` puis `RETR`, et les connexions sont
+//! fermées dès que la plage est complète. Les réponses 4xx (temporaires) deviennent des erreurs
+//! d'E/S `ConnectionAborted`, relancées par la politique de nouvelles tentatives.
+use std::io;
+use anyhow::{Context, Result};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use url::Url;
+use super::protocol::{limited_stream, remote_path, ByteStream, Credentials};
+use super::types::RequestOptions;
+
+const DEFAULT_PORT: u16 = 21;
+const ANONYMOUS_USER: &str = "anonymous";
+const ANONYMOUS_PASSWORD: &str = "anonymous@";
+
+/// Connexion de contrôle FTP
+struct Control {
+    stream: BufReader<TcpStream>,
+}
+
+impl Control {
+    /// Ouvre une session authentifiée en mode binaire
+    async fn connect(url: &Url, credentials: &Credentials) -> Result<Self> {
+        let host = url.host_str().context("URL FTP sans hôte")?;
+        let port = url.port().unwrap_or(DEFAULT_PORT);
+        let stream = TcpStream::connect((host, port))
+            .await
+            .with_context(|| format!("Connexion FTP à {host}:{port}"))?;
+        let mut control = Self { stream: BufReader::new(stream) };
+        control.expect(&[220]).await?;
+
+        let code = control.command(&format!("USER {}", credentials.username), &[230, 331]).await?;
+        if code == 331 {
+            let password = credentials.password.as_deref().unwrap_or(ANONYMOUS_PASSWORD);
+            control.command(&format!("PASS {password}"), &[230, 202]).await?;
+        }
+        control.command("TYPE I", &[200]).await?;
+        Ok(control)
+    }
+
+    /// Lit une réponse complète (éventuellement multi‑lignes `123-...` / `123 ...`)
+    async fn read_reply(&mut self) -> Result<(u16, String)> {
+        let mut first = String::new();
+        if self.stream.read_line(&mut first).await? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connexion FTP fermée").into());
+        }
+        let code: u16 = first
+            .get(..3)
+            .and_then(|c| c.parse().ok())
+            .with_context(|| format!("Réponse FTP invalide: {}", first.trim_end()))?;
+        let mut text = first[3..].trim().to_string();
+        if first.as_bytes().get(3) == Some(&b'-') {
+            let end = format!("{code} ");
+            loop {
+                let mut line = String::new();
+                if self.stream.read_line(&mut line).await? == 0 {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connexion FTP fermée").into());
+                }
+                text.push('\n');
+                text.push_str(line.trim_end());
+                if line.starts_with(&end) {
+                    break;
+                }
+            }
+        }
+        Ok((code, text))
+    }
+
+    async fn expect(&mut self, expected: &[u16]) -> Result<(u16, String)> {
+        let (code, text) = self.read_reply().await?;
+        if expected.contains(&code) {
+            return Ok((code, text));
+        }
+        Err(reply_error(code, &text))
+    }
+
+    /// Envoie `cmd` et retourne le code de la réponse attendue
+    async fn command(&mut self, cmd: &str, expected: &[u16]) -> Result<u16> {
+        let shown = if cmd.starts_with("PASS ") { "PASS ***" } else { cmd };
+        tracing::debug!(command = shown, "Commande FTP");
+        self.stream.get_mut().write_all(format!("{cmd}\r\n").as_bytes()).await?;
+        self.expect(expected).await.map(|(code, _)| code)
+    }
+
+    async fn size(&mut self, path: &str) -> Result<u64> {
+        self.stream.get_mut().write_all(format!("SIZE {path}\r\n").as_bytes()).await?;
+        let (_, text) = self.expect(&[213]).await?;
+        text.trim().parse().with_context(|| format!("Taille FTP invalide: {text}"))
+    }
+
+    /// Ouvre une connexion de données passive (EPSV, sinon PASV), toujours vers l'hôte de contrôle
+    async fn passive(&mut self) -> Result<TcpStream> {
+        let peer = self.stream.get_ref().peer_addr()?;
+        self.stream.get_mut().write_all(b"EPSV\r\n").await?;
+        let (code, text) = self.read_reply().await?;
+        let port = match code {
+            229 => parse_epsv(&text),
+            _ => {
+                self.stream.get_mut().write_all(b"PASV\r\n").await?;
+                let (_, text) = self.expect(&[227]).await?;
+                parse_pasv(&text)
+            }
+        }
+        .with_context(|| format!("Réponse passive FTP invalide: {text}"))?;
+        TcpStream::connect((peer.ip(), port)).await.context("Connexion de données FTP")
+    }
+}
+
+/// `229 Entering Extended Passive Mode (|||6446|)` -> 6446
+fn parse_epsv(text: &str) -> Option<u16> {
+    let inner = text.split_once('(')?.1.split_once(')')?.0;
+    inner.trim_matches('|').parse().ok()
+}
+
+/// `227 Entering Passive Mode (h1,h2,h3,h4,p1,p2)` -> p1 * 256 + p2
+fn parse_pasv(text: &str) -> Option<u16> {
+    let inner = text.split_once('(')?.1.split_once(')')?.0;
+    let numbers: Vec<u16> = inner.split(',').map(|n| n.trim().parse().ok()).collect::<Option<_>>()?;
+    match numbers[..] {
+        [_, _, _, _, p1, p2] => Some(p1 * 256 + p2),
+        _ => None,
+    }
+}
+
+/// Réponse inattendue: 4xx = temporaire (relancée), sinon définitive
+fn reply_error(code: u16, text: &str) -> anyhow::Error {
+    let message = format!("FTP {code}: {}", text.trim());
+    if (400..500).contains(&code) {
+        anyhow::Error::new(io::Error::new(io::ErrorKind::ConnectionAborted, message))
+    } else {
+        anyhow::anyhow!(message)
+    }
+}
+
+fn credentials(url: &Url, request: &RequestOptions) -> Credentials {
+    let mut credentials = Credentials::resolve(url, request, ANONYMOUS_USER);
+    if credentials.username == ANONYMOUS_USER && credentials.password.is_none() {
+        credentials.password = Some(ANONYMOUS_PASSWORD.to_string());
+    }
+    credentials
+}
+
+/// Taille du fichier distant (`SIZE`)
+pub(crate) async fn probe(url: &str, request: &RequestOptions) -> Result<u64> {
+    let url = Url::parse(url).context("URL FTP invalide")?;
+    let mut control = Control::connect(&url, &credentials(&url, request)).await?;
+    let size = control.size(&remote_path(&url)).await?;
+    let _ = control.command("QUIT", &[221]).await;
+    Ok(size)
+}
+
+/// Ouvre `len` octets à partir de `offset` (`REST` puis `RETR`)
+pub(crate) async fn open_range(url: &str, request: &RequestOptions, offset: u64, len: u64) -> Result<ByteStream> {
+    let url = Url::parse(url).context("URL FTP invalide")?;
+    let mut control = Control::connect(&url, &credentials(&url, request)).await?;
+    let data = control.passive().await?;
+    if offset > 0 {
+        control.command(&format!("REST {offset}"), &[350]).await?;
+    }
+    control.command(&format!("RETR {}", remote_path(&url)), &[125, 150]).await?;
+    Ok(limited_stream(data, len, control))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::manager::DownloadManager;
+    use super::super::types::DownloadTask;
+    use futures::StreamExt;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+
+    /// Serveur FTP de test (une session à la fois), journalisant les commandes reçues
+    async fn start_ftp_server(data: Vec<u8>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let commands = log.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let data = data.clone();
+                let commands = commands.clone();
+                tokio::spawn(async move {
+                    let mut control = BufReader::new(socket);
+                    control.get_mut().write_all(b"220-Bienvenue\r\n220 Pret\r\n").await.unwrap();
+                    let mut passive: Option<TcpListener> = None;
+                    let mut offset = 0usize;
+                    let mut line = String::new();
+                    while control.read_line(&mut line).await.unwrap_or(0) > 0 {
+                        let cmd = line.trim_end().to_string();
+                        line.clear();
+                        commands.lock().unwrap().push(cmd.clone());
+                        let reply = match cmd.split_once(' ').map_or((cmd.as_str(), ""), |(c, a)| (c, a)) {
+                            ("USER", _) => "331 Mot de passe requis".to_string(),
+                            ("PASS", _) => "230 Connecte".to_string(),
+                            ("TYPE", _) => "200 Binaire".to_string(),
+                            ("SIZE", _) => format!("213 {}", data.len()),
+                            ("EPSV", _) => {
+                                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                                let port = listener.local_addr().unwrap().port();
+                                passive = Some(listener);
+                                format!("229 Extended Passive Mode (|||{port}|)")
+                            }
+                            ("REST", arg) => {
+                                offset = arg.parse().unwrap();
+                                format!("350 Reprise a {offset}")
+                            }
+                            ("RETR", _) => {
+                                let listener = passive.take().unwrap();
+                                control.get_mut().write_all(b"150 Envoi\r\n").await.unwrap();
+                                let (mut data_socket, _) = listener.accept().await.unwrap();
+                                let _ = data_socket.write_all(&data[offset..]).await;
+                                drop(data_socket);
+                                "226 Termine".to_string()
+                            }
+                            ("QUIT", _) => {
+                                let _ = control.get_mut().write_all(b"221 Au revoir\r\n").await;
+                                break;
+                            }
+                            _ => "502 Non implemente".to_string(),
+                        };
+                        if control.get_mut().write_all(format!("{reply}\r\n").as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        (format!("ftp://127.0.0.1:{}/pub/file.bin", addr.port()), log)
+    }
+
+    #[tokio::test]
+    async fn test_ftp_probe_and_ranged_retrieval() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(10_000).collect();
+        let (url, log) = start_ftp_server(data.clone()).await;
+        let request = RequestOptions::default();
+
+        assert_eq!(probe(&url, &request).await.unwrap(), 10_000);
+
+        // Plage au milieu du fichier: REST puis RETR, lecture arrêtée à la fin de la plage
+        let mut stream = open_range(&url, &request, 4_000, 3_000).await.unwrap();
+        let mut received = Vec::new();
+        while let Some(block) = stream.next().await {
+            received.extend_from_slice(&block.unwrap());
+        }
+        assert_eq!(received, data[4_000..7_000]);
+
+        let commands = log.lock().unwrap().clone();
+        assert!(commands.contains(&"USER anonymous".to_string()));
+        assert!(commands.contains(&"REST 4000".to_string()));
+        assert!(commands.contains(&"RETR /pub/file.bin".to_string()));
+        assert_eq!(parse_pasv("227 Entering Passive Mode (127,0,0,1,19,137)"), Some(5001));
+
+        // Une réponse 4xx est une erreur temporaire
+        let err = reply_error(421, "Trop de connexions");
+        assert_eq!(err.downcast_ref::<io::Error>().unwrap().kind(), io::ErrorKind::ConnectionAborted);
+    }
+
+    #[tokio::test]
+    async fn test_manager_downloads_ftp_source_in_ranges() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(16 * 1024).collect();
+        let (url, log) = start_ftp_server(data.clone()).await;
+        let dir = tempfile::tempdir().unwrap();
+        let output_path = dir.path().join("out_ftp.bin");
+        let task = DownloadTask::builder(url)
+            .with_output(output_path.clone())
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
+
+        let stats = DownloadManager::new().start(task).await.expect("FTP download should succeed");
+        assert_eq!(std::fs::read(&output_path).unwrap(), data);
+        assert_eq!(stats.per_chunk.len(), 4);
+        let mut offsets: Vec<String> = log.lock().unwrap().iter().filter(|c| c.starts_with("REST ")).cloned().collect();
+        offsets.sort();
+        assert_eq!(offsets, vec!["REST 12288", "REST 4096", "REST 8192"]);
+    }
+}
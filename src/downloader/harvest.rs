@@ -0,0 +1,152 @@
+//! Extraction des liens téléchargeables d'une page HTML ou JSON.
+//!
+//! `harvest` récupère la page, collecte les URLs candidates (liens, sources média, inputs)
+//! via `scrapers::links` puis les filtre par extension et/ou motif. Les réponses JSON sont
+//! parcourues récursivement à la recherche de chaînes ressemblant à des URLs.
+use anyhow::{Context, Result};
+use reqwest::Client;
+use scraper::Html;
+use url::Url;
+use crate::scrapers::links::{extract_page_urls, is_download_candidate};
+use super::cookies::CookieJar;
+use super::protocol::Protocol;
+
+/// Critères de sélection des liens extraits
+#[derive(Debug, Clone, Default)]
+pub struct HarvestFilter {
+    /// Extensions acceptées sans le point (ex: `mp4`, `mkv`); vide = toutes
+    pub extensions: Vec<String>,
+    /// Sous-chaîne devant apparaître dans l'URL (insensible à la casse)
+    pub pattern: Option<String>,
+}
+
+impl HarvestFilter {
+    /// Construit un filtre depuis une liste d'extensions séparées par des virgules/espaces
+    pub fn from_extensions(list: &str) -> Self {
+        let extensions = list
+            .split([',', ' ', ';'])
+            .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+            .filter(|e| !e.is_empty())
+            .collect();
+        Self { extensions, pattern: None }
+    }
+
+    pub fn matches(&self, url: &str) -> bool {
+        if let Some(pattern) = &self.pattern
+            && !url.to_lowercase().contains(&pattern.to_lowercase())
+        {
+            return false;
+        }
+        if self.extensions.is_empty() {
+            return true;
+        }
+        // Comparer l'extension du chemin, sans la query string
+        let path = Url::parse(url).map(|u| u.path().to_string()).unwrap_or_else(|_| url.to_string());
+        let ext = path.rsplit_once('.').map(|(_, e)| e.to_lowercase());
+        ext.is_some_and(|ext| self.extensions.contains(&ext))
+    }
+}
+
+/// Extrait et filtre les liens d'un document HTML (ou JSON) déjà récupéré.
+pub fn extract_links(body: &str, base: &Url, filter: &HarvestFilter) -> Vec<String> {
+    let candidates = match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(json) => {
+            let mut found = Vec::new();
+            collect_json_urls(&json, base, &mut found);
+            found
+        }
+        Err(_) => extract_page_urls(&Html::parse_document(body), Some(base)),
+    };
+
+    let mut links: Vec<String> = Vec::new();
+    for url in candidates {
+        // Une page distante ne propose jamais de copier des fichiers locaux (`file:`, `data:`)
+        if Protocol::of(&url) == Protocol::Local {
+            continue;
+        }
+        if filter.matches(&url) && !links.contains(&url) {
+            links.push(url);
+        }
+    }
+    links
+}
+
+fn collect_json_urls(value: &serde_json::Value, base: &Url, out: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(s) => {
+            let looks_like_url = s.starts_with("http") || s.starts_with('/');
+            if looks_like_url && let Ok(url) = base.join(s) {
+                let url = url.to_string();
+                if is_download_candidate(&url) {
+                    out.push(url);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|v| collect_json_urls(v, base, out)),
+        serde_json::Value::Object(map) => map.values().for_each(|v| collect_json_urls(v, base, out)),
+        _ => {}
+    }
+}
+
+/// Récupère `page_url` et retourne les liens téléchargeables correspondant à `filter`.
+pub async fn harvest(page_url: &str, filter: &HarvestFilter) -> Result<Vec<String>> {
+    tracing::info!(url = page_url, "Extraction des liens de la page");
+    let base = Url::parse(page_url).context("URL de page invalide")?;
+    // Les pages protégées par une session (scraper, navigateur) réutilisent ses cookies
+    let client = Client::builder()
+        .cookie_provider(std::sync::Arc::new(CookieJar::global()))
+        .build()
+        .context("Créer client HTTP")?;
+    let resp = client.get(page_url).send().await.context("GET page")?;
+    let body = resp.error_for_status().context("GET status")?.text().await.context("Lire la page")?;
+
+    let links = extract_links(&body, &base, filter);
+    tracing::info!(count = links.len(), "Liens extraits");
+    Ok(links)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"
+        <html><body>
+            <a href="/media/ep1.mp4">Épisode 1</a>
+            <a href="https://cdn.example.com/ep2.MKV?token=abc">Épisode 2</a>
+            <a href="/about.html">À propos</a>
+            <a href="https://t.me/join/ep3.mp4">Telegram</a>
+            <video><source src="trailer.mp4"></video>
+            <a href="/media/ep1.mp4">Doublon</a>
+        </body></html>
+    "#;
+
+    #[test]
+    fn test_extract_links_filters_by_extension() {
+        let base = Url::parse("https://example.com/series/page").unwrap();
+        let links = extract_links(PAGE, &base, &HarvestFilter::from_extensions("mp4, .mkv"));
+
+        assert_eq!(links, vec![
+            "https://example.com/media/ep1.mp4".to_string(),
+            "https://cdn.example.com/ep2.MKV?token=abc".to_string(),
+            "https://example.com/series/trailer.mp4".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_extract_links_with_pattern() {
+        let base = Url::parse("https://example.com/").unwrap();
+        let filter = HarvestFilter { extensions: vec!["mp4".into()], pattern: Some("media".into()) };
+        assert_eq!(extract_links(PAGE, &base, &filter), vec!["https://example.com/media/ep1.mp4".to_string()]);
+    }
+
+    #[test]
+    fn test_extract_links_from_json() {
+        let base = Url::parse("https://api.example.com/list").unwrap();
+        let body = r#"{"items": [{"file": "/v/a.mp4"}, {"file": "https://x.example.com/b.mp4"}, {"title": "c"}]}"#;
+        let links = extract_links(body, &base, &HarvestFilter::from_extensions("mp4"));
+        assert_eq!(links, vec![
+            "https://api.example.com/v/a.mp4".to_string(),
+            "https://x.example.com/b.mp4".to_string(),
+        ]);
+    }
+}
@@ -0,0 +1,111 @@
+//! Plafond de connexions simultanées par hôte, tous téléchargements confondus.
+//!
+//! Mettre en file une saison entière depuis un même site ouvre vite des dizaines de connexions
+//! vers un seul serveur (téléchargements actifs × segments parallèles), de quoi déclencher ses
+//! protections anti‑abus. `HostLimiter::global()` (`[queue] max_connections_per_host`, illimité
+//! par défaut) réserve une place par requête de segment auprès de l'hôte visé: au‑delà du
+//! plafond, les segments suivants attendent qu'une connexion se libère, quel que soit le
+//! téléchargement auquel ils appartiennent. Un miroir sur un autre hôte a son propre plafond.
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use url::Url;
+
+static GLOBAL: OnceLock<HostLimiter> = OnceLock::new();
+
+/// Plafond de connexions par hôte partagé (clonable)
+#[derive(Debug, Clone)]
+pub struct HostLimiter(Arc<Inner>);
+
+#[derive(Debug)]
+struct Inner {
+    /// Connexions simultanées par hôte (0 = illimité)
+    max_per_host: AtomicUsize,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl HostLimiter {
+    /// Nouveau plafond de `max_per_host` connexions par hôte (0 = illimité)
+    pub fn new(max_per_host: usize) -> Self {
+        Self(Arc::new(Inner { max_per_host: AtomicUsize::new(max_per_host), hosts: Mutex::default() }))
+    }
+
+    /// Plafond de l'application, initialisé depuis scrapes.toml au premier appel
+    pub fn global() -> Self {
+        GLOBAL.get_or_init(|| Self::new(max_connections_per_host_from_config(&super::load_config()))).clone()
+    }
+
+    pub fn max_per_host(&self) -> usize {
+        self.0.max_per_host.load(Ordering::Relaxed)
+    }
+
+    /// Modifie le plafond à chaud; les connexions ouvertes vont à leur terme sous l'ancien
+    #[allow(dead_code)]
+    pub fn set_max_per_host(&self, max_per_host: usize) {
+        self.0.max_per_host.store(max_per_host, Ordering::Relaxed);
+        self.0.hosts.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        tracing::info!(max_per_host, "Plafond de connexions par hôte modifié");
+    }
+
+    /// Réserve une connexion vers l'hôte de `url`, en attendant qu'une place se libère; la
+    /// place est rendue quand la réservation est abandonnée. `None` sans plafond ou sans hôte
+    /// (fichier local)
+    pub async fn acquire(&self, url: &str) -> Option<OwnedSemaphorePermit> {
+        let semaphore = self.semaphore(url)?;
+        if semaphore.available_permits() == 0 {
+            tracing::debug!(url, max_per_host = self.max_per_host(), "Plafond de connexions atteint pour l'hôte, attente");
+        }
+        semaphore.acquire_owned().await.ok()
+    }
+
+    fn semaphore(&self, url: &str) -> Option<Arc<Semaphore>> {
+        let max = self.max_per_host();
+        if max == 0 {
+            return None;
+        }
+        let host = host_key(url)?;
+        let mut hosts = self.0.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        Some(hosts.entry(host).or_insert_with(|| Arc::new(Semaphore::new(max))).clone())
+    }
+}
+
+/// Hôte et port de `url` (`example.com:443`); les sous‑domaines comptent à part
+fn host_key(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?.to_ascii_lowercase();
+    Some(match url.port_or_known_default() {
+        Some(port) => format!("{host}:{port}"),
+        None => host,
+    })
+}
+
+/// Lit `[queue] max_connections_per_host` (illimité par défaut, 0 = illimité)
+pub fn max_connections_per_host_from_config(config: &super::AppConfig) -> usize {
+    config.queue.as_ref().and_then(|q| q.max_connections_per_host).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_connections_capped_per_host() {
+        let limiter = HostLimiter::new(2);
+        let first = limiter.acquire("https://cdn.example.com/a.mkv").await;
+        let second = limiter.acquire("https://CDN.example.com:443/b.mkv").await;
+        assert!(first.is_some() && second.is_some());
+        // Troisième connexion vers le même hôte: en attente jusqu'à la libération d'une place
+        let third = limiter.acquire("https://cdn.example.com/c.mkv");
+        tokio::pin!(third);
+        assert!(futures::poll!(third.as_mut()).is_pending());
+        // Autre hôte (ou autre port): plafond distinct
+        assert!(limiter.acquire("http://cdn.example.com/a.mkv").await.is_some());
+        drop(first);
+        assert!(third.await.is_some());
+
+        assert!(limiter.acquire("file:///tmp/a.mkv").await.is_none());
+        limiter.set_max_per_host(0);
+        assert!(limiter.acquire("https://cdn.example.com/a.mkv").await.is_none());
+    }
+}
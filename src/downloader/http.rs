@@ -0,0 +1,101 @@
+//! Réglages du client HTTP partagé par les segments d'un téléchargement.
+//!
+//! Tous les segments d'une tâche passent par un même `reqwest::Client`: une connexion libérée
+//! par un segment sert au suivant, sans nouvelle poignée de main TLS. La section `[http]`
+//! règle cette réutilisation (durée de vie et nombre de connexions inactives conservées par
+//! hôte, keepalive TCP) et permet d'imposer HTTP/2 sans négociation. En HTTPS, HTTP/2 est de
+//! toute façon choisi par ALPN quand le serveur le propose, et ses requêtes partagent alors une
+//! seule connexion.
+use std::time::Duration;
+use reqwest::ClientBuilder;
+
+/// Durée pendant laquelle une connexion inactive est conservée (valeur de reqwest)
+const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Options de connexion appliquées aux clients HTTP du gestionnaire
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpClientOptions {
+    /// HTTP/2 d'emblée, sans négociation (serveurs HTTP/2 uniquement, `http://` compris)
+    pub http2_prior_knowledge: bool,
+    /// Fermeture des connexions inactives après ce délai (`None` = jamais)
+    pub pool_idle_timeout: Option<Duration>,
+    /// Connexions inactives conservées par hôte (`None` = sans limite)
+    pub max_idle_per_host: Option<usize>,
+    /// Intervalle des sondes keepalive TCP (`None` = désactivé)
+    pub tcp_keepalive: Option<Duration>,
+}
+
+impl Default for HttpClientOptions {
+    fn default() -> Self {
+        Self {
+            http2_prior_knowledge: false,
+            pool_idle_timeout: Some(DEFAULT_POOL_IDLE_TIMEOUT),
+            max_idle_per_host: None,
+            tcp_keepalive: None,
+        }
+    }
+}
+
+impl HttpClientOptions {
+    /// Lit la section `[http]` (valeurs par défaut sinon); 0 désactive le délai, la limite ou
+    /// le keepalive correspondant
+    pub fn from_config(config: &super::AppConfig) -> Self {
+        let mut options = Self::default();
+        if let Some(http) = &config.http {
+            if let Some(enabled) = http.http2_prior_knowledge {
+                options.http2_prior_knowledge = enabled;
+            }
+            if let Some(secs) = http.pool_idle_timeout_secs {
+                options.pool_idle_timeout = (secs > 0).then(|| Duration::from_secs(secs));
+            }
+            if let Some(max) = http.max_idle_per_host {
+                options.max_idle_per_host = (max > 0).then_some(max);
+            }
+            if let Some(secs) = http.tcp_keepalive_secs {
+                options.tcp_keepalive = (secs > 0).then(|| Duration::from_secs(secs));
+            }
+        }
+        options
+    }
+
+    /// Applique les options à `builder`
+    pub fn apply(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        builder = builder.pool_idle_timeout(self.pool_idle_timeout).tcp_keepalive(self.tcp_keepalive);
+        if let Some(max) = self.max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_from_config() {
+        let config: super::super::AppConfig = toml::from_str(concat!(
+            "[http]\n",
+            "http2_prior_knowledge = true\n",
+            "pool_idle_timeout_secs = 0\n",
+            "max_idle_per_host = 16\n",
+            "tcp_keepalive_secs = 30\n",
+        ))
+        .unwrap();
+        let options = HttpClientOptions::from_config(&config);
+        assert_eq!(
+            options,
+            HttpClientOptions {
+                http2_prior_knowledge: true,
+                pool_idle_timeout: None,
+                max_idle_per_host: Some(16),
+                tcp_keepalive: Some(Duration::from_secs(30)),
+            }
+        );
+        assert!(options.apply(reqwest::Client::builder()).build().is_ok());
+        assert_eq!(HttpClientOptions::from_config(&Default::default()), HttpClientOptions::default());
+    }
+}
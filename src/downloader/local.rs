@@ -0,0 +1,97 @@
+//! Sources locales: fichiers (`file://`) et données intégrées à l'URL (`data:`).
+//!
+//! Elles passent par le même pipeline que les sources réseau (segments, progression, manifeste,
+//! vérification d'empreinte), ce qui sert aux tests comme aux entrées du sniffer résolues en
+//! `data:`. Un `data:` est décodé en mémoire à chaque plage: ces URLs restent petites. Les URLs
+//! `blob:` n'existent que dans le navigateur qui les a créées et ne sont pas prises en charge.
+use std::io::SeekFrom;
+use anyhow::{Context, Result};
+use base64::Engine;
+use bytes::Bytes;
+use futures::stream;
+use percent_encoding::percent_decode_str;
+use tokio::io::AsyncSeekExt;
+use url::Url;
+use super::protocol::{limited_stream, ByteStream, READ_BLOCK};
+
+/// Taille de la ressource locale
+pub(crate) async fn probe(url: &str) -> Result<u64> {
+    if is_data_url(url) {
+        return Ok(decode_data_url(url)?.len() as u64);
+    }
+    let path = file_path(url)?;
+    let metadata = tokio::fs::metadata(&path).await.with_context(|| format!("Lire {}", path.display()))?;
+    if !metadata.is_file() {
+        anyhow::bail!("{} n'est pas un fichier", path.display());
+    }
+    Ok(metadata.len())
+}
+
+/// Ouvre `[offset, offset + len)` de la ressource locale
+pub(crate) async fn open_range(url: &str, offset: u64, len: u64) -> Result<ByteStream> {
+    if is_data_url(url) {
+        let data = decode_data_url(url)?;
+        let start = (offset as usize).min(data.len());
+        let end = offset.saturating_add(len).min(data.len() as u64) as usize;
+        let data = Bytes::from(data).slice(start..end);
+        let blocks: Vec<Result<Bytes>> = (0..data.len())
+            .step_by(READ_BLOCK)
+            .map(|i| Ok(data.slice(i..(i + READ_BLOCK).min(data.len()))))
+            .collect();
+        return Ok(Box::pin(stream::iter(blocks)));
+    }
+    let path = file_path(url)?;
+    let mut file = tokio::fs::File::open(&path).await.with_context(|| format!("Ouvrir {}", path.display()))?;
+    file.seek(SeekFrom::Start(offset)).await.context("Positionner la lecture")?;
+    Ok(limited_stream(file, len, ()))
+}
+
+fn is_data_url(url: &str) -> bool {
+    url.get(..5).is_some_and(|scheme| scheme.eq_ignore_ascii_case("data:"))
+}
+
+/// Chemin local d'une URL `file://` (`%20` décodé)
+fn file_path(url: &str) -> Result<std::path::PathBuf> {
+    let parsed = Url::parse(url).with_context(|| format!("URL invalide: {url}"))?;
+    parsed.to_file_path().map_err(|_| anyhow::anyhow!("Chemin local invalide: {url}"))
+}
+
+/// Contenu d'une URL `data:[<type>][;base64],<données>`
+fn decode_data_url(url: &str) -> Result<Vec<u8>> {
+    let (header, payload) = url[5..].split_once(',').context("URL data: sans virgule")?;
+    let bytes: Vec<u8> = percent_decode_str(payload).collect();
+    if header.rsplit(';').next().is_some_and(|p| p.eq_ignore_ascii_case("base64")) {
+        // Les espaces éventuels (URL recopiée sur plusieurs lignes) sont ignorés
+        let compact: Vec<u8> = bytes.into_iter().filter(|b| !b.is_ascii_whitespace()).collect();
+        return base64::engine::general_purpose::STANDARD.decode(compact).context("Contenu base64 invalide");
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt;
+
+    #[tokio::test]
+    async fn test_file_and_data_ranges() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mon film.bin");
+        std::fs::write(&path, b"0123456789").unwrap();
+        let url = Url::from_file_path(&path).unwrap().to_string();
+        assert!(url.contains("%20"));
+        assert_eq!(probe(&url).await.unwrap(), 10);
+        let read = |url: String, offset, len| async move {
+            let blocks: Vec<Bytes> = open_range(&url, offset, len).await.unwrap().try_collect().await.unwrap();
+            blocks.concat()
+        };
+        assert_eq!(read(url.clone(), 3, 4).await, b"3456");
+
+        let data = "data:text/plain;base64,SGVsbG8gd29ybGQ=";
+        assert_eq!(probe(data).await.unwrap(), 11);
+        assert_eq!(read(data.to_string(), 6, 100).await, b"world");
+        assert_eq!(read("data:,a%20b".to_string(), 0, 3).await, b"a b");
+        assert!(probe("data:;base64,@@").await.is_err());
+        assert!(probe(Url::from_file_path(dir.path()).unwrap().as_str()).await.is_err());
+    }
+}
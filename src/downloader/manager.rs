@@ -5,232 +5,1216 @@
 //! - À terme, lancer les téléchargements parallèles (HTTP `Range`) et agréger la progression.
 //!
 //! Performance:
-//! - Chaque fichier de chunk est pré‑alloué à la taille exacte de son segment
-//!   pour éviter des réallocations et garantir des écritures positionnées efficaces.
+//! - Le fichier final est pré‑alloué une seule fois à sa taille totale; chaque segment y
+//!   écrit directement à son offset (écritures positionnées), sans passe de fusion.
+//! - Avec `with_adaptive_chunking`, les plages sont fusionnées ou coupées selon le débit mesuré
+//!   par connexion, et une connexion libre reprend la moitié de la plage la plus lente.
+//! - `DownloadTask::mirrors` répartit les segments entre plusieurs sources, avec bascule sur la
+//!   source suivante en cas d'échec et contrôle croisé du fichier reconstitué.
+//! - Un petit fichier (sous `with_small_file_threshold`, par défaut la taille d'un segment) est
+//!   récupéré en une requête, sans manifeste ni préallocation.
+//! - Une connexion muette depuis `with_stall_timeout` (60 s par défaut) est abandonnée et son
+//!   segment relancé depuis l'offset atteint.
+//! - Les étapes de chaque tâche sont publiées sur le bus `DownloadEvents` (`subscribe`).
 use std::{io};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use tokio::fs::{OpenOptions};
 use anyhow::{Context, Result};
-use tokio::io::{AsyncWriteExt};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use std::collections::HashSet;
+use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
-use futures::stream::{self, StreamExt};
-use reqwest::header::{ACCEPT_RANGES, CONTENT_LENGTH, RANGE};
-use super::utils::{create_empty_file, merge_chunks};
-use super::types::{DownloadTask, Chunk};
+use std::time::{Duration, Instant, SystemTime};
+use futures::{future, StreamExt};
+use reqwest::header::{HeaderMap, ACCEPT_ENCODING, ACCEPT_RANGES, CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_RANGE, COOKIE, ETAG, RANGE, RETRY_AFTER};
+use reqwest::StatusCode;
+use super::utils::{create_empty_file, preallocate_file};
+use super::naming::{dedupe_path, filename_from_content_disposition, filename_from_url, CollisionPolicy};
+use super::types::{DownloadTask, Chunk, ChunkTiming, DownloadStats, RequestOptions};
+use super::postprocess::{DownloadOutcome, PostProcessPipeline};
+use super::space::{DiskFull, FileTooLarge, SpaceGuard};
+use super::offline::OfflineMode;
+use super::progress::{ProgressEvent, ProgressReporter};
+use super::error::{DownloadError, DownloadErrorKind};
+use super::events::{DownloadEvent, DownloadEventKind, DownloadEvents, TaskEvents};
+use super::stats::DownloadStatistics;
+use super::cookies::CookieJar;
+use super::throttle::{acquire_all, RateLimiter};
+use super::retry::{is_retryable, is_source_error, parse_retry_after, within_stall, BusyGate, RetryPolicy, Throttled, DEFAULT_STALL_TIMEOUT};
+use super::manifest::{Manifest, ManifestStore};
+use super::scheduler::{AdaptiveChunking, ChunkScheduler};
+use super::mirrors::{MirrorHealth, MirrorSet};
+use super::protocol::{ByteStream, Protocol};
+use super::http::HttpClientOptions;
+use super::hosts::HostLimiter;
+use super::checksum::{self, ExpectedHash, Integrity, TruncatedDownload};
+use super::{ftp, local, sftp};
+use tokio::sync::{broadcast, mpsc};
 
-pub struct DownloadManager;
+/// Intervalle (octets) entre deux sauvegardes de l'offset de reprise d'un segment
+const OFFSET_PERSIST_EVERY: u64 = 1024 * 1024;
+/// Contenu demandé sans compression (sondages, plages, fichiers d'empreinte)
+const IDENTITY_ENCODING: &str = "identity";
+/// Encodages acceptés par un téléchargement en une requête avec `with_decompression(true)`;
+/// reqwest décompresse le corps au fil de l'eau
+const COMPRESSED_ENCODINGS: &str = "gzip, br, deflate";
+/// Connexions simultanées par téléchargement sans `[chunking] max_concurrency`
+const DEFAULT_MAX_CONCURRENCY: usize = 8;
+/// Octets relus depuis une autre source pour contrôler chaque source d'un téléchargement multi‑sources
+const VERIFY_SAMPLE: u64 = 4096;
+
+pub struct DownloadManager {
+    post_processors: PostProcessPipeline,
+    space_guard: SpaceGuard,
+    offline: OfflineMode,
+    progress: Option<mpsc::UnboundedSender<ProgressEvent>>,
+    events: DownloadEvents,
+    rate_limiter: RateLimiter,
+    hosts: HostLimiter,
+    retry: RetryPolicy,
+    stall_timeout: Option<Duration>,
+    adaptive: Option<AdaptiveChunking>,
+    sidecar_checksums: bool,
+    collision: CollisionPolicy,
+    statistics: DownloadStatistics,
+    cookies: CookieJar,
+    decompress: bool,
+    max_concurrency: usize,
+    http: HttpClientOptions,
+    mirror_health: MirrorHealth,
+    /// Taille en dessous de laquelle une requête unique suffit (`None` = `chunk_size` de la tâche)
+    small_file_threshold: Option<u64>,
+}
+
+/// Paramètres partagés par toutes les tâches de segments d'un même téléchargement
+#[derive(Clone, Copy)]
+struct ChunkContext<'a> {
+    client: &'a Client,
+    /// URL principale et miroirs validés
+    mirrors: &'a MirrorSet,
+    request: &'a RequestOptions,
+    output: &'a Path,
+    guard: &'a SpaceGuard,
+    reporter: &'a ProgressReporter,
+    /// Limiteurs appliqués à chaque bloc reçu (par tâche puis global)
+    limiters: &'a [RateLimiter],
+    /// Plafond de connexions par hôte partagé entre téléchargements
+    hosts: &'a HostLimiter,
+    retry: &'a RetryPolicy,
+    /// Silence maximal d'une connexion avant d'abandonner la tentative
+    stall: Option<Duration>,
+    /// Offsets persistés pour la reprise
+    manifest: &'a ManifestStore,
+    /// Bornes actuelles des plages (réduites quand une autre connexion en reprend la fin)
+    scheduler: &'a ChunkScheduler,
+    /// Suspension commune après un `Retry-After` (429/503)
+    busy: &'a BusyGate,
+    origin: Instant,
+}
+
+/// Métadonnées annoncées par le serveur
+#[derive(Debug, Clone)]
+pub struct RemoteMetadata {
+    pub total_size: u64,
+    pub supports_range: bool,
+    pub etag: Option<String>,
+    /// URL effective après les redirections 3xx (l'URL interrogée pour FTP/SFTP)
+    pub final_url: String,
+}
 
 impl DownloadManager {
     /// Initialise un nouveau gestionnaire de téléchargement
     pub fn new() -> Self {
-        Self
+        Self {
+            post_processors: PostProcessPipeline::new(),
+            space_guard: SpaceGuard::default(),
+            offline: OfflineMode::global(),
+            progress: None,
+            events: DownloadEvents::new(),
+            rate_limiter: RateLimiter::global(),
+            hosts: HostLimiter::global(),
+            retry: RetryPolicy::default(),
+            stall_timeout: Some(DEFAULT_STALL_TIMEOUT),
+            adaptive: None,
+            sidecar_checksums: false,
+            collision: CollisionPolicy::default(),
+            statistics: DownloadStatistics::global(),
+            cookies: CookieJar::global(),
+            decompress: false,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            http: HttpClientOptions::default(),
+            mirror_health: MirrorHealth::global(),
+            small_file_threshold: None,
+        }
+    }
+
+    /// Définit les post‑traitements exécutés après un téléchargement réussi
+    pub fn with_post_processors(mut self, pipeline: PostProcessPipeline) -> Self {
+        self.post_processors = pipeline;
+        self
+    }
+
+    /// Définit l'indicateur hors ligne consulté avant chaque requête (global par défaut)
+    pub fn with_offline_mode(mut self, offline: OfflineMode) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Publie la progression octet par octet (par segment et agrégée) sur `tx`
+    pub fn with_progress(mut self, tx: mpsc::UnboundedSender<ProgressEvent>) -> Self {
+        self.progress = Some(tx);
+        self
+    }
+
+    /// Publie sur `events`, partagé avec d'autres gestionnaires, au lieu du bus propre à celui‑ci
+    pub fn with_events(mut self, events: DownloadEvents) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Abonnement aux événements du cycle de vie des tâches (démarrage, progression, fin...)
+    pub fn subscribe(&self) -> broadcast::Receiver<DownloadEvent> {
+        self.events.subscribe()
+    }
+
+    /// Définit la politique de nouvelles tentatives appliquée à chaque segment
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Abandonne et relance une tentative restée `timeout` sans recevoir d'octet (60 s par
+    /// défaut, `None` = attendre indéfiniment)
+    pub fn with_stall_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.stall_timeout = timeout;
+        self
+    }
+
+    /// Active le découpage adaptatif des plages (`None` = segments fixes de `chunk_size`)
+    pub fn with_adaptive_chunking(mut self, adaptive: Option<AdaptiveChunking>) -> Self {
+        self.adaptive = adaptive;
+        self
+    }
+
+    /// Vérifie les fichiers sans empreinte connue à l'aide de `<url>.sha256` / `<url>.md5`
+    /// s'ils existent (résultat dans `DownloadStats::integrity`)
+    pub fn with_sidecar_checksums(mut self, enabled: bool) -> Self {
+        self.sidecar_checksums = enabled;
+        self
+    }
+
+    /// Accepte gzip/brotli/deflate pour les téléchargements en une requête (serveur sans
+    /// `Range`) et écrit le contenu décompressé; les plages restent toujours non compressées
+    pub fn with_decompression(mut self, enabled: bool) -> Self {
+        self.decompress = enabled;
+        self
+    }
+
+    /// Connexions simultanées par téléchargement, sauf tâche fixant les siennes
+    /// (`DownloadTask::max_concurrency`); 8 par défaut
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Options de connexion du client HTTP (réutilisation, keepalive, HTTP/2)
+    pub fn with_http_options(mut self, options: HttpClientOptions) -> Self {
+        self.http = options;
+        self
+    }
+
+    /// Plafond de connexions par hôte, partagé avec d'autres gestionnaires; global par défaut
+    #[allow(dead_code)]
+    pub fn with_host_limiter(mut self, hosts: HostLimiter) -> Self {
+        self.hosts = hosts;
+        self
+    }
+
+    /// Suivi de santé des sources consulté et alimenté par ce gestionnaire; global par défaut
+    #[allow(dead_code)]
+    pub fn with_mirror_health(mut self, health: MirrorHealth) -> Self {
+        self.mirror_health = health;
+        self
+    }
+
+    /// Fichiers HTTP d'au plus `threshold` octets téléchargés en une requête, sans manifeste,
+    /// préallocation ni segments (`Some(0)` désactive; `None` = `chunk_size` de la tâche)
+    pub fn with_small_file_threshold(mut self, threshold: Option<u64>) -> Self {
+        self.small_file_threshold = threshold;
+        self
+    }
+
+    /// Conduite à tenir si la sortie existe déjà sans manifeste (renommage par défaut)
+    pub fn with_collision_policy(mut self, policy: CollisionPolicy) -> Self {
+        self.collision = policy;
+        self
+    }
+
+    /// Compteurs alimentés par ce gestionnaire (octets reçus, échecs); globaux par défaut
+    #[allow(dead_code)]
+    pub fn with_statistics(mut self, statistics: DownloadStatistics) -> Self {
+        self.statistics = statistics;
+        self
+    }
+
+    /// Cookies envoyés et enregistrés par ce gestionnaire (stockage global persistant par défaut)
+    #[allow(dead_code)]
+    pub fn with_cookie_jar(mut self, cookies: CookieJar) -> Self {
+        self.cookies = cookies;
+        self
+    }
+
+    /// Définit la marge d'espace disque surveillée pendant les écritures
+    pub fn with_space_guard(mut self, guard: SpaceGuard) -> Self {
+        self.space_guard = guard;
+        self
     }
 
-    /// Prépare les métadonnées des chunks et les fichiers disque associés.
+    /// Prépare les métadonnées des chunks et le fichier de sortie.
     ///
     /// Détails:
     /// - Génère les segments via `DownloadTask::create_chunks`.
-    /// - Pour chaque segment, crée un fichier temporaire `output.part<index>` si absent,
-    ///   avec une taille pré‑allouée correspondant exactement à `[start..=end]`.
+    /// - Réutilise le manifeste `<nom>.scrapes.json` s'il décrit la même tâche et que
+    ///   `<nom>.partial` a la taille attendue: les segments reprennent alors son découpage
+    ///   (éventuellement adapté) et l'offset enregistré dans `Chunk::downloaded`.
+    /// - Sinon, pré‑alloue `<nom>.partial` à `total_size` et écrit un manifeste vierge.
+    #[allow(dead_code)]
     pub fn prepare(&self, task: &DownloadTask) -> io::Result<Vec<Chunk>> {
+        self.prepare_manifest(task, None, 0).map(|(chunks, _)| chunks)
+    }
+
+    /// Comme `prepare`, en retournant aussi le manifeste (`etag` invalide un manifeste différent).
+    /// `kept` octets déjà présents au début de `<nom>.partial` (`CollisionPolicy::Resume`) sont
+    /// conservés et comptés comme téléchargés.
+    fn prepare_manifest(&self, task: &DownloadTask, etag: Option<&str>, kept: u64) -> io::Result<(Vec<Chunk>, Manifest)> {
         tracing::info!(url = %task.url, total_size = task.total_size, chunk_size = task.chunk_size, "Préparation des segments");
-        let chunks = task.create_chunks();
+        let mut chunks = task.create_chunks();
+        if chunks.is_empty() {
+            return Ok((chunks, Manifest::new(task, &[], etag.map(str::to_string))));
+        }
 
+        let partial = Manifest::partial_path_for(&task.output);
+        let preallocated = std::fs::metadata(&partial).is_ok_and(|m| m.len() == task.total_size);
+        let existing = Manifest::load(&task.output).filter(|m| preallocated && m.matches(task, etag));
+        let Some(mut manifest) = existing else {
+            if kept > 0 {
+                tracing::info!(path = %partial.display(), kept, "Reprise à la suite du fichier existant");
+                preallocate_file(&std::fs::OpenOptions::new().write(true).open(&partial)?, task.total_size)?;
+                for chunk in &mut chunks {
+                    chunk.downloaded = kept.saturating_sub(chunk.start).min(chunk.end - chunk.start + 1);
+                }
+            } else {
+                tracing::debug!(path = %partial.display(), size = task.total_size, "Préallocation du fichier de sortie");
+                create_empty_file(&partial, task.total_size)?;
+            }
+            let mut manifest = Manifest::new(task, &chunks, etag.map(str::to_string));
+            for (state, chunk) in manifest.chunks.iter_mut().zip(&chunks) {
+                state.downloaded = chunk.downloaded;
+            }
+            manifest.save(&task.output)?;
+            return Ok((chunks, manifest));
+        };
+
+        // Reprise au milieu des segments: relire le découpage et les offsets déjà écrits
+        let chunks: Vec<Chunk> = manifest
+            .chunks
+            .iter()
+            .map(|c| Chunk { index: c.index, start: c.start, end: c.end, downloaded: c.downloaded.min(c.len()) })
+            .collect();
         for chunk in &chunks {
-            // Créer le fichier part si absent, pré‑alloué à la taille réelle du chunk
-            if !chunk.path.exists() {
-                tracing::debug!(index = chunk.index, start = chunk.start, end = chunk.end, path = %chunk.path.display(), "Création du fichier de partie");
-                let part_len = (chunk.end - chunk.start) + 1;
-                create_empty_file(&chunk.path, part_len)?;
+            if chunk.downloaded > 0 {
+                tracing::debug!(index = chunk.index, downloaded = chunk.downloaded, "Reprise partielle du segment");
             }
         }
-
-        Ok(chunks)
+        if manifest.etag.is_none() && etag.is_some() {
+            manifest.etag = etag.map(str::to_string);
+            manifest.save(&task.output)?;
+        }
+        Ok((chunks, manifest))
     }
 
     /// Démarre un téléchargement parallèle par plages HTTP (`Range`).
     ///
     /// Stratégie:
     /// - Détecte `content-length` et support `accept-ranges` via HEAD si nécessaire.
-    /// - Prépare les fichiers de parties pour chaque segment.
-    /// - Télécharge les segments en parallèle avec une limite de concurrence.
-    /// - Fusionne les parties en un fichier final à la fin.
-    pub async fn start(&self, mut task: DownloadTask) -> Result<()> {
+    /// - Pré‑alloue `<nom>.partial` et calcule les segments.
+    /// - Télécharge les segments en parallèle avec une limite de concurrence; chaque connexion
+    ///   demande sa prochaine plage à un `ChunkScheduler` (fixe ou adaptatif).
+    /// - Chaque segment écrit directement à son offset dans `<nom>.partial` pré‑alloué.
+    /// - Si la tâche porte des empreintes (`hashes`), la plus forte est vérifiée à la fin.
+    /// - `<nom>.partial` n'est renommé en `<nom>` qu'une fois complet et vérifié: lecteurs
+    ///   multimédia et surveillances de dossier ne voient jamais un fichier à moitié écrit.
+    ///
+    /// Retourne les temps mesurés par segment (`DownloadStats`) pour l'analyse des performances;
+    /// la nature d'un échec se lit avec `DownloadErrorKind::of`.
+    /// Un échec est compté dans `DownloadStatistics`, sauf interruption par le mode hors ligne.
+    /// Chaque étape est publiée sur le bus d'événements (`subscribe`), de `Queued` jusqu'à
+    /// `Completed`, `Failed` ou `Cancelled`.
+    pub async fn start(&self, task: DownloadTask) -> Result<DownloadStats> {
+        let events = self.events.for_task(&task.url, &task.output);
+        events.emit(DownloadEventKind::Queued);
+        let result = self.run(task, &events).await;
+        match &result {
+            Ok(stats) => events.emit(DownloadEventKind::Completed(stats.clone())),
+            Err(e) if self.offline.is_offline() || DownloadErrorKind::of(e) == DownloadErrorKind::Cancelled => events.emit(DownloadEventKind::Cancelled),
+            Err(e) => {
+                self.statistics.record_failure();
+                events.emit(DownloadEventKind::Failed { kind: DownloadErrorKind::of(e), message: format!("{e:#}") });
+            }
+        }
+        result
+    }
+
+    async fn run(&self, mut task: DownloadTask, events: &TaskEvents) -> Result<DownloadStats> {
         tracing::info!(url = %task.url, "Démarrage du téléchargement");
-        let client = Client::builder().build().context("Créer client HTTP")?;
+        self.offline.ensure_online()?;
+        let origin = Instant::now();
+        let client = self.client()?;
 
         // Déterminer la taille et le support des ranges si absent
-        let (total_size, supports_range) = self
+        let (RemoteMetadata { total_size, supports_range, etag, .. }, sources) = self
             .detect_remote_metadata(&client, &task)
             .await
             .context("Détecter métadonnées distantes")?;
+        // Les plages visent directement les cibles des redirections (liens signés à durée limitée)
+        let final_url = sources.first().cloned();
         task.total_size = total_size;
-        tracing::info!(total_size, supports_range, "Métadonnées distantes récupérées");
+        tracing::info!(total_size, supports_range, sources = sources.len(), "Métadonnées distantes récupérées");
+        // Taille annoncée hors limite: refus avant toute écriture sur le disque
+        if let Some(limit) = task.max_size
+            && total_size > limit
+        {
+            tracing::error!(url = %task.url, total_size, limit, "Fichier trop volumineux, téléchargement refusé");
+            return Err(FileTooLarge { url: task.url.clone(), size: total_size, limit }.into());
+        }
+        let Some(kept) = self.plan_output(&mut task, supports_range && total_size > 0) else {
+            tracing::info!(file = %task.output.display(), "Sortie existante conservée, téléchargement ignoré");
+            return Ok(DownloadStats { final_url, skipped: true, ..Default::default() });
+        };
+        let mirrors = MirrorSet::new(sources, self.mirror_health.clone());
+        let partial = Manifest::partial_path_for(&task.output);
+        self.check_space_for(&task)?;
+        events.emit(DownloadEventKind::Started { total_size, output: task.output.clone() });
+        let reporter = ProgressReporter::new(self.progress.clone(), events.clone(), self.statistics.clone(), total_size, origin)
+            .with_mirrors(mirrors.health(), mirrors.urls());
+        let limiters: Vec<RateLimiter> = task
+            .rate_limit
+            .map(RateLimiter::new)
+            .into_iter()
+            .chain(std::iter::once(self.rate_limiter.clone()))
+            .collect();
 
-        // Si le serveur ne supporte pas les ranges, télécharger en 1 requête
-        if !supports_range {
-            tracing::warn!("Serveur sans support Range: téléchargement en une requête");
-            self.download_whole(&client, &task).await?;
-            return Ok(());
+        // Sans support des ranges, sans taille connue (réponse `chunked`) ou pour un petit
+        // fichier, télécharger en un flux unique écrit au fil de l'eau
+        let small = self.is_small(&task, kept);
+        if !supports_range || total_size == 0 || small {
+            let timing = if small {
+                tracing::info!(total_size, "Petit fichier: téléchargement en une requête");
+                self.download_small(&client, &task, &mirrors, &reporter, &limiters, origin).await?
+            } else {
+                if total_size == 0 {
+                    tracing::warn!("Taille inconnue (pas de content-length): téléchargement en flux");
+                } else {
+                    tracing::warn!("Serveur sans support Range: téléchargement en une requête");
+                }
+                self.download_whole(&client, &task, mirrors.url(0), &reporter, &limiters, origin).await?
+            };
+            reporter.chunk_done(0, timing.bytes);
+            events.emit(DownloadEventKind::Merging);
+            // Taille annoncée: le corps reçu doit la respecter (flux sans taille: rien à comparer)
+            let expected = if total_size > 0 { total_size } else { timing.bytes };
+            check_complete(&task.output, expected, timing.bytes, true)?;
+            task.total_size = timing.bytes;
+            let integrity = self.verify_integrity(&client, &task, events).await?;
+            finalize_output(&task.output).await?;
+            let stats = DownloadStats { per_chunk: vec![timing], total_elapsed: origin.elapsed(), final_url, integrity, skipped: false };
+            self.run_post_processors(&task, None, &stats).await?;
+            return Ok(stats);
         }
 
-        // Préparer les chunks et fichiers
-        let chunks = self.prepare(&task).context("Préparer chunks")?;
+        // Préparer les chunks, le fichier de sortie et le manifeste
+        let (chunks, manifest) = self.prepare_manifest(&task, etag.as_deref(), kept).context("Préparer chunks")?;
+        let manifest = ManifestStore::new(task.output.clone(), manifest);
 
-        // Reprise: ignorer les segments déjà complétés (présence d'un marqueur .done)
-        let to_download: Vec<Chunk> = chunks
-            .iter()
-            .cloned()
-            .filter(|c| {
-                let marker = done_marker_path(&c.path);
-                !marker.exists()
-            })
-            .collect();
-        tracing::info!(pending = to_download.len(), total = chunks.len(), "Segments à télécharger");
+        // Reprise: ignorer les segments déjà complétés selon le manifeste
+        let pending = chunks.iter().filter(|c| c.downloaded < c.end - c.start + 1).count();
+        tracing::info!(pending, total = chunks.len(), adaptive = self.adaptive.is_some(), "Segments à télécharger");
+        reporter.add_resumed(chunks.iter().map(|c| c.downloaded).sum());
 
-        // Concurrence bornée
-        let max_concurrency = 8usize;
+        // Concurrence bornée: valeur de la tâche, sinon celle du gestionnaire
+        let max_concurrency = task.max_concurrency.unwrap_or(self.max_concurrency);
         tracing::info!(max_concurrency, "Téléchargements parallèles");
+        let scheduler = ChunkScheduler::new(chunks, self.adaptive.clone(), max_concurrency);
 
-        let url = task.url.clone();
-        stream::iter(to_download.clone())
-            .map(|chunk| {
-                let client = client.clone();
-                let url = url.clone();
-                async move {
-                    if let Err(e) = download_chunk(&client, &url, &chunk).await {
-                        Err(anyhow::anyhow!("chunk {}: {}", chunk.index, e))
-                    } else {
-                        Ok(())
+        let offline = &self.offline;
+        let busy = BusyGate::default();
+        let ctx = ChunkContext {
+            client: &client,
+            mirrors: &mirrors,
+            request: &task.request,
+            output: &partial,
+            guard: &self.space_guard,
+            reporter: &reporter,
+            limiters: &limiters,
+            hosts: &self.hosts,
+            retry: &self.retry,
+            stall: self.stall_timeout,
+            manifest: &manifest,
+            scheduler: &scheduler,
+            busy: &busy,
+            origin,
+        };
+        let ctx = &ctx;
+        // Chaque connexion enchaîne les plages jusqu'à épuisement; un échec n'interrompt pas les autres
+        let workers = (0..max_concurrency).map(|_| async move {
+            let mut results = Vec::new();
+            loop {
+                // Passage hors ligne en cours de route: ne plus lancer de nouveaux segments
+                if let Err(e) = offline.ensure_online() {
+                    results.push(Err(e));
+                    break;
+                }
+                let chunk = match ctx.scheduler.next(ctx.manifest) {
+                    Ok(Some(chunk)) => chunk,
+                    Ok(None) => break,
+                    Err(e) => {
+                        results.push(Err(anyhow::Error::new(e).context("Mettre à jour le manifeste")));
+                        break;
                     }
+                };
+                let result = download_chunk(ctx, &chunk).await;
+                ctx.scheduler.finish(chunk.index, result.is_ok());
+                if let Ok(timing) = &result {
+                    ctx.reporter.chunk_done(chunk.index, timing.bytes);
                 }
-            })
-            .buffer_unordered(max_concurrency)
-            .collect::<Vec<_>>()
+                // Le manque d'espace reste typé (`DiskFull`) pour que l'appelant puisse l'afficher;
+                // les autres erreurs gardent leur catégorie (`DownloadErrorKind`)
+                results.push(result.map_err(|e| match e.downcast::<DiskFull>() {
+                    Ok(full) => full.into(),
+                    Err(e) => DownloadError::new(DownloadErrorKind::of(&e), format!("chunk {}: {}", chunk.index, e)).into(),
+                }));
+            }
+            results
+        });
+        let mut per_chunk = future::join_all(workers)
             .await
             .into_iter()
+            .flatten()
             .collect::<Result<Vec<_>, _>>()?;
+        per_chunk.sort_by_key(|t| t.index);
+        events.emit(DownloadEventKind::Merging);
+        let snapshot = manifest.snapshot();
+        check_complete(&task.output, total_size, snapshot.summary().downloaded, snapshot.is_contiguous())?;
+        if mirrors.len() > 1 {
+            verify_across_sources(ctx, &per_chunk).await?;
+        }
+        let integrity = self.verify_integrity(&client, &task, events).await?;
+        finalize_output(&task.output).await?;
 
-        // Fusion des fichiers partiels
-        let part_paths: Vec<_> = chunks.iter().map(|c| c.path.as_path()).collect();
-        tracing::info!(file = %task.output.display(), parts = part_paths.len(), "Fusion des parties en sortie");
-        merge_chunks(&part_paths, &task.output).context("Fusionner chunks")?;
-        
-        // NE PAS nettoyer les fichiers temporaires - les garder pour permettre la reprise
-        // L'utilisateur peut les supprimer manuellement s'il le souhaite
-        // self.cleanup_temp_files(&chunks).context("Nettoyer fichiers temporaires")?;
-        
-        tracing::info!(file = %task.output.display(), "Téléchargement terminé (fichiers part conservés pour reprise)");
-        Ok(())
+        let stats = DownloadStats { per_chunk, total_elapsed: origin.elapsed(), final_url, integrity, skipped: false };
+        if let Some(slowest) = stats.slowest() {
+            tracing::debug!(index = slowest.index, duration = ?slowest.duration(), "Segment le plus lent");
+        }
+
+        // Le manifeste est conservé sauf si l'étape `cleanup` est configurée
+        self.run_post_processors(&task, Some(Manifest::path_for(&task.output)), &stats).await?;
+
+        tracing::info!(file = %task.output.display(), elapsed = ?stats.total_elapsed, "Téléchargement terminé");
+        Ok(stats)
     }
 
-    /// Effectue une requête HEAD pour récupérer `content-length` et `accept-ranges`.
-    async fn detect_remote_metadata(&self, client: &Client, task: &DownloadTask) -> Result<(u64, bool)> {
-        if task.total_size > 0 {
-            // On connaît déjà la taille; supposer support des ranges et laisser le serveur répondre 206
-            return Ok((task.total_size, true));
+    /// Client HTTP partageant le stockage de cookies du gestionnaire; ses connexions servent à
+    /// tous les segments de la tâche
+    fn client(&self) -> Result<Client> {
+        self.http
+            .apply(Client::builder())
+            .cookie_provider(std::sync::Arc::new(self.cookies.clone()))
+            .build()
+            .context("Créer client HTTP")
+    }
+
+    /// Applique la politique de collision si `output` existe sans manifeste (un manifeste
+    /// signale notre propre téléchargement, repris normalement). Retourne les octets à conserver
+    /// en tête de `<nom>.partial`, ou `None` s'il ne faut rien télécharger.
+    fn plan_output(&self, task: &mut DownloadTask, resumable: bool) -> Option<u64> {
+        let Ok(existing) = std::fs::metadata(&task.output) else {
+            return Some(0);
+        };
+        let partial = Manifest::partial_path_for(&task.output);
+        if Manifest::path_for(&task.output).exists() {
+            // Fichier déjà terminé (manifeste conservé) ou commencé avant l'écriture dans
+            // `.partial`: la reprise repart de son contenu
+            if !partial.exists() && let Err(e) = std::fs::rename(&task.output, &partial) {
+                tracing::warn!(file = %task.output.display(), error = %e, "Reprise du fichier existant impossible");
+            }
+            return Some(0);
+        }
+        match self.collision {
+            CollisionPolicy::Overwrite => {
+                tracing::warn!(file = %task.output.display(), "Sortie existante remplacée");
+                Some(0)
+            }
+            CollisionPolicy::Skip => None,
+            CollisionPolicy::Resume if resumable && existing.len() <= task.total_size => {
+                // Le fichier existant devient le début de `.partial`; un échec de déplacement
+                // repart de zéro sans toucher à l'original
+                match std::fs::rename(&task.output, &partial) {
+                    Ok(()) => Some(existing.len()),
+                    Err(e) => {
+                        tracing::warn!(file = %task.output.display(), error = %e, "Reprise du fichier existant impossible");
+                        Some(0)
+                    }
+                }
+            }
+            CollisionPolicy::Resume | CollisionPolicy::Rename => {
+                let renamed = dedupe_path(&task.output, &HashSet::new());
+                tracing::info!(from = %task.output.display(), to = %renamed.display(), "Sortie existante: nouveau nom");
+                task.output = renamed;
+                Some(0)
+            }
+        }
+    }
+
+    /// Vérifie `<nom>.partial`: empreintes de la tâche (échec si différentes), sinon fichier
+    /// d'empreinte voisin. Le résultat est publié (`Verified`) dès qu'une empreinte a été contrôlée.
+    async fn verify_integrity(&self, client: &Client, task: &DownloadTask, events: &TaskEvents) -> Result<Option<Integrity>> {
+        checksum::verify_file(&Manifest::partial_path_for(&task.output), &task.hashes).await?;
+        if let Some(strongest) = task.hashes.iter().map(|h| h.algorithm).max() {
+            events.emit(DownloadEventKind::Verified(Integrity::Verified(strongest)));
+        }
+        let integrity = self.verify_sidecar(client, task).await?;
+        if let Some(integrity) = integrity {
+            events.emit(DownloadEventKind::Verified(integrity));
         }
+        Ok(integrity)
+    }
 
-        let resp = client.head(&task.url).send().await.context("HEAD request")?;
-        resp.error_for_status_ref().context("HEAD status")?;
+    /// Compare le fichier terminé au fichier d'empreinte voisin de l'URL principale. `None` si
+    /// l'option est désactivée, si la tâche porte déjà ses empreintes ou si aucun n'est trouvé;
+    /// une empreinte différente est signalée sans faire échouer le téléchargement.
+    async fn verify_sidecar(&self, client: &Client, task: &DownloadTask) -> Result<Option<Integrity>> {
+        if !self.sidecar_checksums || !task.hashes.is_empty() || Protocol::of(&task.url) != Protocol::Http || self.offline.is_offline() {
+            return Ok(None);
+        }
+        let Some(expected) = fetch_sidecar(client, &task.url, &task.request).await else {
+            tracing::debug!(url = %task.url, "Aucun fichier d'empreinte voisin");
+            return Ok(None);
+        };
+        if checksum::matches_hash(&Manifest::partial_path_for(&task.output), &expected).await? {
+            tracing::info!(file = %task.output.display(), algorithm = ?expected.algorithm, "Empreinte voisine vérifiée");
+            Ok(Some(Integrity::Verified(expected.algorithm)))
+        } else {
+            tracing::warn!(file = %task.output.display(), algorithm = ?expected.algorithm, expected = %expected.hex, "Fichier corrompu: empreinte voisine différente");
+            Ok(Some(Integrity::Corrupt(expected.algorithm)))
+        }
+    }
 
-        let len = resp
-            .headers()
-            .get(CONTENT_LENGTH)
-            .and_then(|v| v.to_str().ok())
-            .and_then(|s| s.parse::<u64>().ok())
-            .context("En‑tête content-length manquant/invalide")?;
+    /// Exécute le pipeline de post‑traitement configuré (no‑op s'il est vide)
+    async fn run_post_processors(&self, task: &DownloadTask, manifest: Option<PathBuf>, stats: &DownloadStats) -> Result<()> {
+        if self.post_processors.is_empty() {
+            return Ok(());
+        }
+        let outcome = DownloadOutcome {
+            url: task.url.clone(),
+            output: task.output.clone(),
+            total_size: task.total_size,
+            manifest,
+            stats: stats.clone(),
+        };
+        self.post_processors.run(&outcome).await
+    }
 
-        let supports_range = resp
+    /// Nom de fichier proposé par le serveur pour `url`: `Content-Disposition`, sinon dernier
+    /// segment de l'URL finale (après redirections), sinon de l'URL d'origine.
+    ///
+    /// Un HEAD suffit en général; s'il est refusé, un GET `Range: bytes=0-0` est tenté et son
+    /// corps ignoré. `None` si aucun nom exploitable n'est trouvé (ex: `download.php?id=42`).
+    pub async fn resolve_filename(&self, url: &str, request: &RequestOptions) -> Result<Option<String>> {
+        self.offline.ensure_online()?;
+        if Protocol::of(url) != Protocol::Http {
+            return Ok(filename_from_url(url));
+        }
+        let client = self.client()?;
+        let head = apply_request_options(client.head(url), request).send().await.and_then(|r| r.error_for_status());
+        let resp = match head {
+            Ok(resp) => resp,
+            Err(e) => {
+                tracing::debug!(%url, error = %e, "HEAD refusé, nom résolu par GET");
+                apply_request_options(client.get(url), request)
+                    .header(RANGE, "bytes=0-0")
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .context("Résoudre le nom de fichier")?
+            }
+        };
+        let from_header = resp
             .headers()
-            .get(ACCEPT_RANGES)
+            .get(CONTENT_DISPOSITION)
             .and_then(|v| v.to_str().ok())
-            .map(|v| v.eq_ignore_ascii_case("bytes"))
-            .unwrap_or(false);
+            .and_then(filename_from_content_disposition);
+        let name = from_header.or_else(|| filename_from_url(resp.url().as_str())).or_else(|| filename_from_url(url));
+        tracing::debug!(%url, final_url = %resp.url(), ?name, "Nom de fichier résolu");
+        Ok(name)
+    }
+
+    /// Vérification préalable, avant toute préallocation: l'espace libre doit couvrir ce qui
+    /// reste à allouer plus la marge. Un `<nom>.partial` déjà présent (reprise) est déduit.
+    fn check_space_for(&self, task: &DownloadTask) -> Result<()> {
+        let Ok(available) = self.space_guard.available(&task.output) else {
+            return Ok(());
+        };
+        let allocated = std::fs::metadata(Manifest::partial_path_for(&task.output)).map(|m| m.len()).unwrap_or(0);
+        let needed = task.total_size.saturating_sub(allocated).saturating_add(self.space_guard.min_free);
+        if available < needed {
+            tracing::error!(available, needed, min_free = self.space_guard.min_free, "Espace disque insuffisant avant préallocation");
+            return Err(DiskFull { path: task.output.clone(), available, needed }.into());
+        }
+        Ok(())
+    }
+
+    /// Interroge les sources (URL principale puis miroirs) et retourne les métadonnées de la
+    /// première qui répond, avec les sources utilisables: un miroir injoignable, de taille
+    /// différente ou sans le même support des plages est écarté. Chaque source est remplacée
+    /// par son URL finale, pour ne pas suivre la chaîne de redirections à chaque segment.
+    async fn detect_remote_metadata(&self, client: &Client, task: &DownloadTask) -> Result<(RemoteMetadata, Vec<String>)> {
+        let sources = task.sources();
+        if task.total_size > 0 {
+            // On connaît déjà la taille; supposer support des ranges et laisser le serveur répondre 206
+            let final_url = task.url.clone();
+            return Ok((RemoteMetadata { total_size: task.total_size, supports_range: true, etag: None, final_url }, sources));
+        }
+
+        let mut reference: Option<RemoteMetadata> = None;
+        let mut usable = Vec::new();
+        let mut last_error = None;
+        for url in sources {
+            let metadata = match probe_metadata(client, &url, &task.request).await {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    tracing::warn!(%url, error = %e, "Source injoignable, ignorée");
+                    last_error = Some(e);
+                    continue;
+                }
+            };
+            match &reference {
+                None => reference = Some(metadata.clone()),
+                Some(r) if r.total_size == metadata.total_size && r.supports_range == metadata.supports_range => {}
+                Some(r) => {
+                    tracing::warn!(%url, size = metadata.total_size, expected = r.total_size, "Miroir écarté: contenu différent de la source principale");
+                    continue;
+                }
+            }
+            if metadata.final_url != url {
+                tracing::info!(%url, final_url = %metadata.final_url, "Source redirigée");
+            }
+            usable.push(metadata.final_url);
+        }
+        match reference {
+            Some(metadata) => Ok((metadata, usable)),
+            None => Err(last_error.unwrap_or_else(|| anyhow::anyhow!("Aucune source à interroger"))),
+        }
+    }
 
-        Ok((len, supports_range))
+    /// Petit fichier HTTP sans téléchargement commencé à reprendre (manifeste ou octets conservés)
+    fn is_small(&self, task: &DownloadTask, kept: u64) -> bool {
+        let threshold = self.small_file_threshold.unwrap_or(task.chunk_size);
+        task.total_size > 0
+            && task.total_size <= threshold
+            && kept == 0
+            && Protocol::of(&task.url) == Protocol::Http
+            && !Manifest::path_for(&task.output).exists()
+    }
+
+    /// Requête unique d'un petit fichier; un échec transitoire la relance depuis le début, sur la
+    /// source suivante s'il y a des miroirs
+    async fn download_small(&self, client: &Client, task: &DownloadTask, mirrors: &MirrorSet, reporter: &ProgressReporter, limiters: &[RateLimiter], origin: Instant) -> Result<ChunkTiming> {
+        let mut attempt = 1;
+        let mut source = 0;
+        loop {
+            let before = reporter.total();
+            match self.download_whole(client, task, mirrors.url(source), reporter, limiters, origin).await {
+                Ok(timing) => {
+                    mirrors.report_success(source);
+                    return Ok(ChunkTiming { source, ..timing });
+                }
+                Err(e) if attempt < self.retry.max_attempts && is_retryable(&e) => {
+                    mirrors.report_failure(source);
+                    reporter.rewind(before);
+                    let delay = self.retry.delay(attempt);
+                    tracing::warn!(attempt, ?delay, error = %e, "Échec transitoire du petit fichier, nouvelle tentative");
+                    tokio::time::sleep(delay).await;
+                    source = mirrors.failover(source);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
     }
 
-    /// Télécharge tout le fichier en une seule requête (fallback sans `Range`).
-    async fn download_whole(&self, client: &Client, task: &DownloadTask) -> Result<()> {
-        let resp = client.get(&task.url).send().await.context("GET complet")?;
+    async fn download_whole(&self, client: &Client, task: &DownloadTask, url: &str, reporter: &ProgressReporter, limiters: &[RateLimiter], origin: Instant) -> Result<ChunkTiming> {
+        let started = origin.elapsed();
+        let _permit = self.hosts.acquire(url).await;
+        let encoding = if self.decompress { COMPRESSED_ENCODINGS } else { IDENTITY_ENCODING };
+        let request = apply_request_options_with_encoding(client.get(url), &task.request, encoding);
+        let resp = within_stall(self.stall_timeout, async { request.send().await.context("GET complet") }).await?;
         let mut resp = resp.error_for_status().context("GET status")?;
 
-        // Écrire directement dans le fichier final
-        let mut file = OpenOptions::new().create(true).truncate(true).write(true).open(&task.output).await?;
+        // Écrire au fil de l'eau dans `<nom>.partial`
+        let partial = Manifest::partial_path_for(&task.output);
+        let mut file = OpenOptions::new().create(true).truncate(true).write(true).open(&partial).await?;
         let mut downloaded: u64 = 0;
-        while let Some(chunk) = resp.chunk().await.context("Lire chunk HTTP")? {
+        let mut space = self.space_guard.tracker();
+        while let Some(chunk) = within_stall(self.stall_timeout, async { resp.chunk().await.context("Lire chunk HTTP") }).await? {
+            acquire_all(limiters, chunk.len() as u64).await;
+            space.before_write(&partial, chunk.len() as u64)?;
             downloaded += chunk.len() as u64;
+            // Flux sans taille (ou décompressé) dépassant la limite: le début reçu est supprimé
+            if let Some(limit) = task.max_size
+                && downloaded > limit
+            {
+                tracing::error!(url = %task.url, downloaded, limit, "Fichier trop volumineux, téléchargement interrompu");
+                drop(file);
+                let _ = tokio::fs::remove_file(&partial).await;
+                return Err(FileTooLarge { url: task.url.clone(), size: downloaded, limit }.into());
+            }
             file.write_all(&chunk).await?;
+            reporter.record(0, downloaded, chunk.len() as u64);
             tracing::debug!(downloaded, "Téléchargement plein en cours");
         }
         file.flush().await?;
-        Ok(())
+        Ok(ChunkTiming { index: 0, source: 0, bytes: downloaded, started, finished: origin.elapsed() })
     }
+}
 
-    /// Nettoie les fichiers temporaires après fusion réussie
-    fn cleanup_temp_files(&self, chunks: &[Chunk]) -> io::Result<()> {
-        tracing::info!("Nettoyage des fichiers temporaires");
-        
-        for chunk in chunks {
-            // Supprimer le fichier part
-            if chunk.path.exists() {
-                std::fs::remove_file(&chunk.path)?;
-                tracing::debug!(path = %chunk.path.display(), "Fichier part supprimé");
-            }
-            
-            // Supprimer le marqueur .done
-            let marker = done_marker_path(&chunk.path);
-            if marker.exists() {
-                std::fs::remove_file(&marker)?;
-                tracing::debug!(path = %marker.display(), "Marqueur .done supprimé");
-            }
+/// Contrôle final avant vérification des empreintes: `<nom>.partial` fait `expected` octets, les
+/// octets reçus (ou marqués complets dans le manifeste) aussi, et les segments couvrent tout le
+/// fichier (`covered`). Sinon le fichier partiel et son manifeste sont supprimés et
+/// `TruncatedDownload` est rendu: mieux vaut aucun fichier qu'un fichier corrompu présenté
+/// comme terminé.
+fn check_complete(output: &Path, expected: u64, received: u64, covered: bool) -> Result<()> {
+    let partial = Manifest::partial_path_for(output);
+    let on_disk = std::fs::metadata(&partial).map(|m| m.len()).unwrap_or(0);
+    if on_disk == expected && received == expected && covered {
+        return Ok(());
+    }
+    let actual = if on_disk != expected { on_disk } else { received };
+    tracing::error!(file = %partial.display(), expected, on_disk, received, covered, "Taille finale incorrecte, fichier supprimé");
+    let _ = std::fs::remove_file(&partial);
+    let _ = std::fs::remove_file(Manifest::path_for(output));
+    Err(TruncatedDownload { path: output.to_path_buf(), expected, actual }.into())
+}
+
+/// Renomme `<nom>.partial` en `output` (un fichier existant est remplacé): dernière étape,
+/// après les vérifications de taille et d'empreinte
+async fn finalize_output(output: &Path) -> Result<()> {
+    let partial = Manifest::partial_path_for(output);
+    tokio::fs::rename(&partial, output)
+        .await
+        .with_context(|| format!("Renommer {} en {}", partial.display(), output.display()))?;
+    tracing::debug!(file = %output.display(), "Fichier finalisé");
+    Ok(())
+}
+
+/// Lit `[chunking] max_concurrency` (8 par défaut, 0 ignoré)
+pub fn max_concurrency_from_config(config: &super::AppConfig) -> usize {
+    config
+        .chunking
+        .as_ref()
+        .and_then(|c| c.max_concurrency)
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_MAX_CONCURRENCY)
+}
+
+/// Lit `[chunking] small_file_threshold_kib`: `None` (taille d'un segment) sans réglage,
+/// `Some(0)` (requête unique désactivée) pour 0
+pub fn small_file_threshold_from_config(config: &super::AppConfig) -> Option<u64> {
+    config.chunking.as_ref().and_then(|c| c.small_file_threshold_kib).map(|kib| kib * 1024)
+}
+
+/// Lit `[compression] decompress` (désactivé par défaut)
+pub fn decompression_from_config(config: &super::AppConfig) -> bool {
+    config.compression.as_ref().and_then(|c| c.decompress).unwrap_or(false)
+}
+
+/// Effectue une requête HEAD pour récupérer `content-length`, `accept-ranges` et `etag`.
+/// Métadonnées d'une source selon son protocole (HEAD en HTTP, `SIZE` en FTP, `stat` en SFTP et en local)
+async fn probe_metadata(client: &Client, url: &str, request: &RequestOptions) -> Result<RemoteMetadata> {
+    let total_size = match Protocol::of(url) {
+        Protocol::Http => return head_metadata(client, url, request).await,
+        Protocol::Ftp => ftp::probe(url, request).await?,
+        Protocol::Sftp => sftp::probe(url, request).await?,
+        Protocol::Local => local::probe(url).await?,
+    };
+    // FTP (`REST`), SFTP et sources locales (`seek`) permettent toujours la lecture à un offset
+    Ok(RemoteMetadata { total_size, supports_range: true, etag: None, final_url: url.to_string() })
+}
+
+/// Métadonnées HTTP par HEAD. Plusieurs CDN refusent HEAD (403/405) ou omettent
+/// `Content-Length`: on se rabat alors sur un GET `Range: bytes=0-0`.
+async fn head_metadata(client: &Client, url: &str, request: &RequestOptions) -> Result<RemoteMetadata> {
+    let resp = apply_request_options(client.head(url), request)
+        .send()
+        .await
+        .context("HEAD request")?;
+    if let Err(e) = resp.error_for_status_ref() {
+        tracing::debug!(%url, error = %e, "HEAD refusé, sondage par GET");
+        return range_probe_metadata(client, url, request).await;
+    }
+    match content_length(resp.headers()) {
+        Some(len) => {
+            let supports_range = resp
+                .headers()
+                .get(ACCEPT_RANGES)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.eq_ignore_ascii_case("bytes"))
+                .unwrap_or(false);
+            Ok(RemoteMetadata { total_size: len, supports_range, etag: etag(resp.headers()), final_url: resp.url().to_string() })
+        }
+        None => {
+            tracing::debug!(%url, "HEAD sans content-length, sondage par GET");
+            range_probe_metadata(client, url, request).await
         }
-        
-        tracing::info!("Nettoyage terminé");
-        Ok(())
     }
 }
 
-/// Télécharge un segment unique via HTTP `Range` et l'écrit dans le fichier part.
-async fn download_chunk(client: &Client, url: &str, chunk: &Chunk) -> Result<()> {
-    tracing::info!(index = chunk.index, start = chunk.start, end = chunk.end, "Téléchargement du segment");
-    let range_header = format!("bytes={}-{}", chunk.start, chunk.end);
-    let resp = client
-        .get(url)
-        .header(RANGE, range_header)
+/// Sonde par `GET` d'un seul octet: un 206 donne la taille via `Content-Range` et prouve le
+/// support des plages; un 200 (plage ignorée) donne `Content-Length`, et le corps est abandonné
+/// sans être lu. Une taille absente vaut 0: le gestionnaire télécharge alors en flux.
+async fn range_probe_metadata(client: &Client, url: &str, request: &RequestOptions) -> Result<RemoteMetadata> {
+    let resp = apply_request_options(client.get(url), request)
+        .header(RANGE, "bytes=0-0")
         .send()
         .await
-        .context("GET range")?;
+        .context("GET de sondage")?
+        .error_for_status()
+        .context("GET de sondage status")?;
+    let final_url = resp.url().to_string();
+    let metadata = if resp.status() == StatusCode::PARTIAL_CONTENT {
+        // `bytes 0-0/*`: plages acceptées mais taille inconnue, laissée à 0 (flux)
+        let total_size = resp
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.rsplit_once('/'))
+            .and_then(|(_, total)| total.trim().parse::<u64>().ok())
+            .unwrap_or(0);
+        RemoteMetadata { total_size, supports_range: total_size > 0, etag: etag(resp.headers()), final_url }
+    } else {
+        // Pas de content-length (transfert `chunked`): taille inconnue, téléchargement en flux
+        let total_size = content_length(resp.headers()).unwrap_or(0);
+        RemoteMetadata { total_size, supports_range: false, etag: etag(resp.headers()), final_url }
+    };
+    // La réponse est abandonnée ici: la connexion est fermée sans lire le corps
+    drop(resp);
+    Ok(metadata)
+}
+
+fn content_length(headers: &HeaderMap) -> Option<u64> {
+    headers.get(CONTENT_LENGTH).and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<u64>().ok())
+}
+
+fn etag(headers: &HeaderMap) -> Option<String> {
+    headers.get(ETAG).and_then(|v| v.to_str().ok()).map(str::to_string)
+}
+
+/// Métadonnées d'une source HTTP(S) (taille, plages, URL finale), avec le même repli GET
+/// que le gestionnaire
+pub async fn probe_http(client: &Client, url: &str, request: &RequestOptions) -> Result<RemoteMetadata> {
+    head_metadata(client, url, request).await
+}
+
+/// Télécharge un segment unique via HTTP `Range` et l'écrit à son offset dans `ctx.output`.
+///
+/// Retourne le temps mesuré pour ce segment, relatif à `ctx.origin`. L'espace libre est contrôlé
+/// via `ctx.guard` et le débit via `ctx.limiters` pendant l'écriture; en cas d'échec, le
+/// manifeste conserve l'offset atteint sans marquer le segment complet.
+/// Chaque bloc écrit est signalé à `ctx.reporter`. Une erreur transitoire relance le segment
+/// depuis l'offset déjà écrit, selon `ctx.retry`; avec des miroirs, toute erreur imputable à la
+/// source bascule d'abord sur la source suivante, sans délai.
+async fn download_chunk(ctx: &ChunkContext<'_>, chunk: &Chunk) -> Result<ChunkTiming> {
+    let started = ctx.origin.elapsed();
+    let mut downloaded = chunk.downloaded;
+    let mut received: u64 = 0;
+    let mut attempt = 1;
+    let mut source = ctx.mirrors.pick(chunk.index);
+    loop {
+        // Un serveur saturé a demandé d'attendre: ne pas le solliciter avant l'échéance
+        ctx.busy.wait().await;
+        let url = ctx.mirrors.url(source);
+        // Place réservée auprès de l'hôte pour la durée de la tentative, rendue avant tout délai
+        let permit = ctx.hosts.acquire(url).await;
+        let fetched = fetch_chunk_range(ctx, chunk, source, &mut downloaded, &mut received).await;
+        drop(permit);
+        match fetched {
+            Ok(()) => {
+                ctx.mirrors.report_success(source);
+                break;
+            }
+            Err(e) if attempt < ctx.retry.max_attempts && (is_retryable(&e) || (ctx.mirrors.len() > 1 && is_source_error(&e))) => {
+                ctx.mirrors.report_failure(source);
+                let next = ctx.mirrors.failover(source);
+                // Un premier tour des sources se fait sans attendre; au‑delà, délai exponentiel,
+                // ou celui imposé par `Retry-After` pour toute la tâche
+                if next == source || attempt >= ctx.mirrors.len() as u32 {
+                    let retry_after = e.chain().find_map(|c| c.downcast_ref::<Throttled>()).and_then(|t| t.retry_after);
+                    let delay = match retry_after {
+                        Some(wait) => {
+                            ctx.busy.hold(wait);
+                            ctx.reporter.throttled(chunk.index, wait);
+                            wait
+                        }
+                        None => ctx.retry.delay(attempt),
+                    };
+                    tracing::warn!(index = chunk.index, attempt, downloaded, ?delay, error = %e, "Échec transitoire du segment, nouvelle tentative");
+                    tokio::time::sleep(delay).await;
+                } else {
+                    tracing::warn!(index = chunk.index, attempt, downloaded, from = %url, to = %ctx.mirrors.url(next), error = %e, "Échec du segment, bascule sur le miroir suivant");
+                }
+                source = next;
+                attempt += 1;
+            }
+            Err(e) => {
+                if is_source_error(&e) {
+                    ctx.mirrors.report_failure(source);
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    // Marquer ce segment comme complété dans le manifeste
+    ctx.manifest.record(chunk.index, downloaded)?;
+    let timing = ChunkTiming { index: chunk.index, source, bytes: received, started, finished: ctx.origin.elapsed() };
+    tracing::info!(index = chunk.index, bytes = received, attempts = attempt, duration = ?timing.duration(), "Segment complété");
+    Ok(timing)
+}
 
-    // 206 attendu pour une réponse de plage partielle
-    let mut resp = resp.error_for_status().context("GET status")?;
+/// Une tentative: demande `[start + downloaded..=end]` à la source `source` et écrit le flux à
+/// son offset. Le délai d'ouverture de la plage alimente le suivi de santé des sources.
+///
+/// `downloaded` et `received` avancent à chaque bloc écrit, y compris en cas d'échec, afin que la
+/// tentative suivante reprenne exactement où celle‑ci s'est arrêtée. La borne `end` est relue
+/// auprès de `ctx.scheduler`: si une autre connexion a repris la fin de la plage, l'écriture
+/// s'arrête à la nouvelle borne et la connexion est abandonnée.
+async fn fetch_chunk_range(ctx: &ChunkContext<'_>, chunk: &Chunk, source: usize, downloaded: &mut u64, received: &mut u64) -> Result<()> {
+    let ChunkContext { client, mirrors, request, output, guard, reporter, limiters, manifest, scheduler, stall, .. } = *ctx;
+    let end = scheduler.end_of(chunk.index).unwrap_or(chunk.end);
+    tracing::info!(index = chunk.index, start = chunk.start, end, resume_from = *downloaded, "Téléchargement du segment");
+    let opened = Instant::now();
+    let mut stream = within_stall(stall, open_range(client, request, mirrors.url(source), chunk.start + *downloaded, end)).await?;
+    mirrors.report_latency(source, opened.elapsed());
 
-    // Ouvrir le fichier part et écrire en flux
-    let part_path = &chunk.path;
-    let mut file = OpenOptions::new().write(true).truncate(true).open(part_path).await?;
+    // Écriture positionnée dans le fichier final pré‑alloué, après les octets déjà reçus
+    let mut file = OpenOptions::new().write(true).open(output).await?;
+    file.seek(SeekFrom::Start(chunk.start + *downloaded)).await?;
 
-    let mut downloaded: u64 = 0;
-    while let Some(bytes) = resp.chunk().await.context("Lire chunk HTTP")? {
-        downloaded += bytes.len() as u64;
-        file.write_all(&bytes).await?;
-        tracing::debug!(index = chunk.index, downloaded, "Flux reçu pour le segment");
+    let mut since_persist: u64 = 0;
+    let mut space = guard.tracker();
+    let result: Result<()> = async {
+        while let Some(bytes) = within_stall(stall, async { stream.next().await.transpose() }).await? {
+            let len = scheduler.claim(chunk.index, *downloaded, bytes.len() as u64);
+            acquire_all(limiters, len).await;
+            space.before_write(output, len)?;
+            file.write_all(&bytes[..len as usize]).await?;
+            *downloaded += len;
+            *received += len;
+            since_persist += len;
+            reporter.record(chunk.index, *downloaded, len);
+            tracing::debug!(index = chunk.index, downloaded = *downloaded, "Flux reçu pour le segment");
+            // Persister l'offset régulièrement: une pause ne perd que le dernier intervalle
+            if since_persist >= OFFSET_PERSIST_EVERY {
+                file.flush().await?;
+                manifest.record(chunk.index, *downloaded)?;
+                since_persist = 0;
+            }
+            if len < bytes.len() as u64 {
+                tracing::debug!(index = chunk.index, downloaded = *downloaded, "Fin de plage reprise par une autre connexion");
+                break;
+            }
+        }
+        // Un flux clos avant la fin de la plage (connexion coupée) est une erreur transitoire
+        let chunk_len = scheduler.end_of(chunk.index).unwrap_or(end) - chunk.start + 1;
+        if *downloaded < chunk_len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("segment incomplet: {} / {} octets", *downloaded, chunk_len),
+            )
+            .into());
+        }
+        Ok(())
     }
+    .await;
     file.flush().await?;
-    // Marquer ce segment comme complété
-    let marker = done_marker_path(part_path);
-    let _ = OpenOptions::new().create(true).write(true).open(marker).await?;
-    tracing::info!(index = chunk.index, "Segment complété");
+    if result.is_err() {
+        // Conserver ce qui a été écrit pour la prochaine reprise
+        let _ = manifest.record(chunk.index, *downloaded);
+    }
+    result
+}
+
+/// Ouvre les octets `[offset, end]` de `url`, quel que soit son protocole
+async fn open_range(client: &Client, request: &RequestOptions, url: &str, offset: u64, end: u64) -> Result<ByteStream> {
+    let len = end + 1 - offset;
+    match Protocol::of(url) {
+        Protocol::Ftp => ftp::open_range(url, request, offset, len).await,
+        Protocol::Sftp => sftp::open_range(url, request, offset, len).await,
+        Protocol::Local => local::open_range(url, offset, len).await,
+        Protocol::Http => {
+            let resp = apply_request_options(client.get(url), request)
+                .header(RANGE, format!("bytes={offset}-{end}"))
+                .send()
+                .await
+                .context("GET range")?;
+            if matches!(resp.status(), StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE) {
+                let retry_after = resp
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| parse_retry_after(v, SystemTime::now()));
+                return Err(Throttled { status: resp.status().as_u16(), retry_after }.into());
+            }
+
+            // 206 attendu pour une réponse de plage partielle
+            let resp = resp.error_for_status().context("GET status")?;
+            if offset > 0 && resp.status() != StatusCode::PARTIAL_CONTENT {
+                let message = format!("Reprise impossible: le serveur a ignoré la plage demandée ({})", resp.status());
+                return Err(DownloadError::new(DownloadErrorKind::RangeUnsupported, message).into());
+            }
+            Ok(Box::pin(resp.bytes_stream().map(|block| block.context("Lire chunk HTTP"))))
+        }
+    }
+}
+
+/// Lit entièrement les octets `[offset, end]` de `url`
+async fn read_range(client: &Client, request: &RequestOptions, url: &str, offset: u64, end: u64) -> Result<Vec<u8>> {
+    let len = (end + 1 - offset) as usize;
+    let mut stream = open_range(client, request, url, offset, end).await?;
+    let mut bytes = Vec::with_capacity(len);
+    while bytes.len() < len {
+        match stream.next().await.transpose()? {
+            Some(block) => bytes.extend_from_slice(&block),
+            None => anyhow::bail!("Plage incomplète: {} / {} octets", bytes.len(), len),
+        }
+    }
+    bytes.truncate(len);
+    Ok(bytes)
+}
+
+/// Contrôle croisé d'un téléchargement multi‑sources: pour chaque source ayant servi, le début de
+/// l'un de ses segments est relu depuis une autre source et comparé au fichier reconstitué.
+/// Une divergence remet ce segment à zéro dans le manifeste et fait échouer le téléchargement.
+async fn verify_across_sources(ctx: &ChunkContext<'_>, per_chunk: &[ChunkTiming]) -> Result<()> {
+    let layout = ctx.manifest.snapshot();
+    let mut checked = HashSet::new();
+    let mut file = tokio::fs::File::open(ctx.output).await.context("Ouvrir le fichier pour vérification")?;
+    for timing in per_chunk {
+        if !checked.insert(timing.source) {
+            continue;
+        }
+        let Some(state) = layout.chunks.iter().find(|c| c.index == timing.index) else {
+            continue;
+        };
+        let end = state.end.min(state.start + VERIFY_SAMPLE - 1);
+        let others = (1..ctx.mirrors.len()).map(|offset| (timing.source + offset) % ctx.mirrors.len());
+        let mut remote = None;
+        for other in others {
+            let url = ctx.mirrors.url(other);
+            match read_range(ctx.client, ctx.request, url, state.start, end).await {
+                Ok(bytes) => {
+                    remote = Some((url, bytes));
+                    break;
+                }
+                Err(e) => tracing::debug!(%url, error = %e, "Source indisponible pour la vérification"),
+            }
+        }
+        let Some((other_url, remote)) = remote else {
+            tracing::warn!(source = %ctx.mirrors.url(timing.source), "Vérification impossible: aucune autre source disponible");
+            continue;
+        };
+
+        let mut local = vec![0u8; (end - state.start + 1) as usize];
+        file.seek(SeekFrom::Start(state.start)).await?;
+        file.read_exact(&mut local).await?;
+        if remote != local {
+            ctx.manifest.record(state.index, 0)?;
+            let message = format!(
+                "Vérification multi‑sources échouée: octets {}-{} différents entre {} et {}",
+                state.start,
+                end,
+                ctx.mirrors.url(timing.source),
+                other_url
+            );
+            return Err(DownloadError::new(DownloadErrorKind::Verification, message).into());
+        }
+        tracing::debug!(index = state.index, source = %ctx.mirrors.url(timing.source), against = %other_url, "Source vérifiée");
+    }
     Ok(())
 }
 
-fn done_marker_path(part_path: &Path) -> PathBuf {
-    let name = part_path.file_name().unwrap_or_else(|| std::ffi::OsStr::new("part"));
-    let mut s = name.to_string_lossy().to_string();
-    s.push_str(".done");
-    part_path.with_file_name(s)
+/// Premier fichier d'empreinte exploitable parmi `<url>.sha256` puis `<url>.md5`
+async fn fetch_sidecar(client: &Client, url: &str, request: &RequestOptions) -> Option<ExpectedHash> {
+    let base = url::Url::parse(url).ok()?;
+    let segment = base.path_segments()?.next_back()?;
+    let file_name = percent_encoding::percent_decode_str(segment).decode_utf8_lossy().to_string();
+    for (extension, algorithm) in checksum::SIDECAR_EXTENSIONS {
+        // Suffixe ajouté au chemin: la requête éventuelle (`?token=`) reste valable
+        let mut sidecar = base.clone();
+        sidecar.set_path(&format!("{}.{extension}", base.path()));
+        let resp = match apply_request_options(client.get(sidecar.as_str()), request).send().await {
+            Ok(resp) if resp.status().is_success() => resp,
+            Ok(resp) => {
+                tracing::debug!(url = %sidecar, status = %resp.status(), "Fichier d'empreinte absent");
+                continue;
+            }
+            Err(e) => {
+                tracing::debug!(url = %sidecar, error = %e, "Fichier d'empreinte injoignable");
+                continue;
+            }
+        };
+        if resp.content_length().is_some_and(|len| len > checksum::MAX_SIDECAR_LEN as u64) {
+            continue;
+        }
+        let Ok(body) = resp.bytes().await else {
+            continue;
+        };
+        if body.len() > checksum::MAX_SIDECAR_LEN {
+            continue;
+        }
+        if let Some(expected) = checksum::parse_sidecar(&String::from_utf8_lossy(&body), &file_name, algorithm) {
+            return Some(expected);
+        }
+    }
+    None
+}
+
+/// Ajoute les en‑têtes, cookies et identifiants de la tâche à une requête, en demandant le
+/// contenu non compressé (`Accept-Encoding: identity`): tailles et plages portent alors sur les
+/// octets du fichier lui‑même
+fn apply_request_options(builder: RequestBuilder, options: &RequestOptions) -> RequestBuilder {
+    apply_request_options_with_encoding(builder, options, IDENTITY_ENCODING)
 }
 
+/// Comme `apply_request_options` avec `accept_encoding`, sauf si la tâche fixe elle‑même
+/// `Accept-Encoding`
+fn apply_request_options_with_encoding(mut builder: RequestBuilder, options: &RequestOptions, accept_encoding: &str) -> RequestBuilder {
+    if !options.headers.keys().any(|name| name.eq_ignore_ascii_case(ACCEPT_ENCODING.as_str())) {
+        builder = builder.header(ACCEPT_ENCODING, accept_encoding);
+    }
+    for (name, value) in &options.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    if let Some(cookies) = &options.cookies {
+        builder = builder.header(COOKIE, cookies.as_str());
+    }
+    if let Some(auth) = &options.basic_auth {
+        builder = builder.basic_auth(&auth.username, auth.password.as_ref());
+    }
+    if let Some(token) = &options.bearer_token {
+        builder = builder.bearer_auth(token);
+    }
+    builder
+}
 
 #[cfg(test)]
 mod tests {
@@ -244,19 +1228,22 @@ mod tests {
     use hyper::header::{CONTENT_LENGTH as H_CONTENT_LENGTH, CONTENT_RANGE as H_CONTENT_RANGE, RANGE as H_RANGE, ACCEPT_RANGES as H_ACCEPT_RANGES};
     use hyper::StatusCode;
     use tokio::sync::oneshot;
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use sha2::{Digest, Sha256};
+    use crate::downloader::checksum::HashAlgorithm;
 
     #[test]
     fn test_prepare_creates_chunks_and_files() {
         let dir = tempdir().unwrap();
         let output_path = dir.path().join("file.bin");
 
-        let task = DownloadTask {
-            url: "https://example.com/file".to_string(),
-            output: output_path.clone(),
-            total_size: 3_000,
-            chunk_size: 1_000,
-            num_chunks: 0,
-        };
+        let task = DownloadTask::builder("https://example.com/file")
+            .with_output(output_path.clone())
+            .with_total_size(3_000)
+            .with_chunk_size(1_000)
+            .build()
+            .unwrap();
 
         let manager = DownloadManager::new();
         let chunks = manager.prepare(&task).unwrap();
@@ -264,13 +1251,13 @@ mod tests {
         // Should create 3 chunks
         assert_eq!(chunks.len(), 3);
 
-        // Each chunk file should exist
-        for chunk in &chunks {
-            assert!(chunk.path.exists(), "Chunk file {:?} should exist", chunk.path);
-            let metadata = fs::metadata(&chunk.path).unwrap();
-            // Each file should be preallocated to chunk_size
-            assert_eq!(metadata.len(), task.chunk_size);
-        }
+        // The `.partial` file is preallocated once, next to a fresh manifest; no part files are created
+        assert_eq!(fs::metadata(Manifest::partial_path_for(&output_path)).unwrap().len(), task.total_size);
+        assert!(!output_path.exists());
+        let manifest = Manifest::load(&output_path).expect("manifest should be written");
+        assert_eq!(manifest.chunks.len(), 3);
+        assert!(manifest.chunks.iter().all(|c| c.downloaded == 0));
+        assert!(!output_path.with_extension("part0").exists());
 
         // Check chunk boundaries
         assert_eq!(chunks[0].start, 0);
@@ -284,29 +1271,42 @@ mod tests {
         let dir = tempdir().unwrap();
         let output_path = dir.path().join("file.bin");
 
-        let task = DownloadTask {
-            url: "https://example.com/file".to_string(),
-            output: output_path.clone(),
-            total_size: 2_000,
-            chunk_size: 1_000,
-            num_chunks: 0,
-        };
-
-        // Pre-create one of the chunk files manually
-        let precreated_file = output_path.with_extension("part0");
-        fs::File::create(&precreated_file).unwrap();
+        let task = DownloadTask::builder("https://example.com/file")
+            .with_output(output_path.clone())
+            .with_total_size(2_000)
+            .with_chunk_size(1_000)
+            .build()
+            .unwrap();
 
+        // Interrupted download: `.partial` already preallocated, chunk 1 half written
         let manager = DownloadManager::new();
+        let partial = Manifest::partial_path_for(&output_path);
+        manager.prepare(&task).unwrap();
+        fs::write(&partial, vec![7u8; 2_000]).unwrap();
+        let mut manifest = Manifest::load(&output_path).unwrap();
+        manifest.chunks[1].downloaded = 500;
+        manifest.save(&output_path).unwrap();
+
         let chunks = manager.prepare(&task).unwrap();
 
-        // All chunk files should exist
-        for chunk in &chunks {
-            assert!(chunk.path.exists());
-        }
+        // The existing output should not be overwritten and the offset is restored
+        assert_eq!(fs::read(&partial).unwrap(), vec![7u8; 2_000]);
+        assert_eq!(chunks[0].downloaded, 0);
+        assert_eq!(chunks[1].downloaded, 500);
+
+        // A size mismatch means foreign content: reallocate and start a fresh manifest
+        fs::write(&partial, b"other").unwrap();
+        let chunks = manager.prepare(&task).unwrap();
+        assert_eq!(fs::metadata(&partial).unwrap().len(), 2_000);
+        assert_eq!(chunks[1].downloaded, 0);
+        assert_eq!(Manifest::load(&output_path).unwrap().chunks[1].downloaded, 0);
 
-        // The precreated file should not be overwritten, size should be 0
-        let metadata = fs::metadata(&precreated_file).unwrap();
-        assert_eq!(metadata.len(), 0);
+        // A different resource (URL) never reuses the manifest
+        manager.prepare(&task).unwrap();
+        let other = DownloadTask { url: "https://example.com/other".to_string(), ..task };
+        assert_eq!(Manifest::load(&output_path).unwrap().url, "https://example.com/file");
+        manager.prepare(&other).unwrap();
+        assert_eq!(Manifest::load(&output_path).unwrap().url, "https://example.com/other");
     }
 
     #[test]
@@ -314,13 +1314,11 @@ mod tests {
         let dir = tempdir().unwrap();
         let output_path = dir.path().join("file.bin");
 
-        let task = DownloadTask {
-            url: "https://example.com/file".to_string(),
-            output: output_path.clone(),
-            total_size: 0,
-            chunk_size: 1_000,
-            num_chunks: 0,
-        };
+        let task = DownloadTask::builder("https://example.com/file")
+            .with_output(output_path.clone())
+            .with_chunk_size(1_000)
+            .build()
+            .unwrap();
 
         let manager = DownloadManager::new();
         let chunks = manager.prepare(&task).unwrap();
@@ -330,16 +1328,36 @@ mod tests {
     }
 
     async fn start_test_server(data: Vec<u8>, support_range: bool) -> (String, oneshot::Sender<()>) {
+        let (url, tx, _) = start_logging_test_server(data, support_range, 0).await;
+        (url, tx)
+    }
+
+    type RequestLog = Arc<Mutex<Vec<hyper::HeaderMap>>>;
+
+    /// Comme `start_test_server`, en journalisant les en‑têtes de chaque requête;
+    /// les `failures` premières requêtes GET reçoivent un 503.
+    async fn start_logging_test_server(data: Vec<u8>, support_range: bool, failures: usize) -> (String, oneshot::Sender<()>, RequestLog) {
+        let requests: RequestLog = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let failures = Arc::new(AtomicUsize::new(failures));
         let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
         let addr = listener.local_addr().unwrap();
         let (tx, rx) = oneshot::channel::<()>();
 
         let make_svc = make_service_fn(move |_| {
             let data = data.clone();
+            let log = log.clone();
+            let failures = failures.clone();
             async move {
                 Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
                     let data = data.clone();
+                    let fail = req.method() == Method::GET
+                        && failures.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)).is_ok();
+                    log.lock().unwrap().push(req.headers().clone());
                     async move {
+                        if fail {
+                            return Ok::<_, hyper::Error>(Response::builder().status(StatusCode::SERVICE_UNAVAILABLE).body(Body::empty()).unwrap());
+                        }
                         match (req.method().clone(), req.uri().path()) {
                             (m, "/file") if m == Method::HEAD => {
                                 let mut builder = Response::builder()
@@ -394,7 +1412,19 @@ mod tests {
             let _ = server.with_graceful_shutdown(async move { let _ = rx.await; }).await;
         });
 
-        (format!("http://{}:{}/file", addr.ip(), addr.port()), tx)
+        (format!("http://{}:{}/file", addr.ip(), addr.port()), tx, requests)
+    }
+
+    /// En‑têtes `Range` journalisés, triés
+    fn logged_ranges(log: &RequestLog) -> Vec<String> {
+        let mut ranges: Vec<String> = log
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|h| h.get(H_RANGE).and_then(|v| v.to_str().ok()).map(str::to_string))
+            .collect();
+        ranges.sort();
+        ranges
     }
 
     #[tokio::test]
@@ -406,13 +1436,11 @@ mod tests {
         let dir = tempdir().unwrap();
         let output_path = dir.path().join("out_ranged.bin");
 
-        let task = DownloadTask {
-            url,
-            output: output_path.clone(),
-            total_size: 0, // sera détecté via HEAD
-            chunk_size: 4096, // 4 KiB
-            num_chunks: 0,
-        };
+        let task = DownloadTask::builder(url)
+            .with_output(output_path.clone())
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
 
         let manager = DownloadManager::new();
         manager.start(task).await.expect("ranged download should succeed");
@@ -427,20 +1455,1217 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_start_whole_download_no_range() {
-        let data = b"Hello full body without range".to_vec();
-        let (url, shutdown) = start_test_server(data.clone(), false).await;
-
+    async fn test_collision_policies_for_existing_output() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(12 * 1024).collect();
+        let (url, shutdown) = start_test_server(data.clone(), true).await;
         let dir = tempdir().unwrap();
-        let output_path = dir.path().join("out_whole.bin");
+        let output_path = dir.path().join("clip.mp4");
+        let task = || DownloadTask::builder(url.clone()).with_output(output_path.clone()).with_chunk_size(4096).build().unwrap();
 
-        let task = DownloadTask {
-            url,
-            output: output_path.clone(),
-            total_size: 0, // via HEAD
-            chunk_size: 4096,
-            num_chunks: 0,
-        };
+        // Renommage par défaut: le fichier étranger reste intact
+        fs::write(&output_path, b"mine").unwrap();
+        DownloadManager::new().start(task()).await.unwrap();
+        assert_eq!(fs::read(&output_path).unwrap(), b"mine");
+        assert_eq!(fs::read(dir.path().join("clip (1).mp4")).unwrap(), data);
+
+        let stats = DownloadManager::new().with_collision_policy(CollisionPolicy::Skip).start(task()).await.unwrap();
+        assert!(stats.skipped && stats.per_chunk.is_empty());
+        assert_eq!(fs::read(&output_path).unwrap(), b"mine");
+
+        // Reprise: les 5000 premiers octets présents ne sont pas redemandés
+        fs::write(&output_path, &data[..5000]).unwrap();
+        let stats = DownloadManager::new().with_collision_policy(CollisionPolicy::Resume).start(task()).await.unwrap();
+        assert_eq!(stats.total_bytes(), (data.len() - 5000) as u64);
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+
+        fs::remove_file(Manifest::path_for(&output_path)).unwrap();
+        fs::write(&output_path, b"old").unwrap();
+        DownloadManager::new().with_collision_policy(CollisionPolicy::Overwrite).start(task()).await.unwrap();
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_output_appears_only_once_verified() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(12 * 1024).collect();
+        let (url, shutdown) = start_test_server(data.clone(), true).await;
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("movie.mkv");
+        let partial = Manifest::partial_path_for(&output_path);
+        let task = |hash: &str| {
+            DownloadTask::builder(url.clone())
+                .with_output(output_path.clone())
+                .with_chunk_size(4096)
+                .with_hash(ExpectedHash::new(HashAlgorithm::Sha256, hash))
+                .build()
+                .unwrap()
+        };
+        let good: String = Sha256::digest(&data).iter().map(|b| format!("{b:02x}")).collect();
+
+        // Empreinte refusée: rien sous le nom final, l'original remplacé reste intact
+        fs::write(&output_path, b"old").unwrap();
+        let overwrite = || DownloadManager::new().with_collision_policy(CollisionPolicy::Overwrite);
+        let err = overwrite().start(task(&"0".repeat(64))).await.unwrap_err();
+        assert_eq!(DownloadErrorKind::of(&err), DownloadErrorKind::Verification);
+        assert_eq!(fs::read(&output_path).unwrap(), b"old");
+        assert_eq!(fs::read(&partial).unwrap(), data);
+
+        overwrite().start(task(&good)).await.unwrap();
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+        assert!(!partial.exists());
+
+        // Manifeste présent à côté du nom final (fichier terminé, ou commencé avant `.partial`):
+        // repris depuis son contenu, sans rien retélécharger
+        let stats = DownloadManager::new().start(task(&good)).await.unwrap();
+        assert_eq!(stats.total_bytes(), 0);
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_start_resumes_partial_chunk_from_offset() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(8 * 1024).collect();
+        let (url, shutdown, ranges) = start_logging_test_server(data.clone(), true, 0).await;
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("out_resume.bin");
+        let task = DownloadTask::builder(url)
+            .with_output(output_path.clone())
+            .with_total_size(data.len() as u64)
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
+
+        // Pause simulée: 1000 octets du segment 0 écrits, le reste du fichier partiel est à zéro
+        let mut partial = data[..1000].to_vec();
+        partial.resize(data.len(), 0);
+        fs::write(Manifest::partial_path_for(&output_path), &partial).unwrap();
+        let mut manifest = Manifest::new(&task, &task.create_chunks(), None);
+        manifest.chunks[0].downloaded = 1000;
+        manifest.save(&output_path).unwrap();
+
+        let manager = DownloadManager::new();
+        let stats = manager.start(task).await.expect("resumed download should succeed");
+
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+        assert!(!Manifest::partial_path_for(&output_path).exists());
+        assert_eq!(logged_ranges(&ranges), vec!["bytes=1000-4095".to_string(), "bytes=4096-8191".to_string()]);
+        // Seuls les octets manquants ont transité
+        assert_eq!(stats.per_chunk[0].bytes, 4096 - 1000);
+        let summary = Manifest::load(&output_path).unwrap().summary();
+        assert_eq!((summary.done_chunks, summary.total_chunks), (2, 2));
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_start_retries_transient_chunk_failures() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(8 * 1024).collect();
+        let (url, shutdown, ranges) = start_logging_test_server(data.clone(), true, 3).await;
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("out_retry.bin");
+        let task = DownloadTask::builder(url)
+            .with_output(output_path.clone())
+            .with_total_size(data.len() as u64)
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
+        let policy = RetryPolicy {
+            max_attempts: 4,
+            initial_backoff: std::time::Duration::from_millis(10),
+            max_backoff: std::time::Duration::from_millis(50),
+            jitter: 0.0,
+        };
+
+        let manager = DownloadManager::new().with_retry_policy(policy.clone());
+        manager.start(task.clone()).await.expect("transient 503s should be retried");
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+        // 3 échecs puis 2 succès
+        assert_eq!(logged_ranges(&ranges).len(), 5);
+        let _ = shutdown.send(());
+
+        // Sans nouvelle tentative, le premier 503 fait échouer le téléchargement
+        let (url, shutdown, _) = start_logging_test_server(data.clone(), true, 1).await;
+        let task = DownloadTask { url, output: dir.path().join("out_no_retry.bin"), ..task };
+        let manager = DownloadManager::new().with_retry_policy(RetryPolicy { max_attempts: 1, ..policy });
+        assert!(manager.start(task).await.is_err());
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_start_adaptive_chunking_merges_small_segments() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(64 * 1024).collect();
+        let (url, shutdown, log) = start_logging_test_server(data.clone(), true, 0).await;
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("out_adaptive.bin");
+        let task = DownloadTask::builder(url)
+            .with_output(output_path.clone())
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
+        let sizing = AdaptiveChunking { min_chunk_size: 8 * 1024, max_chunk_size: 16 * 1024 };
+        DownloadManager::new().with_adaptive_chunking(Some(sizing)).start(task).await.expect("adaptive download should succeed");
+
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+        // Segments vierges fusionnés: moins de requêtes, chacune dans les bornes configurées
+        let ranges = logged_ranges(&log);
+        assert!(ranges.len() < 16, "{ranges:?}");
+        for range in &ranges {
+            let (start, end) = range.strip_prefix("bytes=").unwrap().split_once('-').unwrap();
+            let len = end.parse::<u64>().unwrap() - start.parse::<u64>().unwrap() + 1;
+            assert!((8 * 1024..=16 * 1024).contains(&len), "{range}");
+        }
+        let summary = Manifest::load(&output_path).unwrap().summary();
+        assert_eq!(summary.done_chunks, summary.total_chunks);
+        assert_eq!(summary.downloaded, data.len() as u64);
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_start_small_file_single_request() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(3 * 1024).collect();
+        // Premier GET en 503: la requête unique est relancée
+        let (url, shutdown, log) = start_logging_test_server(data.clone(), true, 1).await;
+        let dir = tempdir().unwrap();
+        let policy = RetryPolicy { max_attempts: 2, initial_backoff: std::time::Duration::from_millis(10), max_backoff: std::time::Duration::from_millis(10), jitter: 0.0 };
+
+        // Sous la taille d'un segment: un GET sans Range, ni manifeste ni `.partial` restant
+        let output = dir.path().join("small.bin");
+        let task = DownloadTask::builder(url.clone()).with_output(&output).with_chunk_size(4096).build().unwrap();
+        let stats = DownloadManager::new().with_retry_policy(policy.clone()).start(task).await.expect("small download should succeed");
+        assert_eq!(fs::read(&output).unwrap(), data);
+        assert_eq!(stats.total_bytes(), data.len() as u64);
+        assert!(logged_ranges(&log).is_empty());
+        assert!(!Manifest::path_for(&output).exists());
+        assert!(!Manifest::partial_path_for(&output).exists());
+
+        // Seuil à 0: chemin segmenté habituel
+        let output = dir.path().join("chunked.bin");
+        let task = DownloadTask::builder(url).with_output(&output).with_chunk_size(2048).build().unwrap();
+        DownloadManager::new().with_small_file_threshold(Some(0)).start(task).await.expect("chunked download should succeed");
+        assert_eq!(fs::read(&output).unwrap(), data);
+        assert_eq!(logged_ranges(&log), ["bytes=0-2047", "bytes=2048-3071"]);
+        assert!(Manifest::path_for(&output).exists());
+
+        let _ = shutdown.send(());
+    }
+
+    /// Serveur à plages dont le premier GET envoie ses en‑têtes et 512 octets puis se tait sans
+    /// fermer la connexion; rend aussi le nombre de GET reçus
+    async fn start_stalling_server(data: Vec<u8>) -> (String, oneshot::Sender<()>, Arc<AtomicUsize>) {
+        let gets = Arc::new(AtomicUsize::new(0));
+        let counter = gets.clone();
+        // Émetteurs du corps bloqué, gardés ouverts jusqu'à l'arrêt du serveur
+        let stalled: Arc<Mutex<Vec<hyper::body::Sender>>> = Arc::default();
+        let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = oneshot::channel::<()>();
+
+        let make_svc = make_service_fn(move |_| {
+            let (data, counter, stalled) = (data.clone(), counter.clone(), stalled.clone());
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+                    let (data, counter, stalled) = (data.clone(), counter.clone(), stalled.clone());
+                    async move {
+                        if req.method() == Method::HEAD {
+                            return Ok::<_, hyper::Error>(Response::builder()
+                                .header(H_CONTENT_LENGTH, data.len())
+                                .header(H_ACCEPT_RANGES, "bytes")
+                                .body(Body::empty())
+                                .unwrap());
+                        }
+                        let range = req.headers()[H_RANGE].to_str().unwrap().strip_prefix("bytes=").unwrap().to_string();
+                        let (start, end) = range.split_once('-').unwrap();
+                        let (start, end): (usize, usize) = (start.parse().unwrap(), end.parse().unwrap());
+                        let slice = data[start..=end].to_vec();
+                        let response = Response::builder()
+                            .status(StatusCode::PARTIAL_CONTENT)
+                            .header(H_CONTENT_LENGTH, slice.len())
+                            .header(H_CONTENT_RANGE, format!("bytes {start}-{end}/{}", data.len()));
+                        if counter.fetch_add(1, Ordering::SeqCst) > 0 {
+                            return Ok(response.body(Body::from(slice)).unwrap());
+                        }
+                        let (mut sender, body) = Body::channel();
+                        sender.send_data(slice[..512].to_vec().into()).await.unwrap();
+                        stalled.lock().unwrap().push(sender);
+                        Ok(response.body(body).unwrap())
+                    }
+                }))
+            }
+        });
+
+        let server = Server::from_tcp(listener).unwrap().serve(make_svc);
+        tokio::spawn(async move {
+            let _ = server.with_graceful_shutdown(async move { let _ = rx.await; }).await;
+        });
+
+        (format!("http://{}:{}/file", addr.ip(), addr.port()), tx, gets)
+    }
+
+    #[tokio::test]
+    async fn test_start_retries_stalled_chunk() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(8 * 1024).collect();
+        let (url, shutdown, gets) = start_stalling_server(data.clone()).await;
+        let dir = tempdir().unwrap();
+        let output = dir.path().join("stalled.bin");
+        let task = DownloadTask::builder(url).with_output(&output).with_chunk_size(4096).build().unwrap();
+        let policy = RetryPolicy { max_attempts: 3, initial_backoff: std::time::Duration::from_millis(10), max_backoff: std::time::Duration::from_millis(10), jitter: 0.0 };
+        let manager = DownloadManager::new().with_retry_policy(policy).with_stall_timeout(Some(Duration::from_millis(200)));
+
+        let stats = tokio::time::timeout(Duration::from_secs(10), manager.start(task)).await.expect("stalled chunk must not hang").expect("stalled chunk should be retried");
+        assert_eq!(fs::read(&output).unwrap(), data);
+        // Deux segments, dont un relancé depuis l'offset atteint avant le blocage
+        assert_eq!(gets.load(Ordering::SeqCst), 3);
+        assert_eq!(stats.total_bytes(), data.len() as u64);
+        let _ = shutdown.send(());
+
+        let config: crate::downloader::AppConfig = toml::from_str("[retry]\nstall_timeout_secs = 0").unwrap();
+        assert_eq!(crate::downloader::stall_timeout_from_config(&config), None);
+        assert_eq!(crate::downloader::stall_timeout_from_config(&Default::default()), Some(DEFAULT_STALL_TIMEOUT));
+    }
+
+    /// Serveur à plages qui répond après 30 ms; rend le nombre maximal de GET traités en même temps
+    async fn start_counting_server(data: Vec<u8>) -> (String, oneshot::Sender<()>, Arc<AtomicUsize>) {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let seen = peak.clone();
+        let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = oneshot::channel::<()>();
+
+        let make_svc = make_service_fn(move |_| {
+            let (data, in_flight, peak) = (data.clone(), in_flight.clone(), peak.clone());
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+                    let (data, in_flight, peak) = (data.clone(), in_flight.clone(), peak.clone());
+                    async move {
+                        let Some(range) = req.headers().get(H_RANGE) else {
+                            return Ok::<_, hyper::Error>(Response::builder()
+                                .header(H_CONTENT_LENGTH, data.len())
+                                .header(H_ACCEPT_RANGES, "bytes")
+                                .body(Body::empty())
+                                .unwrap());
+                        };
+                        let (start, end) = range.to_str().unwrap().strip_prefix("bytes=").unwrap().split_once('-').unwrap();
+                        let (start, end): (usize, usize) = (start.parse().unwrap(), end.parse().unwrap());
+                        peak.fetch_max(in_flight.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(30)).await;
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        Ok(Response::builder()
+                            .status(StatusCode::PARTIAL_CONTENT)
+                            .header(H_CONTENT_LENGTH, end - start + 1)
+                            .header(H_CONTENT_RANGE, format!("bytes {start}-{end}/{}", data.len()))
+                            .body(Body::from(data[start..=end].to_vec()))
+                            .unwrap())
+                    }
+                }))
+            }
+        });
+
+        let server = Server::from_tcp(listener).unwrap().serve(make_svc);
+        tokio::spawn(async move {
+            let _ = server.with_graceful_shutdown(async move { let _ = rx.await; }).await;
+        });
+
+        (format!("http://{}:{}/file", addr.ip(), addr.port()), tx, seen)
+    }
+
+    #[tokio::test]
+    async fn test_connections_per_host_shared_across_downloads() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(16 * 1024).collect();
+        let (url, shutdown, peak) = start_counting_server(data.clone()).await;
+        let dir = tempdir().unwrap();
+        let hosts = crate::downloader::HostLimiter::new(2);
+        let manager = DownloadManager::new().with_host_limiter(hosts.clone()).with_max_concurrency(4);
+
+        // Deux téléchargements de 4 segments chacun, 4 connexions autorisées par téléchargement
+        let task = |name: &str| DownloadTask::builder(url.clone()).with_output(dir.path().join(name)).with_chunk_size(4096).build().unwrap();
+        let (first, second) = tokio::join!(manager.start(task("first.bin")), manager.start(task("second.bin")));
+        first.unwrap();
+        second.unwrap();
+        assert_eq!(fs::read(dir.path().join("second.bin")).unwrap(), data);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_start_fails_over_to_mirror() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(16 * 1024).collect();
+        // Source principale: HEAD correct mais chaque GET échoue (503)
+        let (primary, shutdown_primary, _) = start_logging_test_server(data.clone(), true, usize::MAX).await;
+        let (mirror, shutdown_mirror, mirror_log) = start_logging_test_server(data.clone(), true, 0).await;
+        // Miroir servant un autre contenu (taille différente): écarté dès le HEAD
+        let (foreign, shutdown_foreign, foreign_log) = start_logging_test_server(data[..100].to_vec(), true, 0).await;
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("out_mirrors.bin");
+        let task = DownloadTask::builder(primary)
+            .with_output(output_path.clone())
+            .with_chunk_size(4096)
+            .with_mirrors(vec![foreign, mirror])
+            .build()
+            .unwrap();
+        let policy = RetryPolicy {
+            max_attempts: 4,
+            initial_backoff: std::time::Duration::from_millis(10),
+            max_backoff: std::time::Duration::from_millis(50),
+            jitter: 0.0,
+        };
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let health = MirrorHealth::new();
+        let manager = DownloadManager::new().with_retry_policy(policy).with_mirror_health(health.clone()).with_progress(tx);
+        let stats = manager.start(task).await.expect("mirror should take over");
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+        // Sources retenues: principale (0) puis miroir valide (1); tous les segments finissent sur le miroir
+        assert!(stats.per_chunk.iter().all(|t| t.source == 1));
+        assert_eq!(logged_ranges(&mirror_log).len(), 4);
+        assert!(logged_ranges(&foreign_log).is_empty());
+        // Santé des sources jointe à la progression: la principale n'a fait qu'échouer
+        let last = std::iter::from_fn(|| rx.try_recv().ok()).last().unwrap();
+        let [primary, mirror] = &last.mirrors[..] else { panic!("{:?}", last.mirrors) };
+        assert!(primary.failures > 0 && primary.failures == primary.requests);
+        assert!(mirror.latency.is_some() && mirror.failures == 0);
+        let urls: Vec<String> = last.mirrors.iter().map(|m| m.url.clone()).collect();
+        assert_eq!(health.stats(&urls)[1].requests, 4);
+
+        let _ = shutdown_primary.send(());
+        let _ = shutdown_mirror.send(());
+        let _ = shutdown_foreign.send(());
+    }
+
+    #[tokio::test]
+    async fn test_start_detects_divergent_mirror() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(16 * 1024).collect();
+        let other: Vec<u8> = data.iter().rev().copied().collect();
+        let (primary, shutdown_primary) = start_test_server(data, true).await;
+        let (mirror, shutdown_mirror) = start_test_server(other, true).await;
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("out_divergent.bin");
+        let task = DownloadTask::builder(primary)
+            .with_output(output_path.clone())
+            .with_chunk_size(4096)
+            .with_mirrors(vec![mirror])
+            .build()
+            .unwrap();
+
+        // Segments répartis entre les deux sources: le contrôle croisé détecte l'écart
+        let err = DownloadManager::new().start(task).await.unwrap_err();
+        assert!(err.to_string().contains("Vérification"), "{err}");
+        let manifest = Manifest::load(&output_path).unwrap();
+        assert!(!manifest.chunks[0].is_done());
+
+        let _ = shutdown_primary.send(());
+        let _ = shutdown_mirror.send(());
+    }
+
+    #[tokio::test]
+    async fn test_start_sends_task_headers_on_every_request() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(8 * 1024).collect();
+        let (url, shutdown, log) = start_logging_test_server(data.clone(), true, 0).await;
+
+        let dir = tempdir().unwrap();
+        let request = RequestOptions::default()
+            .with_header("Referer", "https://example.com/page")
+            .with_cookies("session=abc")
+            .with_basic_auth("user", Some("pass".to_string()));
+        let task = DownloadTask::builder(url)
+            .with_output(dir.path().join("out_headers.bin"))
+            .with_chunk_size(4096)
+            .with_request(request)
+            .build()
+            .unwrap();
+        DownloadManager::new().start(task).await.expect("download with headers should succeed");
+
+        let log = log.lock().unwrap();
+        assert_eq!(log.len(), 3);
+        for headers in log.iter() {
+            assert_eq!(headers.get("referer").unwrap(), "https://example.com/page");
+            assert_eq!(headers.get("cookie").unwrap(), "session=abc");
+            // base64("user:pass")
+            assert_eq!(headers.get("authorization").unwrap(), "Basic dXNlcjpwYXNz");
+            // Tailles et plages portent sur le contenu non compressé
+            assert_eq!(headers.get("accept-encoding").unwrap(), "identity");
+        }
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_start_sends_bearer_token() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(4 * 1024).collect();
+        let (url, shutdown, log) = start_logging_test_server(data.clone(), true, 0).await;
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("out_bearer.bin");
+        let task = DownloadTask::builder(url)
+            .with_output(output_path.clone())
+            .with_chunk_size(4096)
+            .with_request(RequestOptions::default().with_bearer_token("jeton-123"))
+            .build()
+            .unwrap();
+        DownloadManager::new().start(task).await.expect("download with bearer token should succeed");
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+
+        let log = log.lock().unwrap();
+        assert!(!log.is_empty());
+        for headers in log.iter() {
+            assert_eq!(headers.get("authorization").unwrap(), "Bearer jeton-123");
+        }
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_whole_download_decompresses_only_when_enabled() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write as _;
+        let plain: Vec<u8> = b"ligne de journal\n".repeat(512);
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&plain).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        // Serveur sans Range qui compresse dès que le client l'accepte
+        let encodings: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+        let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/app.log", listener.local_addr().unwrap());
+        let (plain_body, log) = (plain.clone(), encodings.clone());
+        let make_svc = make_service_fn(move |_| {
+            let (plain, gzipped, log) = (plain_body.clone(), gzipped.clone(), log.clone());
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+                    let accept = req.headers().get("accept-encoding").and_then(|v| v.to_str().ok()).unwrap_or("").to_string();
+                    log.lock().unwrap().push(accept.clone());
+                    let response = if req.method() == Method::HEAD {
+                        Response::builder().header(H_CONTENT_LENGTH, plain.len()).body(Body::empty())
+                    } else if accept.contains("gzip") {
+                        Response::builder().header("content-encoding", "gzip").body(Body::from(gzipped.clone()))
+                    } else {
+                        Response::builder().body(Body::from(plain.clone()))
+                    };
+                    async move { Ok::<_, hyper::Error>(response.unwrap()) }
+                }))
+            }
+        });
+        let server = Server::from_tcp(listener).unwrap().serve(make_svc);
+        let (shutdown, rx) = oneshot::channel::<()>();
+        tokio::spawn(server.with_graceful_shutdown(async { rx.await.ok(); }));
+
+        let dir = tempdir().unwrap();
+        for decompress in [false, true] {
+            let output = dir.path().join(format!("app-{decompress}.log"));
+            let task = DownloadTask::builder(url.clone()).with_output(output.clone()).build().unwrap();
+            DownloadManager::new().with_offline_mode(OfflineMode::new()).with_decompression(decompress).start(task).await.unwrap();
+            // Dans les deux cas le fichier contient le contenu décompressé, à la taille annoncée
+            assert_eq!(fs::read(&output).unwrap(), plain);
+        }
+        let encodings = encodings.lock().unwrap();
+        assert_eq!(*encodings, ["identity", "identity", "identity", "gzip, br, deflate"]);
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_task_concurrency_overrides_manager_default() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(16 * 1024).collect();
+        let (url, shutdown) = start_test_server(data.clone(), true).await;
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("sequential.bin");
+        let task = DownloadTask::builder(url)
+            .with_output(output_path.clone())
+            .with_chunk_size(4096)
+            .with_max_concurrency(Some(1))
+            .build()
+            .unwrap();
+
+        // Une seule connexion: chaque segment commence après la fin du précédent
+        let stats = DownloadManager::new().with_max_concurrency(8).start(task).await.unwrap();
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+        assert_eq!(stats.per_chunk.len(), 4);
+        assert!(stats.per_chunk.windows(2).all(|w| w[1].started >= w[0].finished), "{:?}", stats.per_chunk);
+
+        let config: crate::downloader::AppConfig = toml::from_str("[chunking]\nmax_concurrency = 3").unwrap();
+        assert_eq!(max_concurrency_from_config(&config), 3);
+        assert_eq!(max_concurrency_from_config(&Default::default()), DEFAULT_MAX_CONCURRENCY);
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_start_copies_file_and_data_urls() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(10 * 1024).collect();
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("source.bin");
+        fs::write(&source, &data).unwrap();
+        let url = url::Url::from_file_path(&source).unwrap().to_string();
+        let output_path = dir.path().join("copy.bin");
+        let task = DownloadTask::builder(url).with_output(output_path.clone()).with_chunk_size(4096).build().unwrap();
+
+        // Même pipeline qu'une source réseau: segments, progression, fichier final
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let stats = DownloadManager::new().with_progress(tx).start(task).await.unwrap();
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+        assert_eq!(stats.per_chunk.len(), 3);
+        let mut last = None;
+        while let Ok(event) = rx.try_recv() {
+            last = Some(event.total_downloaded);
+        }
+        assert_eq!(last, Some(data.len() as u64));
+
+        let output_path = dir.path().join("hello.txt");
+        let task = DownloadTask::builder("data:text/plain;base64,SGVsbG8gd29ybGQ=").with_output(output_path.clone()).build().unwrap();
+        DownloadManager::new().start(task).await.unwrap();
+        assert_eq!(fs::read(&output_path).unwrap(), b"Hello world");
+    }
+
+    #[tokio::test]
+    async fn test_start_honours_task_rate_limit() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(48 * 1024).collect();
+        let (url, shutdown) = start_test_server(data.clone(), true).await;
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("out_throttled.bin");
+        let task = DownloadTask::builder(url)
+            .with_output(output_path.clone())
+            .with_chunk_size(8 * 1024)
+            .with_rate_limit(Some(32 * 1024))
+            .build()
+            .unwrap();
+
+        // 32 KiB de rafale puis 16 KiB à 32 KiB/s: au moins ~0,5 s malgré 6 segments parallèles
+        let stats = DownloadManager::new().start(task).await.expect("throttled download should succeed");
+        assert!(stats.total_elapsed >= std::time::Duration::from_millis(400), "{:?}", stats.total_elapsed);
+        assert_eq!(fs::read(&output_path).unwrap(), data);
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_start_reports_chunk_timings() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(10 * 1024).collect();
+        let (url, shutdown) = start_test_server(data.clone(), true).await;
+
+        let dir = tempdir().unwrap();
+        let task = DownloadTask::builder(url)
+            .with_output(dir.path().join("out_timed.bin"))
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
+
+        let stats = DownloadManager::new().start(task).await.unwrap();
+
+        // 10 KiB / 4 KiB -> 3 segments, le dernier plus petit
+        assert_eq!(stats.per_chunk.len(), 3);
+        let indices: Vec<_> = stats.per_chunk.iter().map(|t| t.index).collect();
+        assert_eq!(indices, vec![0, 1, 2]);
+        assert_eq!(stats.per_chunk[0].bytes, 4096);
+        assert_eq!(stats.per_chunk[2].bytes, 2048);
+        assert_eq!(stats.total_bytes(), data.len() as u64);
+        for timing in &stats.per_chunk {
+            assert!(timing.finished >= timing.started);
+            assert!(timing.finished <= stats.total_elapsed);
+        }
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_start_emits_progress_events() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(10 * 1024).collect();
+        let (url, shutdown) = start_test_server(data.clone(), true).await;
+
+        let dir = tempdir().unwrap();
+        let task = DownloadTask::builder(url)
+            .with_output(dir.path().join("out_progress.bin"))
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        DownloadManager::new().with_progress(tx).start(task).await.unwrap();
+
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        assert!(!events.is_empty());
+        // Le total agrégé croît strictement et atteint la taille complète
+        assert!(events.windows(2).all(|w| w[1].total_downloaded > w[0].total_downloaded));
+        let last = events.last().unwrap();
+        assert_eq!(last.total_downloaded, data.len() as u64);
+        assert_eq!(last.total_size, data.len() as u64);
+        // Dernier événement de chaque segment = taille du segment
+        for (index, expected) in [(0usize, 4096u64), (1, 4096), (2, 2048)] {
+            let chunk_last = events.iter().rev().find(|e| e.chunk_index == index).unwrap();
+            assert_eq!(chunk_last.chunk_downloaded, expected);
+        }
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_start_publishes_lifecycle_events() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(10 * 1024).collect();
+        let (url, shutdown) = start_test_server(data.clone(), true).await;
+        let dir = tempdir().unwrap();
+        let good: String = Sha256::digest(&data).iter().map(|b| format!("{b:02x}")).collect();
+        let task = DownloadTask::builder(url)
+            .with_output(dir.path().join("events.bin"))
+            .with_chunk_size(4096)
+            .with_hash(ExpectedHash::new(HashAlgorithm::Sha256, &good))
+            .build()
+            .unwrap();
+        let manager = DownloadManager::new().with_offline_mode(OfflineMode::new());
+        let mut rx = manager.subscribe();
+        manager.start(task).await.unwrap();
+
+        let mut kinds = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            kinds.push(event.kind);
+        }
+        assert!(matches!(kinds.first(), Some(DownloadEventKind::Queued)));
+        assert!(matches!(&kinds[1], DownloadEventKind::Started { total_size: 10240, .. }));
+        let mut done: Vec<(usize, u64)> = kinds
+            .iter()
+            .filter_map(|k| match k {
+                DownloadEventKind::ChunkDone { index, bytes } => Some((*index, *bytes)),
+                _ => None,
+            })
+            .collect();
+        done.sort();
+        assert_eq!(done, [(0, 4096), (1, 4096), (2, 2048)]);
+        assert!(kinds.iter().any(|k| matches!(k, DownloadEventKind::Progress(p) if p.total_downloaded == 10240)));
+        // Fin de séquence: assemblage, empreinte vérifiée, fichier final en place
+        let tail: Vec<&str> = kinds
+            .iter()
+            .rev()
+            .take(3)
+            .map(|k| match k {
+                DownloadEventKind::Merging => "merging",
+                DownloadEventKind::Verified(Integrity::Verified(HashAlgorithm::Sha256)) => "verified",
+                DownloadEventKind::Completed(stats) if stats.total_bytes() == 10240 => "completed",
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(tail, ["completed", "verified", "merging"]);
+
+        // Échec: publié avec la cause
+        let unreachable = DownloadTask::builder("http://127.0.0.1:9/file").with_output(dir.path().join("never.bin")).build().unwrap();
+        assert!(manager.start(unreachable).await.is_err());
+        let last = std::iter::from_fn(|| rx.try_recv().ok()).last().unwrap();
+        assert!(matches!(last.kind, DownloadEventKind::Failed { kind: DownloadErrorKind::Network, message } if message.contains("métadonnées")));
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_managers_share_event_bus() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(4096).collect();
+        let (url, shutdown) = start_test_server(data, true).await;
+        let dir = tempdir().unwrap();
+        let bus = DownloadEvents::new();
+        let mut rx = bus.subscribe();
+        for name in ["first.bin", "second.bin"] {
+            let manager = DownloadManager::new().with_offline_mode(OfflineMode::new()).with_events(bus.clone());
+            let task = DownloadTask::builder(url.clone()).with_output(dir.path().join(name)).build().unwrap();
+            manager.start(task).await.unwrap();
+        }
+
+        // Un seul abonnement reçoit la fin des deux téléchargements, chacun avec sa sortie
+        let completed: Vec<PathBuf> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter(|e| matches!(e.kind, DownloadEventKind::Completed(_)))
+            .map(|e| e.output)
+            .collect();
+        assert_eq!(completed, [dir.path().join("first.bin"), dir.path().join("second.bin")]);
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_start_feeds_statistics() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(10 * 1024).collect();
+        let (url, shutdown) = start_test_server(data.clone(), true).await;
+        let dir = tempdir().unwrap();
+        let statistics = DownloadStatistics::new();
+        let queue = crate::downloader::DownloadQueue::new(1);
+        let manager = DownloadManager::new().with_offline_mode(OfflineMode::new()).with_statistics(statistics.clone());
+
+        let task = DownloadTask::builder(url).with_output(dir.path().join("out_stats.bin")).with_chunk_size(4096).build().unwrap();
+        manager.start(task).await.unwrap();
+        // Serveur injoignable: échec comptabilisé
+        let unreachable = DownloadTask::builder("http://127.0.0.1:9/file").with_output(dir.path().join("never.bin")).build().unwrap();
+        let err = manager.start(unreachable).await.unwrap_err();
+        assert_eq!(DownloadErrorKind::of(&err), DownloadErrorKind::Network);
+
+        let snapshot = statistics.snapshot(&queue);
+        assert_eq!((snapshot.downloaded_today, snapshot.failures), (data.len() as u64, 1));
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_start_offline_makes_no_network_call() {
+        // Écouteur non bloquant: toute connexion entrante serait visible via `accept`
+        let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let url = format!("http://{}/file", listener.local_addr().unwrap());
+
+        let dir = tempdir().unwrap();
+        let task = DownloadTask::builder(url)
+            .with_output(dir.path().join("out_offline.bin"))
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
+
+        let offline = OfflineMode::new();
+        offline.set_offline(true);
+        let err = DownloadManager::new().with_offline_mode(offline).start(task).await.unwrap_err();
+
+        assert!(err.to_string().contains("hors ligne"));
+        let accepted = listener.accept();
+        assert!(matches!(accepted, Err(ref e) if e.kind() == io::ErrorKind::WouldBlock));
+    }
+
+    /// Espace abondant pour les `plentiful` premières mesures, puis nul
+    struct ShrinkingSpace {
+        plentiful: usize,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl crate::downloader::space::SpaceSource for ShrinkingSpace {
+        fn available(&self, _path: &Path) -> io::Result<u64> {
+            let n = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(if n < self.plentiful { u64::MAX } else { 0 })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_start_aborts_when_space_drops_below_margin() {
+        let data: Vec<u8> = (0u8..=255).cycle().take(8 * 1024).collect();
+        let (url, shutdown) = start_test_server(data, true).await;
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("out_nospace.bin");
+        let task = DownloadTask::builder(url)
+            .with_output(output_path.clone())
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
+
+        // La vérification préalable passe, puis l'espace disparaît pendant l'écriture
+        let source = std::sync::Arc::new(ShrinkingSpace { plentiful: 1, calls: Default::default() });
+        let manager = DownloadManager::new().with_space_guard(SpaceGuard::new(source, 1024, 1));
+        let err = manager.start(task).await.unwrap_err();
+
+        assert!(err.to_string().contains("Espace disque insuffisant"), "{err}");
+        assert!(err.downcast_ref::<DiskFull>().is_some());
+        let manifest = Manifest::load(&output_path).unwrap();
+        assert!(manifest.chunks.iter().all(|c| !c.is_done()));
+
+        let _ = shutdown.send(());
+    }
+
+    /// Serveur de noms: `/download.php` redirige vers un nom parlant, `/attach.php` refuse HEAD
+    /// et annonce son nom par `Content-Disposition`, `/watch` ne porte aucun nom.
+    async fn start_naming_server() -> (String, oneshot::Sender<()>) {
+        let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = oneshot::channel::<()>();
+        let make_svc = make_service_fn(|_| async {
+            Ok::<_, hyper::Error>(service_fn(|req: Request<Body>| async move {
+                let response = match (req.method().clone(), req.uri().path()) {
+                    (_, "/download.php") => Response::builder()
+                        .status(StatusCode::FOUND)
+                        .header(hyper::header::LOCATION, "/files/Episode%2001.mp4")
+                        .body(Body::empty()),
+                    (m, "/attach.php") if m == Method::HEAD => Response::builder().status(StatusCode::METHOD_NOT_ALLOWED).body(Body::empty()),
+                    (_, "/attach.php") => Response::builder()
+                        .status(StatusCode::PARTIAL_CONTENT)
+                        .header(hyper::header::CONTENT_DISPOSITION, "attachment; filename=\"Saison 1.zip\"")
+                        .body(Body::from("x")),
+                    _ => Response::builder().status(StatusCode::OK).body(Body::empty()),
+                };
+                Ok::<_, hyper::Error>(response.unwrap())
+            }))
+        });
+        let server = Server::from_tcp(listener).unwrap().serve(make_svc);
+        tokio::spawn(async move {
+            let _ = server.with_graceful_shutdown(async move { let _ = rx.await; }).await;
+        });
+        (format!("http://{}", addr), tx)
+    }
+
+    #[tokio::test]
+    async fn test_resolve_filename_from_redirect_and_disposition() {
+        let (base, shutdown) = start_naming_server().await;
+        let manager = DownloadManager::new();
+        let request = RequestOptions::default();
+
+        let name = manager.resolve_filename(&format!("{base}/download.php?id=42"), &request).await.unwrap();
+        assert_eq!(name.as_deref(), Some("Episode 01.mp4"));
+        let name = manager.resolve_filename(&format!("{base}/attach.php"), &request).await.unwrap();
+        assert_eq!(name.as_deref(), Some("Saison 1.zip"));
+        assert_eq!(manager.resolve_filename(&format!("{base}/watch"), &request).await.unwrap(), None);
+
+        let _ = shutdown.send(());
+    }
+
+    /// Serveur refusant HEAD (403) mais servant les plages en GET, comme certains CDN.
+    /// `/redirect` renvoie (302) vers `/file`; le compteur rendu compte ces redirections.
+    /// `/busy` répond une fois 429 (`Retry-After: 1`) au premier segment demandé.
+    async fn start_headless_server(data: Vec<u8>) -> (String, oneshot::Sender<()>, Arc<AtomicUsize>) {
+        let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = oneshot::channel::<()>();
+        let redirects = Arc::new(AtomicUsize::new(0));
+        let counter = redirects.clone();
+        let throttled = Arc::new(AtomicBool::new(false));
+        let make_svc = make_service_fn(move |_| {
+            let data = data.clone();
+            let counter = counter.clone();
+            let throttled = throttled.clone();
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+                    let data = data.clone();
+                    let counter = counter.clone();
+                    let throttled = throttled.clone();
+                    async move {
+                        // Fichiers d'empreinte: SHA-256 exact pour `/file`, MD5 faux pour les autres
+                        let path = req.uri().path().to_string();
+                        if path == "/file.sha256" {
+                            let hex: String = Sha256::digest(&data).iter().map(|b| format!("{b:02x}")).collect();
+                            return Ok::<_, hyper::Error>(Response::new(Body::from(format!("{hex}  file\n"))));
+                        }
+                        if path.ends_with(".md5") {
+                            return Ok::<_, hyper::Error>(Response::new(Body::from("0".repeat(32))));
+                        }
+                        if path.ends_with(".sha256") {
+                            return Ok::<_, hyper::Error>(Response::builder().status(StatusCode::NOT_FOUND).body(Body::empty()).unwrap());
+                        }
+                        let segment = req.method() == Method::GET && req.headers().get(H_RANGE).is_some_and(|v| v != "bytes=0-0");
+                        if req.uri().path() == "/busy" && segment && !throttled.swap(true, Ordering::SeqCst) {
+                            return Ok::<_, hyper::Error>(Response::builder()
+                                .status(StatusCode::TOO_MANY_REQUESTS)
+                                .header(hyper::header::RETRY_AFTER, "1")
+                                .body(Body::empty())
+                                .unwrap());
+                        }
+                        if req.uri().path() == "/redirect" {
+                            counter.fetch_add(1, Ordering::SeqCst);
+                            return Ok::<_, hyper::Error>(Response::builder()
+                                .status(StatusCode::FOUND)
+                                .header(hyper::header::LOCATION, "/file?token=signed")
+                                .body(Body::empty())
+                                .unwrap());
+                        }
+                        if req.method() == Method::HEAD {
+                            return Ok::<_, hyper::Error>(Response::builder().status(StatusCode::FORBIDDEN).body(Body::empty()).unwrap());
+                        }
+                        let range = req.headers().get(H_RANGE).and_then(|v| v.to_str().ok()).and_then(|v| v.strip_prefix("bytes="));
+                        let Some((start, end)) = range.and_then(|r| r.split_once('-')) else {
+                            return Ok(Response::new(Body::from(data)));
+                        };
+                        let start: usize = start.parse().unwrap();
+                        let end = end.parse::<usize>().unwrap_or(data.len() - 1).min(data.len() - 1);
+                        Ok(Response::builder()
+                            .status(StatusCode::PARTIAL_CONTENT)
+                            .header(H_CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, data.len()))
+                            .body(Body::from(data[start..=end].to_vec()))
+                            .unwrap())
+                    }
+                }))
+            }
+        });
+        let server = Server::from_tcp(listener).unwrap().serve(make_svc);
+        tokio::spawn(async move {
+            let _ = server.with_graceful_shutdown(async move { let _ = rx.await; }).await;
+        });
+        (format!("http://{}/file", addr), tx, redirects)
+    }
+
+    #[tokio::test]
+    async fn test_probe_falls_back_to_range_get_when_head_blocked() {
+        let data: Vec<u8> = (0..50_000u32).map(|i| (i % 251) as u8).collect();
+        let (url, shutdown, _) = start_headless_server(data.clone()).await;
+        let client = Client::new();
+
+        let metadata = probe_metadata(&client, &url, &RequestOptions::default()).await.unwrap();
+        assert_eq!((metadata.total_size, metadata.supports_range), (50_000, true));
+
+        // Taille inconnue au départ: le téléchargement segmenté passe malgré le 403 sur HEAD
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("headless.bin");
+        let task = DownloadTask::builder(url)
+            .with_output(output_path.clone())
+            .with_chunk_size(8192)
+            .build()
+            .unwrap();
+        let stats = DownloadManager::new().start(task).await.unwrap();
+        assert!(stats.per_chunk.len() > 1);
+        assert_eq!(tokio::fs::read(&output_path).await.unwrap(), data);
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_chunks_target_final_url_after_redirect() {
+        let data: Vec<u8> = (0..40_000u32).map(|i| (i % 199) as u8).collect();
+        let (file_url, shutdown, redirects) = start_headless_server(data.clone()).await;
+        let url = file_url.replace("/file", "/redirect");
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("redirected.bin");
+        let task = DownloadTask::builder(url)
+            .with_output(output_path.clone())
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
+        let stats = DownloadManager::new().start(task).await.unwrap();
+        assert_eq!(tokio::fs::read(&output_path).await.unwrap(), data);
+        assert_eq!(stats.final_url, Some(format!("{file_url}?token=signed")));
+        // HEAD refusé puis GET de sondage: seules ces deux requêtes passent par la redirection
+        assert!(stats.per_chunk.len() > 2);
+        assert_eq!(redirects.load(Ordering::SeqCst), 2);
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_sidecar_checksum_marks_verified_or_corrupt() {
+        let data: Vec<u8> = (0..30_000u32).map(|i| (i % 211) as u8).collect();
+        let (file_url, shutdown, _) = start_headless_server(data.clone()).await;
+        let dir = tempdir().unwrap();
+        let manager = DownloadManager::new().with_sidecar_checksums(true);
+
+        let task = DownloadTask::builder(file_url.clone()).with_output(dir.path().join("good.bin")).with_chunk_size(8192).build().unwrap();
+        let stats = manager.start(task).await.unwrap();
+        assert_eq!(stats.integrity, Some(Integrity::Verified(HashAlgorithm::Sha256)));
+
+        // `.sha256` absent, `.md5` différent: le fichier est gardé mais signalé corrompu
+        let output = dir.path().join("bad.bin");
+        let task = DownloadTask::builder(file_url.replace("/file", "/redirect")).with_output(output.clone()).build().unwrap();
+        let stats = manager.start(task).await.unwrap();
+        assert_eq!(stats.integrity, Some(Integrity::Corrupt(HashAlgorithm::Md5)));
+        assert_eq!(tokio::fs::read(&output).await.unwrap(), data);
+
+        // Désactivé par défaut
+        let task = DownloadTask::builder(file_url).with_output(dir.path().join("plain.bin")).build().unwrap();
+        assert_eq!(DownloadManager::new().start(task).await.unwrap().integrity, None);
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_pauses_task_and_reports_throttling() {
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i % 97) as u8).collect();
+        let (file_url, shutdown, _) = start_headless_server(data.clone()).await;
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("busy.bin");
+        let task = DownloadTask::builder(file_url.replace("/file", "/busy"))
+            .with_output(output_path.clone())
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        // Délai exponentiel quasi nul: seule l'attente demandée par le serveur compte
+        let retry = RetryPolicy { max_attempts: 3, initial_backoff: std::time::Duration::from_millis(1), max_backoff: std::time::Duration::from_millis(1), jitter: 0.0 };
+        let manager = DownloadManager::new().with_retry_policy(retry).with_progress(tx);
+        let started = Instant::now();
+        manager.start(task).await.unwrap();
+        drop(manager);
+        assert!(started.elapsed() >= std::time::Duration::from_secs(1));
+        assert_eq!(tokio::fs::read(&output_path).await.unwrap(), data);
+
+        let mut waits = Vec::new();
+        while let Some(event) = rx.recv().await {
+            waits.extend(event.throttled);
+        }
+        assert_eq!(waits, vec![std::time::Duration::from_secs(1)]);
+
+        let _ = shutdown.send(());
+    }
+
+    /// Serveur sans `Content-Length`: le corps est envoyé en plusieurs morceaux (`chunked`).
+    /// `announced` est la taille que HEAD annonce, éventuellement fausse.
+    async fn start_streaming_server(parts: Vec<Vec<u8>>, announced: Option<u64>) -> (String, oneshot::Sender<()>) {
+        let listener = StdTcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = oneshot::channel::<()>();
+        let make_svc = make_service_fn(move |_| {
+            let parts = parts.clone();
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+                    let parts = parts.clone();
+                    async move {
+                        if req.method() == Method::HEAD {
+                            let mut builder = Response::builder();
+                            if let Some(len) = announced {
+                                builder = builder.header(H_CONTENT_LENGTH, len);
+                            }
+                            return Ok::<_, hyper::Error>(builder.body(Body::empty()).unwrap());
+                        }
+                        let (mut sender, body) = Body::channel();
+                        tokio::spawn(async move {
+                            for part in parts {
+                                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                                if sender.send_data(part.into()).await.is_err() {
+                                    return;
+                                }
+                            }
+                        });
+                        Ok(Response::new(body))
+                    }
+                }))
+            }
+        });
+        let server = Server::from_tcp(listener).unwrap().serve(make_svc);
+        tokio::spawn(async move {
+            let _ = server.with_graceful_shutdown(async move { let _ = rx.await; }).await;
+        });
+        (format!("http://{}/stream", addr), tx)
+    }
+
+    #[tokio::test]
+    async fn test_streams_body_without_content_length() {
+        let parts: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i; 3000]).collect();
+        let expected = parts.concat();
+        let (url, shutdown) = start_streaming_server(parts, None).await;
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("stream.bin");
+        let task = DownloadTask::builder(url)
+            .with_output(output_path.clone())
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let manager = DownloadManager::new().with_progress(tx);
+        let stats = manager.start(task).await.unwrap();
+        drop(manager);
+        assert_eq!(stats.total_bytes(), 12_000);
+        assert_eq!(tokio::fs::read(&output_path).await.unwrap(), expected);
+
+        // Progression fondée sur les octets reçus, sans taille ni temps restant
+        let mut last = None;
+        while let Some(event) = rx.recv().await {
+            last = Some(event);
+        }
+        let last = last.unwrap();
+        assert_eq!((last.total_downloaded, last.total_size, last.eta), (12_000, 0, None));
+
+        let _ = shutdown.send(());
+    }
+
+    #[tokio::test]
+    async fn test_max_size_rejects_announced_and_streamed_overflow() {
+        let dir = tempdir().unwrap();
+        let task = |url: &str, name: &str| {
+            DownloadTask::builder(url)
+                .with_output(dir.path().join(name))
+                .with_chunk_size(4096)
+                .with_max_size(Some(10_000))
+                .build()
+                .unwrap()
+        };
+
+        // Taille annoncée au‑delà de la limite: refus avant la moindre écriture
+        let (url, shutdown) = start_test_server(vec![1u8; 12_000], true).await;
+        let err = DownloadManager::new().start(task(&url, "big.bin")).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<FileTooLarge>().map(|e| (e.size, e.limit)), Some((12_000, 10_000)));
+        assert!(!dir.path().join("big.bin.partial").exists() && !dir.path().join("big.bin").exists());
+        let _ = shutdown.send(());
+
+        // Flux sans taille: interrompu dès que les octets reçus dépassent la limite
+        let parts: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i; 3000]).collect();
+        let (url, shutdown) = start_streaming_server(parts, None).await;
+        let err = DownloadManager::new().start(task(&url, "stream.bin")).await.unwrap_err();
+        let too_large = err.downcast_ref::<FileTooLarge>().expect("erreur typée attendue");
+        assert!(too_large.size > 10_000 && too_large.size <= 12_000, "{}", too_large.size);
+        assert!(!dir.path().join("stream.bin.partial").exists());
+        let _ = shutdown.send(());
+
+        let config: crate::downloader::AppConfig = toml::from_str("[disk]\nmax_file_size_mb = 2").unwrap();
+        assert_eq!(crate::downloader::max_file_size_from_config(&config), Some(2 * 1024 * 1024));
+    }
+
+    #[tokio::test]
+    async fn test_truncated_body_is_rejected_and_removed() {
+        // HEAD annonce 20 000 octets, le corps n'en contient que 12 000
+        let parts: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i; 3000]).collect();
+        let (url, shutdown) = start_streaming_server(parts, Some(20_000)).await;
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("truncated.bin");
+        let task = DownloadTask::builder(url)
+            .with_output(output_path.clone())
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
+        let err = DownloadManager::new().start(task).await.unwrap_err();
+        let truncated = err.downcast_ref::<TruncatedDownload>().expect("erreur typée attendue");
+        assert_eq!((truncated.expected, truncated.actual), (20_000, 12_000));
+        assert!(!output_path.exists());
+        assert!(!Manifest::partial_path_for(&output_path).exists());
+
+        // Segments contigus mais l'un d'eux incomplet: refusé, même si le fichier a la bonne taille
+        let output = dir.path().join("gap.bin");
+        let partial = Manifest::partial_path_for(&output);
+        std::fs::write(&partial, vec![0u8; 100]).unwrap();
+        assert!(check_complete(&output, 100, 100, true).is_ok());
+        assert!(check_complete(&output, 100, 60, true).unwrap_err().downcast_ref::<TruncatedDownload>().is_some());
+        assert!(!partial.exists());
+
+        let _ = shutdown.send(());
+    }
+
+    /// Espace libre fixe
+    struct FixedSpace(u64);
+
+    impl crate::downloader::space::SpaceSource for FixedSpace {
+        fn available(&self, _path: &Path) -> io::Result<u64> {
+            Ok(self.0)
+        }
+    }
+
+    #[test]
+    fn test_space_precheck_deducts_existing_output() {
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("out_precheck.bin");
+        let task = DownloadTask::builder("https://example.com/big.bin")
+            .with_output(output_path.clone())
+            .with_total_size(10_000)
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
+        let manager = DownloadManager::new().with_space_guard(SpaceGuard::new(std::sync::Arc::new(FixedSpace(6_000)), 1_000, 1));
+
+        // Nouveau téléchargement: 10 000 + 1 000 octets requis, 6 000 disponibles
+        let err = manager.check_space_for(&task).unwrap_err();
+        let full = err.downcast_ref::<DiskFull>().unwrap();
+        assert_eq!((full.available, full.needed), (6_000, 11_000));
+
+        // Reprise: le fichier partiel déjà pré‑alloué n'est plus à compter
+        fs::write(Manifest::partial_path_for(&output_path), vec![0u8; 8_000]).unwrap();
+        manager.check_space_for(&task).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_start_whole_download_no_range() {
+        let data = b"Hello full body without range".to_vec();
+        let (url, shutdown) = start_test_server(data.clone(), false).await;
+
+        let dir = tempdir().unwrap();
+        let output_path = dir.path().join("out_whole.bin");
+
+        let task = DownloadTask::builder(url)
+            .with_output(output_path.clone())
+            .with_chunk_size(4096)
+            .build()
+            .unwrap();
 
         let manager = DownloadManager::new();
         manager.start(task).await.expect("whole download should succeed");
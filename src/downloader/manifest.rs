@@ -0,0 +1,226 @@
+//! Manifeste de reprise `<nom>.scrapes.json`, écrit à côté du fichier de sortie.
+//!
+//! URL, taille totale, découpage en segments, offset écrit par segment, ETag et horodatages
+//! tiennent dans ce seul fichier. La reprise après redémarrage s'appuie dessus, et la GUI
+//! peut afficher l'état exact des segments sans parcourir le dossier.
+//!
+//! Les octets eux‑mêmes sont écrits dans `<nom>.partial`, renommé en `<nom>` une fois le
+//! fichier complet et vérifié: le manifeste reste associé au nom final.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use super::types::{Chunk, DownloadTask};
+
+/// Suffixe ajouté au nom du fichier de sortie
+pub(crate) const MANIFEST_SUFFIX: &str = ".scrapes.json";
+/// Suffixe du fichier en cours d'écriture
+const PARTIAL_SUFFIX: &str = ".partial";
+
+/// État persistant d'un segment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkState {
+    pub index: usize,
+    pub start: u64,
+    pub end: u64,
+    /// Octets déjà écrits depuis `start`
+    pub downloaded: u64,
+}
+
+impl ChunkState {
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.downloaded >= self.len()
+    }
+}
+
+/// Résumé affichable de l'avancement d'un téléchargement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ManifestSummary {
+    pub done_chunks: usize,
+    pub total_chunks: usize,
+    pub downloaded: u64,
+    pub total_size: u64,
+}
+
+/// Contenu du manifeste `<nom>.scrapes.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub url: String,
+    pub total_size: u64,
+    pub chunk_size: u64,
+    /// ETag annoncé par le serveur; un changement invalide les données déjà écrites
+    pub etag: Option<String>,
+    /// Horodatages Unix (secondes)
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub chunks: Vec<ChunkState>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+impl Manifest {
+    /// Chemin du manifeste associé à `output` (`video.mp4` -> `video.mp4.scrapes.json`)
+    pub fn path_for(output: &Path) -> PathBuf {
+        with_suffix(output, MANIFEST_SUFFIX)
+    }
+
+    /// Fichier écrit pendant le téléchargement de `output` (`video.mp4` -> `video.mp4.partial`)
+    pub fn partial_path_for(output: &Path) -> PathBuf {
+        with_suffix(output, PARTIAL_SUFFIX)
+    }
+
+    /// Nouveau manifeste, aucun octet écrit
+    pub fn new(task: &DownloadTask, chunks: &[Chunk], etag: Option<String>) -> Self {
+        let now = unix_now();
+        Self {
+            url: task.url.clone(),
+            total_size: task.total_size,
+            chunk_size: task.chunk_size,
+            etag,
+            created_at: now,
+            updated_at: now,
+            chunks: chunks
+                .iter()
+                .map(|c| ChunkState { index: c.index, start: c.start, end: c.end, downloaded: 0 })
+                .collect(),
+        }
+    }
+
+    /// Charge le manifeste de `output`; `None` s'il est absent ou illisible
+    pub fn load(output: &Path) -> Option<Self> {
+        let path = Self::path_for(output);
+        let content = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(manifest) => Some(manifest),
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "Manifeste illisible, ignoré");
+                None
+            }
+        }
+    }
+
+    /// Écrit le manifeste de façon atomique (fichier temporaire puis renommage)
+    pub fn save(&mut self, output: &Path) -> io::Result<()> {
+        self.updated_at = unix_now();
+        let path = Self::path_for(output);
+        let tmp = path.with_extension("json.tmp");
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &path)
+    }
+
+    /// Le manifeste décrit‑il bien cette tâche (même ressource, même découpage initial)?
+    pub fn matches(&self, task: &DownloadTask, etag: Option<&str>) -> bool {
+        let same_etag = match (self.etag.as_deref(), etag) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        };
+        self.url == task.url
+            && self.total_size == task.total_size
+            && self.chunk_size == task.chunk_size
+            && same_etag
+            && self.is_contiguous()
+    }
+
+    /// Les segments (triés par `start`) couvrent `[0, total_size - 1]` sans trou ni chevauchement.
+    /// Le découpage adaptatif modifie les bornes; un manifeste incohérent n'est pas réutilisé.
+    pub(crate) fn is_contiguous(&self) -> bool {
+        let mut next = 0;
+        for chunk in &self.chunks {
+            if chunk.start != next || chunk.end < chunk.start {
+                return false;
+            }
+            next = chunk.end + 1;
+        }
+        next == self.total_size
+    }
+
+    pub fn summary(&self) -> ManifestSummary {
+        ManifestSummary {
+            done_chunks: self.chunks.iter().filter(|c| c.is_done()).count(),
+            total_chunks: self.chunks.len(),
+            downloaded: self.chunks.iter().map(|c| c.downloaded.min(c.len())).sum(),
+            total_size: self.total_size,
+        }
+    }
+}
+
+fn with_suffix(output: &Path, suffix: &str) -> PathBuf {
+    let name = output.file_name().unwrap_or_else(|| std::ffi::OsStr::new("download"));
+    let mut s = name.to_string_lossy().to_string();
+    s.push_str(suffix);
+    output.with_file_name(s)
+}
+
+/// Manifeste partagé par les tâches de segments d'un téléchargement en cours
+pub(crate) struct ManifestStore {
+    output: PathBuf,
+    manifest: Mutex<Manifest>,
+}
+
+impl ManifestStore {
+    pub(crate) fn new(output: PathBuf, manifest: Manifest) -> Self {
+        Self { output, manifest: Mutex::new(manifest) }
+    }
+
+    /// Enregistre l'offset écrit du segment `index` et réécrit le manifeste
+    pub(crate) fn record(&self, index: usize, downloaded: u64) -> io::Result<()> {
+        self.update(|manifest| {
+            if let Some(chunk) = manifest.chunks.iter_mut().find(|c| c.index == index) {
+                chunk.downloaded = downloaded;
+            }
+        })
+    }
+
+    /// Copie de l'état courant (découpage et offsets)
+    pub(crate) fn snapshot(&self) -> Manifest {
+        self.manifest.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Applique `f` au manifeste puis le réécrit (changement de découpage, offsets)
+    pub(crate) fn update(&self, f: impl FnOnce(&mut Manifest)) -> io::Result<()> {
+        let mut manifest = self.manifest.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut manifest);
+        manifest.save(&self.output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_manifest_roundtrip_and_summary() {
+        let dir = tempdir().unwrap();
+        let output = dir.path().join("video.mp4");
+        let task = DownloadTask::builder("https://example.com/video.mp4")
+            .with_output(output.clone())
+            .with_total_size(2_500)
+            .with_chunk_size(1_000)
+            .build()
+            .unwrap();
+        let chunks = task.create_chunks();
+        let store = ManifestStore::new(output.clone(), Manifest::new(&task, &chunks, Some("\"v1\"".to_string())));
+        store.record(0, 1_000).unwrap();
+        store.record(2, 200).unwrap();
+
+        assert_eq!(Manifest::path_for(&output), dir.path().join("video.mp4.scrapes.json"));
+        assert_eq!(Manifest::partial_path_for(&output), dir.path().join("video.mp4.partial"));
+        let loaded = Manifest::load(&output).unwrap();
+        assert!(loaded.matches(&task, Some("\"v1\"")));
+        assert!(!loaded.matches(&task, Some("\"v2\"")));
+        let mut gap = loaded.clone();
+        gap.chunks.remove(1);
+        assert!(!gap.matches(&task, None));
+        assert_eq!(loaded.summary(), ManifestSummary { done_chunks: 1, total_chunks: 3, downloaded: 1_200, total_size: 2_500 });
+    }
+}
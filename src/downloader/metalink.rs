@@ -0,0 +1,189 @@
+//! Lecture des fichiers Metalink: `.meta4` (RFC 5854) et `.metalink` (version 3.0).
+//!
+//! Chaque `<file>` devient une `DownloadTask` dont l'URL principale est la mieux classée
+//! (`priority` la plus basse en v4, `preference` la plus haute en v3), les autres URLs
+//! servant de miroirs. Taille et empreintes sont préremplies: la taille évite la requête HEAD,
+//! les empreintes sont vérifiées en fin de téléchargement.
+use std::path::Path;
+use anyhow::{Context, Result};
+use roxmltree::{Document, Node};
+use super::checksum::{ExpectedHash, HashAlgorithm};
+use super::naming::sanitize_filename;
+use super::protocol::Protocol;
+use super::queue::Priority;
+use super::types::DownloadTask;
+
+/// Entrée `<file>` d'un Metalink
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetalinkFile {
+    pub name: String,
+    pub size: Option<u64>,
+    pub hashes: Vec<ExpectedHash>,
+    /// URLs triées de la plus à la moins prioritaire
+    pub urls: Vec<String>,
+}
+
+impl MetalinkFile {
+    /// Tâche de téléchargement vers `dir/<nom>`, de rang `priority` dans la file; `None` si
+    /// aucune URL n'est exploitable ou si la tâche est invalide (nom vide...)
+    pub fn to_task(&self, dir: &Path, chunk_size: u64, priority: Priority) -> Option<DownloadTask> {
+        let (url, mirrors) = self.urls.split_first()?;
+        // Le nom peut contenir des dossiers (`iso/debian.iso`): seul le dernier composant est gardé
+        let name = Path::new(&self.name).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        DownloadTask::builder(url.clone())
+            .with_output(dir.join(sanitize_filename(&name)))
+            .with_total_size(self.size.unwrap_or(0))
+            .with_chunk_size(chunk_size)
+            .with_mirrors(mirrors.iter().cloned())
+            .with_hashes(self.hashes.iter().cloned())
+            .with_priority(priority)
+            .build()
+            .ok()
+    }
+}
+
+/// Nom local d'un élément, sans espace de noms
+fn is(node: &Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+fn children<'a, 'input>(node: Node<'a, 'input>, name: &'a str) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children().filter(move |c| is(c, name))
+}
+
+fn text(node: Node) -> String {
+    node.text().unwrap_or_default().trim().to_string()
+}
+
+/// Seuls les schémas gérés par le gestionnaire sont retenus (pas de `.torrent`, `ed2k`...)
+fn supported(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    match Protocol::of(&lower) {
+        Protocol::Http => lower.starts_with("http://") || lower.starts_with("https://"),
+        Protocol::Ftp | Protocol::Sftp => true,
+        // Un Metalink téléchargé ne doit pas pouvoir désigner des fichiers de la machine
+        Protocol::Local => false,
+    }
+}
+
+fn parse_file(file: Node) -> Option<MetalinkFile> {
+    let name = file.attribute("name")?.to_string();
+    let size = children(file, "size").next().and_then(|n| text(n).parse().ok());
+
+    // v4: <hash> directement sous <file>; v3: sous <verification>
+    let hash_nodes = children(file, "hash").chain(children(file, "verification").flat_map(|v| children(v, "hash")));
+    let hashes = hash_nodes
+        .filter_map(|h| Some(ExpectedHash::new(HashAlgorithm::parse(h.attribute("type")?)?, &text(h))))
+        .collect();
+
+    // Rang croissant = plus prioritaire
+    let mut ranked: Vec<(i64, String)> = Vec::new();
+    for url in children(file, "url") {
+        let rank = url.attribute("priority").and_then(|p| p.parse().ok()).unwrap_or(999_999);
+        ranked.push((rank, text(url)));
+    }
+    for url in children(file, "resources").flat_map(|r| children(r, "url")) {
+        let preference: i64 = url.attribute("preference").and_then(|p| p.parse().ok()).unwrap_or(0);
+        ranked.push((-preference, text(url)));
+    }
+    ranked.sort_by_key(|(rank, _)| *rank);
+    let mut urls: Vec<String> = Vec::new();
+    for (_, url) in ranked {
+        if supported(&url) && !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    Some(MetalinkFile { name, size, hashes, urls })
+}
+
+/// Analyse le contenu d'un fichier Metalink (v3 ou v4)
+pub fn parse_metalink(xml: &str) -> Result<Vec<MetalinkFile>> {
+    let document = Document::parse(xml).context("XML Metalink invalide")?;
+    let root = document.root_element();
+    if !is(&root, "metalink") {
+        anyhow::bail!("Élément racine <metalink> attendu, trouvé <{}>", root.tag_name().name());
+    }
+    let files = root
+        .descendants()
+        .filter(|n| is(n, "file"))
+        .filter_map(parse_file)
+        .collect();
+    Ok(files)
+}
+
+/// Lit `path` et produit une tâche par fichier décrit, écrite dans `dir` avec la priorité `priority`
+pub fn load_metalink(path: &Path, dir: &Path, chunk_size: u64, priority: Priority) -> Result<Vec<DownloadTask>> {
+    let xml = std::fs::read_to_string(path).with_context(|| format!("Lire {}", path.display()))?;
+    let files = parse_metalink(&xml)?;
+    let tasks: Vec<DownloadTask> = files.iter().filter_map(|f| f.to_task(dir, chunk_size, priority)).collect();
+    if tasks.len() < files.len() {
+        tracing::warn!(path = %path.display(), skipped = files.len() - tasks.len(), "Entrées Metalink sans URL exploitable ignorées");
+    }
+    Ok(tasks)
+}
+
+/// Le chemin désigne‑t‑il un fichier Metalink (`.metalink`, `.meta4`)?
+pub fn is_metalink_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("metalink") || e.eq_ignore_ascii_case("meta4"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const META4: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metalink xmlns="urn:ietf:params:xml:ns:metalink">
+  <file name="iso/debian.iso">
+    <size>14471447</size>
+    <hash type="sha-256">F0CFC2A3E5A4E8B2D6E3AE63E3E2B1B7C4D7D1E4F5A6B7C8D9E0F1A2B3C4D5E6</hash>
+    <hash type="md5">00112233445566778899aabbccddeeff</hash>
+    <url priority="2">ftp://ftp.example.net/debian.iso</url>
+    <url priority="1">https://cdn.example.com/debian.iso</url>
+    <metaurl mediatype="torrent">https://example.com/debian.torrent</metaurl>
+    <url priority="3">ed2k://|file|debian.iso|</url>
+  </file>
+  <file name="notes.txt">
+    <url>https://example.com/notes.txt</url>
+  </file>
+</metalink>"#;
+
+    const METALINK_V3: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metalink version="3.0" xmlns="http://www.metalinker.org/">
+  <files>
+    <file name="film.mkv">
+      <size>2048</size>
+      <verification><hash type="sha1">A9993E364706816ABA3E25717850C26C9CD0D89D</hash></verification>
+      <resources>
+        <url type="http" preference="10">http://slow.example.com/film.mkv</url>
+        <url type="http" preference="100">http://fast.example.com/film.mkv</url>
+      </resources>
+    </file>
+  </files>
+</metalink>"#;
+
+    #[test]
+    fn test_parse_metalink_v4_and_v3() {
+        let files = parse_metalink(META4).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].size, Some(14_471_447));
+        assert_eq!(files[0].urls, vec!["https://cdn.example.com/debian.iso", "ftp://ftp.example.net/debian.iso"]);
+        assert_eq!(files[0].hashes[0].algorithm, HashAlgorithm::Sha256);
+        assert!(files[0].hashes[0].hex.starts_with("f0cfc2"));
+
+        let task = files[0].to_task(Path::new("/tmp/dl"), 1024, Priority::High).unwrap();
+        assert_eq!(task.output, Path::new("/tmp/dl/debian.iso"));
+        assert_eq!(task.total_size, 14_471_447);
+        assert_eq!(task.mirrors, vec!["ftp://ftp.example.net/debian.iso"]);
+        assert_eq!(task.hashes.len(), 2);
+        assert_eq!(task.priority, Priority::High);
+
+        let files = parse_metalink(METALINK_V3).unwrap();
+        assert_eq!(files[0].urls[0], "http://fast.example.com/film.mkv");
+        assert_eq!(files[0].hashes, vec![ExpectedHash::new(HashAlgorithm::Sha1, "a9993e364706816aba3e25717850c26c9cd0d89d")]);
+
+        assert!(parse_metalink("<html></html>").is_err());
+        assert!(is_metalink_path(Path::new("release.META4")));
+    }
+}
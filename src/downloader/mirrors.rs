@@ -0,0 +1,232 @@
+//! Téléchargement multi‑sources: l'URL principale et ses miroirs servent le même fichier.
+//!
+//! Les segments sont répartis à tour de rôle entre les sources; un segment en échec bascule sur
+//! la source suivante et reprend depuis l'offset déjà écrit. Une source qui échoue trop souvent
+//! n'est plus choisie tant qu'il en reste une autre.
+//!
+//! `MirrorHealth` suit chaque source pour toute la session (latence d'ouverture des plages,
+//! taux d'échec): une source déclassée (trop d'échecs, ou trop lente à répondre) passe après les
+//! autres dans tous les téléchargements suivants, sans être exclue. Ces chiffres accompagnent
+//! les événements de progression des téléchargements multi‑sources (`ProgressEvent::mirrors`).
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// Nombre d'échecs au‑delà duquel une source est évitée
+const MAX_SOURCE_FAILURES: u32 = 3;
+/// Requêtes observées avant de juger une source
+const MIN_SAMPLES: u32 = 3;
+/// Latence moyenne d'ouverture au‑delà de laquelle une source est déclassée
+const SLOW_LATENCY: Duration = Duration::from_secs(5);
+/// Poids d'une nouvelle mesure dans la latence moyenne (moyenne mobile exponentielle)
+const LATENCY_WEIGHT: f64 = 0.3;
+
+static GLOBAL: OnceLock<MirrorHealth> = OnceLock::new();
+
+/// Santé d'une source, telle que publiée dans les événements de progression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MirrorStats {
+    pub url: String,
+    /// Tentatives terminées (réussies ou non) depuis le lancement
+    pub requests: u32,
+    pub failures: u32,
+    /// Délai moyen d'ouverture d'une plage (en‑têtes reçus); `None` avant la première mesure
+    pub latency: Option<Duration>,
+    /// Source reléguée après les autres pour le reste de la session
+    pub demoted: bool,
+}
+
+#[derive(Debug, Default)]
+struct Record {
+    requests: u32,
+    failures: u32,
+    latency_samples: u32,
+    latency_secs: f64,
+    demoted: bool,
+}
+
+impl Record {
+    /// Déclasse la source dès qu'elle échoue une fois sur deux ou répond trop lentement; le
+    /// déclassement dure jusqu'à la fin de la session
+    fn judge(&mut self, url: &str) {
+        if self.demoted {
+            return;
+        }
+        let failing = self.requests >= MIN_SAMPLES && self.failures * 2 >= self.requests;
+        let slow = self.latency_samples >= MIN_SAMPLES && self.latency_secs >= SLOW_LATENCY.as_secs_f64();
+        if failing || slow {
+            self.demoted = true;
+            tracing::warn!(url, requests = self.requests, failures = self.failures, latency = ?Duration::from_secs_f64(self.latency_secs), "Source déclassée pour la session");
+        }
+    }
+}
+
+/// Suivi de santé des sources partagé (clonage bon marché) entre les téléchargements
+#[derive(Debug, Clone, Default)]
+pub struct MirrorHealth(Arc<Mutex<HashMap<String, Record>>>);
+
+impl MirrorHealth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Suivi de l'application, commun à tous les `DownloadManager`
+    pub fn global() -> Self {
+        GLOBAL.get_or_init(Self::new).clone()
+    }
+
+    fn update(&self, url: &str, f: impl FnOnce(&mut Record)) {
+        let mut records = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let record = records.entry(url.to_string()).or_default();
+        f(record);
+        record.judge(url);
+    }
+
+    /// Enregistre le délai d'ouverture d'une plage sur `url`
+    pub fn record_latency(&self, url: &str, latency: Duration) {
+        self.update(url, |r| {
+            let secs = latency.as_secs_f64();
+            r.latency_secs = if r.latency_samples == 0 { secs } else { r.latency_secs + LATENCY_WEIGHT * (secs - r.latency_secs) };
+            r.latency_samples += 1;
+        });
+    }
+
+    /// Enregistre l'issue d'une tentative sur `url`
+    pub fn record_attempt(&self, url: &str, success: bool) {
+        self.update(url, |r| {
+            r.requests += 1;
+            if !success {
+                r.failures += 1;
+            }
+        });
+    }
+
+    pub fn is_demoted(&self, url: &str) -> bool {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).get(url).is_some_and(|r| r.demoted)
+    }
+
+    /// Chiffres de chaque URL de `urls`, dans le même ordre
+    pub fn stats(&self, urls: &[String]) -> Vec<MirrorStats> {
+        let records = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        urls.iter()
+            .map(|url| {
+                let record = records.get(url);
+                MirrorStats {
+                    url: url.clone(),
+                    requests: record.map_or(0, |r| r.requests),
+                    failures: record.map_or(0, |r| r.failures),
+                    latency: record.filter(|r| r.latency_samples > 0).map(|r| Duration::from_secs_f64(r.latency_secs)),
+                    demoted: record.is_some_and(|r| r.demoted),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Sources d'un téléchargement (index 0 = source principale)
+#[derive(Debug)]
+pub(crate) struct MirrorSet {
+    urls: Vec<String>,
+    failures: Vec<AtomicU32>,
+    health: MirrorHealth,
+}
+
+impl MirrorSet {
+    pub(crate) fn new(urls: Vec<String>, health: MirrorHealth) -> Self {
+        let failures = urls.iter().map(|_| AtomicU32::new(0)).collect();
+        Self { urls, failures, health }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.urls.len()
+    }
+
+    pub(crate) fn url(&self, source: usize) -> &str {
+        &self.urls[source % self.urls.len()]
+    }
+
+    pub(crate) fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    pub(crate) fn health(&self) -> &MirrorHealth {
+        &self.health
+    }
+
+    /// Source attribuée au segment `index` (répartition circulaire parmi les sources saines)
+    pub(crate) fn pick(&self, index: usize) -> usize {
+        self.first_healthy_from(index % self.len())
+    }
+
+    /// Source à essayer après un échec sur `source`
+    pub(crate) fn failover(&self, source: usize) -> usize {
+        self.first_healthy_from((source + 1) % self.len())
+    }
+
+    pub(crate) fn report_failure(&self, source: usize) {
+        let failures = self.failures[source].fetch_add(1, Ordering::Relaxed) + 1;
+        self.health.record_attempt(self.url(source), false);
+        tracing::debug!(url = %self.url(source), failures, "Échec de la source");
+    }
+
+    pub(crate) fn report_success(&self, source: usize) {
+        self.health.record_attempt(self.url(source), true);
+    }
+
+    pub(crate) fn report_latency(&self, source: usize, latency: Duration) {
+        self.health.record_latency(self.url(source), latency);
+    }
+
+    /// Première source utilisable à partir de `start`: d'abord celles que la session n'a pas
+    /// déclassées, puis les autres; aucune ne l'est si toutes ont trop échoué ici
+    fn first_healthy_from(&self, start: usize) -> usize {
+        let usable = |source: &usize| self.failures[*source].load(Ordering::Relaxed) < MAX_SOURCE_FAILURES;
+        let order = || (0..self.len()).map(|offset| (start + offset) % self.len());
+        order()
+            .filter(usable)
+            .find(|&source| !self.health.is_demoted(self.url(source)))
+            .or_else(|| order().find(usable))
+            .unwrap_or(start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_robin_skips_failing_and_demoted_sources() {
+        let urls = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let mirrors = MirrorSet::new(urls.clone(), MirrorHealth::new());
+        assert_eq!((0..4).map(|i| mirrors.pick(i)).collect::<Vec<_>>(), vec![0, 1, 2, 0]);
+        assert_eq!(mirrors.failover(2), 0);
+
+        for _ in 0..MAX_SOURCE_FAILURES {
+            mirrors.report_failure(1);
+        }
+        assert_eq!(mirrors.pick(1), 2);
+        assert_eq!(mirrors.failover(0), 2);
+        assert_eq!(mirrors.url(2), "c");
+
+        // Session: "b" reste déclassée dans un nouveau téléchargement, "c" lente l'est aussi;
+        // "a" saine passe devant, les autres ne servent qu'en dernier recours
+        let health = mirrors.health().clone();
+        for _ in 0..MIN_SAMPLES {
+            health.record_latency("c", Duration::from_secs(8));
+            health.record_latency("a", Duration::from_millis(40));
+            health.record_attempt("a", true);
+        }
+        let next = MirrorSet::new(urls.clone(), health.clone());
+        assert_eq!((0..3).map(|i| next.pick(i)).collect::<Vec<_>>(), vec![0, 0, 0]);
+        for _ in 0..MAX_SOURCE_FAILURES {
+            next.report_failure(0);
+        }
+        assert_eq!(next.pick(0), 1);
+
+        let stats = health.stats(&urls);
+        assert_eq!((stats[0].requests, stats[0].failures, stats[0].demoted), (6, 3, true));
+        assert_eq!((stats[1].requests, stats[1].failures, stats[1].demoted), (3, 3, true));
+        assert_eq!((stats[2].requests, stats[2].demoted, stats[2].latency), (0, true, Some(Duration::from_secs(8))));
+    }
+}
@@ -2,28 +2,111 @@
 //!
 //! Ce module regroupe:
 //! - **types**: structures de données (`DownloadTask`, `Chunk`) et leurs invariants.
-//! - **utils**: fonctions d'E/S (préallocation/merge) optimisées pour limiter les appels système.
+//! - **utils**: fonctions d'E/S (préallocation du fichier de sortie).
 //! - **manager**: logique de préparation et orchestration du téléchargement.
+//! - **naming**: nettoyage des noms de fichiers et résolution des conflits de sortie.
+//! - **postprocess**: pipeline ordonné d'étapes exécutées après un téléchargement réussi.
+//! - **harvest**: extraction des liens téléchargeables d'une page HTML/JSON.
+//! - **space**: surveillance de l'espace disque libre pendant les écritures.
+//! - **offline**: mode hors ligne empêchant toute requête réseau.
+//! - **progress**: événements de progression (par segment, agrégée, débit lissé, temps restant).
+//! - **events**: bus `broadcast` des étapes de chaque téléchargement (démarrage, fin, échec...).
+//! - **throttle**: limitation de bande passante (par tâche et globale).
+//! - **retry**: nouvelles tentatives par segment (délai exponentiel, gigue).
+//! - **manifest**: manifeste de reprise `<nom>.scrapes.json` (segments, offsets, ETag).
+//! - **resume**: recherche et validation des téléchargements interrompus, `resume_all`.
+//! - **scheduler**: découpage adaptatif des plages selon le débit mesuré (fusion, coupe).
+//! - **mirrors**: répartition des segments entre l'URL principale et ses miroirs, santé des
+//!   sources sur la session (latence, taux d'échec).
+//! - **http**: options du client HTTP (réutilisation des connexions, keepalive, HTTP/2).
+//! - **protocol**: répartition par schéma d'URL (HTTP(S), `ftp://`, `sftp://`, `file://`, `data:`).
+//! - **ftp** / **sftp**: sources FTP (mode passif, `REST`) et SFTP (libssh2, `seek`).
+//! - **local**: copies depuis `file://` et URLs `data:` par le même pipeline.
+//! - **checksum**: contrôle de la taille finale (`TruncatedDownload`), empreintes attendues
+//!   (MD5, SHA‑1, SHA‑256, SHA‑512) et vérification.
+//! - **metalink**: lecture des fichiers `.metalink` / `.meta4` en tâches avec miroirs et empreintes.
+//! - **torrent**: liens magnet et fichiers `.torrent`, transférés par `aria2c` supervisé.
+//! - **batch**: listes d'URLs (format `--input-file` d'aria2 ou JSON) converties en tâches.
+//! - **queue**: file d'attente partagée limitant le nombre de téléchargements simultanés.
+//! - **cookies**: stockage de cookies partagé par les clients HTTP, sauvegardé sur disque.
+//! - **stats**: statistiques agrégées (actifs, en attente, débit cumulé, volume du jour, échecs).
 //!
 //! Conception et performances:
-//! - Les fichiers de parties sont pré‑alloués à la taille exacte du segment pour éviter les
-//!   réallocations et garantir des écritures positionnées constantes.
-//! - La fusion s'appuie sur des tampons de 1 MiB (lecture/écriture) afin de réduire le nombre
-//!   d'appels système lors de la concaténation.
+//! - Le fichier de sortie est pré‑alloué une seule fois; chaque segment y écrit à son propre
+//!   offset, ce qui évite toute passe de fusion.
 //! - `create_chunks` réserve la capacité du vecteur à l'avance et protège contre les tailles
 //!   invalides (`total_size == 0` ou `chunk_size == 0`).
-//!
-//! Extension future:
-//! - Ajout du téléchargement HTTP parallèle (plages `Range`) et reprise.
-//! - Progression par chunk et agrégation vers un indicateur global.
-//! - Vérification d'intégrité (hash) post‑merge.
 mod types;
 mod utils;
 mod manager;
+mod naming;
+mod postprocess;
+mod harvest;
+mod space;
+mod offline;
+mod progress;
+mod events;
+mod throttle;
+mod retry;
+mod manifest;
+mod resume;
+mod scheduler;
+mod mirrors;
+mod http;
+mod protocol;
+mod ftp;
+mod sftp;
+mod local;
+mod checksum;
+mod metalink;
+mod batch;
+mod torrent;
+mod queue;
+mod stats;
+mod cookies;
+mod error;
+mod hosts;
+mod quarantine;
 
-pub use manager::DownloadManager;
+pub use manager::{decompression_from_config, max_concurrency_from_config, probe_http, small_file_threshold_from_config, DownloadManager};
 pub use types::DownloadTask;
-use std::path::PathBuf;
+#[allow(unused_imports)]
+pub use types::{BasicAuth, DownloadTaskBuilder, RequestOptions};
+pub use naming::{dedupe_path, filename_from_url, fit_path_length, sanitize_filename, CollisionPolicy};
+pub use postprocess::PostProcessPipeline;
+pub use harvest::{harvest, HarvestFilter};
+pub use space::{max_file_size_from_config, DiskFull, FileTooLarge, SpaceGuard};
+pub use queue::{DownloadQueue, Priority, QueueSlot};
+pub use stats::{DownloadStatistics, DownloadStatsSnapshot};
+pub use cookies::CookieJar;
+pub use offline::OfflineMode;
+pub use progress::{estimate_eta, ProgressEvent};
+pub use events::{DownloadEventKind, DownloadEvents};
+#[allow(unused_imports)]
+pub use events::DownloadEvent;
+pub use error::DownloadErrorKind;
+#[allow(unused_imports)]
+pub use error::DownloadError;
+pub use throttle::{per_download_limit_from_config, RateLimiter, SpeedRule, SpeedSchedule};
+pub use retry::{parse_retry_after, stall_timeout_from_config, RetryPolicy};
+#[allow(unused_imports)]
+pub use mirrors::{MirrorHealth, MirrorStats};
+pub use http::HttpClientOptions;
+#[allow(unused_imports)]
+pub use hosts::{max_connections_per_host_from_config, HostLimiter};
+pub use scheduler::AdaptiveChunking;
+pub use manifest::{Manifest, ManifestSummary};
+#[allow(unused_imports)]
+pub use manifest::ChunkState;
+pub use resume::{check_resumable, scan_incomplete, IncompleteDownload};
+pub use quarantine::{restore_from_quarantine, PartialCleanup};
+#[allow(unused_imports)]
+pub use resume::resume_all;
+pub use checksum::{parse_sidecar, sidecar_checksums_from_config, ExpectedHash, HashAlgorithm, Integrity, TruncatedDownload};
+pub use metalink::{is_metalink_path, load_metalink};
+pub use batch::{is_url_list_path, load_url_list};
+pub use torrent::{is_torrent_source, torrent_display_name, SwarmProgress, TorrentDownload, TorrentOutcome};
+use std::path::{Path, PathBuf};
 use std::fs;
 use serde::Deserialize;
 
@@ -33,6 +116,18 @@ const DEFAULT_CHUNK_SIZE: u64 = 8 * 1024 * 1024; // 8 MiB
 pub struct AppConfig {
     pub logging: Option<LoggingConfig>,
     pub cleanup: Option<CleanupConfig>,
+    pub post_process: Option<PostProcessConfig>,
+    pub disk: Option<DiskConfig>,
+    pub bandwidth: Option<BandwidthConfig>,
+    pub retry: Option<RetryConfig>,
+    pub chunking: Option<ChunkingConfig>,
+    pub queue: Option<QueueConfig>,
+    pub checksum: Option<ChecksumConfig>,
+    pub cookies: Option<CookiesConfig>,
+    pub compression: Option<CompressionConfig>,
+    pub http: Option<HttpConfig>,
+    pub ffmpeg: Option<FfmpegConfig>,
+    pub scrapers: Option<ScrapersConfig>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,6 +142,165 @@ pub struct CleanupConfig {
     pub remove_temp_files: Option<bool>,
     /// Supprimer les fichiers temporaires en cas d'erreur
     pub remove_on_error: Option<bool>,
+    /// Déplacer les fichiers temporaires dans `.scrapes-incomplete/` en cas d'erreur (prioritaire)
+    pub quarantine_on_error: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostProcessConfig {
+    /// Étapes exécutées dans l'ordre après un téléchargement réussi (ex: `["cleanup"]`)
+    pub steps: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiskConfig {
+    /// Espace libre minimal (MiB) à préserver; le téléchargement s'arrête en dessous
+    pub min_free_space_mb: Option<u64>,
+    /// Taille maximale d'un fichier (MiB, 0 = illimitée); valeur par défaut des tâches
+    pub max_file_size_mb: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BandwidthConfig {
+    /// Débit maximal cumulé de tous les téléchargements (KiB/s, 0 = illimité)
+    pub max_kib_per_sec: Option<u64>,
+    /// Débit maximal de chaque téléchargement (KiB/s, 0 = illimité)
+    pub per_download_kib_per_sec: Option<u64>,
+    /// Plages horaires remplaçant `max_kib_per_sec` (`[[bandwidth.schedule]]`)
+    pub schedule: Option<Vec<SpeedRuleConfig>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SpeedRuleConfig {
+    /// Début de la plage, heure locale `HH:MM`
+    pub from: String,
+    /// Fin de la plage (exclue), `HH:MM`; avant `from` pour traverser minuit
+    pub to: String,
+    /// Débit cumulé pendant la plage (KiB/s, 0 = illimité)
+    pub kib_per_sec: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RetryConfig {
+    /// Nombre total de tentatives par segment (première incluse)
+    pub max_attempts: Option<u32>,
+    /// Délai avant la deuxième tentative (ms), doublé ensuite
+    pub initial_backoff_ms: Option<u64>,
+    /// Plafond du délai entre deux tentatives (ms)
+    pub max_backoff_ms: Option<u64>,
+    /// Gigue relative du délai (0.0 à 1.0)
+    pub jitter: Option<f64>,
+    /// Secondes sans octet reçu avant d'abandonner et relancer un segment (60 par défaut, 0 = jamais)
+    pub stall_timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChunkingConfig {
+    /// Ajuster la taille des segments au débit mesuré (activé par défaut)
+    pub adaptive: Option<bool>,
+    /// Taille minimale d'un segment (KiB)
+    pub min_chunk_size_kib: Option<u64>,
+    /// Taille maximale d'un segment (KiB)
+    pub max_chunk_size_kib: Option<u64>,
+    /// Connexions simultanées par téléchargement (8 par défaut)
+    pub max_concurrency: Option<usize>,
+    /// Fichiers téléchargés en une requête jusqu'à cette taille (KiB; taille d'un segment par
+    /// défaut, 0 désactive)
+    pub small_file_threshold_kib: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueueConfig {
+    /// Nombre maximal de téléchargements simultanés (3 par défaut)
+    pub max_active_downloads: Option<usize>,
+    /// Connexions simultanées vers un même hôte, tous téléchargements confondus (0 = illimité)
+    pub max_connections_per_host: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChecksumConfig {
+    /// Chercher `<url>.sha256` / `<url>.md5` pour vérifier les fichiers sans empreinte connue
+    pub fetch_sidecar: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CookiesConfig {
+    /// Conserver les cookies reçus entre deux lancements (activé par défaut)
+    pub persist: Option<bool>,
+    /// Fichier de sauvegarde (`cookies.json` par défaut)
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompressionConfig {
+    /// Accepter gzip/brotli pour les téléchargements sans `Range` et écrire le contenu
+    /// décompressé (sinon tout est demandé non compressé)
+    pub decompress: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HttpConfig {
+    /// Parler HTTP/2 d'emblée, sans négociation (désactivé par défaut)
+    pub http2_prior_knowledge: Option<bool>,
+    /// Fermer une connexion inactive après ce délai (s, 90 par défaut, 0 = jamais)
+    pub pool_idle_timeout_secs: Option<u64>,
+    /// Connexions inactives conservées par hôte pour les requêtes suivantes (0 = sans limite)
+    pub max_idle_per_host: Option<usize>,
+    /// Intervalle des sondes keepalive TCP (s, 0 = désactivé)
+    pub tcp_keepalive_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FfmpegConfig {
+    /// Exécutable ffmpeg à utiliser plutôt que celui trouvé dans le `PATH`
+    pub path: Option<PathBuf>,
+    /// Exécutable ffprobe (durée, inspection des médias)
+    pub ffprobe_path: Option<PathBuf>,
+    /// Tâches ffmpeg simultanées dans la file de l'onglet FFmpeg (2 par défaut)
+    pub max_jobs: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScrapersConfig {
+    /// Dossier des sites décrits en TOML/YAML pour le scraper générique (`sites` par défaut)
+    pub sites_dir: Option<PathBuf>,
+    /// URL de base du scraper FZMovies (`https://fzmovies.net/` par défaut)
+    pub fzmovies_url: Option<String>,
+    /// Cache disque des pages HTML (activé par défaut)
+    pub cache: Option<bool>,
+    /// Dossier du cache (`.scrapes-cache` par défaut)
+    pub cache_dir: Option<PathBuf>,
+    /// Durée de vie d'une page en cache avant revalidation, en secondes (3600 par défaut)
+    pub cache_ttl_secs: Option<u64>,
+    /// Requêtes lancées par seconde au plus (sans limite par défaut)
+    pub requests_per_second: Option<f64>,
+    /// Requêtes simultanées au plus (10 par défaut)
+    pub concurrency: Option<usize>,
+    /// Délai aléatoire maximal ajouté avant chaque requête (ms, 0 par défaut)
+    pub jitter_ms: Option<u64>,
+    /// Tentatives par page après une erreur transitoire, première incluse (3 par défaut)
+    pub max_attempts: Option<u32>,
+    /// Dossier des sessions de scraping enregistrées (`.scrapes-sessions` par défaut)
+    pub sessions_dir: Option<PathBuf>,
+    /// Qualités préférées par l'enrichissement, de la meilleure à la moins bonne
+    /// (`["High MP4"]` par défaut; le premier lien sert si aucune ne correspond)
+    pub quality_preference: Option<Vec<String>>,
+    /// Résoudre toutes les qualités de chaque épisode (désactivé par défaut)
+    pub resolve_all_qualities: Option<bool>,
+    /// Liens résolus simultanément par l'enrichissement (10 par défaut)
+    pub enrich_concurrency: Option<usize>,
+    /// Délai maximal de résolution d'un lien, en secondes (60 par défaut)
+    pub enrich_link_timeout_secs: Option<u64>,
+    /// Ouvrir chaque page scrapée dans le navigateur, pour le débogage (désactivé par défaut)
+    pub debug_open_pages: Option<bool>,
+    /// Proxys pris à tour de rôle par le scraper (`http://...`; accès direct si vide)
+    pub proxies: Option<Vec<String>>,
+    /// User‑agents pris à tour de rôle par le scraper
+    pub user_agents: Option<Vec<String>>,
+    /// Mise à l'écart d'un proxy après trois échecs de suite, en secondes (300 par défaut)
+    pub proxy_cooldown_secs: Option<u64>,
+    /// Passer les vérifications anti‑robot (Cloudflare) dans Chromium (activé par défaut)
+    pub solve_challenges: Option<bool>,
 }
 
 /// Charge la configuration depuis scrapes.toml
@@ -71,37 +325,24 @@ pub fn init_logging() {
         .init();
 }
 
-/// Nettoie les fichiers temporaires en cas d'erreur
-pub fn cleanup_temp_files_on_error(output: &PathBuf) {
-    let output_dir = output.parent().unwrap_or(std::path::Path::new("."));
-    let output_stem = output.file_stem().unwrap_or_else(|| std::ffi::OsStr::new("file"));
-    
-    // Chercher tous les fichiers .part* et .done
-    if let Ok(entries) = fs::read_dir(output_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                if name.starts_with(&format!("{}.part", output_stem.to_string_lossy())) {
-                    if let Err(e) = fs::remove_file(&path) {
-                        tracing::warn!(?path, error = %e, "Impossible de supprimer le fichier part");
-                    } else {
-                        tracing::debug!(?path, "Fichier part supprimé après erreur");
-                    }
-                }
-                if name.ends_with(".done") && name.starts_with(&format!("{}.part", output_stem.to_string_lossy())) {
-                    if let Err(e) = fs::remove_file(&path) {
-                        tracing::warn!(?path, error = %e, "Impossible de supprimer le marqueur .done");
-                    } else {
-                        tracing::debug!(?path, "Marqueur .done supprimé après erreur");
-                    }
-                }
-            }
+/// Nettoie les fichiers temporaires en cas d'erreur: le manifeste et le `<nom>.partial` qu'il
+/// décrit (la sortie finale n'existe qu'une fois le téléchargement vérifié)
+pub fn cleanup_temp_files_on_error(output: &Path) {
+    let manifest = Manifest::path_for(output);
+    if !manifest.exists() {
+        return;
+    }
+    for path in [&manifest, &Manifest::partial_path_for(output)] {
+        if let Err(e) = fs::remove_file(path) {
+            tracing::warn!(?path, error = %e, "Impossible de supprimer le fichier temporaire");
+        } else {
+            tracing::debug!(?path, "Fichier temporaire supprimé après erreur");
         }
     }
 }
 
 /// API publique minimale: télécharge une ressource `url` vers `output`.
-/// Cache l'ensemble des détails d'orchestration.
+/// Cache l'ensemble des détails d'orchestration; `DownloadErrorKind::of` classe une erreur.
 pub async fn download_to(url: String, output: PathBuf) -> anyhow::Result<()> {
     download_to_with_chunk_size(url, output, None).await
 }
@@ -113,23 +354,33 @@ pub async fn download_to_with_chunk_size(
     output: PathBuf,
     chunk_size: Option<u64>,
 ) -> anyhow::Result<()> {
-    let task = DownloadTask {
-        url,
-        output: output.clone(),
-        total_size: 0,
-        chunk_size: chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE),
-        num_chunks: 0,
-    };
-    let manager = DownloadManager::new();
+    let config = load_config();
+    let task = DownloadTask::builder(url)
+        .with_output(output.clone())
+        .with_chunk_size(chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE))
+        .with_rate_limit(per_download_limit_from_config(&config))
+        .with_max_size(max_file_size_from_config(&config))
+        .build()?;
+    let manager = DownloadManager::new()
+        .with_post_processors(PostProcessPipeline::from_config(&config))
+        .with_space_guard(SpaceGuard::from_config(&config))
+        .with_retry_policy(RetryPolicy::from_config(&config))
+        .with_stall_timeout(stall_timeout_from_config(&config))
+        .with_adaptive_chunking(AdaptiveChunking::from_config(&config))
+        .with_sidecar_checksums(sidecar_checksums_from_config(&config))
+        .with_decompression(decompression_from_config(&config))
+        .with_max_concurrency(max_concurrency_from_config(&config))
+        .with_small_file_threshold(small_file_threshold_from_config(&config))
+        .with_http_options(HttpClientOptions::from_config(&config));
     
     match manager.start(task).await {
-        Ok(()) => Ok(()),
+        Ok(_) => Ok(()),
         Err(e) => {
-            // Nettoyage en cas d'erreur si configuré
-            let config = load_config();
-            if config.cleanup.and_then(|c| c.remove_on_error).unwrap_or(false) {
-                tracing::info!("Nettoyage des fichiers temporaires après erreur");
-                cleanup_temp_files_on_error(&output);
+            // Suppression ou quarantaine des fichiers temporaires si configuré
+            let cleanup = PartialCleanup::from_config(&config);
+            if cleanup != PartialCleanup::Keep {
+                tracing::info!(?cleanup, "Nettoyage des fichiers temporaires après erreur");
+                cleanup.apply(&output);
             }
             Err(e)
         }
@@ -141,6 +392,18 @@ impl Default for AppConfig {
         Self {
             logging: None,
             cleanup: None,
+            post_process: None,
+            disk: None,
+            bandwidth: None,
+            retry: None,
+            chunking: None,
+            queue: None,
+            checksum: None,
+            cookies: None,
+            compression: None,
+            http: None,
+            ffmpeg: None,
+            scrapers: None,
         }
     }
 }
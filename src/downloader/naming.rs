@@ -0,0 +1,304 @@
+//! Aides au nommage des fichiers de sortie.
+//!
+//! - `sanitize_filename` nettoie un nom dérivé d'une URL (caractères interdits, espaces).
+//! - `dedupe_path` choisit un chemin libre en tenant compte du disque **et** des sorties
+//!   déjà réservées par des téléchargements en cours (dont les fichiers n'existent pas encore).
+//! - `fit_path_length` tronque le nom (extension préservée) pour respecter les limites de l'OS.
+//! - `CollisionPolicy` décide du sort d'une sortie déjà présente sur le disque.
+//! - `filename_from_content_disposition` / `filename_from_url` retrouvent le nom annoncé par le
+//!   serveur ou porté par l'URL finale (après redirections).
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+
+/// Longueur maximale d'un nom de fichier (octets), commune à ext4, NTFS et APFS
+const MAX_FILENAME_BYTES: usize = 255;
+
+/// Longueur maximale d'un chemin complet (octets)
+#[cfg(windows)]
+const MAX_PATH_BYTES: usize = 260;
+#[cfg(not(windows))]
+const MAX_PATH_BYTES: usize = 4096;
+
+/// Place réservée aux suffixes des fichiers dérivés (`.scrapes.json.tmp` du manifeste)
+const RESERVED_SUFFIX_BYTES: usize = 24;
+
+/// Extensions de scripts serveur: un tel segment (`download.php`) n'est pas le nom du fichier servi
+const HANDLER_EXTENSIONS: &[&str] = &["php", "asp", "aspx", "jsp", "cgi", "pl"];
+
+/// Conduite à tenir quand la sortie existe déjà sans manifeste de reprise (fichier étranger
+/// ou téléchargement terminé)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CollisionPolicy {
+    /// Écrire dans `nom (1).ext`, `nom (2).ext`... (voir `dedupe_path`)
+    #[default]
+    Rename,
+    /// Remplacer le fichier existant
+    Overwrite,
+    /// Garder le fichier existant et ne rien télécharger
+    Skip,
+    /// Considérer le fichier comme le début de la ressource et télécharger la suite
+    /// (Range requis; sinon renommage)
+    Resume,
+}
+
+/// Remplace les caractères interdits sur les systèmes de fichiers courants par `_`.
+///
+/// Retourne `"download"` si le résultat est vide (ex: segment d'URL vide ou uniquement des points).
+/// Les noms trop longs sont tronqués en conservant l'extension.
+pub fn sanitize_filename(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim().trim_matches('.');
+    if cleaned.is_empty() {
+        "download".to_string()
+    } else {
+        fit_filename(cleaned, MAX_FILENAME_BYTES - RESERVED_SUFFIX_BYTES)
+    }
+}
+
+/// Tronque le nom de fichier de `path` pour respecter les limites de longueur de l'OS.
+///
+/// Seule la partie avant l'extension est raccourcie; une marge est conservée pour les
+/// fichiers dérivés (manifeste `.scrapes.json`).
+pub fn fit_path_length(path: &Path) -> PathBuf {
+    let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+        return path.to_path_buf();
+    };
+    let (stem, ext) = split_name(&name);
+    let fitted = build_name(stem, "", ext, name_budget(path));
+    if fitted.len() < name.len() {
+        tracing::warn!(from = name.len(), to = fitted.len(), "Nom de fichier trop long, tronqué");
+    }
+    path.with_file_name(fitted)
+}
+
+/// Tronque `name` à `max_bytes` octets en conservant l'extension (coupure sur une frontière de caractère).
+fn fit_filename(name: &str, max_bytes: usize) -> String {
+    let (stem, ext) = split_name(name);
+    build_name(stem, "", ext, max_bytes)
+}
+
+/// Sépare `nom.ext` en (`nom`, `Some("ext")`); un point initial n'est pas une extension.
+fn split_name(name: &str) -> (&str, Option<&str>) {
+    match name.rfind('.') {
+        Some(i) if i > 0 && i + 1 < name.len() => (&name[..i], Some(&name[i + 1..])),
+        _ => (name, None),
+    }
+}
+
+/// Assemble `stem + suffix + .ext` en raccourcissant `stem` pour tenir dans `max_bytes`.
+///
+/// Si l'extension occupe à elle seule plus de la moitié du budget, le nom entier est coupé.
+fn build_name(stem: &str, suffix: &str, ext: Option<&str>, max_bytes: usize) -> String {
+    let ext_part = ext.map(|e| format!(".{}", e)).unwrap_or_default();
+    if ext_part.len() > max_bytes / 2 {
+        let full = format!("{}{}{}", stem, suffix, ext_part);
+        return truncate_to_bytes(&full, max_bytes).to_string();
+    }
+    let stem_budget = max_bytes.saturating_sub(suffix.len() + ext_part.len()).max(1);
+    format!("{}{}{}", truncate_to_bytes(stem, stem_budget), suffix, ext_part)
+}
+
+/// Préfixe de `s` d'au plus `max_bytes` octets, sans couper un caractère UTF‑8
+fn truncate_to_bytes(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    let mut end = max_bytes;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+/// Octets disponibles pour le nom de fichier de `path`, compte tenu du dossier parent
+fn name_budget(path: &Path) -> usize {
+    let parent_len = path.parent().map(|p| p.as_os_str().len() + 1).unwrap_or(0);
+    MAX_FILENAME_BYTES
+        .min(MAX_PATH_BYTES.saturating_sub(parent_len))
+        .saturating_sub(RESERVED_SUFFIX_BYTES)
+}
+
+/// Retourne `candidate` s'il est libre, sinon `nom (1).ext`, `nom (2).ext`, etc.
+///
+/// Un chemin est considéré occupé s'il existe sur le disque ou s'il figure dans `pending`
+/// (sorties des téléchargements actifs de la session). Le résultat respecte `fit_path_length`.
+pub fn dedupe_path(candidate: &Path, pending: &HashSet<PathBuf>) -> PathBuf {
+    let candidate = &fit_path_length(candidate);
+    let is_taken = |p: &Path| p.exists() || pending.contains(p);
+    if !is_taken(candidate) {
+        return candidate.to_path_buf();
+    }
+
+    let name = candidate
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "download".to_string());
+    let (stem, ext) = split_name(&name);
+    let budget = name_budget(candidate);
+
+    let mut n = 1usize;
+    loop {
+        let name = build_name(stem, &format!(" ({})", n), ext, budget);
+        let next = candidate.with_file_name(name);
+        if !is_taken(&next) {
+            return next;
+        }
+        n += 1;
+    }
+}
+
+/// Dernier composant d'un nom éventuellement accompagné d'un chemin (`../a/b.mp4` -> `b.mp4`)
+fn base_name(name: &str) -> Option<&str> {
+    let base = name.rsplit(['/', '\\']).next()?.trim();
+    (!base.is_empty()).then_some(base)
+}
+
+/// Nom de fichier annoncé par un en‑tête `Content-Disposition`, nettoyé.
+///
+/// `filename*` (RFC 5987, ex: `UTF-8''na%C3%AFve.mp4`) est préféré à `filename`.
+pub fn filename_from_content_disposition(value: &str) -> Option<String> {
+    let mut plain = None;
+    let mut extended = None;
+    for param in value.split(';').map(str::trim) {
+        let Some((key, raw)) = param.split_once('=') else {
+            continue;
+        };
+        match key.trim().to_ascii_lowercase().as_str() {
+            "filename*" => {
+                let encoded = raw.trim().trim_matches('"');
+                // charset'langue'valeur
+                let encoded = encoded.splitn(3, '\'').nth(2).unwrap_or(encoded);
+                extended = Some(percent_decode_str(encoded).decode_utf8_lossy().into_owned());
+            }
+            "filename" => {
+                let raw = raw.trim();
+                let unquoted = raw.strip_prefix('"').and_then(|r| r.strip_suffix('"')).unwrap_or(raw);
+                plain = Some(unquoted.replace("\\\"", "\""));
+            }
+            _ => {}
+        }
+    }
+    let name = extended.or(plain)?;
+    base_name(&name).map(sanitize_filename)
+}
+
+/// Dernier segment du chemin de `url` s'il ressemble à un nom de fichier (avec extension,
+/// hors scripts serveur comme `download.php`), décodé et nettoyé.
+pub fn filename_from_url(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url).ok()?;
+    let segment = parsed.path_segments()?.next_back()?;
+    let decoded = percent_decode_str(segment).decode_utf8_lossy();
+    let name = base_name(&decoded)?;
+    let (_, extension) = name.rsplit_once('.')?;
+    if extension.is_empty() || HANDLER_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()) {
+        return None;
+    }
+    Some(sanitize_filename(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_filename_from_headers_and_url() {
+        assert_eq!(filename_from_content_disposition("attachment; filename=\"Mon film.mkv\"").as_deref(), Some("Mon film.mkv"));
+        assert_eq!(
+            filename_from_content_disposition("attachment; filename=\"fallback.mp4\"; filename*=UTF-8''na%C3%AFve%20%C3%A9t%C3%A9.mp4").as_deref(),
+            Some("naïve été.mp4")
+        );
+        assert_eq!(filename_from_content_disposition("attachment; filename=../../etc/passwd").as_deref(), Some("passwd"));
+        assert_eq!(filename_from_content_disposition("inline"), None);
+
+        assert_eq!(filename_from_url("https://cdn.example.com/v/Episode%2001.mp4?token=abc").as_deref(), Some("Episode 01.mp4"));
+        assert_eq!(filename_from_url("https://example.com/downloadmp4.php?id=42"), None);
+        assert_eq!(filename_from_url("https://example.com/watch"), None);
+    }
+
+    #[test]
+    fn test_sanitize_filename_replaces_forbidden_chars() {
+        assert_eq!(sanitize_filename("a:b?c.mp4"), "a_b_c.mp4");
+        assert_eq!(sanitize_filename("  ..  "), "download");
+        assert_eq!(sanitize_filename("video.mp4"), "video.mp4");
+    }
+
+    #[test]
+    fn test_dedupe_path_free_candidate_is_kept() {
+        let dir = tempdir().unwrap();
+        let candidate = dir.path().join("video.mp4");
+        assert_eq!(dedupe_path(&candidate, &HashSet::new()), candidate);
+    }
+
+    #[test]
+    fn test_dedupe_path_considers_disk() {
+        let dir = tempdir().unwrap();
+        let candidate = dir.path().join("video.mp4");
+        File::create(&candidate).unwrap();
+
+        let resolved = dedupe_path(&candidate, &HashSet::new());
+        assert_eq!(resolved, dir.path().join("video (1).mp4"));
+    }
+
+    #[test]
+    fn test_fit_filename_preserves_extension() {
+        let long = format!("{}.mp4", "a".repeat(400));
+        let fitted = fit_filename(&long, 100);
+        assert_eq!(fitted.len(), 100);
+        assert!(fitted.ends_with("a.mp4"));
+
+        // Coupure sur une frontière de caractère multi‑octets
+        let fitted = fit_filename(&format!("{}.mkv", "é".repeat(100)), 11);
+        assert_eq!(fitted, "ééé.mkv");
+        assert_eq!(fit_filename("court.mp4", 100), "court.mp4");
+    }
+
+    #[test]
+    fn test_fit_path_length_and_dedupe_stay_under_limit() {
+        let dir = tempdir().unwrap();
+        let candidate = dir.path().join(format!("{}.mp4", "x".repeat(300)));
+
+        let fitted = fit_path_length(&candidate);
+        let name = fitted.file_name().unwrap().to_string_lossy().to_string();
+        assert!(name.len() + RESERVED_SUFFIX_BYTES <= MAX_FILENAME_BYTES);
+        assert!(name.ends_with(".mp4"));
+
+        // Le suffixe " (1)" est conservé malgré la troncature
+        File::create(&fitted).unwrap();
+        let second = dedupe_path(&candidate, &HashSet::new());
+        let second_name = second.file_name().unwrap().to_string_lossy().to_string();
+        assert!(second_name.ends_with(" (1).mp4"));
+        assert_eq!(second_name.len(), name.len());
+    }
+
+    #[test]
+    fn test_two_pending_auto_downloads_get_distinct_paths() {
+        let dir = tempdir().unwrap();
+        let candidate = dir.path().join("video.mp4");
+        let mut pending = HashSet::new();
+
+        // Premier téléchargement auto-nommé: le fichier n'existe pas encore sur le disque
+        let first = dedupe_path(&candidate, &pending);
+        pending.insert(first.clone());
+
+        // Second téléchargement avec le même dernier segment d'URL
+        let second = dedupe_path(&candidate, &pending);
+        pending.insert(second.clone());
+        let third = dedupe_path(&candidate, &pending);
+
+        assert_eq!(first, candidate);
+        assert_eq!(second, dir.path().join("video (1).mp4"));
+        assert_eq!(third, dir.path().join("video (2).mp4"));
+    }
+}
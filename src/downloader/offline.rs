@@ -0,0 +1,44 @@
+//! Mode hors ligne: préparer une file de téléchargements sans aucune activité réseau.
+//!
+//! `OfflineMode::global()` est partagé par l'application (GUI, `download_to`); le
+//! `DownloadManager` et la file de la GUI le consultent avant toute requête sortante.
+//! Les tests injectent leur propre instance pour ne pas dépendre de l'état global.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use anyhow::Result;
+use super::error::{DownloadError, DownloadErrorKind};
+
+static GLOBAL: OnceLock<OfflineMode> = OnceLock::new();
+
+/// Indicateur hors ligne partagé (clonable, lecture sans verrou)
+#[derive(Debug, Clone, Default)]
+pub struct OfflineMode(Arc<AtomicBool>);
+
+impl OfflineMode {
+    /// Nouvel indicateur indépendant, en ligne par défaut
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indicateur global de l'application
+    pub fn global() -> Self {
+        GLOBAL.get_or_init(Self::new).clone()
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub fn set_offline(&self, offline: bool) {
+        self.0.store(offline, Ordering::Relaxed);
+        tracing::info!(offline, "Mode hors ligne modifié");
+    }
+
+    /// Échoue si le mode hors ligne est actif; à appeler avant toute requête réseau.
+    pub fn ensure_online(&self) -> Result<()> {
+        if self.is_offline() {
+            return Err(DownloadError::new(DownloadErrorKind::Cancelled, "Mode hors ligne actif: aucune requête réseau autorisée").into());
+        }
+        Ok(())
+    }
+}
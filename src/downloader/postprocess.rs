@@ -0,0 +1,211 @@
+//! Pipeline d'étapes exécutées après un téléchargement réussi.
+//!
+//! Chaque étape implémente `PostProcessor` et reçoit un `DownloadOutcome` décrivant le
+//! résultat (fichier final, parties, statistiques). Les étapes s'exécutent dans l'ordre
+//! d'insertion; la première erreur interrompt le pipeline et est propagée à l'appelant.
+//!
+//! L'ordre et l'activation se configurent via `scrapes.toml`:
+//! ```toml
+//! [post_process]
+//! steps = ["cleanup", "thumbnails"]
+//! ```
+use std::path::PathBuf;
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use super::types::DownloadStats;
+use super::AppConfig;
+use crate::ffmpeg::thumbnails;
+
+/// Contexte transmis aux post‑traitements
+#[derive(Debug, Clone)]
+pub struct DownloadOutcome {
+    pub url: String,
+    pub output: PathBuf,
+    pub total_size: u64,
+    /// Manifeste de reprise `<nom>.scrapes.json` (`None` en mode requête unique)
+    pub manifest: Option<PathBuf>,
+    pub stats: DownloadStats,
+}
+
+/// Étape de post‑traitement. Retourne une `BoxFuture` pour rester utilisable derrière `dyn`.
+pub trait PostProcessor: Send + Sync {
+    /// Nom court utilisé dans les logs et la configuration
+    fn name(&self) -> &str;
+
+    fn process<'a>(&'a self, ctx: &'a DownloadOutcome) -> BoxFuture<'a, Result<()>>;
+}
+
+/// Suite ordonnée de post‑traitements
+#[derive(Default)]
+pub struct PostProcessPipeline {
+    steps: Vec<Box<dyn PostProcessor>>,
+}
+
+impl PostProcessPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ajoute une étape en fin de pipeline
+    pub fn push(&mut self, step: Box<dyn PostProcessor>) {
+        self.steps.push(step);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Construit le pipeline depuis la configuration.
+    ///
+    /// - `post_process.steps` définit l'ordre explicite (noms inconnus ignorés avec un avertissement).
+    /// - À défaut, `cleanup.remove_temp_files = true` active l'étape `cleanup`.
+    pub fn from_config(config: &AppConfig) -> Self {
+        let names = config
+            .post_process
+            .as_ref()
+            .and_then(|p| p.steps.clone())
+            .unwrap_or_else(|| {
+                let cleanup = config.cleanup.as_ref().and_then(|c| c.remove_temp_files).unwrap_or(false);
+                if cleanup { vec!["cleanup".to_string()] } else { Vec::new() }
+            });
+
+        let mut pipeline = Self::new();
+        for name in names {
+            match name.as_str() {
+                "cleanup" => pipeline.push(Box::new(CleanupTempFiles)),
+                "thumbnails" => pipeline.push(Box::new(GenerateThumbnails)),
+                other => tracing::warn!(step = other, "Étape de post‑traitement inconnue ignorée"),
+            }
+        }
+        pipeline
+    }
+
+    /// Exécute les étapes dans l'ordre; s'arrête à la première erreur.
+    pub async fn run(&self, ctx: &DownloadOutcome) -> Result<()> {
+        for step in &self.steps {
+            tracing::debug!(
+                step = step.name(),
+                url = %ctx.url,
+                output = %ctx.output.display(),
+                total_size = ctx.total_size,
+                elapsed = ?ctx.stats.total_elapsed,
+                "Post‑traitement"
+            );
+            step.process(ctx)
+                .await
+                .with_context(|| format!("Post‑traitement '{}'", step.name()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Supprime le manifeste de reprise après téléchargement réussi.
+pub struct CleanupTempFiles;
+
+impl PostProcessor for CleanupTempFiles {
+    fn name(&self) -> &str {
+        "cleanup"
+    }
+
+    fn process<'a>(&'a self, ctx: &'a DownloadOutcome) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            tracing::info!("Nettoyage des fichiers temporaires");
+            if let Some(manifest) = ctx.manifest.as_ref().filter(|m| m.exists()) {
+                tokio::fs::remove_file(manifest).await?;
+                tracing::debug!(path = %manifest.display(), "Manifeste supprimé");
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Affiche et bande d'aperçu (ffmpeg) à côté des vidéos téléchargées; les autres fichiers
+/// sont ignorés et un échec (ffmpeg absent, flux illisible) ne fait pas échouer le téléchargement.
+pub struct GenerateThumbnails;
+
+impl PostProcessor for GenerateThumbnails {
+    fn name(&self) -> &str {
+        "thumbnails"
+    }
+
+    fn process<'a>(&'a self, ctx: &'a DownloadOutcome) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            if thumbnails::is_video_path(&ctx.output) {
+                thumbnails::generate_after_download(&ctx.output).await;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tempfile::tempdir;
+
+    struct Recorder {
+        name: &'static str,
+        log: Arc<Mutex<Vec<&'static str>>>,
+        fail: bool,
+    }
+
+    impl PostProcessor for Recorder {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn process<'a>(&'a self, _ctx: &'a DownloadOutcome) -> BoxFuture<'a, Result<()>> {
+            Box::pin(async move {
+                self.log.lock().unwrap().push(self.name);
+                if self.fail {
+                    anyhow::bail!("échec simulé");
+                }
+                Ok(())
+            })
+        }
+    }
+
+    fn outcome(output: PathBuf, manifest: Option<PathBuf>) -> DownloadOutcome {
+        DownloadOutcome {
+            url: "http://example.com/file".to_string(),
+            output,
+            total_size: 0,
+            manifest,
+            stats: DownloadStats::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_runs_in_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut pipeline = PostProcessPipeline::new();
+        pipeline.push(Box::new(Recorder { name: "first", log: log.clone(), fail: false }));
+        pipeline.push(Box::new(Recorder { name: "second", log: log.clone(), fail: false }));
+
+        pipeline.run(&outcome(PathBuf::from("out.bin"), None)).await.unwrap();
+        assert_eq!(*log.lock().unwrap(), vec!["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_stops_on_error() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut pipeline = PostProcessPipeline::new();
+        pipeline.push(Box::new(Recorder { name: "failing", log: log.clone(), fail: true }));
+        pipeline.push(Box::new(Recorder { name: "never", log: log.clone(), fail: false }));
+
+        let err = pipeline.run(&outcome(PathBuf::from("out.bin"), None)).await.unwrap_err();
+        assert!(err.to_string().contains("failing"));
+        assert_eq!(*log.lock().unwrap(), vec!["failing"]);
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_removes_manifest() {
+        let dir = tempdir().unwrap();
+        let manifest = dir.path().join("file.bin.scrapes.json");
+        std::fs::write(&manifest, b"{}").unwrap();
+
+        CleanupTempFiles.process(&outcome(dir.path().join("file.bin"), Some(manifest.clone()))).await.unwrap();
+        assert!(!manifest.exists());
+    }
+}
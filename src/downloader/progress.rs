@@ -0,0 +1,223 @@
+//! Progression en temps réel émise par `DownloadManager::start`.
+//!
+//! Chaque bloc reçu du réseau produit un `ProgressEvent` (octets du segment, total agrégé,
+//! débit lissé, temps restant), envoyé sur le canal fourni via `DownloadManager::with_progress`.
+//! Les appelants n'ont plus à scanner les fichiers `.part` sur le disque. Les mêmes événements
+//! sont publiés sur le bus du gestionnaire (`DownloadEventKind::Progress`). Un téléchargement
+//! multi‑sources y joint la santé de chaque source (`MirrorStats`).
+//!
+//! Le débit est une moyenne mobile exponentielle des octets réellement reçus, échantillonnée
+//! toutes les 250 ms avec une constante de temps de 3 s: assez réactive pour suivre un
+//! ralentissement, assez stable pour un temps restant lisible.
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use super::events::{DownloadEventKind, TaskEvents};
+use super::mirrors::{MirrorHealth, MirrorStats};
+use super::stats::DownloadStatistics;
+
+/// Intervalle minimal entre deux échantillons de débit
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+/// Constante de temps de la moyenne mobile
+const SMOOTHING: Duration = Duration::from_secs(3);
+
+/// Événement de progression d'un téléchargement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgressEvent {
+    /// Index du segment concerné (0 en mode requête unique)
+    pub chunk_index: usize,
+    /// Octets du segment présents sur disque (reprise incluse)
+    pub chunk_downloaded: u64,
+    /// Octets présents au total, tous segments confondus (reprise incluse)
+    pub total_downloaded: u64,
+    /// Taille totale annoncée par le serveur (0 si inconnue)
+    pub total_size: u64,
+    /// Débit lissé (moyenne mobile exponentielle), en octets/s
+    pub speed: u64,
+    /// Débit moyen depuis le démarrage, en octets/s (octets repris exclus)
+    pub average_speed: u64,
+    /// Temps restant estimé au débit lissé (`None` si taille ou débit inconnus)
+    pub eta: Option<Duration>,
+    /// Attente imposée par le serveur (429/503 avec `Retry-After`); `None` en transfert normal
+    pub throttled: Option<Duration>,
+    /// Santé de chaque source (vide avec une seule source)
+    pub mirrors: Vec<MirrorStats>,
+}
+
+/// Temps restant pour `remaining` octets à `speed` octets/s
+pub fn estimate_eta(remaining: u64, speed: u64) -> Option<Duration> {
+    (speed > 0).then(|| Duration::from_secs_f64(remaining as f64 / speed as f64))
+}
+
+/// Moyenne mobile exponentielle du débit, alimentée par le total d'octets reçus
+#[derive(Debug)]
+pub(crate) struct SpeedMeter {
+    last_at: Instant,
+    last_bytes: u64,
+    smoothed: Option<f64>,
+}
+
+impl SpeedMeter {
+    pub(crate) fn new(origin: Instant) -> Self {
+        Self { last_at: origin, last_bytes: 0, smoothed: None }
+    }
+
+    /// Intègre le total `bytes` reçu à l'instant `now`; rend le débit lissé (octets/s)
+    pub(crate) fn update(&mut self, bytes: u64, now: Instant) -> u64 {
+        let elapsed = now.saturating_duration_since(self.last_at);
+        if elapsed >= SAMPLE_INTERVAL {
+            let dt = elapsed.as_secs_f64();
+            let instant = bytes.saturating_sub(self.last_bytes) as f64 / dt;
+            // Pondération dépendant de l'écart réel entre échantillons (blocs irréguliers)
+            let alpha = 1.0 - (-dt / SMOOTHING.as_secs_f64()).exp();
+            self.smoothed = Some(match self.smoothed {
+                Some(previous) => previous + alpha * (instant - previous),
+                None => instant,
+            });
+            self.last_at = now;
+            self.last_bytes = bytes;
+        }
+        self.smoothed.unwrap_or(0.0) as u64
+    }
+}
+
+/// Agrège les octets reçus par les segments concurrents et publie les événements
+pub(crate) struct ProgressReporter {
+    tx: Option<mpsc::UnboundedSender<ProgressEvent>>,
+    /// Bus d'événements de la tâche
+    events: TaskEvents,
+    /// Compteurs de l'application, alimentés par les octets reçus
+    statistics: DownloadStatistics,
+    total: AtomicU64,
+    resumed: AtomicU64,
+    total_size: u64,
+    origin: Instant,
+    meter: Mutex<SpeedMeter>,
+    /// Sources suivies, pour un téléchargement multi‑sources
+    mirrors: Option<(MirrorHealth, Vec<String>)>,
+}
+
+impl ProgressReporter {
+    pub(crate) fn new(tx: Option<mpsc::UnboundedSender<ProgressEvent>>, events: TaskEvents, statistics: DownloadStatistics, total_size: u64, origin: Instant) -> Self {
+        Self { tx, events, statistics, total: AtomicU64::new(0), resumed: AtomicU64::new(0), total_size, origin, meter: Mutex::new(SpeedMeter::new(origin)), mirrors: None }
+    }
+
+    /// Joint la santé de `urls` aux événements si la tâche a plusieurs sources
+    pub(crate) fn with_mirrors(mut self, health: &MirrorHealth, urls: &[String]) -> Self {
+        if urls.len() > 1 {
+            self.mirrors = Some((health.clone(), urls.to_vec()));
+        }
+        self
+    }
+
+    fn mirror_stats(&self) -> Vec<MirrorStats> {
+        self.mirrors.as_ref().map(|(health, urls)| health.stats(urls)).unwrap_or_default()
+    }
+
+    /// Compte `bytes` déjà présents sur disque (reprise) sans les inclure dans le débit
+    pub(crate) fn add_resumed(&self, bytes: u64) {
+        self.total.fetch_add(bytes, Ordering::Relaxed);
+        self.resumed.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Octets comptés jusqu'ici (reprise incluse)
+    pub(crate) fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Ramène le total à `total` quand une tentative repart du début
+    pub(crate) fn rewind(&self, total: u64) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    /// Comptabilise `len` octets reçus pour le segment `chunk_index` (déjà `chunk_downloaded` au total)
+    pub(crate) fn record(&self, chunk_index: usize, chunk_downloaded: u64, len: u64) {
+        let total_downloaded = self.total.fetch_add(len, Ordering::Relaxed) + len;
+        self.statistics.record_bytes(len);
+        if self.tx.is_none() && !self.events.is_listened() {
+            return;
+        }
+        let elapsed = self.origin.elapsed().as_secs_f64();
+        let fresh = total_downloaded.saturating_sub(self.resumed.load(Ordering::Relaxed));
+        let average_speed = if elapsed > 0.0 { (fresh as f64 / elapsed) as u64 } else { 0 };
+        let speed = match self.meter.lock().unwrap_or_else(|e| e.into_inner()).update(fresh, Instant::now()) {
+            // Avant le premier échantillon, la moyenne globale tient lieu de débit
+            0 => average_speed,
+            smoothed => smoothed,
+        };
+        let eta = if self.total_size > 0 { estimate_eta(self.total_size.saturating_sub(total_downloaded), speed) } else { None };
+        self.publish(ProgressEvent {
+            chunk_index,
+            chunk_downloaded,
+            total_downloaded,
+            total_size: self.total_size,
+            speed,
+            average_speed,
+            eta,
+            throttled: None,
+            mirrors: self.mirror_stats(),
+        });
+    }
+
+    /// Signale que la tâche patiente `wait` à la demande du serveur (segment `chunk_index`)
+    pub(crate) fn throttled(&self, chunk_index: usize, wait: Duration) {
+        self.publish(ProgressEvent {
+            chunk_index,
+            chunk_downloaded: 0,
+            total_downloaded: self.total.load(Ordering::Relaxed),
+            total_size: self.total_size,
+            speed: 0,
+            average_speed: 0,
+            eta: None,
+            throttled: Some(wait),
+            mirrors: self.mirror_stats(),
+        });
+    }
+
+    /// Signale la fin du segment `chunk_index` après `bytes` octets reçus
+    pub(crate) fn chunk_done(&self, chunk_index: usize, bytes: u64) {
+        self.events.emit(DownloadEventKind::ChunkDone { index: chunk_index, bytes });
+    }
+
+    fn publish(&self, event: ProgressEvent) {
+        // Un récepteur fermé n'interrompt pas le téléchargement
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(event.clone());
+        }
+        self.events.emit(DownloadEventKind::Progress(event));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speed_meter_smooths_and_follows_rate_changes() {
+        let origin = Instant::now();
+        let mut meter = SpeedMeter::new(origin);
+        let at = |ms: u64| origin + Duration::from_millis(ms);
+
+        // Pas d'échantillon avant 250 ms
+        assert_eq!(meter.update(50_000, at(100)), 0);
+        // 1 Mo/s régulier pendant 3 s
+        let mut bytes = 0;
+        for step in 1..=12 {
+            bytes += 250_000;
+            meter.update(bytes, at(step * 250));
+        }
+        assert_eq!(meter.update(bytes, at(3000)), 1_000_000);
+
+        // Chute à 0: le débit décroît progressivement, sans tomber à 0 d'un coup
+        let after_drop = meter.update(bytes, at(3500));
+        assert!(after_drop > 800_000 && after_drop < 1_000_000, "{after_drop}");
+        for step in 1..=40 {
+            meter.update(bytes, at(3500 + step * 250));
+        }
+        assert!(meter.update(bytes, at(13_750)) < 50_000);
+
+        assert_eq!(estimate_eta(2_000_000, 1_000_000), Some(Duration::from_secs(2)));
+        assert_eq!(estimate_eta(2_000_000, 0), None);
+    }
+}
@@ -0,0 +1,124 @@
+//! Sort des fichiers temporaires d'un téléchargement en échec.
+//!
+//! Par défaut `<nom>.partial` et son manifeste `<nom>.scrapes.json` restent en place pour une
+//! reprise. `[cleanup] remove_on_error = true` les supprime; `quarantine_on_error = true` les
+//! déplace plutôt dans `.scrapes-incomplete/`, à côté de la sortie: le dossier de
+//! téléchargement reste propre, mais l'utilisateur peut encore inspecter les octets reçus.
+//! `restore_from_quarantine` remet la paire en place, et une nouvelle tentative reprend alors
+//! là où le téléchargement s'était arrêté.
+use std::io;
+use std::path::{Path, PathBuf};
+use super::manifest::Manifest;
+
+/// Dossier de quarantaine, créé dans le dossier de la sortie
+pub const QUARANTINE_DIR: &str = ".scrapes-incomplete";
+
+/// Traitement des fichiers temporaires après un échec
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PartialCleanup {
+    /// Laisser `.partial` et manifeste en place (reprise directe)
+    #[default]
+    Keep,
+    /// Supprimer les deux fichiers
+    Remove,
+    /// Les déplacer dans `.scrapes-incomplete/`
+    Quarantine,
+}
+
+impl PartialCleanup {
+    /// Lit `[cleanup]`: `quarantine_on_error` l'emporte sur `remove_on_error`
+    pub fn from_config(config: &super::AppConfig) -> Self {
+        let cleanup = config.cleanup.as_ref();
+        if cleanup.and_then(|c| c.quarantine_on_error).unwrap_or(false) {
+            Self::Quarantine
+        } else if cleanup.and_then(|c| c.remove_on_error).unwrap_or(false) {
+            Self::Remove
+        } else {
+            Self::Keep
+        }
+    }
+
+    /// Applique la politique aux fichiers temporaires de `output`
+    pub fn apply(self, output: &Path) {
+        match self {
+            Self::Keep => {}
+            Self::Remove => super::cleanup_temp_files_on_error(output),
+            Self::Quarantine => {
+                if let Err(e) = quarantine_temp_files(output) {
+                    tracing::warn!(file = %output.display(), error = %e, "Mise en quarantaine impossible");
+                }
+            }
+        }
+    }
+}
+
+/// Emplacement de `output` dans la quarantaine (les fichiers temporaires en dérivent)
+pub fn quarantine_path(output: &Path) -> PathBuf {
+    let dir = output.parent().unwrap_or(Path::new(""));
+    dir.join(QUARANTINE_DIR).join(output.file_name().unwrap_or_default())
+}
+
+/// Déplace le manifeste de `output` et le `.partial` qu'il décrit dans la quarantaine, en
+/// remplaçant une paire plus ancienne du même nom; rend l'emplacement en quarantaine, ou `None`
+/// sans manifeste
+pub fn quarantine_temp_files(output: &Path) -> io::Result<Option<PathBuf>> {
+    if !Manifest::path_for(output).exists() {
+        return Ok(None);
+    }
+    let quarantined = quarantine_path(output);
+    if let Some(dir) = quarantined.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    move_pair(output, &quarantined)?;
+    tracing::info!(file = %output.display(), to = %quarantined.display(), "Fichiers temporaires mis en quarantaine");
+    Ok(Some(quarantined))
+}
+
+/// Remet en place la paire mise en quarantaine pour `output`, sauf si un manifeste s'y trouve
+/// déjà; `true` si des fichiers ont été restaurés
+pub fn restore_from_quarantine(output: &Path) -> io::Result<bool> {
+    let quarantined = quarantine_path(output);
+    if !Manifest::path_for(&quarantined).exists() || Manifest::path_for(output).exists() {
+        return Ok(false);
+    }
+    move_pair(&quarantined, output)?;
+    tracing::info!(file = %output.display(), "Fichiers temporaires restaurés depuis la quarantaine");
+    Ok(true)
+}
+
+/// Renomme le `.partial` (s'il existe) puis le manifeste de `from` vers ceux de `to`
+fn move_pair(from: &Path, to: &Path) -> io::Result<()> {
+    let partial = Manifest::partial_path_for(from);
+    if partial.exists() {
+        std::fs::rename(&partial, Manifest::partial_path_for(to))?;
+    }
+    std::fs::rename(Manifest::path_for(from), Manifest::path_for(to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarantine_and_restore_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("episode.mkv");
+        std::fs::write(Manifest::path_for(&output), b"{}").unwrap();
+        std::fs::write(Manifest::partial_path_for(&output), b"octets").unwrap();
+
+        let config: super::super::AppConfig = toml::from_str("[cleanup]\nremove_on_error = true\nquarantine_on_error = true").unwrap();
+        assert_eq!(PartialCleanup::from_config(&config), PartialCleanup::Quarantine);
+        assert_eq!(PartialCleanup::from_config(&Default::default()), PartialCleanup::Keep);
+        PartialCleanup::Quarantine.apply(&output);
+
+        let quarantined = dir.path().join(QUARANTINE_DIR).join("episode.mkv");
+        assert!(!Manifest::path_for(&output).exists() && !Manifest::partial_path_for(&output).exists());
+        assert_eq!(std::fs::read(Manifest::partial_path_for(&quarantined)).unwrap(), b"octets");
+        assert_eq!(quarantine_temp_files(&output).unwrap(), None);
+
+        assert!(restore_from_quarantine(&output).unwrap());
+        assert_eq!(std::fs::read(Manifest::partial_path_for(&output)).unwrap(), b"octets");
+        assert!(Manifest::path_for(&output).exists());
+        assert!(!restore_from_quarantine(&output).unwrap());
+    }
+}
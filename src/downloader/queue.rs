@@ -0,0 +1,308 @@
+//! File d'attente des téléchargements: au plus `max_active` transferts simultanés.
+//!
+//! Chaque téléchargement demande une place (`acquire`) avant de démarrer et la rend en fin de
+//! transfert (`QueueSlot` libère la place à la destruction). Les demandes sont servies par
+//! priorité décroissante, puis dans l'ordre d'arrivée; `bump` fait passer une demande en tête.
+//! La file est partagée (clonage bon marché) entre la GUI et tout autre frontal; elle ne dépend
+//! d'aucun runtime tokio particulier.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+const DEFAULT_MAX_ACTIVE: usize = 3;
+static GLOBAL: OnceLock<DownloadQueue> = OnceLock::new();
+/// Fréquence de contrôle du drapeau d'annulation pendant l'attente
+const CANCEL_POLL: Duration = Duration::from_millis(200);
+
+/// Priorité d'un téléchargement dans la file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    id: u64,
+    seq: u64,
+    priority: Priority,
+}
+
+#[derive(Debug)]
+struct QueueState {
+    max_active: usize,
+    active: usize,
+    next_seq: u64,
+    /// Demandes en attente, dans l'ordre où elles seront servies
+    waiting: Vec<Waiter>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: Mutex<QueueState>,
+    notify: Notify,
+}
+
+/// File partagée limitant le nombre de téléchargements actifs
+#[derive(Debug, Clone)]
+pub struct DownloadQueue {
+    inner: Arc<Inner>,
+}
+
+impl Default for DownloadQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ACTIVE)
+    }
+}
+
+impl DownloadQueue {
+    /// File autorisant `max_active` téléchargements simultanés (au moins 1)
+    pub fn new(max_active: usize) -> Self {
+        let state = QueueState { max_active: max_active.max(1), active: 0, next_seq: 0, waiting: Vec::new() };
+        Self { inner: Arc::new(Inner { state: Mutex::new(state), notify: Notify::new() }) }
+    }
+
+    /// Lit `[queue] max_active_downloads` (3 par défaut)
+    pub fn from_config(config: &super::AppConfig) -> Self {
+        Self::new(config.queue.as_ref().and_then(|q| q.max_active_downloads).unwrap_or(DEFAULT_MAX_ACTIVE))
+    }
+
+    /// File globale de l'application, initialisée depuis scrapes.toml au premier appel
+    pub fn global() -> Self {
+        GLOBAL.get_or_init(|| Self::from_config(&super::load_config())).clone()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.inner.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn max_active(&self) -> usize {
+        self.state().max_active
+    }
+
+    /// Change la limite; une hausse démarre aussitôt les demandes en attente
+    pub fn set_max_active(&self, max_active: usize) {
+        self.state().max_active = max_active.max(1);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Téléchargements en cours
+    pub fn active(&self) -> usize {
+        self.state().active
+    }
+
+    /// Demandes en attente d'une place
+    pub fn queued(&self) -> usize {
+        self.state().waiting.len()
+    }
+
+    /// Attend une place pour le téléchargement `id`, derrière les demandes de priorité égale
+    /// ou supérieure
+    pub async fn acquire(&self, id: u64, priority: Priority) -> QueueSlot {
+        let seq = {
+            let mut state = self.state();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.enqueue(Waiter { id, seq, priority });
+            seq
+        };
+        // Retire la demande si l'attente est abandonnée (future détruite)
+        let mut pending = PendingWaiter { queue: self, seq: Some(seq) };
+        loop {
+            let notified = self.inner.notify.notified();
+            tokio::pin!(notified);
+            // S'inscrire avant de lire l'état: aucune libération ne peut être manquée
+            notified.as_mut().enable();
+            if self.try_grant(seq) {
+                pending.seq = None;
+                tracing::debug!(id, "Place accordée dans la file de téléchargement");
+                return QueueSlot { queue: self.clone() };
+            }
+            notified.await;
+        }
+    }
+
+    /// Comme `acquire`, mais abandonne (`None`) dès que `cancel` passe à vrai (pause, annulation)
+    pub async fn acquire_cancellable(&self, id: u64, priority: Priority, cancel: &AtomicBool) -> Option<QueueSlot> {
+        let acquire = self.acquire(id, priority);
+        tokio::pin!(acquire);
+        loop {
+            tokio::select! {
+                slot = &mut acquire => return Some(slot),
+                _ = tokio::time::sleep(CANCEL_POLL) => {
+                    if cancel.load(Ordering::Relaxed) {
+                        tracing::debug!(id, "Attente dans la file abandonnée");
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Accorde une place à `seq` s'il est en tête de file et qu'une place est libre
+    fn try_grant(&self, seq: u64) -> bool {
+        let mut state = self.state();
+        let first = state.waiting.first().map(|w| w.seq);
+        if state.active >= state.max_active || first != Some(seq) {
+            return false;
+        }
+        state.waiting.remove(0);
+        state.active += 1;
+        drop(state);
+        // La demande suivante peut aussi avoir une place libre
+        self.inner.notify.notify_waiters();
+        true
+    }
+
+    fn release(&self) {
+        let mut state = self.state();
+        state.active = state.active.saturating_sub(1);
+        drop(state);
+        self.inner.notify.notify_waiters();
+    }
+
+    fn withdraw(&self, seq: u64) {
+        let mut state = self.state();
+        state.waiting.retain(|w| w.seq != seq);
+        drop(state);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Identifiants en attente, dans l'ordre de service
+    pub fn waiting_ids(&self) -> Vec<u64> {
+        self.state().waiting.iter().map(|w| w.id).collect()
+    }
+
+    /// Change la priorité d'une demande en attente (replacée derrière celles de même rang);
+    /// `false` si `id` n'attend pas
+    pub fn set_priority(&self, id: u64, priority: Priority) -> bool {
+        let mut state = self.state();
+        let Some(index) = state.waiting.iter().position(|w| w.id == id) else {
+            return false;
+        };
+        let mut waiter = state.waiting.remove(index);
+        waiter.priority = priority;
+        state.enqueue(waiter);
+        drop(state);
+        self.inner.notify.notify_waiters();
+        true
+    }
+
+    /// Place une demande en attente en tête de file, avec la priorité haute pour que les
+    /// demandes hautes suivantes restent derrière elle; `false` si `id` n'attend pas
+    pub fn bump(&self, id: u64) -> bool {
+        let mut state = self.state();
+        let Some(index) = state.waiting.iter().position(|w| w.id == id) else {
+            return false;
+        };
+        let mut waiter = state.waiting.remove(index);
+        waiter.priority = Priority::High;
+        state.waiting.insert(0, waiter);
+        drop(state);
+        self.inner.notify.notify_waiters();
+        tracing::debug!(id, "Téléchargement placé en tête de file");
+        true
+    }
+}
+
+impl QueueState {
+    /// Insère après toutes les demandes de priorité supérieure ou égale
+    fn enqueue(&mut self, waiter: Waiter) {
+        let index = self.waiting.iter().position(|w| w.priority < waiter.priority).unwrap_or(self.waiting.len());
+        self.waiting.insert(index, waiter);
+    }
+}
+
+struct PendingWaiter<'a> {
+    queue: &'a DownloadQueue,
+    seq: Option<u64>,
+}
+
+impl Drop for PendingWaiter<'_> {
+    fn drop(&mut self) {
+        if let Some(seq) = self.seq {
+            self.queue.withdraw(seq);
+        }
+    }
+}
+
+/// Place occupée dans la file; rendue à la destruction
+#[derive(Debug)]
+pub struct QueueSlot {
+    queue: DownloadQueue,
+}
+
+impl Drop for QueueSlot {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[tokio::test]
+    async fn test_queue_limits_active_and_orders_by_priority() {
+        let queue = DownloadQueue::new(2);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let mut handles = Vec::new();
+        for id in 0..5u64 {
+            let (queue, running, peak, order) = (queue.clone(), running.clone(), peak.clone(), order.clone());
+            handles.push(tokio::spawn(async move {
+                let _slot = queue.acquire(id, Priority::Normal).await;
+                order.lock().unwrap().push(id);
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+            }));
+            // Garantir l'ordre d'arrivée des demandes
+            tokio::time::sleep(Duration::from_millis(2)).await;
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+        assert_eq!((queue.active(), queue.queued()), (0, 0));
+
+        // Une attente annulée libère sa position
+        let held = queue.acquire(10, Priority::Normal).await;
+        let _held2 = queue.acquire(11, Priority::Normal).await;
+        let cancel = AtomicBool::new(true);
+        assert!(queue.acquire_cancellable(12, Priority::Normal, &cancel).await.is_none());
+        assert_eq!(queue.queued(), 0);
+        drop(held);
+        assert_eq!(queue.active(), 1);
+
+        // Priorités: haute avant normale avant basse, puis ordre d'arrivée; `bump` passe devant
+        let queue = DownloadQueue::new(1);
+        let held = queue.acquire(0, Priority::Normal).await;
+        let mut waiting = Vec::new();
+        for (id, priority) in [(1, Priority::Low), (2, Priority::Normal), (3, Priority::High), (4, Priority::Normal)] {
+            let queue = queue.clone();
+            waiting.push(tokio::spawn(async move { drop(queue.acquire(id, priority).await) }));
+            tokio::time::sleep(Duration::from_millis(2)).await;
+        }
+        assert_eq!(queue.waiting_ids(), vec![3, 2, 4, 1]);
+        assert!(queue.bump(1));
+        assert!(queue.set_priority(4, Priority::High));
+        assert_eq!(queue.waiting_ids(), vec![1, 3, 4, 2]);
+        assert!(!queue.bump(42));
+        drop(held);
+        for handle in waiting {
+            handle.await.unwrap();
+        }
+        assert_eq!(queue.queued(), 0);
+    }
+}
@@ -0,0 +1,149 @@
+//! Reprise des téléchargements interrompus, au démarrage de l'application.
+//!
+//! `scan_incomplete` parcourt un dossier à la recherche des manifestes `<nom>.scrapes.json`
+//! dont le `<nom>.partial` existe encore, et ne retient que ceux qui décrivent un état cohérent:
+//! segments contigus, offsets dans leurs bornes, fichier partiel à la taille annoncée. Un
+//! manifeste incohérent est signalé et laissé de côté plutôt que de reprendre sur des octets
+//! douteux. `resume_all` relance ensuite chaque téléchargement retenu.
+//!
+//! Le manifeste ne conserve ni miroirs, ni empreintes, ni en‑têtes: la reprise repart de l'URL
+//! principale seule. Un frontal qui les a conservés (historique de la GUI) reconstruit plutôt
+//! ses propres tâches et se sert de `check_resumable` pour valider l'avancement affiché.
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use futures::future;
+use super::manager::DownloadManager;
+use super::manifest::{Manifest, ManifestSummary, MANIFEST_SUFFIX};
+use super::types::{DownloadStats, DownloadTask};
+
+/// Téléchargement interrompu dont l'état sur disque a été validé
+#[derive(Debug, Clone)]
+pub struct IncompleteDownload {
+    /// Nom final du fichier (sans `.partial`)
+    pub output: PathBuf,
+    pub manifest: Manifest,
+}
+
+impl IncompleteDownload {
+    pub fn summary(&self) -> ManifestSummary {
+        self.manifest.summary()
+    }
+
+    /// Tâche reprenant le téléchargement avec le découpage du manifeste
+    #[allow(dead_code)]
+    pub fn task(&self) -> Result<DownloadTask> {
+        DownloadTask::builder(self.manifest.url.clone())
+            .with_output(self.output.clone())
+            .with_chunk_size(self.manifest.chunk_size)
+            .with_total_size(self.manifest.total_size)
+            .build()
+    }
+}
+
+/// Valide l'état laissé par un téléchargement de `output` interrompu; `None` s'il n'y a rien à
+/// reprendre (pas de manifeste ni de `.partial`) ou si l'état est incohérent
+pub fn check_resumable(output: &Path) -> Option<IncompleteDownload> {
+    let partial = Manifest::partial_path_for(output);
+    let on_disk = std::fs::metadata(&partial).ok()?.len();
+    let manifest = Manifest::load(output)?;
+    let problem = if manifest.total_size == 0 || !manifest.is_contiguous() {
+        Some("segments incohérents")
+    } else if manifest.chunks.iter().any(|c| c.downloaded > c.len()) {
+        Some("offset hors de son segment")
+    } else if on_disk != manifest.total_size {
+        Some("taille du fichier partiel différente")
+    } else {
+        None
+    };
+    if let Some(problem) = problem {
+        tracing::warn!(file = %output.display(), on_disk, total_size = manifest.total_size, problem, "Reprise impossible, état ignoré");
+        return None;
+    }
+    Some(IncompleteDownload { output: output.to_path_buf(), manifest })
+}
+
+/// Téléchargements interrompus reprenables dans `dir` (non récursif), triés par nom
+pub fn scan_incomplete(dir: &Path) -> Vec<IncompleteDownload> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::debug!(dir = %dir.display(), error = %e, "Dossier de téléchargement illisible");
+            return Vec::new();
+        }
+    };
+    let mut found: Vec<IncompleteDownload> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().strip_suffix(MANIFEST_SUFFIX)?.to_string();
+            check_resumable(&dir.join(name))
+        })
+        .collect();
+    found.sort_by(|a, b| a.output.cmp(&b.output));
+    tracing::info!(dir = %dir.display(), count = found.len(), "Téléchargements interrompus trouvés");
+    found
+}
+
+/// Reprend en parallèle tous les téléchargements interrompus de `dir` avec `manager`; rend le
+/// résultat de chacun, nom final à l'appui
+#[allow(dead_code)]
+pub async fn resume_all(manager: &DownloadManager, dir: &Path) -> Vec<(PathBuf, Result<DownloadStats>)> {
+    let runs = scan_incomplete(dir).into_iter().map(|incomplete| async move {
+        let result = match incomplete.task() {
+            Ok(task) => manager.start(task).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = &result {
+            tracing::warn!(file = %incomplete.output.display(), error = %e, "Reprise échouée");
+        }
+        (incomplete.output, result)
+    });
+    future::join_all(runs).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use url::Url;
+    use crate::downloader::OfflineMode;
+
+    #[tokio::test]
+    async fn test_scan_validates_and_resume_all_completes() {
+        let dir = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0u8..=255).cycle().take(12 * 1024).collect();
+        let source = dir.path().join("source.bin");
+        std::fs::write(&source, &data).unwrap();
+        let url = Url::from_file_path(&source).unwrap().to_string();
+
+        // Interruption au milieu du deuxième segment: octets écrits et offsets cohérents
+        let interrupted = |name: &str, partial_len: u64| {
+            let output = dir.path().join(name);
+            let task = DownloadTask::builder(url.clone()).with_output(&output).with_chunk_size(4096).with_total_size(data.len() as u64).build().unwrap();
+            let mut manifest = Manifest::new(&task, &task.create_chunks(), None);
+            manifest.chunks[0].downloaded = 4096;
+            manifest.chunks[1].downloaded = 1000;
+            manifest.save(&output).unwrap();
+            let mut partial = data[..5096].to_vec();
+            partial.resize(partial_len as usize, 0);
+            std::fs::write(Manifest::partial_path_for(&output), partial).unwrap();
+            output
+        };
+        let good = interrupted("good.bin", data.len() as u64);
+        interrupted("truncated.bin", 5096);
+        // Terminé, manifeste conservé: rien à reprendre
+        let done = interrupted("done.bin", data.len() as u64);
+        std::fs::rename(Manifest::partial_path_for(&done), &done).unwrap();
+
+        let found = scan_incomplete(dir.path());
+        assert_eq!(found.iter().map(|f| f.output.as_path()).collect::<Vec<_>>(), [good.as_path()]);
+        assert_eq!(found[0].summary().downloaded, 5096);
+
+        let manager = DownloadManager::new().with_offline_mode(OfflineMode::new());
+        let results = resume_all(&manager, dir.path()).await;
+        assert_eq!(results.len(), 1);
+        let stats = results[0].1.as_ref().unwrap();
+        // Seuls les octets manquants ont été copiés
+        assert_eq!(stats.total_bytes(), (data.len() - 5096) as u64);
+        assert_eq!(std::fs::read(&good).unwrap(), data);
+        assert!(scan_incomplete(dir.path()).is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+//! Nouvelles tentatives par segment avec délai exponentiel et gigue.
+//!
+//! Une erreur transitoire (5xx, 429, connexion réinitialisée, corps tronqué) sur un segment
+//! relance uniquement ce segment, à partir de l'offset déjà écrit. Les erreurs définitives
+//! (4xx, disque plein, mode hors ligne) échouent immédiatement.
+//!
+//! Un serveur saturé (429, 503) est signalé par l'erreur typée `Throttled`; son délai
+//! `Retry-After` remplace alors le délai exponentiel et suspend toute la tâche (`BusyGate`),
+//! pas seulement le segment qui l'a reçu.
+//!
+//! Une connexion qui ne transmet plus rien sans se fermer bloquerait son segment, et avec lui
+//! tout le téléchargement: passé `stall_timeout` sans octet reçu (ni en‑têtes à l'ouverture),
+//! la tentative échoue en `TimedOut` et le segment est relancé comme après une coupure.
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+const DEFAULT_JITTER: f64 = 0.2;
+/// Délai sans octet reçu au‑delà duquel une connexion est jugée bloquée
+pub(crate) const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(60);
+/// Plafond appliqué à `Retry-After` (un serveur peut demander plusieurs heures)
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(10 * 60);
+
+/// Politique de nouvelles tentatives appliquée à chaque segment
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Nombre total de tentatives, première incluse (1 = pas de nouvelle tentative)
+    pub max_attempts: u32,
+    /// Délai avant la deuxième tentative; doublé à chaque échec suivant
+    pub initial_backoff: Duration,
+    /// Plafond du délai entre deux tentatives
+    pub max_backoff: Duration,
+    /// Variation aléatoire relative du délai (0.2 = ±20 %)
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            jitter: DEFAULT_JITTER,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Construit la politique depuis la section `[retry]` (valeurs par défaut sinon)
+    pub fn from_config(config: &super::AppConfig) -> Self {
+        let mut policy = Self::default();
+        if let Some(retry) = &config.retry {
+            if let Some(attempts) = retry.max_attempts {
+                policy.max_attempts = attempts.max(1);
+            }
+            if let Some(ms) = retry.initial_backoff_ms {
+                policy.initial_backoff = Duration::from_millis(ms);
+            }
+            if let Some(ms) = retry.max_backoff_ms {
+                policy.max_backoff = Duration::from_millis(ms);
+            }
+            if let Some(jitter) = retry.jitter {
+                policy.jitter = jitter.clamp(0.0, 1.0);
+            }
+        }
+        policy
+    }
+
+    /// Délai sans gigue après l'échec numéro `attempt` (à partir de 1)
+    fn base_delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+
+    /// Délai à attendre après l'échec numéro `attempt`, gigue comprise
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let base = self.base_delay(attempt);
+        // Tirage uniforme dans [-jitter, +jitter] sans dépendance à une crate d'aléa
+        let unit = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+        let factor = 1.0 + self.jitter * (2.0 * unit - 1.0);
+        base.mul_f64(factor.max(0.0))
+    }
+}
+
+/// Lit `[retry] stall_timeout_secs` (60 s par défaut, 0 désactive la détection)
+pub fn stall_timeout_from_config(config: &super::AppConfig) -> Option<Duration> {
+    match config.retry.as_ref().and_then(|r| r.stall_timeout_secs) {
+        Some(0) => None,
+        Some(secs) => Some(Duration::from_secs(secs)),
+        None => Some(DEFAULT_STALL_TIMEOUT),
+    }
+}
+
+/// Attend `future` au plus `stall` (sans limite si `None`); au‑delà, la connexion est jugée
+/// bloquée et l'erreur `TimedOut` rend la tentative relançable
+pub(crate) async fn within_stall<T>(stall: Option<Duration>, future: impl Future<Output = anyhow::Result<T>>) -> anyhow::Result<T> {
+    let Some(stall) = stall else {
+        return future.await;
+    };
+    match tokio::time::timeout(stall, future).await {
+        Ok(result) => result,
+        Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut, format!("connexion bloquée: aucun octet reçu depuis {} s", stall.as_secs_f32())).into()),
+    }
+}
+
+/// Réponse 429 ou 503: le serveur demande de patienter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Throttled {
+    pub status: u16,
+    /// Délai demandé par `Retry-After` (plafonné), s'il est présent et lisible
+    pub retry_after: Option<Duration>,
+}
+
+impl fmt::Display for Throttled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.retry_after {
+            Some(wait) => write!(f, "Serveur occupé (HTTP {}), nouvel essai dans {} s", self.status, wait.as_secs()),
+            None => write!(f, "Serveur occupé (HTTP {})", self.status),
+        }
+    }
+}
+
+impl std::error::Error for Throttled {}
+
+/// `Retry-After`: nombre de secondes ou date HTTP (`Wed, 21 Oct 2015 07:28:00 GMT`)
+pub fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    let wait = match value.parse::<u64>() {
+        Ok(secs) => Duration::from_secs(secs),
+        Err(_) => httpdate::parse_http_date(value).ok()?.duration_since(now).unwrap_or(Duration::ZERO),
+    };
+    Some(wait.min(MAX_RETRY_AFTER))
+}
+
+/// Suspension partagée par les segments d'une tâche: après un `Retry-After`, aucune connexion
+/// ne relance de requête avant l'échéance
+#[derive(Debug, Default)]
+pub(crate) struct BusyGate {
+    until: Mutex<Option<Instant>>,
+}
+
+impl BusyGate {
+    /// Repousse l'échéance à `now + wait` (jamais en arrière)
+    pub(crate) fn hold(&self, wait: Duration) {
+        let deadline = Instant::now() + wait;
+        let mut until = self.until.lock().unwrap_or_else(|e| e.into_inner());
+        if until.is_none_or(|u| u < deadline) {
+            *until = Some(deadline);
+        }
+    }
+
+    /// Attend l'échéance en cours, s'il y en a une
+    pub(crate) async fn wait(&self) {
+        let until = *self.until.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(until) = until {
+            tokio::time::sleep_until(until.into()).await;
+        }
+    }
+}
+
+/// Indique si l'erreur d'un segment est transitoire et mérite une nouvelle tentative
+pub fn is_retryable(err: &anyhow::Error) -> bool {
+    for cause in err.chain() {
+        if cause.downcast_ref::<Throttled>().is_some() {
+            return true;
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return match e.status() {
+                Some(status) => status.is_server_error() || status.as_u16() == 429,
+                None => e.is_timeout() || e.is_connect() || e.is_request() || e.is_body(),
+            };
+        }
+        if let Some(e) = cause.downcast_ref::<io::Error>() {
+            return matches!(
+                e.kind(),
+                io::ErrorKind::UnexpectedEof
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::BrokenPipe
+            );
+        }
+    }
+    false
+}
+
+/// Indique si l'erreur tient à la source (statut HTTP, réseau): un autre miroir peut réussir
+pub fn is_source_error(err: &anyhow::Error) -> bool {
+    is_retryable(err)
+        || err
+            .chain()
+            .any(|cause| cause.downcast_ref::<reqwest::Error>().is_some_and(|e| e.status().is_some()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_grows_exponentially_within_jitter_and_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1_000),
+            jitter: 0.2,
+        };
+        assert_eq!(policy.base_delay(1), Duration::from_millis(100));
+        assert_eq!(policy.base_delay(3), Duration::from_millis(400));
+        assert_eq!(policy.base_delay(8), Duration::from_millis(1_000));
+        for attempt in 1..=8 {
+            let base = policy.base_delay(attempt);
+            let delay = policy.delay(attempt);
+            assert!(delay >= base.mul_f64(0.8) && delay <= base.mul_f64(1.2), "{attempt}: {delay:?}");
+        }
+
+        let err = anyhow::Error::new(io::Error::new(io::ErrorKind::ConnectionReset, "reset")).context("chunk 0");
+        assert!(is_retryable(&err));
+        let err = anyhow::Error::new(io::Error::new(io::ErrorKind::PermissionDenied, "denied"));
+        assert!(!is_retryable(&err));
+        assert!(!is_retryable(&anyhow::anyhow!("Espace disque insuffisant")));
+
+        let now = httpdate::parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+        assert_eq!(parse_retry_after(" 30 ", now), Some(Duration::from_secs(30)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:29:30 GMT", now), Some(Duration::from_secs(90)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("86400", now), Some(MAX_RETRY_AFTER));
+        assert_eq!(parse_retry_after("bientôt", now), None);
+        assert!(is_retryable(&anyhow::Error::new(Throttled { status: 429, retry_after: None }).context("chunk 2")));
+    }
+}
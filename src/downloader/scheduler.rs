@@ -0,0 +1,331 @@
+//! Découpage adaptatif des plages restantes selon le débit mesuré (à la manière d'aria2).
+//!
+//! Les segments issus de `DownloadTask::chunk_size` ne sont qu'un point de départ: avant de
+//! confier une plage à une connexion, `ChunkScheduler` fusionne les segments voisins encore
+//! vierges ou redécoupe un segment trop grand, pour viser `TARGET_SEGMENT_DURATION` au débit
+//! mesuré par connexion (bornes `[chunking]` de scrapes.toml). Une connexion sans travail coupe
+//! en deux la plage active la plus longue à terminer. Chaque changement de découpage est reporté
+//! dans le manifeste de reprise.
+use std::collections::VecDeque;
+use std::io;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use super::manifest::{ChunkState, ManifestStore};
+use super::types::Chunk;
+use super::AppConfig;
+
+const DEFAULT_MIN_CHUNK_SIZE: u64 = 1024 * 1024; // 1 MiB
+const DEFAULT_MAX_CHUNK_SIZE: u64 = 64 * 1024 * 1024; // 64 MiB
+/// Durée visée pour un segment au débit mesuré d'une connexion
+const TARGET_SEGMENT_DURATION: Duration = Duration::from_secs(5);
+/// Poids d'un segment terminé dans la moyenne glissante du débit par connexion
+const THROUGHPUT_SMOOTHING: f64 = 0.3;
+
+/// Bornes du découpage adaptatif
+#[derive(Debug, Clone)]
+pub struct AdaptiveChunking {
+    /// Taille minimale d'un segment (octets); aucune coupe ne descend en dessous
+    pub min_chunk_size: u64,
+    /// Taille maximale d'un segment (octets) après fusion
+    pub max_chunk_size: u64,
+}
+
+impl Default for AdaptiveChunking {
+    fn default() -> Self {
+        Self { min_chunk_size: DEFAULT_MIN_CHUNK_SIZE, max_chunk_size: DEFAULT_MAX_CHUNK_SIZE }
+    }
+}
+
+impl AdaptiveChunking {
+    /// Construit les bornes depuis la section `[chunking]`; `None` si `adaptive = false`
+    pub fn from_config(config: &AppConfig) -> Option<Self> {
+        let mut sizing = Self::default();
+        if let Some(chunking) = &config.chunking {
+            if chunking.adaptive == Some(false) {
+                return None;
+            }
+            if let Some(kib) = chunking.min_chunk_size_kib {
+                sizing.min_chunk_size = (kib * 1024).max(1);
+            }
+            if let Some(kib) = chunking.max_chunk_size_kib {
+                sizing.max_chunk_size = kib * 1024;
+            }
+        }
+        Some(sizing)
+    }
+
+    fn clamp(&self, size: u64) -> u64 {
+        size.clamp(self.min_chunk_size, self.max_chunk_size.max(self.min_chunk_size))
+    }
+}
+
+fn chunk_len(chunk: &Chunk) -> u64 {
+    chunk.end - chunk.start + 1
+}
+
+fn remaining(chunk: &Chunk) -> u64 {
+    chunk_len(chunk).saturating_sub(chunk.downloaded)
+}
+
+/// Plage attribuée à une connexion
+#[derive(Debug)]
+struct ActiveRange {
+    index: usize,
+    start: u64,
+    end: u64,
+    /// Octets réservés depuis `start` (écrits ou en cours d'écriture)
+    claimed: u64,
+    /// Octets reçus depuis l'attribution, pour la mesure du débit
+    received: u64,
+    since: Instant,
+}
+
+impl ActiveRange {
+    fn remaining(&self) -> u64 {
+        (self.end - self.start + 1).saturating_sub(self.claimed)
+    }
+
+    /// Temps restant estimé au débit observé; une plage sans mesure passe pour la plus lente
+    fn eta(&self) -> f64 {
+        let secs = self.since.elapsed().as_secs_f64();
+        if self.received == 0 || secs <= 0.0 {
+            return f64::INFINITY;
+        }
+        self.remaining() as f64 * secs / self.received as f64
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    /// Segments restants, triés par `start`
+    pending: VecDeque<Chunk>,
+    active: Vec<ActiveRange>,
+    next_index: usize,
+    /// Débit moyen par connexion (octets/s), `None` avant le premier segment terminé
+    throughput: Option<f64>,
+}
+
+/// Distribue les plages restantes aux connexions d'un téléchargement
+pub(crate) struct ChunkScheduler {
+    /// `None`: segments fixes, distribués tels quels
+    adaptive: Option<AdaptiveChunking>,
+    connections: usize,
+    state: Mutex<State>,
+}
+
+impl ChunkScheduler {
+    pub(crate) fn new(chunks: Vec<Chunk>, adaptive: Option<AdaptiveChunking>, connections: usize) -> Self {
+        let next_index = chunks.iter().map(|c| c.index + 1).max().unwrap_or(0);
+        let pending = chunks.into_iter().filter(|c| remaining(c) > 0).collect();
+        Self {
+            adaptive,
+            connections: connections.max(1),
+            state: Mutex::new(State { pending, active: Vec::new(), next_index, throughput: None }),
+        }
+    }
+
+    /// Prochaine plage à télécharger, redimensionnée selon le débit mesuré; `None` quand il ne
+    /// reste rien à attribuer. Le manifeste reflète le nouveau découpage avant le retour.
+    pub(crate) fn next(&self, manifest: &ManifestStore) -> io::Result<Option<Chunk>> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let chunk = match (state.pending.pop_front(), &self.adaptive) {
+            (Some(chunk), None) => chunk,
+            (Some(chunk), Some(sizing)) => self.resize(&mut state, chunk, sizing, manifest)?,
+            (None, Some(sizing)) => match steal(&mut state, sizing, manifest)? {
+                Some(chunk) => chunk,
+                None => return Ok(None),
+            },
+            (None, None) => return Ok(None),
+        };
+        state.active.push(ActiveRange {
+            index: chunk.index,
+            start: chunk.start,
+            end: chunk.end,
+            claimed: chunk.downloaded,
+            received: 0,
+            since: Instant::now(),
+        });
+        Ok(Some(chunk))
+    }
+
+    /// Réserve jusqu'à `len` octets du segment `index` au‑delà de `downloaded` et retourne le
+    /// nombre d'octets à écrire, réduit si une autre connexion a repris la fin de la plage.
+    pub(crate) fn claim(&self, index: usize, downloaded: u64, len: u64) -> u64 {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(range) = state.active.iter_mut().find(|r| r.index == index) else {
+            return len;
+        };
+        let allowed = len.min((range.end - range.start + 1).saturating_sub(downloaded));
+        range.claimed = downloaded + allowed;
+        range.received += allowed;
+        allowed
+    }
+
+    /// Borne de fin actuelle du segment actif `index` (réduite après une coupe)
+    pub(crate) fn end_of(&self, index: usize) -> Option<u64> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.active.iter().find(|r| r.index == index).map(|r| r.end)
+    }
+
+    /// Libère le segment `index`; un succès alimente la mesure du débit par connexion
+    pub(crate) fn finish(&self, index: usize, success: bool) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(pos) = state.active.iter().position(|r| r.index == index) else {
+            return;
+        };
+        let range = state.active.swap_remove(pos);
+        let secs = range.since.elapsed().as_secs_f64();
+        if !success || range.received == 0 || secs <= 0.0 {
+            return;
+        }
+        let measured = range.received as f64 / secs;
+        let smoothed = match state.throughput {
+            Some(previous) => previous + THROUGHPUT_SMOOTHING * (measured - previous),
+            None => measured,
+        };
+        state.throughput = Some(smoothed);
+        tracing::debug!(index, bytes_per_sec = smoothed as u64, "Débit par connexion mesuré");
+    }
+
+    /// Taille visée pour la prochaine plage: `TARGET_SEGMENT_DURATION` au débit mesuré, ou une
+    /// part égale du reste par connexion tant qu'aucune mesure n'existe.
+    fn target_size(&self, state: &State, current: &Chunk, sizing: &AdaptiveChunking) -> u64 {
+        let size = match state.throughput {
+            Some(bytes_per_sec) => (bytes_per_sec * TARGET_SEGMENT_DURATION.as_secs_f64()) as u64,
+            None => {
+                let left = remaining(current) + state.pending.iter().map(remaining).sum::<u64>();
+                left / self.connections as u64
+            }
+        };
+        sizing.clamp(size)
+    }
+
+    /// Fusionne `chunk` avec les segments vierges qui le suivent, ou le coupe, selon `target_size`
+    fn resize(&self, state: &mut State, mut chunk: Chunk, sizing: &AdaptiveChunking, manifest: &ManifestStore) -> io::Result<Chunk> {
+        let target = self.target_size(state, &chunk, sizing);
+        let mut absorbed = Vec::new();
+        while state.pending.front().is_some_and(|next| {
+            next.start == chunk.end + 1 && next.downloaded == 0 && remaining(&chunk) + chunk_len(next) <= target
+        }) {
+            if let Some(next) = state.pending.pop_front() {
+                chunk.end = next.end;
+                absorbed.push(next.index);
+            }
+        }
+
+        let mut tail = None;
+        if remaining(&chunk) >= target + sizing.min_chunk_size {
+            let split = Chunk {
+                index: state.next_index,
+                start: chunk.start + chunk.downloaded + target,
+                end: chunk.end,
+                downloaded: 0,
+            };
+            state.next_index += 1;
+            chunk.end = split.start - 1;
+            tail = Some(split.clone());
+            state.pending.push_front(split);
+        }
+
+        if absorbed.is_empty() && tail.is_none() {
+            return Ok(chunk);
+        }
+        tracing::debug!(index = chunk.index, start = chunk.start, end = chunk.end, target, merged = absorbed.len(), split = tail.is_some(), "Segment redimensionné");
+        reshape(manifest, &chunk, &absorbed, tail.as_ref())?;
+        Ok(chunk)
+    }
+}
+
+/// Coupe en deux la plage active la plus longue à terminer; la seconde moitié est retournée
+fn steal(state: &mut State, sizing: &AdaptiveChunking, manifest: &ManifestStore) -> io::Result<Option<Chunk>> {
+    let Some(victim) = state
+        .active
+        .iter_mut()
+        .filter(|r| r.remaining() >= 2 * sizing.min_chunk_size)
+        .max_by(|a, b| a.eta().total_cmp(&b.eta()))
+    else {
+        return Ok(None);
+    };
+    let left = victim.remaining();
+    let stolen = Chunk {
+        index: state.next_index,
+        start: victim.start + victim.claimed + (left - left / 2),
+        end: victim.end,
+        downloaded: 0,
+    };
+    victim.end = stolen.start - 1;
+    let kept = Chunk { index: victim.index, start: victim.start, end: victim.end, downloaded: victim.claimed };
+    state.next_index += 1;
+    tracing::debug!(from = kept.index, index = stolen.index, start = stolen.start, end = stolen.end, "Plage active coupée pour une connexion libre");
+    reshape(manifest, &kept, &[], Some(&stolen))?;
+    Ok(Some(stolen))
+}
+
+/// Reporte un changement de découpage dans le manifeste: nouvelle fin de `chunk`, segments
+/// absorbés retirés, segment coupé ajouté
+fn reshape(manifest: &ManifestStore, chunk: &Chunk, absorbed: &[usize], tail: Option<&Chunk>) -> io::Result<()> {
+    manifest.update(|m| {
+        m.chunks.retain(|c| !absorbed.contains(&c.index));
+        if let Some(state) = m.chunks.iter_mut().find(|c| c.index == chunk.index) {
+            state.end = chunk.end;
+        }
+        if let Some(tail) = tail {
+            m.chunks.push(ChunkState { index: tail.index, start: tail.start, end: tail.end, downloaded: 0 });
+            m.chunks.sort_by_key(|c| c.start);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::downloader::manifest::Manifest;
+    use crate::downloader::types::DownloadTask;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_scheduler_merges_splits_and_steals() {
+        let dir = tempdir().unwrap();
+        let output = dir.path().join("big.bin");
+        let task = DownloadTask::builder("https://example.com/big.bin")
+            .with_output(output.clone())
+            .with_total_size(100_000)
+            .with_chunk_size(10_000)
+            .build()
+            .unwrap();
+        let chunks = task.create_chunks();
+        let manifest = ManifestStore::new(output.clone(), Manifest::new(&task, &chunks, None));
+        let sizing = AdaptiveChunking { min_chunk_size: 10_000, max_chunk_size: 40_000 };
+        let scheduler = ChunkScheduler::new(chunks, Some(sizing), 2);
+
+        // Sans mesure: le reste est partagé entre 2 connexions, plafonné à 40 000 -> 4 segments fusionnés
+        let first = scheduler.next(&manifest).unwrap().unwrap();
+        assert_eq!((first.index, first.start, first.end), (0, 0, 39_999));
+        let second = scheduler.next(&manifest).unwrap().unwrap();
+        assert_eq!((second.start, second.end), (40_000, 69_999));
+        let mut rest = Vec::new();
+        while scheduler.state.lock().unwrap().pending.front().is_some() {
+            let chunk = scheduler.next(&manifest).unwrap().unwrap();
+            rest.push((chunk.start, chunk.end));
+        }
+        assert_eq!(rest, vec![(70_000, 79_999), (80_000, 89_999), (90_000, 99_999)]);
+
+        // Plus rien en attente: seule la plage active assez longue pour deux moitiés est coupée
+        assert_eq!(scheduler.claim(first.index, 0, 30_000), 30_000);
+        let stolen = scheduler.next(&manifest).unwrap().unwrap();
+        assert_eq!((stolen.start, stolen.end), (55_000, 69_999));
+        assert_eq!(scheduler.end_of(second.index), Some(54_999));
+        // La connexion d'origine s'arrête à la nouvelle borne
+        assert_eq!(scheduler.claim(second.index, 0, 30_000), 15_000);
+        assert!(scheduler.next(&manifest).unwrap().is_none());
+
+        // Le manifeste suit le découpage et reste réutilisable pour une reprise
+        let saved = Manifest::load(&output).unwrap();
+        let bounds: Vec<_> = saved.chunks.iter().map(|c| (c.start, c.end)).collect();
+        assert_eq!(
+            bounds,
+            vec![(0, 39_999), (40_000, 54_999), (55_000, 69_999), (70_000, 79_999), (80_000, 89_999), (90_000, 99_999)]
+        );
+        assert!(saved.matches(&task, None));
+    }
+}
@@ -0,0 +1,140 @@
+//! Source SFTP (libssh2), exécutée sur le pool bloquant de tokio.
+//!
+//! Chaque plage ouvre sa propre session: `seek(offset)` puis lecture par blocs, transmis au
+//! gestionnaire via un canal. L'authentification essaie le mot de passe s'il est fourni, sinon
+//! l'agent SSH puis les clés `~/.ssh/id_ed25519` et `~/.ssh/id_rsa`.
+use std::io::{self, Read, Seek, SeekFrom};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use ssh2::{CheckResult, KnownHostFileKind, Session};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use url::Url;
+use super::protocol::{remote_path, ByteStream, Credentials, READ_BLOCK};
+use super::types::RequestOptions;
+
+const DEFAULT_PORT: u16 = 22;
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(PathBuf::from)
+}
+
+/// Vérifie la clé d'hôte contre `~/.ssh/known_hosts`: une clé différente est refusée,
+/// un hôte inconnu n'est que signalé.
+fn check_host_key(session: &Session, host: &str, port: u16) -> Result<()> {
+    let Some(path) = home_dir().map(|home| home.join(".ssh/known_hosts")) else {
+        return Ok(());
+    };
+    let mut known = session.known_hosts()?;
+    if known.read_file(&path, KnownHostFileKind::OpenSSH).is_err() {
+        tracing::warn!(%host, "known_hosts illisible, clé d'hôte non vérifiée");
+        return Ok(());
+    }
+    let (key, _) = session.host_key().context("Clé d'hôte SSH absente")?;
+    match known.check_port(host, port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::NotFound => {
+            tracing::warn!(%host, port, "Hôte SFTP absent de known_hosts");
+            Ok(())
+        }
+        CheckResult::Mismatch => anyhow::bail!("Clé d'hôte SSH différente de known_hosts pour {host}"),
+        CheckResult::Failure => anyhow::bail!("Vérification de la clé d'hôte SSH impossible pour {host}"),
+    }
+}
+
+fn authenticate(session: &Session, credentials: &Credentials) -> Result<()> {
+    let user = credentials.username.as_str();
+    if let Some(password) = &credentials.password {
+        session.userauth_password(user, password).context("Authentification SFTP par mot de passe")?;
+        return Ok(());
+    }
+    if session.userauth_agent(user).is_ok() && session.authenticated() {
+        return Ok(());
+    }
+    for name in ["id_ed25519", "id_rsa"] {
+        let Some(key) = home_dir().map(|home| home.join(".ssh").join(name)) else {
+            break;
+        };
+        if key.exists() && session.userauth_pubkey_file(user, None, &key, None).is_ok() && session.authenticated() {
+            return Ok(());
+        }
+    }
+    anyhow::bail!("Authentification SFTP impossible pour {user}")
+}
+
+/// Session authentifiée vers l'hôte de `url` (bloquant)
+fn connect(url: &Url, request: &RequestOptions) -> Result<Session> {
+    let host = url.host_str().context("URL SFTP sans hôte")?;
+    let port = url.port().unwrap_or(DEFAULT_PORT);
+    let tcp = TcpStream::connect((host, port)).with_context(|| format!("Connexion SFTP à {host}:{port}"))?;
+    let mut session = Session::new().context("Session SSH")?;
+    session.set_tcp_stream(tcp);
+    session.handshake().context("Négociation SSH")?;
+    check_host_key(&session, host, port)?;
+    let default_user = std::env::var("USER").unwrap_or_else(|_| "root".to_string());
+    authenticate(&session, &Credentials::resolve(url, request, &default_user))?;
+    Ok(session)
+}
+
+/// Taille du fichier distant (`stat`)
+pub(crate) async fn probe(url: &str, request: &RequestOptions) -> Result<u64> {
+    let url = Url::parse(url).context("URL SFTP invalide")?;
+    let request = request.clone();
+    tokio::task::spawn_blocking(move || {
+        let session = connect(&url, &request)?;
+        let stat = session.sftp()?.stat(Path::new(&remote_path(&url))).context("stat SFTP")?;
+        stat.size.context("Taille SFTP inconnue")
+    })
+    .await?
+}
+
+/// Ouvre `len` octets à partir de `offset`; la lecture s'arrête si le flux est abandonné
+pub(crate) async fn open_range(url: &str, request: &RequestOptions, offset: u64, len: u64) -> Result<ByteStream> {
+    let url = Url::parse(url).context("URL SFTP invalide")?;
+    let request = request.clone();
+    let (tx, rx) = mpsc::channel::<Result<Bytes>>(4);
+    // Ouvrir la session avant de rendre le flux: les erreurs de connexion restent relançables
+    let (opened_tx, opened_rx) = tokio::sync::oneshot::channel::<Result<()>>();
+    tokio::task::spawn_blocking(move || {
+        let opened = connect(&url, &request).and_then(|session| {
+            let mut file = session.sftp()?.open(Path::new(&remote_path(&url))).context("Ouvrir fichier SFTP")?;
+            file.seek(SeekFrom::Start(offset))?;
+            Ok((session, file))
+        });
+        let (_session, mut file) = match opened {
+            Ok(opened) => {
+                let _ = opened_tx.send(Ok(()));
+                opened
+            }
+            Err(e) => {
+                let _ = opened_tx.send(Err(e));
+                return;
+            }
+        };
+        let mut remaining = len;
+        while remaining > 0 {
+            let mut buf = vec![0u8; READ_BLOCK.min(remaining as usize)];
+            let block = match file.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    buf.truncate(n);
+                    remaining -= n as u64;
+                    Ok(Bytes::from(buf))
+                }
+                Err(e) => {
+                    remaining = 0;
+                    Err(anyhow::Error::new(e).context("Lire flux SFTP"))
+                }
+            };
+            if tx.blocking_send(block).is_err() {
+                break;
+            }
+        }
+    });
+    opened_rx
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::ConnectionAborted, "session SFTP interrompue"))??;
+    Ok(Box::pin(ReceiverStream::new(rx)))
+}
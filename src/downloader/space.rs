@@ -0,0 +1,227 @@
+//! Surveillance de l'espace disque pendant un téléchargement.
+//!
+//! Un `SpaceGuard` interroge une `SpaceSource` (par défaut le disque réel via `free_space`)
+//! au plus tous les `check_every` octets écrits, et échoue proprement si l'espace libre passe
+//! sous la marge configurée, plutôt que de laisser une écriture échouer en `ENOSPC`.
+//! Le manque d'espace est signalé par l'erreur typée `DiskFull` (retrouvable par `downcast`).
+//!
+//! `DownloadTask::max_size` (`[disk] max_file_size_mb` par défaut) protège d'un fichier démesuré
+//! mis en file depuis un lien extrait: taille annoncée ou octets reçus au‑delà de la limite
+//! arrêtent le téléchargement avec l'erreur typée `FileTooLarge`.
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use anyhow::Result;
+
+const DEFAULT_MIN_FREE: u64 = 100 * 1024 * 1024; // 100 MiB
+const DEFAULT_CHECK_EVERY: u64 = 4 * 1024 * 1024; // 4 MiB
+
+/// Espace disponible pour l'utilisateur courant sur le volume contenant `path`.
+///
+/// Si `path` n'existe pas encore (fichier de sortie), le dossier parent est interrogé.
+pub fn free_space(path: &Path) -> io::Result<u64> {
+    let probe = if path.exists() {
+        path
+    } else {
+        path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."))
+    };
+    fs2::available_space(probe)
+}
+
+/// Espace disque insuffisant sur le volume de sortie (avant ou pendant le téléchargement)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskFull {
+    pub path: PathBuf,
+    /// Octets libres mesurés
+    pub available: u64,
+    /// Octets nécessaires (reste à écrire + marge)
+    pub needed: u64,
+}
+
+impl fmt::Display for DiskFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Espace disque insuffisant: {} octets libres, {} requis pour {}",
+            self.available,
+            self.needed,
+            self.path.display()
+        )
+    }
+}
+
+impl std::error::Error for DiskFull {}
+
+/// Fichier dépassant la taille maximale autorisée (`DownloadTask::max_size`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileTooLarge {
+    pub url: String,
+    /// Taille annoncée, ou octets déjà reçus pour un flux sans taille
+    pub size: u64,
+    pub limit: u64,
+}
+
+impl fmt::Display for FileTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Fichier trop volumineux: {} octets pour une limite de {} ({})", self.size, self.limit, self.url)
+    }
+}
+
+impl std::error::Error for FileTooLarge {}
+
+/// Taille maximale d'un fichier (`[disk] max_file_size_mb`), en octets; `None` = illimitée
+pub fn max_file_size_from_config(config: &super::AppConfig) -> Option<u64> {
+    config
+        .disk
+        .as_ref()
+        .and_then(|d| d.max_file_size_mb)
+        .filter(|&mb| mb > 0)
+        .map(|mb| mb * 1024 * 1024)
+}
+
+/// Source d'information sur l'espace libre (remplaçable dans les tests)
+pub trait SpaceSource: Send + Sync {
+    fn available(&self, path: &Path) -> io::Result<u64>;
+}
+
+/// Source par défaut: le système de fichiers réel
+pub struct DiskSpace;
+
+impl SpaceSource for DiskSpace {
+    fn available(&self, path: &Path) -> io::Result<u64> {
+        free_space(path)
+    }
+}
+
+/// Vérifie périodiquement que l'espace libre reste au-dessus d'une marge minimale
+#[derive(Clone)]
+pub struct SpaceGuard {
+    source: Arc<dyn SpaceSource>,
+    /// Marge minimale (octets) à conserver libre sur le volume de sortie
+    pub min_free: u64,
+    /// Intervalle (octets écrits) entre deux vérifications
+    pub check_every: u64,
+}
+
+impl Default for SpaceGuard {
+    fn default() -> Self {
+        Self::new(Arc::new(DiskSpace), DEFAULT_MIN_FREE, DEFAULT_CHECK_EVERY)
+    }
+}
+
+impl SpaceGuard {
+    pub fn new(source: Arc<dyn SpaceSource>, min_free: u64, check_every: u64) -> Self {
+        Self { source, min_free, check_every: check_every.max(1) }
+    }
+
+    /// Construit la garde depuis `[disk] min_free_space_mb` (100 MiB par défaut)
+    pub fn from_config(config: &super::AppConfig) -> Self {
+        let mut guard = Self::default();
+        if let Some(mb) = config.disk.as_ref().and_then(|d| d.min_free_space_mb) {
+            guard.min_free = mb * 1024 * 1024;
+        }
+        guard
+    }
+
+    /// Espace disponible sur le volume de `path`, via la source configurée
+    pub fn available(&self, path: &Path) -> io::Result<u64> {
+        self.source.available(path)
+    }
+
+    /// Échoue si l'espace libre est sous la marge. Une erreur de mesure est seulement journalisée.
+    pub fn check(&self, path: &Path) -> Result<()> {
+        match self.source.available(path) {
+            Ok(available) if available < self.min_free => {
+                tracing::error!(available, min_free = self.min_free, path = %path.display(), "Espace disque insuffisant, arrêt du téléchargement");
+                Err(DiskFull { path: path.to_path_buf(), available, needed: self.min_free }.into())
+            }
+            Ok(_) => Ok(()),
+            Err(e) => {
+                tracing::warn!(error = %e, path = %path.display(), "Impossible de mesurer l'espace disque");
+                Ok(())
+            }
+        }
+    }
+
+    /// Compteur à utiliser dans une boucle d'écriture
+    pub fn tracker(&self) -> SpaceTracker<'_> {
+        SpaceTracker { guard: self, since_check: None }
+    }
+}
+
+/// Déclenche `SpaceGuard::check` au premier appel puis tous les `check_every` octets
+pub struct SpaceTracker<'a> {
+    guard: &'a SpaceGuard,
+    since_check: Option<u64>,
+}
+
+impl SpaceTracker<'_> {
+    /// À appeler avant d'écrire `len` octets dans `path`
+    pub fn before_write(&mut self, path: &Path, len: u64) -> Result<()> {
+        let due = match self.since_check {
+            None => true,
+            Some(n) => n + len >= self.guard.check_every,
+        };
+        if due {
+            self.guard.check(path)?;
+            self.since_check = Some(len);
+        } else if let Some(n) = self.since_check.as_mut() {
+            *n += len;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+    /// Source simulée: espace fixe, nombre d'appels comptés
+    struct FakeSpace {
+        available: AtomicU64,
+        calls: AtomicUsize,
+    }
+
+    impl FakeSpace {
+        fn new(available: u64) -> Arc<Self> {
+            Arc::new(Self { available: AtomicU64::new(available), calls: AtomicUsize::new(0) })
+        }
+    }
+
+    impl SpaceSource for FakeSpace {
+        fn available(&self, _path: &Path) -> io::Result<u64> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.available.load(Ordering::SeqCst))
+        }
+    }
+
+    #[test]
+    fn test_tracker_checks_periodically() {
+        let fake = FakeSpace::new(u64::MAX);
+        let guard = SpaceGuard::new(fake.clone(), 10, 100);
+        let mut tracker = guard.tracker();
+        let path = Path::new("out.bin");
+
+        tracker.before_write(path, 40).unwrap(); // premier appel -> vérification
+        tracker.before_write(path, 40).unwrap();
+        tracker.before_write(path, 40).unwrap(); // 80 + 40 >= 100 -> vérification
+        assert_eq!(fake.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_check_fails_below_margin() {
+        let guard = SpaceGuard::new(FakeSpace::new(5), 10, 1);
+        let err = guard.check(Path::new("out.bin")).unwrap_err();
+        assert!(err.to_string().contains("Espace disque insuffisant"));
+        let full = err.downcast_ref::<DiskFull>().unwrap();
+        assert_eq!((full.available, full.needed), (5, 10));
+    }
+
+    #[test]
+    fn test_free_space_on_missing_file_uses_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(free_space(&dir.path().join("not_yet.bin")).unwrap() > 0);
+    }
+}
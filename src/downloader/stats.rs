@@ -0,0 +1,159 @@
+//! Statistiques agrégées de tous les téléchargements, calculées côté téléchargeur pour que la
+//! GUI comme un mode CLI/démon affichent les mêmes chiffres.
+//!
+//! `DownloadStatistics::global()` est alimenté par chaque `DownloadManager` (octets reçus via
+//! `ProgressReporter`, échecs de `start`); `snapshot` y ajoute l'état de la `DownloadQueue`
+//! (actifs, en attente). Les compteurs du jour repartent de zéro à minuit, heure locale; le
+//! débit cumulé reprend la moyenne mobile de `progress` et retombe à 0 quand tout s'arrête.
+use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use chrono::NaiveDate;
+use serde::Serialize;
+use super::progress::SpeedMeter;
+use super::queue::DownloadQueue;
+
+static GLOBAL: OnceLock<DownloadStatistics> = OnceLock::new();
+
+/// Vue instantanée de l'activité de téléchargement
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DownloadStatsSnapshot {
+    /// Téléchargements occupant une place dans la file
+    pub active: usize,
+    /// Téléchargements attendant une place
+    pub queued: usize,
+    /// Débit cumulé lissé, en octets/s
+    pub bytes_per_sec: u64,
+    /// Octets reçus depuis minuit (heure locale)
+    pub downloaded_today: u64,
+    /// Téléchargements échoués depuis minuit (heure locale)
+    pub failures: u64,
+}
+
+#[derive(Debug)]
+struct Daily {
+    day: NaiveDate,
+    downloaded: u64,
+    failures: u64,
+}
+
+impl Daily {
+    /// Remet les compteurs à zéro si `today` est un nouveau jour
+    fn roll(&mut self, today: NaiveDate) -> &mut Self {
+        if self.day != today {
+            *self = Self { day: today, downloaded: 0, failures: 0 };
+        }
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    /// Octets reçus depuis le lancement (alimente le débit)
+    total: AtomicU64,
+    daily: Mutex<Daily>,
+    meter: Mutex<SpeedMeter>,
+}
+
+/// Compteurs partagés (clonage bon marché) entre les gestionnaires de téléchargement
+#[derive(Debug, Clone)]
+pub struct DownloadStatistics(Arc<Inner>);
+
+impl Default for DownloadStatistics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+impl DownloadStatistics {
+    pub fn new() -> Self {
+        Self(Arc::new(Inner {
+            total: AtomicU64::new(0),
+            daily: Mutex::new(Daily { day: today(), downloaded: 0, failures: 0 }),
+            meter: Mutex::new(SpeedMeter::new(Instant::now())),
+        }))
+    }
+
+    /// Compteurs de l'application, partagés par tous les `DownloadManager`
+    pub fn global() -> Self {
+        GLOBAL.get_or_init(Self::new).clone()
+    }
+
+    fn daily(&self) -> std::sync::MutexGuard<'_, Daily> {
+        self.0.daily.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Comptabilise `bytes` octets reçus du réseau (reprises exclues)
+    pub fn record_bytes(&self, bytes: u64) {
+        self.record_bytes_on(bytes, today());
+    }
+
+    fn record_bytes_on(&self, bytes: u64, today: NaiveDate) {
+        self.0.total.fetch_add(bytes, Ordering::Relaxed);
+        self.daily().roll(today).downloaded += bytes;
+    }
+
+    /// Comptabilise un téléchargement échoué
+    pub fn record_failure(&self) {
+        self.record_failure_on(today());
+    }
+
+    fn record_failure_on(&self, today: NaiveDate) {
+        self.daily().roll(today).failures += 1;
+    }
+
+    /// Chiffres courants, avec l'occupation de `queue`
+    pub fn snapshot(&self, queue: &DownloadQueue) -> DownloadStatsSnapshot {
+        self.snapshot_at(queue, today(), Instant::now())
+    }
+
+    fn snapshot_at(&self, queue: &DownloadQueue, today: NaiveDate, now: Instant) -> DownloadStatsSnapshot {
+        let bytes_per_sec = {
+            let mut meter = self.0.meter.lock().unwrap_or_else(|e| e.into_inner());
+            meter.update(self.0.total.load(Ordering::Relaxed), now)
+        };
+        let mut daily = self.daily();
+        let daily = daily.roll(today);
+        DownloadStatsSnapshot {
+            active: queue.active(),
+            queued: queue.queued(),
+            bytes_per_sec,
+            downloaded_today: daily.downloaded,
+            failures: daily.failures,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use super::super::queue::Priority;
+
+    #[tokio::test]
+    async fn test_snapshot_aggregates_queue_rate_and_daily_counters() {
+        let stats = DownloadStatistics::new();
+        let queue = DownloadQueue::new(1);
+        let day = NaiveDate::from_ymd_opt(2026, 3, 14).unwrap();
+        let origin = Instant::now();
+
+        let _slot = queue.acquire(1, Priority::Normal).await;
+        stats.record_bytes_on(500_000, day);
+        stats.record_failure_on(day);
+        let snapshot = stats.snapshot_at(&queue, day, origin + Duration::from_millis(500));
+        assert_eq!((snapshot.active, snapshot.queued, snapshot.downloaded_today, snapshot.failures), (1, 0, 500_000, 1));
+        // ~500 ko en ~500 ms
+        assert!(snapshot.bytes_per_sec > 900_000 && snapshot.bytes_per_sec <= 1_000_000, "{}", snapshot.bytes_per_sec);
+
+        // Minuit: les compteurs du jour repartent de zéro, le débit décroît sans nouveaux octets
+        let next = day.succ_opt().unwrap();
+        stats.record_bytes_on(1_000, next);
+        let snapshot = stats.snapshot_at(&queue, next, origin + Duration::from_secs(10));
+        assert_eq!((snapshot.downloaded_today, snapshot.failures), (1_000, 0));
+        assert!(snapshot.bytes_per_sec < 100_000, "{}", snapshot.bytes_per_sec);
+    }
+}
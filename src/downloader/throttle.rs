@@ -0,0 +1,240 @@
+//! Limitation de bande passante par seau à jetons (token bucket).
+//!
+//! Un `RateLimiter` est partagé par toutes les tâches de segments d'un téléchargement
+//! (`DownloadTask::rate_limit`) et `RateLimiter::global()` borne l'ensemble de l'application
+//! (`[bandwidth] max_kib_per_sec` dans scrapes.toml, curseur de l'onglet Téléchargements).
+//! Le débit se modifie à chaud; `0` signifie illimité.
+//!
+//! Un `SpeedSchedule` (`[[bandwidth.schedule]]`, section « Plages de débit » de l'onglet
+//! Téléchargements) remplace ce débit selon l'heure locale, ex: 250 KiB/s de 08:00 à 23:00 et la
+//! limite de base (illimitée par défaut) le reste du temps. Il est réévalué à chaque bloc reçu.
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use chrono::NaiveTime;
+use super::AppConfig;
+
+static GLOBAL: OnceLock<RateLimiter> = OnceLock::new();
+
+/// Limiteur de débit partagé (clonable); la capacité du seau correspond à une seconde de débit
+#[derive(Debug, Clone)]
+pub struct RateLimiter(Arc<Inner>);
+
+#[derive(Debug)]
+struct Inner {
+    /// Limite de base, appliquée hors des plages du calendrier
+    bytes_per_sec: AtomicU64,
+    schedule: Mutex<SpeedSchedule>,
+    bucket: Mutex<Bucket>,
+}
+
+/// Plage horaire (heure locale) à débit imposé, `[start, end)`; `start > end` traverse minuit
+/// (ex: 23:00–07:00) et `start == end` ne couvre rien
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeedRule {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    /// Débit imposé pendant la plage (0 = illimité)
+    pub bytes_per_sec: u64,
+}
+
+impl SpeedRule {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// Profils de débit selon l'heure: la première plage contenant l'heure impose son débit
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpeedSchedule {
+    pub rules: Vec<SpeedRule>,
+}
+
+impl SpeedSchedule {
+    /// Lit `[[bandwidth.schedule]]` (`from`/`to` au format `HH:MM`); une entrée illisible est
+    /// ignorée avec un avertissement
+    pub fn from_config(config: &AppConfig) -> Self {
+        let entries = config.bandwidth.as_ref().and_then(|b| b.schedule.as_deref()).unwrap_or_default();
+        let rules = entries
+            .iter()
+            .filter_map(|entry| {
+                let parse = |s: &str| NaiveTime::parse_from_str(s.trim(), "%H:%M").ok();
+                match (parse(&entry.from), parse(&entry.to)) {
+                    (Some(start), Some(end)) => Some(SpeedRule { start, end, bytes_per_sec: entry.kib_per_sec * 1024 }),
+                    _ => {
+                        tracing::warn!(from = %entry.from, to = %entry.to, "Plage de débit ignorée (attendu HH:MM)");
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Plage active à `time`, s'il y en a une
+    pub fn active_rule(&self, time: NaiveTime) -> Option<&SpeedRule> {
+        self.rules.iter().find(|rule| rule.contains(time))
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+}
+
+impl Bucket {
+    /// Consomme `bytes` jetons à l'instant `now` et retourne l'attente nécessaire.
+    /// Le solde peut devenir négatif: les appelants suivants attendent d'autant plus.
+    fn take(&mut self, bytes: u64, rate: u64, now: Instant) -> Duration {
+        let capacity = rate as f64;
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * capacity).min(capacity);
+        self.last = now;
+        self.tokens -= bytes as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / capacity)
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Nouveau limiteur à `bytes_per_sec` octets/s (0 = illimité)
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self(Arc::new(Inner {
+            bytes_per_sec: AtomicU64::new(bytes_per_sec),
+            schedule: Mutex::new(SpeedSchedule::default()),
+            bucket: Mutex::new(Bucket { tokens: bytes_per_sec as f64, last: Instant::now() }),
+        }))
+    }
+
+    /// Limiteur global de l'application, initialisé depuis scrapes.toml au premier appel
+    pub fn global() -> Self {
+        GLOBAL
+            .get_or_init(|| {
+                let config = super::load_config();
+                let limiter = Self::new(global_limit_from_config(&config));
+                limiter.set_schedule(SpeedSchedule::from_config(&config));
+                limiter
+            })
+            .clone()
+    }
+
+    /// Limite de base (hors plages du calendrier)
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.0.bytes_per_sec.load(Ordering::Relaxed)
+    }
+
+    pub fn schedule(&self) -> SpeedSchedule {
+        self.0.schedule.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Remplace les plages de débit à chaud
+    pub fn set_schedule(&self, schedule: SpeedSchedule) {
+        tracing::info!(rules = schedule.rules.len(), "Plages de débit modifiées");
+        *self.0.schedule.lock().unwrap_or_else(|e| e.into_inner()) = schedule;
+    }
+
+    /// Débit appliqué à `time`: celui de la plage active, sinon la limite de base
+    pub fn bytes_per_sec_at(&self, time: NaiveTime) -> u64 {
+        let schedule = self.0.schedule.lock().unwrap_or_else(|e| e.into_inner());
+        match schedule.active_rule(time) {
+            Some(rule) => rule.bytes_per_sec,
+            None => self.bytes_per_sec(),
+        }
+    }
+
+    /// Modifie le débit maximal à chaud (0 = illimité)
+    pub fn set_bytes_per_sec(&self, bytes_per_sec: u64) {
+        self.0.bytes_per_sec.store(bytes_per_sec, Ordering::Relaxed);
+        tracing::info!(bytes_per_sec, "Limite de bande passante modifiée");
+    }
+
+    /// Attend que `bytes` octets puissent être consommés sans dépasser le débit
+    pub async fn acquire(&self, bytes: u64) {
+        let rate = self.bytes_per_sec_at(chrono::Local::now().time());
+        if rate == 0 {
+            return;
+        }
+        let wait = {
+            let mut bucket = self.0.bucket.lock().unwrap_or_else(|e| e.into_inner());
+            bucket.take(bytes, rate, Instant::now())
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Applique successivement chaque limiteur à `bytes` octets reçus
+pub(crate) async fn acquire_all(limiters: &[RateLimiter], bytes: u64) {
+    for limiter in limiters {
+        limiter.acquire(bytes).await;
+    }
+}
+
+/// Limite globale configurée (`[bandwidth] max_kib_per_sec`), en octets/s
+pub fn global_limit_from_config(config: &AppConfig) -> u64 {
+    config.bandwidth.as_ref().and_then(|b| b.max_kib_per_sec).unwrap_or(0) * 1024
+}
+
+/// Limite par téléchargement configurée (`[bandwidth] per_download_kib_per_sec`), en octets/s
+pub fn per_download_limit_from_config(config: &AppConfig) -> Option<u64> {
+    config
+        .bandwidth
+        .as_ref()
+        .and_then(|b| b.per_download_kib_per_sec)
+        .filter(|&kib| kib > 0)
+        .map(|kib| kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_allows_burst_then_paces() {
+        let start = Instant::now();
+        let mut bucket = Bucket { tokens: 1000.0, last: start };
+
+        // Une seconde de débit disponible immédiatement
+        assert_eq!(bucket.take(1000, 1000, start), Duration::ZERO);
+        // Au‑delà, l'attente est proportionnelle à la dette accumulée
+        assert_eq!(bucket.take(500, 1000, start), Duration::from_millis(500));
+        assert_eq!(bucket.take(500, 1000, start), Duration::from_secs(1));
+        // Le temps écoulé rembourse la dette, sans dépasser la capacité
+        assert_eq!(bucket.take(0, 1000, start + Duration::from_secs(5)), Duration::ZERO);
+        assert_eq!(bucket.tokens, 1000.0);
+    }
+
+    #[test]
+    fn test_schedule_overrides_base_limit_by_time_of_day() {
+        let config: AppConfig = toml::from_str(concat!(
+            "[bandwidth]\nmax_kib_per_sec = 0\n",
+            "[[bandwidth.schedule]]\nfrom = \"08:00\"\nto = \"23:00\"\nkib_per_sec = 250\n",
+            "[[bandwidth.schedule]]\nfrom = \"23:30\"\nto = \"01:00\"\nkib_per_sec = 1000\n",
+            "[[bandwidth.schedule]]\nfrom = \"8h\"\nto = \"9h\"\nkib_per_sec = 1\n",
+        ))
+        .unwrap();
+        let limiter = RateLimiter::new(global_limit_from_config(&config));
+        limiter.set_schedule(SpeedSchedule::from_config(&config));
+        assert_eq!(limiter.schedule().rules.len(), 2);
+
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        assert_eq!(limiter.bytes_per_sec_at(at(8, 0)), 250 * 1024);
+        assert_eq!(limiter.bytes_per_sec_at(at(22, 59)), 250 * 1024);
+        // Hors plage: limite de base (illimitée)
+        assert_eq!(limiter.bytes_per_sec_at(at(23, 0)), 0);
+        assert_eq!(limiter.bytes_per_sec_at(at(7, 59)), 0);
+        // Plage traversant minuit
+        assert_eq!(limiter.bytes_per_sec_at(at(0, 30)), 1000 * 1024);
+        limiter.set_bytes_per_sec(64 * 1024);
+        assert_eq!(limiter.bytes_per_sec_at(at(3, 0)), 64 * 1024);
+    }
+}
@@ -0,0 +1,309 @@
+//! Téléchargements BitTorrent (liens magnet et fichiers `.torrent`) via `aria2c`.
+//!
+//! Comme pour le pont ffmpeg, le transfert est délégué à un outil externe supervisé: le module
+//! lit la ligne d'état d'aria2 (`[#id 1.2MiB/33MiB(3%) CN:12 SD:4 DL:250KiB ETA:2m]`) et la
+//! convertit en `SwarmProgress`. Une pause arrête le processus; la reprise relance aria2 avec
+//! `--continue`, qui repart de son fichier de contrôle `.aria2`.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use anyhow::{Context, Result};
+use percent_encoding::percent_decode_str;
+use serde_bencode::value::Value;
+use sha1::{Digest, Sha1};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+/// Exécutable aria2 recherché dans le `PATH`
+const ARIA2C: &str = "aria2c";
+/// Intervalle de sauvegarde du fichier de contrôle `.aria2` (s): une pause perd au plus cela
+const AUTO_SAVE_INTERVAL: u32 = 5;
+/// Fréquence de contrôle du drapeau d'annulation
+const CANCEL_POLL: Duration = Duration::from_millis(300);
+
+/// Lien magnet (`magnet:?xt=urn:btih:<hash>&dn=<nom>&tr=<tracker>`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MagnetLink {
+    /// Info‑hash tel qu'écrit dans le lien (hexadécimal ou base32)
+    pub info_hash: String,
+    pub name: Option<String>,
+    pub trackers: Vec<String>,
+}
+
+impl MagnetLink {
+    pub fn parse(link: &str) -> Option<Self> {
+        let query = link.strip_prefix("magnet:?")?;
+        let mut info_hash = None;
+        let mut name = None;
+        let mut trackers = Vec::new();
+        for pair in query.split('&') {
+            let Some((key, value)) = pair.split_once('=') else {
+                continue;
+            };
+            let value = percent_decode_str(&value.replace('+', " ")).decode_utf8_lossy().into_owned();
+            match key {
+                "xt" => info_hash = value.strip_prefix("urn:btih:").map(str::to_ascii_lowercase),
+                "dn" => name = Some(value),
+                "tr" => trackers.push(value),
+                _ => {}
+            }
+        }
+        Some(Self { info_hash: info_hash?, name, trackers })
+    }
+}
+
+/// Métadonnées lues dans un fichier `.torrent`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TorrentInfo {
+    pub name: String,
+    /// Somme des tailles des fichiers du torrent
+    pub total_size: u64,
+    /// SHA‑1 du dictionnaire `info` (hexadécimal)
+    pub info_hash: String,
+}
+
+fn dict_get<'a>(dict: &'a HashMap<Vec<u8>, Value>, key: &str) -> Option<&'a Value> {
+    dict.get(key.as_bytes())
+}
+
+fn as_int(value: Option<&Value>) -> Option<u64> {
+    match value {
+        Some(Value::Int(n)) => u64::try_from(*n).ok(),
+        _ => None,
+    }
+}
+
+impl TorrentInfo {
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let Value::Dict(root) = serde_bencode::from_bytes::<Value>(bytes).context("Torrent invalide (bencode)")? else {
+            anyhow::bail!("Torrent invalide: dictionnaire racine attendu");
+        };
+        let Some(info @ Value::Dict(fields)) = dict_get(&root, "info") else {
+            anyhow::bail!("Torrent invalide: dictionnaire `info` absent");
+        };
+        let name = match dict_get(fields, "name") {
+            Some(Value::Bytes(name)) => String::from_utf8_lossy(name).into_owned(),
+            _ => anyhow::bail!("Torrent invalide: `info.name` absent"),
+        };
+        // Torrent mono‑fichier: `length`; multi‑fichiers: somme des `files[].length`
+        let total_size = match (as_int(dict_get(fields, "length")), dict_get(fields, "files")) {
+            (Some(length), _) => length,
+            (None, Some(Value::List(files))) => files
+                .iter()
+                .filter_map(|f| match f {
+                    Value::Dict(file) => as_int(dict_get(file, "length")),
+                    _ => None,
+                })
+                .sum(),
+            _ => 0,
+        };
+        // Le sérialiseur trie les clés: l'encodage obtenu est l'encodage canonique du torrent
+        let encoded = serde_bencode::to_bytes(info).context("Réencoder `info`")?;
+        let info_hash = Sha1::digest(&encoded).iter().map(|b| format!("{b:02x}")).collect();
+        Ok(Self { name, total_size, info_hash })
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path).with_context(|| format!("Lire {}", path.display()))?;
+        Self::parse(&bytes)
+    }
+}
+
+/// Le texte saisi désigne‑t‑il un torrent (lien magnet ou chemin `.torrent`)?
+pub fn is_torrent_source(input: &str) -> bool {
+    input.starts_with("magnet:?") || Path::new(input).extension().is_some_and(|e| e.eq_ignore_ascii_case("torrent"))
+}
+
+/// Nom affichable d'un torrent: `dn` du magnet, nom du `.torrent`, sinon info‑hash
+pub fn torrent_display_name(input: &str) -> Option<String> {
+    match MagnetLink::parse(input) {
+        Some(magnet) => Some(magnet.name.unwrap_or(magnet.info_hash)),
+        None => TorrentInfo::read(Path::new(input)).ok().map(|info| info.name),
+    }
+}
+
+/// État de l'essaim lu sur la ligne d'état d'aria2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SwarmProgress {
+    pub downloaded: u64,
+    pub total_size: u64,
+    /// Débit de réception (octets/s)
+    pub speed: u64,
+    /// Connexions aux pairs (`CN`)
+    pub peers: u32,
+    /// Pairs complets (`SD`)
+    pub seeds: u32,
+}
+
+/// `1.5MiB` -> 1_572_864
+fn parse_size(text: &str) -> Option<u64> {
+    let split = text.find(|c: char| c.is_ascii_alphabetic())?;
+    let (number, unit) = text.split_at(split);
+    let factor: u64 = match unit {
+        "B" => 1,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        "TiB" => 1 << 40,
+        _ => return None,
+    };
+    Some((number.parse::<f64>().ok()? * factor as f64) as u64)
+}
+
+/// Analyse une ligne d'état `[#2089b0 400.0KiB/33.2MiB(1%) CN:44 SD:5 DL:115KiB ETA:4m51s]`
+pub fn parse_readout(line: &str) -> Option<SwarmProgress> {
+    let inner = line.trim().strip_prefix("[#")?.strip_suffix(']')?;
+    let mut fields = inner.split_whitespace().skip(1);
+    let amounts = fields.next()?;
+    let (downloaded, total) = amounts.split_once('(').map_or(amounts, |(a, _)| a).split_once('/')?;
+    let mut progress = SwarmProgress { downloaded: parse_size(downloaded)?, total_size: parse_size(total)?, ..Default::default() };
+    for field in fields {
+        match field.split_once(':') {
+            Some(("CN", n)) => progress.peers = n.parse().unwrap_or(0),
+            Some(("SD", n)) => progress.seeds = n.parse().unwrap_or(0),
+            Some(("DL", speed)) => progress.speed = parse_size(speed).unwrap_or(0),
+            _ => {}
+        }
+    }
+    Some(progress)
+}
+
+/// Fin d'un transfert torrent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TorrentOutcome {
+    Completed,
+    /// Arrêté à la demande (pause ou annulation); `--continue` reprendra où il en était
+    Stopped,
+}
+
+/// Transfert d'un lien magnet ou d'un fichier `.torrent` vers `dir`
+#[derive(Debug, Clone)]
+pub struct TorrentDownload {
+    source: String,
+    dir: PathBuf,
+    rate_limit: Option<u64>,
+}
+
+impl TorrentDownload {
+    pub fn new(source: impl Into<String>, dir: impl Into<PathBuf>) -> Self {
+        Self { source: source.into(), dir: dir.into(), rate_limit: None }
+    }
+
+    /// Débit maximal de réception (octets/s)
+    pub fn with_rate_limit(mut self, rate_limit: Option<u64>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    fn args(&self) -> Vec<String> {
+        let mut args = vec![
+            format!("--dir={}", self.dir.display()),
+            "--continue=true".to_string(),
+            "--seed-time=0".to_string(),
+            "--follow-torrent=mem".to_string(),
+            "--bt-save-metadata=false".to_string(),
+            "--summary-interval=0".to_string(),
+            "--enable-color=false".to_string(),
+            "--console-log-level=warn".to_string(),
+            format!("--auto-save-interval={AUTO_SAVE_INTERVAL}"),
+        ];
+        if let Some(limit) = self.rate_limit {
+            args.push(format!("--max-download-limit={limit}"));
+        }
+        args.push(self.source.clone());
+        args
+    }
+
+    /// Lance aria2 et relaie sa progression jusqu'à la fin ou jusqu'à ce que `cancel` passe à vrai
+    pub async fn run(&self, progress_tx: mpsc::UnboundedSender<SwarmProgress>, cancel: Arc<AtomicBool>) -> Result<TorrentOutcome> {
+        tokio::fs::create_dir_all(&self.dir).await.with_context(|| format!("Créer {}", self.dir.display()))?;
+        tracing::info!(source = %self.source, dir = %self.dir.display(), "Démarrage du torrent");
+        let mut child = Command::new(ARIA2C)
+            .args(self.args())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Lancer aria2c (est‑il installé et dans le PATH?)")?;
+        let stdout = child.stdout.take().context("Sortie d'aria2c indisponible")?;
+        let reader = tokio::spawn(relay_readout(stdout, progress_tx));
+
+        let outcome = loop {
+            tokio::select! {
+                status = child.wait() => {
+                    let status = status.context("Attendre aria2c")?;
+                    if !status.success() {
+                        anyhow::bail!("aria2c a échoué ({status})");
+                    }
+                    break TorrentOutcome::Completed;
+                }
+                _ = tokio::time::sleep(CANCEL_POLL) => {
+                    if cancel.load(Ordering::Relaxed) {
+                        child.kill().await.context("Arrêter aria2c")?;
+                        break TorrentOutcome::Stopped;
+                    }
+                }
+            }
+        };
+        let _ = reader.await;
+        tracing::info!(source = %self.source, ?outcome, "Fin du torrent");
+        Ok(outcome)
+    }
+}
+
+/// Lit la sortie d'aria2 (lignes terminées par `\r` ou `\n`) et transmet chaque ligne d'état
+async fn relay_readout(mut stdout: impl AsyncRead + Unpin, progress_tx: mpsc::UnboundedSender<SwarmProgress>) {
+    let mut pending = String::new();
+    let mut buf = [0u8; 4096];
+    while let Ok(n) = stdout.read(&mut buf).await {
+        if n == 0 {
+            break;
+        }
+        pending.push_str(&String::from_utf8_lossy(&buf[..n]));
+        while let Some(end) = pending.find(['\r', '\n']) {
+            let line: String = pending.drain(..=end).collect();
+            if let Some(progress) = parse_readout(&line) {
+                let _ = progress_tx.send(progress);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_magnet_torrent_and_readout_parsing() {
+        let magnet = MagnetLink::parse(
+            "magnet:?xt=urn:btih:C12FE1C06BBA254A9DC9F519B335AA7C1367A88A&dn=Big+Buck%20Bunny&tr=udp%3A%2F%2Ftracker.example%3A80",
+        )
+        .unwrap();
+        assert_eq!(magnet.info_hash, "c12fe1c06bba254a9dc9f519b335aa7c1367a88a");
+        assert_eq!(magnet.name.as_deref(), Some("Big Buck Bunny"));
+        assert_eq!(magnet.trackers, vec!["udp://tracker.example:80"]);
+        assert!(is_torrent_source("magnet:?xt=urn:btih:abc"));
+        assert!(is_torrent_source("/tmp/debian.TORRENT"));
+        assert!(!is_torrent_source("https://example.com/video.mp4"));
+
+        // Torrent multi‑fichiers minimal; clés volontairement dans l'ordre canonique
+        let info = b"d5:filesld6:lengthi3e4:pathl1:aeed6:lengthi4e4:pathl1:beee4:name3:dir12:piece lengthi16384e6:pieces0:e";
+        let mut torrent = b"d8:announce3:url4:info".to_vec();
+        torrent.extend_from_slice(info);
+        torrent.push(b'e');
+        let parsed = TorrentInfo::parse(&torrent).unwrap();
+        assert_eq!(parsed.name, "dir");
+        assert_eq!(parsed.total_size, 7);
+        let expected: String = Sha1::digest(info).iter().map(|b| format!("{b:02x}")).collect();
+        assert_eq!(parsed.info_hash, expected);
+
+        let progress = parse_readout("[#2089b0 400.0KiB/33.2MiB(1%) CN:44 SD:5 DL:115KiB ETA:4m51s]").unwrap();
+        assert_eq!(progress, SwarmProgress { downloaded: 409_600, total_size: 34_812_723, speed: 117_760, peers: 44, seeds: 5 });
+        assert!(parse_readout("05/01 12:00:00 [NOTICE] Download complete").is_none());
+    }
+}
@@ -5,7 +5,16 @@
 //! - `chunk_size` (> 0) est la taille cible d'un segment; le dernier peut être plus petit.
 //! - Les segments générés couvrent l'intervalle `[0, total_size - 1]` sans chevauchement,
 //!   et dans l'ordre croissant.
-use std::path::PathBuf;
+//!
+//! Hors du module `downloader`, une `DownloadTask` ne se construit que par
+//! `DownloadTaskBuilder`, qui valide URL, destination et tailles.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use anyhow::{Context, Result};
+use url::Url;
+use super::checksum::{ExpectedHash, Integrity};
+use super::queue::Priority;
 
 /// Représente un intervalle (chunk) d'un téléchargement
 #[allow(dead_code)]
@@ -14,8 +23,7 @@ pub struct Chunk {
     pub index: usize,
     pub start: u64,
     pub end: u64,
-    pub downloaded: u64, // quantité déjà téléchargée pour ce segment
-    pub path: PathBuf, // fichier temporaire associé à ce segment
+    pub downloaded: u64, // quantité déjà téléchargée pour ce segment (depuis le manifeste)
 }
 
 
@@ -23,15 +31,287 @@ pub struct Chunk {
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct DownloadTask {
-    pub url: String,
-    pub output: PathBuf,
-    pub total_size: u64,
-    pub chunk_size: u64,
-    pub num_chunks: usize,
+    pub(super) url: String,
+    pub(super) output: PathBuf,
+    pub(super) total_size: u64,
+    pub(super) chunk_size: u64,
+    pub(super) num_chunks: usize,
+    /// Débit maximal propre à cette tâche, en octets/s (`None` = seule la limite globale s'applique)
+    pub(super) rate_limit: Option<u64>,
+    /// En‑têtes, cookies et authentification appliqués à toutes les requêtes (HEAD et GET)
+    pub(super) request: RequestOptions,
+    /// URLs supplémentaires servant le même fichier; les segments sont répartis entre elles
+    pub(super) mirrors: Vec<String>,
+    /// Empreintes attendues (Metalink...); la plus forte est vérifiée en fin de téléchargement
+    pub(super) hashes: Vec<ExpectedHash>,
+    /// Rang dans la file d'attente (`DownloadQueue`) face aux autres téléchargements
+    pub(super) priority: Priority,
+    /// Connexions simultanées propres à cette tâche (`None` = valeur du `DownloadManager`)
+    pub(super) max_concurrency: Option<usize>,
+    /// Taille maximale acceptée, en octets (`None` = illimitée); voir `FileTooLarge`
+    pub(super) max_size: Option<u64>,
+}
+
+/// Paramètres HTTP propres à une ressource (liens exigeant un Referer, un cookie ou un jeton)
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    /// En‑têtes supplémentaires (ex: `Referer`, `User-Agent`)
+    pub headers: BTreeMap<String, String>,
+    /// Valeur brute de l'en‑tête `Cookie` (ex: `"session=abc; lang=fr"`)
+    pub cookies: Option<String>,
+    /// Authentification HTTP Basic
+    pub basic_auth: Option<BasicAuth>,
+    /// Jeton envoyé en `Authorization: Bearer <jeton>`
+    pub bearer_token: Option<String>,
+}
+
+/// Identifiants HTTP Basic
+#[derive(Debug, Clone)]
+pub struct BasicAuth {
+    pub username: String,
+    pub password: Option<String>,
+}
+
+#[allow(dead_code)]
+impl RequestOptions {
+    /// Ajoute (ou remplace) un en‑tête
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn with_cookies(mut self, cookies: impl Into<String>) -> Self {
+        self.cookies = Some(cookies.into());
+        self
+    }
+
+    pub fn with_basic_auth(mut self, username: impl Into<String>, password: Option<String>) -> Self {
+        self.basic_auth = Some(BasicAuth { username: username.into(), password });
+        self
+    }
+
+    pub fn with_bearer_token(mut self, token: impl Into<String>) -> Self {
+        self.bearer_token = Some(token.into());
+        self
+    }
+}
+
+
+/// Schémas acceptés par `DownloadTaskBuilder::build`
+const SUPPORTED_SCHEMES: [&str; 6] = ["http", "https", "ftp", "sftp", "file", "data"];
+
+/// Construction validée d'une `DownloadTask`.
+///
+/// Taille totale et nombre de segments restent à 0 (sondés au démarrage) sauf s'ils sont
+/// connus d'avance (Metalink...); `chunk_size` vaut 8 MiB par défaut.
+#[derive(Debug, Clone)]
+pub struct DownloadTaskBuilder {
+    task: DownloadTask,
+}
+
+impl DownloadTaskBuilder {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            task: DownloadTask {
+                url: url.into(),
+                output: PathBuf::new(),
+                total_size: 0,
+                chunk_size: super::DEFAULT_CHUNK_SIZE,
+                num_chunks: 0,
+                rate_limit: None,
+                request: RequestOptions::default(),
+                mirrors: Vec::new(),
+                hashes: Vec::new(),
+                priority: Priority::default(),
+                max_concurrency: None,
+                max_size: None,
+            },
+        }
+    }
+
+    pub fn with_output(mut self, output: impl Into<PathBuf>) -> Self {
+        self.task.output = output.into();
+        self
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: u64) -> Self {
+        self.task.chunk_size = chunk_size;
+        self
+    }
+
+    /// Taille déjà connue (évite d'attendre la sonde pour découper les segments)
+    pub fn with_total_size(mut self, total_size: u64) -> Self {
+        self.task.total_size = total_size;
+        self
+    }
+
+    /// Débit maximal propre à la tâche; `None` ou 0 = seule la limite globale s'applique
+    pub fn with_rate_limit(mut self, rate_limit: Option<u64>) -> Self {
+        self.task.rate_limit = rate_limit.filter(|&limit| limit > 0);
+        self
+    }
+
+    /// Connexions simultanées propres à la tâche; `None` ou 0 = valeur du gestionnaire
+    pub fn with_max_concurrency(mut self, max_concurrency: Option<usize>) -> Self {
+        self.task.max_concurrency = max_concurrency.filter(|&n| n > 0);
+        self
+    }
+
+    /// Taille maximale acceptée, en octets; `None` ou 0 = illimitée
+    pub fn with_max_size(mut self, max_size: Option<u64>) -> Self {
+        self.task.max_size = max_size.filter(|&size| size > 0);
+        self
+    }
+
+    /// Ajoute (ou remplace) un en‑tête envoyé à chaque requête
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.task.request.headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// En‑têtes, cookies et authentification de la tâche (remplace ceux déjà fixés)
+    #[allow(dead_code)]
+    pub fn with_request(mut self, request: RequestOptions) -> Self {
+        self.task.request = request;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn with_mirror(mut self, mirror: impl Into<String>) -> Self {
+        self.task.mirrors.push(mirror.into());
+        self
+    }
+
+    pub fn with_mirrors(mut self, mirrors: impl IntoIterator<Item = String>) -> Self {
+        self.task.mirrors.extend(mirrors);
+        self
+    }
+
+    pub fn with_hash(mut self, hash: ExpectedHash) -> Self {
+        self.task.hashes.push(hash);
+        self
+    }
+
+    pub fn with_hashes(mut self, hashes: impl IntoIterator<Item = ExpectedHash>) -> Self {
+        self.task.hashes.extend(hashes);
+        self
+    }
+
+    /// Rang dans la file d'attente (`Normal` par défaut)
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.task.priority = priority;
+        self
+    }
+
+    /// Vérifie les invariants: URLs exploitables, destination fournie, `chunk_size > 0`
+    pub fn build(self) -> Result<DownloadTask> {
+        let task = self.task;
+        for source in task.sources() {
+            let url = Url::parse(&source).with_context(|| format!("URL invalide: {source}"))?;
+            if !SUPPORTED_SCHEMES.contains(&url.scheme()) {
+                anyhow::bail!("Schéma non pris en charge ({}): {source}", url.scheme());
+            }
+        }
+        if task.output.as_os_str().is_empty() {
+            anyhow::bail!("Destination manquante pour {}", task.url);
+        }
+        if task.chunk_size == 0 {
+            anyhow::bail!("La taille des segments doit être positive");
+        }
+        Ok(task)
+    }
 }
 
 
+/// Mesure de temps d'un segment téléchargé, relative au démarrage du téléchargement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkTiming {
+    pub index: usize,
+    /// Source ayant servi la dernière tentative (0 = `url`, puis `mirrors` dans l'ordre)
+    pub source: usize,
+    /// Octets effectivement reçus pour ce segment
+    pub bytes: u64,
+    /// Décalage du début du segment depuis le démarrage de la tâche
+    pub started: Duration,
+    /// Décalage de la fin du segment depuis le démarrage de la tâche
+    pub finished: Duration,
+}
+
+impl ChunkTiming {
+    /// Durée effective du segment
+    pub fn duration(&self) -> Duration {
+        self.finished.saturating_sub(self.started)
+    }
+}
+
+/// Statistiques retournées par `DownloadManager::start` pour l'analyse des performances.
+///
+/// `per_chunk` est trié par index; les segments repris (déjà complétés) n'y figurent pas.
+#[derive(Debug, Clone, Default)]
+pub struct DownloadStats {
+    pub per_chunk: Vec<ChunkTiming>,
+    pub total_elapsed: Duration,
+    /// Première source utilisée, après redirections si elle a été sondée
+    pub final_url: Option<String>,
+    /// Contrôle par fichier d'empreinte voisin (`with_sidecar_checksums`), s'il a eu lieu
+    pub integrity: Option<Integrity>,
+    /// Sortie déjà présente conservée (`CollisionPolicy::Skip`): rien n'a été téléchargé
+    pub skipped: bool,
+}
+
+impl DownloadStats {
+    /// Total des octets reçus pendant cette exécution
+    #[cfg(test)]
+    pub fn total_bytes(&self) -> u64 {
+        self.per_chunk.iter().map(|c| c.bytes).sum()
+    }
+
+    /// Segment le plus lent (durée maximale), utile pour repérer un miroir ou une plage lente
+    pub fn slowest(&self) -> Option<&ChunkTiming> {
+        self.per_chunk.iter().max_by_key(|c| c.duration())
+    }
+}
+
 impl DownloadTask {
+    pub fn builder(url: impl Into<String>) -> DownloadTaskBuilder {
+        DownloadTaskBuilder::new(url)
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn output(&self) -> &Path {
+        &self.output
+    }
+
+    pub fn mirrors(&self) -> &[String] {
+        &self.mirrors
+    }
+
+    pub fn hashes(&self) -> &[ExpectedHash] {
+        &self.hashes
+    }
+
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    pub fn max_concurrency(&self) -> Option<usize> {
+        self.max_concurrency
+    }
+
+    /// URL principale puis miroirs, sans doublon
+    pub fn sources(&self) -> Vec<String> {
+        let mut sources = vec![self.url.clone()];
+        for mirror in &self.mirrors {
+            if !sources.contains(mirror) {
+                sources.push(mirror.clone());
+            }
+        }
+        sources
+    }
+
     /// Génère les segments à partir de la taille totale et de la taille cible des chunks.
     ///
     /// Contrats:
@@ -56,8 +336,6 @@ impl DownloadTask {
                 start,
                 end,
                 downloaded: 0,
-                // Nom de fichier de partie: `<output>.part<index>`
-                path: self.output.with_extension(format!("part{}", i))
             });
             i += 1;
             start = end + 1;
@@ -75,13 +353,12 @@ mod tests {
     #[test]
     fn test_create_chunks_exact_division() {
         // total_size = 4000 bytes, chunk_size = 1000 → should give 4 chunks of 1000 bytes each
-        let task = DownloadTask {
-            url: "https://example.com/file.bin".to_string(),
-            output: PathBuf::from("file.bin"),
-            total_size: 4000,
-            chunk_size: 1000,
-            num_chunks: 0,
-        };
+        let task = DownloadTask::builder("https://example.com/file.bin")
+            .with_output(PathBuf::from("file.bin"))
+            .with_total_size(4000)
+            .with_chunk_size(1000)
+            .build()
+            .unwrap();
 
         let chunks = task.create_chunks();
         assert_eq!(chunks.len(), 4);
@@ -92,21 +369,20 @@ mod tests {
         assert_eq!(chunks[3].start, 3000);
         assert_eq!(chunks[3].end, 3999);
 
-        // Verify path naming
-        assert_eq!(chunks[0].path, PathBuf::from("file.part0"));
-        assert_eq!(chunks[3].path, PathBuf::from("file.part3"));
+        // Verify indices
+        assert_eq!(chunks[0].index, 0);
+        assert_eq!(chunks[3].index, 3);
     }
 
     #[test]
     fn test_create_chunks_non_divisible() {
         // total_size = 4500, chunk_size = 1000 → should give 5 chunks (last smaller)
-        let task = DownloadTask {
-            url: "https://example.com/file.bin".to_string(),
-            output: PathBuf::from("video.mp4"),
-            total_size: 4500,
-            chunk_size: 1000,
-            num_chunks: 0,
-        };
+        let task = DownloadTask::builder("https://example.com/file.bin")
+            .with_output(PathBuf::from("video.mp4"))
+            .with_total_size(4500)
+            .with_chunk_size(1000)
+            .build()
+            .unwrap();
 
         let chunks = task.create_chunks();
         assert_eq!(chunks.len(), 5);
@@ -125,13 +401,12 @@ mod tests {
     #[test]
     fn test_create_chunks_single_chunk() {
         // file smaller than chunk size → only one chunk
-        let task = DownloadTask {
-            url: "https://example.com/small.txt".to_string(),
-            output: PathBuf::from("small.txt"),
-            total_size: 512,
-            chunk_size: 1024,
-            num_chunks: 0,
-        };
+        let task = DownloadTask::builder("https://example.com/small.txt")
+            .with_output(PathBuf::from("small.txt"))
+            .with_total_size(512)
+            .with_chunk_size(1024)
+            .build()
+            .unwrap();
 
         let chunks = task.create_chunks();
         assert_eq!(chunks.len(), 1);
@@ -142,15 +417,36 @@ mod tests {
     #[test]
     fn test_create_chunks_zero_total_size() {
         // Edge case: empty file
-        let task = DownloadTask {
-            url: "https://example.com/empty.txt".to_string(),
-            output: PathBuf::from("empty.txt"),
-            total_size: 0,
-            chunk_size: 1000,
-            num_chunks: 0,
-        };
+        let task = DownloadTask::builder("https://example.com/empty.txt")
+            .with_output(PathBuf::from("empty.txt"))
+            .with_chunk_size(1000)
+            .build()
+            .unwrap();
 
         let chunks = task.create_chunks();
         assert!(chunks.is_empty());
     }
+
+    #[test]
+    fn test_builder_validates_task() {
+        let task = DownloadTask::builder("https://example.com/file.bin")
+            .with_output("file.bin")
+            .with_header("Referer", "https://example.com/")
+            .with_mirror("ftp://mirror.example.net/file.bin")
+            .with_rate_limit(Some(0))
+            .with_priority(Priority::High)
+            .build()
+            .unwrap();
+        assert_eq!(task.chunk_size, 8 * 1024 * 1024);
+        assert_eq!((task.total_size, task.rate_limit), (0, None));
+        assert_eq!(task.request.headers["Referer"], "https://example.com/");
+        assert_eq!(task.sources().len(), 2);
+        assert_eq!(task.priority(), Priority::High);
+
+        // Destination manquante, segment nul, URL ou schéma invalides
+        assert!(DownloadTask::builder("https://example.com/a").build().is_err());
+        assert!(DownloadTask::builder("https://example.com/a").with_output("a").with_chunk_size(0).build().is_err());
+        assert!(DownloadTask::builder("pas une url").with_output("a").build().is_err());
+        assert!(DownloadTask::builder("https://example.com/a").with_mirror("ed2k://|file|a|").with_output("a").build().is_err());
+    }
 }
@@ -0,0 +1,152 @@
+//! Téléchargement d'un lot d'épisodes (une saison entière du scraper).
+//!
+//! `download_batch` lance au plus `concurrency` téléchargements ffmpeg à la fois, dans l'ordre
+//! du lot, et fusionne leurs événements en une `BatchProgress`: terminés, échoués, dernière
+//! progression de chaque téléchargement en cours et avancement global. Un échec n'interrompt
+//! pas le lot; le drapeau d'annulation arrête ceux en cours et ceux qui n'ont pas démarré.
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use futures::stream::{self, StreamExt};
+use crate::ffmpeg::params::{DownloadError, DownloadOptions, FfmpegProgress};
+
+/// Avancement d'un lot
+#[derive(Debug, Clone, Default)]
+pub struct BatchProgress {
+    /// Téléchargements du lot
+    pub total: usize,
+    /// Terminés avec succès
+    pub completed: usize,
+    /// Échoués ou annulés
+    pub failed: usize,
+    /// Dernière progression de chaque téléchargement en cours, par position dans le lot
+    pub running: BTreeMap<usize, FfmpegProgress>,
+}
+
+impl BatchProgress {
+    /// Téléchargements finis, réussis ou non
+    pub fn finished(&self) -> usize {
+        self.completed + self.failed
+    }
+
+    /// Avancement global de 0 à 100: un téléchargement fini compte pour 100 %, un téléchargement
+    /// en cours pour son pourcentage (0 s'il est inconnu)
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        let running: f64 = self.running.values().filter_map(|p| p.percent).sum();
+        (self.finished() as f64 * 100.0 + running) / self.total as f64
+    }
+}
+
+/// Télécharge chaque `(url, sortie)` de `inputs` avec `options`, `concurrency` à la fois (au
+/// moins 1). `on_progress` reçoit l'avancement fusionné du lot à chaque événement.
+///
+/// Rend le résultat de chaque téléchargement, dans l'ordre de `inputs`.
+///
+/// # Exemple
+/// ```no_run
+/// use scrapes::ffmpeg::{self, DownloadOptions};
+/// use std::path::PathBuf;
+/// use std::sync::Arc;
+///
+/// # async fn example() {
+/// let season = vec![
+///     ("https://cdn.example.com/s01e01.mp4".to_string(), PathBuf::from("Saison 1/Episode 01.mp4")),
+///     ("https://cdn.example.com/s01e02.mp4".to_string(), PathBuf::from("Saison 1/Episode 02.mp4")),
+/// ];
+/// let results = ffmpeg::download_batch(season, DownloadOptions::default(), 2, Some(|batch: &ffmpeg::BatchProgress| {
+///     println!("{}/{} ({:.0} %)", batch.finished(), batch.total, batch.percent());
+/// }), Arc::default()).await;
+/// println!("{} échec(s)", results.iter().filter(|r| r.is_err()).count());
+/// # }
+/// ```
+pub async fn download_batch<F>(
+    inputs: Vec<(String, PathBuf)>,
+    options: DownloadOptions,
+    concurrency: usize,
+    on_progress: Option<F>,
+    cancel: Arc<AtomicBool>,
+) -> Vec<Result<(), DownloadError>>
+where
+    F: Fn(&BatchProgress) + Send + Sync + 'static,
+{
+    let state = Arc::new(Mutex::new(BatchProgress { total: inputs.len(), ..Default::default() }));
+    let on_progress = on_progress.map(Arc::new);
+    tracing::info!(count = inputs.len(), concurrency, "Lot de téléchargements ffmpeg");
+
+    let mut results: Vec<(usize, Result<(), DownloadError>)> = stream::iter(inputs.into_iter().enumerate())
+        .map(|(index, (url, output))| {
+            let (state, on_progress, options, cancel) = (state.clone(), on_progress.clone(), options.clone(), cancel.clone());
+            async move {
+                let result = match cancel.load(Ordering::Relaxed) {
+                    true => Err(DownloadError::Cancelled),
+                    false => {
+                        let (item_state, item_report) = (state.clone(), on_progress.clone());
+                        let on_item = move |progress: &FfmpegProgress| {
+                            update(&item_state, item_report.as_deref(), |batch| {
+                                batch.running.insert(index, progress.clone());
+                            })
+                        };
+                        super::download_with_options(&url, &output, options, Some(on_item), cancel).await
+                    }
+                };
+                if let Err(e) = &result {
+                    tracing::warn!(url, output = %output.display(), error = %e, "Téléchargement du lot échoué");
+                }
+                update(&state, on_progress.as_deref(), |batch| {
+                    batch.running.remove(&index);
+                    match result.is_ok() {
+                        true => batch.completed += 1,
+                        false => batch.failed += 1,
+                    }
+                });
+                (index, result)
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Applique `change` à l'avancement partagé et transmet l'instantané à `on_progress`, hors du verrou
+fn update<F: Fn(&BatchProgress)>(state: &Mutex<BatchProgress>, on_progress: Option<&F>, change: impl FnOnce(&mut BatchProgress)) {
+    let snapshot = {
+        let mut batch = state.lock().unwrap_or_else(|e| e.into_inner());
+        change(&mut batch);
+        batch.clone()
+    };
+    if let Some(on_progress) = on_progress {
+        on_progress(&snapshot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_batch_cancelled_before_start_and_percent() {
+        let inputs: Vec<(String, PathBuf)> = (1..=3).map(|i| (format!("https://cdn.example.com/e{i}.mp4"), PathBuf::from(format!("e{i}.mp4")))).collect();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let report = seen.clone();
+        let cancel = Arc::new(AtomicBool::new(true));
+        let results = download_batch(inputs, DownloadOptions::default(), 2, Some(move |batch: &BatchProgress| {
+            report.lock().unwrap().push(batch.finished());
+        }), cancel).await;
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| matches!(r, Err(DownloadError::Cancelled))));
+        assert_eq!(*seen.lock().unwrap(), [1, 2, 3]);
+
+        let mut running = FfmpegProgress::new(HashMap::new());
+        running.percent = Some(50.0);
+        let batch = BatchProgress { total: 4, completed: 1, failed: 1, running: [(2, running)].into() };
+        assert_eq!(batch.percent(), 62.5);
+        assert_eq!(BatchProgress::default().percent(), 100.0);
+    }
+}
@@ -0,0 +1,270 @@
+//! Recherche des exécutables `ffmpeg` / `ffprobe`.
+//!
+//! Ordre de recherche: chemin fixé dans `scrapes.toml` (`[ffmpeg] path` / `ffprobe_path`),
+//! `PATH`, dossier de l'application (où `install_static_build` dépose sa copie), puis les
+//! emplacements d'installation usuels (Homebrew, `C:\ffmpeg\bin`...). Introuvable, l'outil
+//! produit une erreur `MissingBinary` explicite plutôt qu'un échec de lancement opaque.
+//!
+//! `install_static_build` télécharge une version statique de BtbN (Linux et Windows), vérifie
+//! son SHA‑256 d'après le `checksums.sha256` publié avec elle et l'extrait avec `tar` (Windows 10
+//! et suivants compris, qui lit aussi les `.zip`). Sans empreinte publiée (macOS), ou si elle ne
+//! correspond pas, rien n'est installé.
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use crate::downloader::{self, parse_sidecar, CollisionPolicy, DownloadManager, DownloadTask, HashAlgorithm, RetryPolicy};
+use crate::ffmpeg::params::DownloadError;
+
+/// Outil recherché
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Ffmpeg,
+    Ffprobe,
+}
+
+impl Tool {
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Ffmpeg => "ffmpeg",
+            Tool::Ffprobe => "ffprobe",
+        }
+    }
+
+    /// Nom du fichier exécutable (`ffmpeg.exe` sous Windows)
+    fn file_name(self) -> String {
+        format!("{}{}", self.name(), std::env::consts::EXE_SUFFIX)
+    }
+}
+
+/// Chemin de `tool`, ou `None` s'il est introuvable
+pub fn locate(tool: Tool) -> Option<PathBuf> {
+    let config = downloader::load_config().ffmpeg;
+    let configured = config.and_then(|c| match tool {
+        Tool::Ffmpeg => c.path,
+        Tool::Ffprobe => c.ffprobe_path,
+    });
+    let mut dirs: Vec<PathBuf> = install_dir().into_iter().collect();
+    dirs.extend(common_dirs());
+    locate_with(tool, configured.as_deref(), std::env::var_os("PATH"), &dirs)
+}
+
+/// Chemin de ffmpeg, ou `DownloadError::MissingBinary`
+pub fn ffmpeg() -> Result<PathBuf, DownloadError> {
+    locate(Tool::Ffmpeg).ok_or_else(|| DownloadError::MissingBinary(missing_message(Tool::Ffmpeg)))
+}
+
+/// Explication affichée quand `tool` manque
+pub fn missing_message(tool: Tool) -> String {
+    format!(
+        "{} introuvable (PATH, [ffmpeg] de scrapes.toml, emplacements usuels): installez‑le ou utilisez « Installer ffmpeg »",
+        tool.name()
+    )
+}
+
+fn locate_with(tool: Tool, configured: Option<&Path>, path_var: Option<OsString>, dirs: &[PathBuf]) -> Option<PathBuf> {
+    if let Some(path) = configured {
+        if path.is_file() {
+            return Some(path.to_owned());
+        }
+        tracing::warn!(tool = tool.name(), path = %path.display(), "Chemin configuré introuvable, recherche ailleurs");
+    }
+    let file_name = tool.file_name();
+    path_var
+        .iter()
+        .flat_map(std::env::split_paths)
+        .chain(dirs.iter().cloned())
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Dossiers d'installation habituels hors `PATH`
+fn common_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if cfg!(windows) {
+        dirs.extend([r"C:\ffmpeg\bin", r"C:\Program Files\ffmpeg\bin"].map(PathBuf::from));
+        if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+            dirs.push(PathBuf::from(local).join(r"Microsoft\WinGet\Links"));
+        }
+        if let Some(profile) = std::env::var_os("USERPROFILE") {
+            dirs.push(PathBuf::from(profile).join(r"scoop\shims"));
+        }
+    } else {
+        dirs.extend(["/usr/local/bin", "/usr/bin", "/opt/homebrew/bin", "/opt/local/bin", "/snap/bin"].map(PathBuf::from));
+    }
+    dirs
+}
+
+/// Dossier de données de l'application (`%LOCALAPPDATA%\scrapes`, `~/.local/share/scrapes`...)
+pub fn app_data_dir() -> Option<PathBuf> {
+    let env_dir = |var: &str| std::env::var_os(var).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let base = if cfg!(windows) {
+        env_dir("LOCALAPPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library/Application Support"))
+    } else {
+        env_dir("XDG_DATA_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".local/share")))
+    };
+    base.map(|dir| dir.join("scrapes"))
+}
+
+/// Dossier où `install_static_build` dépose ffmpeg et ffprobe
+pub fn install_dir() -> Option<PathBuf> {
+    app_data_dir().map(|dir| dir.join("ffmpeg").join("bin"))
+}
+
+/// Archive d'une version statique (ffmpeg et ffprobe) et fichier `sha256sum` publié avec elle
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StaticBuild {
+    url: String,
+    checksums: String,
+}
+
+impl StaticBuild {
+    /// Nom de l'archive, tel que le nomme `checksums`
+    fn file_name(&self) -> &str {
+        self.url.rsplit('/').next().unwrap_or_default()
+    }
+}
+
+/// Version statique vérifiable pour cette plateforme
+fn static_build() -> Option<StaticBuild> {
+    const BTBN: &str = "https://github.com/BtbN/FFmpeg-Builds/releases/download/latest";
+    let btbn = |name: &str| Some(StaticBuild { url: format!("{BTBN}/ffmpeg-master-latest-{name}"), checksums: format!("{BTBN}/checksums.sha256") });
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => btbn("linux64-gpl.tar.xz"),
+        ("linux", "aarch64") => btbn("linuxarm64-gpl.tar.xz"),
+        ("windows", "x86_64") => btbn("win64-gpl.zip"),
+        _ => None,
+    }
+}
+
+/// Télécharge une version statique de ffmpeg et ffprobe dans `install_dir()` et rend le chemin
+/// de ffmpeg; `locate` la trouve ensuite sans configuration
+pub async fn install_static_build() -> Result<PathBuf, DownloadError> {
+    let build = static_build().ok_or_else(|| {
+        DownloadError::Other(format!("pas de version statique vérifiable pour {}: installez ffmpeg avec le gestionnaire de paquets", std::env::consts::OS))
+    })?;
+    let bin_dir = install_dir().ok_or_else(|| DownloadError::Other("dossier de données de l'application introuvable".into()))?;
+    let work = bin_dir.with_file_name("download");
+    tokio::fs::create_dir_all(&work).await?;
+    tokio::fs::create_dir_all(&bin_dir).await?;
+
+    let manager = DownloadManager::new()
+        .with_collision_policy(CollisionPolicy::Overwrite)
+        .with_retry_policy(RetryPolicy::from_config(&downloader::load_config()));
+    let checksums = work.join("checksums.sha256");
+    let task = DownloadTask::builder(build.checksums.clone())
+        .with_output(checksums.clone())
+        .build()
+        .map_err(|e| DownloadError::Other(e.to_string()))?;
+    manager.start(task).await.map_err(|e| DownloadError::Other(format!("empreintes de ffmpeg: {e:#}")))?;
+    let expected = parse_sidecar(&tokio::fs::read_to_string(&checksums).await?, build.file_name(), HashAlgorithm::Sha256)
+        .ok_or_else(|| DownloadError::Other(format!("{} absent de {}: installation refusée", build.file_name(), build.checksums)))?;
+
+    // Le gestionnaire vérifie l'empreinte avant de renommer l'archive: un écart est une erreur
+    let archive = work.join(format!("archive{}", archive_extension(&build.url)));
+    tracing::info!(url = %build.url, sha256 = %expected.hex, "Téléchargement de ffmpeg");
+    let task = DownloadTask::builder(build.url.clone())
+        .with_output(archive.clone())
+        .with_hash(expected)
+        .build()
+        .map_err(|e| DownloadError::Other(e.to_string()))?;
+    if let Err(e) = manager.start(task).await {
+        let _ = tokio::fs::remove_dir_all(&work).await;
+        return Err(DownloadError::Other(format!("téléchargement de ffmpeg (installation refusée): {e:#}")));
+    }
+    extract(&archive, &work).await?;
+    for tool in [Tool::Ffmpeg, Tool::Ffprobe] {
+        let found = find_file(&work, &tool.file_name())
+            .ok_or_else(|| DownloadError::Other(format!("{} absent de l'archive", tool.name())))?;
+        let target = bin_dir.join(tool.file_name());
+        tokio::fs::copy(&found, &target).await?;
+        make_executable(&target)?;
+    }
+    let _ = tokio::fs::remove_dir_all(&work).await;
+    let ffmpeg = bin_dir.join(Tool::Ffmpeg.file_name());
+    tracing::info!(path = %ffmpeg.display(), "ffmpeg installé");
+    Ok(ffmpeg)
+}
+
+/// Extension conservée pour que `tar` reconnaisse le format
+fn archive_extension(url: &str) -> &'static str {
+    if url.ends_with(".tar.xz") { ".tar.xz" } else { ".zip" }
+}
+
+async fn extract(archive: &Path, dest: &Path) -> Result<(), DownloadError> {
+    let status = Command::new("tar").arg("-xf").arg(archive).arg("-C").arg(dest).status().await?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(DownloadError::Other(format!("extraction de {} impossible (tar {status})", archive.display()))),
+    }
+}
+
+/// Premier fichier nommé `name` sous `dir` (les archives rangent les binaires dans `*/bin/`)
+fn find_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current).ok()?.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if entry.file_name() == name {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate_order_and_archive_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let (path_dir, extra_dir) = (dir.path().join("path"), dir.path().join("extra"));
+        let nested = dir.path().join("archive/ffmpeg-master/bin");
+        for d in [&path_dir, &extra_dir, &nested] {
+            std::fs::create_dir_all(d).unwrap();
+        }
+        let exe = Tool::Ffmpeg.file_name();
+        std::fs::write(extra_dir.join(&exe), b"").unwrap();
+        let path_var = std::env::join_paths([&path_dir]).ok();
+
+        // Emplacement usuel faute de mieux; le PATH passe avant; le chemin configuré avant tout
+        let extra = std::slice::from_ref(&extra_dir);
+        assert_eq!(locate_with(Tool::Ffmpeg, None, path_var.clone(), extra), Some(extra_dir.join(&exe)));
+        std::fs::write(path_dir.join(&exe), b"").unwrap();
+        assert_eq!(locate_with(Tool::Ffmpeg, None, path_var.clone(), extra), Some(path_dir.join(&exe)));
+        let configured = dir.path().join("custom-ffmpeg");
+        std::fs::write(&configured, b"").unwrap();
+        assert_eq!(locate_with(Tool::Ffmpeg, Some(&configured), path_var.clone(), extra), Some(configured));
+        // Chemin configuré absent: recherche habituelle
+        assert_eq!(locate_with(Tool::Ffmpeg, Some(Path::new("/nonexistent/ffmpeg")), path_var, extra), Some(path_dir.join(&exe)));
+        assert_eq!(locate_with(Tool::Ffprobe, None, None, extra), None);
+
+        std::fs::write(nested.join(Tool::Ffprobe.file_name()), b"").unwrap();
+        assert_eq!(find_file(dir.path(), &Tool::Ffprobe.file_name()), Some(nested.join(Tool::Ffprobe.file_name())));
+        assert_eq!(archive_extension("https://x/ffmpeg-master-latest-linux64-gpl.tar.xz"), ".tar.xz");
+
+        // Empreinte de l'archive de la plateforme dans le checksums.sha256 de BtbN
+        let build = StaticBuild {
+            url: "https://x/latest/ffmpeg-master-latest-linux64-gpl.tar.xz".into(),
+            checksums: "https://x/latest/checksums.sha256".into(),
+        };
+        let listing = format!("{}  ffmpeg-master-latest-win64-gpl.zip\n{}  ffmpeg-master-latest-linux64-gpl.tar.xz\n", "a".repeat(64), "b".repeat(64));
+        assert_eq!(parse_sidecar(&listing, build.file_name(), HashAlgorithm::Sha256).unwrap().hex, "b".repeat(64));
+    }
+}
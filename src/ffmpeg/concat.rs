@@ -0,0 +1,127 @@
+//! Assemblage de plusieurs entrées en une seule sortie.
+//!
+//! Certains épisodes FZTV sont découpés en parties (`part1.mp4`, `part2.mp4`...).
+//! `concat_with_ffmpeg` écrit la liste ordonnée des entrées dans `<sortie>.concat.txt` et la passe
+//! au démultiplexeur `concat` de ffmpeg, qui les enchaîne sans réencodage (`-c copy`): les
+//! parties doivent partager codecs et paramètres. Les entrées peuvent être des fichiers locaux
+//! ou des URLs; la liste est supprimée une fois l'assemblage terminé ou abandonné.
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use crate::ffmpeg::binary;
+use crate::ffmpeg::downloader::{Attempt, discard_tmp, final_output_path, probe_duration, run_ffmpeg_once, sleep_unless_cancelled, stream_args, tmp_output_path};
+use crate::ffmpeg::params::{DownloadError, DownloadOptions, FfmpegProgress};
+use crate::ffmpeg::thumbnails;
+
+/// Options du démultiplexeur: chemins absolus et URLs acceptés dans la liste
+pub(super) const CONCAT_INPUT_ARGS: [&str; 6] = ["-f", "concat", "-safe", "0", "-protocol_whitelist", "file,http,https,tcp,tls,crypto"];
+
+/// Fichier liste d'une sortie: `episode.mp4` -> `episode.mp4.concat.txt`
+pub fn list_path(output_path: &Path) -> PathBuf {
+    let name = output_path.file_name().and_then(|n| n.to_str()).unwrap_or("output");
+    output_path.with_file_name(format!("{name}.concat.txt"))
+}
+
+/// Contenu de la liste: une ligne `file '...'` par entrée, dans l'ordre.
+///
+/// ffmpeg résout les chemins relatifs depuis le dossier de la liste: ils sont rendus absolus.
+pub fn list_file(inputs: &[String]) -> String {
+    inputs
+        .iter()
+        .map(|input| {
+            let entry = match input.contains("://") {
+                true => input.clone(),
+                false => std::path::absolute(input).map_or_else(|_| input.clone(), |p| p.to_string_lossy().into_owned()),
+            };
+            // apostrophe: fermer la chaîne, apostrophe échappée, rouvrir
+            format!("file '{}'\n", entry.replace('\'', r"'\''"))
+        })
+        .collect()
+}
+
+/// Enchaîne `inputs` dans `output_path`.
+///
+/// `opts` s'applique comme pour un téléchargement (audio seul, sous‑titres, arguments
+/// supplémentaires, blocage et redémarrages); la variante HLS et la sélection DASH sont
+/// ignorées. La durée totale, somme des durées des entrées, n'est connue que si ffprobe les
+/// mesure toutes.
+pub async fn concat_with_ffmpeg(
+    inputs: &[String],
+    output_path: impl AsRef<Path>,
+    opts: DownloadOptions,
+    mut progress_tx: mpsc::Sender<FfmpegProgress>,
+    cancel: Arc<AtomicBool>,
+) -> Result<(), DownloadError> {
+    opts.validate()?;
+    binary::ffmpeg()?;
+    if inputs.is_empty() {
+        return Err(DownloadError::InvalidOptions("aucune entrée à assembler".into()));
+    }
+    let output_path = final_output_path(output_path.as_ref(), opts.audio_output());
+    let tmp_path = tmp_output_path(&output_path, opts.audio_output());
+    let list = list_path(&output_path);
+    tokio::fs::write(&list, list_file(inputs)).await?;
+
+    let opts = DownloadOptions { variant: None, dash: None, ..opts };
+    let (args, codecs) = stream_args(&list.to_string_lossy(), &opts);
+    let args = concat_input_args(args);
+    let mut total = Some(Duration::ZERO);
+    for input in inputs {
+        total = match (total, probe_duration(input, opts.stall_timeout, &opts.http_input_args()).await) {
+            (Some(sum), Some(duration)) => Some(sum + duration),
+            _ => None,
+        };
+    }
+    tracing::info!(count = inputs.len(), output = %output_path.display(), ?total, "Assemblage des entrées");
+
+    let mut attempts = 0usize;
+    let result = loop {
+        attempts += 1;
+        match run_ffmpeg_once(&args, &codecs, &tmp_path, opts.stall_timeout, Attempt { number: attempts, total }, &mut progress_tx, &cancel).await {
+            Ok(()) => break tokio::fs::rename(&tmp_path, &output_path).await.map_err(DownloadError::Io),
+            Err(DownloadError::Cancelled) => break Err(DownloadError::Cancelled),
+            Err(e) if opts.auto_restart && e.is_retryable() && attempts < opts.max_restarts => {
+                tracing::warn!(error = %e, attempt = attempts, "Assemblage interrompu, nouvelle tentative");
+                if sleep_unless_cancelled(Duration::from_secs(2_u64.saturating_pow(attempts as u32)), &cancel).await {
+                    break Err(DownloadError::Cancelled);
+                }
+            }
+            Err(e) => break Err(e),
+        }
+    };
+    if result.is_err() {
+        discard_tmp(&tmp_path).await;
+    } else if opts.thumbnails {
+        thumbnails::generate_after_download(&output_path).await;
+    }
+    let _ = tokio::fs::remove_file(&list).await;
+    result
+}
+
+/// Options du démultiplexeur `concat`, placées devant le `-i` de la liste (le premier)
+fn concat_input_args(mut args: Vec<String>) -> Vec<String> {
+    let first = args.iter().position(|a| a == "-i").unwrap_or(0);
+    args.splice(first..first, CONCAT_INPUT_ARGS.map(String::from));
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_file_and_input_args() {
+        let inputs = vec!["https://cdn.example.com/part1.mp4?t=1".to_string(), "/tmp/l'épisode part2.mp4".to_string()];
+        assert_eq!(list_file(&inputs), "file 'https://cdn.example.com/part1.mp4?t=1'\nfile '/tmp/l'\\''épisode part2.mp4'\n");
+        assert!(list_file(&["part3.mp4".to_string()]).starts_with("file '/"));
+        assert_eq!(list_path(Path::new("/tmp/episode.mp4")), PathBuf::from("/tmp/episode.mp4.concat.txt"));
+
+        let args = vec!["-headers".to_string(), "X: 1".to_string(), "-i".to_string(), "list.txt".to_string(), "-i".to_string(), "fr.vtt".to_string()];
+        assert_eq!(concat_input_args(args), vec![
+            "-headers", "X: 1", "-f", "concat", "-safe", "0", "-protocol_whitelist", "file,http,https,tcp,tls,crypto",
+            "-i", "list.txt", "-i", "fr.vtt",
+        ]);
+    }
+}
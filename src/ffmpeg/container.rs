@@ -0,0 +1,104 @@
+//! Choix du conteneur de sortie quand les flux sont copiés (`-c copy`).
+//!
+//! Le mp4 n'accepte qu'une partie des codecs: ni Vorbis, ni PCM, ni VP8, ni sous‑titres en
+//! image (PGS, DVD)... Quand ffprobe révèle une telle piste, la sortie passe d'emblée en
+//! Matroska (`.mkv`), qui accepte à peu près tout. Sinon, un échec de mux reconnu dans stderr
+//! relance une fois le téléchargement en `.mkv`. Dans les deux cas, le nouveau chemin est
+//! annoncé par un événement de progression (`FfmpegProgress::output`) et journalisé.
+use std::path::{Path, PathBuf};
+use crate::ffmpeg::params::DownloadError;
+use crate::ffprobe::{MediaInfo, StreamKind};
+
+/// Extensions de la famille mp4 (même muxer, mêmes limites)
+const MP4_EXTENSIONS: [&str; 3] = ["mp4", "m4v", "mov"];
+const MP4_VIDEO: [&str; 9] = ["h264", "hevc", "av1", "vp9", "mpeg4", "mpeg2video", "mpeg1video", "mjpeg", "png"];
+const MP4_AUDIO: [&str; 9] = ["aac", "mp3", "mp2", "ac3", "eac3", "opus", "flac", "alac", "dts"];
+/// Sous‑titres texte, convertibles en `mov_text`
+const MP4_SUBTITLES: [&str; 6] = ["mov_text", "webvtt", "subrip", "srt", "ass", "ssa"];
+/// Messages de ffmpeg signalant un codec refusé par le muxer
+const MUX_ERRORS: [&str; 4] = [
+    "not currently supported in container",
+    "incompatible with output codec",
+    "Could not write header",
+    "muxer does not support",
+];
+
+/// `true` pour une sortie `.mp4`, `.m4v` ou `.mov`
+pub fn is_mp4_family(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| MP4_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// `episode.mp4` -> `episode.mkv`
+pub fn matroska_path(path: &Path) -> PathBuf {
+    path.with_extension("mkv")
+}
+
+/// Premier codec de `info` que le mp4 ne peut pas recevoir tel quel; les sous‑titres ne
+/// comptent que s'ils sont gardés (`keep_subtitles`)
+pub fn mp4_incompatible_codec(info: &MediaInfo, keep_subtitles: bool) -> Option<String> {
+    info.streams.iter().find_map(|stream| {
+        let codec = stream.codec.as_deref()?;
+        let accepted: &[&str] = match stream.kind {
+            StreamKind::Video => &MP4_VIDEO,
+            StreamKind::Audio => &MP4_AUDIO,
+            StreamKind::Subtitle if keep_subtitles => &MP4_SUBTITLES,
+            _ => return None,
+        };
+        (!accepted.contains(&codec)).then(|| codec.to_string())
+    })
+}
+
+/// `true` si ffmpeg a échoué parce que le muxer refuse un des codecs copiés
+pub fn is_mux_error(error: &DownloadError) -> bool {
+    matches!(error, DownloadError::UnsupportedCodec { .. })
+        || error.stderr_tail().iter().any(|line| MUX_ERRORS.iter().any(|marker| line.contains(marker)))
+}
+
+/// Arguments de sortie adaptés au Matroska: les sous‑titres y sont copiés plutôt que
+/// convertis en `mov_text`, propre au mp4
+pub fn matroska_codecs(codecs: &[String]) -> Vec<String> {
+    let mut adapted = codecs.to_vec();
+    for i in 1..adapted.len() {
+        if adapted[i - 1] == "-c:s" && adapted[i] == "mov_text" {
+            adapted[i] = "copy".to_string();
+        }
+    }
+    adapted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffprobe::StreamInfo;
+
+    fn stream(kind: StreamKind, codec: &str) -> StreamInfo {
+        StreamInfo { index: 0, kind, codec: Some(codec.into()), resolution: None, bitrate: None, channels: None, language: None }
+    }
+
+    #[test]
+    fn test_mp4_compatibility_and_matroska_fallback() {
+        let mut info = MediaInfo {
+            streams: vec![stream(StreamKind::Video, "h264"), stream(StreamKind::Audio, "aac"), stream(StreamKind::Subtitle, "hdmv_pgs_subtitle")],
+            ..Default::default()
+        };
+        assert_eq!(mp4_incompatible_codec(&info, false), None);
+        assert_eq!(mp4_incompatible_codec(&info, true).as_deref(), Some("hdmv_pgs_subtitle"));
+        info.streams[1] = stream(StreamKind::Audio, "vorbis");
+        assert_eq!(mp4_incompatible_codec(&info, false).as_deref(), Some("vorbis"));
+
+        assert!(is_mp4_family(Path::new("/tmp/a.MP4")) && !is_mp4_family(Path::new("/tmp/a.mkv")));
+        assert_eq!(matroska_path(Path::new("/tmp/ep 1.mp4")), PathBuf::from("/tmp/ep 1.mkv"));
+
+        let failed = DownloadError::FfmpegFailed {
+            code: 1,
+            stderr: vec!["[mp4 @ 0x1] Could not write header for output file #0 (incorrect codec parameters ?)".into()],
+        };
+        assert!(is_mux_error(&failed));
+        assert!(!is_mux_error(&DownloadError::HttpStatus { status: 403, stderr: Vec::new() }));
+
+        let codecs: Vec<String> = ["-c", "copy", "-map", "0:s?", "-c:s", "mov_text"].map(String::from).to_vec();
+        assert_eq!(matroska_codecs(&codecs), ["-c", "copy", "-map", "0:s?", "-c:s", "copy"]);
+    }
+}
@@ -0,0 +1,208 @@
+//! Manifestes MPEG-DASH (`.mpd`).
+//!
+//! Le démultiplexeur DASH de ffmpeg lit directement un `.mpd`, mais expose chaque
+//! représentation comme une piste: sans sélection, il choisit lui‑même une vidéo et un audio.
+//! `fetch_manifest` énumère les représentations audio et vidéo (débit, résolution, langue) et
+//! `DashSelection` fixe les deux que ffmpeg télécharge (`-map 0:v:N -map 0:a:M`; ffmpeg numérote
+//! les pistes de chaque type dans l'ordre du manifeste). La durée du manifeste
+//! (`mediaPresentationDuration`) sert de durée totale à la progression quand ffprobe manque.
+use std::time::Duration;
+use roxmltree::{Document, Node};
+use crate::ffmpeg::params::DownloadError;
+
+/// Représentation audio ou vidéo d'un manifeste
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DashRepresentation {
+    pub id: Option<String>,
+    /// Débit annoncé (`bandwidth`), en bit/s
+    pub bandwidth: Option<u64>,
+    /// Largeur × hauteur (vidéo)
+    pub resolution: Option<(u32, u32)>,
+    pub codecs: Option<String>,
+    /// Langue de l'adaptation (`lang`)
+    pub language: Option<String>,
+}
+
+/// Contenu utile d'un manifeste: représentations dans l'ordre du document
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DashManifest {
+    /// Durée totale; `None` pour un direct
+    pub duration: Option<Duration>,
+    pub video: Vec<DashRepresentation>,
+    pub audio: Vec<DashRepresentation>,
+}
+
+/// Représentations retenues, en index parmi `DashManifest::video` / `audio`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DashSelection {
+    pub video: Option<usize>,
+    pub audio: Option<usize>,
+}
+
+impl DashRepresentation {
+    /// `1920×1080 · 5.0 Mb/s · fre`
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some((w, h)) = self.resolution {
+            parts.push(format!("{w}×{h}"));
+        }
+        if let Some(bw) = self.bandwidth {
+            parts.push(format!("{:.1} Mb/s", bw as f64 / 1_000_000.0));
+        }
+        parts.extend(self.language.clone());
+        if parts.is_empty() {
+            parts.extend(self.id.clone());
+        }
+        parts.join(" · ")
+    }
+}
+
+impl DashManifest {
+    /// Sélection complétée: chaque type non choisi prend sa représentation au plus fort débit
+    pub fn select(&self, video: Option<usize>, audio: Option<usize>) -> DashSelection {
+        let best = |reps: &[DashRepresentation]| (0..reps.len()).max_by_key(|&i| reps[i].bandwidth);
+        DashSelection {
+            video: video.filter(|&i| i < self.video.len()).or_else(|| best(&self.video)),
+            audio: audio.filter(|&i| i < self.audio.len()).or_else(|| best(&self.audio)),
+        }
+    }
+}
+
+impl DashSelection {
+    /// Arguments `-map` de la sélection
+    pub fn map_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(video) = self.video {
+            args.extend(["-map".to_string(), format!("0:v:{video}")]);
+        }
+        if let Some(audio) = self.audio {
+            args.extend(["-map".to_string(), format!("0:a:{audio}")]);
+        }
+        args
+    }
+}
+
+/// `true` si `url` désigne un manifeste DASH (`.mpd`, paramètres de requête ignorés)
+pub fn is_manifest_url(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.to_ascii_lowercase().ends_with(".mpd")
+}
+
+/// Télécharge et lit le manifeste `url`
+pub async fn fetch_manifest(url: &str) -> Result<DashManifest, DownloadError> {
+    let body = reqwest::get(url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| DownloadError::Other(format!("manifeste inaccessible: {e}")))?
+        .text()
+        .await
+        .map_err(|e| DownloadError::Other(format!("manifeste illisible: {e}")))?;
+    let manifest = parse_manifest(&body)?;
+    tracing::debug!(url, video = manifest.video.len(), audio = manifest.audio.len(), "Manifeste DASH lu");
+    Ok(manifest)
+}
+
+/// Représentations audio et vidéo de la première période de `xml`
+pub fn parse_manifest(xml: &str) -> Result<DashManifest, DownloadError> {
+    let doc = Document::parse(xml).map_err(|e| DownloadError::Other(format!("manifeste DASH invalide: {e}")))?;
+    let mpd = doc.root_element();
+    let mut manifest = DashManifest {
+        duration: mpd.attribute("mediaPresentationDuration").and_then(parse_iso_duration),
+        ..Default::default()
+    };
+    let Some(period) = children(mpd, "Period").next() else {
+        return Ok(manifest);
+    };
+    for set in children(period, "AdaptationSet") {
+        for rep in children(set, "Representation") {
+            // Le type peut être porté par la représentation ou par l'adaptation
+            let kind = [rep.attribute("mimeType"), set.attribute("mimeType"), set.attribute("contentType")]
+                .into_iter()
+                .flatten()
+                .find_map(|t| t.split('/').next().filter(|k| *k == "video" || *k == "audio"));
+            let representation = DashRepresentation {
+                id: rep.attribute("id").map(String::from),
+                bandwidth: rep.attribute("bandwidth").and_then(|b| b.parse().ok()),
+                resolution: rep.attribute("width").zip(rep.attribute("height")).and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?))),
+                codecs: rep.attribute("codecs").or(set.attribute("codecs")).map(String::from),
+                language: set.attribute("lang").map(String::from),
+            };
+            match kind {
+                Some("video") => manifest.video.push(representation),
+                Some("audio") => manifest.audio.push(representation),
+                _ => {}
+            }
+        }
+    }
+    Ok(manifest)
+}
+
+fn children<'a, 'input>(node: Node<'a, 'input>, name: &'a str) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children().filter(move |c| c.is_element() && c.tag_name().name() == name)
+}
+
+/// Durée ISO 8601 d'un manifeste: `PT1H2M3.5S`, `P1DT2H`
+fn parse_iso_duration(value: &str) -> Option<Duration> {
+    let rest = value.trim().strip_prefix('P')?;
+    let (days, time) = rest.split_once('T').unwrap_or((rest, ""));
+    let mut secs = match days {
+        "" => 0.0,
+        days => days.strip_suffix('D')?.parse::<f64>().ok()? * 86_400.0,
+    };
+    let mut number = String::new();
+    for c in time.chars() {
+        let unit = match c {
+            'H' => 3600.0,
+            'M' => 60.0,
+            'S' => 1.0,
+            _ => {
+                number.push(c);
+                continue;
+            }
+        };
+        secs += number.parse::<f64>().ok()? * unit;
+        number.clear();
+    }
+    (number.is_empty() && secs > 0.0).then(|| Duration::from_secs_f64(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest_and_select_representations() {
+        let xml = r#"<?xml version="1.0"?>
+            <MPD xmlns="urn:mpeg:dash:schema:mpd:2011" mediaPresentationDuration="PT24M3.12S">
+              <Period>
+                <AdaptationSet mimeType="video/mp4" codecs="avc1.64001f">
+                  <Representation id="v1" bandwidth="800000" width="640" height="360"/>
+                  <Representation id="v2" bandwidth="5000000" width="1920" height="1080"/>
+                </AdaptationSet>
+                <AdaptationSet contentType="audio" lang="fr">
+                  <Representation id="a1" mimeType="audio/mp4" bandwidth="128000"/>
+                </AdaptationSet>
+                <AdaptationSet mimeType="text/vtt" lang="en">
+                  <Representation id="t1" bandwidth="256"/>
+                </AdaptationSet>
+              </Period>
+            </MPD>"#;
+        let manifest = parse_manifest(xml).unwrap();
+
+        assert_eq!(manifest.duration, Some(Duration::from_millis(1_443_120)));
+        assert_eq!((manifest.video.len(), manifest.audio.len()), (2, 1));
+        assert_eq!(manifest.video[1].label(), "1920×1080 · 5.0 Mb/s");
+        assert_eq!(manifest.video[0].codecs.as_deref(), Some("avc1.64001f"));
+        assert_eq!(manifest.audio[0].language.as_deref(), Some("fr"));
+
+        // Vidéo non choisie: la meilleure; choix hors limites ignoré
+        let best = manifest.select(None, Some(7));
+        assert_eq!(best, DashSelection { video: Some(1), audio: Some(0) });
+        assert_eq!(manifest.select(Some(0), None).map_args(), vec!["-map", "0:v:0", "-map", "0:a:0"]);
+
+        assert!(is_manifest_url("https://cdn.example.com/show/manifest.MPD?token=abc"));
+        assert!(!is_manifest_url("https://cdn.example.com/show/master.m3u8?f=.mpd"));
+        assert_eq!(parse_iso_duration("P1DT2H"), Some(Duration::from_secs(93_600)));
+        assert_eq!(parse_iso_duration("PT"), None);
+    }
+}
@@ -1,35 +1,149 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::sync::mpsc;
 use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use tokio::process::Command;
 use std::collections::HashMap;
 use tokio::io::{AsyncBufReadExt, BufReader};
-use crate::ffmpeg::params::{DownloadError, DownloadOptions, FfmpegProgress};
+use crate::ffmpeg::{binary, container, dash, hls, resume, segments, stderr, thumbnails};
+use crate::ffmpeg::subtitles::{self, SubtitleMode};
+use crate::downloader::RateLimiter;
+use crate::ffprobe;
+use crate::ffmpeg::params::{AudioFormat, DownloadError, DownloadOptions, FfmpegProgress, TranscodePreset};
+
+/// Intervalle de consultation du drapeau d'annulation
+const CANCEL_POLL: Duration = Duration::from_millis(200);
+
+/// Lancement de ffmpeg, tel que rapporté par les événements de progression
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Attempt {
+    /// Numéro de la tentative, à partir de 1
+    pub number: usize,
+    /// Durée totale de l'entrée, pour le pourcentage et le temps restant
+    pub total: Option<Duration>,
+}
 
 /// Starts ffmpeg to download `input_url` to `output_path`.
 /// Emits progress messages to `progress_tx`. Returns Ok(()) on success.
+///
+/// En mode audio seul (`opts.audio_only`, ou préréglage audio), le fichier produit prend
+/// l'extension du format choisi (voir `final_output_path`).
+///
+/// Les arguments supplémentaires des options sont vérifiés (`DownloadOptions::validate`) avant
+/// tout lancement, et l'absence de ffmpeg est signalée par `DownloadError::MissingBinary`.
+///
+/// Avec `opts.segmented_hls`, les segments d'une playlist HLS sont d'abord téléchargés un à un
+/// (voir `segments`): une nouvelle tentative reprend aux segments manquants.
+///
+/// `opts.read_rate` cadence la lecture de l'entrée (`-readrate`), convertie en multiple du temps
+/// réel d'après le débit annoncé par ffprobe ou la variante HLS.
+///
+/// En copie de flux vers mp4, une piste que le conteneur refuse (annoncée par ffprobe, ou échec
+/// de mux) fait passer la sortie en `.mkv` (voir `container`); le nouveau chemin est annoncé par
+/// `FfmpegProgress::output`.
+///
+/// Avec `opts.resume_vod` et une durée connue, la sortie est écrite par parties (voir `resume`):
+/// une nouvelle tentative, ou un nouvel appel après une interruption, reprend à la fin des
+/// parties déjà écrites au lieu de repartir de zéro.
+///
+/// Seules les erreurs passagères (blocage, coupure, erreur du serveur) entraînent une nouvelle
+/// tentative (`DownloadError::is_retryable`); le numéro de la tentative accompagne chaque
+/// événement de progression (`FfmpegProgress::attempt`).
+///
+/// Dès que `cancel` passe à vrai, ffmpeg est tué, le fichier temporaire supprimé et
+/// `DownloadError::Cancelled` renvoyé, sans nouvelle tentative.
 pub async fn download_with_ffmpeg(
     input_url: &str,
     output_path: impl AsRef<Path>,
     opts: DownloadOptions,
-    mut progress_tx: mpsc::Sender<FfmpegProgress>
+    mut progress_tx: mpsc::Sender<FfmpegProgress>,
+    cancel: Arc<AtomicBool>,
 ) -> Result<(), DownloadError> {
-    let output_path = output_path.as_ref().to_owned();
-    // Préserver l'extension originale pour que ffmpeg puisse détecter le format
-    // Exemple: output.mp4 -> output.mp4.part
-    let tmp_path = {
-        let file_name = output_path.file_name()
-            .and_then(|name| name.to_str())
-            .unwrap_or("output");
-        output_path.with_file_name(format!("{}.mp4", file_name))
+    opts.validate()?;
+    // ffmpeg absent: erreur explicite d'emblée, sans nouvelles tentatives
+    binary::ffmpeg()?;
+    let mut output_path = final_output_path(output_path.as_ref(), opts.audio_output());
+    let mut tmp_path = tmp_output_path(&output_path, opts.audio_output());
+    // HLS par segments: ffmpeg ne fait plus que remuxer le fichier joint, déjà local
+    let playlist = match opts.segmented_hls {
+        true => segments::resolve_playlist(input_url, opts.variant.as_ref(), &opts.request_headers())
+            .await
+            .inspect_err(|e| tracing::warn!(error = %e, "Téléchargement par segments impossible, lecture directe par ffmpeg"))
+            .ok(),
+        false => None,
     };
-
+    let (inputs, mut codecs, mut total, info, subtitles) = match &playlist {
+        Some(playlist) => {
+            let joined = segments::fetch_segments(playlist, &output_path, &opts.request_headers(), &mut progress_tx, &cancel).await?;
+            let local = DownloadOptions { variant: None, dash: None, ..opts.clone() };
+            let (inputs, codecs) = stream_args(&joined.to_string_lossy(), &local);
+            let subtitles = match opts.extract_subtitles {
+                true => probe_media(&joined.to_string_lossy(), opts.stall_timeout, &[]).await.map(|i| has_subtitles(&i)),
+                false => Some(false),
+            };
+            (inputs, codecs, Some(playlist.duration), None, subtitles)
+        }
+        None => {
+            let (inputs, codecs) = stream_args(input_url, &opts);
+            let info = probe_media(opts.variant.as_ref().map_or(input_url, |v| &v.url), opts.stall_timeout, &opts.http_input_args()).await;
+            let bitrate = info.as_ref().and_then(|i| i.bitrate).or_else(|| opts.variant.as_ref().and_then(|v| v.bandwidth));
+            let inputs = match opts.read_rate.and_then(|rate| rate.speed(bitrate, global_bandwidth_limit())) {
+                Some(speed) => {
+                    tracing::info!(speed, bitrate, "Lecture de l'entrée cadencée (-readrate)");
+                    with_input_options(inputs, &read_rate_args(speed))
+                }
+                None => inputs,
+            };
+            let subtitles = info.as_ref().map(has_subtitles);
+            (inputs, codecs, info.as_ref().and_then(|i| i.duration), info, subtitles)
+        }
+    };
+    // Copie des flux vers mp4: un codec que le conteneur refuse fait passer en .mkv
+    let remux = opts.preset.is_none() && opts.audio_output().is_none() && container::is_mp4_family(&output_path);
+    let keep_subtitles = opts.subtitles == SubtitleMode::Copy || !opts.external_subtitles.is_empty();
+    if remux && let Some(codec) = info.as_ref().and_then(|i| container::mp4_incompatible_codec(i, keep_subtitles)) {
+        tracing::info!(codec, "Codec refusé par le mp4, sortie en .mkv");
+        (output_path, tmp_path, codecs) = to_matroska(&output_path, &codecs, &progress_tx);
+    }
+    // sans ffprobe, un manifeste DASH annonce lui‑même sa durée
+    if total.is_none() && dash::is_manifest_url(input_url) {
+        total = dash::fetch_manifest(input_url).await.ok().and_then(|m| m.duration);
+    }
+    let plan = subtitle_plan(opts.extract_subtitles, subtitles);
+    match plan {
+        SubtitlePlan::Skip if opts.extract_subtitles => tracing::info!("Aucune piste de sous‑titres annoncée, pas de .srt"),
+        SubtitlePlan::AfterDownload => tracing::warn!("Pistes de sous‑titres inconnues (échec de ffprobe), extraction tentée sur le fichier terminé"),
+        _ => {}
+    }
+    let extract = plan == SubtitlePlan::Inline;
+    if let Some(total) = total.filter(|_| opts.resume_vod && playlist.is_none()) {
+        resume::download_in_parts(&inputs, &codecs, &output_path, total, &opts, &mut progress_tx, &cancel).await?;
+        // les parties écrites, seule la sortie locale peut encore fournir les sous‑titres
+        if plan != SubtitlePlan::Skip && keep_subtitles {
+            extract_subtitles(&output_path).await;
+        } else if plan != SubtitlePlan::Skip {
+            tracing::warn!("Sous‑titres non extraits: la reprise par parties ne les garde qu'avec SubtitleMode::Copy");
+        }
+        finish_download(&output_path, &opts).await;
+        return Ok(());
+    }
     let mut attempts = 0usize;
 
     loop {
         attempts += 1;
-        let res = run_ffmpeg_once(input_url, &tmp_path, opts.stall_timeout, &mut progress_tx).await;
+        // sous‑titres en sortie supplémentaire de la même commande, placée avant la principale
+        // (dont les options la suivent): l'entrée n'est lue qu'une fois
+        let tmp_srt = subtitle_path(&tmp_path);
+        let outputs = match extract {
+            true => [subtitle_output_args(&tmp_srt.to_string_lossy()), codecs.clone()].concat(),
+            false => codecs.clone(),
+        };
+        let res = run_ffmpeg_once(&inputs, &outputs, &tmp_path, opts.stall_timeout, Attempt { number: attempts, total }, &mut progress_tx, &cancel).await;
+        if res.is_err() && extract {
+            let _ = tokio::fs::remove_file(&tmp_srt).await;
+        }
 
         match res {
             Ok(()) => {
@@ -37,14 +151,40 @@ pub async fn download_with_ffmpeg(
                 tokio::fs::rename(&tmp_path, &output_path)
                     .await
                     .map_err(DownloadError::Io)?;
+                if playlist.is_some() {
+                    let _ = tokio::fs::remove_dir_all(segments::work_dir(&output_path)).await;
+                }
+                if extract {
+                    keep_subtitle_file(&tmp_srt, &subtitle_path(&output_path)).await;
+                } else if plan == SubtitlePlan::AfterDownload && keep_subtitles {
+                    extract_subtitles(&output_path).await;
+                } else if plan == SubtitlePlan::AfterDownload {
+                    tracing::warn!("Sous‑titres non extraits: sans ffprobe, le fichier terminé ne les garde qu'avec SubtitleMode::Copy");
+                }
+                finish_download(&output_path, &opts).await;
                 return Ok(());
             }
+            Err(DownloadError::Cancelled) => {
+                discard_tmp(&tmp_path).await;
+                return Err(DownloadError::Cancelled);
+            }
+            // mux refusé par le mp4: un seul nouvel essai en .mkv, hors du compte des redémarrages
+            Err(e) if remux && container::is_mux_error(&e) && container::is_mp4_family(&output_path) => {
+                tracing::warn!(error = %e, "Flux incompatibles avec le mp4, nouvel essai en .mkv");
+                discard_tmp(&tmp_path).await;
+                (output_path, tmp_path, codecs) = to_matroska(&output_path, &codecs, &progress_tx);
+                attempts -= 1;
+            }
             Err(e) => {
-                // si auto_restart activé et tentatives < max, réessayer ; sinon retourner l'erreur.
-                if opts.auto_restart && attempts < opts.max_restarts {
-                    // petit délai exponentiel
+                // si auto_restart activé, erreur passagère et tentatives < max, réessayer ; sinon retourner l'erreur.
+                if opts.auto_restart && e.is_retryable() && attempts < opts.max_restarts {
+                    tracing::warn!(error = %e, attempt = attempts, "Échec passager de ffmpeg, nouvelle tentative");
+                    // petit délai exponentiel, interrompu par une annulation
                     let backoff = Duration::from_secs(2_u64.saturating_pow(attempts as u32));
-                    tokio::time::sleep(backoff).await;
+                    if sleep_unless_cancelled(backoff, &cancel).await {
+                        discard_tmp(&tmp_path).await;
+                        return Err(DownloadError::Cancelled);
+                    }
                     // continuer la boucle pour réessayer
                     continue;
                 } else {
@@ -55,23 +195,272 @@ pub async fn download_with_ffmpeg(
     }
 }
 
-async fn run_ffmpeg_once(
-    input_url: &str,
+/// Étapes suivant une sortie finalisée: miniatures
+async fn finish_download(output_path: &Path, opts: &DownloadOptions) {
+    if opts.thumbnails {
+        thumbnails::generate_after_download(output_path).await;
+    }
+}
+
+/// Passe la sortie en Matroska: nouveaux chemins final et temporaire, codecs adaptés; le
+/// changement est annoncé par un événement de progression
+fn to_matroska(output_path: &Path, codecs: &[String], progress_tx: &mpsc::Sender<FfmpegProgress>) -> (PathBuf, PathBuf, Vec<String>) {
+    let mkv = container::matroska_path(output_path);
+    let notice = FfmpegProgress { output: Some(mkv.clone()), ..FfmpegProgress::new(HashMap::new()) };
+    let _ = progress_tx.try_send(notice);
+    let tmp = tmp_output_path(&mkv, None);
+    (mkv, tmp, container::matroska_codecs(codecs))
+}
+
+/// Attend `duration` en consultant `cancel`; `true` si l'annulation est survenue entre‑temps
+pub(super) async fn sleep_unless_cancelled(duration: Duration, cancel: &AtomicBool) -> bool {
+    let deadline = tokio::time::Instant::now() + duration;
+    while tokio::time::Instant::now() < deadline {
+        if cancel.load(Ordering::Relaxed) {
+            return true;
+        }
+        tokio::time::sleep_until(deadline.min(tokio::time::Instant::now() + CANCEL_POLL)).await;
+    }
+    cancel.load(Ordering::Relaxed)
+}
+
+/// Supprime le fichier temporaire d'un téléchargement annulé (absent si ffmpeg n'a rien écrit)
+pub(super) async fn discard_tmp(tmp_path: &Path) {
+    match tokio::fs::remove_file(tmp_path).await {
+        Ok(()) => tracing::info!(path = %tmp_path.display(), "Fichier temporaire ffmpeg supprimé après annulation"),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => tracing::warn!(path = %tmp_path.display(), error = %e, "Impossible de supprimer le fichier temporaire ffmpeg"),
+    }
+}
+
+/// Durée totale de `input_url` selon ffprobe (`crate::ffprobe`), pour exprimer la progression
+/// en pourcentage.
+///
+/// `None` pour un direct (durée `N/A`), si ffprobe est absent, échoue ou ne répond pas avant
+/// `timeout`: la progression reste alors limitée aux champs bruts de ffmpeg. `input_args`
+/// (en‑têtes HTTP) précèdent l'entrée comme pour ffmpeg.
+pub async fn probe_duration(input_url: &str, timeout: Duration, input_args: &[String]) -> Option<Duration> {
+    probe_media(input_url, timeout, input_args).await.and_then(|info| info.duration)
+}
+
+/// Comme `probe_duration`, avec tout ce que ffprobe rapporte (débit, pistes)
+async fn probe_media(input_url: &str, timeout: Duration, input_args: &[String]) -> Option<ffprobe::MediaInfo> {
+    match ffprobe::probe_with_timeout(input_url, timeout, input_args).await {
+        Ok(info) => Some(info),
+        Err(e) => {
+            tracing::debug!(url = input_url, error = %e, "Durée inconnue, progression sans pourcentage");
+            None
+        }
+    }
+}
+
+/// Limite de bande passante globale appliquée maintenant (plages horaires comprises), en octets/s
+fn global_bandwidth_limit() -> u64 {
+    RateLimiter::global().bytes_per_sec_at(chrono::Local::now().time())
+}
+
+/// Option d'entrée cadençant la lecture à `speed` fois le temps réel
+fn read_rate_args(speed: f64) -> Vec<String> {
+    vec!["-readrate".to_string(), format!("{speed:.3}")]
+}
+
+/// Fichier temporaire écrit par ffmpeg, renommé en `output_path` une fois terminé.
+///
+/// L'extension est répétée pour que ffmpeg puisse détecter le format: `output.mp4` -> `output.mp4.mp4`,
+/// `output.mkv` -> `output.mkv.mkv` (mp4 faute d'extension)
+pub(super) fn tmp_output_path(output_path: &Path, audio_only: Option<AudioFormat>) -> PathBuf {
+    let file_name = output_path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("output");
+    let extension = audio_only
+        .map(AudioFormat::extension)
+        .or_else(|| output_path.extension().and_then(|e| e.to_str()))
+        .unwrap_or("mp4");
+    output_path.with_file_name(format!("{}.{}", file_name, extension))
+}
+
+/// Fichier réellement produit: en mode audio seul, `episode.mp4` -> `episode.m4a`
+pub fn final_output_path(output_path: &Path, audio_only: Option<AudioFormat>) -> PathBuf {
+    match audio_only {
+        Some(format) => output_path.with_extension(format.extension()),
+        None => output_path.to_owned(),
+    }
+}
+
+/// Arguments de codec: copie de tous les flux, piste audio seule dans le format demandé, ou
+/// réencodage du préréglage
+fn codec_args(audio_only: Option<AudioFormat>, preset: Option<TranscodePreset>) -> Vec<String> {
+    let args: &[&str] = match (audio_only, preset) {
+        (Some(format), _) => format.codec_args(),
+        (None, Some(preset)) => preset.codec_args(),
+        (None, None) => &["-c", "copy"],
+    };
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+/// Arguments d'entrée (`-i` et options associées) et de sortie (sélection des pistes, codecs)
+/// de la commande ffmpeg pour `opts`
+pub(super) fn stream_args(input_url: &str, opts: &DownloadOptions) -> (Vec<String>, Vec<String>) {
+    let mut codecs = codec_args(opts.audio_only, opts.preset);
+    // encodeur matériel à la place de libx264/libx265 (préréglage vidéo seulement)
+    let hwaccel = opts.hwaccel.filter(|_| opts.preset.is_some() && opts.audio_output().is_none());
+    if let Some(accel) = hwaccel {
+        codecs = accel.encoder_args(&codecs);
+    }
+    // la variante HLS choisie remplace la playlist maîtresse en entrée
+    let mut inputs = hls::input_args(input_url, opts.variant.as_ref());
+    // représentations DASH choisies (l'audio seul ne télécharge pas la vidéo)
+    if let Some(selection) = opts.dash.filter(|_| opts.variant.is_none()) {
+        let video = selection.video.filter(|_| opts.audio_output().is_none());
+        inputs.extend(dash::DashSelection { video, ..selection }.map_args());
+    }
+    // sous‑titres intégrés et externes (sans objet en audio seul)
+    if opts.audio_output().is_none() {
+        let input_count = inputs.iter().filter(|a| *a == "-i").count();
+        let explicit_maps = inputs.iter().any(|a| a == "-map");
+        let (subtitle_inputs, subtitle_outputs) = subtitles::mux_args(opts.subtitles, &opts.external_subtitles, input_count, explicit_maps);
+        // les entrées de sous‑titres doivent précéder les -map de la variante
+        let first_map = inputs.iter().position(|a| a == "-map").unwrap_or(inputs.len());
+        inputs.splice(first_map..first_map, subtitle_inputs);
+        codecs.extend(subtitle_outputs);
+    }
+    codecs.extend(opts.metadata.args());
+    codecs.extend(opts.extra_output_args.iter().cloned());
+    // en‑têtes HTTP puis arguments de l'utilisateur, qui ont ainsi le dernier mot
+    let mut input_options = opts.http_input_args();
+    input_options.extend(opts.extra_input_args.iter().cloned());
+    let mut inputs = with_input_options(inputs, &input_options);
+    // décodage matériel de l'entrée principale
+    if let Some(accel) = hwaccel {
+        inputs.splice(0..0, accel.input_args());
+    }
+    (inputs, codecs)
+}
+
+/// Répète `extra` devant chaque `-i`: une option d'entrée ne vaut que pour l'entrée qui la suit
+pub(super) fn with_input_options(inputs: Vec<String>, extra: &[String]) -> Vec<String> {
+    let mut spliced = Vec::with_capacity(inputs.len() + extra.len());
+    for arg in inputs {
+        if arg == "-i" {
+            spliced.extend(extra.iter().cloned());
+        }
+        spliced.push(arg);
+    }
+    spliced
+}
+
+/// Chemin du fichier de sous‑titres associé à une sortie: `episode.mp4` -> `episode.srt`
+pub fn subtitle_path(output_path: &Path) -> PathBuf {
+    output_path.with_extension("srt")
+}
+
+/// `true` si ffprobe annonce au moins une piste de sous‑titres
+fn has_subtitles(info: &ffprobe::MediaInfo) -> bool {
+    info.streams_of(ffprobe::StreamKind::Subtitle).next().is_some()
+}
+
+/// Moment de l'extraction des sous‑titres demandés
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SubtitlePlan {
+    /// Non demandés, ou aucune piste annoncée par ffprobe
+    Skip,
+    /// Sortie `.srt` supplémentaire de la commande ffmpeg
+    Inline,
+    /// ffprobe en échec: extraction depuis le fichier terminé (`extract_subtitles`)
+    AfterDownload,
+}
+
+/// `probed`: présence de pistes selon ffprobe, `None` si le sondage a échoué
+fn subtitle_plan(requested: bool, probed: Option<bool>) -> SubtitlePlan {
+    match (requested, probed) {
+        (false, _) | (true, Some(false)) => SubtitlePlan::Skip,
+        (true, Some(true)) => SubtitlePlan::Inline,
+        (true, None) => SubtitlePlan::AfterDownload,
+    }
+}
+
+/// Seconde sortie ffmpeg copiant les pistes de sous‑titres de la première entrée vers `srt_path`.
+///
+/// `-map 0:s?` rend la sélection optionnelle; une sortie sans aucune piste reste toutefois une
+/// erreur pour ffmpeg, d'où la vérification préalable (`has_subtitles`).
+fn subtitle_output_args(srt_path: &str) -> Vec<String> {
+    ["-map", "0:s?", "-c:s", "srt", srt_path].iter().map(|s| s.to_string()).collect()
+}
+
+/// Renomme le `.srt` écrit à côté du fichier temporaire; un fichier vide est supprimé
+async fn keep_subtitle_file(tmp_srt: &Path, srt_path: &Path) {
+    match tokio::fs::metadata(tmp_srt).await {
+        Ok(meta) if meta.len() > 0 => match tokio::fs::rename(tmp_srt, srt_path).await {
+            Ok(()) => tracing::info!(path = %srt_path.display(), "Sous‑titres extraits"),
+            Err(e) => tracing::warn!(error = %e, "Impossible de renommer les sous‑titres"),
+        },
+        _ => {
+            tracing::warn!("Aucun sous‑titre extrait");
+            let _ = tokio::fs::remove_file(tmp_srt).await;
+        }
+    }
+}
+
+/// Extrait les sous‑titres d'une sortie locale déjà finalisée dans un `.srt` à côté d'elle.
+///
+/// Non bloquant pour le téléchargement: un échec (aucune piste, codec non convertible) est
+/// seulement journalisé et le fichier vide éventuel est supprimé.
+async fn extract_subtitles(output_path: &Path) {
+    let srt_path = subtitle_path(output_path);
+    let ffmpeg = match binary::ffmpeg() {
+        Ok(ffmpeg) => ffmpeg,
+        Err(e) => {
+            tracing::warn!(error = %e, "Sous‑titres non extraits");
+            return;
+        }
+    };
+    let status = Command::new(ffmpeg)
+        .args(["-y", "-nostats", "-i"])
+        .arg(output_path)
+        .args(subtitle_output_args(&srt_path.to_string_lossy()))
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await;
+    match status {
+        Ok(status) if status.success() => {
+            tracing::info!(path = %srt_path.display(), "Sous‑titres extraits");
+        }
+        Ok(status) => {
+            tracing::warn!(code = ?status.code(), "Aucun sous‑titre extrait");
+            let _ = tokio::fs::remove_file(&srt_path).await;
+        }
+        Err(e) => tracing::warn!(error = %e, "Impossible de lancer ffmpeg pour les sous‑titres"),
+    }
+}
+
+pub(super) async fn run_ffmpeg_once(
+    inputs: &[String],
+    codecs: &[String],
     tmp_path: &Path,
     stall_timeout: Duration,
-    progress_tx: &mut mpsc::Sender<FfmpegProgress>
+    attempt: Attempt,
+    progress_tx: &mut mpsc::Sender<FfmpegProgress>,
+    cancel: &AtomicBool,
 ) -> Result<(), DownloadError> {
+    if cancel.load(Ordering::Relaxed) {
+        return Err(DownloadError::Cancelled);
+    }
+    // pourcentage et temps restant, si la durée totale est connue
+    let started = tokio::time::Instant::now();
+    let snapshot = |fields: &HashMap<String, String>| FfmpegProgress {
+        attempt: attempt.number,
+        ..FfmpegProgress::new(fields.clone()).with_estimate(attempt.total, started.elapsed())
+    };
     // Construire les arguments ffmpeg :
-    // -y écraser, -i entré, -c copy minimiser le réencodage –progress pipe :1, -nostats, output.tmp
-    let mut cmd = Command::new("ffmpeg");
+    // -y écraser, entrées (-i ...), codecs (-c copy minimiser le réencodage, ou audio seul) –progress pipe :1, -nostats, output.tmp
+    let mut cmd = Command::new(binary::ffmpeg()?);
     let output_str = tmp_path.to_str()
         .ok_or_else(|| DownloadError::Other("chemin de sortie invalide (UTF-8 requis)".into()))?;
+    cmd.arg("-y");
+    cmd.args(inputs);
+    cmd.args(codecs);
     cmd.args(&[
-        "-y",
-        "-i",
-        input_url,
-        "-c",
-        "copy",
         "-progress",
         "pipe:1",
         "-nostats",
@@ -81,6 +470,7 @@ async fn run_ffmpeg_once(
     // ensure stdout is piped (progress), stderr inherited or captured if you prefer
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
+    cmd.kill_on_drop(true);
 
     let mut child = cmd.spawn().map_err(DownloadError::Io)?;
 
@@ -93,11 +483,17 @@ async fn run_ffmpeg_once(
         .take()
         .ok_or_else(|| DownloadError::Other("impossible de prendre stderr de ffmpeg".into()))?;
 
-    // Lire stderr de manière concurrente (on le transmet à stderr pour le débogage)
+    // Lire stderr de manière concurrente: journalisé, et les dernières lignes gardées pour
+    // expliquer un échec
+    let tail = stderr::StderrTail::default();
     let mut serr = BufReader::new(stderr).lines();
-    tokio::spawn(async move {
-        while let Ok(Some(line)) = serr.next_line().await {
-            eprintln!("[ffmpeg stderr] {}", line);
+    let stderr_task = tokio::spawn({
+        let tail = tail.clone();
+        async move {
+            while let Ok(Some(line)) = serr.next_line().await {
+                tracing::debug!(target: "ffmpeg", "{line}");
+                tail.push(line);
+            }
         }
     });
 
@@ -106,19 +502,22 @@ async fn run_ffmpeg_once(
     // ffmpeg -progress produit des paires clé=valeur, séparées par des lignes vides, et "progress=end" à la fin
     let mut current: HashMap<String, String> = HashMap::new();
 
+    // échéance de blocage, repoussée à chaque ligne reçue
+    let mut stall_deadline = tokio::time::Instant::now() + stall_timeout;
     loop {
         // lire la prochaine ligne avec timeout pour détecter le blocage
         let read_fut = reader.next_line();
-        let timeout = tokio::time::sleep(stall_timeout);
+        let timeout = tokio::time::sleep_until(stall_deadline);
         tokio::select! {
             maybe_line = read_fut => {
+                stall_deadline = tokio::time::Instant::now() + stall_timeout;
                 match maybe_line {
                     Ok(Some(line)) => {
                         let line = line.trim().to_string();
                         if line.is_empty() {
                             // ligne vide → limite de paquet de progression ; émettre si on a quelque chose
                             if !current.is_empty() {
-                                let _ = progress_tx.try_send(FfmpegProgress::new(current.clone()));
+                                let _ = progress_tx.try_send(snapshot(&current));
                                 current.clear();
                             }
                             continue;
@@ -130,7 +529,7 @@ async fn run_ffmpeg_once(
                             current.insert(k.to_string(), v.to_string());
                             // émission immédiate de progression pour certaines clés si désirée :
                             if k == "out_time_ms" || k == "progress" {
-                                let _ = progress_tx.try_send(FfmpegProgress::new(current.clone()));
+                                let _ = progress_tx.try_send(snapshot(&current));
                                 // ne pas effacer ; continuer à accumuler
                             }
                         }
@@ -148,11 +547,18 @@ async fn run_ffmpeg_once(
             }
             _ = timeout => {
                 // blocage détecté
-                eprintln!("blocage ffmpeg détecté (aucune progression pendant {:?}), arrêt du processus", stall_timeout);
+                tracing::warn!(?stall_timeout, "Blocage de ffmpeg détecté, arrêt du processus");
                 // tentative de tuer le processus enfant
                 let _ = child.kill().await;
                 // retourner une erreur pour que l'appelant puisse choisir de redémarrer
-                return Err(DownloadError::Other("blocage détecté".into()));
+                return Err(DownloadError::Stalled(stall_timeout));
+            }
+            _ = tokio::time::sleep(CANCEL_POLL) => {
+                if cancel.load(Ordering::Relaxed) {
+                    tracing::info!("Téléchargement ffmpeg annulé, arrêt du processus");
+                    let _ = child.kill().await;
+                    return Err(DownloadError::Cancelled);
+                }
             }
         }
     }
@@ -162,18 +568,23 @@ async fn run_ffmpeg_once(
     if status.success() {
         // émettre la progression finale avec les champs restants
         if !current.is_empty() {
-            let _ = progress_tx.try_send(FfmpegProgress::new(current.clone()));
+            let _ = progress_tx.try_send(snapshot(&current));
         }
         Ok(())
     } else {
+        // stderr se ferme avec le processus: attendre ses dernières lignes
+        let _ = stderr_task.await;
         let code = status.code().unwrap_or(-1);
-        Err(DownloadError::FfmpegExit(code))
+        let error = stderr::classify(code, tail.lines());
+        tracing::warn!(code, error = %error, "Échec de ffmpeg");
+        Err(error)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ffmpeg::params::ReadRate;
     use tempfile::TempDir;
     use tokio::time::Duration;
 
@@ -183,6 +594,213 @@ mod tests {
         assert_eq!(opts.stall_timeout, Duration::from_secs(20));
         assert!(opts.auto_restart);
         assert_eq!(opts.max_restarts, 3);
+        assert!(!opts.extract_subtitles);
+    }
+
+    #[test]
+    fn test_subtitle_plan_falls_back_after_failed_probe() {
+        assert_eq!(subtitle_plan(false, None), SubtitlePlan::Skip);
+        assert_eq!(subtitle_plan(true, Some(false)), SubtitlePlan::Skip);
+        assert_eq!(subtitle_plan(true, Some(true)), SubtitlePlan::Inline);
+        assert_eq!(subtitle_plan(true, None), SubtitlePlan::AfterDownload);
+    }
+
+    #[test]
+    fn test_subtitle_args_map_optional_stream() {
+        assert_eq!(subtitle_output_args("episode.srt"), vec!["-map", "0:s?", "-c:s", "srt", "episode.srt"]);
+        assert_eq!(subtitle_path(Path::new("/tmp/episode.mp4")), PathBuf::from("/tmp/episode.srt"));
+    }
+
+    #[test]
+    fn test_http_input_args_before_each_input() {
+        let opts = DownloadOptions {
+            user_agent: Some("Mozilla/5.0".into()),
+            headers: [("Referer", "https://fztvseries.mobi/"), ("Cookie", "PHPSESSID=abc")]
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            external_subtitles: vec![subtitles::ExternalSubtitle::new("https://cdn.example.com/fr.vtt")],
+            ..Default::default()
+        };
+        // une ligne `Nom: valeur\r\n` par en‑tête, dans l'ordre du BTreeMap
+        let headers = "Cookie: PHPSESSID=abc\r\nReferer: https://fztvseries.mobi/\r\n";
+        assert_eq!(opts.http_input_args(), vec!["-user_agent", "Mozilla/5.0", "-headers", headers]);
+        assert!(DownloadOptions::default().http_input_args().is_empty());
+
+        // répétées devant l'entrée principale comme devant le sous‑titre externe
+        let (inputs, _) = stream_args("https://cdn.example.com/video.m3u8", &opts);
+        assert_eq!(inputs, vec![
+            "-user_agent", "Mozilla/5.0", "-headers", headers, "-i", "https://cdn.example.com/video.m3u8",
+            "-user_agent", "Mozilla/5.0", "-headers", headers, "-i", "https://cdn.example.com/fr.vtt",
+        ]);
+        assert!(opts.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_broken_headers() {
+        let with_header = |name: &str, value: &str| DownloadOptions {
+            headers: [(name.to_string(), value.to_string())].into_iter().collect(),
+            ..Default::default()
+        };
+        assert!(with_header("Referer", "https://example.com/").validate().is_ok());
+        // nom vide, à deux‑points ou sur plusieurs lignes; valeur injectant un en‑tête
+        for (name, value) in [("", "x"), ("Referer:", "x"), ("X-A\r\nX-B", "x"), ("Referer", "a\r\nX-Injected: 1"), ("Referer", "a\nb")] {
+            assert!(matches!(with_header(name, value).validate(), Err(DownloadError::InvalidOptions(_))), "{name:?}: {value:?}");
+        }
+        let user_agent = DownloadOptions { user_agent: Some("UA\r\nX-Injected: 1".into()), ..Default::default() };
+        assert!(user_agent.validate().is_err());
+    }
+
+    #[test]
+    fn test_percent_and_eta_from_total_duration() {
+        let total = Some(Duration::from_millis(120_500));
+
+        let mut fields = HashMap::new();
+        fields.insert("out_time_ms".to_string(), "30125000".to_string());
+        fields.insert("progress".to_string(), "continue".to_string());
+        // Un quart du média en 10 s: encore 30 s au même rythme
+        let progress = FfmpegProgress::new(fields.clone()).with_estimate(total, Duration::from_secs(10));
+        assert_eq!(progress.out_time, Some(Duration::from_micros(30_125_000)));
+        assert_eq!(progress.percent, Some(25.0));
+        assert_eq!(progress.eta, Some(Duration::from_secs(30)));
+
+        // DASH: position légèrement négative au démarrage
+        let mut early = HashMap::new();
+        early.insert("out_time_us".to_string(), "-23220".to_string());
+        assert_eq!(FfmpegProgress::new(early).out_time, Some(Duration::ZERO));
+
+        // Direct: pas de durée, pas d'estimation
+        let live = FfmpegProgress::new(fields.clone()).with_estimate(None, Duration::from_secs(10));
+        assert_eq!((live.percent, live.eta), (None, None));
+
+        fields.insert("progress".to_string(), "end".to_string());
+        let done = FfmpegProgress::new(fields).with_estimate(total, Duration::from_secs(40));
+        assert_eq!((done.percent, done.eta), (Some(100.0), Some(Duration::ZERO)));
+
+        // Réencodage à 0.5x: le temps restant suit la vitesse courante de ffmpeg
+        let mut encoding = HashMap::new();
+        encoding.insert("out_time_us".to_string(), "60000000".to_string());
+        encoding.insert("speed".to_string(), " 0.5x".to_string());
+        let progress = FfmpegProgress::new(encoding).with_estimate(Some(Duration::from_secs(120)), Duration::from_secs(90));
+        assert_eq!((progress.percent, progress.eta), (Some(50.0), Some(Duration::from_secs(120))));
+    }
+
+    #[test]
+    fn test_read_rate_follows_media_bitrate() {
+        // 250 KiB/s pour un flux à 4 Mbit/s: un peu moins de la moitié du temps réel
+        let speed = ReadRate::BytesPerSec(250 * 1024).speed(Some(4_000_000), 0).unwrap();
+        assert!((speed - 0.512).abs() < 1e-9);
+        assert_eq!(ReadRate::GlobalLimit.speed(Some(4_000_000), 1_000_000), Some(2.0));
+        // Sans limite globale, rien ne cadence; débit du média inconnu: temps réel
+        assert_eq!(ReadRate::GlobalLimit.speed(Some(4_000_000), 0), None);
+        assert_eq!(ReadRate::BytesPerSec(1024).speed(None, 0), Some(1.0));
+        assert_eq!(ReadRate::BytesPerSec(1024).speed(Some(100_000_000), 0), Some(0.1));
+        assert_eq!(ReadRate::Speed(2.0).speed(None, 123), Some(2.0));
+
+        let inputs = with_input_options(vec!["-i".into(), "a.m3u8".into()], &read_rate_args(0.512));
+        assert_eq!(inputs, ["-readrate", "0.512", "-i", "a.m3u8"]);
+        let invalid = DownloadOptions { read_rate: Some(ReadRate::Speed(0.0)), ..Default::default() };
+        assert!(matches!(invalid.validate(), Err(DownloadError::InvalidOptions(_))));
+    }
+
+    #[test]
+    fn test_progress_typed_fields() {
+        let block = "frame=1440\nfps=96.3\nbitrate=1843.2kbits/s\ntotal_size=13828096\nout_time_us=60000000\nout_time=00:01:00.000000\nspeed=4.01x\nprogress=continue";
+        let fields: HashMap<String, String> = block.lines().filter_map(|l| l.split_once('=')).map(|(k, v)| (k.into(), v.into())).collect();
+        let progress = FfmpegProgress::new(fields);
+        assert_eq!(progress.out_time, Some(Duration::from_secs(60)));
+        assert_eq!((progress.bitrate_kbps, progress.speed), (Some(1843.2), Some(4.01)));
+        assert_eq!((progress.frames, progress.total_size), (Some(1440), Some(13_828_096)));
+        // Clés sans champ dédié conservées telles quelles
+        assert_eq!(progress.fields.get("fps").map(String::as_str), Some("96.3"));
+
+        // Début de flux: ffmpeg n'a encore rien mesuré
+        let fields = [("bitrate", "N/A"), ("speed", "N/A"), ("out_time_us", "N/A")].map(|(k, v)| (k.to_string(), v.to_string()));
+        let progress = FfmpegProgress::new(fields.into_iter().collect());
+        assert_eq!((progress.out_time, progress.bitrate_kbps, progress.speed), (None, None, None));
+    }
+
+    #[test]
+    fn test_audio_only_codec_args_and_output_extension() {
+        assert_eq!(codec_args(None, None), vec!["-c", "copy"]);
+        assert_eq!(codec_args(Some(AudioFormat::M4a), None), vec!["-vn", "-sn", "-acodec", "copy"]);
+        assert_eq!(codec_args(Some(AudioFormat::Opus), None), vec!["-vn", "-sn", "-acodec", "libopus", "-b:a", "128k"]);
+        let output = Path::new("/tmp/episode.mp4");
+        assert_eq!(final_output_path(output, None), PathBuf::from("/tmp/episode.mp4"));
+        assert_eq!(final_output_path(output, Some(AudioFormat::Mp3)), PathBuf::from("/tmp/episode.mp3"));
+        // Le fichier temporaire garde le conteneur de la sortie
+        assert_eq!(tmp_output_path(Path::new("/tmp/episode.mkv"), None), PathBuf::from("/tmp/episode.mkv.mkv"));
+        assert_eq!(tmp_output_path(output, Some(AudioFormat::Mp3)), PathBuf::from("/tmp/episode.mp4.mp3"));
+
+        // Préréglages: réencodage à la place de la copie; l'audio seul garde la priorité
+        assert_eq!(codec_args(None, Some(TranscodePreset::H265Compact))[..2], ["-c:v", "libx265"]);
+        assert_eq!(codec_args(Some(AudioFormat::M4a), Some(TranscodePreset::H264Hd))[0], "-vn");
+        let audio = DownloadOptions { preset: Some(TranscodePreset::Audio128k), ..Default::default() };
+        assert_eq!(audio.audio_output(), Some(AudioFormat::M4a));
+        assert!(stream_args("in.mp4", &audio).1.ends_with(&["-vn", "-sn", "-c:a", "aac", "-b:a", "128k"].map(String::from)));
+        let both = DownloadOptions { audio_only: Some(AudioFormat::Mp3), ..audio };
+        assert!(matches!(both.validate(), Err(DownloadError::InvalidOptions(_))));
+    }
+
+    #[test]
+    fn test_extra_args_validated_and_spliced_before_each_input() {
+        let whitelist = vec!["-protocol_whitelist".to_string(), "file,https,tcp,tls".to_string()];
+        let inputs = vec!["-i", "video.m3u8", "-i", "audio.m3u8", "-map", "0:v"].into_iter().map(String::from).collect();
+        assert_eq!(with_input_options(inputs, &whitelist), vec![
+            "-protocol_whitelist", "file,https,tcp,tls", "-i", "video.m3u8",
+            "-protocol_whitelist", "file,https,tcp,tls", "-i", "audio.m3u8",
+            "-map", "0:v",
+        ]);
+
+        let with_args = |input: &[&str], output: &[&str]| DownloadOptions {
+            extra_input_args: input.iter().map(|s| s.to_string()).collect(),
+            extra_output_args: output.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        };
+        assert!(with_args(&["-protocol_whitelist", "file,https"], &["-map", "0", "-metadata", "title=Épisode 1"]).validate().is_ok());
+        assert!(matches!(with_args(&[], &["-progress", "pipe:2"]).validate(), Err(DownloadError::InvalidOptions(_))));
+        assert!(with_args(&["-i", "autre.mp4"], &[]).validate().is_err());
+        assert!(with_args(&[], &["-map", "-metadata", "a=b"]).validate().is_err());
+        assert!(with_args(&[], &["-c:a"]).validate().is_err());
+        assert!(with_args(&[""], &[]).validate().is_err());
+        let audio = DownloadOptions { audio_only: Some(AudioFormat::Mp3), ..with_args(&[], &["-c:v", "libx264"]) };
+        assert!(audio.validate().is_err());
+    }
+
+    #[test]
+    fn test_stream_args_for_variant_dash_and_subtitles() {
+        let variant = hls::HlsVariant {
+            url: "video.m3u8".into(),
+            bandwidth: None,
+            resolution: None,
+            codecs: None,
+            audio_url: Some("audio.m3u8".into()),
+        };
+        let opts = DownloadOptions {
+            variant: Some(variant),
+            subtitles: SubtitleMode::Copy,
+            external_subtitles: vec![subtitles::ExternalSubtitle::new("fr.vtt")],
+            preset: None,
+            hwaccel: None,
+            segmented_hls: false,
+            thumbnails: false,
+            ..Default::default()
+        };
+        let (inputs, outputs) = stream_args("master.m3u8", &opts);
+        assert_eq!(inputs, vec!["-i", "video.m3u8", "-i", "audio.m3u8", "-i", "fr.vtt", "-map", "0:v", "-map", "1:a"]);
+        assert_eq!(outputs, vec!["-c", "copy", "-map", "2:s", "-map", "0:s?", "-c:s", "mov_text"]);
+
+        let dash = DownloadOptions { dash: Some(dash::DashSelection { video: Some(1), audio: Some(0) }), subtitles: SubtitleMode::Copy, ..Default::default() };
+        assert_eq!(stream_args("manifest.mpd", &dash), (
+            vec!["-i", "manifest.mpd", "-map", "0:v:1", "-map", "0:a:0"].into_iter().map(String::from).collect(),
+            vec!["-c", "copy", "-map", "0:s?", "-c:s", "mov_text"].into_iter().map(String::from).collect(),
+        ));
+        let dash_audio = DownloadOptions { audio_only: Some(AudioFormat::M4a), ..dash };
+        assert_eq!(stream_args("manifest.mpd", &dash_audio).0, vec!["-i", "manifest.mpd", "-map", "0:a:0"]);
+
+        let audio = DownloadOptions { audio_only: Some(AudioFormat::M4a), ..opts };
+        assert_eq!(stream_args("master.m3u8", &audio).1, vec!["-vn", "-sn", "-acodec", "copy"]);
+        assert_eq!(stream_args("master.m3u8", &DownloadOptions::default()).1, vec!["-c", "copy", "-sn"]);
     }
 
     #[tokio::test]
@@ -197,6 +815,23 @@ mod tests {
             stall_timeout: Duration::from_secs(1),
             auto_restart: false,
             max_restarts: 0,
+            extract_subtitles: false,
+            variant: None,
+            dash: None,
+            audio_only: None,
+            extra_input_args: Vec::new(),
+            extra_output_args: Vec::new(),
+            user_agent: None,
+            headers: Default::default(),
+            subtitles: SubtitleMode::Drop,
+            external_subtitles: Vec::new(),
+            preset: None,
+            hwaccel: None,
+            segmented_hls: false,
+            thumbnails: false,
+            read_rate: None,
+            resume_vod: false,
+            metadata: Default::default(),
         };
 
         let (tx, _rx) = mpsc::channel(10);
@@ -206,7 +841,8 @@ mod tests {
             "file:///nonexistent/invalid/path",
             &output_path,
             opts,
-            tx
+            tx,
+            Arc::default(),
         ).await;
 
         // Devrait échouer avec une erreur IO ou FfmpegExit
@@ -222,6 +858,23 @@ mod tests {
             stall_timeout: Duration::from_millis(100),
             auto_restart: false,
             max_restarts: 3,
+            extract_subtitles: false,
+            variant: None,
+            dash: None,
+            audio_only: None,
+            extra_input_args: Vec::new(),
+            extra_output_args: Vec::new(),
+            user_agent: None,
+            headers: Default::default(),
+            subtitles: SubtitleMode::Drop,
+            external_subtitles: Vec::new(),
+            preset: None,
+            hwaccel: None,
+            segmented_hls: false,
+            thumbnails: false,
+            read_rate: None,
+            resume_vod: false,
+            metadata: Default::default(),
         };
 
         let (tx, _rx) = mpsc::channel(10);
@@ -231,13 +884,47 @@ mod tests {
             "file:///nonexistent",
             &output_path,
             opts,
-            tx
+            tx,
+            Arc::default(),
         ).await;
 
         assert!(result.is_err());
         // Avec auto_restart=false, devrait échouer après une seule tentative
     }
 
+    #[tokio::test]
+    async fn test_cancel_stops_retries_and_removes_tmp() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_path = temp_dir.path().join("episode.mp4");
+        let tmp_path = temp_dir.path().join("episode.mp4.mp4");
+        std::fs::write(&tmp_path, b"partiel").unwrap();
+        let opts = DownloadOptions { stall_timeout: Duration::from_secs(1), auto_restart: true, max_restarts: 3, ..Default::default() };
+
+        // Annulation pendant le délai avant la deuxième tentative (2 s)
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            flag.store(true, Ordering::Relaxed);
+        });
+        let (tx, _rx) = mpsc::channel(10);
+        let start = std::time::Instant::now();
+        let result = download_with_ffmpeg("file:///nonexistent", &output_path, opts, tx, cancel.clone()).await;
+
+        if binary::locate(binary::Tool::Ffmpeg).is_none() {
+            // ffmpeg absent: erreur explicite, sans tentative ni attente
+            assert!(matches!(result, Err(DownloadError::MissingBinary(_))));
+            assert!(start.elapsed() < Duration::from_secs(1));
+            // l'attente entre deux tentatives reste interrompue par l'annulation
+            tokio::time::sleep(Duration::from_millis(400)).await;
+            assert!(sleep_unless_cancelled(Duration::from_secs(2), &cancel).await);
+            return;
+        }
+        assert!(matches!(result, Err(DownloadError::Cancelled)));
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(!tmp_path.exists() && !output_path.exists());
+    }
+
     #[tokio::test]
     async fn test_progress_channel_capacity() {
         // Test que le canal de progression fonctionne correctement
@@ -297,6 +984,23 @@ mod tests {
             stall_timeout: Duration::from_secs(30),
             auto_restart: true,
             max_restarts: 5,
+            extract_subtitles: false,
+            variant: None,
+            dash: None,
+            audio_only: None,
+            extra_input_args: Vec::new(),
+            extra_output_args: Vec::new(),
+            user_agent: None,
+            headers: Default::default(),
+            subtitles: SubtitleMode::Drop,
+            external_subtitles: Vec::new(),
+            preset: None,
+            hwaccel: None,
+            segmented_hls: false,
+            thumbnails: false,
+            read_rate: None,
+            resume_vod: false,
+            metadata: Default::default(),
         };
         
         let opts2 = opts1.clone();
@@ -322,6 +1026,23 @@ mod tests {
             stall_timeout: short_timeout,
             auto_restart: false,
             max_restarts: 0,
+            extract_subtitles: false,
+            variant: None,
+            dash: None,
+            audio_only: None,
+            extra_input_args: Vec::new(),
+            extra_output_args: Vec::new(),
+            user_agent: None,
+            headers: Default::default(),
+            subtitles: SubtitleMode::Drop,
+            external_subtitles: Vec::new(),
+            preset: None,
+            hwaccel: None,
+            segmented_hls: false,
+            thumbnails: false,
+            read_rate: None,
+            resume_vod: false,
+            metadata: Default::default(),
         };
         
         assert_eq!(opts.stall_timeout, short_timeout);
@@ -335,6 +1056,23 @@ mod tests {
             stall_timeout: Duration::from_millis(100),
             auto_restart: true,
             max_restarts: 2,
+            extract_subtitles: false,
+            variant: None,
+            dash: None,
+            audio_only: None,
+            extra_input_args: Vec::new(),
+            extra_output_args: Vec::new(),
+            user_agent: None,
+            headers: Default::default(),
+            subtitles: SubtitleMode::Drop,
+            external_subtitles: Vec::new(),
+            preset: None,
+            hwaccel: None,
+            segmented_hls: false,
+            thumbnails: false,
+            read_rate: None,
+            resume_vod: false,
+            metadata: Default::default(),
         };
         
         let temp_dir = TempDir::new().unwrap();
@@ -347,7 +1085,8 @@ mod tests {
             "file:///nonexistent",
             &output_path,
             opts,
-            tx
+            tx,
+            Arc::default(),
         ).await;
         
         let elapsed = start.elapsed();
@@ -0,0 +1,174 @@
+//! Variantes d'une playlist HLS maîtresse.
+//!
+//! Une playlist maîtresse (`#EXT-X-STREAM-INF`) propose le même flux en plusieurs qualités.
+//! Sans consigne, ffmpeg garde la meilleure vidéo; `list_variants` les énumère (résolution,
+//! débit) pour laisser l'utilisateur choisir, et `DownloadOptions::variant` transmet le choix
+//! à ffmpeg. Quand la variante tire son audio d'un groupe `#EXT-X-MEDIA` séparé, la piste par
+//! défaut du groupe est téléchargée avec elle.
+use std::collections::HashMap;
+use std::fmt;
+use url::Url;
+use crate::ffmpeg::params::DownloadError;
+
+/// Une qualité proposée par la playlist maîtresse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HlsVariant {
+    /// Playlist média de la variante (absolue)
+    pub url: String,
+    /// Débit crête annoncé (`BANDWIDTH`), en bit/s
+    pub bandwidth: Option<u64>,
+    /// Largeur × hauteur (`RESOLUTION`)
+    pub resolution: Option<(u32, u32)>,
+    pub codecs: Option<String>,
+    /// Playlist audio séparée (`#EXT-X-MEDIA` du groupe `AUDIO`), s'il y en a une
+    pub audio_url: Option<String>,
+}
+
+impl fmt::Display for HlsVariant {
+    /// `1920×1080 · 5.0 Mb/s`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.resolution, self.bandwidth) {
+            (Some((w, h)), Some(bw)) => write!(f, "{w}×{h} · {:.1} Mb/s", bw as f64 / 1_000_000.0),
+            (Some((w, h)), None) => write!(f, "{w}×{h}"),
+            (None, Some(bw)) => write!(f, "{:.1} Mb/s", bw as f64 / 1_000_000.0),
+            (None, None) => f.write_str(&self.url),
+        }
+    }
+}
+
+/// `true` si `url` désigne une playlist HLS (`.m3u8` ou `.m3u`, paramètres de requête ignorés)
+pub fn is_playlist_url(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url).to_ascii_lowercase();
+    path.ends_with(".m3u8") || path.ends_with(".m3u")
+}
+
+/// Télécharge `master_url` et renvoie ses variantes, de la meilleure à la moins bonne; vide si
+/// l'URL désigne directement une playlist média (une seule qualité)
+pub async fn list_variants(master_url: &str) -> Result<Vec<HlsVariant>, DownloadError> {
+    let response = reqwest::get(master_url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| DownloadError::Other(format!("playlist inaccessible: {e}")))?;
+    let base = response.url().clone();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| DownloadError::Other(format!("playlist illisible: {e}")))?;
+    let variants = parse_master_playlist(&base, &body);
+    tracing::debug!(url = master_url, count = variants.len(), "Variantes HLS trouvées");
+    Ok(variants)
+}
+
+/// Variantes de la playlist maîtresse `body`, URLs résolues depuis `base` et triées par débit
+/// décroissant; les variantes sans URI valide sont ignorées
+pub fn parse_master_playlist(base: &Url, body: &str) -> Vec<HlsVariant> {
+    let resolve = |uri: &str| base.join(uri).ok().map(String::from);
+    let mut audio_groups: HashMap<String, String> = HashMap::new();
+    let mut variants = Vec::new();
+    let mut pending: Option<HashMap<String, String>> = None;
+
+    for line in body.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(attrs) = line.strip_prefix("#EXT-X-MEDIA:") {
+            let attrs = parse_attributes(attrs);
+            let is_audio = attrs.get("TYPE").is_some_and(|t| t == "AUDIO");
+            if let (true, Some(group), Some(url)) = (is_audio, attrs.get("GROUP-ID"), attrs.get("URI").and_then(|u| resolve(u))) {
+                // La piste par défaut du groupe l'emporte sur la première rencontrée
+                if attrs.get("DEFAULT").is_some_and(|d| d == "YES") || !audio_groups.contains_key(group) {
+                    audio_groups.insert(group.clone(), url);
+                }
+            }
+        } else if let Some(attrs) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            pending = Some(parse_attributes(attrs));
+        } else if line.starts_with('#') {
+            continue;
+        } else if let Some(attrs) = pending.take() {
+            let Some(url) = resolve(line) else { continue };
+            variants.push((attrs, url));
+        }
+    }
+
+    let mut variants: Vec<HlsVariant> = variants
+        .into_iter()
+        .map(|(attrs, url)| HlsVariant {
+            url,
+            bandwidth: attrs.get("BANDWIDTH").and_then(|b| b.parse().ok()),
+            resolution: attrs.get("RESOLUTION").and_then(|r| {
+                let (w, h) = r.split_once('x')?;
+                Some((w.parse().ok()?, h.parse().ok()?))
+            }),
+            codecs: attrs.get("CODECS").cloned(),
+            audio_url: attrs.get("AUDIO").and_then(|group| audio_groups.get(group)).cloned(),
+        })
+        .collect();
+    variants.sort_by_key(|v| std::cmp::Reverse(v.bandwidth));
+    variants
+}
+
+/// `BANDWIDTH=1280000,CODECS="avc1.4d401f,mp4a.40.2"` -> attributs, guillemets retirés
+fn parse_attributes(attrs: &str) -> HashMap<String, String> {
+    let mut parsed = HashMap::new();
+    let mut rest = attrs;
+    while let Some((key, after)) = rest.split_once('=') {
+        let (value, next) = match after.strip_prefix('"') {
+            Some(quoted) => match quoted.split_once('"') {
+                Some((value, next)) => (value, next.trim_start_matches(',')),
+                None => (quoted, ""),
+            },
+            None => after.split_once(',').unwrap_or((after, "")),
+        };
+        parsed.insert(key.trim().to_string(), value.to_string());
+        rest = next;
+    }
+    parsed
+}
+
+/// Arguments d'entrée de ffmpeg: `-i input_url`, ou la variante choisie et, si son audio est
+/// séparé, sa playlist audio avec une sélection explicite des deux pistes
+pub fn input_args(input_url: &str, variant: Option<&HlsVariant>) -> Vec<String> {
+    let Some(variant) = variant else {
+        return vec!["-i".to_string(), input_url.to_string()];
+    };
+    match &variant.audio_url {
+        Some(audio) => ["-i", &variant.url, "-i", audio, "-map", "0:v", "-map", "1:a"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        None => vec!["-i".to_string(), variant.url.clone()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_master_playlist_variants() {
+        let base = Url::parse("https://cdn.example.com/show/master.m3u8").unwrap();
+        let body = "#EXTM3U\n\
+            #EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"en\",URI=\"audio/en.m3u8\"\n\
+            #EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"fr\",DEFAULT=YES,URI=\"audio/fr.m3u8\"\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=800000,RESOLUTION=640x360,CODECS=\"avc1.4d401e,mp4a.40.2\"\n\
+            360p/index.m3u8\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=5000000,RESOLUTION=1920x1080,AUDIO=\"aud\"\n\
+            https://other.example.com/1080p.m3u8\n";
+        let variants = parse_master_playlist(&base, body);
+
+        assert_eq!(variants.len(), 2);
+        assert_eq!(variants[0].url, "https://other.example.com/1080p.m3u8");
+        assert_eq!(variants[0].audio_url.as_deref(), Some("https://cdn.example.com/show/audio/fr.m3u8"));
+        assert_eq!(variants[0].to_string(), "1920×1080 · 5.0 Mb/s");
+        assert_eq!(variants[1].url, "https://cdn.example.com/show/360p/index.m3u8");
+        assert_eq!(variants[1].codecs.as_deref(), Some("avc1.4d401e,mp4a.40.2"));
+        assert_eq!(variants[1].resolution, Some((640, 360)));
+        assert!(parse_master_playlist(&base, "#EXTM3U\n#EXTINF:10,\nseg0.ts\n").is_empty());
+
+        assert_eq!(input_args("master.m3u8", None), vec!["-i", "master.m3u8"]);
+        assert!(is_playlist_url("https://cdn.example.com/index.M3U8?token=1") && !is_playlist_url("https://cdn.example.com/ep.mp4?f=.m3u8"));
+        assert_eq!(input_args("master.m3u8", Some(&variants[1])), vec!["-i", "https://cdn.example.com/show/360p/index.m3u8"]);
+        assert_eq!(input_args("master.m3u8", Some(&variants[0])), vec![
+            "-i", "https://other.example.com/1080p.m3u8",
+            "-i", "https://cdn.example.com/show/audio/fr.m3u8",
+            "-map", "0:v", "-map", "1:a",
+        ]);
+    }
+}
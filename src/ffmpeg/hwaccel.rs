@@ -0,0 +1,193 @@
+//! Accélération matérielle des préréglages de réencodage.
+//!
+//! `DownloadOptions::hwaccel` fait décoder l'entrée par le GPU (`-hwaccel`) et, sauf en mode
+//! `Auto`, remplace les encodeurs logiciels des préréglages vidéo (`libx264`, `libx265`) par
+//! leur équivalent matériel: NVENC (NVIDIA), Quick Sync (Intel), VideoToolbox (macOS) ou VA‑API
+//! (Linux). Le réglage de qualité suit (`-crf` devient `-cq`, `-global_quality`, `-q:v` ou
+//! `-qp`). Sans effet en copie de flux ou en audio seul.
+//!
+//! `available` interroge `ffmpeg -hwaccels` pour ne proposer que les méthodes que la version
+//! installée sait utiliser (la présence du matériel lui‑même n'est vérifiée qu'au lancement).
+use std::process::Stdio;
+use tokio::process::Command;
+use crate::ffmpeg::binary;
+use crate::ffmpeg::params::DownloadError;
+
+/// Périphérique VA‑API par défaut sous Linux
+const VAAPI_DEVICE: &str = "/dev/dri/renderD128";
+
+/// Méthode d'accélération matérielle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HwAccel {
+    /// Décodage par la méthode que ffmpeg juge disponible, encodage logiciel
+    Auto,
+    /// NVIDIA: décodage CUDA, encodage `h264_nvenc` / `hevc_nvenc`
+    Nvenc,
+    /// Intel Quick Sync: `h264_qsv` / `hevc_qsv`
+    Qsv,
+    /// macOS: `h264_videotoolbox` / `hevc_videotoolbox`
+    VideoToolbox,
+    /// Linux (Intel, AMD): `h264_vaapi` / `hevc_vaapi`
+    Vaapi,
+}
+
+impl HwAccel {
+    pub const ALL: [HwAccel; 5] = [HwAccel::Auto, HwAccel::Nvenc, HwAccel::Qsv, HwAccel::VideoToolbox, HwAccel::Vaapi];
+
+    /// Nom affiché dans l'onglet FFmpeg
+    pub fn label(self) -> &'static str {
+        match self {
+            HwAccel::Auto => "Auto (décodage)",
+            HwAccel::Nvenc => "NVIDIA NVENC",
+            HwAccel::Qsv => "Intel Quick Sync",
+            HwAccel::VideoToolbox => "VideoToolbox",
+            HwAccel::Vaapi => "VA-API",
+        }
+    }
+
+    /// Méthode correspondante de `ffmpeg -hwaccels` (`None` pour `Auto`)
+    fn method(self) -> Option<&'static str> {
+        match self {
+            HwAccel::Auto => None,
+            HwAccel::Nvenc => Some("cuda"),
+            HwAccel::Qsv => Some("qsv"),
+            HwAccel::VideoToolbox => Some("videotoolbox"),
+            HwAccel::Vaapi => Some("vaapi"),
+        }
+    }
+
+    /// Options placées devant la première entrée
+    pub fn input_args(self) -> Vec<String> {
+        let mut args = vec!["-hwaccel".to_string(), self.method().unwrap_or("auto").to_string()];
+        if self == HwAccel::Vaapi {
+            args.extend(["-vaapi_device".to_string(), VAAPI_DEVICE.to_string()]);
+        }
+        args
+    }
+
+    /// Encodeur matériel remplaçant l'encodeur logiciel `codec` (`None`: garder le logiciel)
+    fn encoder(self, codec: &str) -> Option<String> {
+        let family = match codec {
+            "libx264" => "h264",
+            "libx265" => "hevc",
+            _ => return None,
+        };
+        let suffix = match self {
+            HwAccel::Auto => return None,
+            HwAccel::Nvenc => "nvenc",
+            HwAccel::Qsv => "qsv",
+            HwAccel::VideoToolbox => "videotoolbox",
+            HwAccel::Vaapi => "vaapi",
+        };
+        Some(format!("{family}_{suffix}"))
+    }
+
+    /// Adapte les arguments de sortie d'un préréglage logiciel à l'encodeur matériel: encodeur,
+    /// réglage de qualité, et pour VA‑API envoi des images au GPU (`hwupload`)
+    pub fn encoder_args(self, codecs: &[String]) -> Vec<String> {
+        if !codecs.windows(2).any(|w| w[0] == "-c:v" && self.encoder(&w[1]).is_some()) {
+            return codecs.to_vec();
+        }
+        let mut args = Vec::with_capacity(codecs.len() + 2);
+        let mut i = 0;
+        while i < codecs.len() {
+            let value = codecs.get(i + 1).map_or("", String::as_str);
+            // remplacement de la paire option + valeur, ou `None` pour garder l'argument
+            let pair = match codecs[i].as_str() {
+                "-c:v" => self.encoder(value).map(|encoder| vec!["-c:v".to_string(), encoder]),
+                "-crf" => Some(self.quality_args(value.parse().unwrap_or(23))),
+                // préréglages x264 compris par NVENC et Quick Sync seulement
+                "-preset" if matches!(self, HwAccel::VideoToolbox | HwAccel::Vaapi) => Some(Vec::new()),
+                // VA‑API encode des images envoyées au GPU
+                "-pix_fmt" if self == HwAccel::Vaapi => Some(Vec::new()),
+                "-vf" if self == HwAccel::Vaapi => Some(vec!["-vf".to_string(), format!("{value},format=nv12,hwupload")]),
+                _ => None,
+            };
+            match pair {
+                Some(pair) => {
+                    args.extend(pair);
+                    i += 2;
+                }
+                None => {
+                    args.push(codecs[i].clone());
+                    i += 1;
+                }
+            }
+        }
+        if self == HwAccel::Vaapi && !codecs.iter().any(|a| a == "-vf") {
+            args.extend(["-vf".to_string(), "format=nv12,hwupload".to_string()]);
+        }
+        args
+    }
+
+    /// Équivalent matériel de `-crf crf`
+    fn quality_args(self, crf: u32) -> Vec<String> {
+        let (option, value) = match self {
+            HwAccel::Nvenc => ("-cq", crf),
+            HwAccel::Qsv => ("-global_quality", crf),
+            // échelle 1‑100, plus haut = meilleur
+            HwAccel::VideoToolbox => ("-q:v", 100u32.saturating_sub(crf * 2).max(1)),
+            HwAccel::Vaapi | HwAccel::Auto => ("-qp", crf),
+        };
+        vec![option.to_string(), value.to_string()]
+    }
+}
+
+/// Méthodes listées par `ffmpeg -hwaccels` (après la ligne `Hardware acceleration methods:`)
+pub fn parse_hwaccels(output: &str) -> Vec<HwAccel> {
+    let methods: Vec<&str> = output
+        .lines()
+        .skip_while(|line| !line.starts_with("Hardware acceleration methods"))
+        .skip(1)
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    HwAccel::ALL
+        .into_iter()
+        .filter(|accel| accel.method().is_none_or(|method| methods.contains(&method)))
+        .collect()
+}
+
+/// Méthodes utilisables avec le ffmpeg installé; `Auto` est toujours proposé
+pub async fn available() -> Result<Vec<HwAccel>, DownloadError> {
+    let output = Command::new(binary::ffmpeg()?)
+        .args(["-hide_banner", "-hwaccels"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .await?;
+    let accels = parse_hwaccels(&String::from_utf8_lossy(&output.stdout));
+    tracing::info!(?accels, "Accélérations matérielles de ffmpeg");
+    Ok(accels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffmpeg::params::TranscodePreset;
+
+    fn preset(preset: TranscodePreset) -> Vec<String> {
+        preset.codec_args().iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_hwaccels_detection_and_encoder_args() {
+        let output = "Hardware acceleration methods:\nvdpau\ncuda\nvaapi\nqsv\n\n";
+        assert_eq!(parse_hwaccels(output), [HwAccel::Auto, HwAccel::Nvenc, HwAccel::Qsv, HwAccel::Vaapi]);
+        assert_eq!(parse_hwaccels("Hardware acceleration methods:\n"), [HwAccel::Auto]);
+
+        let nvenc = HwAccel::Nvenc.encoder_args(&preset(TranscodePreset::H265Compact));
+        assert_eq!(nvenc, ["-c:v", "hevc_nvenc", "-preset", "medium", "-cq", "28", "-tag:v", "hvc1", "-c:a", "aac", "-b:a", "96k"]);
+        let vaapi = HwAccel::Vaapi.encoder_args(&preset(TranscodePreset::H264Hd));
+        assert_eq!(vaapi[..4], ["-c:v", "h264_vaapi", "-qp", "20"]);
+        assert!(vaapi.windows(2).any(|w| w[0] == "-vf" && w[1] == "scale=-2:'min(1080,ih)',format=nv12,hwupload"));
+        assert!(!vaapi.contains(&"-pix_fmt".to_string()) && !vaapi.contains(&"-preset".to_string()));
+        assert_eq!(HwAccel::VideoToolbox.encoder_args(&preset(TranscodePreset::H264Hd))[2..4], ["-q:v", "60"]);
+
+        // Auto ne change que le décodage; l'audio seul n'a pas d'encodeur vidéo
+        assert_eq!(HwAccel::Auto.encoder_args(&preset(TranscodePreset::H264Hd)), preset(TranscodePreset::H264Hd));
+        assert_eq!(HwAccel::Qsv.encoder_args(&preset(TranscodePreset::Audio128k)), preset(TranscodePreset::Audio128k));
+        assert_eq!(HwAccel::Vaapi.input_args(), ["-hwaccel", "vaapi", "-vaapi_device", "/dev/dri/renderD128"]);
+        assert_eq!(HwAccel::Auto.input_args(), ["-hwaccel", "auto"]);
+    }
+}
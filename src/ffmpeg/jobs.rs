@@ -0,0 +1,281 @@
+//! File des tâches ffmpeg: téléchargements, assemblages et enregistrements, au plus
+//! `max_active` simultanés.
+//!
+//! Chaque tâche reçoit un identifiant, un statut (en attente, en cours, terminée, échouée,
+//! annulée), sa dernière progression et son propre drapeau d'annulation, comme les
+//! téléchargements de la GUI. La limite repose sur une `DownloadQueue` dédiée: une tâche attend
+//! sa place avant de lancer ffmpeg. Chaque tâche tourne dans son propre thread avec son runtime
+//! tokio, la file s'utilise donc aussi hors de tout runtime. `jobs()` donne un instantané pour
+//! l'affichage.
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use crate::downloader::{AppConfig, DownloadQueue, Priority};
+use crate::ffmpeg::params::{DownloadError, DownloadOptions, FfmpegProgress};
+use crate::ffmpeg::record::RecordOptions;
+
+/// Tâches ffmpeg simultanées sans `[ffmpeg] max_jobs`
+const DEFAULT_MAX_JOBS: usize = 2;
+
+pub type JobId = u64;
+
+/// `[ffmpeg] max_jobs`: tâches ffmpeg simultanées (2 par défaut)
+pub fn max_jobs_from_config(config: &AppConfig) -> usize {
+    config.ffmpeg.as_ref().and_then(|f| f.max_jobs).unwrap_or(DEFAULT_MAX_JOBS)
+}
+
+/// Entrée(s) d'une tâche
+#[derive(Debug, Clone)]
+pub enum JobInput {
+    /// Une URL ou un fichier, téléchargé ou converti
+    Download(String),
+    /// Source téléchargée par le gestionnaire puis finalisée par ffmpeg (`fetch_then_remux`)
+    Fetch(String),
+    /// Parties assemblées dans l'ordre (`concat_with_options`)
+    Concat(Vec<String>),
+    /// Direct enregistré dans les limites données (`record_with_options`)
+    Record(String, RecordOptions),
+}
+
+impl JobInput {
+    /// Description courte: l'URL, ou la première partie et leur nombre
+    pub fn label(&self) -> String {
+        match self {
+            JobInput::Download(url) | JobInput::Fetch(url) | JobInput::Record(url, _) => url.clone(),
+            JobInput::Concat(parts) => match parts.as_slice() {
+                [first, rest @ ..] if !rest.is_empty() => format!("{first} (+{} parties)", rest.len()),
+                _ => parts.concat(),
+            },
+        }
+    }
+}
+
+/// Tâche à soumettre
+#[derive(Debug, Clone)]
+pub struct FfmpegJob {
+    pub input: JobInput,
+    pub output: PathBuf,
+    pub options: DownloadOptions,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    /// Message de l'erreur (les lignes de stderr sont dans `JobInfo::stderr`)
+    Failed(String),
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Failed(_) | JobStatus::Cancelled)
+    }
+}
+
+/// État d'une tâche à un instant donné
+#[derive(Debug, Clone)]
+pub struct JobInfo {
+    pub id: JobId,
+    pub label: String,
+    pub output: PathBuf,
+    pub status: JobStatus,
+    /// Dernière progression reçue de ffmpeg
+    pub progress: Option<FfmpegProgress>,
+    /// Dernières lignes de stderr en cas d'échec
+    pub stderr: Vec<String>,
+}
+
+#[derive(Debug)]
+struct JobEntry {
+    info: JobInfo,
+    cancel: Arc<AtomicBool>,
+}
+
+/// File partagée (clonage bon marché) des tâches ffmpeg
+#[derive(Debug, Clone)]
+pub struct FfmpegJobQueue {
+    jobs: Arc<Mutex<Vec<JobEntry>>>,
+    queue: DownloadQueue,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Default for FfmpegJobQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_JOBS)
+    }
+}
+
+impl FfmpegJobQueue {
+    /// File autorisant `max_active` tâches simultanées (au moins 1)
+    pub fn new(max_active: usize) -> Self {
+        Self { jobs: Arc::default(), queue: DownloadQueue::new(max_active), next_id: Arc::new(AtomicU64::new(1)) }
+    }
+
+    /// Lit `[ffmpeg] max_jobs` (2 par défaut)
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self::new(max_jobs_from_config(config))
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, Vec<JobEntry>> {
+        self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn max_active(&self) -> usize {
+        self.queue.max_active()
+    }
+
+    /// Change la limite; une hausse démarre aussitôt les tâches en attente
+    pub fn set_max_active(&self, max_active: usize) {
+        self.queue.set_max_active(max_active);
+    }
+
+    /// Ajoute `job` à la file et rend son identifiant; la tâche démarre dès qu'une place se libère
+    pub fn submit(&self, job: FfmpegJob) -> JobId {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let info = JobInfo {
+            id,
+            label: job.input.label(),
+            output: job.output.clone(),
+            status: JobStatus::Queued,
+            progress: None,
+            stderr: Vec::new(),
+        };
+        self.entries().push(JobEntry { info, cancel: cancel.clone() });
+        tracing::info!(id, input = %job.input.label(), "Tâche ffmpeg ajoutée à la file");
+
+        let jobs = self.clone();
+        std::thread::spawn(move || match tokio::runtime::Runtime::new() {
+            Ok(rt) => rt.block_on(jobs.run(id, job, cancel)),
+            Err(e) => jobs.finish(id, Err(DownloadError::Io(e))),
+        });
+        id
+    }
+
+    async fn run(&self, id: JobId, job: FfmpegJob, cancel: Arc<AtomicBool>) {
+        let Some(_slot) = self.queue.acquire_cancellable(id, Priority::Normal, &cancel).await else {
+            self.finish(id, Err(DownloadError::Cancelled));
+            return;
+        };
+        self.update(id, |info| info.status = JobStatus::Running);
+        let jobs = self.clone();
+        let on_progress = Some(move |progress: &FfmpegProgress| {
+            jobs.update(id, |info| {
+                // Conteneur remplacé (repli .mkv): la liste montre le vrai fichier
+                if let Some(output) = &progress.output {
+                    info.output = output.clone();
+                }
+                info.progress = Some(progress.clone());
+            })
+        });
+        let FfmpegJob { input, output, options } = job;
+        let result = match input {
+            JobInput::Download(url) => super::download_with_options(&url, &output, options, on_progress, cancel).await,
+            JobInput::Fetch(url) => super::fetch_then_remux(&url, &output, options, on_progress, cancel).await,
+            JobInput::Concat(parts) => super::concat_with_options(&parts, &output, options, on_progress, cancel).await,
+            JobInput::Record(url, record) => super::record_with_options(&url, &output, options, record, on_progress, cancel).await,
+        };
+        self.finish(id, result);
+    }
+
+    fn update(&self, id: JobId, change: impl FnOnce(&mut JobInfo)) {
+        if let Some(entry) = self.entries().iter_mut().find(|e| e.info.id == id) {
+            change(&mut entry.info);
+        }
+    }
+
+    fn finish(&self, id: JobId, result: Result<(), DownloadError>) {
+        match &result {
+            Ok(()) => tracing::info!(id, "Tâche ffmpeg terminée"),
+            Err(DownloadError::Cancelled) => tracing::info!(id, "Tâche ffmpeg annulée"),
+            Err(e) => tracing::warn!(id, error = %e, "Tâche ffmpeg échouée"),
+        }
+        self.update(id, |info| {
+            info.status = match result {
+                Ok(()) => JobStatus::Completed,
+                Err(DownloadError::Cancelled) => JobStatus::Cancelled,
+                Err(e) => {
+                    info.stderr = e.stderr_tail().to_vec();
+                    JobStatus::Failed(e.to_string())
+                }
+            };
+        });
+    }
+
+    /// Instantané de toutes les tâches, dans l'ordre de soumission
+    pub fn jobs(&self) -> Vec<JobInfo> {
+        self.entries().iter().map(|e| e.info.clone()).collect()
+    }
+
+    /// Tâches en attente ou en cours
+    pub fn pending(&self) -> usize {
+        self.entries().iter().filter(|e| !e.info.status.is_finished()).count()
+    }
+
+    /// Demande l'arrêt d'une tâche en attente ou en cours; `false` si elle est déjà finie
+    pub fn cancel(&self, id: JobId) -> bool {
+        let entries = self.entries();
+        match entries.iter().find(|e| e.info.id == id && !e.info.status.is_finished()) {
+            Some(entry) => {
+                entry.cancel.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Retire les tâches finies de la liste
+    pub fn clear_finished(&self) {
+        self.entries().retain(|e| !e.info.status.is_finished());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn wait_until(jobs: &FfmpegJobQueue, id: JobId, done: impl Fn(&JobStatus) -> bool) -> JobStatus {
+        for _ in 0..200 {
+            let status = jobs.jobs().into_iter().find(|j| j.id == id).map(|j| j.status);
+            if let Some(status) = status.filter(|s| done(s)) {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        panic!("tâche {id} bloquée");
+    }
+
+    #[tokio::test]
+    async fn test_jobs_wait_for_a_slot_and_can_be_cancelled() {
+        let jobs = FfmpegJobQueue::new(1);
+        // Place occupée: les tâches soumises restent en attente
+        let held = jobs.queue.acquire(0, Priority::Normal).await;
+        let dir = tempfile::tempdir().unwrap();
+        let job = |name: &str| FfmpegJob {
+            input: JobInput::Download(dir.path().join(format!("{name}.mp4")).to_string_lossy().into_owned()),
+            output: dir.path().join(format!("{name}-out.mp4")),
+            options: DownloadOptions { auto_restart: false, ..Default::default() },
+        };
+        let (first, second) = (jobs.submit(job("absent1")), jobs.submit(job("absent2")));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(jobs.jobs().iter().all(|j| j.status == JobStatus::Queued));
+        assert_eq!(jobs.pending(), 2);
+
+        assert!(jobs.cancel(first));
+        assert_eq!(wait_until(&jobs, first, JobStatus::is_finished).await, JobStatus::Cancelled);
+        assert!(!jobs.cancel(first));
+
+        // Place libérée: la seconde tâche démarre et échoue (entrée absente, ou ffmpeg absent)
+        drop(held);
+        assert!(matches!(wait_until(&jobs, second, JobStatus::is_finished).await, JobStatus::Failed(_)));
+        assert_eq!(jobs.pending(), 0);
+        jobs.clear_finished();
+        assert!(jobs.jobs().is_empty());
+
+        let concat = JobInput::Concat(vec!["a.mp4".into(), "b.mp4".into(), "c.mp4".into()]);
+        assert_eq!(concat.label(), "a.mp4 (+2 parties)");
+    }
+}
@@ -0,0 +1,103 @@
+//! Métadonnées et chapitres de la sortie.
+//!
+//! Par défaut ffmpeg recopie les métadonnées globales et les chapitres de la première entrée;
+//! `MetadataOptions` permet de les écarter (`-map_metadata -1`, `-map_chapters -1`) et d'écrire
+//! titre, série, saison et numéro d'épisode (`-metadata`), reconnus par les lecteurs et les
+//! médiathèques (balises `title`, `show`, `season_number`, `episode_sort`, `episode_id`). Le
+//! scraper ne donne que des noms (« Season 2 », « Episode 05 - ... »): `for_scraped_episode` en
+//! tire les numéros.
+
+/// Métadonnées écrites dans la sortie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataOptions {
+    /// garder les métadonnées globales de l'entrée (titre, encodeur, dates...)
+    pub copy_metadata: bool,
+    /// garder les chapitres de l'entrée
+    pub copy_chapters: bool,
+    pub title: Option<String>,
+    /// nom de la série
+    pub show: Option<String>,
+    pub season: Option<u32>,
+    pub episode: Option<u32>,
+}
+
+impl Default for MetadataOptions {
+    fn default() -> Self {
+        Self { copy_metadata: true, copy_chapters: true, title: None, show: None, season: None, episode: None }
+    }
+}
+
+impl MetadataOptions {
+    /// Métadonnées d'un épisode scrapé: `series` comme série, le nom de l'épisode comme titre,
+    /// numéros de saison et d'épisode tirés des noms (`None` s'ils n'en contiennent pas)
+    pub fn for_scraped_episode(series: &str, season_name: &str, episode_name: &str) -> Self {
+        let non_empty = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+        Self {
+            title: non_empty(episode_name),
+            show: non_empty(series),
+            season: number_in(season_name),
+            episode: number_in(episode_name),
+            ..Self::default()
+        }
+    }
+
+    /// Arguments de sortie correspondants; vide pour les options par défaut
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if !self.copy_metadata {
+            args.extend(["-map_metadata", "-1"].map(String::from));
+        }
+        if !self.copy_chapters {
+            args.extend(["-map_chapters", "-1"].map(String::from));
+        }
+        let mut tag = |key: &str, value: String| args.extend(["-metadata".to_string(), format!("{key}={value}")]);
+        if let Some(title) = self.title.as_deref().filter(|t| !t.is_empty()) {
+            tag("title", title.to_string());
+        }
+        if let Some(show) = self.show.as_deref().filter(|s| !s.is_empty()) {
+            tag("show", show.to_string());
+        }
+        if let Some(season) = self.season {
+            tag("season_number", season.to_string());
+        }
+        if let Some(episode) = self.episode {
+            tag("episode_sort", episode.to_string());
+        }
+        if let (Some(season), Some(episode)) = (self.season, self.episode) {
+            tag("episode_id", format!("S{season:02}E{episode:02}"));
+        }
+        args
+    }
+}
+
+/// Premier nombre d'un nom: `Season 02` -> 2, `Episode 5 - Pilot` -> 5
+pub fn number_in(name: &str) -> Option<u32> {
+    let start = name.find(|c: char| c.is_ascii_digit())?;
+    let digits: String = name[start..].chars().take_while(char::is_ascii_digit).collect();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_args_from_scraped_names() {
+        assert!(MetadataOptions::default().args().is_empty());
+
+        let meta = MetadataOptions::for_scraped_episode("Breaking Bad", "Season 2", "Episode 05 - Breakage");
+        assert_eq!((meta.season, meta.episode), (Some(2), Some(5)));
+        assert_eq!(meta.args(), [
+            "-metadata", "title=Episode 05 - Breakage",
+            "-metadata", "show=Breaking Bad",
+            "-metadata", "season_number=2",
+            "-metadata", "episode_sort=5",
+            "-metadata", "episode_id=S02E05",
+        ]);
+
+        let stripped = MetadataOptions { copy_metadata: false, copy_chapters: false, ..MetadataOptions::for_scraped_episode("", "Specials", "") };
+        assert_eq!(stripped.args(), ["-map_metadata", "-1", "-map_chapters", "-1"]);
+        assert_eq!(number_in("S03E07"), Some(3));
+        assert_eq!(number_in("Pilot"), None);
+    }
+}
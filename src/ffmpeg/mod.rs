@@ -1,11 +1,40 @@
 pub mod params;
+pub mod binary;
 pub mod downloader;
+pub mod hls;
+pub mod dash;
+pub mod record;
+pub mod subtitles;
+pub mod segments;
+pub mod concat;
+pub mod thumbnails;
+pub mod stderr;
+pub mod jobs;
+pub mod container;
+pub mod resume;
+pub mod metadata;
+pub mod batch;
+pub mod hwaccel;
+pub mod pipeline;
 
-pub use params::{DownloadError, DownloadOptions, FfmpegProgress};
+pub use params::{AudioFormat, DownloadError, DownloadOptions, FfmpegProgress, ReadRate, TranscodePreset};
+pub use hls::HlsVariant;
+pub use dash::DashManifest;
+pub use record::RecordOptions;
+pub use metadata::MetadataOptions;
+pub use hwaccel::HwAccel;
+pub use jobs::{FfmpegJob, FfmpegJobQueue, JobInput, JobStatus};
+pub use batch::{download_batch, BatchProgress};
+pub use subtitles::{ExternalSubtitle, SubtitleMode};
 
 use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
 use tokio::sync::mpsc;
 use crate::ffmpeg::downloader::download_with_ffmpeg;
+use crate::ffmpeg::record::record_with_ffmpeg;
+use crate::ffmpeg::concat::concat_with_ffmpeg;
+use crate::ffmpeg::pipeline::fetch_then_remux_with_ffmpeg;
 
 /// Télécharge une URL vers un fichier de sortie avec les options par défaut.
 /// 
@@ -25,7 +54,7 @@ pub async fn download(
     input_url: impl AsRef<str>,
     output_path: impl AsRef<Path>,
 ) -> Result<(), DownloadError> {
-    download_with_options(input_url, output_path, DownloadOptions::default(), None::<fn(&FfmpegProgress)>).await
+    download_with_options(input_url, output_path, DownloadOptions::default(), None::<fn(&FfmpegProgress)>, Arc::default()).await
 }
 
 /// Télécharge une URL avec un callback pour suivre la progression.
@@ -41,8 +70,8 @@ pub async fn download(
 ///     "https://example.com/video.mp4",
 ///     "output.mp4",
 ///     |progress| {
-///         if let Some(time) = progress.fields.get("out_time_ms") {
-///             println!("Temps: {} ms", time);
+///         if let Some(time) = progress.out_time {
+///             println!("Temps: {:?}", time);
 ///         }
 ///     }
 /// ).await?;
@@ -57,16 +86,18 @@ pub async fn download_with_progress<F>(
 where
     F: Fn(&FfmpegProgress) + Send + Sync + 'static,
 {
-    download_with_options(input_url, output_path, DownloadOptions::default(), Some(on_progress)).await
+    download_with_options(input_url, output_path, DownloadOptions::default(), Some(on_progress), Arc::default()).await
 }
 
 /// Télécharge une URL avec des options personnalisées et un callback optionnel de progression.
 /// 
-/// Cette fonction offre le contrôle maximal sur le processus de téléchargement.
+/// Cette fonction offre le contrôle maximal sur le processus de téléchargement. Passer `cancel`
+/// à vrai arrête ffmpeg, supprime le fichier temporaire et renvoie `DownloadError::Cancelled`.
 /// 
 /// # Exemple
 /// ```no_run
 /// use scrapes::ffmpeg::{self, DownloadOptions};
+/// use std::sync::{Arc, atomic::AtomicBool};
 /// use std::time::Duration;
 /// 
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
@@ -74,6 +105,23 @@ where
 ///     stall_timeout: Duration::from_secs(30),
 ///     auto_restart: true,
 ///     max_restarts: 5,
+///     extract_subtitles: true,
+///     variant: None,
+///     dash: None,
+///     audio_only: None,
+///     extra_input_args: Vec::new(),
+///     extra_output_args: vec!["-metadata".into(), "title=Épisode 1".into()],
+///     user_agent: None,
+///     headers: [("Referer".to_string(), "https://example.com/".to_string())].into(),
+///     subtitles: ffmpeg::SubtitleMode::Copy,
+///     external_subtitles: Vec::new(),
+///     preset: None,
+///     hwaccel: None,
+///     segmented_hls: false,
+///     thumbnails: true,
+///     read_rate: None,
+///     resume_vod: false,
+///     metadata: ffmpeg::MetadataOptions::for_scraped_episode("Ma série", "Season 1", "Episode 01"),
 /// };
 /// 
 /// ffmpeg::download_with_options(
@@ -82,7 +130,8 @@ where
 ///     options,
 ///     Some(|progress| {
 ///         println!("Progression: {:?}", progress.fields);
-///     })
+///     }),
+///     Arc::new(AtomicBool::new(false)),
 /// ).await?;
 /// # Ok(())
 /// # }
@@ -92,37 +141,166 @@ pub async fn download_with_options<F>(
     output_path: impl AsRef<Path>,
     options: DownloadOptions,
     on_progress: Option<F>,
+    cancel: Arc<AtomicBool>,
 ) -> Result<(), DownloadError>
 where
     F: Fn(&FfmpegProgress) + Send + Sync + 'static,
 {
     let input_url = input_url.as_ref();
+    let (progress_tx, callback_task) = progress_channel(on_progress);
+
+    // Lancer le téléchargement
+    // Le canal se ferme automatiquement quand progress_tx est drop (à la fin de download_with_ffmpeg)
+    let result = download_with_ffmpeg(input_url, output_path, options, progress_tx, cancel).await;
+
+    // Attendre que le callback ait fini de traiter tous les messages
+    // Le canal se ferme quand progress_tx est drop, ce qui fait que progress_rx.recv() retourne None
+    let _ = callback_task.await;
+
+    result
+}
+
+/// Enregistre un flux en direct dans les limites de `record` (durée maximale, heure d'arrêt,
+/// découpage en segments).
+///
+/// Passer `cancel` à vrai arrête l'enregistrement en conservant ce qui a été reçu.
+///
+/// # Exemple
+/// ```no_run
+/// use scrapes::ffmpeg::{self, DownloadOptions, RecordOptions};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let record = RecordOptions {
+///     max_duration: Some(Duration::from_secs(2 * 3600)),
+///     stop_at: ffmpeg::record::next_clock_time(23, 0),
+///     segment_every: Some(Duration::from_secs(30 * 60)),
+/// };
+/// ffmpeg::record_with_options(
+///     "https://live.example.com/stream.m3u8",
+///     "match.mp4",
+///     DownloadOptions::default(),
+///     record,
+///     None::<fn(&ffmpeg::FfmpegProgress)>,
+///     Arc::default(),
+/// ).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn record_with_options<F>(
+    input_url: impl AsRef<str>,
+    output_path: impl AsRef<Path>,
+    options: DownloadOptions,
+    record: RecordOptions,
+    on_progress: Option<F>,
+    cancel: Arc<AtomicBool>,
+) -> Result<(), DownloadError>
+where
+    F: Fn(&FfmpegProgress) + Send + Sync + 'static,
+{
+    let (progress_tx, callback_task) = progress_channel(on_progress);
+    let result = record_with_ffmpeg(input_url.as_ref(), output_path, options, record, progress_tx, cancel).await;
+    let _ = callback_task.await;
+    result
+}
+
+/// Assemble plusieurs entrées (fichiers ou URLs), dans l'ordre, en une seule sortie avec le
+/// démultiplexeur `concat` de ffmpeg (parties d'un même épisode).
+///
+/// # Exemple
+/// ```no_run
+/// use scrapes::ffmpeg::{self, DownloadOptions};
+/// use std::sync::Arc;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let parts = ["https://cdn.example.com/ep1-part1.mp4", "https://cdn.example.com/ep1-part2.mp4"];
+/// ffmpeg::concat_with_options(
+///     &parts,
+///     "episode1.mp4",
+///     DownloadOptions::default(),
+///     None::<fn(&ffmpeg::FfmpegProgress)>,
+///     Arc::default(),
+/// ).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn concat_with_options<F>(
+    inputs: &[impl AsRef<str>],
+    output_path: impl AsRef<Path>,
+    options: DownloadOptions,
+    on_progress: Option<F>,
+    cancel: Arc<AtomicBool>,
+) -> Result<(), DownloadError>
+where
+    F: Fn(&FfmpegProgress) + Send + Sync + 'static,
+{
+    let inputs: Vec<String> = inputs.iter().map(|i| i.as_ref().to_string()).collect();
+    let (progress_tx, callback_task) = progress_channel(on_progress);
+    let result = concat_with_ffmpeg(&inputs, output_path, options, progress_tx, cancel).await;
+    let _ = callback_task.await;
+    result
+}
+
+/// Télécharge une URL en deux temps: la source par le gestionnaire de téléchargement (plages
+/// parallèles, reprise), puis une finalisation locale par ffmpeg selon `options`.
+///
+/// Un seul flux de progression couvre les deux étapes (`pipeline::Stage::of`); une annulation
+/// garde la source partielle, reprise au prochain appel.
+///
+/// # Exemple
+/// ```no_run
+/// use scrapes::ffmpeg::{self, DownloadOptions};
+/// use std::sync::Arc;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// ffmpeg::fetch_then_remux(
+///     "https://cdn.example.com/episode1.mkv",
+///     "episode1.mp4",
+///     DownloadOptions::default(),
+///     Some(|progress: &ffmpeg::FfmpegProgress| {
+///         println!("{:?} {:?}", ffmpeg::pipeline::Stage::of(progress), progress.percent);
+///     }),
+///     Arc::default(),
+/// ).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn fetch_then_remux<F>(
+    input_url: impl AsRef<str>,
+    output_path: impl AsRef<Path>,
+    options: DownloadOptions,
+    on_progress: Option<F>,
+    cancel: Arc<AtomicBool>,
+) -> Result<(), DownloadError>
+where
+    F: Fn(&FfmpegProgress) + Send + Sync + 'static,
+{
+    let (progress_tx, callback_task) = progress_channel(on_progress);
+    let result = fetch_then_remux_with_ffmpeg(input_url.as_ref(), output_path, options, progress_tx, cancel).await;
+    let _ = callback_task.await;
+    result
+}
+
+/// Canal de progression relié à `on_progress`; la tâche se termine quand l'émetteur est libéré
+fn progress_channel<F>(on_progress: Option<F>) -> (mpsc::Sender<FfmpegProgress>, tokio::task::JoinHandle<()>)
+where
+    F: Fn(&FfmpegProgress) + Send + Sync + 'static,
+{
     let (progress_tx, mut progress_rx) = mpsc::channel(100);
 
     // Spawner une tâche pour gérer les callbacks de progression
     let callback_task = if let Some(callback) = on_progress {
-        Some(tokio::spawn(async move {
+        tokio::spawn(async move {
             while let Some(progress) = progress_rx.recv().await {
                 callback(&progress);
             }
-        }))
+        })
     } else {
         // Si pas de callback, on consomme juste les messages pour éviter de bloquer
-        Some(tokio::spawn(async move {
+        tokio::spawn(async move {
             while let Some(_) = progress_rx.recv().await {}
-        }))
+        })
     };
-
-    // Lancer le téléchargement
-    // Le canal se ferme automatiquement quand progress_tx est drop (à la fin de download_with_ffmpeg)
-    let result = download_with_ffmpeg(input_url, output_path, options, progress_tx).await;
-
-    // Attendre que le callback ait fini de traiter tous les messages
-    // Le canal se ferme quand progress_tx est drop, ce qui fait que progress_rx.recv() retourne None
-    if let Some(task) = callback_task {
-        // Attendre que la tâche se termine naturellement (quand le canal est fermé)
-        let _ = task.await;
-    }
-
-    result
+    (progress_tx, callback_task)
 }
@@ -1,17 +1,89 @@
+use std::path::PathBuf;
 use std::time::Duration;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use crate::ffmpeg::dash::DashSelection;
+use crate::ffmpeg::hls::HlsVariant;
+use crate::ffmpeg::hwaccel::HwAccel;
+use crate::ffmpeg::metadata::MetadataOptions;
+use crate::ffmpeg::subtitles::{ExternalSubtitle, SubtitleMode};
 
 /// Événement de progression émis depuis `-progress pipe:1` de ffmpeg
 #[derive(Debug, Clone)]
 pub struct FfmpegProgress {
-    pub fields: HashMap<String, String>
+    /// Position atteinte dans le média: `out_time_us`, ou `out_time_ms` qui, malgré son nom,
+    /// est lui aussi en microsecondes; `None` tant que ffmpeg indique `N/A`. Les flux DASH
+    /// commencent souvent légèrement avant zéro: une position négative compte pour zéro
+    pub out_time: Option<Duration>,
+    /// Débit de sortie (`bitrate=1843.2kbits/s`)
+    pub bitrate_kbps: Option<f64>,
+    /// Vitesse de traitement (`speed=0.85x`), en multiple du temps réel
+    pub speed: Option<f64>,
+    /// Images traitées (`frame=`), absent en audio seul
+    pub frames: Option<u64>,
+    /// Octets écrits dans la sortie (`total_size=`)
+    pub total_size: Option<u64>,
+    /// Toutes les paires `clé=valeur` reçues, y compris celles sans champ dédié
+    pub fields: HashMap<String, String>,
+    /// Sortie effective, annoncée quand elle change en cours de route (repli `.mkv`)
+    pub output: Option<PathBuf>,
+    /// Avancement de 0 à 100, si ffprobe a donné la durée totale de l'entrée
+    pub percent: Option<f64>,
+    /// Temps restant estimé au rythme observé depuis le lancement de ffmpeg
+    pub eta: Option<Duration>,
+    /// Tentative en cours, à partir de 1 (redémarrages après un blocage ou une coupure)
+    pub attempt: usize,
 }
 
 impl FfmpegProgress {
-    /// Crée un nouveau FfmpegProgress avec les champs donnés
-    #[inline]
+    /// Crée un nouveau FfmpegProgress avec les champs donnés, dont les clés connues sont
+    /// converties (`N/A` ou valeur illisible: `None`)
     pub fn new(fields: HashMap<String, String>) -> Self {
-        Self { fields }
+        let get = |key: &str| fields.get(key).map(|v| v.trim()).filter(|v| *v != "N/A");
+        let out_time = get("out_time_us")
+            .or_else(|| get("out_time_ms"))
+            .and_then(|us| us.parse::<i64>().ok())
+            .map(|us| Duration::from_micros(us.max(0) as u64));
+        let positive = |v: f64| (v.is_finite() && v > 0.0).then_some(v);
+        let bitrate_kbps = get("bitrate").and_then(|v| v.trim_end_matches("kbits/s").parse().ok()).and_then(positive);
+        let speed = get("speed").and_then(|v| v.trim_end_matches('x').parse().ok()).and_then(positive);
+        let frames = get("frame").and_then(|v| v.parse().ok());
+        let total_size = get("total_size").and_then(|v| v.parse().ok());
+        Self { out_time, bitrate_kbps, speed, frames, total_size, fields, output: None, percent: None, eta: None, attempt: 1 }
+    }
+
+    /// Complète `percent` et `eta` à partir de la durée totale `total` de l'entrée et du temps
+    /// `elapsed` écoulé depuis le lancement de ffmpeg; sans durée (direct) rien n'est calculé.
+    ///
+    /// Le temps restant suit la vitesse courante de ffmpeg quand elle est connue: lors d'un
+    /// réencodage, la moyenne depuis le lancement inclut l'analyse de l'entrée et le démarrage
+    /// de l'encodeur et sous‑estime la durée restante
+    pub fn with_estimate(mut self, total: Option<Duration>, elapsed: Duration) -> Self {
+        let (Some(total), Some(position)) = (total.filter(|t| !t.is_zero()), self.out_time) else {
+            return self;
+        };
+        let done = self.fields.get("progress").is_some_and(|p| p == "end");
+        let ratio = if done { 1.0 } else { (position.as_secs_f64() / total.as_secs_f64()).clamp(0.0, 1.0) };
+        self.percent = Some(ratio * 100.0);
+        if let Some(speed) = self.speed.filter(|_| !done) {
+            self.eta = Some(total.saturating_sub(position).div_f64(speed));
+        } else if ratio > 0.0 {
+            self.eta = Some(elapsed.mul_f64((1.0 - ratio) / ratio));
+        }
+        self
+    }
+
+    /// Progression d'une reprise à `offset` (voir `resume`): position, pourcentage et fin
+    /// rapportés au média entier de durée `total`; le temps restant est celui de la partie
+    pub fn resumed_at(mut self, offset: Duration, total: Duration) -> Self {
+        if let Some(position) = self.out_time {
+            self.out_time = Some(position + offset);
+        }
+        if let Some(percent) = self.percent {
+            let remaining = total.saturating_sub(offset).as_secs_f64();
+            let ratio = (offset.as_secs_f64() + remaining * percent / 100.0) / total.as_secs_f64();
+            self.percent = Some((ratio * 100.0).clamp(0.0, 100.0));
+        }
+        self
     }
 }
 
@@ -23,6 +95,62 @@ pub enum DownloadError {
     Io(#[from] std::io::Error),
     #[error("autre: {0}")]
     Other(String),
+    #[error("téléchargement annulé")]
+    Cancelled,
+    #[error("options ffmpeg invalides: {0}")]
+    InvalidOptions(String),
+    #[error("{0}")]
+    MissingBinary(String),
+    #[error("blocage: aucune progression de ffmpeg pendant {0:?}")]
+    Stalled(Duration),
+    /// Les variantes suivantes proviennent de l'analyse de stderr (`ffmpeg::stderr::classify`)
+    /// et gardent ses dernières lignes
+    #[error("le serveur a répondu HTTP {status}{}", http_hint(*status))]
+    HttpStatus { status: u16, stderr: Vec<String> },
+    #[error("données invalides: l'entrée n'est pas un média lisible (page HTML, lien expiré?)")]
+    InvalidData { stderr: Vec<String> },
+    #[error("flux chiffré ou protégé (DRM): ffmpeg ne peut pas le déchiffrer")]
+    Encrypted { stderr: Vec<String> },
+    #[error("codec non pris en charge: {codec} (essayez un préréglage de réencodage ou un autre conteneur)")]
+    UnsupportedCodec { codec: String, stderr: Vec<String> },
+    #[error("ffmpeg s'est terminé avec le statut {code}")]
+    FfmpegFailed { code: i32, stderr: Vec<String> },
+}
+
+impl DownloadError {
+    /// Dernières lignes de stderr de ffmpeg associées à l'erreur (vide si non capturées)
+    pub fn stderr_tail(&self) -> &[String] {
+        match self {
+            DownloadError::HttpStatus { stderr, .. }
+            | DownloadError::InvalidData { stderr }
+            | DownloadError::Encrypted { stderr }
+            | DownloadError::UnsupportedCodec { stderr, .. }
+            | DownloadError::FfmpegFailed { stderr, .. } => stderr,
+            _ => &[],
+        }
+    }
+
+    /// `true` si une nouvelle tentative a des chances d'aboutir: blocage, erreur d'E/S, erreur
+    /// du serveur (5xx, 408, 429) ou échec non classé. Un lien refusé ou introuvable, des données
+    /// illisibles, un flux chiffré ou un codec non pris en charge échoueraient à l'identique
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            DownloadError::Stalled(_) | DownloadError::Io(_) | DownloadError::FfmpegFailed { .. } | DownloadError::FfmpegExit(_) => true,
+            DownloadError::HttpStatus { status, .. } => matches!(status, 408 | 429 | 500..),
+            _ => false,
+        }
+    }
+}
+
+/// Piste de résolution affichée après un statut HTTP
+fn http_hint(status: u16) -> &'static str {
+    match status {
+        401 | 403 => " (accès refusé: ajoutez le Referer, les cookies ou le User-Agent du site)",
+        404 | 410 => " (introuvable: le lien a peut‑être expiré)",
+        429 => " (trop de requêtes: réessayez plus tard)",
+        500.. => " (erreur du serveur)",
+        _ => "",
+    }
 }
 
 /// Options contrôlant le comportement
@@ -34,6 +162,41 @@ pub struct DownloadOptions {
     pub auto_restart: bool,
     /// nombre maximum de tentatives de redémarrage
     pub max_restarts: usize,
+    /// extraire aussi les pistes de sous‑titres intégrées vers un fichier `.srt` à côté de la sortie
+    pub extract_subtitles: bool,
+    /// qualité choisie dans une playlist HLS maîtresse (`hls::list_variants`); `None` laisse ffmpeg prendre la meilleure
+    pub variant: Option<HlsVariant>,
+    /// représentations choisies dans un manifeste DASH (`dash::fetch_manifest`); `None` laisse ffmpeg choisir
+    pub dash: Option<DashSelection>,
+    /// ne garder que la piste audio, dans ce format; l'extension de la sortie est remplacée en conséquence
+    pub audio_only: Option<AudioFormat>,
+    /// arguments ajoutés avant `-i` (ex. `-protocol_whitelist file,http,https,tcp,tls`, `-headers ...`)
+    pub extra_input_args: Vec<String>,
+    /// arguments ajoutés après les codecs, avant le fichier de sortie (ex. `-map 0`, `-metadata title=...`)
+    pub extra_output_args: Vec<String>,
+    /// `User-Agent` envoyé pour les entrées HTTP (`-user_agent`)
+    pub user_agent: Option<String>,
+    /// en‑têtes HTTP des entrées (`Referer`, `Cookie`...), passés en `-headers`; les flux repérés
+    /// par le sniffer répondent souvent 403 sans eux
+    pub headers: BTreeMap<String, String>,
+    /// pistes de sous‑titres intégrées: écartées, ou gardées (converties en `mov_text`)
+    pub subtitles: SubtitleMode,
+    /// fichiers `.vtt` / `.srt` externes à intégrer comme pistes supplémentaires
+    pub external_subtitles: Vec<ExternalSubtitle>,
+    /// réencodage nommé à la place de `-c copy`; incompatible avec `audio_only`
+    pub preset: Option<TranscodePreset>,
+    /// décodage et encodage matériels d'un préréglage vidéo (`hwaccel::available` liste les méthodes utilisables); sans effet en copie
+    pub hwaccel: Option<HwAccel>,
+    /// HLS: télécharger les segments un à un (reprise après interruption) puis remuxer, au lieu de laisser ffmpeg lire le flux
+    pub segmented_hls: bool,
+    /// générer une affiche et une bande d'aperçu à côté de la sortie (`thumbnails::generate_thumbnails`)
+    pub thumbnails: bool,
+    /// cadence de lecture de l'entrée (`-readrate`, ffmpeg 5 ou plus) pour ne pas saturer la connexion; `None` lit au plus vite
+    pub read_rate: Option<ReadRate>,
+    /// vidéo à la demande: écrire par parties dans `<sortie>.parts/` pour qu'une nouvelle tentative, même après redémarrage de l'application, reprenne où la précédente s'est arrêtée (`resume`)
+    pub resume_vod: bool,
+    /// métadonnées et chapitres de la sortie (copie depuis l'entrée, titre, série, saison, épisode)
+    pub metadata: MetadataOptions,
 }
 
 impl Default for DownloadOptions {
@@ -42,6 +205,216 @@ impl Default for DownloadOptions {
             stall_timeout: Duration::from_secs(20),
             auto_restart: true,
             max_restarts: 3,
+            extract_subtitles: false,
+            variant: None,
+            dash: None,
+            audio_only: None,
+            extra_input_args: Vec::new(),
+            extra_output_args: Vec::new(),
+            user_agent: None,
+            headers: BTreeMap::new(),
+            subtitles: SubtitleMode::Drop,
+            external_subtitles: Vec::new(),
+            preset: None,
+            hwaccel: None,
+            segmented_hls: false,
+            thumbnails: false,
+            read_rate: None,
+            resume_vod: false,
+            metadata: MetadataOptions::default(),
+        }
+    }
+}
+
+/// Options que le module pilote lui‑même: les redéfinir casserait le suivi ou la sortie
+const RESERVED_ARGS: [&str; 5] = ["-i", "-y", "-n", "-progress", "-nostats"];
+/// Options qui attendent une valeur
+const VALUED_ARGS: [&str; 10] = ["-map", "-metadata", "-protocol_whitelist", "-headers", "-user_agent", "-c", "-codec", "-f", "-t", "-ss"];
+
+impl DownloadOptions {
+    /// Format de sortie sans vidéo: mode audio seul ou préréglage audio; `None` si la vidéo est gardée
+    pub fn audio_output(&self) -> Option<AudioFormat> {
+        self.audio_only.or_else(|| self.preset.and_then(TranscodePreset::audio_format))
+    }
+
+    /// Options d'entrée HTTP de ffmpeg: `-user_agent` et `-headers` (une ligne `Nom: valeur\r\n`
+    /// par en‑tête)
+    pub fn http_input_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(user_agent) = &self.user_agent {
+            args.extend(["-user_agent".to_string(), user_agent.clone()]);
+        }
+        if !self.headers.is_empty() {
+            let headers: String = self.headers.iter().map(|(name, value)| format!("{name}: {value}\r\n")).collect();
+            args.extend(["-headers".to_string(), headers]);
+        }
+        args
+    }
+
+    /// Mêmes en‑têtes pour les requêtes faites hors de ffmpeg (playlists, segments HLS)
+    pub fn request_headers(&self) -> Vec<(String, String)> {
+        let mut headers: Vec<_> = self.headers.iter().map(|(n, v)| (n.clone(), v.clone())).collect();
+        headers.extend(self.user_agent.clone().map(|ua| ("User-Agent".to_string(), ua)));
+        headers
+    }
+
+    /// Refuse les arguments supplémentaires manifestement incompatibles: option gérée par le
+    /// module, argument vide, option sans sa valeur, codec vidéo en mode audio seul, sous‑titre
+    /// externe sans URL, préréglage combiné au mode audio seul, en‑tête HTTP mal formé
+    pub fn validate(&self) -> Result<(), DownloadError> {
+        let invalid = |msg: String| Err(DownloadError::InvalidOptions(msg));
+        let broken = |text: &str| text.contains(['\r', '\n']);
+        if let Some(name) = self.headers.keys().find(|n| n.trim().is_empty() || n.contains(':') || broken(n)) {
+            return invalid(format!("nom d'en‑tête invalide: `{name}`"));
+        }
+        if let Some(name) = self.headers.iter().find(|(_, v)| broken(v)).map(|(n, _)| n) {
+            return invalid(format!("valeur de l'en‑tête `{name}` sur plusieurs lignes"));
+        }
+        if self.user_agent.as_deref().is_some_and(broken) {
+            return invalid("User-Agent sur plusieurs lignes".into());
+        }
+        if self.external_subtitles.iter().any(|s| s.url.trim().is_empty()) {
+            return invalid("sous‑titre externe sans URL".into());
+        }
+        for args in [&self.extra_input_args, &self.extra_output_args] {
+            if args.iter().any(|a| a.trim().is_empty()) {
+                return invalid("argument vide".into());
+            }
+            if let Some(reserved) = args.iter().find(|a| RESERVED_ARGS.contains(&a.as_str())) {
+                return invalid(format!("`{reserved}` est géré par le téléchargeur"));
+            }
+            // Option attendant une valeur, suivie d'une autre option ou de rien
+            for (i, arg) in args.iter().enumerate() {
+                let expects_value = VALUED_ARGS.contains(&arg.as_str()) || arg.starts_with("-c:") || arg.starts_with("-metadata:");
+                if expects_value && args.get(i + 1).is_none_or(|next| next.starts_with('-') && next.len() > 1) {
+                    return invalid(format!("`{arg}` attend une valeur"));
+                }
+            }
+        }
+        if let Some(ReadRate::Speed(speed)) = self.read_rate
+            && !(speed.is_finite() && speed > 0.0)
+        {
+            return invalid(format!("cadence de lecture invalide: {speed}"));
+        }
+        if let (Some(_), Some(preset)) = (self.audio_only, self.preset) {
+            return invalid(format!("le préréglage « {} » remplace le mode audio seul", preset.label()));
+        }
+        if self.audio_output().is_some()
+            && let Some(video) = self.extra_output_args.iter().find(|a| matches!(a.as_str(), "-c:v" | "-vcodec" | "-vf"))
+        {
+            return invalid(format!("`{video}` contredit le mode audio seul"));
+        }
+        Ok(())
+    }
+}
+
+/// Cadence de lecture d'une entrée, exprimée pour ffmpeg en multiple du temps réel (`-readrate`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReadRate {
+    /// Multiple du temps réel: 1.0 équivaut à `-re`, 2.0 lit deux fois plus vite que la lecture
+    Speed(f64),
+    /// Débit maximal en octets/s, converti d'après le débit du média
+    BytesPerSec(u64),
+    /// Limite de bande passante globale (`[bandwidth]`, plages horaires comprises), lue au
+    /// lancement; sans limite, l'entrée est lue au plus vite
+    GlobalLimit,
+}
+
+impl ReadRate {
+    /// Cadence la plus lente acceptée: en deçà, le délai de blocage se déclencherait
+    const MIN_SPEED: f64 = 0.1;
+
+    /// Valeur de `-readrate` pour un média de `bitrate` bit/s, la limite globale valant
+    /// `global_bytes_per_sec` (0 = illimitée); `None` si rien ne limite la lecture.
+    ///
+    /// Débit du média inconnu: lecture en temps réel, qui ne dépasse pas le débit du flux
+    pub fn speed(self, bitrate: Option<u64>, global_bytes_per_sec: u64) -> Option<f64> {
+        let bytes_per_sec = match self {
+            ReadRate::Speed(speed) => return Some(speed),
+            ReadRate::BytesPerSec(bytes_per_sec) => bytes_per_sec,
+            ReadRate::GlobalLimit => global_bytes_per_sec,
+        };
+        if bytes_per_sec == 0 {
+            return None;
+        }
+        let speed = bitrate.filter(|&b| b > 0).map_or(1.0, |bitrate| bytes_per_sec as f64 * 8.0 / bitrate as f64);
+        Some(speed.max(Self::MIN_SPEED))
+    }
+}
+
+/// Réencodage nommé, à la place de la copie des flux (`-c copy`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscodePreset {
+    /// H.264 (libx264, CRF 20) limité à 1080 lignes, AAC 192 kbit/s: lisible partout
+    H264Hd,
+    /// H.265 (libx265, CRF 28), AAC 96 kbit/s: fichiers environ deux fois plus petits
+    H265Compact,
+    /// Piste audio seule réencodée en AAC 128 kbit/s (`.m4a`)
+    Audio128k,
+}
+
+impl TranscodePreset {
+    pub const ALL: [TranscodePreset; 3] = [TranscodePreset::H264Hd, TranscodePreset::H265Compact, TranscodePreset::Audio128k];
+
+    /// Nom affiché dans l'onglet FFmpeg
+    pub fn label(self) -> &'static str {
+        match self {
+            TranscodePreset::H264Hd => "H.264 1080p",
+            TranscodePreset::H265Compact => "H.265 compact",
+            TranscodePreset::Audio128k => "Audio AAC 128k",
+        }
+    }
+
+    /// Format de sortie des préréglages sans vidéo
+    pub fn audio_format(self) -> Option<AudioFormat> {
+        match self {
+            TranscodePreset::Audio128k => Some(AudioFormat::M4a),
+            _ => None,
+        }
+    }
+
+    /// Arguments de sortie ffmpeg: codecs et réglages du préréglage
+    pub fn codec_args(self) -> &'static [&'static str] {
+        match self {
+            TranscodePreset::H264Hd => &[
+                "-c:v", "libx264", "-preset", "medium", "-crf", "20", "-vf", "scale=-2:'min(1080,ih)'",
+                "-pix_fmt", "yuv420p", "-c:a", "aac", "-b:a", "192k",
+            ],
+            TranscodePreset::H265Compact => &["-c:v", "libx265", "-preset", "medium", "-crf", "28", "-tag:v", "hvc1", "-c:a", "aac", "-b:a", "96k"],
+            TranscodePreset::Audio128k => &["-vn", "-sn", "-c:a", "aac", "-b:a", "128k"],
+        }
+    }
+}
+
+/// Format de sortie du mode audio seul
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    /// AAC copié sans réencodage (piste audio habituelle des flux HLS/mp4)
+    M4a,
+    /// Réencodé en MP3 (libmp3lame, VBR haute qualité)
+    Mp3,
+    /// Réencodé en Opus (libopus, 128 kbit/s)
+    Opus,
+}
+
+impl AudioFormat {
+    pub const ALL: [AudioFormat; 3] = [AudioFormat::M4a, AudioFormat::Mp3, AudioFormat::Opus];
+
+    /// Extension du fichier produit, qui détermine aussi le conteneur choisi par ffmpeg
+    pub fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::M4a => "m4a",
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Opus => "opus",
+        }
+    }
+
+    /// Arguments de sortie ffmpeg: sans vidéo (`-vn`) ni sous‑titres (`-sn`), codec audio du format
+    pub fn codec_args(self) -> &'static [&'static str] {
+        match self {
+            AudioFormat::M4a => &["-vn", "-sn", "-acodec", "copy"],
+            AudioFormat::Mp3 => &["-vn", "-sn", "-acodec", "libmp3lame", "-q:a", "2"],
+            AudioFormat::Opus => &["-vn", "-sn", "-acodec", "libopus", "-b:a", "128k"],
         }
     }
 }
@@ -0,0 +1,297 @@
+//! Téléchargement en deux temps: le réseau par le gestionnaire, la finalisation par ffmpeg.
+//!
+//! Lu directement par ffmpeg, un fichier distant passe par une seule connexion et une coupure
+//! relance tout. `fetch_then_remux_with_ffmpeg` confie d'abord la source au gestionnaire de
+//! téléchargement: plages parallèles, reprise par manifeste, nouvelles tentatives, plafonds de
+//! débit et par hôte. Un fichier simple est écrit dans `<sortie>.fetch/`, une playlist HLS à la
+//! demande segment par segment dans `<sortie>.hls/` (voir `segments`). ffmpeg ne fait ensuite
+//! que remuxer (ou réencoder, avec un préréglage) la copie locale, puis le dossier est supprimé.
+//!
+//! Les deux étapes forment un seul flux de progression: le téléchargement couvre 0 à 90 %, la
+//! finalisation 90 à 100 %, et `Stage::of` indique l'étape de chaque événement. Les manifestes
+//! DASH, les variantes à audio séparé, les directs et les playlists chiffrées restent lus
+//! directement par ffmpeg.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use crate::downloader::{self, AdaptiveChunking, CollisionPolicy, DownloadManager, DownloadTask, HttpClientOptions, Manifest, ProgressEvent, RetryPolicy};
+use crate::ffmpeg::{binary, dash, hls, segments};
+use crate::ffmpeg::downloader::download_with_ffmpeg;
+use crate::ffmpeg::params::{DownloadError, DownloadOptions, FfmpegProgress};
+
+/// Part de l'avancement global réservée au téléchargement, en pourcentage
+const FETCH_SHARE: f64 = 90.0;
+/// Intervalle de consultation du drapeau d'annulation
+const CANCEL_POLL: Duration = Duration::from_millis(200);
+
+/// Étape d'un téléchargement en deux temps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Source téléchargée par le gestionnaire
+    Fetch,
+    /// Copie locale finalisée par ffmpeg
+    Remux,
+}
+
+impl Stage {
+    fn as_str(self) -> &'static str {
+        match self {
+            Stage::Fetch => "fetch",
+            Stage::Remux => "remux",
+        }
+    }
+
+    /// Étape d'un événement de progression (champ `stage`); `None` hors d'un téléchargement en
+    /// deux temps
+    pub fn of(progress: &FfmpegProgress) -> Option<Stage> {
+        match progress.fields.get("stage").map(String::as_str) {
+            Some("fetch") => Some(Stage::Fetch),
+            Some("remux") => Some(Stage::Remux),
+            _ => None,
+        }
+    }
+
+    /// Rapporte un événement de l'étape à l'avancement global
+    fn apply(self, mut progress: FfmpegProgress) -> FfmpegProgress {
+        progress.fields.insert("stage".to_string(), self.as_str().to_string());
+        progress.percent = progress.percent.map(|percent| match self {
+            Stage::Fetch => percent * FETCH_SHARE / 100.0,
+            Stage::Remux => FETCH_SHARE + percent * (100.0 - FETCH_SHARE) / 100.0,
+        });
+        progress
+    }
+}
+
+/// Dossier de la source d'une sortie: `episode.mp4` -> `episode.mp4.fetch`
+pub fn fetch_dir(output_path: &Path) -> PathBuf {
+    let name = output_path.file_name().and_then(|n| n.to_str()).unwrap_or("output");
+    output_path.with_file_name(format!("{name}.fetch"))
+}
+
+/// Copie locale de `url` dans `dir`: `source.<extension de l'URL>`, `source.bin` à défaut
+fn source_path(dir: &Path, url: &str) -> PathBuf {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let extension = path
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext)
+        .filter(|ext| !ext.is_empty() && ext.len() <= 4 && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+        .unwrap_or("bin");
+    dir.join(format!("source.{extension}"))
+}
+
+/// `false` pour les sources que seul ffmpeg sait lire: manifestes DASH, variantes HLS à audio
+/// séparé
+fn fetchable(input_url: &str, opts: &DownloadOptions) -> bool {
+    opts.dash.is_none() && !dash::is_manifest_url(input_url) && opts.variant.as_ref().is_none_or(|v| v.audio_url.is_none())
+}
+
+/// Options de la finalisation: entrée locale, sans variante, en‑têtes, cadence ni découpage
+fn local_options(opts: &DownloadOptions) -> DownloadOptions {
+    DownloadOptions {
+        variant: None,
+        dash: None,
+        user_agent: None,
+        headers: Default::default(),
+        segmented_hls: false,
+        resume_vod: false,
+        read_rate: None,
+        ..opts.clone()
+    }
+}
+
+/// Canal dont les événements sont rapportés à l'avancement global par `stage` puis transmis à
+/// `progress_tx`; la tâche se termine quand l'émetteur est libéré
+fn relay(stage: Stage, progress_tx: &mpsc::Sender<FfmpegProgress>) -> (mpsc::Sender<FfmpegProgress>, tokio::task::JoinHandle<()>) {
+    let (stage_tx, mut stage_rx) = mpsc::channel::<FfmpegProgress>(progress_tx.max_capacity());
+    let progress_tx = progress_tx.clone();
+    let task = tokio::spawn(async move {
+        while let Some(progress) = stage_rx.recv().await {
+            let _ = progress_tx.try_send(stage.apply(progress));
+        }
+    });
+    (stage_tx, task)
+}
+
+/// Télécharge `input_url` par le gestionnaire de téléchargement, puis laisse ffmpeg finaliser
+/// la copie locale vers `output_path` selon `opts`.
+///
+/// Une annulation ou un échec laisse la source (ou les segments) en place: un nouvel appel
+/// reprend le téléchargement là où il s'était arrêté, ou passe directement à la finalisation si
+/// la source est complète. Les sources inadaptées (voir le module) sont confiées telles quelles
+/// à `download_with_ffmpeg`.
+pub async fn fetch_then_remux_with_ffmpeg(
+    input_url: &str,
+    output_path: impl AsRef<Path>,
+    opts: DownloadOptions,
+    progress_tx: mpsc::Sender<FfmpegProgress>,
+    cancel: Arc<AtomicBool>,
+) -> Result<(), DownloadError> {
+    opts.validate()?;
+    binary::ffmpeg()?;
+    let output_path = output_path.as_ref();
+    if !fetchable(input_url, &opts) {
+        tracing::info!(input_url, "Source réservée à ffmpeg, lecture directe");
+        return download_with_ffmpeg(input_url, output_path, opts, progress_tx, cancel).await;
+    }
+
+    let headers = opts.request_headers();
+    let (mut fetch_tx, fetch_relay) = relay(Stage::Fetch, &progress_tx);
+    let fetched = match hls::is_playlist_url(input_url) || opts.variant.is_some() {
+        true => match segments::resolve_playlist(input_url, opts.variant.as_ref(), &headers).await {
+            Ok(playlist) => segments::fetch_segments(&playlist, output_path, &headers, &mut fetch_tx, &cancel)
+                .await
+                .map(|joined| Some((joined, segments::work_dir(output_path)))),
+            Err(e) => {
+                tracing::warn!(error = %e, "Playlist non téléchargeable par segments, lecture directe par ffmpeg");
+                Ok(None)
+            }
+        },
+        false => fetch_file(input_url, output_path, &headers, &fetch_tx, &cancel).await.map(|source| Some((source, fetch_dir(output_path)))),
+    };
+    drop(fetch_tx);
+    let _ = fetch_relay.await;
+    let Some((source, work_dir)) = fetched? else {
+        return download_with_ffmpeg(input_url, output_path, opts, progress_tx, cancel).await;
+    };
+
+    tracing::info!(source = %source.display(), output = %output_path.display(), "Source téléchargée, finalisation par ffmpeg");
+    let (remux_tx, remux_relay) = relay(Stage::Remux, &progress_tx);
+    let result = download_with_ffmpeg(&source.to_string_lossy(), output_path, local_options(&opts), remux_tx, cancel).await;
+    let _ = remux_relay.await;
+    if result.is_ok() {
+        let _ = tokio::fs::remove_dir_all(&work_dir).await;
+    }
+    result
+}
+
+/// Télécharge le fichier `url` dans `fetch_dir(output_path)`; rend la copie locale. Une copie
+/// complète d'un appel précédent est reprise telle quelle
+async fn fetch_file(
+    url: &str,
+    output_path: &Path,
+    headers: &[(String, String)],
+    progress_tx: &mpsc::Sender<FfmpegProgress>,
+    cancel: &AtomicBool,
+) -> Result<PathBuf, DownloadError> {
+    let dir = fetch_dir(output_path);
+    tokio::fs::create_dir_all(&dir).await?;
+    let source = source_path(&dir, url);
+    // la sortie du gestionnaire n'apparaît qu'une fois le fichier complet
+    if source.exists() && !Manifest::path_for(&source).exists() {
+        tracing::info!(source = %source.display(), "Source déjà téléchargée");
+        return Ok(source);
+    }
+
+    let config = downloader::load_config();
+    let (events_tx, mut events_rx) = mpsc::unbounded_channel::<ProgressEvent>();
+    // ni décompression ni post‑traitements: la copie n'est qu'une étape vers la sortie
+    let manager = DownloadManager::new()
+        .with_retry_policy(RetryPolicy::from_config(&config))
+        .with_stall_timeout(downloader::stall_timeout_from_config(&config))
+        .with_adaptive_chunking(AdaptiveChunking::from_config(&config))
+        .with_max_concurrency(downloader::max_concurrency_from_config(&config))
+        .with_small_file_threshold(downloader::small_file_threshold_from_config(&config))
+        .with_http_options(HttpClientOptions::from_config(&config))
+        .with_collision_policy(CollisionPolicy::Overwrite)
+        .with_progress(events_tx);
+    let builder = DownloadTask::builder(url.to_string())
+        .with_output(source.clone())
+        .with_rate_limit(downloader::per_download_limit_from_config(&config));
+    let task = headers
+        .iter()
+        .fold(builder, |builder, (name, value)| builder.with_header(name.clone(), value.clone()))
+        .build()
+        .map_err(|e| DownloadError::Other(e.to_string()))?;
+    tracing::info!(url, source = %source.display(), "Téléchargement de la source");
+
+    let progress_tx = progress_tx.clone();
+    let relay = tokio::spawn(async move {
+        while let Some(event) = events_rx.recv().await {
+            let _ = progress_tx.try_send(fetch_progress(&event));
+        }
+    });
+    // `None`: annulé, la copie partielle et son manifeste restent pour la reprise
+    let result = {
+        let download = manager.start(task);
+        tokio::pin!(download);
+        loop {
+            tokio::select! {
+                result = &mut download => break Some(result),
+                _ = tokio::time::sleep(CANCEL_POLL) => {
+                    if cancel.load(Ordering::Relaxed) {
+                        tracing::info!(source = %source.display(), "Téléchargement de la source annulé, reprise possible");
+                        break None;
+                    }
+                }
+            }
+        }
+    };
+    // le gestionnaire libéré ferme le canal d'événements
+    drop(manager);
+    let _ = relay.await;
+    match result {
+        Some(result) => result.map(|_| source).map_err(|e| DownloadError::Other(format!("source {url}: {e:#}"))),
+        None => Err(DownloadError::Cancelled),
+    }
+}
+
+/// Événement du gestionnaire exprimé en progression ffmpeg: octets reçus (`total_size`), débit
+/// et temps restant du téléchargement
+fn fetch_progress(event: &ProgressEvent) -> FfmpegProgress {
+    let mut fields = HashMap::new();
+    fields.insert("downloaded".to_string(), event.total_downloaded.to_string());
+    fields.insert("progress".to_string(), "continue".to_string());
+    let mut progress = FfmpegProgress::new(fields);
+    progress.total_size = Some(event.total_downloaded);
+    progress.bitrate_kbps = (event.speed > 0).then(|| event.speed as f64 * 8.0 / 1000.0);
+    progress.percent = (event.total_size > 0).then(|| (event.total_downloaded as f64 * 100.0 / event.total_size as f64).min(100.0));
+    progress.eta = event.eta;
+    progress
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffmpeg::HlsVariant;
+
+    #[test]
+    fn test_stage_progress_and_fetch_layout() {
+        let event = ProgressEvent {
+            chunk_index: 0,
+            chunk_downloaded: 0,
+            total_downloaded: 25_000_000,
+            total_size: 100_000_000,
+            speed: 1_000_000,
+            average_speed: 900_000,
+            eta: Some(Duration::from_secs(75)),
+            throttled: None,
+            mirrors: Vec::new(),
+        };
+        let fetch = Stage::Fetch.apply(fetch_progress(&event));
+        assert_eq!(Stage::of(&fetch), Some(Stage::Fetch));
+        assert_eq!((fetch.percent, fetch.eta, fetch.bitrate_kbps), (Some(22.5), Some(Duration::from_secs(75)), Some(8000.0)));
+        let mut remux = FfmpegProgress::new(HashMap::new());
+        remux.percent = Some(50.0);
+        let remux = Stage::Remux.apply(remux);
+        assert_eq!((Stage::of(&remux), remux.percent), (Some(Stage::Remux), Some(95.0)));
+        assert_eq!(Stage::of(&FfmpegProgress::new(HashMap::new())), None);
+
+        let dir = fetch_dir(Path::new("/tmp/Saison 1/Episode 01.mp4"));
+        assert_eq!(dir, PathBuf::from("/tmp/Saison 1/Episode 01.mp4.fetch"));
+        assert_eq!(source_path(&dir, "https://cdn.example.com/v/ep1.MKV?sig=a.b"), dir.join("source.MKV"));
+        assert_eq!(source_path(&dir, "https://cdn.example.com/watch.php/stream"), dir.join("source.bin"));
+
+        let opts = DownloadOptions::default();
+        assert!(fetchable("https://cdn.example.com/ep1.mp4", &opts));
+        assert!(!fetchable("https://cdn.example.com/manifest.mpd", &opts));
+        let variant = HlsVariant { url: "https://cdn.example.com/720p.m3u8".into(), bandwidth: None, resolution: None, codecs: None, audio_url: Some("a.m3u8".into()) };
+        assert!(!fetchable("https://cdn.example.com/master.m3u8", &DownloadOptions { variant: Some(variant), ..opts.clone() }));
+        let local = local_options(&DownloadOptions { user_agent: Some("UA".into()), segmented_hls: true, ..opts });
+        assert!(local.user_agent.is_none() && !local.segmented_hls && local.http_input_args().is_empty());
+    }
+}
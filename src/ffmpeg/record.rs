@@ -0,0 +1,204 @@
+//! Enregistrement de flux en direct.
+//!
+//! Un direct n'a pas de fin: lancé comme un téléchargement, ffmpeg tourne jusqu'à ce qu'on le
+//! tue, et un mp4 interrompu n'est pas lisible. `record_with_ffmpeg` borne l'enregistrement
+//! (`RecordOptions::max_duration`, heure d'arrêt `stop_at`), écrit un mp4 fragmenté lisible à
+//! tout moment et peut découper la sortie en segments de N minutes, nommés par leur heure de
+//! début. Après un blocage, l'enregistrement reprend dans un nouveau fichier (`episode-2.mp4`)
+//! pour le temps restant; l'arrêt demandé (`cancel`) conserve ce qui a été enregistré.
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, SystemTime};
+use chrono::TimeZone;
+use tokio::sync::mpsc;
+use crate::ffmpeg::binary;
+use crate::ffmpeg::downloader::{Attempt, final_output_path, run_ffmpeg_once, sleep_unless_cancelled, stream_args};
+use crate::ffmpeg::params::{AudioFormat, DownloadError, DownloadOptions, FfmpegProgress};
+
+/// Temps restant en dessous duquel l'enregistrement est considéré terminé
+const MIN_REMAINING: Duration = Duration::from_secs(1);
+/// mp4 fragmenté: lisible même si ffmpeg est interrompu
+const FRAGMENTED_MP4: &str = "+frag_keyframe+empty_moov";
+
+/// Limites d'un enregistrement en direct
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordOptions {
+    /// Durée maximale de l'enregistrement
+    pub max_duration: Option<Duration>,
+    /// Heure d'arrêt (voir `next_clock_time`)
+    pub stop_at: Option<SystemTime>,
+    /// Nouveau fichier toutes les N minutes (`episode-20250315-210000.mp4`...)
+    pub segment_every: Option<Duration>,
+}
+
+impl RecordOptions {
+    /// Fin de l'enregistrement commencé à `start`: la plus proche des deux limites; `None` sans
+    /// limite
+    pub fn deadline(&self, start: SystemTime) -> Option<SystemTime> {
+        let by_duration = self.max_duration.map(|d| start + d);
+        match (by_duration, self.stop_at) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// Prochaine occurrence de `hour:minute` en heure locale (aujourd'hui, sinon demain)
+pub fn next_clock_time(hour: u32, minute: u32) -> Option<SystemTime> {
+    let now = chrono::Local::now();
+    let today = now.date_naive().and_hms_opt(hour, minute, 0)?;
+    let at = chrono::Local.from_local_datetime(&today).earliest()?;
+    let at = if at <= now { at + chrono::Duration::days(1) } else { at };
+    Some(at.into())
+}
+
+/// Enregistre le direct `input_url` jusqu'à la fin fixée par `record`.
+///
+/// `opts` règle le flux comme pour un téléchargement (variante, audio seul, arguments,
+/// blocage et redémarrages). Sans `max_duration` ni `stop_at`, l'enregistrement est refusé
+/// (`DownloadError::InvalidOptions`). Une fin de flux avant l'échéance termine normalement.
+pub async fn record_with_ffmpeg(
+    input_url: &str,
+    output_path: impl AsRef<Path>,
+    opts: DownloadOptions,
+    record: RecordOptions,
+    mut progress_tx: mpsc::Sender<FfmpegProgress>,
+    cancel: Arc<AtomicBool>,
+) -> Result<(), DownloadError> {
+    opts.validate()?;
+    binary::ffmpeg()?;
+    let deadline = record.deadline(SystemTime::now())
+        .ok_or_else(|| DownloadError::InvalidOptions("enregistrement sans limite: max_duration ou stop_at requis".into()))?;
+    let output_path = final_output_path(output_path.as_ref(), opts.audio_output());
+    let (inputs, codecs) = stream_args(input_url, &opts);
+    let fragmented = opts.audio_output().is_none_or(|f| f == AudioFormat::M4a);
+    let mut attempts = 0usize;
+
+    loop {
+        let remaining = deadline.duration_since(SystemTime::now()).unwrap_or_default();
+        if remaining < MIN_REMAINING {
+            return Ok(());
+        }
+        attempts += 1;
+        let mut args = codecs.clone();
+        args.extend(record_args(remaining, record.segment_every, fragmented));
+        // Segments: écrits directement sous leur nom; sinon fichier temporaire renommé ensuite
+        let (target, part) = match record.segment_every {
+            Some(_) => (segment_pattern(&output_path), None),
+            None => {
+                let part = part_path(&output_path, attempts);
+                (tmp_path(&part), Some(part))
+            }
+        };
+        tracing::info!(url = input_url, ?remaining, attempt = attempts, "Enregistrement du direct");
+        let res = run_ffmpeg_once(&inputs, &args, &target, opts.stall_timeout, Attempt { number: attempts, total: Some(remaining) }, &mut progress_tx, &cancel).await;
+        if let Some(part) = part {
+            keep_recorded(&target, &part).await;
+        }
+
+        match res {
+            Ok(()) => return Ok(()),
+            // Arrêt demandé: ce qui a été enregistré est conservé
+            Err(DownloadError::Cancelled) => return Ok(()),
+            Err(e) if opts.auto_restart && e.is_retryable() && attempts < opts.max_restarts => {
+                tracing::warn!(error = %e, "Enregistrement interrompu, reprise dans un nouveau fichier");
+                if sleep_unless_cancelled(Duration::from_secs(2), &cancel).await {
+                    return Ok(());
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Arguments de sortie: durée restante, mp4 fragmenté, découpage éventuel
+fn record_args(remaining: Duration, segment_every: Option<Duration>, fragmented: bool) -> Vec<String> {
+    let mut args = vec!["-t".to_string(), format!("{:.3}", remaining.as_secs_f64())];
+    match segment_every {
+        Some(every) => {
+            args.extend(["-f", "segment", "-segment_time"].map(String::from));
+            args.push(every.as_secs().max(1).to_string());
+            args.extend(["-reset_timestamps", "1", "-strftime", "1"].map(String::from));
+            if fragmented {
+                args.extend(["-segment_format_options".to_string(), format!("movflags={FRAGMENTED_MP4}")]);
+            }
+        }
+        None if fragmented => args.extend(["-movflags".to_string(), FRAGMENTED_MP4.to_string()]),
+        None => {}
+    }
+    args
+}
+
+/// `episode.mp4` -> `episode-%Y%m%d-%H%M%S.mp4` (heure de début de chaque segment)
+fn segment_pattern(output_path: &Path) -> PathBuf {
+    with_suffix(output_path, "-%Y%m%d-%H%M%S")
+}
+
+/// Fichier de la tentative `attempt`: `episode.mp4`, puis `episode-2.mp4`...
+fn part_path(output_path: &Path, attempt: usize) -> PathBuf {
+    match attempt {
+        1 => output_path.to_owned(),
+        n => with_suffix(output_path, &format!("-{n}")),
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("record");
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => path.with_file_name(format!("{stem}{suffix}.{ext}")),
+        None => path.with_file_name(format!("{stem}{suffix}")),
+    }
+}
+
+/// Fichier temporaire d'une partie, extension conservée pour le choix du conteneur
+fn tmp_path(part: &Path) -> PathBuf {
+    let name = part.file_name().and_then(|n| n.to_str()).unwrap_or("record");
+    let ext = part.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
+    part.with_file_name(format!("{name}.{ext}"))
+}
+
+/// Renomme la partie enregistrée (même interrompue: le mp4 fragmenté reste lisible)
+async fn keep_recorded(tmp: &Path, part: &Path) {
+    match tokio::fs::metadata(tmp).await {
+        Ok(meta) if meta.len() > 0 => {
+            if let Err(e) = tokio::fs::rename(tmp, part).await {
+                tracing::warn!(path = %tmp.display(), error = %e, "Impossible de finaliser l'enregistrement");
+            }
+        }
+        Ok(_) => {
+            let _ = tokio::fs::remove_file(tmp).await;
+        }
+        Err(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadline_args_and_part_names() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let limits = RecordOptions {
+            max_duration: Some(Duration::from_secs(3600)),
+            stop_at: Some(start + Duration::from_secs(600)),
+            segment_every: None,
+        };
+        assert_eq!(limits.deadline(start), Some(start + Duration::from_secs(600)));
+        assert_eq!(RecordOptions::default().deadline(start), None);
+        let next = next_clock_time(3, 30).unwrap();
+        assert!(next > SystemTime::now() && next <= SystemTime::now() + Duration::from_secs(25 * 3600));
+
+        assert_eq!(record_args(Duration::from_millis(90_500), None, true), vec!["-t", "90.500", "-movflags", "+frag_keyframe+empty_moov"]);
+        assert_eq!(record_args(Duration::from_secs(60), Some(Duration::from_secs(900)), false), vec![
+            "-t", "60.000", "-f", "segment", "-segment_time", "900", "-reset_timestamps", "1", "-strftime", "1",
+        ]);
+
+        let output = Path::new("/tmp/live.mp4");
+        assert_eq!(part_path(output, 1), PathBuf::from("/tmp/live.mp4"));
+        assert_eq!(part_path(output, 3), PathBuf::from("/tmp/live-3.mp4"));
+        assert_eq!(tmp_path(&part_path(output, 3)), PathBuf::from("/tmp/live-3.mp4.mp4"));
+        assert_eq!(segment_pattern(output), PathBuf::from("/tmp/live-%Y%m%d-%H%M%S.mp4"));
+    }
+}
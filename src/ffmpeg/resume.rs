@@ -0,0 +1,229 @@
+//! Reprise d'une vidéo à la demande interrompue.
+//!
+//! Lu directement par ffmpeg, un téléchargement interrompu (blocage, coupure, fermeture de
+//! l'application) repart de zéro. Avec `DownloadOptions::resume_vod`, chaque tentative écrit une
+//! partie dans `<sortie>.parts/` (`part-000.mp4`, `part-001.mp4`...), en mp4 fragmenté pour
+//! rester lisible même coupée. Avant chaque tentative, ffprobe mesure les parties déjà écrites
+//! et ffmpeg reprend à leur durée cumulée (`-ss` devant chaque `-i`). Le média entier écrit, le
+//! démultiplexeur `concat` assemble les parties vers la sortie et le dossier est supprimé.
+//!
+//! En copie de flux, `-ss` part de l'image clé précédente: quelques images peuvent se répéter à
+//! la jointure. Seules les entrées de durée connue s'y prêtent; le repli `.mkv` sur échec de mux
+//! (`container`) ne s'applique pas ici.
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use crate::ffmpeg::concat::{self, CONCAT_INPUT_ARGS};
+use crate::ffmpeg::downloader::{Attempt, discard_tmp, probe_duration, run_ffmpeg_once, sleep_unless_cancelled, tmp_output_path, with_input_options};
+use crate::ffmpeg::params::{DownloadError, DownloadOptions, FfmpegProgress};
+
+/// Délai accordé à ffprobe pour une partie locale
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Écart toléré entre les parties écrites et la durée annoncée: ffprobe arrondit, et la
+/// dernière image d'une partie complète peut manquer
+const COMPLETE_MARGIN: Duration = Duration::from_secs(1);
+/// Conteneurs de la famille mp4, écrits fragmentés pour survivre à une coupure
+const FRAGMENTED_EXTENSIONS: [&str; 4] = ["mp4", "m4v", "mov", "m4a"];
+
+/// Dossier des parties d'une sortie: `episode.mp4` -> `episode.mp4.parts`
+pub fn parts_dir(output_path: &Path) -> PathBuf {
+    let name = output_path.file_name().and_then(|n| n.to_str()).unwrap_or("output");
+    output_path.with_file_name(format!("{name}.parts"))
+}
+
+/// Partie numéro `index` (à partir de 0): `part-003.mp4`
+fn part_path(dir: &Path, index: usize, extension: &str) -> PathBuf {
+    dir.join(format!("part-{index:03}.{extension}"))
+}
+
+/// Parties présentes dans `dir`, dans l'ordre; les autres fichiers sont ignorés
+async fn part_files(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let mut parts = Vec::new();
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return parts;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        let is_part = path.file_name().and_then(|n| n.to_str()).is_some_and(|name| {
+            name.strip_prefix("part-")
+                .and_then(|rest| rest.strip_suffix(extension))
+                .and_then(|index| index.strip_suffix('.'))
+                .is_some_and(|index| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+        });
+        if is_part {
+            parts.push(path);
+        }
+    }
+    parts.sort();
+    parts
+}
+
+/// Parties utilisables et leur durée cumulée. Une partie illisible ou vide (coupée avant son
+/// premier fragment) est supprimée avec les suivantes: la reprise repart de la précédente
+async fn written_parts(dir: &Path, extension: &str) -> (Vec<PathBuf>, Duration) {
+    let mut parts = part_files(dir, extension).await;
+    let mut written = Duration::ZERO;
+    for (i, part) in parts.iter().enumerate() {
+        match probe_duration(&part.to_string_lossy(), PROBE_TIMEOUT, &[]).await.filter(|d| !d.is_zero()) {
+            Some(duration) => written += duration,
+            None => {
+                for unusable in &parts[i..] {
+                    tracing::debug!(part = %unusable.display(), "Partie inutilisable supprimée");
+                    let _ = tokio::fs::remove_file(unusable).await;
+                }
+                parts.truncate(i);
+                break;
+            }
+        }
+    }
+    (parts, written)
+}
+
+/// Option d'entrée démarrant la lecture à `offset`
+fn seek_args(offset: Duration) -> Vec<String> {
+    vec!["-ss".to_string(), format!("{:.3}", offset.as_secs_f64())]
+}
+
+/// Codecs d'une partie: mp4 fragmenté, lisible même si ffmpeg est interrompu
+fn part_codecs(codecs: &[String], extension: &str) -> Vec<String> {
+    let mut codecs = codecs.to_vec();
+    if FRAGMENTED_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()) {
+        codecs.extend(["-movflags", "+frag_keyframe+empty_moov+default_base_moof"].map(String::from));
+    }
+    codecs
+}
+
+/// Télécharge `inputs` de durée `total` vers `output_path` partie par partie, en reprenant
+/// après les parties d'une tentative précédente, puis les assemble.
+///
+/// Les redémarrages suivent `opts` (`auto_restart`, `max_restarts`); un échec définitif garde
+/// les parties pour un prochain appel, une annulation les supprime.
+pub(super) async fn download_in_parts(
+    inputs: &[String],
+    codecs: &[String],
+    output_path: &Path,
+    total: Duration,
+    opts: &DownloadOptions,
+    progress_tx: &mut mpsc::Sender<FfmpegProgress>,
+    cancel: &AtomicBool,
+) -> Result<(), DownloadError> {
+    let dir = parts_dir(output_path);
+    tokio::fs::create_dir_all(&dir).await?;
+    let extension = output_path.extension().and_then(|e| e.to_str()).unwrap_or("mp4").to_string();
+    let codecs = part_codecs(codecs, &extension);
+    let mut attempts = 0usize;
+
+    let parts = loop {
+        let (parts, written) = written_parts(&dir, &extension).await;
+        if !parts.is_empty() && written + COMPLETE_MARGIN >= total {
+            break parts;
+        }
+        attempts += 1;
+        let part = part_path(&dir, parts.len(), &extension);
+        let inputs = match written.is_zero() {
+            true => inputs.to_vec(),
+            false => {
+                tracing::info!(parts = parts.len(), ?written, ?total, "Reprise après les parties déjà écrites");
+                with_input_options(inputs.to_vec(), &seek_args(written))
+            }
+        };
+        // progression de la partie, rapportée au média entier
+        let (mut part_tx, mut part_rx) = mpsc::channel::<FfmpegProgress>(progress_tx.max_capacity());
+        let relay = tokio::spawn({
+            let progress_tx = progress_tx.clone();
+            async move {
+                while let Some(progress) = part_rx.recv().await {
+                    let _ = progress_tx.try_send(progress.resumed_at(written, total));
+                }
+            }
+        });
+        let res = run_ffmpeg_once(&inputs, &codecs, &part, opts.stall_timeout, Attempt { number: attempts, total: Some(total.saturating_sub(written)) }, &mut part_tx, cancel).await;
+        drop(part_tx);
+        let _ = relay.await;
+
+        match res {
+            Ok(()) => break part_files(&dir, &extension).await,
+            Err(DownloadError::Cancelled) => {
+                let _ = tokio::fs::remove_dir_all(&dir).await;
+                return Err(DownloadError::Cancelled);
+            }
+            Err(e) if opts.auto_restart && e.is_retryable() && attempts < opts.max_restarts => {
+                tracing::warn!(error = %e, attempt = attempts, "Téléchargement interrompu, reprise à la prochaine tentative");
+                if sleep_unless_cancelled(Duration::from_secs(2_u64.saturating_pow(attempts as u32)), cancel).await {
+                    let _ = tokio::fs::remove_dir_all(&dir).await;
+                    return Err(DownloadError::Cancelled);
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    };
+
+    join_parts(&parts, output_path, opts, cancel).await?;
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+    Ok(())
+}
+
+/// Assemble `parts` sans réencodage vers `output_path`, via un fichier temporaire
+async fn join_parts(parts: &[PathBuf], output_path: &Path, opts: &DownloadOptions, cancel: &AtomicBool) -> Result<(), DownloadError> {
+    let tmp_path = tmp_output_path(output_path, opts.audio_output());
+    let list = parts_dir(output_path).join("parts.txt");
+    let entries: Vec<String> = parts.iter().map(|p| p.to_string_lossy().into_owned()).collect();
+    tokio::fs::write(&list, concat::list_file(&entries)).await?;
+    tracing::info!(parts = parts.len(), output = %output_path.display(), "Assemblage des parties");
+
+    let mut inputs: Vec<String> = CONCAT_INPUT_ARGS.map(String::from).to_vec();
+    inputs.extend(["-i".to_string(), list.to_string_lossy().into_owned()]);
+    // les métadonnées ne traversent pas le démultiplexeur: elles sont réécrites ici
+    let mut codecs: Vec<String> = ["-map", "0", "-c", "copy"].map(String::from).to_vec();
+    codecs.extend(opts.metadata.args());
+    // assemblage local et rapide: pas de progression affichée
+    let (mut silent, _) = mpsc::channel(1);
+    let res = run_ffmpeg_once(&inputs, &codecs, &tmp_path, opts.stall_timeout, Attempt { number: 1, total: None }, &mut silent, cancel).await;
+    match res {
+        Ok(()) => Ok(tokio::fs::rename(&tmp_path, output_path).await?),
+        Err(e) => {
+            discard_tmp(&tmp_path).await;
+            if matches!(e, DownloadError::Cancelled) {
+                let _ = tokio::fs::remove_dir_all(parts_dir(output_path)).await;
+            }
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_parts_layout_and_resume_args() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("Episode 01.mp4");
+        let parts = parts_dir(&output);
+        assert_eq!(parts, dir.path().join("Episode 01.mp4.parts"));
+        std::fs::create_dir_all(&parts).unwrap();
+        for name in ["part-001.mp4", "part-000.mp4", "parts.txt", "part-x.mp4", "part-002.mkv"] {
+            std::fs::write(parts.join(name), b"").unwrap();
+        }
+        assert_eq!(part_files(&parts, "mp4").await, [part_path(&parts, 0, "mp4"), part_path(&parts, 1, "mp4")]);
+        assert!(part_files(&dir.path().join("absent"), "mp4").await.is_empty());
+
+        let inputs = with_input_options(vec!["-i".into(), "video.m3u8".into()], &seek_args(Duration::from_millis(754_250)));
+        assert_eq!(inputs, ["-ss", "754.250", "-i", "video.m3u8"]);
+        let copy = ["-c", "copy"].map(String::from);
+        assert_eq!(part_codecs(&copy, "mp4")[2..], ["-movflags", "+frag_keyframe+empty_moov+default_base_moof"]);
+        assert_eq!(part_codecs(&copy, "mkv"), copy);
+
+        // Reprise aux trois quarts: la moitié de la partie restante mène à 87,5 %
+        let mut fields = HashMap::new();
+        fields.insert("out_time_us".to_string(), "150000000".to_string());
+        let total = Duration::from_secs(1200);
+        let progress = FfmpegProgress::new(fields)
+            .with_estimate(Some(Duration::from_secs(300)), Duration::from_secs(30))
+            .resumed_at(Duration::from_secs(900), total);
+        assert_eq!(progress.out_time, Some(Duration::from_secs(1050)));
+        assert_eq!(progress.percent, Some(87.5));
+    }
+}
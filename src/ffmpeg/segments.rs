@@ -0,0 +1,278 @@
+//! Téléchargement HLS segment par segment, avec reprise.
+//!
+//! Lu directement par ffmpeg, un flux HLS interrompu repart de zéro. Avec
+//! `DownloadOptions::segmented_hls`, la playlist média est lue ici: chaque segment `.ts` /
+//! `.m4s` passe par le gestionnaire de téléchargement (reprise, nouvelles tentatives, plafonds
+//! par hôte) vers `<sortie>.hls/`, et l'état `state.json` y note les segments terminés. Une
+//! nouvelle tentative, même après redémarrage de l'application, ne télécharge que les segments
+//! manquants. Les segments sont ensuite mis bout à bout (après le segment d'initialisation
+//! fMP4 éventuel) et ffmpeg remuxe le tout vers la sortie.
+//!
+//! Les playlists chiffrées (`#EXT-X-KEY`), les directs (sans `#EXT-X-ENDLIST`) et les variantes
+//! à audio séparé ne s'y prêtent pas: `resolve_playlist` les refuse et l'appelant revient à la
+//! lecture directe par ffmpeg.
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use url::Url;
+use crate::downloader::{self, CollisionPolicy, DownloadManager, DownloadTask, RetryPolicy};
+use crate::ffmpeg::hls::{self, HlsVariant};
+use crate::ffmpeg::params::{DownloadError, FfmpegProgress};
+
+/// Segments téléchargés en parallèle
+const SEGMENT_CONCURRENCY: usize = 4;
+/// Intervalle de consultation du drapeau d'annulation
+const CANCEL_POLL: Duration = Duration::from_millis(200);
+
+/// Playlist média à la demande (VOD), URLs absolues
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaPlaylist {
+    /// URL de la playlist média (clé de l'état de reprise)
+    pub url: String,
+    /// Segment d'initialisation fMP4 (`#EXT-X-MAP`)
+    pub init: Option<String>,
+    pub segments: Vec<String>,
+    /// Somme des `#EXTINF`
+    pub duration: Duration,
+}
+
+/// Segments terminés, persistés dans `<sortie>.hls/state.json`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct SegmentState {
+    playlist: String,
+    count: usize,
+    completed: BTreeSet<usize>,
+}
+
+/// Dossier de travail des segments de `output_path`: `episode.mp4` -> `episode.mp4.hls/`
+pub fn work_dir(output_path: &Path) -> PathBuf {
+    let name = output_path.file_name().and_then(|n| n.to_str()).unwrap_or("output");
+    output_path.with_file_name(format!("{name}.hls"))
+}
+
+/// Playlist média à télécharger: celle de `variant`, sinon `input_url` elle‑même ou, pour une
+/// playlist maîtresse, sa meilleure variante
+pub async fn resolve_playlist(input_url: &str, variant: Option<&HlsVariant>, headers: &[(String, String)]) -> Result<MediaPlaylist, DownloadError> {
+    let mut url = variant.map_or(input_url, |v| v.url.as_str()).to_string();
+    let mut body = fetch_text(&url, headers).await?;
+    if variant.is_none() {
+        let base = Url::parse(&url).map_err(|e| DownloadError::Other(format!("URL invalide: {e}")))?;
+        if let Some(best) = hls::parse_master_playlist(&base, &body).into_iter().next() {
+            if best.audio_url.is_some() {
+                return Err(DownloadError::Other("variante à audio séparé".into()));
+            }
+            url = best.url;
+            body = fetch_text(&url, headers).await?;
+        }
+    } else if variant.is_some_and(|v| v.audio_url.is_some()) {
+        return Err(DownloadError::Other("variante à audio séparé".into()));
+    }
+    let base = Url::parse(&url).map_err(|e| DownloadError::Other(format!("URL invalide: {e}")))?;
+    parse_media_playlist(&base, &body)
+}
+
+async fn fetch_text(url: &str, headers: &[(String, String)]) -> Result<String, DownloadError> {
+    let request = headers.iter().fold(reqwest::Client::new().get(url), |req, (name, value)| req.header(name, value));
+    request
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| DownloadError::Other(format!("playlist inaccessible: {e}")))?
+        .text()
+        .await
+        .map_err(|e| DownloadError::Other(format!("playlist illisible: {e}")))
+}
+
+/// Segments de la playlist média `body`; refuse les playlists chiffrées, sans fin ou vides
+pub fn parse_media_playlist(base: &Url, body: &str) -> Result<MediaPlaylist, DownloadError> {
+    let unsupported = |why: &str| Err(DownloadError::Other(format!("playlist non prise en charge: {why}")));
+    let resolve = |uri: &str| base.join(uri).map(String::from).map_err(|e| DownloadError::Other(format!("URI de segment invalide: {e}")));
+    let mut playlist = MediaPlaylist { url: base.to_string(), init: None, segments: Vec::new(), duration: Duration::ZERO };
+    let mut ended = false;
+    for line in body.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(attrs) = line.strip_prefix("#EXT-X-KEY:") {
+            if !attrs.contains("METHOD=NONE") {
+                return unsupported("segments chiffrés");
+            }
+        } else if let Some(attrs) = line.strip_prefix("#EXT-X-MAP:") {
+            let uri = attrs.split("URI=\"").nth(1).and_then(|rest| rest.split('"').next());
+            playlist.init = uri.map(resolve).transpose()?;
+        } else if let Some(info) = line.strip_prefix("#EXTINF:") {
+            let secs: f64 = info.split(',').next().and_then(|d| d.trim().parse().ok()).unwrap_or(0.0);
+            playlist.duration += Duration::from_secs_f64(secs.max(0.0));
+        } else if line == "#EXT-X-ENDLIST" {
+            ended = true;
+        } else if !line.starts_with('#') {
+            playlist.segments.push(resolve(line)?);
+        }
+    }
+    if !ended {
+        return unsupported("direct (pas de #EXT-X-ENDLIST)");
+    }
+    if playlist.segments.is_empty() {
+        return unsupported("aucun segment");
+    }
+    Ok(playlist)
+}
+
+impl SegmentState {
+    fn path(dir: &Path) -> PathBuf {
+        dir.join("state.json")
+    }
+
+    /// État enregistré pour `playlist`, ou un état vierge si la playlist a changé
+    fn load(dir: &Path, playlist: &MediaPlaylist) -> Self {
+        let saved = std::fs::read(Self::path(dir)).ok().and_then(|b| serde_json::from_slice::<Self>(&b).ok());
+        match saved {
+            Some(state) if state.playlist == playlist.url && state.count == playlist.segments.len() => state,
+            _ => Self { playlist: playlist.url.clone(), count: playlist.segments.len(), completed: BTreeSet::new() },
+        }
+    }
+
+    fn save(&self, dir: &Path) -> std::io::Result<()> {
+        std::fs::write(Self::path(dir), serde_json::to_vec(self)?)
+    }
+
+    /// Segments encore à télécharger (non notés, ou dont le fichier a disparu)
+    fn pending(&self, dir: &Path, playlist: &MediaPlaylist) -> Vec<usize> {
+        (0..self.count)
+            .filter(|i| !self.completed.contains(i) || !segment_path(dir, playlist, *i).exists())
+            .collect()
+    }
+}
+
+fn extension_of(url: &str) -> &str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit_once('.').map(|(_, ext)| ext).filter(|ext| !ext.contains('/') && ext.len() <= 4).unwrap_or("ts")
+}
+
+fn segment_path(dir: &Path, playlist: &MediaPlaylist, index: usize) -> PathBuf {
+    dir.join(format!("seg-{index:05}.{}", extension_of(&playlist.segments[index])))
+}
+
+/// Télécharge les segments manquants de `playlist` dans `work_dir(output_path)` puis les met
+/// bout à bout; rend le fichier joint, à remuxer par ffmpeg. Une annulation laisse les segments
+/// terminés en place pour la reprise.
+pub async fn fetch_segments(
+    playlist: &MediaPlaylist,
+    output_path: &Path,
+    headers: &[(String, String)],
+    progress_tx: &mut mpsc::Sender<FfmpegProgress>,
+    cancel: &AtomicBool,
+) -> Result<PathBuf, DownloadError> {
+    let dir = work_dir(output_path);
+    tokio::fs::create_dir_all(&dir).await?;
+    let mut state = SegmentState::load(&dir, playlist);
+    let pending = state.pending(&dir, playlist);
+    tracing::info!(playlist = %playlist.url, total = state.count, pending = pending.len(), "Téléchargement des segments HLS");
+
+    let manager = DownloadManager::new()
+        .with_collision_policy(CollisionPolicy::Overwrite)
+        .with_retry_policy(RetryPolicy::from_config(&downloader::load_config()));
+    let init_path = dir.join(format!("init.{}", playlist.init.as_deref().map_or("mp4", extension_of)));
+    if let Some(init) = &playlist.init
+        && !init_path.exists()
+    {
+        fetch_one(&manager, init, &init_path, headers).await?;
+    }
+
+    let started = Instant::now();
+    let already = state.count - pending.len();
+    let mut downloads = futures::stream::iter(pending)
+        .map(|index| {
+            let manager = &manager;
+            let path = segment_path(&dir, playlist, index);
+            async move { fetch_one(manager, &playlist.segments[index], &path, headers).await.map(|()| index) }
+        })
+        .buffer_unordered(SEGMENT_CONCURRENCY);
+    loop {
+        let next = tokio::select! {
+            next = downloads.next() => next,
+            _ = tokio::time::sleep(CANCEL_POLL) => {
+                if cancel.load(Ordering::Relaxed) {
+                    tracing::info!(done = state.completed.len(), "Segments HLS annulés, reprise possible");
+                    return Err(DownloadError::Cancelled);
+                }
+                continue;
+            }
+        };
+        let Some(index) = next.transpose()? else { break };
+        state.completed.insert(index);
+        state.save(&dir)?;
+        let _ = progress_tx.try_send(segment_progress(state.completed.len(), state.count, already, started.elapsed()));
+    }
+    drop(downloads);
+
+    let joined = dir.join(format!("joined.{}", if playlist.init.is_some() { "mp4" } else { extension_of(&playlist.segments[0]) }));
+    let mut out = tokio::fs::File::create(&joined).await?;
+    let parts = playlist.init.as_ref().map(|_| init_path.clone()).into_iter().chain((0..state.count).map(|i| segment_path(&dir, playlist, i)));
+    for part in parts {
+        let mut segment = tokio::fs::File::open(&part).await?;
+        tokio::io::copy(&mut segment, &mut out).await?;
+    }
+    out.flush().await?;
+    Ok(joined)
+}
+
+async fn fetch_one(manager: &DownloadManager, url: &str, path: &Path, headers: &[(String, String)]) -> Result<(), DownloadError> {
+    let builder = DownloadTask::builder(url.to_string()).with_output(path.to_owned());
+    let task = headers
+        .iter()
+        .fold(builder, |builder, (name, value)| builder.with_header(name.clone(), value.clone()))
+        .build()
+        .map_err(|e| DownloadError::Other(e.to_string()))?;
+    manager.start(task).await.map(|_| ()).map_err(|e| DownloadError::Other(format!("segment {url}: {e:#}")))
+}
+
+/// Avancement exprimé en segments; le temps restant ne compte que ceux téléchargés cette fois
+fn segment_progress(done: usize, total: usize, already: usize, elapsed: Duration) -> FfmpegProgress {
+    let mut fields = std::collections::HashMap::new();
+    fields.insert("segments".to_string(), format!("{done}/{total}"));
+    fields.insert("progress".to_string(), if done == total { "end" } else { "continue" }.to_string());
+    let mut progress = FfmpegProgress::new(fields);
+    progress.percent = Some(done as f64 * 100.0 / total.max(1) as f64);
+    let fetched = done.saturating_sub(already);
+    if fetched > 0 {
+        progress.eta = Some(elapsed.mul_f64((total - done) as f64 / fetched as f64));
+    }
+    progress
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_media_playlist_and_resume_state() {
+        let base = Url::parse("https://cdn.example.com/show/720p/index.m3u8").unwrap();
+        let body = "#EXTM3U\n#EXT-X-TARGETDURATION:6\n#EXT-X-MAP:URI=\"init.mp4\"\n\
+            #EXTINF:6.0,\nseg0.m4s?t=1\n#EXTINF:6.0,\nseg1.m4s\n#EXTINF:3.5,\n/abs/seg2.m4s\n#EXT-X-ENDLIST\n";
+        let playlist = parse_media_playlist(&base, body).unwrap();
+        assert_eq!(playlist.init.as_deref(), Some("https://cdn.example.com/show/720p/init.mp4"));
+        assert_eq!(playlist.segments[2], "https://cdn.example.com/abs/seg2.m4s");
+        assert_eq!(playlist.duration, Duration::from_millis(15_500));
+        assert!(parse_media_playlist(&base, "#EXTM3U\n#EXTINF:6.0,\nseg0.ts\n").is_err());
+        assert!(parse_media_playlist(&base, "#EXT-X-KEY:METHOD=AES-128,URI=\"k\"\n#EXTINF:6,\ns.ts\n#EXT-X-ENDLIST").is_err());
+
+        // Reprise: seuls les segments non notés ou disparus restent à télécharger
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = SegmentState::load(dir.path(), &playlist);
+        assert_eq!(state.pending(dir.path(), &playlist), vec![0, 1, 2]);
+        std::fs::write(segment_path(dir.path(), &playlist, 0), b"seg0").unwrap();
+        state.completed.extend([0, 1]);
+        state.save(dir.path()).unwrap();
+        let reloaded = SegmentState::load(dir.path(), &playlist);
+        assert_eq!(reloaded.pending(dir.path(), &playlist), vec![1, 2]);
+        let other = MediaPlaylist { url: "https://cdn.example.com/other.m3u8".into(), ..playlist.clone() };
+        assert!(SegmentState::load(dir.path(), &other).completed.is_empty());
+
+        let progress = segment_progress(3, 4, 1, Duration::from_secs(10));
+        assert_eq!((progress.percent, progress.eta), (Some(75.0), Some(Duration::from_secs(5))));
+        assert_eq!(work_dir(Path::new("/tmp/episode.mp4")), PathBuf::from("/tmp/episode.mp4.hls"));
+    }
+}
@@ -0,0 +1,135 @@
+//! Analyse de la sortie d'erreur de ffmpeg.
+//!
+//! `run_ffmpeg_once` conserve les dernières lignes de stderr (`StderrTail`); quand ffmpeg échoue,
+//! `classify` y cherche les causes courantes (refus HTTP, données invalides, chiffrement/DRM,
+//! codec non pris en charge) pour rendre une `DownloadError` exploitable plutôt qu'un simple
+//! code de sortie. Les lignes restent attachées à l'erreur pour l'affichage dans la GUI.
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use crate::ffmpeg::params::DownloadError;
+
+/// Nombre de lignes de stderr conservées
+pub const STDERR_TAIL_LINES: usize = 20;
+
+/// Dernières lignes de stderr d'un processus ffmpeg, partagées avec la tâche qui les lit
+#[derive(Debug, Clone, Default)]
+pub struct StderrTail {
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl StderrTail {
+    /// Ajoute une ligne, en oubliant la plus ancienne au‑delà de `STDERR_TAIL_LINES`
+    pub fn push(&self, line: String) {
+        let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        if lines.len() == STDERR_TAIL_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    /// Copie des lignes conservées, de la plus ancienne à la plus récente
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+    }
+}
+
+/// Erreur correspondant à l'échec de ffmpeg (statut `code`) d'après les lignes de `stderr`
+pub fn classify(code: i32, stderr: Vec<String>) -> DownloadError {
+    if let Some(status) = stderr.iter().find_map(|line| http_status(line)) {
+        return DownloadError::HttpStatus { status, stderr };
+    }
+    if stderr.iter().any(|line| is_encryption_error(line)) {
+        return DownloadError::Encrypted { stderr };
+    }
+    if let Some(codec) = stderr.iter().find_map(|line| unsupported_codec(line)) {
+        return DownloadError::UnsupportedCodec { codec, stderr };
+    }
+    if stderr.iter().any(|line| line.contains("Invalid data found when processing input")) {
+        return DownloadError::InvalidData { stderr };
+    }
+    DownloadError::FfmpegFailed { code, stderr }
+}
+
+/// Statut de `Server returned 403 Forbidden (access denied)` ou `HTTP error 404 Not Found`
+fn http_status(line: &str) -> Option<u16> {
+    let rest = ["Server returned ", "HTTP error "]
+        .iter()
+        .find_map(|marker| line.split_once(marker).map(|(_, rest)| rest))?;
+    // `Server returned 4XX Client Error, but not one of 40{0,1,3,4}`: statut exact inconnu
+    match rest.get(..3)? {
+        "4XX" => Some(400),
+        "5XX" => Some(500),
+        digits => digits.parse().ok().filter(|status| (400..600).contains(status)),
+    }
+}
+
+fn is_encryption_error(line: &str) -> bool {
+    let line = line.to_ascii_lowercase();
+    ["encrypt", "decrypt", "drm protected", "drm-protected", "widevine", "playready", "unable to open key file"]
+        .iter()
+        .any(|marker| line.contains(marker))
+}
+
+/// Codec cité par `Decoder (codec xyz) not found`, `Unknown encoder 'xyz'` ou
+/// `Could not find tag for codec xyz in stream`
+fn unsupported_codec(line: &str) -> Option<String> {
+    let word = |rest: &str| rest.split(|c: char| c.is_whitespace() || c == ')' || c == '\'').next().map(str::to_string);
+    if let Some((_, rest)) = line.split_once("(codec ").filter(|_| line.contains("not found")) {
+        return word(rest);
+    }
+    if let Some((_, rest)) = line.split_once("Unknown encoder '") {
+        return word(rest);
+    }
+    if let Some((_, rest)) = line.split_once("Could not find tag for codec ") {
+        return word(rest);
+    }
+    if line.contains("codec not currently supported in container") || line.contains("Unsupported codec") {
+        return Some("inconnu".into());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_classify_common_failures() {
+        let forbidden = lines("[https @ 0x55] HTTP error 403 Forbidden\nhttps://cdn/x.m3u8: Server returned 403 Forbidden (access denied)");
+        assert!(matches!(classify(1, forbidden), DownloadError::HttpStatus { status: 403, .. }));
+        let client = lines("Server returned 4XX Client Error, but not one of 40{0,1,3,4}");
+        assert!(matches!(classify(1, client), DownloadError::HttpStatus { status: 400, .. }));
+
+        let drm = lines("[hls @ 0x1] SAMPLE-AES encryption is not supported yet\nInvalid data found when processing input");
+        assert!(matches!(classify(1, drm), DownloadError::Encrypted { .. }));
+        let codec = lines("[mp4 @ 0x2] Could not find tag for codec pcm_s16le in stream #1, codec not currently supported in container");
+        assert!(matches!(classify(1, codec), DownloadError::UnsupportedCodec { codec, .. } if codec == "pcm_s16le"));
+        let decoder = lines("Decoder (codec av1) not found for input stream #0:0");
+        assert!(matches!(classify(1, decoder), DownloadError::UnsupportedCodec { codec, .. } if codec == "av1"));
+        assert!(matches!(classify(1, lines("x.ts: Invalid data found when processing input")), DownloadError::InvalidData { .. }));
+
+        match classify(8, lines("Conversion failed!")) {
+            DownloadError::FfmpegFailed { code: 8, stderr } => assert_eq!(stderr, ["Conversion failed!"]),
+            other => panic!("échec non classé attendu, obtenu {other:?}"),
+        }
+
+        // Nouvelle tentative: blocage et erreurs du serveur, pas un lien refusé ni un codec
+        assert!(DownloadError::Stalled(Duration::from_secs(20)).is_retryable());
+        assert!(classify(1, lines("Server returned 503 Service Unavailable")).is_retryable());
+        assert!(classify(1, lines("Connection reset by peer")).is_retryable());
+        assert!(!classify(1, lines("HTTP error 404 Not Found")).is_retryable());
+        assert!(!classify(1, lines("Decoder (codec av1) not found for input stream #0:0")).is_retryable());
+        assert!(!DownloadError::Cancelled.is_retryable());
+
+        let tail = StderrTail::default();
+        (0..STDERR_TAIL_LINES + 5).for_each(|i| tail.push(i.to_string()));
+        let kept = tail.lines();
+        assert_eq!(kept.len(), STDERR_TAIL_LINES);
+        assert_eq!(kept.first().map(String::as_str), Some("5"));
+    }
+}
@@ -0,0 +1,85 @@
+//! Pistes de sous‑titres d'un téléchargement ffmpeg.
+//!
+//! Avec `-c copy` vers mp4, les sous‑titres WebVTT d'un flux HLS sont perdus (ou font échouer
+//! le mux): le conteneur n'accepte que `mov_text`. `SubtitleMode::Copy` sélectionne toutes les
+//! pistes de l'entrée et les convertit, `Drop` les écarte explicitement. Des fichiers externes
+//! (`.vtt`, `.srt`) peuvent en plus être téléchargés et intégrés comme pistes supplémentaires,
+//! avec leur langue.
+
+/// Traitement des pistes de sous‑titres intégrées à l'entrée
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubtitleMode {
+    /// Écarter les sous‑titres (`-sn`)
+    #[default]
+    Drop,
+    /// Les garder, convertis en `mov_text` pour le mp4
+    Copy,
+}
+
+/// Fichier de sous‑titres externe à intégrer à la sortie
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalSubtitle {
+    /// URL ou chemin du `.vtt` / `.srt`
+    pub url: String,
+    /// Code de langue ISO 639 (`fre`, `eng`...) inscrit dans la piste
+    pub language: Option<String>,
+}
+
+impl ExternalSubtitle {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into(), language: None }
+    }
+}
+
+/// Entrées supplémentaires et arguments de sortie pour les sous‑titres.
+///
+/// `input_count` entrées précèdent déjà celles des fichiers externes; `explicit_maps` indique
+/// qu'elles ont fixé leurs propres `-map` (variante HLS à audio séparé). Dès qu'une piste de
+/// sous‑titres est sélectionnée, la vidéo et l'audio de la première entrée le sont aussi: un seul
+/// `-map` désactive la sélection automatique de ffmpeg. Les fichiers externes passent avant les
+/// pistes intégrées pour que leurs index de sortie (et donc leur langue) soient connus.
+pub fn mux_args(mode: SubtitleMode, externals: &[ExternalSubtitle], input_count: usize, explicit_maps: bool) -> (Vec<String>, Vec<String>) {
+    if mode == SubtitleMode::Drop && externals.is_empty() {
+        return (Vec::new(), vec!["-sn".to_string()]);
+    }
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    if !explicit_maps {
+        outputs.extend(["-map", "0:v?", "-map", "0:a?"].map(String::from));
+    }
+    for (i, subtitle) in externals.iter().enumerate() {
+        inputs.extend(["-i".to_string(), subtitle.url.clone()]);
+        outputs.extend(["-map".to_string(), format!("{}:s", input_count + i)]);
+        if let Some(language) = &subtitle.language {
+            outputs.extend([format!("-metadata:s:s:{i}"), format!("language={language}")]);
+        }
+    }
+    if mode == SubtitleMode::Copy {
+        outputs.extend(["-map", "0:s?"].map(String::from));
+    }
+    outputs.extend(["-c:s", "mov_text"].map(String::from));
+    (inputs, outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mux_args_for_each_mode() {
+        assert_eq!(mux_args(SubtitleMode::Drop, &[], 1, false), (vec![], vec!["-sn".to_string()]));
+
+        let (inputs, outputs) = mux_args(SubtitleMode::Copy, &[], 1, false);
+        assert!(inputs.is_empty());
+        assert_eq!(outputs, vec!["-map", "0:v?", "-map", "0:a?", "-map", "0:s?", "-c:s", "mov_text"]);
+
+        // Variante à audio séparé (deux entrées, maps déjà posés) + deux fichiers externes
+        let externals = [
+            ExternalSubtitle { url: "https://cdn.example.com/fr.vtt".into(), language: Some("fre".into()) },
+            ExternalSubtitle::new("en.srt"),
+        ];
+        let (inputs, outputs) = mux_args(SubtitleMode::Drop, &externals, 2, true);
+        assert_eq!(inputs, vec!["-i", "https://cdn.example.com/fr.vtt", "-i", "en.srt"]);
+        assert_eq!(outputs, vec!["-map", "2:s", "-metadata:s:s:0", "language=fre", "-map", "3:s", "-c:s", "mov_text"]);
+    }
+}
@@ -0,0 +1,147 @@
+//! Miniatures d'une vidéo téléchargée.
+//!
+//! `generate_thumbnails` extrait avec ffmpeg une image d'affiche (`episode.poster.png`, prise à
+//! 10 % de la durée pour éviter les génériques noirs) et une bande d'aperçu de quelques images
+//! réparties sur toute la vidéo (`episode.preview.png`), à côté du fichier. L'historique de la
+//! GUI les affiche pour les téléchargements terminés. La bande demande la durée (ffprobe): sans
+//! elle, seule l'affiche est produite.
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use crate::ffmpeg::binary;
+use crate::ffmpeg::downloader::probe_duration;
+use crate::ffmpeg::params::DownloadError;
+
+/// Position de l'affiche quand la durée est inconnue
+const FALLBACK_POSTER_AT: Duration = Duration::from_secs(5);
+/// Délai accordé à ffprobe pour un fichier local
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Extensions traitées comme vidéo
+const VIDEO_EXTENSIONS: [&str; 8] = ["mp4", "mkv", "webm", "mov", "avi", "m4v", "ts", "flv"];
+
+/// Dimensions des miniatures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThumbnailOptions {
+    /// Largeur de l'affiche en pixels (hauteur proportionnelle)
+    pub poster_width: u32,
+    /// Nombre d'images de la bande d'aperçu (0 = pas de bande)
+    pub preview_frames: u32,
+    /// Largeur de chaque image de la bande
+    pub preview_width: u32,
+}
+
+impl Default for ThumbnailOptions {
+    fn default() -> Self {
+        Self { poster_width: 480, preview_frames: 6, preview_width: 160 }
+    }
+}
+
+/// Fichiers produits par `generate_thumbnails`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnails {
+    pub poster: PathBuf,
+    pub preview: Option<PathBuf>,
+}
+
+/// `episode.mp4` -> `episode.poster.png`
+pub fn poster_path(video: &Path) -> PathBuf {
+    video.with_extension("poster.png")
+}
+
+/// `episode.mp4` -> `episode.preview.png`
+pub fn preview_path(video: &Path) -> PathBuf {
+    video.with_extension("preview.png")
+}
+
+/// `true` si l'extension de `path` est celle d'une vidéo
+pub fn is_video_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| VIDEO_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Génère l'affiche et, si la durée est connue, la bande d'aperçu de `video`
+pub async fn generate_thumbnails(video: &Path, options: &ThumbnailOptions) -> Result<Thumbnails, DownloadError> {
+    let duration = probe_duration(&video.to_string_lossy(), PROBE_TIMEOUT, &[]).await;
+    let poster = poster_path(video);
+    let poster_at = duration.map_or(FALLBACK_POSTER_AT, |d| d.mul_f64(0.1));
+    run_ffmpeg(poster_args(video, &poster, poster_at, options.poster_width)).await?;
+
+    let preview = match duration.filter(|d| !d.is_zero() && options.preview_frames > 0) {
+        Some(duration) => {
+            let preview = preview_path(video);
+            run_ffmpeg(preview_args(video, &preview, duration, options.preview_frames, options.preview_width)).await?;
+            Some(preview)
+        }
+        None => None,
+    };
+    tracing::info!(video = %video.display(), preview = preview.is_some(), "Miniatures générées");
+    Ok(Thumbnails { poster, preview })
+}
+
+/// Variante non bloquante pour la fin d'un téléchargement: un échec est seulement journalisé
+pub async fn generate_after_download(video: &Path) {
+    if let Err(e) = generate_thumbnails(video, &ThumbnailOptions::default()).await {
+        tracing::warn!(video = %video.display(), error = %e, "Miniatures non générées");
+    }
+}
+
+async fn run_ffmpeg(args: Vec<String>) -> Result<(), DownloadError> {
+    let status = Command::new(binary::ffmpeg()?)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .status()
+        .await?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(DownloadError::FfmpegExit(status.code().unwrap_or(-1))),
+    }
+}
+
+/// Une image à `at` (recherche avant `-i`, rapide), mise à l'échelle
+fn poster_args(video: &Path, poster: &Path, at: Duration, width: u32) -> Vec<String> {
+    vec![
+        "-y".into(), "-v".into(), "error".into(),
+        "-ss".into(), format!("{:.3}", at.as_secs_f64()),
+        "-i".into(), video.to_string_lossy().into_owned(),
+        "-frames:v".into(), "1".into(),
+        "-vf".into(), format!("scale={width}:-2"),
+        poster.to_string_lossy().into_owned(),
+    ]
+}
+
+/// `frames` images réparties sur `duration`, juxtaposées sur une ligne (`tile`)
+fn preview_args(video: &Path, preview: &Path, duration: Duration, frames: u32, width: u32) -> Vec<String> {
+    let fps = frames as f64 / duration.as_secs_f64();
+    vec![
+        "-y".into(), "-v".into(), "error".into(),
+        "-i".into(), video.to_string_lossy().into_owned(),
+        "-vf".into(), format!("fps={fps:.6},scale={width}:-2,tile={frames}x1"),
+        "-frames:v".into(), "1".into(),
+        preview.to_string_lossy().into_owned(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_paths_and_args() {
+        let video = Path::new("/tmp/Episode 01.mp4");
+        assert_eq!(poster_path(video), PathBuf::from("/tmp/Episode 01.poster.png"));
+        assert_eq!(preview_path(video), PathBuf::from("/tmp/Episode 01.preview.png"));
+        assert!(is_video_path(video) && is_video_path(Path::new("a.MKV")));
+        assert!(!is_video_path(Path::new("a.zip")) && !is_video_path(Path::new("README")));
+
+        let poster = poster_args(video, &poster_path(video), Duration::from_millis(144_500), 480);
+        assert_eq!(poster[3..5], ["-ss", "144.500"]);
+        assert_eq!(poster[9..11], ["-vf", "scale=480:-2"]);
+        let preview = preview_args(video, &preview_path(video), Duration::from_secs(1200), 6, 160);
+        assert_eq!(preview[6], "fps=0.005000,scale=160:-2,tile=6x1");
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use serde::Deserialize;
+
+/// Description d'un média telle que rapportée par ffprobe
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MediaInfo {
+    /// Durée totale; `None` pour un direct
+    pub duration: Option<Duration>,
+    /// Débit global, en bit/s
+    pub bitrate: Option<u64>,
+    /// Conteneur(s) reconnus par ffprobe (`hls`, `mov,mp4,m4a,3gp,3g2,mj2`...)
+    pub format_name: Option<String>,
+    pub streams: Vec<StreamInfo>,
+}
+
+/// Nature d'une piste
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamKind {
+    Video,
+    Audio,
+    Subtitle,
+    Data,
+    Other,
+}
+
+/// Une piste du média
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamInfo {
+    /// Index ffmpeg (`-map 0:<index>`)
+    pub index: u32,
+    pub kind: StreamKind,
+    /// Codec court (`h264`, `aac`, `webvtt`...)
+    pub codec: Option<String>,
+    /// Largeur × hauteur (vidéo)
+    pub resolution: Option<(u32, u32)>,
+    /// Débit de la piste, en bit/s, s'il est annoncé
+    pub bitrate: Option<u64>,
+    /// Canaux (audio)
+    pub channels: Option<u32>,
+    /// Langue (`tags.language`)
+    pub language: Option<String>,
+}
+
+impl MediaInfo {
+    /// Lit la sortie de `ffprobe -print_format json -show_format -show_streams`
+    pub fn from_json(json: &[u8]) -> Result<Self, serde_json::Error> {
+        let raw: RawOutput = serde_json::from_slice(json)?;
+        let format = raw.format.unwrap_or_default();
+        Ok(Self {
+            duration: format.duration.as_deref().and_then(parse_seconds),
+            bitrate: format.bit_rate.and_then(|b| b.parse().ok()),
+            format_name: format.format_name,
+            streams: raw.streams.into_iter().map(StreamInfo::from).collect(),
+        })
+    }
+
+    /// Pistes d'une nature donnée, dans l'ordre du média
+    pub fn streams_of(&self, kind: StreamKind) -> impl Iterator<Item = &StreamInfo> {
+        self.streams.iter().filter(move |s| s.kind == kind)
+    }
+
+    /// Résolution de la plus grande piste vidéo
+    pub fn resolution(&self) -> Option<(u32, u32)> {
+        self.streams_of(StreamKind::Video).filter_map(|s| s.resolution).max_by_key(|(w, h)| w * h)
+    }
+}
+
+/// Secondes écrites par ffprobe (`1425.120000`); `None` pour `N/A` ou une durée nulle
+fn parse_seconds(value: &str) -> Option<Duration> {
+    let secs: f64 = value.trim().parse().ok()?;
+    (secs.is_finite() && secs > 0.0).then(|| Duration::from_secs_f64(secs))
+}
+
+impl From<RawStream> for StreamInfo {
+    fn from(raw: RawStream) -> Self {
+        let kind = match raw.codec_type.as_deref() {
+            Some("video") => StreamKind::Video,
+            Some("audio") => StreamKind::Audio,
+            Some("subtitle") => StreamKind::Subtitle,
+            Some("data") => StreamKind::Data,
+            _ => StreamKind::Other,
+        };
+        Self {
+            index: raw.index,
+            kind,
+            codec: raw.codec_name,
+            resolution: raw.width.zip(raw.height).filter(|&(w, h)| w > 0 && h > 0),
+            bitrate: raw.bit_rate.and_then(|b| b.parse().ok()),
+            channels: raw.channels,
+            language: raw.tags.get("language").cloned(),
+        }
+    }
+}
+
+/// Sortie JSON brute de ffprobe (les nombres à virgule et débits y sont des chaînes)
+#[derive(Deserialize)]
+struct RawOutput {
+    #[serde(default)]
+    streams: Vec<RawStream>,
+    format: Option<RawFormat>,
+}
+
+#[derive(Deserialize, Default)]
+struct RawFormat {
+    format_name: Option<String>,
+    duration: Option<String>,
+    bit_rate: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawStream {
+    #[serde(default)]
+    index: u32,
+    codec_type: Option<String>,
+    codec_name: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    bit_rate: Option<String>,
+    channels: Option<u32>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_media_info_from_ffprobe_json() {
+        let json = br#"{
+            "streams": [
+                {"index": 0, "codec_name": "h264", "codec_type": "video", "width": 1920, "height": 1080},
+                {"index": 1, "codec_name": "aac", "codec_type": "audio", "channels": 2, "bit_rate": "128000", "tags": {"language": "fre"}},
+                {"index": 2, "codec_name": "webvtt", "codec_type": "subtitle"}
+            ],
+            "format": {"format_name": "hls", "duration": "1425.120000", "bit_rate": "5128000"}
+        }"#;
+        let info = MediaInfo::from_json(json).unwrap();
+
+        assert_eq!(info.duration, Some(Duration::from_millis(1_425_120)));
+        assert_eq!(info.bitrate, Some(5_128_000));
+        assert_eq!(info.format_name.as_deref(), Some("hls"));
+        assert_eq!(info.resolution(), Some((1920, 1080)));
+        let audio: Vec<_> = info.streams_of(StreamKind::Audio).collect();
+        assert_eq!((audio[0].index, audio[0].channels, audio[0].language.as_deref()), (1, Some(2), Some("fre")));
+        assert_eq!(info.streams_of(StreamKind::Subtitle).count(), 1);
+
+        // Direct: durée `N/A`, pas de section format du tout pour certains flux
+        let live = MediaInfo::from_json(br#"{"format": {"duration": "N/A"}}"#).unwrap();
+        assert_eq!((live.duration, live.streams.len()), (None, 0));
+        assert_eq!(MediaInfo::from_json(b"{}").unwrap(), MediaInfo::default());
+    }
+}
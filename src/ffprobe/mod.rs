@@ -0,0 +1,58 @@
+//! Inspection d'un média avec `ffprobe`.
+//!
+//! `probe` lance `ffprobe -print_format json -show_format -show_streams` et rend un
+//! `MediaInfo` typé (durée, débit, pistes, codecs, résolution): la GUI l'affiche avant un
+//! téléchargement et le module ffmpeg s'en sert pour exprimer la progression en pourcentage.
+pub mod info;
+
+pub use info::{MediaInfo, StreamInfo, StreamKind};
+
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use crate::ffmpeg::binary::{self, Tool};
+
+/// Délai par défaut accordé à ffprobe (lecture des en‑têtes d'un flux distant)
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(thiserror::Error, Debug)]
+pub enum ProbeError {
+    #[error("impossible de lancer ffprobe: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("ffprobe a échoué ({code:?}): {message}")]
+    Exit { code: Option<i32>, message: String },
+    #[error("sortie ffprobe illisible: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("ffprobe n'a pas répondu à temps")]
+    Timeout,
+    #[error("{0}")]
+    MissingBinary(String),
+}
+
+/// Inspecte `input_url` (fichier local ou URL) avec le délai par défaut; `input_args`
+/// (`-headers`, `-user_agent`...) sont placés devant l'URL
+pub async fn probe(input_url: &str, input_args: &[String]) -> Result<MediaInfo, ProbeError> {
+    probe_with_timeout(input_url, DEFAULT_PROBE_TIMEOUT, input_args).await
+}
+
+/// Inspecte `input_url`; ffprobe est tué s'il ne répond pas avant `timeout`
+pub async fn probe_with_timeout(input_url: &str, timeout: Duration, input_args: &[String]) -> Result<MediaInfo, ProbeError> {
+    let ffprobe = binary::locate(Tool::Ffprobe).ok_or_else(|| ProbeError::MissingBinary(binary::missing_message(Tool::Ffprobe)))?;
+    let output = Command::new(ffprobe)
+        .args(["-v", "error", "-print_format", "json", "-show_format", "-show_streams"])
+        .args(input_args)
+        .arg(input_url)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(timeout, output).await.map_err(|_| ProbeError::Timeout)??;
+    if !output.status.success() {
+        return Err(ProbeError::Exit {
+            code: output.status.code(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    let info = MediaInfo::from_json(&output.stdout)?;
+    tracing::debug!(url = input_url, duration = ?info.duration, streams = info.streams.len(), "Média inspecté par ffprobe");
+    Ok(info)
+}
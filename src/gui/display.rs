@@ -0,0 +1,40 @@
+//! Aides d'affichage partagées entre les onglets (troncature des URLs longues).
+
+use egui::{Color32, Response, RichText, Ui};
+
+/// Nombre maximal de caractères affichés pour une URL avant troncature
+pub const MAX_URL_DISPLAY_CHARS: usize = 80;
+
+/// Tronque `text` à `max_chars` caractères (et non octets) en ajoutant `…`.
+pub fn truncate_for_display(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Affiche une URL tronquée en petit; l'URL complète apparaît au survol si elle a été coupée.
+pub fn url_label(ui: &mut Ui, url: &str, color: Color32) -> Response {
+    let display = truncate_for_display(url, MAX_URL_DISPLAY_CHARS);
+    let truncated = display.len() != url.len();
+    let response = ui.label(RichText::new(display).small().color(color));
+    if truncated {
+        response.on_hover_text(url)
+    } else {
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_for_display() {
+        assert_eq!(truncate_for_display("https://a.b/c", 80), "https://a.b/c");
+        assert_eq!(truncate_for_display("abcdef", 3), "abc…");
+        // Pas de coupure au milieu d'un caractère multi‑octets
+        assert_eq!(truncate_for_display("ééééé", 2), "éé…");
+        assert_eq!(truncate_for_display("abc", 3), "abc");
+    }
+}
@@ -4,16 +4,21 @@
 //! - Liste des téléchargements actifs avec progression
 //! - Formulaire pour ajouter de nouveaux téléchargements
 //! - Statistiques globales
+//! - Affiche et bande d'aperçu des vidéos terminées (étape `thumbnails` du post‑traitement)
 
-use egui::{Ui, ProgressBar, RichText, Color32, ScrollArea, Frame, Stroke, Rounding, Context};
-use std::path::PathBuf;
+use egui::{Ui, ProgressBar, RichText, Color32, ScrollArea, Frame, Stroke, Rounding, Context, TextureHandle};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::{Mutex, mpsc};
-use std::collections::HashMap;
+use tokio::sync::{broadcast, Mutex, mpsc};
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
 use serde::{Serialize, Deserialize};
 use std::fs;
-use crate::downloader::{DownloadTask, DownloadManager};
+use std::time::{Duration, SystemTime};
+use chrono::TimeZone;
+use crate::downloader::{self, AdaptiveChunking, DownloadTask, DiskFull, DownloadManager, FileTooLarge, HttpClientOptions, DownloadQueue, DownloadStatistics, DownloadStatsSnapshot, CollisionPolicy, CookieJar, ExpectedHash, HarvestFilter, Integrity, OfflineMode, PartialCleanup, PostProcessPipeline, Manifest, Priority, ManifestSummary, DownloadErrorKind, DownloadEventKind, DownloadEvents, IncompleteDownload, estimate_eta, QueueSlot, RateLimiter, RetryPolicy, SpaceGuard, SpeedRule, SpeedSchedule, dedupe_path, fit_path_length, is_metalink_path, is_torrent_source, is_url_list_path, load_metalink, load_url_list, sanitize_filename, torrent_display_name, SwarmProgress, TorrentDownload, TorrentOutcome, TruncatedDownload};
+use super::display::url_label;
+use crate::ffmpeg::thumbnails;
 
 /// ID unique pour chaque téléchargement
 pub type DownloadId = u64;
@@ -23,14 +28,40 @@ pub type DownloadId = u64;
 pub struct DownloadItem {
     pub id: DownloadId,
     pub url: String,
+    #[serde(default)]
+    pub mirrors: Vec<String>, // URLs supplémentaires servant le même fichier
+    #[serde(default)]
+    pub hashes: Vec<ExpectedHash>, // Empreintes attendues (Metalink), vérifiées en fin de téléchargement
+    #[serde(default)]
+    pub priority: Priority, // Rang dans la file partagée
+    #[serde(default)]
+    pub resolved_url: Option<String>, // Cible des redirections, si différente de `url`
+    #[serde(default)]
+    pub collision: CollisionPolicy, // Sort d'une sortie déjà présente, choisi à l'ajout
+    #[serde(default)]
+    pub integrity: Option<Integrity>, // Contrôle par `<url>.sha256` / `<url>.md5`, s'il a eu lieu
+    #[serde(default)]
+    pub start_at: Option<SystemTime>, // Départ programmé (statut `Scheduled` jusque‑là)
+    #[serde(default)]
+    pub max_concurrency: Option<usize>, // Connexions simultanées (sinon `[chunking] max_concurrency`)
     #[serde(with = "pathbuf_serde")]
     pub output_path: PathBuf,
     pub status: DownloadStatus, // SÉRIALISÉ pour sauvegarder le statut dans le JSON
     pub progress: f32, // 0.0 à 1.0
     pub speed: Option<u64>, // bytes/s
+    #[serde(skip)]
+    pub eta: Option<Duration>, // Temps restant estimé par le gestionnaire
+    #[serde(skip)]
+    pub throttled_until: Option<std::time::Instant>, // Fin de l'attente demandée par le serveur
     pub total_size: Option<u64>, // bytes
     pub downloaded: u64, // bytes téléchargés
     pub error_message: Option<String>,
+    #[serde(default)]
+    pub completed_at: Option<SystemTime>, // Instant de fin, pour le délai avant passage en historique
+    #[serde(skip)]
+    pub swarm: Option<(u32, u32)>, // Torrents: (pairs connectés, seeders)
+    #[serde(skip)]
+    pub chunks: Option<ManifestSummary>, // État des segments lu dans le manifeste (pause/erreur)
     #[serde(skip)]
     pub cancel_flag: Arc<AtomicBool>,
     #[serde(skip)]
@@ -61,6 +92,7 @@ mod pathbuf_serde {
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", content = "value")]
 pub enum DownloadStatus {
+    Scheduled,
     Queued,
     Downloading,
     Paused,
@@ -79,6 +111,7 @@ impl Default for DownloadStatus {
 impl DownloadStatus {
     fn color(&self) -> Color32 {
         match self {
+            DownloadStatus::Scheduled => Color32::from_rgb(180, 160, 255),
             DownloadStatus::Queued => Color32::from_gray(150),
             DownloadStatus::Downloading => Color32::from_rgb(100, 200, 255),
             DownloadStatus::Paused => Color32::from_rgb(255, 200, 100),
@@ -91,6 +124,7 @@ impl DownloadStatus {
     
     fn text(&self) -> &'static str {
         match self {
+            DownloadStatus::Scheduled => "⏰ Programmé",
             DownloadStatus::Queued => "⏳ En attente",
             DownloadStatus::Downloading => "⬇️ Téléchargement",
             DownloadStatus::Paused => "⏸️ En pause",
@@ -106,12 +140,16 @@ impl DownloadStatus {
 #[derive(Clone, Debug)]
 pub enum DownloadProgress {
     Started { id: DownloadId, total_size: u64 },
-    Progress { id: DownloadId, downloaded: u64, speed: Option<u64> },
+    Progress { id: DownloadId, downloaded: u64, speed: Option<u64>, eta: Option<Duration> },
     Merging { id: DownloadId },
     Completed { id: DownloadId },
     Error { id: DownloadId, error: String },
     Paused { id: DownloadId },
     Cancelled { id: DownloadId },
+    Swarm { id: DownloadId, peers: u32, seeds: u32 }, // Torrents: pairs connectés et seeders
+    Resolved { id: DownloadId, url: String }, // URL finale après redirections
+    Throttled { id: DownloadId, wait: Duration }, // Serveur saturé: attente imposée (Retry-After)
+    Integrity { id: DownloadId, integrity: Integrity }, // Résultat du fichier d'empreinte voisin
 }
 
 impl DownloadProgress {
@@ -124,6 +162,10 @@ impl DownloadProgress {
             DownloadProgress::Error { id, .. } => *id,
             DownloadProgress::Paused { id } => *id,
             DownloadProgress::Cancelled { id } => *id,
+            DownloadProgress::Swarm { id, .. } => *id,
+            DownloadProgress::Resolved { id, .. } => *id,
+            DownloadProgress::Throttled { id, .. } => *id,
+            DownloadProgress::Integrity { id, .. } => *id,
         }
     }
 }
@@ -138,12 +180,80 @@ pub enum DownloadFilter {
     All,         // Tous
 }
 
+/// Comportement d'un téléchargement terminé dans la vue "Actifs"
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompletionBehavior {
+    MoveImmediately,        // Passage immédiat en historique
+    KeepFor(Duration),      // Reste visible pendant le délai de grâce
+    KeepUntilCleared,       // Reste jusqu'à archivage manuel
+}
+
+impl CompletionBehavior {
+    fn label(&self) -> &'static str {
+        match self {
+            CompletionBehavior::MoveImmediately => "Immédiat",
+            CompletionBehavior::KeepFor(_) => "Après quelques secondes",
+            CompletionBehavior::KeepUntilCleared => "Manuel",
+        }
+    }
+    
+    /// Indique si un téléchargement terminé à `completed_at` doit passer en historique à `now`
+    fn should_archive(&self, completed_at: SystemTime, now: SystemTime) -> bool {
+        match self {
+            CompletionBehavior::MoveImmediately => true,
+            CompletionBehavior::KeepFor(grace) => now.duration_since(completed_at).unwrap_or_default() >= *grace,
+            CompletionBehavior::KeepUntilCleared => false,
+        }
+    }
+}
+
+const COMPLETION_GRACE: Duration = Duration::from_secs(5);
+/// Heure proposée pour un départ programmé (heures creuses)
+const OFF_PEAK_HOUR: u32 = 2;
+/// Plafond des connexions simultanées choisies dans les options avancées
+const MAX_CONNECTIONS: usize = 32;
+
+/// Téléchargement à ajouter (formulaire, imports); en attente dans `collision_prompts` si sa
+/// sortie existe déjà
+struct NewDownload {
+    url: String,
+    mirrors: Vec<String>,
+    hashes: Vec<ExpectedHash>,
+    priority: Priority,
+    output: PathBuf,
+    start_at: Option<SystemTime>, // Départ programmé (statut `Scheduled` jusque‑là)
+    max_concurrency: Option<usize>, // Connexions simultanées propres (options avancées)
+}
+
+impl NewDownload {
+    fn new(url: String, output: PathBuf) -> Self {
+        Self { url, mirrors: Vec::new(), hashes: Vec::new(), priority: Priority::default(), output, start_at: None, max_concurrency: None }
+    }
+    
+    fn from_task(task: &DownloadTask) -> Self {
+        Self {
+            mirrors: task.mirrors().to_vec(),
+            hashes: task.hashes().to_vec(),
+            priority: task.priority(),
+            max_concurrency: task.max_concurrency(),
+            ..Self::new(task.url().to_string(), task.output().to_path_buf())
+        }
+    }
+}
+
 /// Onglet des téléchargements
 pub struct DownloadsTab {
     downloads: Arc<Mutex<HashMap<DownloadId, DownloadItem>>>,
     history: Arc<Mutex<HashMap<DownloadId, DownloadItem>>>, // Téléchargements terminés
     new_url: String,
+    new_mirrors: String, // Une URL de miroir par ligne
     new_path: String,
+    new_priority: Priority,
+    new_max_concurrency: Option<usize>, // Connexions simultanées (options avancées), sinon la config
+    new_scheduled: bool, // Départ programmé plutôt qu'immédiat
+    new_start_date: String, // AAAA-MM-JJ
+    new_start_time: (u32, u32), // (heure, minute), heure locale
+    schedule_error: Option<String>,
     default_download_dir: PathBuf, // Dossier par défaut pour les téléchargements
     next_id: Arc<Mutex<DownloadId>>,
     progress_rx: Option<mpsc::UnboundedReceiver<DownloadProgress>>,
@@ -152,25 +262,66 @@ pub struct DownloadsTab {
     filter: DownloadFilter,
     path_selection_rx: Option<mpsc::UnboundedReceiver<PathBuf>>, // Canal pour recevoir les sélections de chemin
     path_selection_tx: Option<mpsc::UnboundedSender<PathBuf>>, // Canal pour envoyer les sélections de chemin
+    import_selection_rx: mpsc::UnboundedReceiver<PathBuf>, // Metalink ou listes d'URLs choisis via le dialogue
+    import_selection_tx: mpsc::UnboundedSender<PathBuf>,
+    import_error: Option<String>, // Dernière erreur d'import (Metalink, liste d'URLs)
+    resolved_name_rx: mpsc::UnboundedReceiver<(String, PathBuf)>, // (URL, chemin) après résolution du nom côté serveur
+    resolved_name_tx: mpsc::UnboundedSender<(String, PathBuf)>,
+    auto_rename_conflicts: bool, // Renommer `nom (1).ext` si la sortie est déjà prise (disque ou file active)
+    collision_prompts: Vec<NewDownload>, // Ajouts en attente d'une décision sur un fichier existant
+    harvest_url: String, // Page dont on veut extraire les liens
+    harvest_extensions: String, // Extensions acceptées (ex: "mp4, mkv")
+    harvest_running: Arc<AtomicBool>,
+    harvest_results: Arc<Mutex<Vec<String>>>,
+    harvest_error: Arc<Mutex<Option<String>>>,
+    completion_behavior: CompletionBehavior,
+    offline: OfflineMode, // Hors ligne: la file se remplit mais rien ne démarre
+    rate_limiter: RateLimiter, // Limite globale partagée par tous les téléchargements
+    speed_schedule: SpeedSchedule, // Plages de débit en cours d'édition (copie de celles du limiteur)
+    queue: DownloadQueue, // Nombre maximal de téléchargements simultanés
+    statistics: DownloadStatistics, // Débit cumulé, volume du jour et échecs (téléchargeur)
+    partial_cleanup: PartialCleanup, // Bouton 🗑️: suppression, ou quarantaine avec `[cleanup] quarantine_on_error`
+    thumbnails: HashMap<PathBuf, Option<TextureHandle>>, // Miniatures chargées (None: absente ou illisible)
 }
 
 impl Default for DownloadsTab {
     fn default() -> Self {
-        let (tx, rx) = mpsc::unbounded_channel();
-        let (path_tx, path_rx) = mpsc::unbounded_channel();
-        
         // Déterminer le dossier de téléchargement par défaut
         let default_dir = std::env::var("USERPROFILE")
             .or_else(|_| std::env::var("HOME"))
             .map(|home| PathBuf::from(home).join("Downloads"))
             .unwrap_or_else(|_| PathBuf::from("."));
+        let mut tab = Self::new(default_dir, PartialCleanup::from_config(&downloader::load_config()));
+        journal_download_events();
+        
+        // Charger l'historique au démarrage
+        tab.load_history();
+        
+        tab
+    }
+}
+
+impl DownloadsTab {
+    /// Onglet vide (sans historique) téléchargeant dans `default_download_dir`
+    fn new(default_download_dir: PathBuf, partial_cleanup: PartialCleanup) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (path_tx, path_rx) = mpsc::unbounded_channel();
+        let (import_tx, import_rx) = mpsc::unbounded_channel();
+        let (resolved_tx, resolved_rx) = mpsc::unbounded_channel();
         
-        let mut tab = Self {
+        Self {
             downloads: Arc::new(Mutex::new(HashMap::new())),
             history: Arc::new(Mutex::new(HashMap::new())),
             new_url: String::new(),
+            new_mirrors: String::new(),
+            new_priority: Priority::default(),
+            new_max_concurrency: None,
+            new_scheduled: false,
+            new_start_date: String::new(),
+            new_start_time: (OFF_PEAK_HOUR, 0),
+            schedule_error: None,
             new_path: String::new(),
-            default_download_dir: default_dir,
+            default_download_dir,
             next_id: Arc::new(Mutex::new(0)),
             progress_rx: Some(rx),
             progress_tx: Some(tx),
@@ -178,50 +329,101 @@ impl Default for DownloadsTab {
             filter: DownloadFilter::Active,
             path_selection_rx: Some(path_rx),
             path_selection_tx: Some(path_tx),
-        };
-        
-        // Charger l'historique au démarrage
-        tab.load_history();
-        
-        tab
+            import_selection_rx: import_rx,
+            import_selection_tx: import_tx,
+            import_error: None,
+            resolved_name_rx: resolved_rx,
+            resolved_name_tx: resolved_tx,
+            auto_rename_conflicts: true,
+            collision_prompts: Vec::new(),
+            harvest_url: String::new(),
+            harvest_extensions: "mp4, mkv".to_string(),
+            harvest_running: Arc::new(AtomicBool::new(false)),
+            harvest_results: Arc::new(Mutex::new(Vec::new())),
+            harvest_error: Arc::new(Mutex::new(None)),
+            completion_behavior: CompletionBehavior::KeepFor(COMPLETION_GRACE),
+            offline: OfflineMode::global(),
+            rate_limiter: RateLimiter::global(),
+            speed_schedule: RateLimiter::global().schedule(),
+            queue: DownloadQueue::global(),
+            statistics: DownloadStatistics::global(),
+            partial_cleanup,
+            thumbnails: HashMap::new(),
+        }
     }
-}
-
-impl DownloadsTab {
+    
     /// Définit le contexte egui pour les mises à jour
     pub fn set_context(&mut self, ctx: Context) {
         self.ctx = Some(ctx);
     }
     
-    /// Suggère un nom de fichier basé sur l'URL
+    /// Suggère un nom de fichier basé sur l'URL, puis demande au serveur le nom réel
+    /// (`Content-Disposition`, URL après redirections) pour corriger la suggestion.
     fn suggest_filename_from_url(&mut self) {
-        if let Ok(url) = url::Url::parse(&self.new_url) {
-            // Essayer d'extraire le nom de fichier de l'URL
-            if let Some(segments) = url.path_segments() {
-                let segments: Vec<_> = segments.collect();
-                if let Some(last_segment) = segments.last() {
-                    // Nettoyer le segment (enlever les paramètres de requête)
-                    let clean_segment = last_segment.split('?').next().unwrap_or(last_segment);
-                    if !clean_segment.is_empty() && clean_segment.contains('.') {
-                        // C'est probablement un nom de fichier
-                        let suggested_path = self.default_download_dir.join(clean_segment);
-                        self.new_path = suggested_path.to_string_lossy().to_string();
-                        return;
+        self.suggest_filename_locally();
+        self.resolve_filename_remotely();
+    }
+    
+    /// Résolution du nom par le gestionnaire (HEAD, ou GET si refusé) dans un thread séparé
+    fn resolve_filename_remotely(&self) {
+        let url = self.new_url.trim().to_string();
+        if self.offline.is_offline() || !(url.starts_with("http://") || url.starts_with("https://")) {
+            return;
+        }
+        let tx = self.resolved_name_tx.clone();
+        let dir = self.default_download_dir.clone();
+        let offline = self.offline.clone();
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+            rt.block_on(async move {
+                let manager = DownloadManager::new().with_offline_mode(offline);
+                match manager.resolve_filename(&url, &Default::default()).await {
+                    Ok(Some(name)) => {
+                        let _ = tx.send((url, dir.join(name)));
                     }
+                    Ok(None) => {}
+                    Err(e) => tracing::debug!(%url, error = %e, "Nom de fichier distant non résolu"),
                 }
+            });
+        });
+    }
+    
+    /// Applique les noms résolus, si l'URL saisie n'a pas changé entre‑temps
+    fn process_resolved_names(&mut self) {
+        while let Ok((url, path)) = self.resolved_name_rx.try_recv() {
+            if self.new_url.trim() == url {
+                self.new_path = path.to_string_lossy().to_string();
+            }
+        }
+    }
+    
+    /// Suggestion immédiate, sans réseau, depuis l'URL saisie
+    fn suggest_filename_locally(&mut self) {
+        // Torrent: le contenu prend le nom annoncé par le magnet (`dn`) ou le fichier .torrent
+        if is_torrent_source(&self.new_url) {
+            if let Some(name) = torrent_display_name(&self.new_url) {
+                self.new_path = self.default_download_dir.join(sanitize_filename(&name)).to_string_lossy().to_string();
+            }
+            return;
+        }
+        if let Ok(url) = url::Url::parse(&self.new_url) {
+            // Essayer d'extraire le nom de fichier de l'URL (hors scripts comme `download.php`)
+            if let Some(name) = downloader::filename_from_url(url.as_str()) {
+                let suggested_path = self.default_download_dir.join(name);
+                self.new_path = suggested_path.to_string_lossy().to_string();
+                return;
             }
             
             // Si pas de nom de fichier dans l'URL, essayer d'extraire depuis les paramètres
             // ou utiliser le domaine + timestamp
             if let Some(domain) = url.domain() {
-                // Essayer de trouver une extension dans le path
-                let path = url.path();
-                let extension = if path.contains('.') {
-                    path.rsplit('.').next().unwrap_or("bin")
-                } else {
-                    // Essayer de deviner l'extension depuis le Content-Type ou utiliser "bin"
-                    "bin"
-                };
+                // Essayer de trouver une extension dans le path (jamais celle d'un script serveur;
+                // le nom réel arrive ensuite via `resolve_filename_remotely`)
+                let extension = url.path()
+                    .rsplit_once('.')
+                    .map(|(_, ext)| ext)
+                    .filter(|ext| !matches!(ext.to_ascii_lowercase().as_str(), "php" | "asp" | "aspx" | "jsp" | "cgi" | "pl"))
+                    .unwrap_or("bin");
                 
                 // Utiliser le domaine (nettoyé) + extension
                 let clean_domain = domain.replace('.', "_").replace('-', "_");
@@ -280,6 +482,70 @@ impl DownloadsTab {
         }
     }
     
+    /// Importe les Metalink, listes d'URLs (et `.torrent`) choisis via le dialogue ou déposés sur la fenêtre
+    fn process_imports(&mut self, ctx: &Context) {
+        let mut paths = Vec::new();
+        while let Ok(path) = self.import_selection_rx.try_recv() {
+            paths.push(path);
+        }
+        let dropped = ctx.input(|i| i.raw.dropped_files.iter().filter_map(|f| f.path.clone()).collect::<Vec<_>>());
+        let (lists, others): (Vec<_>, Vec<_>) = dropped.into_iter().partition(|p| is_metalink_path(p) || is_url_list_path(p));
+        paths.extend(lists);
+        let chunk_size = 8 * 1024 * 1024; // 8 MiB, comme les téléchargements ajoutés à la main
+        for path in paths {
+            let tasks = if is_metalink_path(&path) {
+                load_metalink(&path, &self.default_download_dir, chunk_size, self.new_priority)
+            } else {
+                load_url_list(&path, &self.default_download_dir, chunk_size, self.new_priority)
+            };
+            self.import_tasks(&path, tasks);
+        }
+        // Fichiers .torrent déposés: mis en file comme un lien magnet
+        for path in others {
+            let source = path.to_string_lossy().to_string();
+            if is_torrent_source(&source) {
+                let name = torrent_display_name(&source).unwrap_or_else(|| "torrent".to_string());
+                let output = self.default_download_dir.join(sanitize_filename(&name));
+                self.enqueue_download(NewDownload::new(source, output));
+            }
+        }
+    }
+    
+    /// Met en file une entrée par tâche importée (miroirs et empreintes compris); la file
+    /// partagée décide ensuite de l'ordre de départ
+    fn import_tasks(&mut self, path: &std::path::Path, tasks: anyhow::Result<Vec<DownloadTask>>) {
+        match tasks {
+            Ok(tasks) if tasks.is_empty() => {
+                self.import_error = Some(format!("{}: aucune entrée téléchargeable", path.display()));
+            }
+            Ok(tasks) => {
+                tracing::info!(path = %path.display(), entries = tasks.len(), "Import de téléchargements");
+                for task in tasks {
+                    self.enqueue_download(NewDownload::from_task(&task));
+                }
+                self.import_error = None;
+            }
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "Import impossible");
+                self.import_error = Some(format!("{}: {e:#}", path.display()));
+            }
+        }
+    }
+    
+    /// Ouvre le dialogue de sélection d'un fichier à importer (thread séparé)
+    fn browse_for_import(&mut self, filter: &'static str, extensions: &'static [&'static str]) {
+        let tx = self.import_selection_tx.clone();
+        let default_dir = self.default_download_dir.clone();
+        std::thread::spawn(move || {
+            let dialog = rfd::FileDialog::new()
+                .set_directory(&default_dir)
+                .add_filter(filter, extensions);
+            if let Some(path) = dialog.pick_file() {
+                let _ = tx.send(path);
+            }
+        });
+    }
+    
     /// Traite les messages de progression reçus (non-bloquant pour le thread UI)
     fn process_progress_updates(&mut self) {
         if let Some(ref mut rx) = self.progress_rx {
@@ -293,51 +559,54 @@ impl DownloadsTab {
                         match progress {
                             DownloadProgress::Started { total_size, .. } => {
                                 download.status = DownloadStatus::Downloading;
+                                download.chunks = None;
                                 download.total_size = Some(total_size);
                                 download.progress = 0.0;
                             }
-                            DownloadProgress::Progress { downloaded, speed, .. } => {
+                            DownloadProgress::Progress { downloaded, speed, eta, .. } => {
                                 download.downloaded = downloaded;
                                 download.speed = speed;
-                                if let Some(total) = download.total_size {
+                                download.eta = eta;
+                                if let Some(total) = download.total_size.filter(|&t| t > 0) {
                                     download.progress = downloaded as f32 / total as f32;
                                 }
                             }
                             DownloadProgress::Merging { .. } => {
                                 download.status = DownloadStatus::Merging;
                             }
-                            DownloadProgress::Completed { id } => {
+                            DownloadProgress::Completed { .. } => {
                                 download.status = DownloadStatus::Completed;
                                 download.progress = 1.0;
                                 download.speed = None;
-                                
-                                // Déplacer vers l'historique (non-bloquant)
-                                drop(downloads);
-                                if let (Ok(mut downloads), Ok(mut history)) = (
-                                    self.downloads.try_lock(),
-                                    self.history.try_lock(),
-                                ) {
-                                    if let Some(mut completed) = downloads.remove(&id) {
-                                        // S'assurer que le statut est bien Completed
-                                        completed.status = DownloadStatus::Completed;
-                                        completed.progress = 1.0;
-                                        history.insert(id, completed);
-                                        needs_save = true;
-                                    }
-                                }
-                                continue; // On a déjà drop downloads, pas besoin de continuer
+                                download.eta = None;
+                                download.completed_at = Some(SystemTime::now());
+                                // Le passage en historique est géré par `archive_completed`
                             }
                             DownloadProgress::Error { error, .. } => {
                                 download.status = DownloadStatus::Error(error.clone());
                                 download.error_message = Some(error);
+                                download.chunks = Manifest::load(&download.output_path).map(|m| m.summary());
                                 needs_save = true;
                             }
                             DownloadProgress::Paused { .. } => {
                                 download.status = DownloadStatus::Paused;
+                                download.chunks = Manifest::load(&download.output_path).map(|m| m.summary());
                             }
                             DownloadProgress::Cancelled { .. } => {
                                 download.status = DownloadStatus::Cancelled;
                             }
+                            DownloadProgress::Swarm { peers, seeds, .. } => {
+                                download.swarm = Some((peers, seeds));
+                            }
+                            DownloadProgress::Throttled { wait, .. } => {
+                                download.throttled_until = Some(std::time::Instant::now() + wait);
+                            }
+                            DownloadProgress::Resolved { url, .. } => {
+                                download.resolved_url = (url != download.url).then_some(url);
+                            }
+                            DownloadProgress::Integrity { integrity, .. } => {
+                                download.integrity = Some(integrity);
+                            }
                         }
                         needs_save = true;
                     }
@@ -348,6 +617,11 @@ impl DownloadsTab {
                 }
             }
             
+            // Déplacer vers l'historique les terminés selon le comportement choisi
+            if self.archive_completed(SystemTime::now()) {
+                needs_save = true;
+            }
+            
             // Sauvegarder dans un thread séparé pour ne pas bloquer l'UI
             if needs_save {
                 self.save_history_async();
@@ -360,20 +634,153 @@ impl DownloadsTab {
         }
     }
     
+    /// Déplace vers l'historique les téléchargements terminés dont le délai est écoulé.
+    /// Retourne `true` si au moins un élément a été déplacé.
+    fn archive_completed(&mut self, now: SystemTime) -> bool {
+        let (Ok(mut downloads), Ok(mut history)) = (self.downloads.try_lock(), self.history.try_lock()) else {
+            return false;
+        };
+        let behavior = self.completion_behavior;
+        let ready: Vec<DownloadId> = downloads.values()
+            .filter(|d| d.status == DownloadStatus::Completed)
+            .filter(|d| behavior.should_archive(d.completed_at.unwrap_or(now), now))
+            .map(|d| d.id)
+            .collect();
+        for id in &ready {
+            if let Some(completed) = downloads.remove(id) {
+                history.insert(*id, completed);
+            }
+        }
+        !ready.is_empty()
+    }
+    
+    /// Met en file les téléchargements programmés arrivés à échéance et demande un
+    /// rafraîchissement pour la prochaine échéance
+    fn start_due_downloads(&mut self, ctx: &Context) {
+        let now = SystemTime::now();
+        let (due, next) = match self.downloads.try_lock() {
+            Ok(downloads) => {
+                let next = downloads.values()
+                    .filter(|d| d.status == DownloadStatus::Scheduled)
+                    .filter_map(|d| d.start_at)
+                    .filter(|at| *at > now)
+                    .min();
+                (due_scheduled(downloads.values(), now), next)
+            }
+            Err(_) => return,
+        };
+        if let Some(next) = next {
+            ctx.request_repaint_after(next.duration_since(now).unwrap_or_default());
+        }
+        for id in due {
+            tracing::info!(id, "Départ programmé atteint");
+            self.start_scheduled_now(id);
+        }
+    }
+    
+    /// Sort un téléchargement de l'état `Scheduled` et le confie à la file (restée en attente
+    /// en mode hors ligne)
+    fn start_scheduled_now(&mut self, id: DownloadId) {
+        if let Ok(mut downloads) = self.downloads.try_lock()
+            && let Some(download) = downloads.get_mut(&id)
+            && download.status == DownloadStatus::Scheduled
+        {
+            download.status = DownloadStatus::Queued;
+            download.start_at = None;
+        } else {
+            return;
+        }
+        self.resume_download(id);
+        self.save_history_async();
+    }
+    
+    /// Archive manuellement un téléchargement terminé resté dans la vue "Actifs"
+    fn archive_download(&mut self, id: DownloadId) {
+        if let (Ok(mut downloads), Ok(mut history)) = (self.downloads.try_lock(), self.history.try_lock())
+            && let Some(completed) = downloads.remove(&id)
+        {
+            history.insert(id, completed);
+        }
+        self.save_history_async();
+    }
+    
     pub fn show(&mut self, ui: &mut Ui) {
         // Traiter les mises à jour de progression
         self.process_progress_updates();
         // Traiter les sélections de chemin depuis le dialogue de fichier
         self.process_path_selections();
+        self.process_resolved_names();
+        // Importer les Metalink et listes d'URLs choisis ou déposés sur la fenêtre
+        self.process_imports(&ui.ctx().clone());
+        self.show_collision_prompt(&ui.ctx().clone());
+        self.start_due_downloads(ui.ctx());
         ui.vertical(|ui| {
             // En-tête avec statistiques
             ui.horizontal(|ui| {
                 ui.heading("📥 Gestionnaire de Téléchargements");
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    let stats = self.get_stats();
-                    ui.label(RichText::new(format!("Actifs: {} | Terminés: {}", stats.active, stats.completed))
+                    let DownloadStats { snapshot, completed } = self.get_stats();
+                    ui.label(RichText::new(format!(
+                        "Actifs: {} | En attente: {} | {:.2} MB/s | Aujourd'hui: {:.1} MB | Échecs: {} | Terminés: {}",
+                        snapshot.active,
+                        snapshot.queued,
+                        snapshot.bytes_per_sec as f64 / 1_048_576.0,
+                        snapshot.downloaded_today as f64 / 1_048_576.0,
+                        snapshot.failures,
+                        completed,
+                    ))
                         .color(Color32::GRAY)
                         .small());
+                    egui::ComboBox::from_id_source("completion_behavior")
+                        .selected_text(self.completion_behavior.label())
+                        .show_ui(ui, |ui| {
+                            for behavior in [
+                                CompletionBehavior::MoveImmediately,
+                                CompletionBehavior::KeepFor(COMPLETION_GRACE),
+                                CompletionBehavior::KeepUntilCleared,
+                            ] {
+                                ui.selectable_value(&mut self.completion_behavior, behavior, behavior.label());
+                            }
+                        });
+                    ui.label(RichText::new("Terminés → historique:").small().color(Color32::GRAY));
+                    
+                    let mut offline = self.offline.is_offline();
+                    if ui.checkbox(&mut offline, "✈️ Hors ligne")
+                        .on_hover_text("Ajouter à la file sans aucune activité réseau; la file démarre à la désactivation")
+                        .changed()
+                    {
+                        self.set_offline(offline);
+                    }
+                    
+                    if ui.small_button("🍪 Vider les cookies")
+                        .on_hover_text("Oublier les cookies de session enregistrés (fichier compris); les sites concernés redemanderont connexion ou vérification")
+                        .clicked()
+                    {
+                        CookieJar::global().clear();
+                        tracing::info!("Cookies de session effacés");
+                    }
+                    
+                    let mut kib_per_sec = self.rate_limiter.bytes_per_sec() / 1024;
+                    let slider = egui::Slider::new(&mut kib_per_sec, 0..=100_000)
+                        .logarithmic(true)
+                        .suffix(" KiB/s")
+                        .custom_formatter(|v, _| if v == 0.0 { "illimité".to_string() } else { format!("{}", v as u64) });
+                    if ui.add(slider)
+                        .on_hover_text("Débit maximal cumulé de tous les téléchargements (0 = illimité), hors plages de débit")
+                        .changed()
+                    {
+                        self.rate_limiter.set_bytes_per_sec(kib_per_sec * 1024);
+                    }
+                    ui.label(RichText::new("Limite:").small().color(Color32::GRAY));
+                    
+                    let mut max_active = self.queue.max_active();
+                    if ui.add(egui::DragValue::new(&mut max_active).range(1..=32))
+                        .on_hover_text("Nombre maximal de téléchargements actifs; les suivants attendent leur tour")
+                        .changed()
+                    {
+                        self.queue.set_max_active(max_active);
+                    }
+                    ui.label(RichText::new("Simultanés:").small().color(Color32::GRAY));
                 });
             });
             ui.separator();
@@ -391,7 +798,7 @@ impl DownloadsTab {
                     ui.horizontal(|ui| {
                         ui.label(RichText::new("URL:").strong());
                         let url_edit = ui.text_edit_singleline(&mut self.new_url)
-                            .on_hover_text("URL du fichier à télécharger");
+                            .on_hover_text("URL du fichier à télécharger, lien magnet ou chemin d'un fichier .torrent");
                         
                         // Si l'URL change, suggérer automatiquement le nom de fichier
                         if url_edit.changed() && !self.new_url.is_empty() {
@@ -401,6 +808,14 @@ impl DownloadsTab {
                     
                     ui.add_space(4.0);
                     
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new("Miroirs:").strong());
+                        ui.add(egui::TextEdit::multiline(&mut self.new_mirrors).desired_rows(2).hint_text("Une URL par ligne (optionnel)"))
+                            .on_hover_text("Autres URLs du même fichier: les segments sont répartis entre elles, avec bascule si l'une échoue");
+                    });
+                    
+                    ui.add_space(4.0);
+                    
                     ui.horizontal(|ui| {
                         ui.label(RichText::new("Destination:").strong());
                         ui.text_edit_singleline(&mut self.new_path)
@@ -412,6 +827,54 @@ impl DownloadsTab {
                         }
                     });
                     
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new("Priorité:").strong());
+                        for priority in [Priority::High, Priority::Normal, Priority::Low] {
+                            ui.radio_value(&mut self.new_priority, priority, priority_label(priority));
+                        }
+                    });
+                    
+                    ui.horizontal(|ui| {
+                        if ui.checkbox(&mut self.new_scheduled, "⏰ Programmer")
+                            .on_hover_text("Garder le téléchargement en attente jusqu'à l'heure choisie (heures creuses)")
+                            .changed()
+                            && self.new_scheduled
+                            && self.new_start_date.is_empty()
+                        {
+                            // Par défaut: demain, en heures creuses
+                            let tomorrow = chrono::Local::now().date_naive() + chrono::Days::new(1);
+                            self.new_start_date = tomorrow.format("%Y-%m-%d").to_string();
+                        }
+                        if self.new_scheduled {
+                            ui.add(egui::TextEdit::singleline(&mut self.new_start_date).desired_width(90.0).hint_text("AAAA-MM-JJ"));
+                            ui.label("à");
+                            ui.add(egui::DragValue::new(&mut self.new_start_time.0).range(0..=23).custom_formatter(|v, _| format!("{v:02}")));
+                            ui.label(":");
+                            ui.add(egui::DragValue::new(&mut self.new_start_time.1).range(0..=59).custom_formatter(|v, _| format!("{v:02}")));
+                        }
+                    });
+                    if let Some(error) = &self.schedule_error {
+                        ui.label(RichText::new(format!("⚠ {error}")).small().color(Color32::from_rgb(255, 150, 100)));
+                    }
+                    
+                    ui.collapsing("⚙ Options avancées", |ui| {
+                        ui.horizontal(|ui| {
+                            let mut custom = self.new_max_concurrency.is_some();
+                            if ui.checkbox(&mut custom, "Connexions simultanées:")
+                                .on_hover_text("Segments téléchargés en parallèle pour ce fichier (sinon [chunking] max_concurrency de scrapes.toml)")
+                                .changed()
+                            {
+                                self.new_max_concurrency = custom.then(|| downloader::max_concurrency_from_config(&downloader::load_config()));
+                            }
+                            if let Some(connections) = &mut self.new_max_concurrency {
+                                ui.add(egui::DragValue::new(connections).range(1..=MAX_CONNECTIONS));
+                            }
+                        });
+                    });
+                    
+                    ui.checkbox(&mut self.auto_rename_conflicts, "Renommer automatiquement en cas de conflit")
+                        .on_hover_text("Ajoute un suffixe (1), (2)... si le fichier existe ou est déjà utilisé par un téléchargement en cours");
+                    
                     // Aide contextuelle
                     if self.new_path.is_empty() && !self.new_url.is_empty() {
                         ui.label(RichText::new("💡 Astuce: Le nom de fichier sera suggéré automatiquement depuis l'URL")
@@ -427,9 +890,25 @@ impl DownloadsTab {
                         }
                         if ui.button(RichText::new("🗑️ Effacer").size(14.0)).clicked() {
                             self.new_url.clear();
+                            self.new_mirrors.clear();
                             self.new_path.clear();
                         }
+                        if ui.button(RichText::new("📄 Metalink...").size(14.0))
+                            .on_hover_text("Ajouter toutes les entrées d'un fichier .metalink / .meta4 (glisser‑déposer accepté)")
+                            .clicked()
+                        {
+                            self.browse_for_import("Metalink", &["metalink", "meta4"]);
+                        }
+                        if ui.button(RichText::new("📋 Liste d'URLs...").size(14.0))
+                            .on_hover_text("Ajouter toutes les URLs d'un fichier texte (format aria2 : out=, dir=, checksum=) ou JSON")
+                            .clicked()
+                        {
+                            self.browse_for_import("Liste d'URLs", &["txt", "list", "json"]);
+                        }
                     });
+                    if let Some(error) = &self.import_error {
+                        ui.label(RichText::new(format!("⚠ Import: {error}")).small().color(Color32::from_rgb(255, 150, 100)));
+                    }
                     
                     ui.add_space(8.0);
                     
@@ -453,6 +932,11 @@ impl DownloadsTab {
                     }
                 });
             
+            ui.add_space(8.0);
+            
+            self.show_harvest_panel(ui);
+            self.show_speed_schedule_panel(ui);
+            
             ui.add_space(12.0);
             
             // Filtres et en-tête
@@ -526,6 +1010,175 @@ impl DownloadsTab {
         });
     }
     
+    /// Panneau "Extraire les liens d'une page": récupère les liens et permet de tous les ajouter
+    fn show_harvest_panel(&mut self, ui: &mut Ui) {
+        ui.collapsing("🔗 Extraire les liens d'une page", |ui| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Page:").strong());
+                ui.text_edit_singleline(&mut self.harvest_url)
+                    .on_hover_text("URL d'une page HTML ou JSON listant des fichiers");
+            });
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Extensions:").strong());
+                ui.text_edit_singleline(&mut self.harvest_extensions)
+                    .on_hover_text("Séparées par des virgules; vide = tous les liens");
+            });
+            
+            let running = self.harvest_running.load(Ordering::Relaxed);
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!running && !self.harvest_url.is_empty(), egui::Button::new("🔎 Extraire")).clicked() {
+                    self.start_harvest();
+                }
+                if running {
+                    ui.spinner();
+                }
+            });
+            
+            if let Ok(error) = self.harvest_error.try_lock()
+                && let Some(ref error) = *error
+            {
+                ui.label(RichText::new(format!("Erreur: {}", error)).color(Color32::from_rgb(255, 100, 100)).small());
+            }
+            
+            let links = match self.harvest_results.try_lock() {
+                Ok(guard) => guard.clone(),
+                Err(_) => Vec::new(),
+            };
+            if !links.is_empty() {
+                ui.label(RichText::new(format!("{} lien(s) trouvé(s)", links.len())).small().color(Color32::GRAY));
+                ScrollArea::vertical().id_source("harvest_links").max_height(120.0).show(ui, |ui| {
+                    for link in &links {
+                        url_label(ui, link, Color32::from_rgb(100, 200, 255));
+                    }
+                });
+                if ui.button(format!("➕ Ajouter les {} liens à la file", links.len())).clicked() {
+                    for link in &links {
+                        let output = self.auto_output_path(link);
+                        self.enqueue_download(NewDownload::new(link.clone(), output));
+                    }
+                    if let Ok(mut guard) = self.harvest_results.try_lock() {
+                        guard.clear();
+                    }
+                }
+            }
+        });
+    }
+    
+    /// Paramètres « Plages de débit »: limites par heure de la journée, appliquées à chaud à la
+    /// limite globale (`[[bandwidth.schedule]]` dans scrapes.toml au démarrage)
+    fn show_speed_schedule_panel(&mut self, ui: &mut Ui) {
+        ui.collapsing("⚙ Paramètres: plages de débit", |ui| {
+            let now = chrono::Local::now().time();
+            let active = match self.speed_schedule.active_rule(now) {
+                Some(rule) => format!("plage {} active: {}", format_rule_span(rule), format_kib_limit(rule.bytes_per_sec)),
+                None => format!("hors plage: limite de base, {}", format_kib_limit(self.rate_limiter.bytes_per_sec())),
+            };
+            ui.label(RichText::new(format!("Maintenant ({}) — {active}", now.format("%H:%M"))).small().color(Color32::GRAY));
+            
+            let mut changed = false;
+            let mut removed = None;
+            for (index, rule) in self.speed_schedule.rules.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label("De");
+                    changed |= time_edit(ui, &mut rule.start);
+                    ui.label("à");
+                    changed |= time_edit(ui, &mut rule.end);
+                    let mut kib_per_sec = rule.bytes_per_sec / 1024;
+                    if ui.add(egui::DragValue::new(&mut kib_per_sec).range(0..=1_000_000).suffix(" KiB/s"))
+                        .on_hover_text("Débit cumulé pendant la plage (0 = illimité)")
+                        .changed()
+                    {
+                        rule.bytes_per_sec = kib_per_sec * 1024;
+                        changed = true;
+                    }
+                    if ui.small_button("🗑").on_hover_text("Supprimer la plage").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = removed {
+                self.speed_schedule.rules.remove(index);
+                changed = true;
+            }
+            if ui.button("➕ Ajouter une plage").clicked() {
+                self.speed_schedule.rules.push(SpeedRule {
+                    start: chrono::NaiveTime::from_hms_opt(8, 0, 0).unwrap_or_default(),
+                    end: chrono::NaiveTime::from_hms_opt(23, 0, 0).unwrap_or_default(),
+                    bytes_per_sec: 250 * 1024,
+                });
+                changed = true;
+            }
+            ui.label(RichText::new("La première plage contenant l'heure l'emporte; en dehors, le curseur « Limite » s'applique.")
+                .small()
+                .color(Color32::GRAY));
+            if changed {
+                self.rate_limiter.set_schedule(self.speed_schedule.clone());
+            }
+        });
+    }
+    
+    /// Lance l'extraction des liens dans un thread séparé
+    fn start_harvest(&mut self) {
+        let page_url = self.harvest_url.clone();
+        let filter = HarvestFilter::from_extensions(&self.harvest_extensions);
+        let running = self.harvest_running.clone();
+        let results = self.harvest_results.clone();
+        let error = self.harvest_error.clone();
+        running.store(true, Ordering::Relaxed);
+        
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+            rt.block_on(async move {
+                let outcome = downloader::harvest(&page_url, &filter).await;
+                match outcome {
+                    Ok(links) => {
+                        *results.lock().await = links;
+                        *error.lock().await = None;
+                    }
+                    Err(e) => *error.lock().await = Some(e.to_string()),
+                }
+                running.store(false, Ordering::Relaxed);
+            });
+        });
+    }
+    
+    /// Chemin de sortie automatique pour une URL (dernier segment nettoyé dans le dossier par défaut)
+    fn auto_output_path(&self, url: &str) -> PathBuf {
+        let name = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.path_segments().and_then(|mut s| s.next_back().map(|s| s.to_string())))
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| "download".to_string());
+        self.default_download_dir.join(sanitize_filename(&name))
+    }
+    
+    /// Affiche d'une vidéo terminée, bande d'aperçu au survol
+    fn show_thumbnails(&mut self, ui: &mut Ui, video: &Path) {
+        let Some(poster) = self.thumbnail(ui.ctx(), &thumbnails::poster_path(video)) else {
+            return;
+        };
+        let preview = self.thumbnail(ui.ctx(), &thumbnails::preview_path(video));
+        let response = ui.add(egui::Image::new(egui::load::SizedTexture::from_handle(&poster)).max_width(240.0));
+        if let Some(preview) = preview {
+            response.on_hover_ui(|ui| {
+                ui.add(egui::Image::new(egui::load::SizedTexture::from_handle(&preview)).max_width(720.0));
+            });
+        }
+    }
+    
+    /// Texture d'une miniature PNG, chargée une seule fois
+    fn thumbnail(&mut self, ctx: &Context, path: &Path) -> Option<TextureHandle> {
+        self.thumbnails
+            .entry(path.to_path_buf())
+            .or_insert_with(|| {
+                let image = image::open(path).ok()?.to_rgba8();
+                let size = [image.width() as usize, image.height() as usize];
+                let pixels = egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+                Some(ctx.load_texture(path.to_string_lossy(), pixels, egui::TextureOptions::LINEAR))
+            })
+            .clone()
+    }
+    
     fn render_download_item(&mut self, ui: &mut Ui, download: &DownloadItem) {
         Frame::group(ui.style())
             .fill(Color32::from_rgb(25, 25, 30))
@@ -538,6 +1191,27 @@ impl DownloadsTab {
                     ui.label(RichText::new(download.status.text())
                         .color(download.status.color())
                         .strong());
+                    if matches!(download.status, DownloadStatus::Queued | DownloadStatus::Paused) {
+                        let mut priority = download.priority;
+                        egui::ComboBox::from_id_source(("priority", download.id))
+                            .selected_text(RichText::new(priority_label(priority)).small())
+                            .width(90.0)
+                            .show_ui(ui, |ui| {
+                                for p in [Priority::High, Priority::Normal, Priority::Low] {
+                                    ui.selectable_value(&mut priority, p, priority_label(p));
+                                }
+                            });
+                        if priority != download.priority {
+                            self.set_download_priority(download.id, priority);
+                        }
+                    } else if download.priority != Priority::Normal {
+                        ui.label(RichText::new(priority_label(download.priority)).small().color(Color32::GRAY));
+                    }
+                    if download.status == DownloadStatus::Queued
+                        && let Some(position) = self.queue.waiting_ids().iter().position(|&w| w == download.id)
+                    {
+                        ui.label(RichText::new(format!("#{} dans la file", position + 1)).small().color(Color32::GRAY));
+                    }
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         match download.status {
                             DownloadStatus::Downloading | DownloadStatus::Merging => {
@@ -548,14 +1222,33 @@ impl DownloadsTab {
                                     self.cancel_download(download.id);
                                 }
                             }
+                            DownloadStatus::Scheduled => {
+                                if ui.small_button("▶️").on_hover_text("Démarrer maintenant").clicked() {
+                                    self.start_scheduled_now(download.id);
+                                }
+                                if ui.small_button("❌").clicked() {
+                                    self.cancel_download(download.id);
+                                }
+                            }
                             DownloadStatus::Paused | DownloadStatus::Queued => {
                                 if ui.small_button("▶️").clicked() {
                                     self.resume_download(download.id);
                                 }
+                                if download.status == DownloadStatus::Queued
+                                    && ui.small_button("⏫").on_hover_text("Passer en tête de file").clicked()
+                                {
+                                    self.bump_download(download.id);
+                                }
                                 if ui.small_button("❌").clicked() {
                                     self.cancel_download(download.id);
                                 }
                             }
+                            DownloadStatus::Completed if self.filter != DownloadFilter::Completed => {
+                                let archive = ui.small_button("📦").on_hover_text("Déplacer vers l'historique");
+                                if archive.clicked() {
+                                    self.archive_download(download.id);
+                                }
+                            }
                             DownloadStatus::Error(_) | DownloadStatus::Cancelled => {
                                 // Seulement pour les téléchargements actifs, pas l'historique
                                 if matches!(self.filter, DownloadFilter::Active | DownloadFilter::All) {
@@ -568,7 +1261,11 @@ impl DownloadsTab {
                         }
                         
                         // Bouton pour nettoyer les fichiers part (toujours disponible)
-                        if ui.small_button("🗑️").on_hover_text("Nettoyer les fichiers part").clicked() {
+                        let (icon, hover) = match self.partial_cleanup {
+                            PartialCleanup::Quarantine => ("📦", "Déplacer le fichier partiel et son manifeste dans .scrapes-incomplete/"),
+                            _ => ("🗑️", "Supprimer le manifeste de reprise"),
+                        };
+                        if ui.small_button(icon).on_hover_text(hover).clicked() {
                             self.cleanup_part_files(download.id);
                         }
                     });
@@ -581,14 +1278,18 @@ impl DownloadsTab {
                     .and_then(|n| n.to_str())
                     .unwrap_or("Fichier inconnu");
                 ui.label(RichText::new(filename).strong());
+                if download.status == DownloadStatus::Completed {
+                    self.show_thumbnails(ui, &download.output_path);
+                }
                 
-                // URL (tronquée)
-                let url_display = if download.url.len() > 80 {
-                    format!("{}...", &download.url[..80])
-                } else {
-                    download.url.clone()
-                };
-                ui.label(RichText::new(url_display).small().color(Color32::GRAY));
+                // URL (tronquée, complète au survol)
+                url_label(ui, &download.url, Color32::GRAY);
+                if let Some(resolved) = &download.resolved_url {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new("↪").small().color(Color32::GRAY));
+                        url_label(ui, resolved, Color32::DARK_GRAY);
+                    });
+                }
                 
                 ui.add_space(8.0);
                 
@@ -603,12 +1304,21 @@ impl DownloadsTab {
                     
                     // Informations de progression
                     ui.horizontal(|ui| {
-                        if let Some(total) = download.total_size {
-                            let downloaded_mb = download.downloaded as f64 / 1_048_576.0;
-                            let total_mb = total as f64 / 1_048_576.0;
-                            ui.label(RichText::new(format!("{:.2} MB / {:.2} MB", downloaded_mb, total_mb))
-                                .small()
-                                .color(Color32::GRAY));
+                        let downloaded_mb = download.downloaded as f64 / 1_048_576.0;
+                        match download.total_size.filter(|&t| t > 0) {
+                            Some(total) => {
+                                let total_mb = total as f64 / 1_048_576.0;
+                                ui.label(RichText::new(format!("{:.2} MB / {:.2} MB", downloaded_mb, total_mb))
+                                    .small()
+                                    .color(Color32::GRAY));
+                            }
+                            // Flux sans Content-Length: seuls les octets reçus sont connus
+                            None if download.downloaded > 0 => {
+                                ui.label(RichText::new(format!("{:.2} MB (taille inconnue)", downloaded_mb))
+                                    .small()
+                                    .color(Color32::GRAY));
+                            }
+                            None => {}
                         }
                         
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -618,37 +1328,61 @@ impl DownloadsTab {
                                     .small()
                                     .color(Color32::GRAY));
                             }
+                            if let Some(left) = download.throttled_until
+                                .and_then(|until| until.checked_duration_since(std::time::Instant::now()))
+                            {
+                                ui.label(RichText::new(format!("⏳ attente {} (serveur occupé)", format_eta(left)))
+                                    .small()
+                                    .color(Color32::from_rgb(255, 200, 100)));
+                            } else if let Some(eta) = download.eta {
+                                ui.label(RichText::new(format!("⏱ {}", format_eta(eta)))
+                                    .small()
+                                    .color(Color32::GRAY));
+                            }
+                            if let Some((peers, seeds)) = download.swarm {
+                                ui.label(RichText::new(format!("👥 {} pairs ({} seeders)", peers, seeds))
+                                    .small()
+                                    .color(Color32::GRAY));
+                            }
                         });
                     });
                 } else if let DownloadStatus::Error(ref err) = download.status {
                     ui.label(RichText::new(format!("Erreur: {}", err))
                         .color(Color32::from_rgb(255, 100, 100))
                         .small());
+                    chunk_summary_label(ui, download.chunks);
+                } else if download.status == DownloadStatus::Paused {
+                    chunk_summary_label(ui, download.chunks);
+                } else if let (DownloadStatus::Scheduled, Some(at)) = (&download.status, download.start_at) {
+                    ui.label(RichText::new(format!("⏰ Prévu le {}", format_start_at(at)))
+                        .color(DownloadStatus::Scheduled.color())
+                        .small());
                 } else if download.status == DownloadStatus::Completed {
                     ui.label(RichText::new("✅ Téléchargement terminé")
                         .color(Color32::from_rgb(100, 255, 100))
                         .small());
+                    match download.integrity {
+                        Some(Integrity::Verified(algorithm)) => {
+                            ui.label(RichText::new(format!("🔒 Vérifié ({algorithm:?})"))
+                                .color(Color32::from_rgb(100, 255, 100))
+                                .small());
+                        }
+                        Some(Integrity::Corrupt(algorithm)) => {
+                            ui.label(RichText::new(format!("⚠️ Corrompu: empreinte {algorithm:?} différente"))
+                                .color(Color32::from_rgb(255, 100, 100))
+                                .small());
+                        }
+                        None => {}
+                    }
                 }
             });
     }
     
+    /// Chiffres du téléchargeur (file, débit, volume du jour) et taille de l'historique
     fn get_stats(&self) -> DownloadStats {
         // Utiliser try_lock pour ne pas bloquer le thread UI
-        let downloads = match self.downloads.try_lock() {
-            Ok(guard) => guard,
-            Err(_) => return DownloadStats { active: 0, completed: 0 },
-        };
-        let history = match self.history.try_lock() {
-            Ok(guard) => guard,
-            Err(_) => return DownloadStats { active: 0, completed: 0 },
-        };
-        
-        let active = downloads.values()
-            .filter(|d| matches!(d.status, DownloadStatus::Downloading | DownloadStatus::Merging | DownloadStatus::Queued))
-            .count();
-        let completed = history.len();
-        
-        DownloadStats { active, completed }
+        let completed = self.history.try_lock().map(|history| history.len()).unwrap_or(0);
+        DownloadStats { snapshot: self.statistics.snapshot(&self.queue), completed }
     }
     
     fn add_download(&mut self) {
@@ -656,7 +1390,57 @@ impl DownloadsTab {
             return;
         }
         
-        let output_path = PathBuf::from(&self.new_path);
+        let start_at = if self.new_scheduled {
+            let (hour, minute) = self.new_start_time;
+            match parse_start_at(&self.new_start_date, hour, minute) {
+                Some(at) if at > SystemTime::now() => Some(at),
+                Some(_) => {
+                    self.schedule_error = Some("L'heure de départ programmée est déjà passée".to_string());
+                    return;
+                }
+                None => {
+                    self.schedule_error = Some(format!("Date de départ invalide: {} (attendu AAAA-MM-JJ)", self.new_start_date));
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        self.schedule_error = None;
+        
+        let mut new = NewDownload::new(self.new_url.clone(), PathBuf::from(&self.new_path));
+        new.mirrors = self.new_mirrors.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string).collect();
+        new.priority = self.new_priority;
+        new.max_concurrency = self.new_max_concurrency;
+        new.start_at = start_at;
+        self.enqueue_download(new);
+        
+        // Réinitialiser le formulaire (la programmation reste, pour ajouter plusieurs fichiers)
+        self.new_url.clear();
+        self.new_mirrors.clear();
+        self.new_path.clear();
+        self.new_priority = Priority::default();
+        self.new_max_concurrency = None;
+    }
+    
+    /// Ajoute un téléchargement en file (statut `Queued`) et sauvegarde l'historique. Sans
+    /// renommage automatique, une sortie déjà présente sur le disque attend le choix de
+    /// l'utilisateur (`show_collision_prompt`).
+    fn enqueue_download(&mut self, mut new: NewDownload) {
+        new.output = fit_path_length(&new.output);
+        if self.auto_rename_conflicts {
+            new.output = self.resolve_output_conflict(&new.output);
+        } else if new.output.exists() && !Manifest::path_for(&new.output).exists() && !is_torrent_source(&new.url) {
+            self.collision_prompts.push(new);
+            return;
+        }
+        self.push_download(new, CollisionPolicy::default());
+    }
+    
+    /// Crée l'entrée (`Queued`, ou `Scheduled` si un départ est programmé) avec la politique de
+    /// collision retenue
+    fn push_download(&mut self, new: NewDownload, collision: CollisionPolicy) {
+        let NewDownload { url, mirrors, hashes, priority, output: output_path, start_at, max_concurrency } = new;
         let id = {
             let mut next_id = self.next_id.blocking_lock();
             *next_id += 1;
@@ -665,14 +1449,27 @@ impl DownloadsTab {
         
         let item = DownloadItem {
             id,
-            url: self.new_url.clone(),
-            output_path: output_path.clone(),
-            status: DownloadStatus::Queued,
+            url,
+            mirrors,
+            hashes,
+            priority,
+            resolved_url: None,
+            collision,
+            integrity: None,
+            start_at,
+            max_concurrency,
+            output_path,
+            status: if start_at.is_some() { DownloadStatus::Scheduled } else { DownloadStatus::Queued },
             progress: 0.0,
             speed: None,
+            eta: None,
+            throttled_until: None,
             total_size: None,
             downloaded: 0,
             error_message: None,
+            completed_at: None,
+            swarm: None,
+            chunks: None,
             cancel_flag: Arc::new(AtomicBool::new(false)),
             task_handle: Some(Arc::new(Mutex::new(None))),
         };
@@ -698,57 +1495,176 @@ impl DownloadsTab {
         
         // Sauvegarder l'historique de manière asynchrone
         self.save_history_async();
-        
-        // Réinitialiser le formulaire
-        self.new_url.clear();
-        self.new_path.clear();
     }
     
-    /// Charge l'historique depuis le fichier JSON (appelé une seule fois au démarrage)
+    /// Fenêtre demandant quoi faire d'une sortie déjà présente (un ajout à la fois)
+    fn show_collision_prompt(&mut self, ctx: &Context) {
+        let Some(pending) = self.collision_prompts.first() else {
+            return;
+        };
+        let mut choice = None;
+        egui::Window::new("Fichier existant")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("{} existe déjà.", pending.output.display()));
+                url_label(ui, &pending.url, Color32::GRAY);
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    for policy in [CollisionPolicy::Rename, CollisionPolicy::Overwrite, CollisionPolicy::Resume, CollisionPolicy::Skip] {
+                        let (label, hint) = collision_label(policy);
+                        if ui.button(label).on_hover_text(hint).clicked() {
+                            choice = Some(policy);
+                        }
+                    }
+                });
+            });
+        let Some(policy) = choice else {
+            return;
+        };
+        let mut new = self.collision_prompts.remove(0);
+        match policy {
+            CollisionPolicy::Skip => tracing::info!(file = %new.output.display(), "Ajout ignoré: fichier existant conservé"),
+            CollisionPolicy::Rename => {
+                new.output = self.resolve_output_conflict(&new.output);
+                self.push_download(new, policy);
+            }
+            CollisionPolicy::Overwrite | CollisionPolicy::Resume => self.push_download(new, policy),
+        }
+    }
+    
+    /// Choisit une sortie libre en consultant le disque et les sorties des téléchargements actifs
+    fn resolve_output_conflict(&self, candidate: &std::path::Path) -> PathBuf {
+        // attente du verrou (comme `next_id`): sans la liste, deux sorties pourraient coïncider
+        let pending: HashSet<PathBuf> = self.downloads.blocking_lock()
+            .values()
+            .filter(|d| !matches!(d.status, DownloadStatus::Completed | DownloadStatus::Cancelled))
+            .map(|d| d.output_path.clone())
+            .collect();
+        let resolved = dedupe_path(candidate, &pending);
+        if resolved != candidate {
+            tracing::info!(from = %candidate.display(), to = %resolved.display(), "Conflit de nom de fichier résolu");
+        }
+        resolved
+    }
+    
+    /// Charge l'historique depuis le fichier JSON (appelé une seule fois au démarrage). L'avancement
+    /// des téléchargements inachevés est relu depuis leur manifeste validé, et les téléchargements
+    /// interrompus du dossier par défaut absents de l'historique sont proposés en pause.
     fn load_history(&mut self) {
         // Charger dans un thread séparé pour ne pas bloquer l'UI au démarrage
         let downloads = self.downloads.clone();
         let history = self.history.clone();
         let next_id = self.next_id.clone();
+        let default_dir = self.default_download_dir.clone();
         
         std::thread::spawn(move || {
-            if let Ok(content) = fs::read_to_string(HISTORY_FILE) {
-                if let Ok(items) = serde_json::from_str::<Vec<DownloadItem>>(&content) {
-                    let mut downloads_guard = downloads.blocking_lock();
-                    let mut history_guard = history.blocking_lock();
-                    let mut max_id = 0;
-                    
-                    for mut item in items {
-                        // Réinitialiser les champs non-sérialisables
-                        item.cancel_flag = Arc::new(AtomicBool::new(false));
-                        item.task_handle = Some(Arc::new(Mutex::new(None)));
-                        
-                        max_id = max_id.max(item.id);
-                        
-                        // Séparer les téléchargements actifs de l'historique
-                        if matches!(item.status, DownloadStatus::Completed) {
-                            // Téléchargements terminés -> historique
-                            history_guard.insert(item.id, item);
-                        } else if matches!(item.status, DownloadStatus::Downloading | DownloadStatus::Merging) {
-                            // Téléchargements en cours -> remettre en file
-                            item.status = DownloadStatus::Queued;
-                            downloads_guard.insert(item.id, item);
-                        } else {
-                            // Autres (Queued, Paused, Error, Cancelled) -> actifs
-                            downloads_guard.insert(item.id, item);
-                        }
-                    }
-                    drop(downloads_guard);
-                    drop(history_guard);
-                    
-                    // Mettre à jour le prochain ID
-                    let mut next_id_guard = next_id.blocking_lock();
-                    *next_id_guard = max_id + 1;
+            let items = fs::read_to_string(HISTORY_FILE)
+                .ok()
+                .and_then(|content| serde_json::from_str::<Vec<DownloadItem>>(&content).ok());
+            let loaded = items.is_some();
+            let mut downloads_guard = downloads.blocking_lock();
+            let mut history_guard = history.blocking_lock();
+            let mut max_id = 0;
+            
+            for mut item in items.unwrap_or_default() {
+                // Réinitialiser les champs non-sérialisables
+                item.cancel_flag = Arc::new(AtomicBool::new(false));
+                item.task_handle = Some(Arc::new(Mutex::new(None)));
+                
+                max_id = max_id.max(item.id);
+                if !matches!(item.status, DownloadStatus::Completed) && !is_torrent_source(&item.url) {
+                    Self::restore_resume_state(&mut item);
+                }
+                
+                // Séparer les téléchargements actifs de l'historique
+                if matches!(item.status, DownloadStatus::Completed) {
+                    // Téléchargements terminés -> historique
+                    history_guard.insert(item.id, item);
+                } else if matches!(item.status, DownloadStatus::Downloading | DownloadStatus::Merging) {
+                    // Téléchargements en cours -> remettre en file
+                    item.status = DownloadStatus::Queued;
+                    downloads_guard.insert(item.id, item);
+                } else {
+                    // Autres (Scheduled, Queued, Paused, Error, Cancelled) -> actifs
+                    downloads_guard.insert(item.id, item);
                 }
             }
+            
+            // Manifestes orphelins (historique perdu, téléchargement lancé ailleurs): en pause
+            let known: HashSet<PathBuf> = downloads_guard.values().chain(history_guard.values()).map(|d| d.output_path.clone()).collect();
+            let mut last_id = if loaded { max_id + 1 } else { 0 };
+            for found in downloader::scan_incomplete(&default_dir) {
+                if known.contains(&found.output) {
+                    continue;
+                }
+                last_id += 1;
+                tracing::info!(id = last_id, file = %found.output.display(), "Téléchargement interrompu retrouvé");
+                downloads_guard.insert(last_id, Self::recovered_item(last_id, &found));
+            }
+            drop(downloads_guard);
+            drop(history_guard);
+            
+            // Mettre à jour le prochain ID
+            let mut next_id_guard = next_id.blocking_lock();
+            *next_id_guard = last_id;
         });
     }
     
+    /// Aligne l'avancement affiché sur l'état du disque: manifeste et `.partial` cohérents, sinon
+    /// le téléchargement repartira de zéro
+    fn restore_resume_state(item: &mut DownloadItem) {
+        match downloader::check_resumable(&item.output_path) {
+            Some(found) => {
+                let summary = found.summary();
+                item.downloaded = summary.downloaded;
+                item.total_size = Some(summary.total_size);
+                item.progress = summary.downloaded as f32 / summary.total_size as f32;
+                item.chunks = Some(summary);
+            }
+            None => {
+                if item.downloaded > 0 {
+                    tracing::info!(id = item.id, file = %item.output_path.display(), "Aucun état de reprise valide, reprise depuis le début");
+                }
+                item.downloaded = 0;
+                item.progress = 0.0;
+                item.chunks = None;
+            }
+        }
+    }
+    
+    /// Entrée en pause pour un téléchargement interrompu retrouvé sur le disque
+    fn recovered_item(id: DownloadId, found: &IncompleteDownload) -> DownloadItem {
+        let summary = found.summary();
+        DownloadItem {
+            id,
+            url: found.manifest.url.clone(),
+            mirrors: Vec::new(),
+            hashes: Vec::new(),
+            priority: Priority::default(),
+            resolved_url: None,
+            collision: CollisionPolicy::default(),
+            integrity: None,
+            start_at: None,
+            max_concurrency: None,
+            output_path: found.output.clone(),
+            status: DownloadStatus::Paused,
+            progress: summary.downloaded as f32 / summary.total_size as f32,
+            speed: None,
+            eta: None,
+            throttled_until: None,
+            total_size: Some(summary.total_size),
+            downloaded: summary.downloaded,
+            error_message: None,
+            completed_at: None,
+            swarm: None,
+            chunks: Some(summary),
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+            task_handle: Some(Arc::new(Mutex::new(None))),
+        }
+    }
+    
     /// Sauvegarde l'historique dans le fichier JSON (version synchrone - à éviter dans le thread UI)
     fn save_history(&self) {
         // Utiliser try_lock pour ne pas bloquer
@@ -868,6 +1784,9 @@ impl DownloadsTab {
     
     /// Reprend un téléchargement en pause (non-bloquant)
     fn resume_download(&mut self, id: DownloadId) {
+        if self.offline.is_offline() {
+            return;
+        }
         // Vérifier l'état avec try_lock
         let can_resume = {
             match self.downloads.try_lock() {
@@ -880,26 +1799,20 @@ impl DownloadsTab {
             }
         };
         
-        if !can_resume {
+        if !can_resume || self.downloads.try_lock().is_ok_and(|d| d.get(&id).is_some_and(Self::has_live_thread)) {
             return;
         }
         
-        // Cloner les données nécessaires
-        let (url, output) = {
-            match self.downloads.try_lock() {
-                Ok(downloads) => {
-                    if let Some(d) = downloads.get(&id) {
-                        (Some(d.url.clone()), Some(d.output_path.clone()))
-                    } else {
-                        (None, None)
-                    }
-                }
-                Err(_) => (None, None),
-            }
+        // Copie de l'entrée transmise au thread de téléchargement
+        let download = match self.downloads.try_lock() {
+            Ok(downloads) => downloads.get(&id).cloned(),
+            Err(_) => None, // Si on ne peut pas acquérir le lock, skip
         };
         
-        if let (Some(url), Some(output)) = (url, output) {
+        if let Some(download) = download {
+            let (priority, cancel) = (download.priority, download.cancel_flag.clone());
             let tx = self.progress_tx.clone().expect("Progress channel should exist");
+            let offline = self.offline.clone();
             
             // Mettre à jour le statut (non-bloquant)
             if let Ok(mut downloads) = self.downloads.try_lock() {
@@ -909,26 +1822,35 @@ impl DownloadsTab {
                 }
             }
             
-            // Relancer le téléchargement avec runtime multi-thread
-            std::thread::Builder::new()
+            // Relancer le téléchargement avec runtime multi-thread, une fois une place obtenue dans la file
+            let queue = self.queue.clone();
+            let handle = std::thread::Builder::new()
                 .name(format!("download-{}", id))
                 .spawn(move || {
+                    let Some(_slot) = Self::wait_for_slot(&queue, id, priority, &cancel) else {
+                        return;
+                    };
                     let rt = tokio::runtime::Builder::new_multi_thread()
                         .worker_threads(4)
                         .enable_all()
                         .build()
                         .expect("Failed to create runtime");
                     rt.block_on(async move {
-                        let result = Self::run_download(id, url, output, tx.clone()).await;
+                        let result = if is_torrent_source(&download.url) {
+                            Self::run_torrent(id, download.url, download.output_path, cancel, offline, tx.clone()).await
+                        } else {
+                            Self::run_download(download, offline, tx.clone()).await
+                        };
                         if let Err(e) = result {
                             let _ = tx.send(DownloadProgress::Error {
                                 id,
-                                error: e.to_string(),
+                                error: describe_error(&e),
                             });
                         }
                     });
                 })
                 .expect("Failed to spawn download thread");
+            self.store_thread_handle(id, handle);
         }
     }
     
@@ -953,10 +1875,16 @@ impl DownloadsTab {
             download.progress = 0.0;
             download.downloaded = 0;
             download.error_message = None;
+            download.integrity = None;
+            download.start_at = None;
             download.cancel_flag = Arc::new(AtomicBool::new(false));
             download.task_handle = Some(Arc::new(Mutex::new(None)));
             
-            // NE PAS supprimer les fichiers part - ils seront réutilisés pour la reprise
+            // NE PAS supprimer les fichiers part - ils seront réutilisés pour la reprise; ceux
+            // mis en quarantaine sont remis en place
+            if let Err(e) = downloader::restore_from_quarantine(&download.output_path) {
+                tracing::warn!(error = %e, "Restauration depuis la quarantaine impossible pour le téléchargement {}", id);
+            }
             
             // Retirer de l'historique si présent
             let mut history = self.history.blocking_lock();
@@ -973,62 +1901,51 @@ impl DownloadsTab {
         }
     }
     
-    /// Nettoie manuellement les fichiers part d'un téléchargement (non-bloquant)
+    /// Supprime manuellement le manifeste de reprise d'un téléchargement, ou le met en
+    /// quarantaine avec son fichier partiel selon `[cleanup]` (non-bloquant)
     fn cleanup_part_files(&mut self, id: DownloadId) {
-        // Chercher dans les téléchargements actifs d'abord (non-bloquant)
-        let download = match self.downloads.try_lock() {
-            Ok(downloads) => downloads.get(&id).cloned(),
+        // Chercher dans les téléchargements actifs d'abord, puis dans l'historique (non-bloquant)
+        let output_path = match self.downloads.try_lock() {
+            Ok(downloads) => downloads.get(&id).map(|d| d.output_path.clone()),
             Err(_) => None,
-        };
-        
-        // Si pas trouvé dans actifs, chercher dans l'historique (non-bloquant)
-        let download = if let Some(d) = download {
-            Some(d)
-        } else {
-            match self.history.try_lock() {
-                Ok(history) => history.get(&id).cloned(),
-                Err(_) => None,
-            }
-        };
+        }
+        .or_else(|| match self.history.try_lock() {
+            Ok(history) => history.get(&id).map(|d| d.output_path.clone()),
+            Err(_) => None,
+        });
         
-        if let Some(download) = download {
-            let output_dir = download.output_path.parent().unwrap_or(std::path::Path::new("."));
-            let output_stem = download.output_path.file_stem().unwrap_or_else(|| std::ffi::OsStr::new("file"));
-            
+        if let Some(output_path) = output_path {
+            // Demande explicite: quarantaine si configurée, suppression sinon
+            let cleanup = match self.partial_cleanup {
+                PartialCleanup::Quarantine => PartialCleanup::Quarantine,
+                _ => PartialCleanup::Remove,
+            };
             // Effectuer le nettoyage dans un thread séparé pour ne pas bloquer l'UI
-            let output_dir = output_dir.to_path_buf();
-            let output_stem = output_stem.to_string_lossy().to_string();
             std::thread::spawn(move || {
-                let mut removed_count = 0;
-                if let Ok(entries) = std::fs::read_dir(&output_dir) {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                            // Supprimer les fichiers part
-                            if name.starts_with(&format!("{}.part", output_stem)) && !name.ends_with(".done") {
-                                if std::fs::remove_file(&path).is_ok() {
-                                    removed_count += 1;
-                                }
-                            }
-                            // Supprimer les marqueurs .done
-                            if name.ends_with(".done") && name.starts_with(&format!("{}.part", output_stem)) {
-                                if std::fs::remove_file(&path).is_ok() {
-                                    removed_count += 1;
-                                }
-                            }
-                        }
-                    }
-                }
-                tracing::info!("Nettoyé {} fichier(s) part pour le téléchargement {}", removed_count, id);
+                tracing::info!(?cleanup, "Nettoyage des fichiers de reprise du téléchargement {}", id);
+                cleanup.apply(&output_path);
             });
         }
     }
     
-    /// Démarre tous les téléchargements en file d'attente
+    /// Active/désactive le mode hors ligne; au retour en ligne, la file démarre
+    fn set_offline(&mut self, offline: bool) {
+        self.offline.set_offline(offline);
+        if !offline {
+            self.start_downloads();
+        }
+    }
+    
+    /// Démarre tous les téléchargements en file d'attente (sans effet en mode hors ligne)
     fn start_downloads(&mut self) {
+        if self.offline.is_offline() {
+            tracing::info!("Mode hors ligne: les téléchargements restent en file");
+            return;
+        }
         let downloads = self.downloads.blocking_lock();
         let queued: Vec<_> = downloads.values()
             .filter(|d| matches!(d.status, DownloadStatus::Queued | DownloadStatus::Paused))
+            .filter(|d| !Self::has_live_thread(d))
             .cloned()
             .collect();
         drop(downloads);
@@ -1042,24 +1959,27 @@ impl DownloadsTab {
         // Démarrer chaque téléchargement dans une tâche tokio séparée
         for download in queued {
             let id = download.id;
-            let url = download.url.clone();
-            let output = download.output_path.clone();
+            let priority = download.priority;
+            let cancel = download.cancel_flag.clone();
             let tx = progress_tx.clone();
+            let offline = self.offline.clone();
             
-            // Mettre à jour le statut (non-bloquant)
+            // Le statut reste « En attente » jusqu'à l'événement Started: la file décide du départ
             if let Ok(mut downloads) = self.downloads.try_lock() {
                 if let Some(d) = downloads.get_mut(&id) {
-                    d.status = DownloadStatus::Downloading;
+                    d.status = DownloadStatus::Queued;
                 }
             }
             
-            // Lancer chaque téléchargement dans son propre thread avec son propre runtime tokio
-            // Cela permet un parallélisme illimité - chaque téléchargement est complètement indépendant
-            let url_clone = url.clone();
-            let output_clone = output.clone();
+            // Chaque téléchargement a son propre thread et son propre runtime tokio, mais n'en
+            // crée le runtime qu'après avoir obtenu une place dans la file partagée
+            let queue = self.queue.clone();
             let handle = std::thread::Builder::new()
                 .name(format!("download-{}", id))
                 .spawn(move || {
+                    let Some(_slot) = Self::wait_for_slot(&queue, id, priority, &cancel) else {
+                        return;
+                    };
                     // Créer un runtime tokio multi-thread pour chaque téléchargement
                     // Cela permet un vrai parallélisme - chaque téléchargement peut utiliser plusieurs threads
                     let rt = tokio::runtime::Builder::new_multi_thread()
@@ -1068,175 +1988,572 @@ impl DownloadsTab {
                         .build()
                         .expect("Failed to create runtime");
                     rt.block_on(async move {
-                        let result = Self::run_download(id, url_clone, output_clone, tx.clone()).await;
+                        let result = if is_torrent_source(&download.url) {
+                            Self::run_torrent(id, download.url, download.output_path, cancel, offline, tx.clone()).await
+                        } else {
+                            Self::run_download(download, offline, tx.clone()).await
+                        };
                         if let Err(e) = result {
                             let _ = tx.send(DownloadProgress::Error {
                                 id,
-                                error: e.to_string(),
+                                error: describe_error(&e),
                             });
                         }
                     });
                 })
                 .expect("Failed to spawn download thread");
             
-            // Stocker le handle pour pouvoir l'arrêter (non-bloquant)
-            if let Ok(mut downloads) = self.downloads.try_lock() {
-                if let Some(d) = downloads.get_mut(&id) {
-                    if let Some(handle_arc) = &d.task_handle {
-                        if let Ok(mut handle_opt) = handle_arc.try_lock() {
-                            *handle_opt = Some(handle);
-                        }
+            self.store_thread_handle(id, handle);
+        }
+    }
+    
+    /// Stocke le handle du thread de téléchargement pour pouvoir l'arrêter (non-bloquant)
+    fn store_thread_handle(&self, id: DownloadId, handle: std::thread::JoinHandle<()>) {
+        if let Ok(mut downloads) = self.downloads.try_lock() {
+            if let Some(d) = downloads.get_mut(&id) {
+                if let Some(handle_arc) = &d.task_handle {
+                    if let Ok(mut handle_opt) = handle_arc.try_lock() {
+                        *handle_opt = Some(handle);
                     }
                 }
             }
         }
     }
     
-    /// Exécute un téléchargement et envoie les mises à jour de progression
+    /// Un thread tourne (ou attend sa place dans la file) pour ce téléchargement
+    fn has_live_thread(download: &DownloadItem) -> bool {
+        download.task_handle.as_ref().is_some_and(|handle| {
+            handle.try_lock().is_ok_and(|h| h.as_ref().is_some_and(|h| !h.is_finished()))
+        })
+    }
+    
+    /// Attend une place dans la file partagée; `None` si le téléchargement est mis en pause
+    /// ou annulé entre-temps
+    fn wait_for_slot(queue: &DownloadQueue, id: DownloadId, priority: Priority, cancel: &AtomicBool) -> Option<QueueSlot> {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .expect("Failed to create runtime")
+            .block_on(queue.acquire_cancellable(id, priority, cancel))
+    }
+    
+    /// Change la priorité d'un téléchargement; s'il attend déjà sa place, il est replacé dans la file
+    fn set_download_priority(&mut self, id: DownloadId, priority: Priority) {
+        if let Ok(mut downloads) = self.downloads.try_lock()
+            && let Some(d) = downloads.get_mut(&id)
+        {
+            d.priority = priority;
+        }
+        self.queue.set_priority(id, priority);
+        self.save_history_async();
+    }
+    
+    /// Passe un téléchargement en attente devant les autres (priorité haute, conservée à la reprise)
+    fn bump_download(&mut self, id: DownloadId) {
+        if let Ok(mut downloads) = self.downloads.try_lock()
+            && let Some(d) = downloads.get_mut(&id)
+        {
+            d.priority = Priority::High;
+        }
+        if !self.queue.bump(id) {
+            // Pas encore dans la file (hors ligne, pas démarré): la priorité suffira au départ
+            tracing::debug!("Téléchargement {} pas encore en attente d'une place", id);
+        }
+        self.save_history_async();
+    }
+    
+    /// Exécute le téléchargement décrit par `download` (copie de l'entrée) et envoie les mises à
+    /// jour de progression
     async fn run_download(
-        id: DownloadId,
-        url: String,
-        output: PathBuf,
+        download: DownloadItem,
+        offline: OfflineMode,
         progress_tx: mpsc::UnboundedSender<DownloadProgress>,
     ) -> anyhow::Result<()> {
         use std::time::{Instant, Duration};
-        use tokio::time::sleep;
+        let DownloadItem { id, url, mirrors, hashes, priority, output_path: output, collision, max_concurrency, .. } = download;
         
-        // Détecter la taille totale d'abord
-        let client = reqwest::Client::builder().build()?;
-        let resp = client.head(&url).send().await?;
-        resp.error_for_status_ref()?;
-        
-        let total_size = resp
-            .headers()
-            .get(reqwest::header::CONTENT_LENGTH)
-            .and_then(|v| v.to_str().ok())
-            .and_then(|s| s.parse::<u64>().ok())
-            .unwrap_or(0);
+        // Détecter la taille totale d'abord (URL principale, puis miroirs). Seules les sources
+        // HTTP(S) sont interrogées ici; pour FTP/SFTP la taille arrive avec la première progression.
+        offline.ensure_online()?;
+        let config = downloader::load_config();
+        let http = HttpClientOptions::from_config(&config);
+        let client = http.apply(reqwest::Client::builder()).cookie_provider(Arc::new(CookieJar::global())).build()?;
+        let sources: Vec<&String> = std::iter::once(&url).chain(&mirrors).collect();
+        let mut total_size = 0;
+        let mut head_error = None;
+        for source in sources.iter().filter(|s| s.starts_with("http://") || s.starts_with("https://")) {
+            // HEAD, ou GET d'un octet si le serveur refuse HEAD
+            match downloader::probe_http(&client, source, &Default::default()).await {
+                Ok(metadata) => {
+                    total_size = metadata.total_size;
+                    if source.as_str() == url.as_str() {
+                        let _ = progress_tx.send(DownloadProgress::Resolved { id, url: metadata.final_url });
+                    }
+                    head_error = None;
+                    break;
+                }
+                Err(e) => head_error = Some(e),
+            }
+        }
+        let other_protocols = sources.iter().any(|s| !s.starts_with("http://") && !s.starts_with("https://"));
+        if let Some(e) = head_error
+            && !other_protocols
+        {
+            return Err(e);
+        }
         
         let _ = progress_tx.send(DownloadProgress::Started { id, total_size });
         
         // Démarrer le téléchargement dans une tâche séparée pour suivre la progression
-        let manager = DownloadManager::new();
-        let task = DownloadTask {
-            url: url.clone(),
-            output: output.clone(),
-            total_size: 0,
-            chunk_size: 8 * 1024 * 1024, // 8 MiB
-            num_chunks: 0,
-        };
+        let manager = DownloadManager::new()
+            .with_post_processors(PostProcessPipeline::from_config(&config))
+            .with_space_guard(SpaceGuard::from_config(&config))
+            .with_retry_policy(RetryPolicy::from_config(&config))
+            .with_stall_timeout(downloader::stall_timeout_from_config(&config))
+            .with_adaptive_chunking(AdaptiveChunking::from_config(&config))
+            .with_sidecar_checksums(downloader::sidecar_checksums_from_config(&config))
+            .with_decompression(downloader::decompression_from_config(&config))
+            .with_max_concurrency(downloader::max_concurrency_from_config(&config))
+            .with_small_file_threshold(downloader::small_file_threshold_from_config(&config))
+            .with_http_options(http)
+            .with_collision_policy(collision)
+            .with_offline_mode(offline)
+            .with_events(DownloadEvents::global());
+        let task = DownloadTask::builder(url.clone())
+            .with_output(output.clone())
+            .with_rate_limit(downloader::per_download_limit_from_config(&config))
+            .with_max_concurrency(max_concurrency)
+            .with_max_size(downloader::max_file_size_from_config(&config))
+            .with_mirrors(mirrors)
+            .with_hashes(hashes)
+            .with_priority(priority)
+            .build()?;
         
-        let start_time = Instant::now();
+        // Relayer les événements de cette tâche, publiés sur le bus partagé par le gestionnaire
+        let mut events_rx = manager.subscribe();
         let progress_tx_clone = progress_tx.clone();
-        
-        // Tâche de suivi de progression (compte les chunks complétés)
+        let (event_url, event_output) = (url.clone(), output.clone());
         let progress_task = tokio::spawn(async move {
-            let mut last_downloaded = 0u64;
-            let chunk_size = 8 * 1024 * 1024; // 8 MiB
-            let output_dir = output.parent().unwrap_or(std::path::Path::new("."));
-            let output_stem = output.file_stem().unwrap_or_else(|| std::ffi::OsStr::new("file"));
-            
+            let mut last_sent = Instant::now() - Duration::from_secs(1);
+            let mut size_known = total_size > 0;
             loop {
-                sleep(Duration::from_millis(500)).await;
-                
-                // Compter les chunks complétés (présence de fichiers .done)
-                let mut completed_chunks = 0u64;
-                let mut total_chunks = 0u64;
-                
-                if let Ok(entries) = std::fs::read_dir(&output_dir) {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                            // Compter les fichiers part
-                            if name.starts_with(&format!("{}.part", output_stem.to_string_lossy())) && !name.ends_with(".done") {
-                                total_chunks += 1;
-                            }
-                            // Compter les chunks complétés
-                            if name.ends_with(".done") && name.starts_with(&format!("{}.part", output_stem.to_string_lossy())) {
-                                completed_chunks += 1;
-                                total_chunks += 1;
-                            }
-                        }
-                    }
-                }
-                
-                // Calculer les bytes téléchargés basés sur les chunks complétés
-                let current_downloaded = if total_size > 0 && total_chunks > 0 {
-                    // Estimer basé sur les chunks complétés
-                    let chunks_expected = (total_size + chunk_size - 1) / chunk_size;
-                    let bytes_per_chunk = if chunks_expected > 0 { total_size / chunks_expected } else { chunk_size };
-                    completed_chunks * bytes_per_chunk
-                } else {
-                    // Fallback: vérifier la taille réelle des fichiers part
-                    let mut actual_size = 0u64;
-                    if let Ok(entries) = std::fs::read_dir(&output_dir) {
-                        for entry in entries.flatten() {
-                            let path = entry.path();
-                            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                                if name.starts_with(&format!("{}.part", output_stem.to_string_lossy())) && !name.ends_with(".done") {
-                                    if let Ok(meta) = std::fs::metadata(&path) {
-                                        actual_size += meta.len();
-                                    }
-                                }
-                            }
+                let event = match events_rx.recv().await {
+                    Ok(event) if event.url != event_url || event.output != event_output => continue,
+                    Ok(event) => match event.kind {
+                        DownloadEventKind::Progress(event) => event,
+                        DownloadEventKind::Merging => {
+                            let _ = progress_tx_clone.send(DownloadProgress::Merging { id });
+                            continue;
                         }
-                    }
-                    actual_size
+                        _ => continue,
+                    },
+                    // Événements de progression perdus: les suivants portent le total à jour
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
                 };
-                
-                // Limiter à la taille totale
-                let current_downloaded = current_downloaded.min(total_size);
-                
-                if current_downloaded > last_downloaded || current_downloaded == 0 {
-                    let elapsed = start_time.elapsed().as_secs_f64();
-                    let speed = if elapsed > 0.0 && current_downloaded > 0 {
-                        Some((current_downloaded as f64 / elapsed) as u64)
-                    } else {
-                        None
-                    };
-                    
-                    let _ = progress_tx_clone.send(DownloadProgress::Progress {
-                        id,
-                        downloaded: current_downloaded,
-                        speed,
-                    });
-                    
-                    last_downloaded = current_downloaded;
-                    
-                    // Si on a atteint la taille totale, arrêter le suivi
-                    if total_size > 0 && current_downloaded >= total_size {
-                        break;
-                    }
+                if let Some(wait) = event.throttled {
+                    let _ = progress_tx_clone.send(DownloadProgress::Throttled { id, wait });
+                    continue;
+                }
+                if !size_known && event.total_size > 0 {
+                    size_known = true;
+                    let _ = progress_tx_clone.send(DownloadProgress::Started { id, total_size: event.total_size });
+                }
+                // Limiter le rafraîchissement de l'UI à ~10 Hz, sauf pour l'octet final
+                let finished = event.total_size > 0 && event.total_downloaded >= event.total_size;
+                if !finished && last_sent.elapsed() < Duration::from_millis(100) {
+                    continue;
                 }
+                last_sent = Instant::now();
+                let _ = progress_tx_clone.send(DownloadProgress::Progress {
+                    id,
+                    downloaded: event.total_downloaded,
+                    speed: (event.speed > 0).then_some(event.speed),
+                    eta: event.eta,
+                });
             }
         });
         
         // Exécuter le téléchargement
         let download_result = manager.start(task).await;
         
-        // Arrêter le suivi de progression
-        progress_task.abort();
-        
-        let _ = progress_tx.send(DownloadProgress::Merging { id });
+        // Fermer le bus (le gestionnaire détient l'émetteur) puis vider les derniers événements
+        drop(manager);
+        let _ = progress_task.await;
         
         match download_result {
-            Ok(_) => {
+            Ok(stats) => {
+                // La sonde a pu être sautée (taille connue): afficher tout de même l'URL finale
+                if let Some(url) = stats.final_url {
+                    let _ = progress_tx.send(DownloadProgress::Resolved { id, url });
+                }
+                if stats.skipped {
+                    tracing::info!(id, file = %output.display(), "Fichier existant conservé, rien à télécharger");
+                }
+                if let Some(integrity) = stats.integrity {
+                    let _ = progress_tx.send(DownloadProgress::Integrity { id, integrity });
+                }
                 let _ = progress_tx.send(DownloadProgress::Completed { id });
                 Ok(())
             }
             Err(e) => {
                 let _ = progress_tx.send(DownloadProgress::Error {
                     id,
-                    error: e.to_string(),
+                    error: describe_error(&e),
                 });
                 Err(e)
             }
         }
     }
+    
+    /// Exécute un torrent (magnet ou `.torrent`) via aria2; le contenu est écrit dans le dossier
+    /// de `output`. La pause (drapeau `cancel`) arrête aria2, qui reprendra avec `--continue`.
+    async fn run_torrent(
+        id: DownloadId,
+        source: String,
+        output: PathBuf,
+        cancel: Arc<AtomicBool>,
+        offline: OfflineMode,
+        progress_tx: mpsc::UnboundedSender<DownloadProgress>,
+    ) -> anyhow::Result<()> {
+        offline.ensure_online()?;
+        let dir = output.parent().map(PathBuf::from).unwrap_or_else(|| PathBuf::from("."));
+        let config = downloader::load_config();
+        let torrent = TorrentDownload::new(source, dir).with_rate_limit(downloader::per_download_limit_from_config(&config));
+        let _ = progress_tx.send(DownloadProgress::Started { id, total_size: 0 });
+        
+        // Relayer l'état de l'essaim; la taille n'est connue qu'après réception des métadonnées
+        let (swarm_tx, mut swarm_rx) = mpsc::unbounded_channel::<SwarmProgress>();
+        let relay_tx = progress_tx.clone();
+        let relay = tokio::spawn(async move {
+            let mut total_size = 0;
+            while let Some(swarm) = swarm_rx.recv().await {
+                if swarm.total_size != total_size {
+                    total_size = swarm.total_size;
+                    let _ = relay_tx.send(DownloadProgress::Started { id, total_size });
+                }
+                let _ = relay_tx.send(DownloadProgress::Progress {
+                    id,
+                    downloaded: swarm.downloaded,
+                    speed: (swarm.speed > 0).then_some(swarm.speed),
+                    eta: (total_size > 0).then(|| estimate_eta(total_size.saturating_sub(swarm.downloaded), swarm.speed)).flatten(),
+                });
+                let _ = relay_tx.send(DownloadProgress::Swarm { id, peers: swarm.peers, seeds: swarm.seeds });
+            }
+        });
+        
+        let result = torrent.run(swarm_tx, cancel).await;
+        let _ = relay.await;
+        match result {
+            Ok(TorrentOutcome::Completed) => {
+                let _ = progress_tx.send(DownloadProgress::Completed { id });
+                Ok(())
+            }
+            // Pause/annulation: le statut a déjà été mis à jour par l'UI
+            Ok(TorrentOutcome::Stopped) => Ok(()),
+            Err(e) => {
+                let _ = progress_tx.send(DownloadProgress::Error { id, error: describe_error(&e) });
+                Err(e)
+            }
+        }
+    }
 }
 
 struct DownloadStats {
-    active: usize,
+    snapshot: DownloadStatsSnapshot,
     completed: usize,
 }
 
+/// `3725 s` -> `1 h 02 min`, `192 s` -> `3 min 12 s`
+pub(super) fn format_eta(eta: Duration) -> String {
+    let secs = eta.as_secs();
+    match secs {
+        0..=59 => format!("{} s", secs),
+        60..=3599 => format!("{} min {:02} s", secs / 60, secs % 60),
+        _ => format!("{} h {:02} min", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Instant local `date` (AAAA-MM-JJ) à `hour:minute`; `None` si la date ou l'heure n'existe pas
+fn parse_start_at(date: &str, hour: u32, minute: u32) -> Option<SystemTime> {
+    let date = chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()?;
+    let local = chrono::Local.from_local_datetime(&date.and_hms_opt(hour, minute, 0)?).earliest()?;
+    Some(local.into())
+}
+
+/// `15/03 à 02:00`, en heure locale
+fn format_start_at(at: SystemTime) -> String {
+    chrono::DateTime::<chrono::Local>::from(at).format("%d/%m à %H:%M").to_string()
+}
+
+/// Téléchargements programmés dont l'heure de départ est atteinte à `now`
+fn due_scheduled<'a>(items: impl IntoIterator<Item = &'a DownloadItem>, now: SystemTime) -> Vec<DownloadId> {
+    items.into_iter()
+        .filter(|d| d.status == DownloadStatus::Scheduled && d.start_at.is_none_or(|at| at <= now))
+        .map(|d| d.id)
+        .collect()
+}
+
+/// Libellé et explication d'une politique de collision
+/// Journalise une fois pour toutes les étapes publiées sur le bus partagé des téléchargements
+fn journal_download_events() {
+    static STARTED: std::sync::Once = std::sync::Once::new();
+    STARTED.call_once(|| {
+        let mut events_rx = DownloadEvents::global().subscribe();
+        std::thread::spawn(move || loop {
+            match events_rx.blocking_recv() {
+                Ok(event) => event.trace(),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => tracing::debug!(skipped, "Événements de téléchargement non journalisés"),
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        });
+    });
+}
+
+fn collision_label(policy: CollisionPolicy) -> (&'static str, &'static str) {
+    match policy {
+        CollisionPolicy::Rename => ("📝 Renommer", "Télécharger sous « nom (1).ext »"),
+        CollisionPolicy::Overwrite => ("♻️ Écraser", "Remplacer le fichier existant"),
+        CollisionPolicy::Resume => ("⏯️ Reprendre", "Compléter le fichier existant (serveur compatible Range requis)"),
+        CollisionPolicy::Skip => ("⏭️ Ignorer", "Garder le fichier existant et ne rien télécharger"),
+    }
+}
+
+fn priority_label(priority: Priority) -> &'static str {
+    match priority {
+        Priority::High => "🔺 Haute",
+        Priority::Normal => "Normale",
+        Priority::Low => "🔻 Basse",
+    }
+}
+
+/// Message d'erreur affiché: le manque d'espace disque et les fichiers tronqués sont détaillés en Mo,
+/// les autres échecs sont précédés de leur catégorie (`DownloadErrorKind`)
+fn describe_error(error: &anyhow::Error) -> String {
+    if let Some(full) = error.chain().find_map(|cause| cause.downcast_ref::<DiskFull>()) {
+        return format!(
+            "💾 Disque plein: {:.1} Mo libres, {:.1} Mo nécessaires pour {}",
+            full.available as f64 / 1_048_576.0,
+            full.needed as f64 / 1_048_576.0,
+            full.path.display()
+        );
+    }
+    if let Some(too_large) = error.chain().find_map(|cause| cause.downcast_ref::<FileTooLarge>()) {
+        return format!(
+            "📏 Fichier trop volumineux: {:.1} Mo pour une limite de {:.1} Mo ([disk] max_file_size_mb)",
+            too_large.size as f64 / 1_048_576.0,
+            too_large.limit as f64 / 1_048_576.0
+        );
+    }
+    if let Some(truncated) = error.chain().find_map(|cause| cause.downcast_ref::<TruncatedDownload>()) {
+        return format!(
+            "✂️ Fichier incomplet supprimé: {:.1} Mo reçus sur {:.1} Mo annoncés",
+            truncated.actual as f64 / 1_048_576.0,
+            truncated.expected as f64 / 1_048_576.0
+        );
+    }
+    match DownloadErrorKind::of(error) {
+        DownloadErrorKind::Network => format!("🌐 Erreur réseau: {error}"),
+        DownloadErrorKind::RangeUnsupported => format!("↔️ Plages non prises en charge: {error}"),
+        DownloadErrorKind::Cancelled => "📴 Interrompu: mode hors ligne actif".to_string(),
+        DownloadErrorKind::Verification => format!("🛡️ Vérification échouée: {error}"),
+        DownloadErrorKind::Io => format!("💽 Erreur d'écriture: {error}"),
+        _ => error.to_string(),
+    }
+}
+
+/// Champ heure:minute; `true` si l'heure a changé
+fn time_edit(ui: &mut Ui, time: &mut chrono::NaiveTime) -> bool {
+    use chrono::Timelike;
+    let (mut hour, mut minute) = (time.hour(), time.minute());
+    let changed = ui.add(egui::DragValue::new(&mut hour).range(0..=23).custom_formatter(|v, _| format!("{v:02}"))).changed()
+        | ui.add(egui::DragValue::new(&mut minute).range(0..=59).custom_formatter(|v, _| format!("{v:02}"))).changed();
+    if changed && let Some(updated) = chrono::NaiveTime::from_hms_opt(hour, minute, 0) {
+        *time = updated;
+    }
+    changed
+}
+
+fn format_rule_span(rule: &SpeedRule) -> String {
+    format!("{}–{}", rule.start.format("%H:%M"), rule.end.format("%H:%M"))
+}
+
+fn format_kib_limit(bytes_per_sec: u64) -> String {
+    if bytes_per_sec == 0 { "illimité".to_string() } else { format!("{} KiB/s", bytes_per_sec / 1024) }
+}
+
+/// Avancement par segments (lu dans le manifeste), affiché pour les téléchargements interrompus
+fn chunk_summary_label(ui: &mut Ui, summary: Option<ManifestSummary>) {
+    let Some(summary) = summary else { return };
+    ui.label(RichText::new(format!(
+        "🧩 {}/{} segments terminés — {:.2} / {:.2} MB",
+        summary.done_chunks,
+        summary.total_chunks,
+        summary.downloaded as f64 / 1_048_576.0,
+        summary.total_size as f64 / 1_048_576.0,
+    ))
+    .small()
+    .color(Color32::GRAY));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_error_details_disk_full_and_truncation() {
+        let full = DiskFull { path: PathBuf::from("film.mkv"), available: 1_048_576, needed: 3 * 1_048_576 };
+        let message = describe_error(&anyhow::Error::new(full));
+        assert_eq!(message, "💾 Disque plein: 1.0 Mo libres, 3.0 Mo nécessaires pour film.mkv");
+        assert_eq!(describe_error(&anyhow::anyhow!("autre")), "autre");
+        let truncated = TruncatedDownload { path: PathBuf::from("film.mkv"), expected: 4 * 1_048_576, actual: 1_048_576 };
+        let message = describe_error(&anyhow::Error::new(truncated).context("Téléchargement"));
+        assert_eq!(message, "✂️ Fichier incomplet supprimé: 1.0 Mo reçus sur 4.0 Mo annoncés");
+        let mismatch = crate::downloader::DownloadError::new(DownloadErrorKind::Verification, "Empreinte invalide");
+        assert_eq!(describe_error(&mismatch.into()), "🛡️ Vérification échouée: Empreinte invalide");
+    }
+
+    #[test]
+    fn test_completion_behavior_grace_period() {
+        let behavior = CompletionBehavior::KeepFor(Duration::from_secs(5));
+        let completed_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+
+        assert!(!behavior.should_archive(completed_at, completed_at));
+        assert!(!behavior.should_archive(completed_at, completed_at + Duration::from_secs(4)));
+        assert!(behavior.should_archive(completed_at, completed_at + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_completion_behavior_immediate_and_manual() {
+        let now = SystemTime::now();
+        assert!(CompletionBehavior::MoveImmediately.should_archive(now, now));
+        assert!(!CompletionBehavior::KeepUntilCleared.should_archive(now, now + Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_archive_completed_moves_only_after_grace() {
+        let mut tab = DownloadsTab {
+            completion_behavior: CompletionBehavior::KeepFor(Duration::from_secs(5)),
+            ..DownloadsTab::new(PathBuf::from("."), PartialCleanup::Keep)
+        };
+        let completed_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let item = DownloadItem {
+            id: 42,
+            url: "http://example.com/a.mp4".to_string(),
+            mirrors: Vec::new(),
+            hashes: Vec::new(),
+            priority: Priority::default(),
+            resolved_url: None,
+            collision: CollisionPolicy::default(),
+            integrity: None,
+            start_at: None,
+            max_concurrency: None,
+            output_path: PathBuf::from("a.mp4"),
+            status: DownloadStatus::Completed,
+            progress: 1.0,
+            speed: None,
+            eta: None,
+            throttled_until: None,
+            total_size: None,
+            downloaded: 0,
+            error_message: None,
+            completed_at: Some(completed_at),
+            swarm: None,
+            chunks: None,
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+            task_handle: None,
+        };
+        tab.downloads.blocking_lock().insert(42, item);
+
+        assert!(!tab.archive_completed(completed_at + Duration::from_secs(2)));
+        assert!(tab.downloads.blocking_lock().contains_key(&42));
+
+        assert!(tab.archive_completed(completed_at + Duration::from_secs(6)));
+        assert!(!tab.downloads.blocking_lock().contains_key(&42));
+        assert!(tab.history.blocking_lock().contains_key(&42));
+    }
+
+    #[test]
+    fn test_offline_mode_keeps_items_queued() {
+        let mut tab = DownloadsTab { offline: OfflineMode::new(), ..DownloadsTab::new(PathBuf::from("."), PartialCleanup::Keep) };
+        tab.offline.set_offline(true);
+        let item = DownloadItem {
+            id: 7,
+            url: "http://127.0.0.1:9/never.mp4".to_string(),
+            mirrors: Vec::new(),
+            hashes: Vec::new(),
+            priority: Priority::default(),
+            resolved_url: None,
+            collision: CollisionPolicy::default(),
+            integrity: None,
+            start_at: None,
+            max_concurrency: None,
+            output_path: PathBuf::from("never.mp4"),
+            status: DownloadStatus::Queued,
+            progress: 0.0,
+            speed: None,
+            eta: None,
+            throttled_until: None,
+            total_size: None,
+            downloaded: 0,
+            error_message: None,
+            completed_at: None,
+            swarm: None,
+            chunks: None,
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+            task_handle: None,
+        };
+        tab.downloads.blocking_lock().insert(7, item);
+
+        // Aucun thread de téléchargement n'est lancé: l'élément reste en file
+        tab.start_downloads();
+        tab.resume_download(7);
+        let downloads = tab.downloads.blocking_lock();
+        assert_eq!(downloads[&7].status, DownloadStatus::Queued);
+        assert!(downloads[&7].task_handle.is_none());
+    }
+
+    #[test]
+    fn test_scheduled_downloads_start_when_due() {
+        assert!(parse_start_at("2026-02-30", 2, 0).is_none());
+        assert!(parse_start_at("demain", 2, 0).is_none());
+        let at = parse_start_at("2030-03-15", 2, 5).unwrap();
+        assert_eq!(format_start_at(at), "15/03 à 02:05");
+
+        let now = SystemTime::now();
+        let item = |id: DownloadId, status: DownloadStatus, start_at: SystemTime| DownloadItem {
+            id,
+            url: format!("http://127.0.0.1:9/{id}.mp4"),
+            mirrors: Vec::new(),
+            hashes: Vec::new(),
+            priority: Priority::default(),
+            resolved_url: None,
+            collision: CollisionPolicy::default(),
+            integrity: None,
+            start_at: Some(start_at),
+            max_concurrency: None,
+            output_path: PathBuf::from(format!("{id}.mp4")),
+            status,
+            progress: 0.0,
+            speed: None,
+            eta: None,
+            throttled_until: None,
+            total_size: None,
+            downloaded: 0,
+            error_message: None,
+            completed_at: None,
+            swarm: None,
+            chunks: None,
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+            task_handle: None,
+        };
+        let items = [
+            item(1, DownloadStatus::Scheduled, now - Duration::from_secs(60)),
+            item(2, DownloadStatus::Scheduled, now + Duration::from_secs(3600)),
+            // Annulé avant l'échéance: ne démarre pas
+            item(3, DownloadStatus::Cancelled, now - Duration::from_secs(60)),
+        ];
+        assert_eq!(due_scheduled(&items, now), vec![1]);
+        assert_eq!(due_scheduled(&items, now + Duration::from_secs(3600)), vec![1, 2]);
+    }
+}
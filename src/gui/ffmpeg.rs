@@ -2,17 +2,30 @@
 //!
 //! Permet de:
 //! - Configurer les téléchargements via FFmpeg
-//! - Suivre la progression en temps réel
+//! - Inspecter le média (durée, pistes, codecs) avec ffprobe avant de le télécharger
+//! - Choisir la qualité d'une playlist HLS maîtresse ou les pistes d'un manifeste DASH
+//! - Réencoder avec un préréglage (H.264 1080p, H.265 compact, audio 128k)
+//! - Télécharger un flux HLS segment par segment, avec reprise après interruption
+//! - Assembler plusieurs parties (URLs séparées par des espaces) en un seul fichier
+//! - Enregistrer un direct avec durée maximale, heure d'arrêt et segments
+//! - Signaler un ffmpeg introuvable et en installer une version statique
+//! - Mettre plusieurs tâches en file (nombre simultané réglable) et suivre leur progression
 //! - Gérer les options de redémarrage et timeout
 //! - Sélectionner les chemins via un explorateur de fichiers
 //! - Historique des chemins utilisés
 
-use egui::{Ui, RichText, Color32, ScrollArea};
+use egui::{Ui, RichText, Color32, ScrollArea, ProgressBar};
 use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
 use tokio::sync::{Mutex, mpsc};
 use std::path::PathBuf;
-use crate::ffmpeg::{self, DownloadOptions, FfmpegProgress};
+use crate::ffmpeg::{self, AudioFormat, DashManifest, DownloadOptions, ExternalSubtitle, FfmpegJob, FfmpegJobQueue, HlsVariant, HwAccel, JobInput, JobStatus, MetadataOptions, ReadRate, RecordOptions, SubtitleMode, TranscodePreset};
+use crate::ffmpeg::jobs::JobInfo;
+use crate::ffmpeg::pipeline;
+use crate::ffmpeg::dash::{self, DashRepresentation};
+use crate::ffprobe::{self, MediaInfo, StreamKind};
+use super::downloads::format_eta;
 use std::time::Duration;
+use std::collections::BTreeMap;
 use serde::{Serialize, Deserialize};
 use std::fs;
 
@@ -26,28 +39,79 @@ pub struct FfmpegTab {
     stall_timeout_secs: u64,
     max_restarts: u32,
     auto_restart: bool,
-    is_downloading: bool,
-    cancel_flag: Arc<AtomicBool>,
-    progress: Arc<Mutex<FfmpegProgressUI>>,
-    error_message: Arc<Mutex<Option<String>>>,
-    task_handle: Option<std::thread::JoinHandle<()>>,
+    extract_subtitles: bool,
+    subtitle_mode: SubtitleMode,
+    /// URLs de `.vtt` / `.srt` à intégrer, séparées par des espaces
+    external_subtitles: String,
+    audio_only: bool,
+    audio_format: AudioFormat,
+    /// Réencodage choisi (`None`: copie des flux)
+    preset: Option<TranscodePreset>,
+    /// Accélération matérielle du préréglage, parmi celles que ffmpeg sait utiliser
+    /// (`hwaccels`, détectées une fois à la première sélection d'un préréglage vidéo)
+    hwaccel: Option<HwAccel>,
+    hwaccels: Arc<Mutex<Option<Vec<HwAccel>>>>,
+    detecting_hwaccels: Arc<AtomicBool>,
+    segmented_hls: bool,
+    /// Source téléchargée par le gestionnaire, ffmpeg ne faisant que la finaliser
+    two_stage: bool,
+    resume_vod: bool,
+    thumbnails: bool,
+    /// Cadence de lecture (`None`: au plus vite); `read_rate_kib` sert au débit fixe
+    read_rate: Option<ReadRate>,
+    read_rate_kib: u64,
+    /// Arguments supplémentaires, séparés par des espaces (guillemets pour grouper)
+    extra_input_args: String,
+    extra_output_args: String,
+    /// User-Agent et en‑têtes HTTP des entrées (un `Nom: valeur` par ligne)
+    user_agent: String,
+    http_headers: String,
+    /// Métadonnées de la sortie; saison et épisode à 0 = non renseignés
+    copy_metadata: bool,
+    copy_chapters: bool,
+    metadata_title: String,
+    metadata_show: String,
+    metadata_season: u32,
+    metadata_episode: u32,
+    /// Enregistrement d'un direct: durée max et segments en minutes (0 = aucun), heure d'arrêt
+    record_live: bool,
+    record_max_minutes: u32,
+    record_stop_enabled: bool,
+    record_stop_hour: u32,
+    record_stop_minute: u32,
+    record_segment_minutes: u32,
+    variants: Arc<Mutex<Option<Fetched<Vec<HlsVariant>>>>>,
+    fetching_variants: Arc<AtomicBool>,
+    /// Index dans `variants`; `None` laisse ffmpeg choisir la meilleure qualité
+    selected_variant: Option<usize>,
+    /// Dernier manifeste DASH lu, et représentations choisies (`None` = meilleur débit)
+    dash_manifest: Arc<Mutex<Option<Fetched<DashManifest>>>>,
+    selected_dash_video: Option<usize>,
+    selected_dash_audio: Option<usize>,
+    /// Dernière inspection ffprobe
+    media_info: Arc<Mutex<Option<Fetched<MediaInfo>>>>,
+    probing: Arc<AtomicBool>,
+    /// ffmpeg trouvé au lancement (ou installé depuis); `None` affiche l'avertissement
+    ffmpeg_path: Option<PathBuf>,
+    /// Installation en cours, et son résultat (chemin de ffmpeg ou erreur)
+    installing: Arc<AtomicBool>,
+    install_result: Arc<Mutex<Option<Result<PathBuf, String>>>>,
+    /// File des tâches lancées depuis l'onglet
+    jobs: FfmpegJobQueue,
+    /// Options refusées par `DownloadOptions::validate` au lancement
+    error_message: Option<String>,
     path_selection_tx: Option<mpsc::UnboundedSender<PathBuf>>,
     path_selection_rx: Option<mpsc::UnboundedReceiver<PathBuf>>,
 }
 
+/// Résultat d'une requête en arrière-plan pour une URL: (URL, valeur ou erreur)
+type Fetched<T> = (String, Result<T, String>);
+
 #[derive(Serialize, Deserialize)]
 struct PathHistory {
     paths: Vec<String>,
 }
 
-// Utiliser le type FfmpegProgress du module ffmpeg mais avec des champs simplifiés pour l'UI
-#[derive(Clone, Debug, Default)]
-struct FfmpegProgressUI {
-    out_time_ms: Option<String>,
-    bitrate: Option<String>,
-    speed: Option<String>,
-}
-
 impl Default for FfmpegTab {
     fn default() -> Self {
         let (tx, rx) = mpsc::unbounded_channel();
@@ -58,11 +122,50 @@ impl Default for FfmpegTab {
             stall_timeout_secs: 30,
             max_restarts: 3,
             auto_restart: true,
-            is_downloading: false,
-            cancel_flag: Arc::new(AtomicBool::new(false)),
-            progress: Arc::new(Mutex::new(FfmpegProgressUI::default())),
-            error_message: Arc::new(Mutex::new(None)),
-            task_handle: None,
+            extract_subtitles: false,
+            subtitle_mode: SubtitleMode::Drop,
+            external_subtitles: String::new(),
+            audio_only: false,
+            audio_format: AudioFormat::M4a,
+            preset: None,
+            hwaccel: None,
+            hwaccels: Arc::new(Mutex::new(None)),
+            detecting_hwaccels: Arc::new(AtomicBool::new(false)),
+            segmented_hls: false,
+            two_stage: false,
+            resume_vod: false,
+            thumbnails: false,
+            read_rate: None,
+            read_rate_kib: 1024,
+            extra_input_args: String::new(),
+            extra_output_args: String::new(),
+            user_agent: String::new(),
+            http_headers: String::new(),
+            copy_metadata: true,
+            copy_chapters: true,
+            metadata_title: String::new(),
+            metadata_show: String::new(),
+            metadata_season: 0,
+            metadata_episode: 0,
+            record_live: false,
+            record_max_minutes: 120,
+            record_stop_enabled: false,
+            record_stop_hour: 23,
+            record_stop_minute: 0,
+            record_segment_minutes: 0,
+            variants: Arc::new(Mutex::new(None)),
+            fetching_variants: Arc::new(AtomicBool::new(false)),
+            selected_variant: None,
+            dash_manifest: Arc::new(Mutex::new(None)),
+            selected_dash_video: None,
+            selected_dash_audio: None,
+            media_info: Arc::new(Mutex::new(None)),
+            probing: Arc::new(AtomicBool::new(false)),
+            ffmpeg_path: ffmpeg::binary::locate(ffmpeg::binary::Tool::Ffmpeg),
+            installing: Arc::new(AtomicBool::new(false)),
+            install_result: Arc::new(Mutex::new(None)),
+            jobs: FfmpegJobQueue::from_config(&crate::downloader::load_config()),
+            error_message: None,
             path_selection_tx: Some(tx),
             path_selection_rx: Some(rx),
         };
@@ -79,6 +182,7 @@ impl FfmpegTab {
         ui.vertical(|ui| {
             ui.heading("🎬 Téléchargement FFmpeg");
             ui.separator();
+            self.show_missing_ffmpeg(ui);
             
             // Configuration avec style amélioré
             egui::Frame::group(ui.style())
@@ -93,9 +197,14 @@ impl FfmpegTab {
                     ui.horizontal(|ui| {
                         ui.label(RichText::new("URL d'entrée:").strong());
                         ui.text_edit_singleline(&mut self.input_url)
-                            .on_hover_text("URL du flux (ex: m3u8, mp4)");
+                            .on_hover_text("URL du flux (ex: m3u8, mpd, mp4); plusieurs URLs séparées par des espaces sont assemblées dans l'ordre");
                     });
                     
+                    ui.add_space(4.0);
+                    self.show_variant_picker(ui);
+                    ui.add_space(4.0);
+                    self.show_media_info(ui);
+                    
                     ui.add_space(4.0);
                     
                     ui.horizontal(|ui| {
@@ -153,71 +262,537 @@ impl FfmpegTab {
                     ui.add_space(4.0);
                     
                     ui.checkbox(&mut self.auto_restart, RichText::new("Redémarrage automatique").strong());
+                    ui.checkbox(&mut self.extract_subtitles, RichText::new("Extraire les sous‑titres (.srt)").strong())
+                        .on_hover_text("Copie les pistes de sous‑titres intégrées dans un fichier .srt à côté de la vidéo");
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new("Sous‑titres dans la vidéo:").strong());
+                        egui::ComboBox::from_id_source("ffmpeg_subtitle_mode")
+                            .selected_text(match self.subtitle_mode {
+                                SubtitleMode::Drop => "Ignorer",
+                                SubtitleMode::Copy => "Conserver",
+                            })
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.subtitle_mode, SubtitleMode::Drop, "Ignorer");
+                                ui.selectable_value(&mut self.subtitle_mode, SubtitleMode::Copy, "Conserver");
+                            });
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new("Sous‑titres externes:").strong());
+                        ui.text_edit_singleline(&mut self.external_subtitles)
+                            .on_hover_text("URLs de fichiers .vtt / .srt à intégrer comme pistes, séparées par des espaces");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.audio_only, RichText::new("Audio seulement").strong())
+                            .on_hover_text("Ignore la vidéo; l'extension du fichier de sortie est remplacée par celle du format");
+                        ui.add_enabled_ui(self.audio_only, |ui| {
+                            egui::ComboBox::from_id_source("ffmpeg_audio_format")
+                                .selected_text(self.audio_format.extension())
+                                .show_ui(ui, |ui| {
+                                    for format in AudioFormat::ALL {
+                                        ui.selectable_value(&mut self.audio_format, format, format.extension());
+                                    }
+                                });
+                        });
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new("Préréglage:").strong());
+                        ui.add_enabled_ui(!self.audio_only, |ui| {
+                            egui::ComboBox::from_id_source("ffmpeg_preset")
+                                .selected_text(self.preset.map_or("Copie (sans réencodage)", TranscodePreset::label))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut self.preset, None, "Copie (sans réencodage)");
+                                    for preset in TranscodePreset::ALL {
+                                        ui.selectable_value(&mut self.preset, Some(preset), preset.label());
+                                    }
+                                });
+                        })
+                        .response
+                        .on_hover_text("Réencode au lieu de copier les flux; plus lent, progression selon la vitesse d'encodage");
+                    });
+                    if !self.audio_only && self.preset.is_some_and(|p| p.audio_format().is_none()) {
+                        self.show_hwaccel(ui);
+                    }
+                    ui.checkbox(&mut self.segmented_hls, RichText::new("Téléchargement par segments (reprise)").strong())
+                        .on_hover_text("HLS à la demande: segments téléchargés un à un, une interruption ne reprend que les manquants");
+                    ui.checkbox(&mut self.two_stage, RichText::new("Télécharger puis finaliser").strong())
+                        .on_hover_text("Source téléchargée par le gestionnaire (plages parallèles, reprise), puis remuxée en local par ffmpeg");
+                    ui.checkbox(&mut self.resume_vod, RichText::new("Reprise des vidéos à la demande").strong())
+                        .on_hover_text("Écrit par parties (.parts/): une nouvelle tentative reprend où la précédente s'est arrêtée");
+                    ui.checkbox(&mut self.thumbnails, RichText::new("Miniatures après téléchargement").strong())
+                        .on_hover_text("Affiche (.poster.png) et bande d'aperçu (.preview.png) à côté du fichier");
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new("Cadence de lecture:").strong());
+                        let fixed = ReadRate::BytesPerSec(self.read_rate_kib * 1024);
+                        egui::ComboBox::from_id_source("ffmpeg_read_rate")
+                            .selected_text(read_rate_label(self.read_rate))
+                            .show_ui(ui, |ui| {
+                                for rate in [None, Some(ReadRate::Speed(1.0)), Some(ReadRate::Speed(2.0)), Some(ReadRate::GlobalLimit), Some(fixed)] {
+                                    let label = read_rate_label(rate);
+                                    if ui.selectable_label(read_rate_label(self.read_rate) == label, label).clicked() {
+                                        self.read_rate = rate;
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text("-readrate: évite qu'une vidéo à la demande sature la connexion");
+                        if let Some(ReadRate::BytesPerSec(_)) = self.read_rate
+                            && ui.add(egui::DragValue::new(&mut self.read_rate_kib).range(16..=1_000_000).suffix(" KiB/s")).changed()
+                        {
+                            self.read_rate = Some(ReadRate::BytesPerSec(self.read_rate_kib * 1024));
+                        }
+                    });
+                    
+                    ui.add_space(4.0);
+                    
+                    ui.checkbox(&mut self.record_live, RichText::new("🔴 Enregistrer un direct").strong())
+                        .on_hover_text("Enregistrement borné, lisible même interrompu; ⏹️ Arrêter conserve ce qui a été enregistré");
+                    if self.record_live {
+                        ui.horizontal(|ui| {
+                            ui.label("Durée max (min, 0 = aucune):");
+                            ui.add(egui::DragValue::new(&mut self.record_max_minutes).range(0..=24 * 60));
+                            ui.checkbox(&mut self.record_stop_enabled, "Arrêt à");
+                            ui.add_enabled(self.record_stop_enabled, egui::DragValue::new(&mut self.record_stop_hour).range(0..=23));
+                            ui.label(":");
+                            ui.add_enabled(self.record_stop_enabled, egui::DragValue::new(&mut self.record_stop_minute).range(0..=59));
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Nouveau fichier toutes les (min, 0 = jamais):");
+                            ui.add(egui::DragValue::new(&mut self.record_segment_minutes).range(0..=24 * 60));
+                        });
+                    }
+                    
+                    ui.add_space(4.0);
+                    
+                    ui.collapsing("En‑têtes HTTP", |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("User-Agent:").strong());
+                            ui.text_edit_singleline(&mut self.user_agent);
+                        });
+                        ui.label(RichText::new("En‑têtes (un par ligne):").strong());
+                        ui.add(egui::TextEdit::multiline(&mut self.http_headers).desired_rows(2).hint_text("Referer: https://...\nCookie: session=..."))
+                            .on_hover_text("Envoyés avec -headers; nécessaires pour de nombreux flux repérés par le sniffer (sinon 403)");
+                    });
+                    
+                    ui.collapsing("Métadonnées", |ui| {
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut self.copy_metadata, "Copier les métadonnées de l'entrée");
+                            ui.checkbox(&mut self.copy_chapters, "Copier les chapitres");
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("Titre:").strong());
+                            ui.text_edit_singleline(&mut self.metadata_title);
+                            ui.label(RichText::new("Série:").strong());
+                            ui.text_edit_singleline(&mut self.metadata_show);
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("Saison:").strong());
+                            ui.add(egui::DragValue::new(&mut self.metadata_season).range(0..=999));
+                            ui.label(RichText::new("Épisode:").strong());
+                            ui.add(egui::DragValue::new(&mut self.metadata_episode).range(0..=9999));
+                        })
+                        .response
+                        .on_hover_text("0 = non renseigné; l'épisode est alors tiré du titre (« Episode 05 »)");
+                    });
+
+                    ui.collapsing("Arguments avancés", |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("Entrée:").strong());
+                            ui.text_edit_singleline(&mut self.extra_input_args)
+                                .on_hover_text("Placés avant -i, ex: -protocol_whitelist file,http,https,tcp,tls");
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("Sortie:").strong());
+                            ui.text_edit_singleline(&mut self.extra_output_args)
+                                .on_hover_text("Placés avant le fichier de sortie, ex: -map 0 -metadata \"title=Épisode 1\"");
+                        });
+                    });
                     
                     ui.add_space(12.0);
                     ui.separator();
                     ui.add_space(8.0);
                     
                     ui.horizontal(|ui| {
-                        let button_enabled = !self.input_url.is_empty() && !self.output_path.is_empty() && !self.is_downloading;
+                        let button_enabled = !self.input_url.is_empty() && !self.output_path.is_empty();
                         if ui.add_enabled(button_enabled, egui::Button::new(RichText::new("▶️ Démarrer").size(14.0)))
+                            .on_hover_text("Ajoute la tâche à la file; elle démarre dès qu'une place se libère")
                             .clicked() {
                             self.start_download();
                         }
-                        
-                        if self.is_downloading {
-                            if ui.button(RichText::new("⏹️ Arrêter").size(14.0).color(Color32::from_rgb(255, 100, 100)))
-                                .clicked() {
-                                self.stop_download();
-                            }
-                            ui.spinner();
-                            ui.label(RichText::new("Téléchargement en cours...").color(Color32::YELLOW));
-                        }
                     });
+                    // Options incohérentes: signalées au lancement, sans créer de tâche
+                    if let Some(ref error) = self.error_message {
+                        ui.label(RichText::new(format!("❌ {}", error)).color(Color32::from_rgb(255, 100, 100)));
+                    }
                 });
             
             ui.add_space(12.0);
-            
-            // Progression
-            ui.heading("📊 Progression");
-            ui.add_space(4.0);
-            
-            egui::Frame::group(ui.style())
-                .fill(Color32::from_rgb(25, 25, 30))
-                .stroke(egui::Stroke::new(1.0, Color32::from_rgb(50, 50, 60)))
-                .rounding(egui::Rounding::same(6.0))
-                .inner_margin(egui::Margin::same(12.0))
-                .show(ui, |ui| {
-                    // Afficher les erreurs (non-bloquant)
-                    if let Ok(error_guard) = self.error_message.try_lock() {
-                        if let Some(ref error) = *error_guard {
-                            ui.label(RichText::new(format!("❌ Erreur: {}", error))
-                                .color(Color32::from_rgb(255, 100, 100)));
-                            ui.add_space(8.0);
-                        }
+            self.show_jobs(ui);
+        });
+    }
+    
+    /// Liste des tâches ffmpeg: en attente, en cours et terminées
+    fn show_jobs(&mut self, ui: &mut Ui) {
+        let jobs = self.jobs.jobs();
+        ui.horizontal(|ui| {
+            ui.heading("📋 Tâches");
+            let mut max_active = self.jobs.max_active();
+            ui.label(RichText::new("Simultanées:").small());
+            if ui.add(egui::DragValue::new(&mut max_active).range(1..=8)).changed() {
+                self.jobs.set_max_active(max_active);
+            }
+            if jobs.iter().any(|j| j.status.is_finished()) && ui.small_button("🧹 Effacer les terminées").clicked() {
+                self.jobs.clear_finished();
+            }
+        });
+        ui.add_space(4.0);
+        if self.jobs.pending() > 0 {
+            // Progression reçue en arrière-plan: rafraîchir sans attendre la souris
+            ui.ctx().request_repaint_after(Duration::from_millis(500));
+        }
+        
+        egui::Frame::group(ui.style())
+            .fill(Color32::from_rgb(25, 25, 30))
+            .stroke(egui::Stroke::new(1.0, Color32::from_rgb(50, 50, 60)))
+            .rounding(egui::Rounding::same(6.0))
+            .inner_margin(egui::Margin::same(12.0))
+            .show(ui, |ui| {
+                ui.set_min_width(ui.available_width());
+                if jobs.is_empty() {
+                    ui.label(RichText::new("Les tâches lancées et leur progression apparaîtront ici")
+                        .color(Color32::GRAY));
+                }
+                for (i, job) in jobs.iter().enumerate() {
+                    if i > 0 {
+                        ui.separator();
                     }
-                    
-                    // Lire la progression (non-bloquant)
-                    let progress = match self.progress.try_lock() {
-                        Ok(guard) => guard.clone(),
-                        Err(_) => FfmpegProgressUI::default(),
-                    };
-                    
-                    if self.is_downloading {
-                        if let Some(ref time) = progress.out_time_ms {
-                            ui.label(RichText::new(format!("Temps: {}", time)).strong());
-                        }
-                        if let Some(ref bitrate) = progress.bitrate {
-                            ui.label(RichText::new(format!("Débit: {}", bitrate)).small().color(Color32::GRAY));
-                        }
-                        if let Some(ref speed) = progress.speed {
-                            ui.label(RichText::new(format!("Vitesse: {}", speed)).small().color(Color32::GRAY));
-                        }
-                    } else {
-                        ui.label(RichText::new("Les informations de progression apparaîtront ici")
-                            .color(Color32::GRAY));
+                    self.render_job(ui, job);
+                }
+            });
+    }
+    
+    fn render_job(&self, ui: &mut Ui, job: &JobInfo) {
+        let (icon, status, color) = match &job.status {
+            JobStatus::Queued => ("⏳", "En attente", Color32::GRAY),
+            JobStatus::Running => ("⬇️", "En cours", Color32::YELLOW),
+            JobStatus::Completed => ("✅", "Terminée", Color32::from_rgb(100, 220, 120)),
+            JobStatus::Failed(_) => ("❌", "Échec", Color32::from_rgb(255, 100, 100)),
+            JobStatus::Cancelled => ("⏹️", "Annulée", Color32::GRAY),
+        };
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(format!("{icon} {status}")).color(color).strong());
+            let name = job.output.file_name().map_or_else(|| job.output.display().to_string(), |n| n.to_string_lossy().into_owned());
+            ui.label(RichText::new(name).strong()).on_hover_text(&job.label);
+            if !job.status.is_finished()
+                && ui.small_button(RichText::new("⏹️ Arrêter").color(Color32::from_rgb(255, 100, 100))).clicked()
+            {
+                // La tâche voit le drapeau, tue ffmpeg et supprime son fichier temporaire
+                self.jobs.cancel(job.id);
+            }
+        });
+        
+        if let (JobStatus::Running, Some(progress)) = (&job.status, &job.progress) {
+            if let Some(percent) = progress.percent {
+                ui.add(ProgressBar::new((percent / 100.0) as f32)
+                    .fill(Color32::from_rgb(100, 200, 255))
+                    .show_percentage());
+            } else if let Some(time) = progress.out_time {
+                // Durée inconnue (direct, ffprobe absent): position atteinte
+                ui.label(RichText::new(format!("Temps: {}", format_eta(time))).strong());
+            }
+            let mut details = Vec::new();
+            match pipeline::Stage::of(progress) {
+                Some(pipeline::Stage::Fetch) => details.push("Téléchargement de la source".to_string()),
+                Some(pipeline::Stage::Remux) => details.push("Finalisation".to_string()),
+                None => {}
+            }
+            if progress.attempt > 1 {
+                details.push(format!("Tentative {}", progress.attempt));
+            }
+            if let Some(eta) = progress.eta {
+                details.push(format!("⏱ {}", format_eta(eta)));
+            }
+            if let Some(bitrate) = progress.bitrate_kbps {
+                details.push(format!("Débit: {:.0} kbit/s", bitrate));
+            }
+            if let Some(speed) = progress.speed {
+                details.push(format!("Vitesse: {:.2}x", speed));
+            }
+            if let Some(size) = progress.total_size {
+                details.push(format!("{:.1} Mio écrits", size as f64 / (1024.0 * 1024.0)));
+            }
+            if let Some(frames) = progress.frames.filter(|&f| f > 0) {
+                details.push(format!("{frames} images"));
+            }
+            if !details.is_empty() {
+                ui.label(RichText::new(details.join(" · ")).small().color(Color32::GRAY));
+            }
+        }
+        
+        if let JobStatus::Failed(error) = &job.status {
+            ui.label(RichText::new(error).color(Color32::from_rgb(255, 100, 100)));
+            if !job.stderr.is_empty() {
+                ui.push_id(job.id, |ui| {
+                    ui.collapsing("Détails (sortie de ffmpeg)", |ui| {
+                        egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                            for line in &job.stderr {
+                                ui.label(RichText::new(line).monospace().small().color(Color32::GRAY));
+                            }
+                        });
+                    });
+                });
+            }
+        }
+    }
+    
+    /// Sélecteur de qualité pour une playlist HLS maîtresse
+    fn show_variant_picker(&mut self, ui: &mut Ui) {
+        if dash::is_manifest_url(&self.input_url) {
+            self.show_dash_picker(ui);
+            return;
+        }
+        let listed = self.listed_variants();
+        // Variante choisie pour une autre URL ou une liste rechargée: retour à "Auto"
+        if self.selected_variant.is_some_and(|i| i >= listed.len()) {
+            self.selected_variant = None;
+        }
+        let fetching = self.fetching_variants.load(Ordering::Relaxed);
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Qualité:").strong());
+            let selected_text = match self.selected_variant {
+                Some(i) => listed[i].to_string(),
+                None => "Auto (meilleure)".to_string(),
+            };
+            egui::ComboBox::from_id_source("ffmpeg_variant")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.selected_variant, None, "Auto (meilleure)");
+                    for (i, variant) in listed.iter().enumerate() {
+                        ui.selectable_value(&mut self.selected_variant, Some(i), variant.to_string());
                     }
                 });
+            self.show_fetch_button(ui, fetching);
+            if fetching {
+                ui.spinner();
+            } else if let Ok(guard) = self.variants.try_lock() {
+                match guard.as_ref() {
+                    Some((url, Err(e))) if *url == self.input_url => {
+                        ui.label(RichText::new(e).small().color(Color32::from_rgb(255, 100, 100)));
+                    }
+                    Some((url, Ok(variants))) if *url == self.input_url && variants.is_empty() => {
+                        ui.label(RichText::new("Une seule qualité disponible").small().color(Color32::GRAY));
+                    }
+                    _ => {}
+                }
+            }
+        });
+    }
+    
+    /// Sélecteurs de représentations vidéo et audio pour un manifeste DASH
+    fn show_dash_picker(&mut self, ui: &mut Ui) {
+        let manifest = self.listed_dash().unwrap_or_default();
+        let fetching = self.fetching_variants.load(Ordering::Relaxed);
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Vidéo:").strong());
+            dash_combo(ui, "ffmpeg_dash_video", &manifest.video, &mut self.selected_dash_video);
+            ui.label(RichText::new("Audio:").strong());
+            dash_combo(ui, "ffmpeg_dash_audio", &manifest.audio, &mut self.selected_dash_audio);
+            self.show_fetch_button(ui, fetching);
+            if fetching {
+                ui.spinner();
+            } else if let Ok(guard) = self.dash_manifest.try_lock()
+                && let Some((url, Err(e))) = guard.as_ref()
+                && *url == self.input_url
+            {
+                ui.label(RichText::new(e).small().color(Color32::from_rgb(255, 100, 100)));
+            }
+        });
+    }
+    
+    fn show_fetch_button(&mut self, ui: &mut Ui, fetching: bool) {
+        if ui.add_enabled(!self.input_url.is_empty() && !fetching, egui::Button::new("🔍 Lister les qualités"))
+            .on_hover_text("Lit la playlist maîtresse (m3u8) ou le manifeste DASH (mpd) pour proposer ses pistes")
+            .clicked() {
+            self.fetch_variants();
+        }
+    }
+    
+    /// Manifeste DASH lu pour l'URL actuellement saisie
+    fn listed_dash(&self) -> Option<DashManifest> {
+        match self.dash_manifest.try_lock().ok().as_deref() {
+            Some(Some((url, Ok(manifest)))) if *url == self.input_url => Some(manifest.clone()),
+            _ => None,
+        }
+    }
+    
+    /// Variantes listées pour l'URL actuellement saisie (vide sinon)
+    fn listed_variants(&self) -> Vec<HlsVariant> {
+        match self.variants.try_lock().ok().as_deref() {
+            Some(Some((url, Ok(variants)))) if *url == self.input_url => variants.clone(),
+            _ => Vec::new(),
+        }
+    }
+    
+    /// Lit la playlist maîtresse ou le manifeste DASH en arrière-plan pour remplir les sélecteurs
+    fn fetch_variants(&mut self) {
+        self.selected_variant = None;
+        self.selected_dash_video = None;
+        self.selected_dash_audio = None;
+        self.fetching_variants.store(true, Ordering::Relaxed);
+        let url = self.input_url.clone();
+        let variants = self.variants.clone();
+        let dash_manifest = self.dash_manifest.clone();
+        let fetching = self.fetching_variants.clone();
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+            if dash::is_manifest_url(&url) {
+                let result = rt.block_on(dash::fetch_manifest(&url)).map_err(|e| e.to_string());
+                *dash_manifest.blocking_lock() = Some((url, result));
+            } else {
+                let result = rt.block_on(ffmpeg::hls::list_variants(&url)).map_err(|e| e.to_string());
+                *variants.blocking_lock() = Some((url, result));
+            }
+            fetching.store(false, Ordering::Relaxed);
+        });
+    }
+    
+    /// Bouton d'inspection et résumé du média pour l'URL saisie
+    fn show_media_info(&mut self, ui: &mut Ui) {
+        let probing = self.probing.load(Ordering::Relaxed);
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!self.input_url.is_empty() && !probing, egui::Button::new("ℹ️ Inspecter"))
+                .on_hover_text("Durée, pistes et codecs du média (ffprobe)")
+                .clicked() {
+                self.probe_media();
+            }
+            if probing {
+                ui.spinner();
+            }
+        });
+        let Ok(guard) = self.media_info.try_lock() else { return };
+        match guard.as_ref() {
+            Some((url, Ok(info))) if *url == self.input_url => {
+                let mut summary = vec![info.duration.map_or("direct".to_string(), format_eta)];
+                if let Some((w, h)) = info.resolution() {
+                    summary.push(format!("{w}×{h}"));
+                }
+                if let Some(bitrate) = info.bitrate {
+                    summary.push(format!("{:.1} Mb/s", bitrate as f64 / 1_000_000.0));
+                }
+                summary.extend(info.format_name.clone());
+                ui.label(RichText::new(summary.join(" · ")).strong());
+                for stream in &info.streams {
+                    ui.label(RichText::new(describe_stream(stream)).small().color(Color32::GRAY));
+                }
+            }
+            Some((url, Err(e))) if *url == self.input_url => {
+                ui.label(RichText::new(e).small().color(Color32::from_rgb(255, 100, 100)));
+            }
+            _ => {}
+        }
+    }
+    
+    /// Avertissement si ffmpeg est introuvable, avec installation d'une version statique
+    fn show_missing_ffmpeg(&mut self, ui: &mut Ui) {
+        if let Some(Ok(path)) = self.install_result.try_lock().ok().and_then(|mut r| r.take_if(|r| r.is_ok())) {
+            self.ffmpeg_path = Some(path);
+        }
+        if self.ffmpeg_path.is_some() {
+            return;
+        }
+        let installing = self.installing.load(Ordering::Relaxed);
+        ui.label(RichText::new(ffmpeg::binary::missing_message(ffmpeg::binary::Tool::Ffmpeg)).color(Color32::from_rgb(255, 180, 80)));
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!installing, egui::Button::new("⬇️ Installer ffmpeg"))
+                .on_hover_text("Télécharge une version statique dans le dossier de l'application")
+                .clicked()
+            {
+                self.install_ffmpeg();
+            }
+            if ui.add_enabled(!installing, egui::Button::new("🔄 Rechercher à nouveau")).clicked() {
+                self.ffmpeg_path = ffmpeg::binary::locate(ffmpeg::binary::Tool::Ffmpeg);
+            }
+            if installing {
+                ui.spinner();
+                ui.label("Installation...");
+            }
+        });
+        if let Ok(guard) = self.install_result.try_lock()
+            && let Some(Err(e)) = guard.as_ref()
+        {
+            ui.label(RichText::new(e).small().color(Color32::from_rgb(255, 100, 100)));
+        }
+        ui.separator();
+    }
+    
+    /// Installe ffmpeg en arrière-plan (`binary::install_static_build`)
+    /// Choix de l'accélération matérielle; lance la détection au premier affichage
+    fn show_hwaccel(&mut self, ui: &mut Ui) {
+        let detected = self.hwaccels.try_lock().ok().and_then(|guard| guard.clone());
+        if detected.is_none() && self.ffmpeg_path.is_some() && !self.detecting_hwaccels.swap(true, Ordering::Relaxed) {
+            let hwaccels = self.hwaccels.clone();
+            std::thread::spawn(move || {
+                let accels = match tokio::runtime::Runtime::new() {
+                    Ok(rt) => rt.block_on(ffmpeg::hwaccel::available()).unwrap_or_else(|e| {
+                        tracing::warn!(error = %e, "Accélérations matérielles non détectées");
+                        Vec::new()
+                    }),
+                    Err(_) => Vec::new(),
+                };
+                *hwaccels.blocking_lock() = Some(accels);
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Accélération:").strong());
+            let Some(accels) = detected else {
+                ui.spinner();
+                ui.label(RichText::new("Détection...").color(Color32::GRAY));
+                return;
+            };
+            egui::ComboBox::from_id_source("ffmpeg_hwaccel")
+                .selected_text(self.hwaccel.map_or("Aucune (logiciel)", HwAccel::label))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.hwaccel, None, "Aucune (logiciel)");
+                    for accel in accels {
+                        ui.selectable_value(&mut self.hwaccel, Some(accel), accel.label());
+                    }
+                })
+                .response
+                .on_hover_text("Méthodes que ffmpeg sait utiliser (ffmpeg -hwaccels); le GPU doit aussi être présent");
+        });
+    }
+
+    fn install_ffmpeg(&mut self) {
+        self.installing.store(true, Ordering::Relaxed);
+        let installing = self.installing.clone();
+        let install_result = self.install_result.clone();
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+            let result = rt.block_on(ffmpeg::binary::install_static_build()).map_err(|e| e.to_string());
+            *install_result.blocking_lock() = Some(result);
+            installing.store(false, Ordering::Relaxed);
+        });
+    }
+    
+    /// Lance ffprobe en arrière-plan sur l'URL saisie
+    fn probe_media(&mut self) {
+        self.probing.store(true, Ordering::Relaxed);
+        let url = self.input_url.clone();
+        let media_info = self.media_info.clone();
+        let probing = self.probing.clone();
+        // Mêmes en‑têtes que le téléchargement, sinon la sonde prend le 403
+        let input_args = DownloadOptions {
+            user_agent: Some(self.user_agent.trim().to_string()).filter(|ua| !ua.is_empty()),
+            headers: parse_headers(&self.http_headers),
+            ..Default::default()
+        }
+        .http_input_args();
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+            let result = rt
+                .block_on(ffprobe::probe(&url, &input_args))
+                .map_err(|e| e.to_string());
+            *media_info.blocking_lock() = Some((url, result));
+            probing.store(false, Ordering::Relaxed);
         });
     }
     
@@ -308,104 +883,166 @@ impl FfmpegTab {
             return;
         }
         
-        // Sauvegarder le chemin dans l'historique
-        self.save_path_to_history(self.output_path.clone());
-        
-        self.is_downloading = true;
-        self.cancel_flag.store(false, Ordering::Relaxed);
-        
-        // Réinitialiser les erreurs (non-bloquant)
-        if let Ok(mut guard) = self.error_message.try_lock() {
-            *guard = None;
+        let options = DownloadOptions {
+            stall_timeout: Duration::from_secs(self.stall_timeout_secs),
+            auto_restart: self.auto_restart,
+            max_restarts: self.max_restarts as usize,
+            extract_subtitles: self.extract_subtitles,
+            variant: self.selected_variant.and_then(|i| self.listed_variants().get(i).cloned()),
+            dash: self.listed_dash().map(|m| m.select(self.selected_dash_video, self.selected_dash_audio)),
+            audio_only: self.audio_only.then_some(self.audio_format),
+            extra_input_args: split_args(&self.extra_input_args),
+            extra_output_args: split_args(&self.extra_output_args),
+            user_agent: Some(self.user_agent.trim().to_string()).filter(|ua| !ua.is_empty()),
+            headers: parse_headers(&self.http_headers),
+            subtitles: self.subtitle_mode,
+            external_subtitles: self.external_subtitles.split_whitespace().map(ExternalSubtitle::new).collect(),
+            preset: self.preset.filter(|_| !self.audio_only),
+            hwaccel: self.hwaccel,
+            segmented_hls: self.segmented_hls,
+            resume_vod: self.resume_vod,
+            metadata: metadata_options(self),
+            thumbnails: self.thumbnails,
+            read_rate: self.read_rate,
+        };
+        let record = self.record_live.then(|| RecordOptions {
+            max_duration: (self.record_max_minutes > 0).then(|| Duration::from_secs(self.record_max_minutes as u64 * 60)),
+            stop_at: self.record_stop_enabled.then(|| ffmpeg::record::next_clock_time(self.record_stop_hour, self.record_stop_minute)).flatten(),
+            segment_every: (self.record_segment_minutes > 0).then(|| Duration::from_secs(self.record_segment_minutes as u64 * 60)),
+        });
+        // Arguments incohérents: signalés tout de suite, sans lancer ffmpeg
+        if let Err(e) = options.validate() {
+            self.error_message = Some(e.to_string());
+            return;
         }
+        self.error_message = None;
         
-        let progress = self.progress.clone();
-        let error_msg = self.error_message.clone();
-        let cancel_flag = self.cancel_flag.clone();
-        let input_url = self.input_url.clone();
-        let output_path = PathBuf::from(&self.output_path);
-        let stall_timeout = Duration::from_secs(self.stall_timeout_secs);
-        let max_restarts = self.max_restarts as usize;
-        let auto_restart = self.auto_restart;
-        
-        // Créer un canal pour les mises à jour de progression
-        let (progress_tx, mut progress_rx) = mpsc::unbounded_channel::<FfmpegProgressUI>();
+        // Sauvegarder le chemin dans l'historique
+        self.save_path_to_history(self.output_path.clone());
         
-        // Lancer le téléchargement dans un thread séparé
-        let handle = std::thread::spawn(move || {
-            let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
-            rt.block_on(async move {
-                // Tâche pour traiter les mises à jour de progression (spawnée dans le runtime)
-                let progress_clone = progress.clone();
-                let progress_task = tokio::spawn(async move {
-                    while let Some(prog_ui) = progress_rx.recv().await {
-                        if let Ok(mut guard) = progress_clone.try_lock() {
-                            *guard = prog_ui;
-                        }
-                    }
-                });
-                
-                let options = DownloadOptions {
-                    stall_timeout,
-                    auto_restart,
-                    max_restarts,
-                };
-                
-                let progress_tx_clone = progress_tx.clone();
-                let error_msg_clone = error_msg.clone();
-                
-                let result = ffmpeg::download_with_options(
-                    &input_url,
-                    &output_path,
-                    options,
-                    Some(move |prog: &FfmpegProgress| {
-                        // Envoyer la progression via le canal au lieu de bloquer
-                        let prog_ui = FfmpegProgressUI {
-                            out_time_ms: prog.fields.get("out_time_ms").cloned(),
-                            bitrate: prog.fields.get("bitrate").cloned(),
-                            speed: prog.fields.get("speed").cloned(),
-                        };
-                        let _ = progress_tx_clone.send(prog_ui);
-                    }),
-                ).await;
-                
-                // Fermer le canal pour signaler la fin
-                drop(progress_tx);
-                
-                match result {
-                    Ok(_) => {
-                        // Succès - réinitialiser la progression (non-bloquant)
-                        if let Ok(mut guard) = progress.try_lock() {
-                            *guard = FfmpegProgressUI::default();
-                        }
-                    }
-                    Err(e) => {
-                        // Erreur (non-bloquant)
-                        if let Ok(mut guard) = error_msg_clone.try_lock() {
-                            *guard = Some(e.to_string());
-                        }
-                    }
-                }
-                
-                // Attendre que la tâche de progression se termine
-                let _ = progress_task.await;
-            });
+        let parts: Vec<String> = self.input_url.split_whitespace().map(str::to_string).collect();
+        let input = match record {
+            Some(record) => JobInput::Record(self.input_url.clone(), record),
+            // Épisode en plusieurs parties
+            None if parts.len() > 1 => JobInput::Concat(parts),
+            None if self.two_stage => JobInput::Fetch(self.input_url.clone()),
+            None => JobInput::Download(self.input_url.clone()),
+        };
+        self.jobs.submit(FfmpegJob { input, output: PathBuf::from(&self.output_path), options });
+    }
+}
+
+/// Liste déroulante des représentations d'un type; `None` = meilleur débit
+fn dash_combo(ui: &mut Ui, id: &str, representations: &[DashRepresentation], selected: &mut Option<usize>) {
+    if selected.is_some_and(|i| i >= representations.len()) {
+        *selected = None;
+    }
+    egui::ComboBox::from_id_source(id)
+        .selected_text(selected.map_or("Auto (meilleure)".to_string(), |i| representations[i].label()))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(selected, None, "Auto (meilleure)");
+            for (i, representation) in representations.iter().enumerate() {
+                ui.selectable_value(selected, Some(i), representation.label());
+            }
         });
-        
-        self.task_handle = Some(handle);
+}
+
+/// `#1 audio aac · 2 canaux · fre`
+fn describe_stream(stream: &ffprobe::StreamInfo) -> String {
+    let kind = match stream.kind {
+        StreamKind::Video => "vidéo",
+        StreamKind::Audio => "audio",
+        StreamKind::Subtitle => "sous‑titres",
+        StreamKind::Data => "données",
+        StreamKind::Other => "autre",
+    };
+    let mut parts = vec![format!("#{} {} {}", stream.index, kind, stream.codec.as_deref().unwrap_or("?"))];
+    if let Some((w, h)) = stream.resolution {
+        parts.push(format!("{w}×{h}"));
     }
-    
-    fn stop_download(&mut self) {
-        self.cancel_flag.store(true, Ordering::Relaxed);
-        self.is_downloading = false;
-        
-        // Note: FFmpeg ne peut pas être arrêté facilement une fois lancé
-        // On peut améliorer ça en ajoutant un mécanisme d'annulation dans le downloader FFmpeg
-        if let Some(handle) = self.task_handle.take() {
-            // Ne pas bloquer - laisser le thread se terminer en arrière-plan
-            std::thread::spawn(move || {
-                let _ = handle.join();
-            });
+    if let Some(channels) = stream.channels {
+        parts.push(format!("{channels} canaux"));
+    }
+    parts.extend(stream.language.clone());
+    parts.join(" · ")
+}
+
+/// Découpe une ligne d'arguments sur les espaces; les guillemets doubles groupent une valeur
+/// contenant des espaces (`-metadata "title=Épisode 1"`)
+fn split_args(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut started = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    args.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            }
+            c => {
+                current.push(c);
+                started = true;
+            }
         }
     }
+    if started {
+        args.push(current);
+    }
+    args
+}
+
+/// Libellé d'une cadence de lecture dans l'onglet
+/// Métadonnées saisies; un épisode non renseigné est tiré du titre (« Episode 05 - ... »)
+fn metadata_options(tab: &FfmpegTab) -> MetadataOptions {
+    let scraped = MetadataOptions::for_scraped_episode(&tab.metadata_show, "", &tab.metadata_title);
+    MetadataOptions {
+        copy_metadata: tab.copy_metadata,
+        copy_chapters: tab.copy_chapters,
+        season: (tab.metadata_season > 0).then_some(tab.metadata_season),
+        episode: (tab.metadata_episode > 0).then_some(tab.metadata_episode).or(scraped.episode),
+        ..scraped
+    }
+}
+
+fn read_rate_label(rate: Option<ReadRate>) -> String {
+    match rate {
+        None => "Au plus vite".into(),
+        Some(ReadRate::Speed(1.0)) => "Temps réel (-re)".into(),
+        Some(ReadRate::Speed(speed)) => format!("×{speed} le temps réel"),
+        Some(ReadRate::GlobalLimit) => "Limite de bande passante globale".into(),
+        Some(ReadRate::BytesPerSec(_)) => "Débit fixe".into(),
+    }
+}
+
+/// En‑têtes saisis, un `Nom: valeur` par ligne; les lignes sans `:` sont ignorées
+fn parse_headers(text: &str) -> BTreeMap<String, String> {
+    text.lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .filter(|(name, _)| !name.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_args_keeps_quoted_values() {
+        assert_eq!(split_args("  -map 0  -metadata \"title=Épisode 1\" "), vec!["-map", "0", "-metadata", "title=Épisode 1"]);
+        assert_eq!(split_args("-metadata comment=\"\""), vec!["-metadata", "comment="]);
+        assert!(split_args("   ").is_empty());
+
+        let headers = parse_headers("Referer: https://site.example/ep1\n\n  Cookie:a=1; b=2 \nnimporte quoi");
+        assert_eq!(headers.get("Referer").map(String::as_str), Some("https://site.example/ep1"));
+        assert_eq!(headers.get("Cookie").map(String::as_str), Some("a=1; b=2"));
+        assert_eq!(headers.len(), 2);
+    }
 }
@@ -12,12 +12,14 @@
 //! - `scraper.rs`: Composant UI pour le scraper FZTV
 //! - `sniffer.rs`: Composant UI pour le sniffer réseau
 //! - `ffmpeg.rs`: Composant UI pour les téléchargements FFmpeg
+//! - `display.rs`: Aides d'affichage partagées (troncature des URLs)
 
 mod app;
 mod downloads;
 mod scraper;
 mod sniffer;
 mod ffmpeg;
+mod display;
 
 pub use app::ScrapesApp;
 
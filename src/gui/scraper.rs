@@ -1,24 +1,84 @@
 //! Composant UI pour le scraper FZTV.
 //!
 //! Permet de:
-//! - Saisir l'URL de base et l'URL de la série
-//! - Lancer le scraping des saisons/épisodes
-//! - Visualiser les résultats avec les liens de téléchargement
+//! - Saisir l'URL de base et l'URL de la série, ou chercher la série par son titre
+//! - Lancer le scraping des saisons/épisodes et suivre sa progression (barre et journal)
+//! - Visualiser les résultats avec les liens de téléchargement, et les exporter (JSON, CSV, M3U)
+//! - Rouvrir les sessions enregistrées après chaque scraping réussi
+//! - Télécharger une saison entière avec ffmpeg (`ffmpeg::download_batch`)
 
-use egui::{Ui, RichText, Color32};
+use egui::{Ui, RichText, Color32, ProgressBar};
+use std::path::PathBuf;
 use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
-use tokio::sync::Mutex;
-use crate::scrapers::{FztvScraper, Season};
+use tokio::sync::{mpsc, Mutex};
+use tokio_util::sync::CancellationToken;
+use crate::downloader::{self, sanitize_filename};
+use crate::ffmpeg::{self, BatchProgress, DownloadOptions};
+use crate::scrapers::{FztvScraper, ScraperRegistry, Season, SiteScraper};
+use crate::scrapers::{cache, politeness, rotation};
+use crate::scrapers::export::{self, ExportFormat};
+use crate::scrapers::session::{self, ScrapeSession};
+use crate::scrapers::episode::show_name_from_url;
+use crate::scrapers::incremental::{self, ResolvedLink, ScrapeDiff};
+use crate::scrapers::progress::{ScrapeEvent, ScrapeProgress};
+use crate::scrapers::site::SearchResult;
+use crate::scrapers::fzscrape::fztv_scraper::{enrich_options_from_config, preferred_link, Episode};
+use super::display::url_label;
+
+/// Lignes gardées dans le journal du scraping
+const MAX_LOG_LINES: usize = 200;
 
 /// Onglet du scraper FZTV
 pub struct ScraperTab {
     base_url: String,
     series_url: String,
+    /// Nom de la série dans les fichiers téléchargés (`Serie.S02E05.mp4`); vide: tiré de l'URL
+    series_title: String,
+    use_browser_fallback: bool,
+    /// Comparer le scraping à la session enregistrée de la série (`scrapers::incremental`)
+    incremental: bool,
+    /// Résoudre les liens réels après le scraping (`SiteScraper::enrich`)
+    resolve_links: bool,
     is_scraping: bool,
-    cancel_flag: Arc<AtomicBool>,
+    /// Arrêt du scraping en cours (un jeton neuf par lancement)
+    cancel: CancellationToken,
     results: Arc<Mutex<Vec<Season>>>,
     error_message: Arc<Mutex<Option<String>>>,
     task_handle: Option<std::thread::JoinHandle<()>>,
+    /// Étapes du scraping en cours, cumulées et journalisées à chaque image
+    progress_rx: Option<mpsc::UnboundedReceiver<ScrapeEvent>>,
+    scrape_progress: ScrapeProgress,
+    scrape_log: Vec<String>,
+    /// Dossier des saisons téléchargées (un sous‑dossier par saison)
+    output_dir: String,
+    /// Dernier téléchargement de saison lancé, et son drapeau d'arrêt
+    season_download: Arc<std::sync::Mutex<Option<SeasonDownload>>>,
+    season_cancel: Arc<AtomicBool>,
+    /// URL de la saison en cours d'actualisation (`SiteScraper::list_episodes`)
+    season_refresh: Arc<std::sync::Mutex<Option<String>>>,
+    /// Recherche de série sur le site de l'URL de base
+    search_query: String,
+    searching: Arc<AtomicBool>,
+    search_results: Arc<std::sync::Mutex<Vec<SearchResult>>>,
+    /// Issue du dernier export des résultats
+    export_status: Arc<std::sync::Mutex<Option<String>>>,
+    /// Sessions enregistrées (relues au premier affichage et après chaque enregistrement)
+    sessions: Arc<std::sync::Mutex<Vec<ScrapeSession>>>,
+    sessions_loaded: bool,
+    /// Écarts du dernier scraping avec la session précédente
+    last_diff: Arc<std::sync::Mutex<Option<ScrapeDiff>>>,
+}
+
+/// Téléchargement d'une saison: résolution des liens, puis lot ffmpeg
+#[derive(Debug, Clone, Default)]
+struct SeasonDownload {
+    season: String,
+    /// Liens réels en cours de résolution (avant le lot)
+    resolving: bool,
+    /// Épisodes sans lien exploitable
+    skipped: usize,
+    progress: BatchProgress,
+    done: bool,
 }
 
 impl Default for ScraperTab {
@@ -26,17 +86,43 @@ impl Default for ScraperTab {
         Self {
             base_url: "https://www.fztvseries.mobi/".to_string(),
             series_url: String::new(),
+            series_title: String::new(),
+            use_browser_fallback: false,
+            incremental: true,
+            resolve_links: false,
             is_scraping: false,
-            cancel_flag: Arc::new(AtomicBool::new(false)),
+            cancel: CancellationToken::new(),
             results: Arc::new(Mutex::new(Vec::new())),
             error_message: Arc::new(Mutex::new(None)),
             task_handle: None,
+            progress_rx: None,
+            scrape_progress: ScrapeProgress::default(),
+            scrape_log: Vec::new(),
+            output_dir: std::env::var("USERPROFILE")
+                .or_else(|_| std::env::var("HOME"))
+                .map(|home| PathBuf::from(home).join("Downloads").to_string_lossy().into_owned())
+                .unwrap_or_else(|_| ".".to_string()),
+            season_download: Arc::default(),
+            season_cancel: Arc::new(AtomicBool::new(false)),
+            season_refresh: Arc::default(),
+            search_query: String::new(),
+            searching: Arc::new(AtomicBool::new(false)),
+            search_results: Arc::default(),
+            export_status: Arc::default(),
+            sessions: Arc::default(),
+            sessions_loaded: false,
+            last_diff: Arc::default(),
         }
     }
 }
 
 impl ScraperTab {
     pub fn show(&mut self, ui: &mut Ui) {
+        self.poll_progress();
+        if !self.sessions_loaded {
+            self.sessions_loaded = true;
+            self.refresh_sessions();
+        }
         ui.vertical(|ui| {
             ui.heading("🔍 Scraper FZTV");
             ui.separator();
@@ -58,12 +144,41 @@ impl ScraperTab {
                     
                     ui.add_space(4.0);
                     
+                    self.show_search(ui);
+                    
+                    ui.add_space(4.0);
+                    
                     ui.horizontal(|ui| {
                         ui.label(RichText::new("URL de la série:").strong());
                         ui.text_edit_singleline(&mut self.series_url)
                             .on_hover_text("URL complète de la page de la série");
                     });
                     
+                    ui.add_space(4.0);
+                    
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new("Nom de la série:").strong());
+                        ui.text_edit_singleline(&mut self.series_title)
+                            .on_hover_text("Nom des fichiers téléchargés (Serie.S02E05.mp4); vide: tiré de l'URL de la série");
+                    });
+                    
+                    ui.add_space(4.0);
+                    
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new("Dossier de sortie:").strong());
+                        ui.text_edit_singleline(&mut self.output_dir)
+                            .on_hover_text("Les saisons téléchargées y sont rangées, une par sous‑dossier");
+                    });
+                    
+                    ui.add_space(4.0);
+                    
+                    ui.checkbox(&mut self.use_browser_fallback, "🌐 Repli navigateur (pages JavaScript)")
+                        .on_hover_text("Si aucun lien n'est trouvé dans le HTML, charger la page dans Chromium et capturer les URLs média du trafic réseau");
+                    ui.checkbox(&mut self.resolve_links, "🔗 Résoudre les liens réels")
+                        .on_hover_text("Après le scraping, chercher l'URL réelle de la qualité préférée de chaque épisode (de toutes avec resolve_all_qualities); les liens déjà résolus sont gardés");
+                    ui.checkbox(&mut self.incremental, "🔁 Incrémental (session enregistrée)")
+                        .on_hover_text("Reprendre de la session de la série les saisons sans nouvel épisode, liens résolus compris, et marquer NEW les épisodes parus depuis");
+                    
                     ui.add_space(12.0);
                     
                     ui.horizontal(|ui| {
@@ -86,6 +201,10 @@ impl ScraperTab {
             
             ui.add_space(12.0);
             
+            self.show_sessions(ui);
+            self.show_scrape_progress(ui);
+            self.show_season_download(ui);
+            
             // Résultats avec scroll
             ui.heading("📋 Résultats");
             ui.add_space(4.0);
@@ -116,11 +235,35 @@ impl ScraperTab {
                                 .color(Color32::DARK_GRAY));
                         });
                     } else {
-                        ui.label(RichText::new(format!("{} saison(s) trouvée(s)", results.len()))
-                            .color(Color32::GRAY)
-                            .small());
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(format!("{} saison(s) trouvée(s)", results.len()))
+                                .color(Color32::GRAY)
+                                .small());
+                            for format in ExportFormat::ALL {
+                                if ui.small_button(format!("💾 {}", format.label()))
+                                    .on_hover_text(match format {
+                                        ExportFormat::Json => "Saisons, épisodes et liens complets",
+                                        ExportFormat::Csv => "Une ligne par lien",
+                                        ExportFormat::M3u => "Liste de lecture des liens résolus",
+                                    })
+                                    .clicked()
+                                {
+                                    self.start_export(format, results.clone());
+                                }
+                            }
+                            if let Some(diff) = self.last_diff.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+                                ui.label(RichText::new(format!("🆕 {} nouvel(s) épisode(s), {} saison(s) changée(s)", diff.new_episodes(), diff.changed_seasons()))
+                                    .small()
+                                    .color(Color32::from_rgb(120, 220, 120)));
+                            }
+                            if let Some(status) = self.export_status.lock().unwrap_or_else(|e| e.into_inner()).as_deref() {
+                                ui.label(RichText::new(status).small().color(Color32::GRAY));
+                            }
+                        });
                         ui.add_space(4.0);
                         
+                        let season_running = self.season_download_running();
+                        let refreshing = self.season_refresh.lock().unwrap_or_else(|e| e.into_inner()).clone();
                         for season in results {
                             egui::Frame::group(ui.style())
                                 .fill(Color32::from_rgb(25, 25, 30))
@@ -128,7 +271,26 @@ impl ScraperTab {
                                 .rounding(egui::Rounding::same(6.0))
                                 .inner_margin(egui::Margin::same(12.0))
                                 .show(ui, |ui| {
-                                    ui.label(RichText::new(&season.name).strong());
+                                    ui.horizontal(|ui| {
+                                        ui.label(RichText::new(&season.name).strong());
+                                        let enabled = !season_running && season.episodes.iter().any(|e| !e.download_links.is_empty());
+                                        if ui.add_enabled(enabled, egui::Button::new("⬇️ Télécharger la saison"))
+                                            .on_hover_text("Télécharge chaque épisode avec ffmpeg, quelques‑uns à la fois")
+                                            .clicked()
+                                        {
+                                            self.start_season_download(&season);
+                                        }
+                                        let enabled = !self.is_scraping && refreshing.is_none();
+                                        if ui.add_enabled(enabled, egui::Button::new("🔄 Actualiser"))
+                                            .on_hover_text("Relit les épisodes de cette saison seulement et marque les nouveaux")
+                                            .clicked()
+                                        {
+                                            self.refresh_season(&season);
+                                        }
+                                        if refreshing.as_deref() == Some(season.url.as_str()) {
+                                            ui.spinner();
+                                        }
+                                    });
                                     ui.label(RichText::new(format!("{} épisode(s)", season.episodes.len()))
                                         .small()
                                         .color(Color32::GRAY));
@@ -136,13 +298,19 @@ impl ScraperTab {
                                     if !season.episodes.is_empty() {
                                         ui.collapsing("Épisodes", |ui| {
                                             for episode in &season.episodes {
-                                                ui.label(RichText::new(&episode.name).small());
+                                                ui.horizontal(|ui| {
+                                                    if episode.is_new {
+                                                        ui.label(RichText::new("NEW").small().strong().color(Color32::from_rgb(120, 220, 120)));
+                                                    }
+                                                    ui.label(RichText::new(&episode.name).small());
+                                                });
                                                 if !episode.download_links.is_empty() {
                                                     ui.indent("links", |ui| {
                                                         for link in &episode.download_links {
-                                                            ui.label(RichText::new(format!("{}: {}", link.quality, link.url))
-                                                                .small()
-                                                                .color(Color32::from_rgb(100, 200, 255)));
+                                                            ui.horizontal(|ui| {
+                                                                ui.label(RichText::new(format!("{}:", link.quality)).small());
+                                                                url_label(ui, &link.url, Color32::from_rgb(100, 200, 255));
+                                                            });
                                                         }
                                                     });
                                                 }
@@ -163,30 +331,74 @@ impl ScraperTab {
         }
         
         self.is_scraping = true;
-        self.cancel_flag.store(false, Ordering::Relaxed);
+        self.cancel = CancellationToken::new();
+        let (progress_tx, progress_rx) = mpsc::unbounded_channel();
+        self.progress_rx = Some(progress_rx);
+        self.scrape_progress = ScrapeProgress::default();
+        self.scrape_log.clear();
         
         // Réinitialiser les résultats
         let results = self.results.clone();
         let error_msg = self.error_message.clone();
-        let cancel_flag = self.cancel_flag.clone();
+        let sessions = self.sessions.clone();
+        let last_diff = self.last_diff.clone();
+        *last_diff.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        let incremental = self.incremental;
+        let resolve_links = self.resolve_links;
+        let cancel = self.cancel.clone();
         let base_url = self.base_url.clone();
         let series_url = self.series_url.clone();
+        let use_browser_fallback = self.use_browser_fallback;
         
         // Lancer le scraping dans un thread séparé
         let handle = std::thread::spawn(move || {
             let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
             rt.block_on(async move {
-                let scraper = FztvScraper::new(base_url);
+                let config = downloader::load_config();
+                let fztv = FztvScraper::new(base_url)
+                    .with_browser_fallback(use_browser_fallback)
+                    .with_options(politeness::scraper_options_from_config(&config))
+                    .with_cache(cache::page_cache_from_config(&config))
+                    .with_rotation(rotation::rotation_from_config(&config))
+                    .with_enrich_options(enrich_options_from_config(&config))
+                    .with_cancel(cancel.clone())
+                    .with_progress(progress_tx);
+                let registry = ScraperRegistry::from_config(&config, fztv);
                 
-                // Vérifier le flag d'annulation périodiquement
-                let result = if cancel_flag.load(Ordering::Relaxed) {
-                    Err(anyhow::anyhow!("Annulé par l'utilisateur"))
-                } else {
-                    scraper.scrape_all(&series_url).await
-                };
+                let store = session::session_store_from_config(&config);
+                
+                // Le scraper FZTV s'arrête de lui‑même à l'annulation, requête en cours comprise
+                let result = async {
+                    if cancel.is_cancelled() {
+                        anyhow::bail!("Annulé par l'utilisateur");
+                    }
+                    let scraper = registry
+                        .for_url(&series_url)
+                        .ok_or_else(|| anyhow::anyhow!("Aucun scraper pour ce domaine (URL de base ou dossier des sites)"))?;
+                    tracing::info!(site = scraper.name(), "Scraper choisi pour la série");
+                    let previous = incremental.then(|| store.load(&session::session_id(&series_url))).flatten();
+                    let seasons = match &previous {
+                        Some(previous) => {
+                            let seasons = scraper.list_seasons_since(&series_url, &previous.seasons).await?;
+                            let (seasons, diff) = incremental::merge_with_previous(&previous.seasons, seasons);
+                            *last_diff.lock().unwrap_or_else(|e| e.into_inner()) = Some(diff);
+                            seasons
+                        }
+                        None => scraper.list_seasons(&series_url).await?,
+                    };
+                    match resolve_links {
+                        true => scraper.enrich(seasons).await,
+                        false => Ok(seasons),
+                    }
+                }
+                .await;
                 
                 match result {
                     Ok(seasons) => {
+                        match store.save(ScrapeSession::new(&series_url, seasons.clone())) {
+                            Ok(_) => *sessions.lock().unwrap_or_else(|e| e.into_inner()) = store.list(),
+                            Err(e) => tracing::warn!(url = %series_url, error = %e, "Session de scraping non enregistrée"),
+                        }
                         let mut guard = results.blocking_lock();
                         *guard = seasons;
                         drop(guard);
@@ -202,8 +414,424 @@ impl ScraperTab {
         self.task_handle = Some(handle);
     }
     
+    /// Champ de recherche et séries trouvées; « Choisir » remplit l'URL de la série
+    fn show_search(&mut self, ui: &mut Ui) {
+        let searching = self.searching.load(Ordering::Relaxed);
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Rechercher:").strong());
+            let response = ui.text_edit_singleline(&mut self.search_query)
+                .on_hover_text("Titre de la série, cherché sur le site de l'URL de base");
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            let enabled = !searching && !self.search_query.trim().is_empty();
+            if (ui.add_enabled(enabled, egui::Button::new("🔎 Rechercher")).clicked() || submitted) && enabled {
+                self.start_search();
+            }
+            if searching {
+                ui.spinner();
+            }
+        });
+
+        let results = self.search_results.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if results.is_empty() {
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .id_source("search_results")
+            .max_height(150.0)
+            .show(ui, |ui| {
+                for result in &results {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("Choisir").clicked() {
+                            self.series_url = result.url.clone();
+                            self.series_title = result.title.clone();
+                        }
+                        let title = match result.year {
+                            Some(year) => format!("{} ({})", result.title, year),
+                            None => result.title.clone(),
+                        };
+                        ui.label(RichText::new(title).small()).on_hover_text(&result.url);
+                    });
+                }
+            });
+    }
+
+    /// Cherche `search_query` avec le scraper du domaine de l'URL de base
+    fn start_search(&mut self) {
+        let query = self.search_query.trim().to_string();
+        let base_url = self.base_url.clone();
+        let use_browser_fallback = self.use_browser_fallback;
+        let searching = self.searching.clone();
+        let results = self.search_results.clone();
+        let error_msg = self.error_message.clone();
+        searching.store(true, Ordering::Relaxed);
+        results.lock().unwrap_or_else(|e| e.into_inner()).clear();
+
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+            rt.block_on(async move {
+                let config = downloader::load_config();
+                let fztv = FztvScraper::new(base_url.clone())
+                    .with_browser_fallback(use_browser_fallback)
+                    .with_options(politeness::scraper_options_from_config(&config))
+                    .with_cache(cache::page_cache_from_config(&config))
+                    .with_rotation(rotation::rotation_from_config(&config))
+                    .with_enrich_options(enrich_options_from_config(&config));
+                let registry = ScraperRegistry::from_config(&config, fztv);
+                let found = match registry.for_url(&base_url) {
+                    Some(scraper) => scraper.search(&query).await,
+                    None => Err(anyhow::anyhow!("Aucun scraper pour ce domaine (URL de base ou dossier des sites)")),
+                };
+                match found {
+                    Ok(found) if found.is_empty() => *error_msg.lock().await = Some(format!("Aucune série trouvée pour « {} »", query)),
+                    Ok(found) => {
+                        *error_msg.lock().await = None;
+                        *results.lock().unwrap_or_else(|e| e.into_inner()) = found;
+                    }
+                    Err(e) => *error_msg.lock().await = Some(e.to_string()),
+                }
+                searching.store(false, Ordering::Relaxed);
+            });
+        });
+    }
+
+    /// Demande le fichier de destination puis y exporte `seasons` au format `format`
+    fn start_export(&mut self, format: ExportFormat, seasons: Vec<Season>) {
+        let status = self.export_status.clone();
+        let dir = self.output_dir.clone();
+        std::thread::spawn(move || {
+            let dialog = rfd::FileDialog::new()
+                .set_directory(&dir)
+                .set_file_name(format!("scraping.{}", format.extension()))
+                .add_filter(format.label(), &[format.extension()]);
+            let Some(path) = dialog.save_file() else {
+                return;
+            };
+            let message = match export::export(&seasons, format, &path) {
+                Ok(()) => format!("✅ Exporté: {}", path.display()),
+                Err(e) => format!("❌ Export échoué: {:#}", e),
+            };
+            *status.lock().unwrap_or_else(|e| e.into_inner()) = Some(message);
+        });
+    }
+
+    /// Relit les sessions enregistrées en arrière‑plan
+    fn refresh_sessions(&self) {
+        let sessions = self.sessions.clone();
+        std::thread::spawn(move || {
+            let found = session::session_store_from_config(&downloader::load_config()).list();
+            *sessions.lock().unwrap_or_else(|e| e.into_inner()) = found;
+        });
+    }
+
+    /// Sessions enregistrées: « Ouvrir » remplace les résultats par ceux de la session
+    fn show_sessions(&mut self, ui: &mut Ui) {
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if sessions.is_empty() {
+            return;
+        }
+        ui.collapsing(format!("🗂️ Sessions ({})", sessions.len()), |ui| {
+            egui::ScrollArea::vertical()
+                .id_source("scrape_sessions")
+                .max_height(150.0)
+                .show(ui, |ui| {
+                    for saved in &sessions {
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(!self.is_scraping, egui::Button::new("📂 Ouvrir").small()).clicked() {
+                                self.open_session(saved);
+                            }
+                            if ui.small_button("🗑️").on_hover_text("Supprimer la session").clicked() {
+                                self.delete_session(&saved.id);
+                            }
+                            ui.label(RichText::new(format_timestamp(saved.updated_at)).small().color(Color32::GRAY));
+                            ui.label(RichText::new(format!("{} saison(s) · {} épisode(s)", saved.seasons.len(), saved.episode_count())).small());
+                            url_label(ui, &saved.source_url, Color32::from_rgb(100, 200, 255));
+                        });
+                    }
+                });
+        });
+        ui.add_space(8.0);
+    }
+
+    fn open_session(&mut self, saved: &ScrapeSession) {
+        let Ok(mut results) = self.results.try_lock() else {
+            return;
+        };
+        *results = saved.seasons.clone();
+        self.series_url = saved.source_url.clone();
+        self.series_title.clear();
+        *self.last_diff.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        if let Ok(mut error) = self.error_message.try_lock() {
+            *error = None;
+        }
+        self.scrape_progress = ScrapeProgress::default();
+        self.scrape_log.clear();
+        tracing::info!(id = %saved.id, url = %saved.source_url, "Session de scraping rouverte");
+    }
+
+    fn delete_session(&mut self, id: &str) {
+        let store = session::session_store_from_config(&downloader::load_config());
+        if let Err(e) = store.delete(id) {
+            tracing::warn!(id, error = %e, "Session de scraping non supprimée");
+        }
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner()).retain(|s| s.id != id);
+    }
+
+    /// Cumule les étapes reçues du scraper; le scraping est terminé quand son thread l'est
+    fn poll_progress(&mut self) {
+        if let Some(rx) = self.progress_rx.as_mut() {
+            while let Ok(event) = rx.try_recv() {
+                self.scrape_progress.apply(&event);
+                self.scrape_log.push(event.describe());
+            }
+            let excess = self.scrape_log.len().saturating_sub(MAX_LOG_LINES);
+            self.scrape_log.drain(..excess);
+        }
+        if self.is_scraping && self.task_handle.as_ref().is_some_and(|h| h.is_finished()) {
+            self.is_scraping = false;
+        }
+    }
+
+    /// Barre de progression et journal du dernier scraping
+    fn show_scrape_progress(&mut self, ui: &mut Ui) {
+        if !self.is_scraping && self.scrape_log.is_empty() {
+            return;
+        }
+        let progress = &self.scrape_progress;
+        egui::Frame::group(ui.style())
+            .fill(Color32::from_rgb(30, 30, 35))
+            .rounding(egui::Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.set_min_width(ui.available_width());
+                let mut summary = format!("{}/{} saison(s) · {} épisode(s)", progress.seasons_done, progress.seasons_total, progress.episodes);
+                if progress.links_total > 0 {
+                    summary.push_str(&format!(" · {}/{} lien(s) résolu(s)", progress.links_found, progress.links_total));
+                }
+                if !progress.failures.is_empty() {
+                    summary.push_str(&format!(" · {} échec(s)", progress.failures.len()));
+                }
+                ui.label(RichText::new(summary).strong());
+                match progress.fraction() {
+                    Some(fraction) => ui.add(ProgressBar::new(fraction).show_percentage()),
+                    None => ui.add(ProgressBar::new(0.0).animate(self.is_scraping)),
+                };
+                if let Some(url) = progress.current_url.as_deref().filter(|_| self.is_scraping) {
+                    ui.label(RichText::new(url).small().color(Color32::GRAY));
+                }
+                if !progress.failures.is_empty() {
+                    ui.collapsing(RichText::new(format!("⚠️ Échecs ({})", progress.failures.len())).color(Color32::from_rgb(255, 160, 80)), |ui| {
+                        for failure in &progress.failures {
+                            ui.horizontal(|ui| {
+                                url_label(ui, &failure.url, Color32::from_rgb(255, 160, 80));
+                                ui.label(RichText::new(format!("{} tentative(s): {}", failure.attempts, failure.error)).small().color(Color32::GRAY));
+                            });
+                        }
+                    });
+                }
+                ui.collapsing(format!("Journal ({})", self.scrape_log.len()), |ui| {
+                    egui::ScrollArea::vertical()
+                        .id_source("scrape_log")
+                        .max_height(150.0)
+                        .stick_to_bottom(true)
+                        .show(ui, |ui| {
+                            for line in &self.scrape_log {
+                                ui.label(RichText::new(line).small().monospace());
+                            }
+                        });
+                });
+            });
+        ui.add_space(8.0);
+    }
+
+    fn season_download_running(&self) -> bool {
+        self.season_download.lock().unwrap_or_else(|e| e.into_inner()).as_ref().is_some_and(|d| !d.done)
+    }
+
+    /// Avancement du dernier téléchargement de saison
+    fn show_season_download(&mut self, ui: &mut Ui) {
+        let Some(download) = self.season_download.lock().unwrap_or_else(|e| e.into_inner()).clone() else {
+            return;
+        };
+        egui::Frame::group(ui.style())
+            .fill(Color32::from_rgb(30, 30, 35))
+            .rounding(egui::Rounding::same(8.0))
+            .show(ui, |ui| {
+                ui.set_min_width(ui.available_width());
+                let batch = &download.progress;
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format!("⬇️ {}", download.season)).strong());
+                    if download.resolving {
+                        ui.spinner();
+                        ui.label(RichText::new("Résolution des liens...").color(Color32::YELLOW));
+                    } else {
+                        ui.label(format!("{}/{} épisode(s)", batch.finished(), batch.total));
+                    }
+                    if !download.done && ui.button(RichText::new("⏹️ Arrêter").color(Color32::from_rgb(255, 100, 100))).clicked() {
+                        self.season_cancel.store(true, Ordering::Relaxed);
+                    }
+                });
+                if !download.resolving {
+                    ui.add(ProgressBar::new((batch.percent() / 100.0) as f32).show_percentage());
+                }
+                let mut details = Vec::new();
+                if batch.failed > 0 {
+                    details.push(format!("{} échec(s)", batch.failed));
+                }
+                if download.skipped > 0 {
+                    details.push(format!("{} épisode(s) sans lien", download.skipped));
+                }
+                if download.done {
+                    details.push(format!("terminé: {} réussi(s)", batch.completed));
+                }
+                if !details.is_empty() {
+                    ui.label(RichText::new(details.join(" · ")).small().color(Color32::GRAY));
+                }
+            });
+        ui.add_space(8.0);
+    }
+
+    /// Résout le lien réel de chaque épisode de `season` puis les télécharge en lot dans
+    /// `<dossier>/<saison>/<épisode>.mp4`
+    fn start_season_download(&mut self, season: &Season) {
+        let dir = PathBuf::from(&self.output_dir).join(sanitize_filename(&season.name));
+        let episodes = season.episodes.clone();
+        let season_name = season.name.clone();
+        let show = Some(self.series_title.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .or_else(|| show_name_from_url(&self.series_url))
+            .unwrap_or_default();
+        let season_url = season.url.clone();
+        let series_url = self.series_url.clone();
+        let base_url = self.base_url.clone();
+        let results = self.results.clone();
+        let sessions = self.sessions.clone();
+        let state = self.season_download.clone();
+        let cancel = self.season_cancel.clone();
+        cancel.store(false, Ordering::Relaxed);
+        *state.lock().unwrap_or_else(|e| e.into_inner()) = Some(SeasonDownload { season: season.name.clone(), resolving: true, ..Default::default() });
+        let update = move |change: &dyn Fn(&mut SeasonDownload)| {
+            if let Some(download) = state.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+                change(download);
+            }
+        };
+
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+            rt.block_on(async move {
+                let config = downloader::load_config();
+                let enrich = enrich_options_from_config(&config);
+                let preference = enrich.quality_preference.clone();
+                let fztv = FztvScraper::new(base_url)
+                    .with_options(politeness::scraper_options_from_config(&config))
+                    .with_cache(cache::page_cache_from_config(&config))
+                    .with_rotation(rotation::rotation_from_config(&config))
+                    .with_enrich_options(enrich);
+                let registry = ScraperRegistry::from_config(&config, fztv);
+                let scraper = registry.for_url(&season_url);
+                let mut inputs = Vec::new();
+                let mut resolved = Vec::new();
+                for episode in &episodes {
+                    if cancel.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let known = episode.download_links.iter().any(|l| !l.actual_download_urls.is_empty());
+                    match episode_media_url(scraper.as_deref(), episode, &preference).await {
+                        Some(url) => {
+                            if let Some(link) = preferred_link(&episode.download_links, &preference).filter(|_| !known) {
+                                let link = episode.download_links[link].url.clone();
+                                resolved.push(ResolvedLink { episode: episode.name.clone(), link, url: url.clone() });
+                            }
+                            let name = episode.info(&season_name).file_name(&show, &episode.name, "mp4");
+                            inputs.push((url, dir.join(name)));
+                        }
+                        None => update(&|d| d.skipped += 1),
+                    }
+                }
+                // liens résolus gardés dans les résultats et la session de la série
+                if !resolved.is_empty() {
+                    incremental::record_resolved(&mut results.lock().await, &season_url, &resolved);
+                    let store = session::session_store_from_config(&config);
+                    if let Some(mut saved) = store.load(&session::session_id(&series_url)) {
+                        if incremental::record_resolved(&mut saved.seasons, &season_url, &resolved) > 0 {
+                            match store.save(saved) {
+                                Ok(_) => *sessions.lock().unwrap_or_else(|e| e.into_inner()) = store.list(),
+                                Err(e) => tracing::warn!(url = %series_url, error = %e, "Liens résolus non enregistrés dans la session"),
+                            }
+                        }
+                    }
+                }
+                if let Err(e) = tokio::fs::create_dir_all(&dir).await {
+                    tracing::warn!(dir = %dir.display(), error = %e, "Dossier de la saison non créé");
+                }
+                update(&|d| {
+                    d.resolving = false;
+                    d.progress.total = inputs.len();
+                });
+                let concurrency = ffmpeg::jobs::max_jobs_from_config(&downloader::load_config());
+                let report = update.clone();
+                ffmpeg::download_batch(inputs, DownloadOptions::default(), concurrency, Some(move |batch: &BatchProgress| {
+                    report(&|d| d.progress = batch.clone());
+                }), cancel).await;
+                update(&|d| d.done = true);
+            });
+        });
+    }
+
+    /// Relit les épisodes de `season` seule (`SiteScraper::list_episodes`), les compare à ceux
+    /// affichés (`merge_with_previous`) et met à jour résultats et session de la série
+    fn refresh_season(&mut self, season: &Season) {
+        let previous = season.clone();
+        let series_url = self.series_url.clone();
+        let base_url = self.base_url.clone();
+        let results = self.results.clone();
+        let sessions = self.sessions.clone();
+        let error_msg = self.error_message.clone();
+        let last_diff = self.last_diff.clone();
+        let refresh = self.season_refresh.clone();
+        *refresh.lock().unwrap_or_else(|e| e.into_inner()) = Some(season.url.clone());
+
+        std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().expect("Failed to create runtime");
+            rt.block_on(async move {
+                let config = downloader::load_config();
+                let fztv = FztvScraper::new(base_url)
+                    .with_options(politeness::scraper_options_from_config(&config))
+                    .with_cache(cache::page_cache_from_config(&config))
+                    .with_rotation(rotation::rotation_from_config(&config));
+                let registry = ScraperRegistry::from_config(&config, fztv);
+                let episodes = match registry.for_url(&previous.url) {
+                    Some(scraper) => scraper.list_episodes(&previous.url).await,
+                    None => Err(anyhow::anyhow!("Aucun scraper pour ce domaine (URL de base ou dossier des sites)")),
+                };
+                match episodes {
+                    Ok(episodes) => {
+                        let current = Season { episodes, ..previous.clone() };
+                        let (mut merged, diff) = incremental::merge_with_previous(std::slice::from_ref(&previous), vec![current]);
+                        let season = merged.remove(0);
+                        tracing::info!(season = %season.name, new_episodes = diff.new_episodes(), "Saison actualisée");
+                        *last_diff.lock().unwrap_or_else(|e| e.into_inner()) = Some(diff);
+                        if let Some(shown) = results.lock().await.iter_mut().find(|s| s.url == season.url) {
+                            *shown = season.clone();
+                        }
+                        let store = session::session_store_from_config(&config);
+                        if let Some(mut saved) = store.load(&session::session_id(&series_url)) {
+                            if let Some(stored) = saved.seasons.iter_mut().find(|s| s.url == season.url) {
+                                *stored = season;
+                                match store.save(saved) {
+                                    Ok(_) => *sessions.lock().unwrap_or_else(|e| e.into_inner()) = store.list(),
+                                    Err(e) => tracing::warn!(url = %series_url, error = %e, "Session de scraping non enregistrée"),
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => *error_msg.lock().await = Some(e.to_string()),
+                }
+            });
+            *refresh.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        });
+    }
+
     fn stop_scraping(&mut self) {
-        self.cancel_flag.store(true, Ordering::Relaxed);
+        self.cancel.cancel();
         self.is_scraping = false;
         
         // Attendre que le thread se termine
@@ -213,3 +841,26 @@ impl ScraperTab {
     }
 }
 
+/// Date locale d'un horodatage Unix (`16/10/2026 18:03`)
+fn format_timestamp(secs: u64) -> String {
+    let at = std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+    chrono::DateTime::<chrono::Local>::from(at).format("%d/%m/%Y %H:%M").to_string()
+}
+
+/// Lien média d'un épisode: lien réel déjà résolu (de la qualité préférée d'abord), sinon
+/// résolution du lien de la qualité préférée par le scraper du site
+async fn episode_media_url(scraper: Option<&dyn SiteScraper>, episode: &Episode, preference: &[String]) -> Option<String> {
+    let link = &episode.download_links[preferred_link(&episode.download_links, preference)?];
+    let resolved = link.actual_download_urls.first()
+        .or_else(|| episode.download_links.iter().find_map(|l| l.actual_download_urls.first()));
+    if let Some(url) = resolved {
+        return Some(url.clone());
+    }
+    match scraper?.resolve_download(link).await {
+        Ok(url) => url,
+        Err(e) => {
+            tracing::warn!(episode = %episode.name, error = %e, "Lien de l'épisode non résolu");
+            None
+        }
+    }
+}
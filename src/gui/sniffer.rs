@@ -10,6 +10,7 @@ use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
 use tokio::sync::Mutex;
 use std::time::Duration;
 use crate::sniffers::network_sniffer::{NetworkSniffer, NetworkEntry, open_browser};
+use super::display::url_label;
 
 /// Onglet du sniffer réseau
 pub struct SnifferTab {
@@ -21,6 +22,7 @@ pub struct SnifferTab {
     captured_requests: Arc<Mutex<Vec<NetworkEntry>>>,
     error_message: Arc<Mutex<Option<String>>>,
     task_handle: Option<std::thread::JoinHandle<()>>,
+    copy_feedback: Option<String>, // Confirmation après copie des URLs filtrées
 }
 
 impl Default for SnifferTab {
@@ -34,6 +36,7 @@ impl Default for SnifferTab {
             captured_requests: Arc::new(Mutex::new(Vec::new())),
             error_message: Arc::new(Mutex::new(None)),
             task_handle: None,
+            copy_feedback: None,
         }
     }
 }
@@ -156,11 +159,15 @@ impl SnifferTab {
                         // Filtre d'affichage
                         ui.horizontal(|ui| {
                             ui.label(RichText::new("🔍 Filtrer l'affichage:").strong());
-                            ui.text_edit_singleline(&mut self.display_filter)
+                            let filter_edit = ui.text_edit_singleline(&mut self.display_filter)
                                 .on_hover_text("Filtrer les requêtes affichées par URL, méthode, type, etc.");
+                            if filter_edit.changed() {
+                                self.copy_feedback = None;
+                            }
                             if !self.display_filter.is_empty() {
                                 if ui.button("✖️").clicked() {
                                     self.display_filter.clear();
+                                    self.copy_feedback = None;
                                 }
                             }
                         });
@@ -185,6 +192,16 @@ impl SnifferTab {
                             ui.label(RichText::new(format!("{} requête(s) affichée(s) / {} total", filtered_requests.len(), requests.len()))
                                 .color(Color32::GRAY)
                                 .small());
+                            if ui.button("📋 Copier les URLs")
+                                .on_hover_text("Copier les URLs affichées (une par ligne) pour le téléchargement par lot")
+                                .clicked()
+                            {
+                                ui.ctx().copy_text(urls_to_clipboard_text(&filtered_requests));
+                                self.copy_feedback = Some(format!("✅ {} URL(s) copiée(s)", filtered_requests.len()));
+                            }
+                            if let Some(feedback) = &self.copy_feedback {
+                                ui.label(RichText::new(feedback).color(Color32::from_rgb(100, 255, 100)).small());
+                            }
                             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                                 if ui.button("💾 Exporter JSON").clicked() {
                                     // L'export est déjà fait automatiquement par le sniffer
@@ -235,9 +252,7 @@ impl SnifferTab {
                                         });
                                         
                                         // URL
-                                        ui.label(RichText::new(&request.url)
-                                            .small()
-                                            .color(Color32::from_rgb(220, 220, 220)));
+                                        url_label(ui, &request.url, Color32::from_rgb(220, 220, 220));
                                         
                                         // Bouton pour ouvrir l'URL
                                         if ui.button(RichText::new("🔗 Ouvrir").size(10.0)).clicked() {
@@ -351,3 +366,33 @@ impl SnifferTab {
     }
 }
 
+/// Concatène les URLs des entrées, une par ligne, pour le presse‑papiers
+fn urls_to_clipboard_text(entries: &[NetworkEntry]) -> String {
+    entries.iter().map(|e| e.url.as_str()).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(url: &str) -> NetworkEntry {
+        NetworkEntry {
+            url: url.to_string(),
+            method: Some("GET".to_string()),
+            status: Some(200),
+            resource_type: Some("Media".to_string()),
+            headers: None,
+            timestamp: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_urls_to_clipboard_text_one_per_line() {
+        let entries = vec![entry("https://a.example.com/1.mp4"), entry("https://b.example.com/2.m3u8")];
+        assert_eq!(
+            urls_to_clipboard_text(&entries),
+            "https://a.example.com/1.mp4\nhttps://b.example.com/2.m3u8"
+        );
+        assert_eq!(urls_to_clipboard_text(&[]), "");
+    }
+}
@@ -1,6 +1,7 @@
 mod scrapers;
 mod downloader;
 mod ffmpeg;
+mod ffprobe;
 mod sniffers;
 mod gui;
 
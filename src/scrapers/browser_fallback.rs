@@ -0,0 +1,82 @@
+//! Repli navigateur pour les pages dont les liens sont injectés par JavaScript.
+//!
+//! Lorsque l'extraction statique ne trouve rien, la page est chargée dans Chromium via
+//! `NetworkSniffer` et les URLs média/téléchargement sont extraites du trafic capturé.
+use anyhow::Result;
+use futures::future::BoxFuture;
+use crate::sniffers::network_sniffer::{NetworkEntry, NetworkSniffer};
+use crate::scrapers::links::is_download_candidate;
+
+/// Extensions considérées comme des fichiers média téléchargeables
+const MEDIA_EXTENSIONS: &[&str] = &["mp4", "mkv", "webm", "avi", "mov", "m3u8", "mpd", "mp3", "m4a"];
+
+/// Capture le trafic réseau d'une page (remplaçable dans les tests)
+pub trait NetworkCapture: Send + Sync {
+    fn capture<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Vec<NetworkEntry>>>;
+}
+
+/// Capture par défaut: lance `NetworkSniffer` sans filtre sur la page, sans fenêtre ni export
+pub struct SnifferCapture;
+
+impl NetworkCapture for SnifferCapture {
+    fn capture<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Vec<NetworkEntry>>> {
+        Box::pin(async move {
+            let sniffer = NetworkSniffer::new(None).headless();
+            sniffer.sniff(url).await?;
+            Ok(sniffer.get_results().await)
+        })
+    }
+}
+
+/// Retient les requêtes média (type `Media` ou extension connue), sans doublons ni liens sociaux.
+pub fn media_urls_from_entries(entries: &[NetworkEntry]) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for entry in entries {
+        let is_media_type = entry.resource_type.as_deref().is_some_and(|t| t.eq_ignore_ascii_case("media"));
+        if (is_media_type || has_media_extension(&entry.url))
+            && is_download_candidate(&entry.url)
+            && !urls.contains(&entry.url)
+        {
+            urls.push(entry.url.clone());
+        }
+    }
+    urls
+}
+
+fn has_media_extension(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit_once('.')
+        .map(|(_, ext)| ext.to_lowercase())
+        .is_some_and(|ext| MEDIA_EXTENSIONS.contains(&ext.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(url: &str, resource_type: &str) -> NetworkEntry {
+        NetworkEntry {
+            url: url.to_string(),
+            method: Some("GET".to_string()),
+            status: Some(200),
+            resource_type: Some(resource_type.to_string()),
+            headers: None,
+            timestamp: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_media_urls_from_entries() {
+        let entries = vec![
+            entry("https://cdn.example.com/app.js", "Script"),
+            entry("https://cdn.example.com/ep1.mp4?token=1", "Other"),
+            entry("https://stream.example.com/live", "Media"),
+            entry("https://cdn.example.com/ep1.mp4?token=1", "Media"),
+            entry("https://t.me/channel/video.mp4", "Media"),
+        ];
+        assert_eq!(media_urls_from_entries(&entries), vec![
+            "https://cdn.example.com/ep1.mp4?token=1".to_string(),
+            "https://stream.example.com/live".to_string(),
+        ]);
+    }
+}
@@ -0,0 +1,140 @@
+//! Cache disque des pages HTML du scraper.
+//!
+//! `FztvScraper::fetch_page` le consulte avant chaque requête: une page plus jeune que la durée
+//! de vie (`[scrapers] cache_ttl_secs`) est rendue sans requête; une page plus ancienne est
+//! revalidée (`If-None-Match` / `If-Modified-Since`), un `304` la rafraîchit sans la
+//! retélécharger. Une entrée par URL: `<dossier>/<sha256 de l'URL>.json`.
+//!
+//! Les URLs portant une clé de session (`dkey`, `downloadkey`...) ne sont jamais mises en cache:
+//! elles expirent vite et leurs pages donnent des liens à usage unique.
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::Url;
+use crate::downloader::AppConfig;
+
+/// Dossier du cache par défaut
+pub const DEFAULT_CACHE_DIR: &str = ".scrapes-cache";
+/// Durée de vie par défaut d'une page (1 h)
+pub const DEFAULT_TTL: Duration = Duration::from_secs(3600);
+
+/// Paramètres de requête qui rendent une page propre à une session
+const VOLATILE_PARAMS: [&str; 3] = ["dkey", "downloadkey", "downloadoptionskey"];
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Page en cache et ses validateurs HTTP
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedPage {
+    pub url: String,
+    /// Dernière récupération ou revalidation (secondes Unix)
+    pub fetched_at: u64,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String,
+}
+
+impl CachedPage {
+    /// La page a‑t‑elle moins de `ttl`?
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        unix_now().saturating_sub(self.fetched_at) < ttl.as_secs()
+    }
+}
+
+/// Cache disque des pages, indexé par URL
+#[derive(Debug, Clone)]
+pub struct PageCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl PageCache {
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self { dir: dir.into(), ttl }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// La page de `url` peut‑elle être mise en cache (pas de clé de session)?
+    pub fn cacheable(url: &str) -> bool {
+        Url::parse(url).is_ok_and(|u| !u.query_pairs().any(|(key, _)| VOLATILE_PARAMS.iter().any(|p| key.eq_ignore_ascii_case(p))))
+    }
+
+    fn path_for(&self, url: &str) -> PathBuf {
+        let digest = Sha256::digest(url.as_bytes());
+        let name: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+        self.dir.join(format!("{name}.json"))
+    }
+
+    /// Entrée de `url`, fraîche ou non; `None` si absente ou illisible
+    pub fn get(&self, url: &str) -> Option<CachedPage> {
+        let content = fs::read_to_string(self.path_for(url)).ok()?;
+        serde_json::from_str::<CachedPage>(&content).ok().filter(|page| page.url == url)
+    }
+
+    /// Enregistre la page de `url` (fichier temporaire puis renommage)
+    pub fn put(&self, url: &str, etag: Option<String>, last_modified: Option<String>, body: &str) -> io::Result<()> {
+        self.write(&CachedPage { url: url.to_string(), fetched_at: unix_now(), etag, last_modified, body: body.to_string() })
+    }
+
+    /// Page revalidée (`304`): repart pour une durée de vie
+    pub fn touch(&self, mut page: CachedPage) -> io::Result<CachedPage> {
+        page.fetched_at = unix_now();
+        self.write(&page)?;
+        Ok(page)
+    }
+
+    fn write(&self, page: &CachedPage) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path_for(&page.url);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string(page).map_err(io::Error::other)?)?;
+        fs::rename(&tmp, &path)
+    }
+}
+
+/// Cache de `[scrapers]` (`cache`, `cache_dir`, `cache_ttl_secs`); `None` si désactivé
+pub fn page_cache_from_config(config: &AppConfig) -> Option<PageCache> {
+    let scrapers = config.scrapers.as_ref();
+    if scrapers.and_then(|s| s.cache) == Some(false) {
+        return None;
+    }
+    let dir = scrapers.and_then(|s| s.cache_dir.clone()).unwrap_or_else(|| PathBuf::from(DEFAULT_CACHE_DIR));
+    let ttl = scrapers.and_then(|s| s.cache_ttl_secs).map(Duration::from_secs).unwrap_or(DEFAULT_TTL);
+    Some(PageCache::new(dir, ttl))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_cache_roundtrip_and_volatile_urls() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = PageCache::new(dir.path().join("cache"), Duration::from_secs(60));
+        let url = "https://fztvseries.mobi/v/serie.htm";
+        assert_eq!(cache.get(url), None);
+
+        cache.put(url, Some("\"v1\"".into()), None, "<html>1</html>").unwrap();
+        let page = cache.get(url).unwrap();
+        assert_eq!((page.etag.as_deref(), page.body.as_str()), (Some("\"v1\""), "<html>1</html>"));
+        assert!(page.is_fresh(cache.ttl()));
+
+        let stale = CachedPage { fetched_at: 0, ..page };
+        assert!(!stale.is_fresh(cache.ttl()));
+        assert!(cache.touch(stale).unwrap().is_fresh(cache.ttl()));
+        assert_eq!(cache.get("https://fztvseries.mobi/v/autre.htm"), None);
+
+        assert!(PageCache::cacheable(url));
+        assert!(PageCache::cacheable("https://fztvseries.mobi/season.php?id=4"));
+        assert!(!PageCache::cacheable("https://fztvseries.mobi/downloadmp4.php?fileid=1&dkey=abc"));
+        assert!(!PageCache::cacheable("https://fzmovies.net/download1.php?downloadoptionskey=x"));
+    }
+}
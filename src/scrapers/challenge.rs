@@ -0,0 +1,158 @@
+//! Pages anti‑robot (Cloudflare, DDoS-Guard) rencontrées par le scraper.
+//!
+//! `is_challenge` reconnaît une page de vérification à son statut (403, 429, 503) et à son
+//! contenu (`cf-mitigated: challenge`, `cf_chl_opt`, « Just a moment... »). `FztvScraper` la fait
+//! alors résoudre par un `ChallengeSolver`: par défaut `BrowserSolver` charge la page dans
+//! Chromium sans interface, attend que le script de vérification la remplace, et rend les
+//! cookies obtenus (`cf_clearance`...) avec le user‑agent du navigateur. Les cookies rejoignent
+//! la session du scraper et ce user‑agent accompagne ses requêtes suivantes: Cloudflare lie
+//! l'autorisation aux deux (et à l'adresse IP, d'où l'intérêt d'un proxy stable pendant la
+//! session).
+use std::time::Duration;
+use anyhow::{Context, Result};
+use chromiumoxide::{Browser, BrowserConfig};
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use url::Url;
+use crate::downloader::CookieJar;
+
+/// Délai maximal laissé au navigateur pour passer la vérification
+const SOLVE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Intervalle entre deux examens de la page pendant la vérification
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Marqueurs d'une page de vérification dans le HTML
+const BODY_MARKERS: [&str; 7] = [
+    "cf_chl_opt",
+    "challenge-platform",
+    "cf-browser-verification",
+    "<title>Just a moment...</title>",
+    "Checking your browser before accessing",
+    "Attention Required! | Cloudflare",
+    "DDoS-Guard",
+];
+
+/// La réponse (`status`, `headers`, `body`) est‑elle une page de vérification anti‑robot?
+pub fn is_challenge(status: StatusCode, headers: &HeaderMap, body: &str) -> bool {
+    if !matches!(status, StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE) {
+        return false;
+    }
+    let mitigated = headers.get("cf-mitigated").and_then(|v| v.to_str().ok()).is_some_and(|v| v.eq_ignore_ascii_case("challenge"));
+    mitigated || BODY_MARKERS.iter().any(|marker| body.contains(marker))
+}
+
+/// Cookie obtenu par le navigateur
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolvedCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+}
+
+/// Issue d'une vérification passée
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Clearance {
+    pub cookies: Vec<SolvedCookie>,
+    /// User‑agent du navigateur, à reprendre pour que les cookies restent valables
+    pub user_agent: Option<String>,
+}
+
+impl Clearance {
+    /// Ajoute les cookies à `jar` pour `url`; rend le nombre de cookies acceptés. Chromium note
+    /// `.domaine` les cookies de domaine, et le seul hôte les autres
+    pub fn store_in(&self, jar: &CookieJar, url: &Url) -> usize {
+        self.cookies
+            .iter()
+            .filter(|c| {
+                let mut set_cookie = format!("{}={}; Path={}", c.name, c.value, c.path);
+                if let Some(domain) = c.domain.strip_prefix('.') {
+                    set_cookie.push_str(&format!("; Domain={domain}"));
+                }
+                jar.add_cookie(&set_cookie, url)
+            })
+            .count()
+    }
+}
+
+/// Passe la vérification d'une page (remplaçable dans les tests)
+pub trait ChallengeSolver: Send + Sync {
+    fn solve<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Clearance>>;
+}
+
+/// Vérification passée dans Chromium sans interface: aucune fenêtre ne s'ouvre en plein scraping
+pub struct BrowserSolver;
+
+impl ChallengeSolver for BrowserSolver {
+    fn solve<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Clearance>> {
+        Box::pin(async move {
+            let config = BrowserConfig::builder()
+                .build()
+                .map_err(|e| anyhow::anyhow!("Configuration du navigateur: {}", e))?;
+            let (mut browser, mut handler) = Browser::launch(config).await.context("Lancement de Chromium")?;
+            let handler_task = tokio::spawn(async move { while handler.next().await.is_some_and(|h| h.is_ok()) {} });
+
+            let result = async {
+                let page = browser.new_page(url).await?;
+                let user_agent = page.user_agent().await.ok();
+                // La page de vérification se recharge d'elle‑même une fois le script exécuté
+                let passed = tokio::time::timeout(SOLVE_TIMEOUT, async {
+                    loop {
+                        tokio::time::sleep(POLL_INTERVAL).await;
+                        let content = page.content().await.unwrap_or_default();
+                        let cleared = page.get_cookies().await.unwrap_or_default().iter().any(|c| c.name == "cf_clearance");
+                        if cleared || !BODY_MARKERS.iter().any(|marker| content.contains(marker)) {
+                            break;
+                        }
+                    }
+                })
+                .await;
+                if passed.is_err() {
+                    anyhow::bail!("Vérification anti‑robot non passée en {:?}", SOLVE_TIMEOUT);
+                }
+                let cookies = page
+                    .get_cookies()
+                    .await?
+                    .into_iter()
+                    .map(|c| SolvedCookie { name: c.name, value: c.value, domain: c.domain, path: c.path })
+                    .collect();
+                Ok(Clearance { cookies, user_agent })
+            }
+            .await;
+
+            let _ = browser.close().await;
+            handler_task.abort();
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_challenge() {
+        let cf_page = "<html><head><title>Just a moment...</title></head><script>window._cf_chl_opt={}</script></html>";
+        let empty = HeaderMap::new();
+        assert!(is_challenge(StatusCode::SERVICE_UNAVAILABLE, &empty, cf_page));
+        assert!(is_challenge(StatusCode::FORBIDDEN, &empty, cf_page));
+        assert!(!is_challenge(StatusCode::OK, &empty, cf_page), "une page servie n'est pas un défi");
+        assert!(!is_challenge(StatusCode::SERVICE_UNAVAILABLE, &empty, "<html>Maintenance</html>"));
+
+        let mut mitigated = HeaderMap::new();
+        mitigated.insert("cf-mitigated", "challenge".parse().unwrap());
+        assert!(is_challenge(StatusCode::FORBIDDEN, &mitigated, ""));
+
+        let jar = CookieJar::in_memory();
+        let url = Url::parse("https://www.fztvseries.mobi/serie.htm").unwrap();
+        let clearance = Clearance {
+            cookies: vec![SolvedCookie { name: "cf_clearance".into(), value: "abc".into(), domain: ".fztvseries.mobi".into(), path: "/".into() }],
+            user_agent: None,
+        };
+        assert_eq!(clearance.store_in(&jar, &url), 1);
+        assert_eq!(jar.header_for(&url).as_deref(), Some("cf_clearance=abc"));
+    }
+}
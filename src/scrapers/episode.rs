@@ -0,0 +1,141 @@
+//! Métadonnées structurées des épisodes scrapés.
+//!
+//! Les sites ne donnent que des noms libres (« Season 2 », « Episode 05 - Breakage »,
+//! « Breaking Bad S02E05 (2009-04-05) »...). `parse_episode` en tire les numéros de saison et
+//! d'épisode, le titre et la date de diffusion quand ils y figurent; `EpisodeInfo::file_name`
+//! en fait des noms de fichiers reconnus par les médiathèques (`Breaking.Bad.S02E05.mp4`).
+use std::sync::LazyLock;
+use chrono::NaiveDate;
+use regex::Regex;
+use url::Url;
+use crate::downloader::sanitize_filename;
+use crate::ffmpeg::metadata::number_in;
+
+/// `S02E05`, `s2 e5`
+static SEASON_EPISODE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\bS(\d{1,2})\s*E(\d{1,3})\b").unwrap());
+/// `2x05`
+static CROSS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\b(\d{1,2})x(\d{1,3})\b").unwrap());
+/// `Season 2`, `Saison 02`, `Series 3`
+static SEASON: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\b(?:season|saison|series)\s*(\d{1,3})\b").unwrap());
+/// `Episode 5`, `Épisode 05`, `Ep. 5`, `E05`
+static EPISODE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)(?:\b[ée]pisode|\bep\.?|\bE)\s*(\d{1,4})\b").unwrap());
+/// Dates de diffusion: `2009-04-05`, `05/04/2009`, `05.04.2009`, `5 Apr 2009`, `5 April 2009`
+static DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\d{4}-\d{2}-\d{2}|\d{1,2}[/.]\d{1,2}[/.]\d{4}|\d{1,2} [A-Za-z]{3,9} \d{4}").unwrap());
+
+/// Séparateurs à retirer autour d'un titre
+const SEPARATORS: &[char] = &['-', '–', '—', ':', '|', '.', ',', '(', ')', '[', ']', ' '];
+
+/// Ce que le nom d'un épisode (et de sa saison) dit de lui
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpisodeInfo {
+    pub season_number: Option<u32>,
+    pub episode_number: Option<u32>,
+    /// Titre propre de l'épisode, sans numéros ni date
+    pub title: Option<String>,
+    pub air_date: Option<NaiveDate>,
+}
+
+/// Analyse `episode_name`; la saison vient du nom de l'épisode (`S02E05`, `2x05`) ou, à
+/// défaut, de `season_name`
+pub fn parse_episode(season_name: &str, episode_name: &str) -> EpisodeInfo {
+    let (air_date, date_span) = find_date(episode_name);
+    // la date écartée, ses chiffres ne passent pas pour des numéros
+    let name = match date_span {
+        Some((start, end)) => format!("{}{}", &episode_name[..start], &episode_name[end..]),
+        None => episode_name.to_string(),
+    };
+
+    let (season_number, episode_number, rest) = if let Some(caps) = SEASON_EPISODE.captures(&name).or_else(|| CROSS.captures(&name)) {
+        (caps[1].parse().ok(), caps[2].parse().ok(), &name[caps.get(0).map_or(0, |m| m.end())..])
+    } else if let Some(caps) = EPISODE.captures(&name) {
+        let season = SEASON.captures(&name).and_then(|c| c[1].parse().ok());
+        (season, caps[1].parse().ok(), &name[caps.get(0).map_or(0, |m| m.end())..])
+    } else {
+        (None, None, name.as_str())
+    };
+    let season_number = season_number.or_else(|| self::season_number(season_name));
+    let title = Some(rest.trim_matches(SEPARATORS).to_string()).filter(|t| !t.is_empty() && t.parse::<u32>().is_err());
+
+    EpisodeInfo { season_number, episode_number, title, air_date }
+}
+
+/// Numéro d'une saison d'après son nom (`Season 2`, `Saison 02`, `3`)
+pub fn season_number(season_name: &str) -> Option<u32> {
+    SEASON.captures(season_name).and_then(|c| c[1].parse().ok()).or_else(|| number_in(season_name))
+}
+
+/// Première date reconnue de `name`, avec sa position
+fn find_date(name: &str) -> (Option<NaiveDate>, Option<(usize, usize)>) {
+    for found in DATE.find_iter(name) {
+        let date = ["%Y-%m-%d", "%d/%m/%Y", "%d.%m.%Y", "%d %b %Y", "%d %B %Y"]
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(found.as_str(), format).ok());
+        if date.is_some() {
+            return (date, Some((found.start(), found.end())));
+        }
+    }
+    (None, None)
+}
+
+impl EpisodeInfo {
+    /// Nom de fichier `Serie.S02E05.ext` (`Serie.E05.ext` sans saison); sans numéro d'épisode,
+    /// `fallback` nettoyé
+    pub fn file_name(&self, show: &str, fallback: &str, extension: &str) -> String {
+        let Some(episode) = self.episode_number else {
+            return format!("{}.{extension}", sanitize_filename(fallback));
+        };
+        let code = match self.season_number {
+            Some(season) => format!("S{season:02}E{episode:02}"),
+            None => format!("E{episode:02}"),
+        };
+        let show: Vec<&str> = show.split(|c: char| c.is_whitespace() || c == '.').filter(|w| !w.is_empty()).collect();
+        let stem = match show.is_empty() {
+            true => code,
+            false => format!("{}.{code}", show.join(".")),
+        };
+        format!("{}.{extension}", sanitize_filename(&stem))
+    }
+}
+
+/// Nom de série lisible tiré du dernier segment de `url`: `.../Breaking-Bad.htm` -> `Breaking Bad`
+pub fn show_name_from_url(url: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let segment = parsed.path_segments()?.rev().find(|s| !s.is_empty())?;
+    let segment = percent_encoding::percent_decode_str(segment).decode_utf8_lossy();
+    let stem = segment.rsplit_once('.').map_or(segment.as_ref(), |(stem, _)| stem);
+    let name = stem.split(['-', '_', '+', ' ']).filter(|w| !w.is_empty()).collect::<Vec<_>>().join(" ");
+    Some(name).filter(|n| !n.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_episode() {
+        let info = parse_episode("Season 2", "Episode 05 - Breakage");
+        assert_eq!((info.season_number, info.episode_number, info.title.as_deref()), (Some(2), Some(5), Some("Breakage")));
+        assert_eq!(info.file_name("Breaking Bad", "Episode 05 - Breakage", "mp4"), "Breaking.Bad.S02E05.mp4");
+
+        // numéros dans le nom de l'épisode, date de diffusion entre parenthèses
+        let info = parse_episode("Specials", "Breaking Bad S03E07 - One Minute (2010-05-02)");
+        assert_eq!((info.season_number, info.episode_number), (Some(3), Some(7)));
+        assert_eq!(info.title.as_deref(), Some("One Minute"));
+        assert_eq!(info.air_date, NaiveDate::from_ymd_opt(2010, 5, 2));
+
+        let info = parse_episode("Saison 1", "Épisode 3");
+        assert_eq!((info.season_number, info.episode_number, info.title), (Some(1), Some(3), None));
+        assert_eq!(parse_episode("", "1x12: Pilot").file_name("", "", "mkv"), "S01E12.mkv");
+        assert_eq!(parse_episode("Season 4", "Finale 25/12/2011").air_date, NaiveDate::from_ymd_opt(2011, 12, 25));
+        assert_eq!(parse_episode("Season 4", "Ep. 9 (5 Apr 2009)").air_date, NaiveDate::from_ymd_opt(2009, 4, 5));
+
+        // sans numéro d'épisode: le nom d'origine
+        let info = parse_episode("Season 1", "Bonus: Making of");
+        assert_eq!((info.episode_number, info.title.as_deref()), (None, Some("Bonus: Making of")));
+        assert_eq!(info.file_name("Show", "Bonus: Making of", "mp4"), "Bonus_ Making of.mp4");
+
+        assert_eq!(show_name_from_url("https://fztvseries.mobi/v/Breaking-Bad.htm").as_deref(), Some("Breaking Bad"));
+        assert_eq!(show_name_from_url("pas une url"), None);
+    }
+}
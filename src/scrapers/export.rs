@@ -0,0 +1,150 @@
+//! Export des résultats du scraper.
+//!
+//! - JSON: l'arbre complet des saisons (`Season` -> `Episode` -> `DownloadLink`), relisible
+//!   par serde
+//! - CSV: une ligne par lien (et par URL réelle s'il en a plusieurs), pour un tableur
+//! - M3U: la première URL réelle de chaque épisode, pour un lecteur; les épisodes sans lien
+//!   résolu sont omis
+use std::path::Path;
+use anyhow::{Context, Result};
+use crate::scrapers::fzscrape::fztv_scraper::Season;
+
+/// Format d'export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    M3u,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Json, ExportFormat::Csv, ExportFormat::M3u];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "JSON",
+            ExportFormat::Csv => "CSV",
+            ExportFormat::M3u => "M3U",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::M3u => "m3u",
+        }
+    }
+}
+
+/// Arbre des saisons en JSON indenté
+pub fn to_json(seasons: &[Season]) -> Result<String> {
+    serde_json::to_string_pretty(seasons).context("Sérialisation JSON des saisons")
+}
+
+/// Champ CSV, entre guillemets s'il contient un séparateur, un guillemet ou un saut de ligne
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Liste plate des liens: `saison,episode,qualite,lien,file_id,url_reelle`
+pub fn to_csv(seasons: &[Season]) -> String {
+    let mut csv = String::from("saison,episode,qualite,lien,file_id,url_reelle\n");
+    for season in seasons {
+        for episode in &season.episodes {
+            for link in &episode.download_links {
+                let actual: Vec<&str> = match link.actual_download_urls.is_empty() {
+                    true => vec![""],
+                    false => link.actual_download_urls.iter().map(String::as_str).collect(),
+                };
+                for url in actual {
+                    let row = [&season.name, &episode.name, &link.quality, &link.url, link.file_id.as_deref().unwrap_or(""), url];
+                    csv.push_str(&row.map(csv_field).join(","));
+                    csv.push('\n');
+                }
+            }
+        }
+    }
+    csv
+}
+
+/// Liste de lecture étendue: un `#EXTINF` « saison - épisode » par URL réelle
+pub fn to_m3u(seasons: &[Season]) -> String {
+    let mut m3u = String::from("#EXTM3U\n");
+    for season in seasons {
+        for episode in &season.episodes {
+            let Some(url) = episode.download_links.iter().find_map(|l| l.actual_download_urls.first()) else {
+                continue;
+            };
+            // un titre sur une ligne: les sauts de ligne casseraient le format
+            let title = format!("{} - {}", season.name, episode.name).replace(['\n', '\r'], " ");
+            m3u.push_str(&format!("#EXTINF:-1,{title}\n{url}\n"));
+        }
+    }
+    m3u
+}
+
+pub fn render(seasons: &[Season], format: ExportFormat) -> Result<String> {
+    match format {
+        ExportFormat::Json => to_json(seasons),
+        ExportFormat::Csv => Ok(to_csv(seasons)),
+        ExportFormat::M3u => Ok(to_m3u(seasons)),
+    }
+}
+
+/// Écrit `seasons` au format `format` dans `path`
+pub fn export(seasons: &[Season], format: ExportFormat, path: &Path) -> Result<()> {
+    let content = render(seasons, format)?;
+    std::fs::write(path, content).with_context(|| format!("Écriture de {}", path.display()))?;
+    tracing::info!(path = %path.display(), format = format.label(), seasons = seasons.len(), "Résultats exportés");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scrapers::fzscrape::fztv_scraper::{DownloadLink, Episode};
+
+    fn link(quality: &str, actual: &[&str]) -> DownloadLink {
+        DownloadLink {
+            quality: quality.into(),
+            url: "episode.php?fileid=7&dkey=k".into(),
+            file_id: Some("7".into()),
+            dkey: Some("k".into()),
+            actual_download_urls: actual.iter().map(|u| u.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_export_formats() {
+        let seasons = vec![Season {
+            name: "Season 1".into(),
+            url: "https://fztvseries.mobi/s1".into(),
+            episodes: vec![
+                Episode { name: "Pilot, \"part 1\"".into(), download_links: vec![link("High MP4", &["https://cdn/a.mp4", "https://cdn/b.mp4"])], is_new: false },
+                Episode { name: "Episode 2".into(), download_links: vec![link("Low", &[])], is_new: false },
+            ],
+        }];
+
+        assert_eq!(
+            to_csv(&seasons),
+            "saison,episode,qualite,lien,file_id,url_reelle\n\
+             Season 1,\"Pilot, \"\"part 1\"\"\",High MP4,episode.php?fileid=7&dkey=k,7,https://cdn/a.mp4\n\
+             Season 1,\"Pilot, \"\"part 1\"\"\",High MP4,episode.php?fileid=7&dkey=k,7,https://cdn/b.mp4\n\
+             Season 1,Episode 2,Low,episode.php?fileid=7&dkey=k,7,\n"
+        );
+        assert_eq!(to_m3u(&seasons), "#EXTM3U\n#EXTINF:-1,Season 1 - Pilot, \"part 1\"\nhttps://cdn/a.mp4\n");
+
+        let back: Vec<Season> = serde_json::from_str(&to_json(&seasons).unwrap()).unwrap();
+        assert_eq!(back[0].episodes[0].download_links[0].actual_download_urls.len(), 2);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("serie.m3u");
+        export(&seasons, ExportFormat::M3u, &path).unwrap();
+        assert!(std::fs::read_to_string(path).unwrap().starts_with("#EXTM3U\n"));
+    }
+}
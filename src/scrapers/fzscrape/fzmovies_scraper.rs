@@ -0,0 +1,271 @@
+//! Scraper FZMovies, le site de films du même réseau que FZTV.
+//!
+//! Une page de film (`movie-<titre>--hmp4.htm`) liste une option par qualité
+//! (`download1.php?downloadoptionskey=...`, parfois dans un `onclick` comme sur FZTV); chaque
+//! option mène, en une ou deux pages intermédiaires (`download.php?downloadkey=...`), à la page
+//! finale des liens réels, lue comme celle de FZTV (`textbox`, `filelink`, `flink`).
+//!
+//! Le client HTTP, le sémaphore, le repli navigateur et l'extraction des pages finales sont ceux
+//! de `FztvScraper`, construit sur l'URL de base de FZMovies. Pour le registre, un film est une
+//! « saison » à un seul épisode.
+use anyhow::Result;
+use futures::future::{join_all, LocalBoxFuture};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use url::Url;
+use crate::downloader::AppConfig;
+use crate::scrapers::cache::PageCache;
+use crate::scrapers::politeness::ScraperOptions;
+use crate::scrapers::rotation::RotationPool;
+use crate::scrapers::fzscrape::fztv_scraper::{parse_onclick, year_in, DownloadLink, Episode, FztvScraper, Season};
+use crate::scrapers::site::{SearchResult, SiteScraper};
+
+/// URL de base par défaut (`[scrapers] fzmovies_url`)
+pub const DEFAULT_BASE_URL: &str = "https://fzmovies.net/";
+
+/// Pages intermédiaires suivies au plus entre une option de qualité et la page des liens
+const MAX_HOPS: usize = 4;
+
+/// Liens vers les options de qualité d'une page de film
+const OPTION_LINKS: &str = "a[onclick*=\"window.location.href\"], a[href*=\"download1.php\"], a[href*=\"downloadoptionskey\"], a[href*=\"downloadmp4.php\"]";
+
+/// Liens vers la page suivante d'une option (clé de téléchargement, page des liens)
+const HOP_LINKS: &str = "a#downloadlink, a#dlink2, a[href*=\"downloadkey\"], a[href*=\"download.php?\"], a[href*=\"downloadmp4.php\"]";
+
+/// Film avec ses options de qualité et leurs liens réels
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Movie {
+    pub name: String,
+    pub url: String,
+    pub year: Option<u32>,
+    pub download_links: Vec<DownloadLink>,
+}
+
+impl Movie {
+    /// Le film comme saison à un épisode, pour l'onglet Scraper
+    fn into_season(self) -> Season {
+        Season {
+            name: self.name.clone(),
+            url: self.url,
+            episodes: vec![Episode { name: self.name, download_links: self.download_links, is_new: false }],
+        }
+    }
+}
+
+/// Scraper spécialisé pour FZMovies
+pub struct FzmoviesScraper {
+    inner: FztvScraper,
+}
+
+impl FzmoviesScraper {
+    /// Crée une nouvelle instance du scraper FZMovies
+    pub fn new(base_url: String) -> Self {
+        Self { inner: FztvScraper::new(base_url) }
+    }
+
+    /// Débit et concurrence des requêtes, comme `FztvScraper::with_options`
+    pub fn with_options(mut self, options: ScraperOptions) -> Self {
+        self.inner = self.inner.with_options(options);
+        self
+    }
+
+    /// Proxys et user‑agents tournants, comme `FztvScraper::with_rotation`
+    pub fn with_rotation(mut self, rotation: Option<RotationPool>) -> Self {
+        self.inner = self.inner.with_rotation(rotation);
+        self
+    }
+
+    /// Cache disque des pages, comme `FztvScraper::with_cache`
+    pub fn with_cache(mut self, cache: Option<PageCache>) -> Self {
+        self.inner = self.inner.with_cache(cache);
+        self
+    }
+
+    /// Page du film `movie_url`: titre, année et options de qualité, chacune résolue jusqu'à ses
+    /// liens réels (une option non résolue garde une liste vide)
+    pub async fn scrape_movie(&self, movie_url: &str) -> Result<Movie> {
+        let url = self.inner.resolve_url(movie_url)?;
+        info!("Scraping du film FZMovies: {}", url);
+        let html = self.inner.fetch_page(&url).await?;
+        let page = Url::parse(&url)?;
+        let mut movie = parse_movie(&Html::parse_document(&html), &page);
+
+        let resolved = join_all(movie.download_links.iter().map(|link| self.resolve_link(&link.url))).await;
+        for (link, urls) in movie.download_links.iter_mut().zip(resolved) {
+            match urls {
+                Ok(urls) => link.actual_download_urls = urls,
+                Err(e) => warn!("Option {} non résolue ({}): {}", link.quality, link.url, e),
+            }
+        }
+        info!("Film {}: {} option(s), {} résolue(s)", movie.name, movie.download_links.len(),
+              movie.download_links.iter().filter(|l| !l.actual_download_urls.is_empty()).count());
+        Ok(movie)
+    }
+
+    /// Suit les pages intermédiaires de l'option `option_url` et rend les liens réels de la page
+    /// finale
+    pub async fn resolve_link(&self, option_url: &str) -> Result<Vec<String>> {
+        let mut url = self.inner.resolve_url(option_url)?;
+        for _ in 0..MAX_HOPS {
+            let html = self.inner.fetch_page(&url).await?;
+            let document = Html::parse_document(&html);
+            match next_hop(&document, &Url::parse(&url)?) {
+                Some(next) if next != url => {
+                    info!("Page intermédiaire FZMovies: {} -> {}", url, next);
+                    url = next;
+                }
+                _ => return self.inner.download_page_urls(&url, &document).await,
+            }
+        }
+        warn!("Trop de pages intermédiaires pour {}", option_url);
+        Ok(Vec::new())
+    }
+}
+
+/// Titre, année et options de qualité (non résolues) d'une page de film
+fn parse_movie(document: &Html, page: &Url) -> Movie {
+    let text = |e: ElementRef| e.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ");
+    let heading = ["div.moviename", "h2", "h1", "title"]
+        .iter()
+        .filter_map(|s| Selector::parse(s).ok())
+        .find_map(|s| document.select(&s).map(text).find(|t| !t.is_empty()))
+        .unwrap_or_else(|| "Film".to_string());
+    let year = year_in(&heading);
+    let name = match year {
+        Some(year) => heading.replace(&format!("({year})"), "").trim().to_string(),
+        None => heading,
+    };
+
+    let mut download_links: Vec<DownloadLink> = Vec::new();
+    let Ok(options) = Selector::parse(OPTION_LINKS) else {
+        return Movie { name, url: page.to_string(), year, download_links };
+    };
+    for element in document.select(&options) {
+        let (href, file_id, dkey) = match element.value().attr("onclick").and_then(parse_onclick) {
+            Some((url, file_id, dkey)) => (url, Some(file_id), dkey),
+            None => match element.value().attr("href") {
+                Some(href) => (href.to_string(), None, None),
+                None => continue,
+            },
+        };
+        let Ok(url) = page.join(href.trim()) else { continue };
+        if download_links.iter().any(|l| l.url == url.as_str()) {
+            continue;
+        }
+        // la résolution est parfois hors du lien, dans l'élément de liste qui le contient
+        let container = element.parent().and_then(ElementRef::wrap).map(text).unwrap_or_default();
+        download_links.push(DownloadLink {
+            quality: resolution_of(&text(element))
+                .or_else(|| resolution_of(&container))
+                .or_else(|| label_of(&text(element)))
+                .unwrap_or_else(|| "Qualité inconnue".to_string()),
+            url: url.to_string(),
+            file_id,
+            dkey,
+            actual_download_urls: Vec::new(),
+        });
+    }
+    Movie { name, url: page.to_string(), year, download_links }
+}
+
+/// Résolution (`720p`) citée dans un libellé d'option
+fn resolution_of(label: &str) -> Option<String> {
+    label
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c: char| !c.is_ascii_alphanumeric()))
+        .find(|w| w.len() > 3 && w.ends_with('p') && w[..w.len() - 1].chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_string)
+}
+
+/// Libellé d'option sans « Download » ni « File » (`High MP4`)
+fn label_of(label: &str) -> Option<String> {
+    let label = label.split_whitespace().filter(|w| !w.eq_ignore_ascii_case("download") && !w.eq_ignore_ascii_case("file")).collect::<Vec<_>>().join(" ");
+    (!label.is_empty()).then_some(label)
+}
+
+/// Page suivante d'une page intermédiaire (`onclick` ou `href`), résolue depuis `page`
+fn next_hop(document: &Html, page: &Url) -> Option<String> {
+    let hops = Selector::parse(HOP_LINKS).ok()?;
+    document.select(&hops).find_map(|element| {
+        let href = element.value().attr("onclick").and_then(parse_onclick).map(|(url, _, _)| url)
+            .or_else(|| element.value().attr("href").map(str::to_string))?;
+        page.join(href.trim()).ok().map(|url| url.to_string())
+    })
+}
+
+/// URL de base de `[scrapers] fzmovies_url` (`DEFAULT_BASE_URL` par défaut)
+pub fn fzmovies_url_from_config(config: &AppConfig) -> String {
+    config
+        .scrapers
+        .as_ref()
+        .and_then(|s| s.fzmovies_url.clone())
+        .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+}
+
+impl SiteScraper for FzmoviesScraper {
+    fn name(&self) -> &str {
+        "FZMovies"
+    }
+
+    fn domains(&self) -> Vec<String> {
+        self.inner.domains()
+    }
+
+    /// Formulaire de recherche de la page d'accueil, comme sur FZTV
+    fn search<'a>(&'a self, query: &'a str) -> LocalBoxFuture<'a, Result<Vec<SearchResult>>> {
+        Box::pin(self.inner.search(query))
+    }
+
+    fn list_seasons<'a>(&'a self, movie_url: &'a str) -> LocalBoxFuture<'a, Result<Vec<Season>>> {
+        Box::pin(async move { Ok(vec![self.scrape_movie(movie_url).await?.into_season()]) })
+    }
+
+    fn list_episodes<'a>(&'a self, movie_url: &'a str) -> LocalBoxFuture<'a, Result<Vec<Episode>>> {
+        Box::pin(async move { Ok(self.scrape_movie(movie_url).await?.into_season().episodes) })
+    }
+
+    /// Premier lien réel déjà résolu, sinon résolution de l'option
+    fn resolve_download<'a>(&'a self, link: &'a DownloadLink) -> LocalBoxFuture<'a, Result<Option<String>>> {
+        Box::pin(async move {
+            match link.actual_download_urls.first() {
+                Some(url) => Ok(Some(url.clone())),
+                None => Ok(self.resolve_link(&link.url).await?.into_iter().next()),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_movie_options_and_next_hop() {
+        let page = Url::parse("https://fzmovies.net/movie-Inception--hmp4.htm").unwrap();
+        let document = Html::parse_document(r#"
+            <div class="moviename">Inception (2010)</div>
+            <ul class="moviesfiles">
+                <li><a id="downloadoptionslink2" href="download1.php?downloadoptionskey=abc">Download High MP4 File</a> (720p) 1.2 GB</li>
+                <li><a href="download1.php?downloadoptionskey=abc">Doublon</a></li>
+                <li><a onclick='window.location.href="downloadmp4.php?fileid=42&dkey=k9"; return false;'><b>1080p</b></a></li>
+                <li><a href="/download1.php?downloadoptionskey=mp4">Download Standard MP4 File</a></li>
+            </ul>"#);
+        let movie = parse_movie(&document, &page);
+        assert_eq!((movie.name.as_str(), movie.year), ("Inception", Some(2010)));
+        let links: Vec<_> = movie.download_links.iter().map(|l| (l.quality.as_str(), l.url.as_str(), l.file_id.as_deref(), l.dkey.as_deref())).collect();
+        assert_eq!(links, [
+            ("720p", "https://fzmovies.net/download1.php?downloadoptionskey=abc", None, None),
+            ("1080p", "https://fzmovies.net/downloadmp4.php?fileid=42&dkey=k9", Some("42"), Some("k9")),
+            ("Standard MP4", "https://fzmovies.net/download1.php?downloadoptionskey=mp4", None, None),
+        ]);
+
+        let options = Url::parse("https://fzmovies.net/download1.php?downloadoptionskey=abc").unwrap();
+        let hop = Html::parse_document(r#"<a id="downloadlink" href="download.php?downloadkey=xyz">Download</a>"#);
+        assert_eq!(next_hop(&hop, &options).as_deref(), Some("https://fzmovies.net/download.php?downloadkey=xyz"));
+        let last = Html::parse_document(r#"<input name="filelink" value="https://cdn.example/inception.mp4">"#);
+        assert_eq!(next_hop(&last, &options), None);
+        assert_eq!(label_of("Download File"), None);
+        assert_eq!(resolution_of("MP4 (480p)").as_deref(), Some("480p"));
+    }
+}
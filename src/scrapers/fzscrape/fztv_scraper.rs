@@ -2,12 +2,26 @@ use anyhow::{Context, Result};
 use reqwest::Client;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 use url::Url;
-use tokio::sync::Semaphore;
+use tokio::sync::{mpsc, Semaphore};
+use tokio_util::sync::CancellationToken;
 use std::sync::Arc;
 use futures::stream::{self, StreamExt};
 use webbrowser;
+use crate::scrapers::links::extract_page_urls;
+use crate::scrapers::browser_fallback::{media_urls_from_entries, NetworkCapture, SnifferCapture};
+use crate::scrapers::cache::{CachedPage, PageCache};
+use crate::scrapers::politeness::{Pacer, ScraperOptions};
+use crate::scrapers::rotation::{self, RotationPool, Route};
+use crate::scrapers::challenge::{self, BrowserSolver, ChallengeSolver};
+use crate::scrapers::episode::{parse_episode, EpisodeInfo};
+use crate::scrapers::incremental;
+use crate::scrapers::progress::{FetchFailure, ScrapeEvent, ScrapeReport};
+use crate::downloader::parse_retry_after;
+use crate::scrapers::site::{SearchResult, SiteScraper};
+use futures::future::LocalBoxFuture;
+use crate::downloader::{AppConfig, CookieJar};
 
 /// Structure représentant une saison avec ses épisodes
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +36,16 @@ pub struct Season {
 pub struct Episode {
     pub name: String,
     pub download_links: Vec<DownloadLink>,
+    /// Paru depuis la session précédente (voir `scrapers::incremental`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_new: bool,
+}
+
+impl Episode {
+    /// Numéros, titre et date de diffusion tirés du nom (saison de `season_name` à défaut)
+    pub fn info(&self, season_name: &str) -> EpisodeInfo {
+        parse_episode(season_name, &self.name)
+    }
 }
 
 /// Structure représentant un lien de téléchargement
@@ -34,39 +58,340 @@ pub struct DownloadLink {
     pub actual_download_urls: Vec<String>,
 }
 
+/// Formulaire de recherche supposé quand la page d'accueil n'en montre pas
+const DEFAULT_SEARCH_ACTION: &str = "search.php";
+const DEFAULT_SEARCH_FIELD: &str = "search";
+
+/// Nombre maximal de requêtes HTTP simultanées par défaut (sémaphore partagé par `fetch_page`,
+/// voir `ScraperOptions::concurrency`)
+const MAX_CONCURRENT_REQUESTS: usize = crate::scrapers::politeness::DEFAULT_CONCURRENCY;
+
+/// Paramètres de la phase d'enrichissement (résolution des liens réels), distincts du scraping principal.
+///
+/// Chaque résolution enchaîne plusieurs `fetch_page`, tous bornés par le sémaphore global de
+/// `ScraperOptions::concurrency` permis: au‑delà de cette valeur, les résolutions supplémentaires
+/// restent en attente d'un permis sans augmenter le débit réel.
+#[derive(Debug, Clone)]
+pub struct EnrichOptions {
+    /// Nombre de liens résolus simultanément
+    pub concurrency: usize,
+    /// Délai maximal pour résoudre un lien (toutes requêtes comprises)
+    pub link_timeout: std::time::Duration,
+    /// Qualités préférées, de la meilleure à la moins bonne (`["1080p", "720p", "High MP4"]`);
+    /// chaque motif est cherché sans casse dans `DownloadLink::quality`, le premier lien de
+    /// l'épisode sert si aucun ne correspond
+    pub quality_preference: Vec<String>,
+    /// Résoudre toutes les qualités de chaque épisode plutôt que la préférée seule
+    pub all_qualities: bool,
+}
+
+impl Default for EnrichOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: MAX_CONCURRENT_REQUESTS,
+            link_timeout: std::time::Duration::from_secs(60),
+            quality_preference: vec![DEFAULT_QUALITY.to_string()],
+            all_qualities: false,
+        }
+    }
+}
+
+impl EnrichOptions {
+    /// Indices des liens de `links` à résoudre: tous, ou le préféré seul, sauf ceux qui ont
+    /// déjà une URL réelle (reprise d'une session)
+    pub fn links_to_resolve(&self, links: &[DownloadLink]) -> Vec<usize> {
+        let candidates: Vec<usize> = match self.all_qualities {
+            true => (0..links.len()).collect(),
+            false => preferred_link(links, &self.quality_preference).into_iter().collect(),
+        };
+        candidates.into_iter().filter(|&i| links[i].actual_download_urls.is_empty()).collect()
+    }
+}
+
+/// Qualité préférée par défaut
+pub const DEFAULT_QUALITY: &str = "High MP4";
+
+/// Indice du lien de la qualité la mieux classée dans `preference`, sinon du premier lien
+pub fn preferred_link(links: &[DownloadLink], preference: &[String]) -> Option<usize> {
+    preference
+        .iter()
+        .map(|wanted| wanted.to_lowercase())
+        .find_map(|wanted| links.iter().position(|link| link.quality.to_lowercase().contains(&wanted)))
+        .or_else(|| (!links.is_empty()).then_some(0))
+}
+
+/// Lit `[scrapers] quality_preference`, `resolve_all_qualities`, `enrich_concurrency` et
+/// `enrich_link_timeout_secs`
+pub fn enrich_options_from_config(config: &AppConfig) -> EnrichOptions {
+    let mut options = EnrichOptions::default();
+    if let Some(scrapers) = &config.scrapers {
+        if let Some(concurrency) = scrapers.enrich_concurrency {
+            options.concurrency = concurrency.max(1);
+        }
+        if let Some(secs) = scrapers.enrich_link_timeout_secs {
+            options.link_timeout = std::time::Duration::from_secs(secs.max(1));
+        }
+        if let Some(preference) = scrapers.quality_preference.clone().filter(|p| !p.is_empty()) {
+            options.quality_preference = preference;
+        }
+        options.all_qualities = scrapers.resolve_all_qualities.unwrap_or(false);
+    }
+    options
+}
+
 /// Scraper spécialisé pour FZTV Series
 pub struct FztvScraper {
     client: Client,
     base_url: String,
     // Semaphore pour limiter les requêtes concurrentes
     semaphore: Arc<Semaphore>,
+    // Débit, concurrence et délai aléatoire des requêtes
+    options: ScraperOptions,
+    pacer: Arc<Pacer>,
+    // Repli navigateur (sniffer) quand l'extraction statique ne trouve aucun lien
+    use_browser_fallback: bool,
+    network_capture: Arc<dyn NetworkCapture>,
+    enrich: EnrichOptions,
+    // Arrêt demandé: les requêtes en cours sont abandonnées et le scraping rend une erreur
+    cancel: CancellationToken,
+    // Étapes du scraping, pour l'affichage de la progression
+    progress: Option<mpsc::UnboundedSender<ScrapeEvent>>,
+    // Cache disque des pages (revalidées par ETag / Last-Modified une fois expirées)
+    cache: Option<PageCache>,
+    // Pages abandonnées, rendues par `scrape_all` dans son rapport
+    failures: Arc<std::sync::Mutex<Vec<FetchFailure>>>,
+    // Proxys et user‑agents pris à tour de rôle à chaque requête (`None`: `client` seul)
+    rotation: Option<RotationPool>,
+    // Session de cookies de `client` (celle du téléchargeur par défaut)
+    cookies: CookieJar,
+    // Vérifications anti‑robot: résolution, une à la fois, et user‑agent du navigateur qui
+    // les a passées (repris par les requêtes suivantes)
+    challenge_solver: Arc<dyn ChallengeSolver>,
+    challenge_lock: Arc<tokio::sync::Mutex<()>>,
+    challenges_passed: Arc<std::sync::atomic::AtomicU64>,
+    clearance_agent: Arc<std::sync::Mutex<Option<String>>>,
+}
+
+/// Réponse d'une tentative de `fetch_page`
+enum PageResponse {
+    Page { html: String, etag: Option<String>, last_modified: Option<String> },
+    /// `304`: la page en cache est toujours valable
+    NotModified,
+}
+
+/// Échec d'une tentative de `fetch_page`
+struct AttemptError {
+    error: anyhow::Error,
+    /// Erreur passagère (délai, connexion, 5xx, 429): la page peut être redemandée
+    transient: bool,
+    /// Délai demandé par le serveur (`Retry-After`)
+    retry_after: Option<std::time::Duration>,
+    /// Page de vérification anti‑robot, à passer dans le navigateur
+    challenge: bool,
+}
+
+impl AttemptError {
+    fn fatal(error: anyhow::Error) -> Self {
+        Self { error, transient: false, retry_after: None, challenge: false }
+    }
+
+    fn network(error: reqwest::Error, context: &'static str) -> Self {
+        let transient = error.is_timeout() || error.is_connect() || error.is_request() || error.is_body();
+        Self { error: anyhow::Error::new(error).context(context), transient, retry_after: None, challenge: false }
+    }
 }
 
 impl FztvScraper {
     /// Crée une nouvelle instance du scraper FZTV
     pub fn new(base_url: String) -> Self {
-        let client = Client::builder()
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .expect("Impossible de créer le client HTTP");
+        // Session partagée avec le téléchargeur (liens qui exigent les cookies du site)
+        let cookies = CookieJar::global();
+        let client = rotation::build_client(None, &cookies).expect("Impossible de créer le client HTTP");
+
+        // Limite les requêtes concurrentes pour ne pas surcharger le serveur
+        let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS));
+
+        Self {
+            client,
+            base_url,
+            semaphore,
+            options: ScraperOptions::default(),
+            pacer: Arc::new(Pacer::new(&ScraperOptions::default())),
+            use_browser_fallback: false,
+            network_capture: Arc::new(SnifferCapture),
+            enrich: EnrichOptions::default(),
+            cancel: CancellationToken::new(),
+            progress: None,
+            cache: None,
+            failures: Arc::default(),
+            rotation: None,
+            cookies,
+            challenge_solver: Arc::new(BrowserSolver),
+            challenge_lock: Arc::default(),
+            challenges_passed: Arc::default(),
+            clearance_agent: Arc::default(),
+        }
+    }
+
+    /// Remplace la session de cookies du client et de la rotation (celle du téléchargeur par
+    /// défaut)
+    #[cfg(test)]
+    pub fn with_cookie_jar(mut self, cookies: CookieJar) -> Self {
+        self.client = rotation::build_client(None, &cookies).expect("Impossible de créer le client HTTP");
+        self.rotation = self.rotation.map(|pool| pool.with_cookie_jar(&cookies));
+        self.cookies = cookies;
+        self
+    }
+
+    /// Définit la concurrence, le délai par lien et les qualités résolues par
+    /// `enrich_with_actual_links`
+    pub fn with_enrich_options(mut self, options: EnrichOptions) -> Self {
+        self.enrich = options;
+        self
+    }
+
+    /// Arrête le scraping à l'annulation de `cancel`: la requête en cours (ou l'attente d'un
+    /// permis) est abandonnée et `scrape_all`, `scrape_seasons`, `scrape_episodes` et
+    /// `enrich_with_actual_links` rendent une erreur au lieu de résultats partiels
+    pub fn with_cancel(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Envoie les étapes du scraping (pages, saisons, liens résolus) sur `tx`
+    pub fn with_progress(mut self, tx: mpsc::UnboundedSender<ScrapeEvent>) -> Self {
+        self.progress = Some(tx);
+        self
+    }
+
+    /// Limite débit et concurrence des requêtes (`[scrapers] requests_per_second`, `concurrency`,
+    /// `jitter_ms`); la concurrence borne aussi les saisons traitées en parallèle
+    pub fn with_options(mut self, options: ScraperOptions) -> Self {
+        self.semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+        self.pacer = Arc::new(Pacer::new(&options));
+        self.options = options;
+        self
+    }
+
+    /// Fait passer chaque requête par le proxy et le user‑agent suivants de `rotation`
+    /// (`[scrapers] proxies`, `user_agents`); un proxy bloqué est mis à l'écart un moment. Les
+    /// clients de la rotation prennent la session de cookies du scraper
+    pub fn with_rotation(mut self, rotation: Option<RotationPool>) -> Self {
+        self.rotation = rotation.map(|pool| pool.with_cookie_jar(&self.cookies));
+        self
+    }
+
+    /// Client (et user‑agent) de la prochaine requête
+    fn route(&self) -> Route {
+        match &self.rotation {
+            Some(pool) => pool.pick(),
+            None => Route { index: 0, client: self.client.clone(), user_agent: None },
+        }
+    }
+
+    /// User‑agent de la requête passant par `route`: celui du navigateur qui a passé la dernière
+    /// vérification anti‑robot, sinon celui de la rotation
+    fn user_agent_for(&self, route: &Route) -> Option<String> {
+        self.clearance_agent.lock().unwrap_or_else(|e| e.into_inner()).clone().or_else(|| route.user_agent.clone())
+    }
+
+    /// Passe la vérification anti‑robot de `url` dans le navigateur et reprend ses cookies et son
+    /// user‑agent. `seen` est le nombre de vérifications passées avant la requête: si une autre
+    /// requête en a passé une entre‑temps, il suffit de réessayer
+    async fn pass_challenge(&self, url: &str, seen: u64) -> Result<()> {
+        let _solving = self.or_cancel(self.challenge_lock.lock()).await?;
+        if self.challenges_passed.load(std::sync::atomic::Ordering::SeqCst) != seen {
+            return Ok(());
+        }
+        info!("🛡️ Vérification anti-robot sur {}, passage dans le navigateur", url);
+        let clearance = self.or_cancel(self.challenge_solver.solve(url)).await??;
+        let stored = clearance.store_in(&self.cookies, &Url::parse(url)?);
+        if let Some(agent) = clearance.user_agent {
+            *self.clearance_agent.lock().unwrap_or_else(|e| e.into_inner()) = Some(agent);
+        }
+        self.challenges_passed.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        info!("🛡️ Vérification passée: {} cookie(s) repris pour la session", stored);
+        Ok(())
+    }
+
+    /// Issue d'une requête pour la santé de son proxy: erreur réseau ou statut de blocage
+    fn report_route(&self, route: &Route, outcome: std::result::Result<reqwest::StatusCode, ()>) {
+        if let Some(pool) = &self.rotation {
+            pool.report(route.index, outcome.is_ok_and(|status| !rotation::is_blocked_status(status)));
+        }
+    }
+
+    /// Sert les pages de `cache` tant qu'elles sont fraîches (`None`: toujours le réseau)
+    pub fn with_cache(mut self, cache: Option<PageCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    fn emit(&self, event: ScrapeEvent) {
+        if let Some(tx) = &self.progress {
+            let _ = tx.send(event);
+        }
+    }
+
+    /// Erreur si l'arrêt a été demandé
+    fn check_cancelled(&self) -> Result<()> {
+        if self.cancel.is_cancelled() {
+            return Err(anyhow::anyhow!("Scraping annulé"));
+        }
+        Ok(())
+    }
+
+    /// Attend `future`, sauf si l'arrêt est demandé entre‑temps
+    async fn or_cancel<T>(&self, future: impl std::future::Future<Output = T>) -> Result<T> {
+        tokio::select! {
+            biased;
+            _ = self.cancel.cancelled() => Err(anyhow::anyhow!("Scraping annulé")),
+            value = future => Ok(value),
+        }
+    }
 
-        // Limite à 10 requêtes concurrentes pour ne pas surcharger le serveur
-        let semaphore = Arc::new(Semaphore::new(10));
+    /// Active le repli navigateur: si une page ne contient aucun lien dans son HTML statique,
+    /// elle est chargée via `NetworkSniffer` et les URLs média du trafic sont retenues.
+    pub fn with_browser_fallback(mut self, enabled: bool) -> Self {
+        self.use_browser_fallback = enabled;
+        self
+    }
 
-        Self { client, base_url, semaphore }
+    /// Retourne `urls` tel quel s'il est non vide; sinon tente le repli navigateur (si activé).
+    async fn fallback_if_empty(&self, page_url: &str, urls: Vec<String>) -> Vec<String> {
+        if !urls.is_empty() || !self.use_browser_fallback {
+            return urls;
+        }
+        info!("🌐 Aucun lien statique, repli navigateur pour: {}", page_url);
+        match self.network_capture.capture(page_url).await {
+            Ok(entries) => {
+                let urls = media_urls_from_entries(&entries);
+                info!("🌐 {} URLs média capturées via le navigateur ({} requêtes)", urls.len(), entries.len());
+                urls
+            }
+            Err(e) => {
+                warn!("Repli navigateur échoué pour {}: {}", page_url, e);
+                Vec::new()
+            }
+        }
     }
 
-    /// Ouvre une URL dans le navigateur par défaut pour debug (ACTIVÉ pour le test)
+    /// Ouvre une URL dans le navigateur par défaut pour debug, seulement avec
+    /// `ScraperOptions::debug_open_pages`
     fn open_in_browser(&self, url: &str, description: &str) {
-        info!("🌐 Ouverture dans le navigateur: {} - {}", description, url);
+        if !self.options.debug_open_pages {
+            return;
+        }
+        debug!("🌐 Ouverture dans le navigateur: {} - {}", description, url);
         if let Err(e) = webbrowser::open(url) {
             warn!("Impossible d'ouvrir le navigateur pour {}: {}", url, e);
         }
     }
 
-    /// Scrape toutes les saisons disponibles sur la page principale
-    pub async fn scrape_seasons(&self, main_url: &str) -> Result<Vec<Season>> {
+    /// Scrape toutes les saisons disponibles sur la page principale; celles de `previous`
+    /// (session enregistrée) sont reprises sans requête, sauf la plus récente des connues
+    /// (`incremental::reusable_seasons`), qui peut encore gagner des épisodes
+    pub async fn scrape_seasons(&self, main_url: &str, previous: &[Season]) -> Result<Vec<Season>> {
         info!("Début du scraping des saisons FZTV depuis: {}", main_url);
         
         // Ouvrir la page principale dans le navigateur pour debug
@@ -102,20 +427,37 @@ impl FztvScraper {
             }
         }
         
+        self.emit(ScrapeEvent::SeasonsFound { count: season_infos.len() });
+        
+        // Saisons reprises de la session: les connues, sauf la plus récente
+        let known = incremental::reusable_seasons(previous, &season_infos);
+        
         // Scraper toutes les saisons en parallèle avec contrôle de concurrence
-        let seasons = stream::iter(season_infos)
-            .map(|(name, url)| async move {
-                let episodes = self.scrape_episodes(&url).await.ok()?;
+        let seasons = stream::iter(season_infos.into_iter().zip(known))
+            .map(|((name, url), known)| async move {
+                // les saisons pas encore commencées sont sautées dès l'arrêt
+                if self.cancel.is_cancelled() {
+                    return None;
+                }
+                if let Some(known) = known {
+                    debug!("Saison {} reprise de la session ({} épisodes)", name, known.episodes.len());
+                    self.emit(ScrapeEvent::SeasonScraped { name: name.clone(), episodes: known.episodes.len() });
+                    return Some(Season { name, url, episodes: known.episodes.clone() });
+                }
+                let episodes = self.scrape_episodes(&url).await;
+                self.emit(ScrapeEvent::SeasonScraped { name: name.clone(), episodes: episodes.as_ref().map_or(0, Vec::len) });
+                let episodes = episodes.ok()?;
                 Some(Season {
                     name,
                     url,
                     episodes,
                 })
             })
-            .buffer_unordered(10)  // Traiter jusqu'à 10 saisons en parallèle
+            .buffer_unordered(self.options.concurrency.max(1))  // Saisons en parallèle, requêtes bornées par le sémaphore
             .filter_map(|x| async { x })
             .collect::<Vec<_>>()
             .await;
+        self.check_cancelled()?;
         
         info!("{} saisons FZTV trouvées", seasons.len());
         Ok(seasons)
@@ -137,6 +479,7 @@ impl FztvScraper {
         
         // Essayer différents sélecteurs pour trouver les épisodes
         let mut episodes = Vec::new();
+        self.check_cancelled()?;
         
         // Sélecteur 1: ul.list (original)
         if let Ok(selector) = Selector::parse("ul.list") {
@@ -164,13 +507,37 @@ impl FztvScraper {
             }
         }
         
+        // Dernier recours: liens injectés en JavaScript, capturés via le navigateur
+        if episodes.is_empty() {
+            let urls = self.fallback_if_empty(season_url, Vec::new()).await;
+            if !urls.is_empty() {
+                episodes.push(Episode {
+                    name: "Liens capturés (navigateur)".to_string(),
+                    is_new: false,
+                    download_links: urls
+                        .into_iter()
+                        .map(|url| DownloadLink {
+                            quality: "Réseau".to_string(),
+                            url: url.clone(),
+                            file_id: None,
+                            dkey: None,
+                            actual_download_urls: vec![url],
+                        })
+                        .collect(),
+                });
+            }
+        }
+        
         info!("{} épisodes FZTV trouvés pour cette saison", episodes.len());
         Ok(episodes)
     }
     
-    /// Debug function pour examiner la structure HTML
+    /// Debug function pour examiner la structure HTML (niveau `debug` de tracing, sautée en dessous)
     async fn debug_html_structure(&self, document: &Html, season_url: &str) -> Result<()> {
-        info!("=== DEBUG HTML STRUCTURE pour {} ===", season_url);
+        if !tracing::enabled!(tracing::Level::DEBUG) {
+            return Ok(());
+        }
+        debug!("=== DEBUG HTML STRUCTURE pour {} ===", season_url);
         
         // Chercher tous les éléments qui pourraient contenir des épisodes
         let debug_selectors = vec![
@@ -183,7 +550,7 @@ impl FztvScraper {
             if let Ok(selector) = Selector::parse(selector_str) {
                 let count = document.select(&selector).count();
                 if count > 0 {
-                    info!("Sélecteur '{}': {} éléments trouvés", selector_str, count);
+                    debug!("Sélecteur '{}': {} éléments trouvés", selector_str, count);
                     
                     // Afficher les premiers éléments pour comprendre la structure
                     for (i, element) in document.select(&selector).enumerate() {
@@ -194,24 +561,24 @@ impl FztvScraper {
                         } else { 
                             text 
                         };
-                        info!("  Exemple {}: {}", i + 1, text_preview);
+                        debug!("  Exemple {}: {}", i + 1, text_preview);
                         
                         // Afficher les attributs importants
                         if let Some(onclick) = element.value().attr("onclick") {
-                            info!("    onclick: {}", onclick);
+                            debug!("    onclick: {}", onclick);
                         }
                         if let Some(href) = element.value().attr("href") {
-                            info!("    href: {}", href);
+                            debug!("    href: {}", href);
                         }
                         if let Some(class) = element.value().attr("class") {
-                            info!("    class: {}", class);
+                            debug!("    class: {}", class);
                         }
                     }
                 }
             }
         }
         
-        info!("=== FIN DEBUG HTML STRUCTURE ===");
+        debug!("=== FIN DEBUG HTML STRUCTURE ===");
         Ok(())
     }
     
@@ -277,6 +644,7 @@ impl FztvScraper {
                 episodes.push(Episode {
                     name: episode_name,
                     download_links,
+                    is_new: false,
                 });
             }
         }
@@ -317,36 +685,7 @@ impl FztvScraper {
 
     /// Parse le contenu onclick pour extraire l'URL de window.location.href, le fileid et le dkey
     fn parse_onclick(&self, onclick: &str) -> Option<(String, String, Option<String>)> {
-        // Rechercher l'URL dans window.location.href (c'est l'URL importante, pas window.open)
-        if let Some(start) = onclick.find("window.location.href=\"") {
-            let start = start + 22; // Longueur de "window.location.href=\""
-            if let Some(end) = onclick[start..].find("\"") {
-                let url = &onclick[start..start + end];
-                
-                // Rechercher le fileid dans l'URL
-                if let Some(fileid_start) = onclick.find("fileid=") {
-                    let fileid_start = fileid_start + 7; // Longueur de "fileid="
-                    if let Some(fileid_end) = onclick[fileid_start..].find("&") {
-                        let file_id = &onclick[fileid_start..fileid_start + fileid_end];
-                        
-                        // Rechercher le dkey
-                        let dkey = if let Some(dkey_start) = onclick.find("dkey=") {
-                            let dkey_start = dkey_start + 5; // Longueur de "dkey="
-                            if let Some(dkey_end) = onclick[dkey_start..].find("\"") {
-                                Some(onclick[dkey_start..dkey_start + dkey_end].to_string())
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        };
-                        
-                        return Some((url.to_string(), file_id.to_string(), dkey));
-                    }
-                }
-            }
-        }
-        None
+        parse_onclick(onclick)
     }
 
     /// Scrape les URLs de téléchargement réelles avec traitement rapide pour éviter l'expiration
@@ -369,24 +708,22 @@ impl FztvScraper {
             Ok(html) => html,
             Err(e) => {
                 warn!("Erreur lors de la récupération de la page {}: {}", full_url, e);
-                return Ok(Vec::new()); // Retourner une liste vide au lieu d'échouer
+                // Retourner une liste vide au lieu d'échouer; page injoignable: pas de repli navigateur
+                return Ok(Vec::new());
             }
         };
         
         let document = Html::parse_document(&html);
         
         // Si c'est une page episode.php, chercher le lien "DOWNLOAD THIS EPISODE ON YOUR DEVICE"
-        if download_page_url.contains("episode.php") {
-            return self.scrape_episode_page(&document).await;
-        }
-        
-        // Si c'est une page downloadmp4.php, chercher directement les liens
-        if download_page_url.contains("downloadmp4.php") {
-            return self.scrape_download_page_fast(&document).await;
-        }
+        // Si c'est une page downloadmp4.php (ou autre), chercher directement les liens
+        let urls = if download_page_url.contains("episode.php") {
+            self.scrape_episode_page(&document).await?
+        } else {
+            self.scrape_download_page_fast(&document).await?
+        };
         
-        // Sinon, essayer de scraper directement
-        self.scrape_download_page_fast(&document).await
+        Ok(self.fallback_if_empty(&full_url, urls).await)
     }
 
     /// Scrape les URLs de téléchargement réelles depuis la page de téléchargement
@@ -409,24 +746,22 @@ impl FztvScraper {
             Ok(html) => html,
             Err(e) => {
                 info!("Erreur lors de la récupération de la page {}: {}", full_url, e);
-                return Ok(Vec::new()); // Retourner une liste vide au lieu d'échouer
+                // Retourner une liste vide au lieu d'échouer; page injoignable: pas de repli navigateur
+                return Ok(Vec::new());
             }
         };
         
         let document = Html::parse_document(&html);
         
         // Si c'est une page episode.php, chercher le lien "DOWNLOAD THIS EPISODE ON YOUR DEVICE"
-        if download_page_url.contains("episode.php") {
-            return self.scrape_episode_page(&document).await;
-        }
-        
-        // Si c'est une page downloadmp4.php, chercher directement les liens
-        if download_page_url.contains("downloadmp4.php") {
-            return self.scrape_download_page(&document).await;
-        }
+        // Si c'est une page downloadmp4.php (ou autre), chercher directement les liens
+        let urls = if download_page_url.contains("episode.php") {
+            self.scrape_episode_page(&document).await?
+        } else {
+            self.scrape_download_page(&document).await?
+        };
         
-        // Sinon, essayer de scraper directement
-        self.scrape_download_page(&document).await
+        Ok(self.fallback_if_empty(&full_url, urls).await)
     }
 
     /// Scrape une page episode.php pour trouver le lien de téléchargement
@@ -548,32 +883,9 @@ impl FztvScraper {
     async fn find_all_urls_in_page(&self, document: &Html) -> Result<Vec<String>> {
         info!("🔍 Recherche générale de toutes les URLs dans la page");
         
-        let mut urls = Vec::new();
-        
-        // Chercher tous les inputs avec des URLs
-        let input_selector = Selector::parse("input[type=\"text\"]")
-            .map_err(|e| anyhow::anyhow!("Impossible de créer le sélecteur pour les inputs: {}", e))?;
-        
-        for input in document.select(&input_selector) {
-            if let Some(value) = input.value().attr("value") {
-                if value.starts_with("http") && !value.contains("t.me") && !value.contains("instagram") && !value.contains("fzmovies.live") {
-                    urls.push(value.to_string());
-                    info!("🔗 URL trouvée dans input: {}", value);
-                }
-            }
-        }
-        
-        // Chercher tous les liens avec des URLs
-        let link_selector = Selector::parse("a[href*=\"http\"]")
-            .map_err(|e| anyhow::anyhow!("Impossible de créer le sélecteur pour les liens: {}", e))?;
-        
-        for link in document.select(&link_selector) {
-            if let Some(href) = link.value().attr("href") {
-                if href.starts_with("http") && !href.contains("t.me") && !href.contains("instagram") && !href.contains("fzmovies.live") {
-                    urls.push(href.to_string());
-                    info!("🔗 URL trouvée dans lien: {}", href);
-                }
-            }
+        let urls = extract_page_urls(document, None);
+        for url in &urls {
+            info!("🔗 URL trouvée: {}", url);
         }
         
         info!("🔍 {} URLs trouvées dans la page", urls.len());
@@ -593,8 +905,8 @@ impl FztvScraper {
         let document = Html::parse_document(&html);
         
         // Debug HTML complet pour comprendre la structure
-        info!("🧪 === DEBUG HTML COMPLET ===");
-        info!("🧪 Taille du HTML: {} caractères", html.len());
+        debug!("🧪 === DEBUG HTML COMPLET ===");
+        debug!("🧪 Taille du HTML: {} caractères", html.len());
         
         // Chercher tous les divs
         let div_selector = Selector::parse("div").unwrap();
@@ -603,13 +915,13 @@ impl FztvScraper {
             div_count += 1;
             if div_count <= 10 { // Limiter à 10 pour éviter le spam
                 if let Some(class) = div.value().attr("class") {
-                    info!("🧪 Div {}: class='{}'", div_count, class);
+                    debug!("🧪 Div {}: class='{}'", div_count, class);
                 } else {
-                    info!("🧪 Div {}: pas de class", div_count);
+                    debug!("🧪 Div {}: pas de class", div_count);
                 }
             }
         }
-        info!("🧪 Total divs trouvés: {}", div_count);
+        debug!("🧪 Total divs trouvés: {}", div_count);
         
         // Chercher tous les inputs
         let input_selector = Selector::parse("input").unwrap();
@@ -619,26 +931,26 @@ impl FztvScraper {
             let name = input.value().attr("name").unwrap_or("pas de name");
             let value = input.value().attr("value").unwrap_or("pas de value");
             let input_type = input.value().attr("type").unwrap_or("pas de type");
-            info!("🧪 Input {}: name='{}', type='{}', value='{}'", input_count, name, input_type, value);
+            debug!("🧪 Input {}: name='{}', type='{}', value='{}'", input_count, name, input_type, value);
         }
-        info!("🧪 Total inputs trouvés: {}", input_count);
+        debug!("🧪 Total inputs trouvés: {}", input_count);
         
         // Chercher spécifiquement div.downloadlinks2
         let downloadlinks_selector = Selector::parse("div.downloadlinks2").unwrap();
         let mut downloadlinks_count = 0;
         for div in document.select(&downloadlinks_selector) {
             downloadlinks_count += 1;
-            info!("🧪 Div downloadlinks2 {} trouvé!", downloadlinks_count);
+            debug!("🧪 Div downloadlinks2 {} trouvé!", downloadlinks_count);
             
             // Chercher les inputs filelink dans ce div
             let filelink_selector = Selector::parse("input[name=\"filelink\"]").unwrap();
             for input in div.select(&filelink_selector) {
                 if let Some(value) = input.value().attr("value") {
-                    info!("🧪 Input filelink trouvé: {}", value);
+                    debug!("🧪 Input filelink trouvé: {}", value);
                 }
             }
         }
-        info!("🧪 Total div.downloadlinks2 trouvés: {}", downloadlinks_count);
+        debug!("🧪 Total div.downloadlinks2 trouvés: {}", downloadlinks_count);
         
         // Chercher tous les liens
         let link_selector = Selector::parse("a").unwrap();
@@ -648,12 +960,12 @@ impl FztvScraper {
             if link_count <= 10 { // Limiter à 10
                 let href = link.value().attr("href").unwrap_or("pas de href");
                 let id = link.value().attr("id").unwrap_or("pas d'id");
-                info!("🧪 Lien {}: href='{}', id='{}'", link_count, href, id);
+                debug!("🧪 Lien {}: href='{}', id='{}'", link_count, href, id);
             }
         }
-        info!("🧪 Total liens trouvés: {}", link_count);
+        debug!("🧪 Total liens trouvés: {}", link_count);
         
-        info!("🧪 === FIN DEBUG HTML COMPLET ===");
+        debug!("🧪 === FIN DEBUG HTML COMPLET ===");
         
         // Utiliser la méthode rapide pour extraire les URLs
         let urls = self.scrape_download_page_fast(&document).await?;
@@ -784,6 +1096,13 @@ impl FztvScraper {
         Ok(download_urls)
     }
 
+    /// URLs réelles de la page de liens finale `document` (`textbox`, `filelink`, `flink`...),
+    /// sans ouvrir de navigateur; repli navigateur sur `url` si la page n'en donne aucune
+    pub(crate) async fn download_page_urls(&self, url: &str, document: &Html) -> Result<Vec<String>> {
+        let urls = self.scrape_download_page_fast(document).await?;
+        Ok(self.fallback_if_empty(url, urls).await)
+    }
+
     /// Scrape une page de téléchargement depuis une URL
     async fn scrape_download_page_from_url(&self, url: &str) -> Result<Vec<String>> {
         let html = self.fetch_page(url).await?;
@@ -793,33 +1112,146 @@ impl FztvScraper {
 
 
     /// Récupère le contenu HTML d'une page
-    async fn fetch_page(&self, url: &str) -> Result<String> {
+    pub(crate) async fn fetch_page(&self, url: &str) -> Result<String> {
         info!("Récupération de la page FZTV: {}", url);
+        self.check_cancelled()?;
+        self.emit(ScrapeEvent::Fetching { url: url.to_string() });
+        
+        let cache = self.cache.as_ref().filter(|_| PageCache::cacheable(url));
+        let cached = cache.and_then(|c| c.get(url));
+        if let (Some(cache), Some(page)) = (cache, &cached) && page.is_fresh(cache.ttl()) {
+            info!("Page servie par le cache: {}", url);
+            return Ok(page.body.clone());
+        }
+        
+        // Erreurs transitoires: nouvelles tentatives avec délai exponentiel (ou Retry-After)
+        let retry = &self.options.retry;
+        let max_attempts = retry.max_attempts.max(1);
+        let mut attempt = 1;
+        let mut challenged = false;
+        let response = loop {
+            let passed = self.challenges_passed.load(std::sync::atomic::Ordering::SeqCst);
+            match self.fetch_attempt(url, cached.as_ref()).await {
+                Ok(response) => break response,
+                Err(failed) if self.cancel.is_cancelled() => return Err(failed.error),
+                // Vérification anti‑robot: une seule résolution par page, puis nouvel essai
+                Err(failed) if failed.challenge && !challenged => {
+                    challenged = true;
+                    if let Err(e) = self.pass_challenge(url, passed).await {
+                        warn!("Vérification anti-robot non passée pour {}: {:#}", url, e);
+                        self.record_failure(url, &failed.error, attempt);
+                        return Err(failed.error);
+                    }
+                }
+                Err(failed) if failed.transient && attempt < max_attempts => {
+                    let wait = failed.retry_after.unwrap_or_else(|| retry.delay(attempt));
+                    warn!("Tentative {}/{} échouée pour {} ({}), nouvel essai dans {:?}", attempt, max_attempts, url, failed.error, wait);
+                    self.or_cancel(tokio::time::sleep(wait)).await?;
+                    attempt += 1;
+                }
+                Err(failed) => {
+                    self.record_failure(url, &failed.error, attempt);
+                    return Err(failed.error);
+                }
+            }
+        };
         
+        match (response, cache, cached) {
+            (PageResponse::NotModified, Some(cache), Some(page)) => {
+                info!("Page inchangée (304), servie par le cache: {}", url);
+                let body = page.body.clone();
+                if let Err(e) = cache.touch(page) {
+                    warn!("Cache non mis à jour pour {}: {}", url, e);
+                }
+                Ok(body)
+            }
+            (PageResponse::NotModified, ..) => Err(anyhow::anyhow!("Erreur HTTP: 304 sans page en cache")),
+            (PageResponse::Page { html, etag, last_modified }, cache, _) => {
+                if let Some(cache) = cache
+                    && let Err(e) = cache.put(url, etag, last_modified, &html)
+                {
+                    warn!("Page non mise en cache {}: {}", url, e);
+                }
+                Ok(html)
+            }
+        }
+    }
+
+    /// Une requête de `url`, conditionnelle si la page est en cache (`cached`)
+    async fn fetch_attempt(&self, url: &str, cached: Option<&CachedPage>) -> std::result::Result<PageResponse, AttemptError> {
         // Acquérir le semaphore pour limiter les requêtes concurrentes
-        let _permit = self.semaphore
-            .acquire()
+        let _permit = self.or_cancel(self.semaphore.acquire())
             .await
-            .map_err(|e| anyhow::anyhow!("Erreur d'acquisition du semaphore: {}", e))?;
-        
-        let response = self.client
-            .get(url)
-            .send()
+            .map_err(AttemptError::fatal)?
+            .map_err(|e| AttemptError::fatal(anyhow::anyhow!("Erreur d'acquisition du semaphore: {}", e)))?;
+        self.or_cancel(self.pacer.wait()).await.map_err(AttemptError::fatal)?;
+        
+        // Page expirée: revalidation conditionnelle
+        let route = self.route();
+        let mut request = route.client.get(url);
+        if let Some(agent) = self.user_agent_for(&route) {
+            request = request.header(reqwest::header::USER_AGENT, agent);
+        }
+        if let Some(page) = cached {
+            if let Some(etag) = &page.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(modified) = &page.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, modified);
+            }
+        }
+        let response = self.or_cancel(request.send())
             .await
-            .context("Erreur lors de la requête HTTP")?;
-        
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!("Erreur HTTP: {}", response.status()));
+            .map_err(AttemptError::fatal)?
+            .inspect_err(|_| self.report_route(&route, Err(())))
+            .map_err(|e| AttemptError::network(e, "Erreur lors de la requête HTTP"))?;
+        
+        let status = response.status();
+        self.report_route(&route, Ok(status));
+        let header = |name| response.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        if status == reqwest::StatusCode::NOT_MODIFIED && cached.is_some() {
+            return Ok(PageResponse::NotModified);
+        }
+        if !status.is_success() {
+            let retry_after = header(reqwest::header::RETRY_AFTER).and_then(|v| parse_retry_after(&v, std::time::SystemTime::now()));
+            if self.options.solve_challenges && matches!(status.as_u16(), 403 | 429 | 503) {
+                let headers = response.headers().clone();
+                let body = self.or_cancel(response.text()).await.map_err(AttemptError::fatal)?.unwrap_or_default();
+                if challenge::is_challenge(status, &headers, &body) {
+                    return Err(AttemptError {
+                        error: anyhow::anyhow!("Vérification anti-robot (HTTP {})", status),
+                        transient: false,
+                        retry_after: None,
+                        challenge: true,
+                    });
+                }
+            }
+            return Err(AttemptError {
+                error: anyhow::anyhow!("Erreur HTTP: {}", status),
+                transient: status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS || status == reqwest::StatusCode::REQUEST_TIMEOUT,
+                retry_after,
+                challenge: false,
+            });
         }
         
-        let html = response.text().await
-            .context("Impossible de lire le contenu de la réponse")?;
-        
-        Ok(html)
+        let (etag, last_modified) = (header(reqwest::header::ETAG), header(reqwest::header::LAST_MODIFIED));
+        let html = self.or_cancel(response.text())
+            .await
+            .map_err(AttemptError::fatal)?
+            .map_err(|e| AttemptError::network(e, "Impossible de lire le contenu de la réponse"))?;
+        Ok(PageResponse::Page { html, etag, last_modified })
+    }
+
+    /// Note l'abandon de `url` pour le rapport du scraping et la progression
+    fn record_failure(&self, url: &str, error: &anyhow::Error, attempts: u32) {
+        warn!("Abandon de {} après {} tentative(s): {}", url, attempts, error);
+        let failure = FetchFailure { url: url.to_string(), error: format!("{:#}", error), attempts };
+        self.failures.lock().unwrap_or_else(|e| e.into_inner()).push(failure.clone());
+        self.emit(ScrapeEvent::Failed(failure));
     }
 
     /// Résout une URL relative en URL absolue
-    fn resolve_url(&self, url: &str) -> Result<String> {
+    pub(crate) fn resolve_url(&self, url: &str) -> Result<String> {
         if url.starts_with("http://") || url.starts_with("https://") {
             Ok(url.to_string())
         } else {
@@ -831,17 +1263,81 @@ impl FztvScraper {
         }
     }
 
-    /// Scrape toutes les données (saisons et épisodes) depuis une URL principale
-    pub async fn scrape_all(&self, main_url: &str) -> Result<Vec<Season>> {
+    /// Scrape toutes les données (saisons et épisodes) depuis une URL principale, en reprenant
+    /// de `previous` les saisons que `scrape_seasons` ne relit pas; le rapport liste les pages
+    /// abandonnées après leurs tentatives (saisons ou liens manquants)
+    pub async fn scrape_all(&self, main_url: &str, previous: &[Season]) -> Result<(Vec<Season>, ScrapeReport)> {
         info!("Début du scraping complet FZTV depuis: {}", main_url);
         
-        let seasons = self.scrape_seasons(main_url).await?;
+        let start = self.failures.lock().unwrap_or_else(|e| e.into_inner()).len();
+        let seasons = self.scrape_seasons(main_url, previous).await?;
+        let report = {
+            let mut failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+            let start = start.min(failures.len());
+            ScrapeReport { failures: failures.split_off(start) }
+        };
         
-        info!("Scraping FZTV terminé. {} saisons avec un total de {} épisodes trouvés", 
+        info!("Scraping FZTV terminé. {} saisons avec un total de {} épisodes trouvés, {} page(s) en échec", 
               seasons.len(), 
-              seasons.iter().map(|s| s.episodes.len()).sum::<usize>());
+              seasons.iter().map(|s| s.episodes.len()).sum::<usize>(),
+              report.failures.len());
+        for proxy in self.rotation.iter().flat_map(RotationPool::stats) {
+            info!("Proxy {}: {} requête(s), {} échec(s){}",
+                  proxy.proxy.as_deref().unwrap_or("direct"), proxy.requests, proxy.failures,
+                  if proxy.benched { ", à l'écart" } else { "" });
+        }
         
-        Ok(seasons)
+        Ok((seasons, report))
+    }
+
+    /// Cherche les séries dont le titre correspond à `query` via le formulaire de recherche du
+    /// site (lu sur la page d'accueil: action, méthode, champs cachés); rend titre, année et URL
+    /// de chaque série trouvée
+    pub async fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        info!("Recherche FZTV: {}", query);
+        let base = Url::parse(&self.base_url).context("URL de base invalide")?;
+        let form = match self.fetch_page(base.as_str()).await {
+            Ok(html) => SearchForm::find(&Html::parse_document(&html), &base),
+            Err(e) => {
+                warn!("Page d'accueil FZTV illisible ({}), formulaire de recherche par défaut", e);
+                None
+            }
+        };
+        let form = match form {
+            Some(form) => form,
+            None => SearchForm::fallback(&base)?,
+        };
+
+        let _permit = self.semaphore
+            .acquire()
+            .await
+            .map_err(|e| anyhow::anyhow!("Erreur d'acquisition du semaphore: {}", e))?;
+        self.or_cancel(self.pacer.wait()).await?;
+        let fields = form.fields(query);
+        let route = self.route();
+        let mut request = match form.post {
+            true => route.client.post(form.action.clone()).form(&fields),
+            false => route.client.get(form.action.clone()).query(&fields),
+        };
+        if let Some(agent) = self.user_agent_for(&route) {
+            request = request.header(reqwest::header::USER_AGENT, agent);
+        }
+        let response = request.send().await
+            .inspect_err(|_| self.report_route(&route, Err(())))
+            .context("Erreur lors de la requête de recherche")?;
+        self.report_route(&route, Ok(response.status()));
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!("Erreur HTTP: {}", response.status()));
+        }
+        let page = response.url().clone();
+        let html = response.text().await.context("Impossible de lire les résultats de recherche")?;
+        let results = parse_search_results(&Html::parse_document(&html), &page, query);
+        info!("{} séries trouvées pour « {} »", results.len(), query);
+        Ok(results)
     }
 
     /// Scrape les liens de téléchargement réels avec traitement rapide pour éviter l'expiration
@@ -1032,8 +1528,22 @@ impl FztvScraper {
     }
 
     /// Enrichit les saisons existantes avec les liens de téléchargement réels
-    /// Ne traite que le premier lien "High MP4" ou le premier lien disponible
+    /// Ne traite que le lien de la qualité préférée de chaque épisode (« High MP4 » par défaut,
+    /// sinon le premier), ou tous ses liens avec `EnrichOptions::all_qualities`
+    ///
+    /// La concurrence, le délai par lien et les qualités suivent `EnrichOptions` (voir
+    /// `with_enrich_options`).
     pub async fn enrich_with_actual_links(&self, seasons: Vec<Season>) -> Result<Vec<Season>> {
+        self.enrich_with(seasons, |url| async move { self.scrape_actual_download_link_fast(&url).await })
+            .await
+    }
+
+    /// Cœur de l'enrichissement, paramétré par l'étape de résolution d'un lien
+    async fn enrich_with<F, Fut>(&self, seasons: Vec<Season>, resolve: F) -> Result<Vec<Season>>
+    where
+        F: Fn(String) -> Fut,
+        Fut: std::future::Future<Output = Result<Option<String>>>,
+    {
         info!("Début de l'enrichissement des liens de téléchargement");
         
         // Créer une liste de toutes les tâches à traiter (season_idx, episode_idx, url, quality)
@@ -1041,38 +1551,47 @@ impl FztvScraper {
         
         for (season_idx, season) in seasons.iter().enumerate() {
             for (episode_idx, episode) in season.episodes.iter().enumerate() {
-                // Trouver l'index du premier lien "High MP4" ou prendre le premier
-                let target_index = episode.download_links.iter()
-                    .position(|link| link.quality.contains("High MP4"))
-                    .or_else(|| {
-                        if episode.download_links.is_empty() {
-                            None
-                        } else {
-                            Some(0)
-                        }
-                    });
-                
-                if let Some(link_idx) = target_index {
+                // Lien de la qualité préférée, ou tous les liens de l'épisode
+                for link_idx in self.enrich.links_to_resolve(&episode.download_links) {
                     let link = &episode.download_links[link_idx];
                     tasks.push((
                         season_idx,
                         episode_idx,
                         link_idx,
                         link.url.clone(),
-                        episode.name.clone(),
+                        match self.enrich.all_qualities {
+                            true => format!("{} ({})", episode.name, link.quality),
+                            false => episode.name.clone(),
+                        },
                     ));
                 }
             }
         }
         
-        info!("Traitement de {} liens en parallèle", tasks.len());
+        let concurrency = self.enrich.concurrency.max(1);
+        let link_timeout = self.enrich.link_timeout;
+        info!("Traitement de {} liens ({} en parallèle, délai {:?} par lien)", tasks.len(), concurrency, link_timeout);
+        self.emit(ScrapeEvent::LinksToResolve { count: tasks.len() });
         
         // Traiter toutes les tâches en parallèle avec limitation de concurrence
+        let resolve = &resolve;
         let results: Vec<_> = stream::iter(tasks)
             .map(|(season_idx, episode_idx, link_idx, url, episode_name)| async move {
+                if self.cancel.is_cancelled() {
+                    return None;
+                }
                 info!("Scraping du lien pour l'épisode: {}", episode_name);
                 
-                match self.scrape_actual_download_link_fast(&url).await {
+                let resolved = match self.or_cancel(tokio::time::timeout(link_timeout, resolve(url.clone()))).await.ok()? {
+                    Ok(result) => result,
+                    Err(_) => {
+                        warn!("Délai dépassé pour {} ({:?})", episode_name, link_timeout);
+                        self.record_failure(&url, &anyhow::anyhow!("Délai de {:?} dépassé", link_timeout), 1);
+                        Ok(None)
+                    }
+                };
+                self.emit(ScrapeEvent::LinkResolved { episode: episode_name.clone(), found: matches!(resolved, Ok(Some(_))) });
+                match resolved {
                     Ok(Some(download_url)) => {
                         info!("Lien trouvé pour {}: {}", episode_name, download_url);
                         Some((season_idx, episode_idx, link_idx, download_url))
@@ -1087,11 +1606,13 @@ impl FztvScraper {
                     }
                 }
             })
-            .buffer_unordered(20)  // Traiter jusqu'à 20 liens en parallèle (le semaphore dans fetch_page limite à 10 requêtes réelles)
+            .buffer_unordered(concurrency)  // Les requêtes réelles restent bornées par le sémaphore de fetch_page
             .filter_map(|x| async { x })
             .collect()
             .await;
         
+        self.check_cancelled()?;
+        
         // Appliquer les résultats aux saisons
         let mut enriched_seasons = seasons;
         for (season_idx, episode_idx, link_idx, download_url) in results {
@@ -1109,6 +1630,199 @@ impl FztvScraper {
     }
 }
 
+/// Parse le contenu onclick pour extraire l'URL de window.location.href, le fileid et le dkey
+/// (même schéma sur FZTV et FZMovies)
+pub(crate) fn parse_onclick(onclick: &str) -> Option<(String, String, Option<String>)> {
+    // Rechercher l'URL dans window.location.href (c'est l'URL importante, pas window.open)
+    if let Some(start) = onclick.find("window.location.href=\"") {
+        let start = start + 22; // Longueur de "window.location.href=\""
+        if let Some(end) = onclick[start..].find("\"") {
+            let url = &onclick[start..start + end];
+            
+            // Rechercher le fileid dans l'URL
+            if let Some(fileid_start) = onclick.find("fileid=") {
+                let fileid_start = fileid_start + 7; // Longueur de "fileid="
+                if let Some(fileid_end) = onclick[fileid_start..].find("&") {
+                    let file_id = &onclick[fileid_start..fileid_start + fileid_end];
+                    
+                    // Rechercher le dkey
+                    let dkey = if let Some(dkey_start) = onclick.find("dkey=") {
+                        let dkey_start = dkey_start + 5; // Longueur de "dkey="
+                        if let Some(dkey_end) = onclick[dkey_start..].find("\"") {
+                            Some(onclick[dkey_start..dkey_start + dkey_end].to_string())
+                        } else {
+                            None
+                        }
+                    } else {
+                        None
+                    };
+                    
+                    return Some((url.to_string(), file_id.to_string(), dkey));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Formulaire de recherche du site
+#[derive(Debug, Clone, PartialEq)]
+struct SearchForm {
+    action: Url,
+    post: bool,
+    /// Champ recevant la recherche
+    field: String,
+    /// Champs cachés et valeurs par défaut des autres champs (listes, boutons)
+    extra: Vec<(String, String)>,
+}
+
+impl SearchForm {
+    /// Premier formulaire de `document` ayant un champ texte (de préférence nommé `search`,
+    /// `q`, `query`...); les URLs sont résolues depuis `page`
+    fn find(document: &Html, page: &Url) -> Option<Self> {
+        let forms = Selector::parse("form").ok()?;
+        let text_inputs = Selector::parse("input[type=\"text\"][name], input[type=\"search\"][name], input:not([type])[name]").ok()?;
+        let others = Selector::parse("input[type=\"hidden\"][name], input[type=\"submit\"][name], select[name]").ok()?;
+        let option = Selector::parse("option").ok()?;
+        let is_search = |name: &str| ["search", "q", "query", "keyword", "s"].iter().any(|n| name.eq_ignore_ascii_case(n) || name.to_ascii_lowercase().contains("search"));
+
+        let mut candidates = Vec::new();
+        for form in document.select(&forms) {
+            let inputs: Vec<_> = form.select(&text_inputs).filter_map(|i| i.value().attr("name")).collect();
+            let Some(field) = inputs.iter().find(|n| is_search(n)).or(inputs.first()) else { continue };
+            let action = form.value().attr("action").unwrap_or("");
+            let extra = form
+                .select(&others)
+                .filter_map(|e| {
+                    let name = e.value().attr("name")?;
+                    let value = match e.value().name() {
+                        "select" => {
+                            let selected = e.select(&option).find(|o| o.value().attr("selected").is_some()).or_else(|| e.select(&option).next())?;
+                            selected.value().attr("value").map(str::to_string).unwrap_or_else(|| selected.text().collect::<String>().trim().to_string())
+                        }
+                        _ => e.value().attr("value").unwrap_or("").to_string(),
+                    };
+                    Some((name.to_string(), value))
+                })
+                .collect();
+            let form = SearchForm {
+                action: page.join(action).ok()?,
+                post: form.value().attr("method").is_some_and(|m| m.eq_ignore_ascii_case("post")),
+                field: field.to_string(),
+                extra,
+            };
+            candidates.push((is_search(&form.field), form));
+        }
+        candidates.iter().position(|(search, _)| *search).or((!candidates.is_empty()).then_some(0)).map(|i| candidates.swap_remove(i).1)
+    }
+
+    /// `search.php?search=...` relatif à l'URL de base
+    fn fallback(base: &Url) -> Result<Self> {
+        Ok(SearchForm {
+            action: base.join(DEFAULT_SEARCH_ACTION).context("URL de recherche invalide")?,
+            post: false,
+            field: DEFAULT_SEARCH_FIELD.to_string(),
+            extra: Vec::new(),
+        })
+    }
+
+    /// Champs envoyés pour `query`
+    fn fields(&self, query: &str) -> Vec<(String, String)> {
+        let mut fields = vec![(self.field.clone(), query.to_string())];
+        fields.extend(self.extra.iter().filter(|(name, _)| *name != self.field).cloned());
+        fields
+    }
+}
+
+/// Séries d'une page de résultats: liens internes dont le texte contient chaque mot de `query`
+/// (sans tenir compte de la casse), hors formulaire et pagination; l'année est lue dans le texte
+/// du lien ou de son parent (`Breaking Bad (2008)`)
+fn parse_search_results(document: &Html, page: &Url, query: &str) -> Vec<SearchResult> {
+    let Ok(links) = Selector::parse("a[href]") else { return Vec::new() };
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let mut results: Vec<SearchResult> = Vec::new();
+    for link in document.select(&links) {
+        let text = link.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ");
+        let lower = text.to_lowercase();
+        if text.is_empty() || !words.iter().all(|w| lower.contains(w.as_str())) {
+            continue;
+        }
+        let Some(url) = link.value().attr("href").and_then(|href| page.join(href.trim()).ok()) else { continue };
+        if url.host_str() != page.host_str() || url.path() == page.path() || results.iter().any(|r| r.url == url.as_str()) {
+            continue;
+        }
+        let parent_text = link.parent().and_then(scraper::ElementRef::wrap).map(|p| p.text().collect::<String>()).unwrap_or_default();
+        let year = year_in(&text).or_else(|| year_in(&parent_text));
+        let title = match year {
+            Some(year) => text.replace(&format!("({year})"), "").trim().to_string(),
+            None => text,
+        };
+        results.push(SearchResult { title, year, url: url.to_string() });
+    }
+    results
+}
+
+/// Première année plausible (1900‑2099) de `text`, isolée des autres chiffres
+pub(crate) fn year_in(text: &str) -> Option<u32> {
+    let bytes = text.as_bytes();
+    (0..bytes.len().saturating_sub(3)).find_map(|i| {
+        let digits = &bytes[i..i + 4];
+        let isolated = (i == 0 || !bytes[i - 1].is_ascii_digit()) && bytes.get(i + 4).is_none_or(|b| !b.is_ascii_digit());
+        let year: u32 = std::str::from_utf8(digits).ok()?.parse().ok()?;
+        (isolated && digits.iter().all(u8::is_ascii_digit) && (1900..2100).contains(&year)).then_some(year)
+    })
+}
+
+impl SiteScraper for FztvScraper {
+    fn name(&self) -> &str {
+        "FZTV"
+    }
+
+    /// Domaine de l'URL de base
+    fn domains(&self) -> Vec<String> {
+        Url::parse(&self.base_url).ok().and_then(|u| u.host_str().map(str::to_string)).into_iter().collect()
+    }
+
+    fn search<'a>(&'a self, query: &'a str) -> LocalBoxFuture<'a, Result<Vec<SearchResult>>> {
+        Box::pin(self.search(query))
+    }
+
+    fn list_seasons<'a>(&'a self, series_url: &'a str) -> LocalBoxFuture<'a, Result<Vec<Season>>> {
+        self.list_seasons_since(series_url, &[])
+    }
+
+    /// Saisons de `scrape_all`, seules les nouvelles et la plus récente des connues étant relues; ses
+    /// échecs parviennent à l'onglet par la progression
+    fn list_seasons_since<'a>(&'a self, series_url: &'a str, previous: &'a [Season]) -> LocalBoxFuture<'a, Result<Vec<Season>>> {
+        Box::pin(async move {
+            let (seasons, report) = self.scrape_all(series_url, previous).await?;
+            if !report.is_empty() {
+                warn!("{} page(s) abandonnée(s) pour {}", report.failures.len(), series_url);
+            }
+            Ok(seasons)
+        })
+    }
+
+    fn list_episodes<'a>(&'a self, season_url: &'a str) -> LocalBoxFuture<'a, Result<Vec<Episode>>> {
+        Box::pin(self.scrape_episodes(season_url))
+    }
+
+    /// Qualité préférée (ou toutes) de chaque épisode, selon `with_enrich_options`
+    fn enrich<'a>(&'a self, seasons: Vec<Season>) -> LocalBoxFuture<'a, Result<Vec<Season>>> {
+        Box::pin(self.enrich_with_actual_links(seasons))
+    }
+
+    /// Premier lien réel déjà résolu, sinon `episode.php -> downloadmp4.php -> liens`
+    fn resolve_download<'a>(&'a self, link: &'a DownloadLink) -> LocalBoxFuture<'a, Result<Option<String>>> {
+        Box::pin(async move {
+            match link.actual_download_urls.first() {
+                Some(url) => Ok(Some(url.clone())),
+                None => self.scrape_actual_download_link_fast(&link.url).await,
+            }
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1126,4 +1840,551 @@ mod tests {
         assert_eq!(file_id, "154326");
         assert_eq!(dkey, Some("d7bf5ed1208135eee507edac13ac6d54".to_string()));
     }
+
+    #[test]
+    fn test_search_form_and_results() {
+        let page = Url::parse("https://fztvseries.mobi/").unwrap();
+        let home = Html::parse_document(r#"
+            <form action="/login.php" method="post"><input type="text" name="user"></form>
+            <form action="search.php" method="POST">
+                <input type="text" name="search"><input type="hidden" name="beginsearch" value="Search">
+                <select name="vsearch"><option value="">Tout</option><option value="t" selected>Titres</option></select>
+            </form>"#);
+        let form = SearchForm::find(&home, &page).unwrap();
+        assert_eq!(form.action.as_str(), "https://fztvseries.mobi/search.php");
+        assert!(form.post);
+        assert_eq!(form.fields("breaking"), [("search", "breaking"), ("beginsearch", "Search"), ("vsearch", "t")].map(|(k, v)| (k.to_string(), v.to_string())));
+        assert_eq!(SearchForm::find(&Html::parse_document("<p>rien</p>"), &page), None);
+        assert_eq!(SearchForm::fallback(&page).unwrap().action.as_str(), "https://fztvseries.mobi/search.php");
+
+        let results = Html::parse_document(r#"
+            <a href="search.php?search=breaking+bad&page=2">Breaking Bad 2</a>
+            <div><a href="/v/breaking-bad.htm">Breaking  Bad (2008)</a></div>
+            <div><a href="v/breaking-bad.htm">Breaking Bad</a></div>
+            <div><a href="https://autre.site/breaking-bad">Breaking Bad</a></div>
+            <div><a href="/v/el-camino.htm">El Camino: A Breaking Bad Movie</a> 2019 - 1080p</div>
+            <a href="/v/better.htm">Better Call Saul</a>"#);
+        let page = Url::parse("https://fztvseries.mobi/search.php?search=breaking+bad").unwrap();
+        let found = parse_search_results(&results, &page, "breaking BAD");
+        assert_eq!(found, [
+            SearchResult { title: "Breaking Bad".into(), year: Some(2008), url: "https://fztvseries.mobi/v/breaking-bad.htm".into() },
+            SearchResult { title: "El Camino: A Breaking Bad Movie".into(), year: Some(2019), url: "https://fztvseries.mobi/v/el-camino.htm".into() },
+        ]);
+        assert_eq!(year_in("S01 1080p 12019"), None);
+    }
+
+    /// Capture simulée: retourne des entrées fixes et compte les appels
+    struct MockCapture {
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl NetworkCapture for MockCapture {
+        fn capture<'a>(&'a self, _url: &'a str) -> futures::future::BoxFuture<'a, Result<Vec<crate::sniffers::network_sniffer::NetworkEntry>>> {
+            Box::pin(async move {
+                self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(vec![crate::sniffers::network_sniffer::NetworkEntry {
+                    url: "https://cdn.example.com/episode1.mp4".to_string(),
+                    method: Some("GET".to_string()),
+                    status: Some(200),
+                    resource_type: Some("Media".to_string()),
+                    headers: None,
+                    timestamp: 0.0,
+                }])
+            })
+        }
+    }
+
+    fn season_with_links(count: usize) -> Season {
+        Season {
+            name: "Saison 1".to_string(),
+            url: "http://example.com/s1".to_string(),
+            episodes: (0..count)
+                .map(|i| Episode {
+                    name: format!("Épisode {}", i + 1),
+                    download_links: vec![DownloadLink {
+                        quality: "High MP4".to_string(),
+                        url: format!("episode.php?id={}", i),
+                        file_id: None,
+                        dkey: None,
+                        actual_download_urls: Vec::new(),
+                    }],
+                    is_new: false,
+                })
+                .collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enrich_respects_configured_concurrency() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let scraper = FztvScraper::new("http://example.com".to_string())
+            .with_enrich_options(EnrichOptions { concurrency: 3, link_timeout: std::time::Duration::from_secs(5), ..Default::default() });
+        let in_flight = AtomicUsize::new(0);
+        let max_seen = AtomicUsize::new(0);
+
+        let seasons = scraper
+            .enrich_with(vec![season_with_links(12)], |url| {
+                let (in_flight, max_seen) = (&in_flight, &max_seen);
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_seen.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    Ok(Some(format!("https://cdn.example.com/{}", url)))
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(max_seen.load(Ordering::SeqCst), 3);
+        assert!(seasons[0].episodes.iter().all(|e| e.download_links[0].actual_download_urls.len() == 1));
+    }
+
+    #[tokio::test]
+    async fn test_enrich_skips_links_exceeding_timeout() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let scraper = FztvScraper::new("http://example.com".to_string())
+            .with_enrich_options(EnrichOptions { concurrency: 2, link_timeout: std::time::Duration::from_millis(10), ..Default::default() })
+            .with_progress(tx);
+
+        let seasons = scraper
+            .enrich_with(vec![season_with_links(2)], |url| async move {
+                if url.ends_with("id=0") {
+                    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                }
+                Ok(Some(format!("https://cdn.example.com/{}", url)))
+            })
+            .await
+            .unwrap();
+
+        assert!(seasons[0].episodes[0].download_links[0].actual_download_urls.is_empty());
+        assert_eq!(seasons[0].episodes[1].download_links[0].actual_download_urls.len(), 1);
+
+        // un lien expiré compte comme résolu sans URL
+        let mut progress = crate::scrapers::progress::ScrapeProgress::default();
+        while let Ok(event) = rx.try_recv() {
+            progress.apply(&event);
+        }
+        assert_eq!((progress.links_total, progress.links_done, progress.links_found), (2, 2, 1));
+    }
+
+    #[tokio::test]
+    async fn test_enrich_quality_preference_and_all_qualities() {
+        let link = |quality: &str, id: usize| DownloadLink {
+            quality: quality.to_string(),
+            url: format!("episode.php?id={}", id),
+            file_id: None,
+            dkey: None,
+            actual_download_urls: Vec::new(),
+        };
+        let season = Season {
+            name: "Saison 1".to_string(),
+            url: "http://example.com/s1".to_string(),
+            episodes: vec![
+                Episode { name: "Épisode 1".to_string(), download_links: vec![link("Low", 0), link("High MP4", 1), link("720p HD", 2)], is_new: false },
+                Episode { name: "Épisode 2".to_string(), download_links: vec![link("Low", 3), link("Mobile", 4)], is_new: false },
+            ],
+        };
+        let preference = vec!["1080p".to_string(), "720P".to_string(), "High MP4".to_string()];
+        assert_eq!(preferred_link(&season.episodes[0].download_links, &preference), Some(2));
+        assert_eq!(preferred_link(&season.episodes[1].download_links, &preference), Some(0));
+        assert_eq!(preferred_link(&[], &preference), None);
+
+        let resolve = |url: String| async move { Ok(Some(format!("https://cdn.example.com/{}", url))) };
+        let resolved = |seasons: &[Season]| -> Vec<Vec<usize>> {
+            seasons[0].episodes.iter().map(|e| e.download_links.iter().map(|l| l.actual_download_urls.len()).collect()).collect()
+        };
+
+        let scraper = FztvScraper::new("http://example.com".to_string())
+            .with_enrich_options(EnrichOptions { quality_preference: preference.clone(), ..Default::default() });
+        let seasons = scraper.enrich_with(vec![season.clone()], resolve).await.unwrap();
+        assert_eq!(resolved(&seasons), [vec![0, 0, 1], vec![1, 0]]);
+
+        let scraper = FztvScraper::new("http://example.com".to_string())
+            .with_enrich_options(EnrichOptions { all_qualities: true, ..Default::default() });
+        let seasons = scraper.enrich_with(vec![season], resolve).await.unwrap();
+        assert_eq!(resolved(&seasons), [vec![1, 1, 1], vec![1, 1]]);
+        assert_eq!(seasons[0].episodes[0].download_links[2].actual_download_urls, ["https://cdn.example.com/episode.php?id=2"]);
+
+        let config: AppConfig = toml::from_str(concat!(
+            "[scrapers]\n",
+            "resolve_all_qualities = true\n",
+            "enrich_concurrency = 0\n",
+            "enrich_link_timeout_secs = 15\n",
+        )).unwrap();
+        let options = enrich_options_from_config(&config);
+        assert_eq!((options.all_qualities, options.concurrency, options.link_timeout), (true, 1, std::time::Duration::from_secs(15)));
+        assert_eq!(options.quality_preference, [DEFAULT_QUALITY]);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_interrupts_requests_and_enrichment() {
+        use std::time::Duration;
+
+        // Serveur qui accepte les connexions sans jamais répondre
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/serie.htm", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let cancel = CancellationToken::new();
+        let scraper = FztvScraper::new(url.clone()).with_cancel(cancel.clone());
+        let stop = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            stop.cancel();
+        });
+        let fetched = tokio::time::timeout(Duration::from_secs(5), scraper.fetch_page(&url)).await.expect("requête non interrompue");
+        assert!(fetched.unwrap_err().to_string().contains("annulé"));
+
+        let cancel = CancellationToken::new();
+        let scraper = FztvScraper::new(url).with_cancel(cancel.clone());
+        let enriched = scraper.enrich_with(vec![season_with_links(4)], |_| {
+            let cancel = cancel.clone();
+            async move {
+                cancel.cancel();
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                Ok(None)
+            }
+        });
+        assert!(tokio::time::timeout(Duration::from_secs(5), enriched).await.expect("enrichissement non interrompu").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_cache_and_etag_revalidation() {
+        use hyper::service::{make_service_fn, service_fn};
+        use hyper::{Body, Request, Response, Server, StatusCode};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        // Serveur: ETag fixe, 304 si le client le présente
+        let (requests, revalidated) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let (req_count, reval_count) = (requests.clone(), revalidated.clone());
+        let make_svc = make_service_fn(move |_| {
+            let (req_count, reval_count) = (req_count.clone(), reval_count.clone());
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+                    req_count.fetch_add(1, Ordering::SeqCst);
+                    let matched = req.headers().get("if-none-match").is_some_and(|v| v == "\"v1\"");
+                    if matched {
+                        reval_count.fetch_add(1, Ordering::SeqCst);
+                    }
+                    async move {
+                        let response = match matched {
+                            true => Response::builder().status(StatusCode::NOT_MODIFIED).body(Body::empty()),
+                            false => Response::builder().header("etag", "\"v1\"").body(Body::from("<html>page</html>")),
+                        };
+                        Ok::<_, hyper::Error>(response.unwrap())
+                    }
+                }))
+            }
+        });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v/serie.htm", listener.local_addr().unwrap());
+        tokio::spawn(Server::from_tcp(listener).unwrap().serve(make_svc));
+
+        let dir = tempfile::tempdir().unwrap();
+        // Durée de vie nulle: chaque récupération revalide
+        let scraper = FztvScraper::new(url.clone()).with_cache(Some(PageCache::new(dir.path(), Duration::ZERO)));
+        assert_eq!(scraper.fetch_page(&url).await.unwrap(), "<html>page</html>");
+        assert_eq!(scraper.fetch_page(&url).await.unwrap(), "<html>page</html>");
+        assert_eq!((requests.load(Ordering::SeqCst), revalidated.load(Ordering::SeqCst)), (2, 1));
+
+        // Page fraîche: aucune requête
+        let scraper = FztvScraper::new(url.clone()).with_cache(Some(PageCache::new(dir.path(), Duration::from_secs(3600))));
+        assert_eq!(scraper.fetch_page(&url).await.unwrap(), "<html>page</html>");
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        // Clé de session: jamais servie par le cache
+        let volatile = format!("{url}?fileid=1&dkey=k");
+        scraper.fetch_page(&volatile).await.unwrap();
+        scraper.fetch_page(&volatile).await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_retries_transient_errors_and_reports_failures() {
+        use hyper::service::{make_service_fn, service_fn};
+        use hyper::{Body, Request, Response, Server, StatusCode};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use crate::downloader::RetryPolicy;
+
+        // /flaky: deux 503 puis la page; /missing: 404; /down: toujours 503
+        let requests = Arc::new(AtomicUsize::new(0));
+        let flaky = Arc::new(AtomicUsize::new(0));
+        let (req_count, flaky_count) = (requests.clone(), flaky.clone());
+        let make_svc = make_service_fn(move |_| {
+            let (req_count, flaky_count) = (req_count.clone(), flaky_count.clone());
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+                    req_count.fetch_add(1, Ordering::SeqCst);
+                    let status = match req.uri().path() {
+                        "/flaky" if flaky_count.fetch_add(1, Ordering::SeqCst) < 2 => StatusCode::SERVICE_UNAVAILABLE,
+                        "/flaky" => StatusCode::OK,
+                        "/missing" => StatusCode::NOT_FOUND,
+                        _ => StatusCode::SERVICE_UNAVAILABLE,
+                    };
+                    async move { Ok::<_, hyper::Error>(Response::builder().status(status).body(Body::from("<html>ok</html>")).unwrap()) }
+                }))
+            }
+        });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(Server::from_tcp(listener).unwrap().serve(make_svc));
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let retry = RetryPolicy { max_attempts: 3, initial_backoff: std::time::Duration::from_millis(1), max_backoff: std::time::Duration::from_millis(1), jitter: 0.0 };
+        let scraper = FztvScraper::new(base.clone())
+            .with_options(ScraperOptions { retry, ..Default::default() })
+            .with_progress(tx);
+
+        assert_eq!(scraper.fetch_page(&format!("{base}/flaky")).await.unwrap(), "<html>ok</html>");
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+        assert!(scraper.fetch_page(&format!("{base}/missing")).await.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 4, "404 non relancé");
+        assert!(scraper.fetch_page(&format!("{base}/down")).await.is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 7);
+
+        let failures: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|event| match event {
+                ScrapeEvent::Failed(failure) => Some((failure.url.rsplit('/').next().unwrap().to_string(), failure.attempts)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(failures, [("missing".to_string(), 1), ("down".to_string(), 3)]);
+        assert_eq!(scraper.failures.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_rotates_user_agents_and_tracks_blocks() {
+        use hyper::service::{make_service_fn, service_fn};
+        use hyper::{Body, Request, Response, Server, StatusCode};
+        use crate::downloader::RetryPolicy;
+
+        // /blocked: toujours 429; ailleurs la page. Le user-agent de chaque requête est noté
+        let agents = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = agents.clone();
+        let make_svc = make_service_fn(move |_| {
+            let seen = seen.clone();
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+                    let agent = req.headers().get("user-agent").and_then(|v| v.to_str().ok()).unwrap_or_default().to_string();
+                    seen.lock().unwrap().push(agent);
+                    let status = match req.uri().path() {
+                        "/blocked" => StatusCode::TOO_MANY_REQUESTS,
+                        _ => StatusCode::OK,
+                    };
+                    let response = Response::builder().status(status).header("set-cookie", "session=abc; Path=/");
+                    async move { Ok::<_, hyper::Error>(response.body(Body::from("<html>ok</html>")).unwrap()) }
+                }))
+            }
+        });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(Server::from_tcp(listener).unwrap().serve(make_svc));
+
+        let pool = RotationPool::new(&[], vec!["agent-a".into(), "agent-b".into()], std::time::Duration::from_secs(60), &CookieJar::in_memory()).unwrap();
+        let jar = CookieJar::in_memory();
+        let scraper = FztvScraper::new(base.clone())
+            .with_options(ScraperOptions { retry: RetryPolicy { max_attempts: 1, ..Default::default() }, ..Default::default() })
+            .with_rotation(Some(pool.clone()))
+            .with_cookie_jar(jar.clone());
+
+        for _ in 0..3 {
+            assert_eq!(scraper.fetch_page(&format!("{base}/page")).await.unwrap(), "<html>ok</html>");
+        }
+        assert_eq!(*agents.lock().unwrap(), ["agent-a", "agent-b", "agent-a"]);
+        // les clients de la rotation déposent leurs cookies dans la session du scraper
+        assert_eq!(jar.header_for(&Url::parse(&base).unwrap()).as_deref(), Some("session=abc"));
+        assert!(!pool.stats()[0].benched);
+
+        for _ in 0..3 {
+            assert!(scraper.fetch_page(&format!("{base}/blocked")).await.is_err());
+        }
+        let stats = &pool.stats()[0];
+        assert_eq!((stats.requests, stats.failures, stats.benched), (6, 3, true));
+    }
+
+    struct MockSolver {
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl ChallengeSolver for MockSolver {
+        fn solve<'a>(&'a self, _url: &'a str) -> futures::future::BoxFuture<'a, Result<challenge::Clearance>> {
+            Box::pin(async move {
+                self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(challenge::Clearance {
+                    cookies: vec![challenge::SolvedCookie { name: "cf_clearance".into(), value: "ok".into(), domain: "127.0.0.1".into(), path: "/".into() }],
+                    user_agent: Some("navigateur".into()),
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_passes_anti_bot_challenge_once() {
+        use hyper::service::{make_service_fn, service_fn};
+        use hyper::{Body, Request, Response, Server, StatusCode};
+
+        // Page de vérification Cloudflare tant que le cookie et le user-agent du navigateur manquent
+        let make_svc = make_service_fn(|_| async {
+            Ok::<_, hyper::Error>(service_fn(|req: Request<Body>| async move {
+                let header = |name| req.headers().get(name).and_then(|v| v.to_str().ok()).unwrap_or_default().to_string();
+                let cleared = header("cookie").contains("cf_clearance=ok") && header("user-agent") == "navigateur";
+                let response = match cleared {
+                    true => Response::new(Body::from("<html>ok</html>")),
+                    false => Response::builder()
+                        .status(StatusCode::SERVICE_UNAVAILABLE)
+                        .body(Body::from("<html><title>Just a moment...</title><script>_cf_chl_opt={}</script></html>"))
+                        .unwrap(),
+                };
+                Ok::<_, hyper::Error>(response)
+            }))
+        });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(Server::from_tcp(listener).unwrap().serve(make_svc));
+
+        let solver = Arc::new(MockSolver { calls: Default::default() });
+        let mut scraper = FztvScraper::new(base.clone()).with_cookie_jar(CookieJar::in_memory());
+        scraper.challenge_solver = solver.clone();
+        for page in ["a", "b"] {
+            assert_eq!(scraper.fetch_page(&format!("{base}/{page}")).await.unwrap(), "<html>ok</html>");
+        }
+        assert_eq!(solver.calls.load(std::sync::atomic::Ordering::SeqCst), 1, "cookies réutilisés pour la page suivante");
+
+        // désactivé: la page de vérification reste une erreur HTTP
+        let solver = Arc::new(MockSolver { calls: Default::default() });
+        let mut scraper = FztvScraper::new(base.clone())
+            .with_cookie_jar(CookieJar::in_memory())
+            .with_options(ScraperOptions { solve_challenges: false, retry: crate::downloader::RetryPolicy { max_attempts: 1, ..Default::default() }, ..Default::default() });
+        scraper.challenge_solver = solver.clone();
+        assert!(scraper.fetch_page(&format!("{base}/a")).await.is_err());
+        assert_eq!(solver.calls.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_browser_fallback_used_when_static_extraction_is_empty() {
+        let capture = Arc::new(MockCapture { calls: Default::default() });
+        let mut scraper = FztvScraper::new("http://example.com".to_string()).with_browser_fallback(true);
+        scraper.network_capture = capture.clone();
+
+        // Page dont les liens sont injectés en JavaScript: rien dans le HTML statique
+        let document = Html::parse_document("<html><body><script>load()</script></body></html>");
+        let static_urls = scraper.scrape_download_page_fast(&document).await.unwrap();
+        assert!(static_urls.is_empty());
+
+        let urls = scraper.fallback_if_empty("http://example.com/downloadmp4.php", static_urls).await;
+        assert_eq!(urls, vec!["https://cdn.example.com/episode1.mp4".to_string()]);
+        assert_eq!(capture.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // Résultats statiques présents ou repli désactivé: le navigateur n'est pas lancé
+        let kept = scraper.fallback_if_empty("http://example.com/p", vec!["https://a/b.mp4".to_string()]).await;
+        assert_eq!(kept, vec!["https://a/b.mp4".to_string()]);
+        let scraper = scraper.with_browser_fallback(false);
+        assert!(scraper.fallback_if_empty("http://example.com/p", Vec::new()).await.is_empty());
+        assert_eq!(capture.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_browser_fallback_through_scraping_but_not_on_fetch_errors() {
+        use hyper::service::{make_service_fn, service_fn};
+        use hyper::{Body, Request, Response, Server, StatusCode};
+        use std::sync::atomic::Ordering;
+
+        // Pages statiques sans liens (injectés en JavaScript); /missing/… répond 404
+        let make_svc = make_service_fn(|_| async {
+            Ok::<_, hyper::Error>(service_fn(|req: Request<Body>| async move {
+                let response = match req.uri().path() {
+                    "/episode.php" => Response::new(Body::from(r#"<html><body><a href="downloadmp4.php?f=1">Download</a></body></html>"#)),
+                    "/gone.php" => Response::new(Body::from(r#"<html><body><a href="missing/downloadmp4.php">Download</a></body></html>"#)),
+                    "/missing/downloadmp4.php" => Response::builder().status(StatusCode::NOT_FOUND).body(Body::empty()).unwrap(),
+                    _ => Response::new(Body::from("<html><body><script>load()</script></body></html>")),
+                };
+                Ok::<_, hyper::Error>(response)
+            }))
+        });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(Server::from_tcp(listener).unwrap().serve(make_svc));
+
+        let capture = Arc::new(MockCapture { calls: Default::default() });
+        let mut scraper = FztvScraper::new(base.clone()).with_browser_fallback(true);
+        scraper.network_capture = capture.clone();
+
+        let episodes = scraper.scrape_episodes(&format!("{base}season")).await.unwrap();
+        assert_eq!(episodes[0].download_links[0].url, "https://cdn.example.com/episode1.mp4");
+        assert_eq!(capture.calls.load(Ordering::SeqCst), 1);
+
+        let link = scraper.scrape_actual_download_link_fast("episode.php").await.unwrap();
+        assert_eq!(link.as_deref(), Some("https://cdn.example.com/episode1.mp4"));
+        assert_eq!(capture.calls.load(Ordering::SeqCst), 2);
+
+        // page de téléchargement injoignable: pas de navigateur
+        assert_eq!(scraper.scrape_actual_download_link_fast("gone.php").await.unwrap(), None);
+        assert_eq!(capture.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_scrape_seasons_reuses_settled_seasons() {
+        use hyper::service::{make_service_fn, service_fn};
+        use hyper::{Body, Request, Response, Server};
+
+        // /asc liste les saisons 1 à 3, /desc de la plus récente à la plus ancienne; les pages
+        // de saison n'ont pas d'épisodes
+        let paths = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = paths.clone();
+        let make_svc = make_service_fn(move |_| {
+            let seen = seen.clone();
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+                    let path = req.uri().path().to_string();
+                    let order: &[u32] = match path.as_str() {
+                        "/asc" => &[1, 2, 3],
+                        "/desc" => &[3, 2, 1],
+                        _ => &[],
+                    };
+                    if order.is_empty() {
+                        seen.lock().unwrap().push(path);
+                    }
+                    let body = order
+                        .iter()
+                        .map(|n| format!(r#"<a itemprop="url" href="/season-{n}"><span itemprop="name">Season {n}</span></a>"#))
+                        .collect::<String>();
+                    async move { Ok::<_, hyper::Error>(Response::new(Body::from(format!("<html><body>{body}</body></html>")))) }
+                }))
+            }
+        });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(Server::from_tcp(listener).unwrap().serve(make_svc));
+
+        let known = |n: u32| Season {
+            name: format!("Season {n}"),
+            url: format!("{base}/season-{n}"),
+            episodes: vec![Episode { name: "Episode 1".into(), download_links: Vec::new(), is_new: false }],
+        };
+        let scraper = FztvScraper::new(base.clone());
+        let fetched = || {
+            let mut fetched = std::mem::take(&mut *paths.lock().unwrap());
+            fetched.sort();
+            fetched
+        };
+
+        // saison 1 reprise de la session, 2 (plus récente connue) et 3 (nouvelle) relues
+        let mut seasons = scraper.scrape_seasons(&format!("{base}/asc"), &[known(1), known(2)]).await.unwrap();
+        seasons.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(fetched(), ["/season-2", "/season-3"]);
+        assert_eq!(seasons.iter().map(|s| s.episodes.len()).collect::<Vec<_>>(), [1, 0, 0]);
+
+        // page de la plus récente à la plus ancienne: la saison en cours reste relue
+        let seasons = scraper.scrape_seasons(&format!("{base}/desc"), &[known(1), known(2), known(3)]).await.unwrap();
+        assert_eq!(fetched(), ["/season-3"]);
+        assert_eq!(seasons.len(), 3);
+    }
 }
@@ -1 +1,2 @@
 pub mod fztv_scraper;
+pub mod fzmovies_scraper;
@@ -0,0 +1,537 @@
+//! Scraper générique piloté par des règles.
+//!
+//! Les sélecteurs de `FztvScraper` sont écrits en dur et cassent au moindre changement du site.
+//! Ici, un site est décrit dans un fichier TOML ou YAML du dossier `[scrapers] sites_dir`
+//! (`sites/` par défaut): sélecteurs des saisons et des épisodes, pagination, extraction des
+//! liens, expressions régulières appliquées aux attributs `onclick`. `SiteRules::compile` en
+//! fait un `GenericScraper`, qui rend les mêmes `Season` / `Episode` / `DownloadLink` que le
+//! scraper FZTV: un nouveau site s'ajoute sans recompiler.
+//!
+//! ```toml
+//! name = "Mon site"
+//! base_url = "https://series.example.com/"
+//!
+//! [seasons]                     # absent: la page de la série est l'unique saison
+//! selectors = ["a[itemprop=\"url\"]"]
+//! name = "span[itemprop=\"name\"]"
+//!
+//! [episodes]
+//! selectors = ["ul.list", "table tr"]   # essayés dans l'ordre
+//! pagination = { next = "a.next", max_pages = 10 }
+//!
+//! [links]
+//! selector = "a[onclick], a[href*=\"download\"]"
+//! quality = "small"
+//! onclick = 'window\.location\.href="(?P<url>[^"]*fileid=(?P<file_id>[^&"]+)[^"]*)"'
+//! ```
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use anyhow::{Context, Result};
+use futures::future::LocalBoxFuture;
+use futures::stream::{self, StreamExt};
+use regex::Regex;
+use reqwest::Client;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use url::Url;
+use crate::downloader::{AppConfig, CookieJar};
+use crate::scrapers::fzscrape::fztv_scraper::{DownloadLink, Episode, Season};
+use crate::scrapers::site::SiteScraper;
+
+/// Dossier des descriptions de sites par défaut
+const DEFAULT_SITES_DIR: &str = "sites";
+/// Pages suivies au plus par liste paginée, sauf `max_pages`
+const DEFAULT_MAX_PAGES: usize = 20;
+/// Saisons (ou pages intermédiaires) traitées en parallèle
+const CONCURRENCY: usize = 4;
+
+fn default_attr() -> String {
+    "href".to_string()
+}
+
+fn default_max_pages() -> usize {
+    DEFAULT_MAX_PAGES
+}
+
+/// Description d'un site, lue depuis TOML ou YAML
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SiteRules {
+    pub name: String,
+    /// Base des liens relatifs
+    pub base_url: String,
+    /// Hôtes servis par ces règles (celui de `base_url` à défaut); les sous‑domaines suivent
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    /// Liste des saisons sur la page de la série; absente, la page est l'unique saison
+    pub seasons: Option<ListRule>,
+    /// Entrées d'épisode sur la page d'une saison
+    pub episodes: ListRule,
+    /// Liens de téléchargement dans chaque entrée d'épisode
+    pub links: LinkRule,
+    /// Page intermédiaire d'un lien, d'où sont tirées les URLs réelles (`actual_download_urls`)
+    pub download_page: Option<LinkRule>,
+}
+
+/// Entrées d'une liste (saisons, épisodes)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListRule {
+    /// Sélecteurs des entrées, essayés dans l'ordre jusqu'au premier qui trouve quelque chose
+    pub selectors: Vec<String>,
+    /// Sous‑sélecteur du nom de l'entrée (son texte à défaut)
+    pub name: Option<String>,
+    /// Attribut portant l'URL de l'entrée, sur elle ou sur un descendant (saisons)
+    #[serde(default = "default_attr")]
+    pub attr: String,
+    pub pagination: Option<Pagination>,
+}
+
+/// Lien vers la page suivante d'une liste
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pagination {
+    /// Sélecteur du lien « page suivante »
+    pub next: String,
+    #[serde(default = "default_attr")]
+    pub attr: String,
+    #[serde(default = "default_max_pages")]
+    pub max_pages: usize,
+}
+
+/// Extraction des liens d'une entrée ou d'une page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LinkRule {
+    pub selector: String,
+    /// Sous‑sélecteur de la qualité affichée (texte du lien à défaut)
+    pub quality: Option<String>,
+    #[serde(default = "default_attr")]
+    pub attr: String,
+    /// Expression appliquée à l'attribut `onclick`, avec les groupes nommés `url` (requis),
+    /// `file_id` et `dkey`; prioritaire sur `attr` quand elle reconnaît l'attribut
+    pub onclick: Option<String>,
+    /// Ne garder que les URLs reconnues par cette expression
+    pub filter: Option<String>,
+    /// Liens directs vers le média: recopiés dans `actual_download_urls`
+    #[serde(default)]
+    pub direct: bool,
+}
+
+impl SiteRules {
+    pub fn from_toml(text: &str) -> Result<Self> {
+        toml::from_str(text).context("Règles TOML invalides")
+    }
+
+    pub fn from_yaml(text: &str) -> Result<Self> {
+        serde_yaml::from_str(text).context("Règles YAML invalides")
+    }
+
+    /// Lit `path` selon son extension (`.yaml` / `.yml`, sinon TOML)
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| format!("Lecture de {}", path.display()))?;
+        let yaml = path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("yaml") || e.eq_ignore_ascii_case("yml"));
+        if yaml { Self::from_yaml(&text) } else { Self::from_toml(&text) }
+            .with_context(|| format!("Site {}", path.display()))
+    }
+
+    /// Vérifie et compile sélecteurs et expressions en un scraper prêt à l'emploi
+    pub fn compile(self) -> Result<GenericScraper> {
+        let base_url = Url::parse(&self.base_url).with_context(|| format!("URL de base invalide: {}", self.base_url))?;
+        let mut hosts = self.hosts.iter().map(|h| h.to_ascii_lowercase()).collect::<Vec<_>>();
+        if hosts.is_empty() {
+            hosts.extend(base_url.host_str().map(str::to_ascii_lowercase));
+        }
+        let client = Client::builder()
+            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
+            .timeout(std::time::Duration::from_secs(30))
+            .cookie_provider(Arc::new(CookieJar::global()))
+            .build()
+            .context("Impossible de créer le client HTTP")?;
+        Ok(GenericScraper {
+            seasons: self.seasons.as_ref().map(|r| CompiledList::new(r, "seasons")).transpose()?,
+            episodes: CompiledList::new(&self.episodes, "episodes")?,
+            links: CompiledLinks::new(&self.links, "links")?,
+            download_page: self.download_page.as_ref().map(|r| CompiledLinks::new(r, "download_page")).transpose()?,
+            base_url,
+            hosts,
+            client,
+            rules: self,
+        })
+    }
+}
+
+fn selector(css: &str, rule: &str) -> Result<Selector> {
+    Selector::parse(css).map_err(|e| anyhow::anyhow!("Sélecteur invalide dans [{rule}] ({css}): {e}"))
+}
+
+fn regex(pattern: &str, rule: &str) -> Result<Regex> {
+    Regex::new(pattern).with_context(|| format!("Expression invalide dans [{rule}]"))
+}
+
+#[derive(Debug)]
+struct CompiledList {
+    selectors: Vec<Selector>,
+    name: Option<Selector>,
+    attr: String,
+    /// Descendant portant `attr`, quand l'entrée elle‑même ne l'a pas
+    with_attr: Selector,
+    pagination: Option<(Selector, String, usize)>,
+}
+
+impl CompiledList {
+    fn new(rule: &ListRule, name: &str) -> Result<Self> {
+        if rule.selectors.is_empty() {
+            anyhow::bail!("[{name}] sans sélecteur");
+        }
+        Ok(Self {
+            selectors: rule.selectors.iter().map(|css| selector(css, name)).collect::<Result<_>>()?,
+            name: rule.name.as_deref().map(|css| selector(css, name)).transpose()?,
+            attr: rule.attr.clone(),
+            with_attr: selector(&format!("[{}]", rule.attr), name)?,
+            pagination: rule.pagination.as_ref().map(|p| Ok::<_, anyhow::Error>((selector(&p.next, name)?, p.attr.clone(), p.max_pages.max(1)))).transpose()?,
+        })
+    }
+
+    /// Entrées du premier sélecteur qui en trouve
+    fn entries<'a>(&self, document: &'a Html) -> Vec<ElementRef<'a>> {
+        self.selectors
+            .iter()
+            .map(|s| document.select(s).collect::<Vec<_>>())
+            .find(|entries| !entries.is_empty())
+            .unwrap_or_default()
+    }
+
+    /// Nom d'une entrée: sous‑sélecteur, sinon ses premiers mots, sinon `fallback`
+    fn entry_name(&self, element: &ElementRef, fallback: impl FnOnce() -> String) -> String {
+        let text = match &self.name {
+            Some(name) => element.select(name).next().map(|e| e.text().collect::<String>()),
+            None => Some(element.text().collect::<String>()),
+        };
+        text.map(|t| t.split_whitespace().take(8).collect::<Vec<_>>().join(" "))
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(fallback)
+    }
+
+    /// URL de la page suivante, résolue depuis `page`
+    fn next_page(&self, document: &Html, page: &Url) -> Option<Url> {
+        let (next, attr, _) = self.pagination.as_ref()?;
+        let href = document.select(next).next()?.value().attr(attr)?;
+        page.join(href.trim()).ok()
+    }
+}
+
+#[derive(Debug)]
+struct CompiledLinks {
+    selector: Selector,
+    quality: Option<Selector>,
+    attr: String,
+    onclick: Option<Regex>,
+    filter: Option<Regex>,
+    direct: bool,
+}
+
+impl CompiledLinks {
+    fn new(rule: &LinkRule, name: &str) -> Result<Self> {
+        let onclick = rule.onclick.as_deref().map(|p| regex(p, name)).transpose()?;
+        if onclick.as_ref().is_some_and(|re| !re.capture_names().any(|n| n == Some("url"))) {
+            anyhow::bail!("[{name}] onclick: groupe nommé (?P<url>...) requis");
+        }
+        Ok(Self {
+            selector: selector(&rule.selector, name)?,
+            quality: rule.quality.as_deref().map(|css| selector(css, name)).transpose()?,
+            attr: rule.attr.clone(),
+            onclick,
+            filter: rule.filter.as_deref().map(|p| regex(p, name)).transpose()?,
+            direct: rule.direct,
+        })
+    }
+
+    /// Liens de `element` (ou d'une page entière), URLs résolues depuis `page`, sans doublons
+    fn extract(&self, element: ElementRef, page: &Url) -> Vec<DownloadLink> {
+        let mut links: Vec<DownloadLink> = Vec::new();
+        for link in element.select(&self.selector) {
+            let from_onclick = self.onclick.as_ref().zip(link.value().attr("onclick")).and_then(|(re, onclick)| {
+                let captures = re.captures(onclick)?;
+                let group = |name: &str| captures.name(name).map(|m| m.as_str().to_string());
+                Some((group("url")?, group("file_id"), group("dkey")))
+            });
+            let Some((raw, file_id, dkey)) = from_onclick.or_else(|| link.value().attr(&self.attr).map(|href| (href.to_string(), None, None))) else {
+                continue;
+            };
+            let Ok(url) = page.join(raw.trim()).map(String::from) else { continue };
+            if self.filter.as_ref().is_some_and(|re| !re.is_match(&url)) || links.iter().any(|l| l.url == url) {
+                continue;
+            }
+            let quality = self
+                .quality
+                .as_ref()
+                .and_then(|q| link.select(q).next())
+                .map_or_else(|| link.text().collect::<String>(), |e| e.text().collect::<String>());
+            let quality = Some(quality.trim().to_string()).filter(|q| !q.is_empty()).unwrap_or_else(|| "Qualité inconnue".to_string());
+            links.push(DownloadLink {
+                quality,
+                actual_download_urls: if self.direct { vec![url.clone()] } else { Vec::new() },
+                url,
+                file_id,
+                dkey,
+            });
+        }
+        links
+    }
+}
+
+/// Scraper compilé depuis des `SiteRules`
+#[derive(Debug)]
+pub struct GenericScraper {
+    rules: SiteRules,
+    base_url: Url,
+    hosts: Vec<String>,
+    client: Client,
+    seasons: Option<CompiledList>,
+    episodes: CompiledList,
+    links: CompiledLinks,
+    download_page: Option<CompiledLinks>,
+}
+
+impl GenericScraper {
+    /// Saisons de la série `main_url` avec leurs épisodes, puis URLs réelles des liens si le
+    /// site décrit une page intermédiaire (`download_page`)
+    pub async fn scrape_all(&self, main_url: &str) -> Result<Vec<Season>> {
+        info!("Scraping {} depuis: {}", self.rules.name, main_url);
+        let season_pages = match &self.seasons {
+            Some(rule) => self.list_pages(rule, main_url, |document, page| season_entries(rule, document, page)).await?,
+            None => vec![(self.rules.name.clone(), main_url.to_string())],
+        };
+        let seasons: Vec<Season> = stream::iter(season_pages)
+            .map(|(name, url)| async move {
+                match self.scrape_episodes(&url).await {
+                    Ok(episodes) => Some(Season { name, url, episodes }),
+                    Err(e) => {
+                        warn!("Saison {} ignorée ({}): {:#}", name, url, e);
+                        None
+                    }
+                }
+            })
+            .buffered(CONCURRENCY)
+            .filter_map(|season| async { season })
+            .collect()
+            .await;
+        info!("{}: {} saisons, {} épisodes", self.rules.name, seasons.len(), seasons.iter().map(|s| s.episodes.len()).sum::<usize>());
+        Ok(seasons)
+    }
+
+    /// Épisodes d'une saison, pages suivantes comprises
+    pub async fn scrape_episodes(&self, season_url: &str) -> Result<Vec<Episode>> {
+        let mut episodes = self.list_pages(&self.episodes, season_url, |document, page| episode_entries(&self.episodes, &self.links, document, page)).await?;
+        if let Some(rule) = &self.download_page {
+            let links = episodes.iter_mut().flat_map(|e| e.download_links.iter_mut()).filter(|l| l.actual_download_urls.is_empty());
+            stream::iter(links)
+                .for_each_concurrent(CONCURRENCY, |link| async move {
+                    match self.fetch_document(&link.url, |document, page| rule.extract(document.root_element(), page)).await {
+                        Ok(found) => link.actual_download_urls = found.into_iter().map(|l| l.url).collect(),
+                        Err(e) => warn!("Page de téléchargement {} illisible: {:#}", link.url, e),
+                    }
+                })
+                .await;
+        }
+        Ok(episodes)
+    }
+
+    /// Parcourt `url` et ses pages suivantes (au plus `max_pages`), en cumulant `parse`
+    async fn list_pages<T>(&self, rule: &CompiledList, url: &str, parse: impl Fn(&Html, &Url) -> Vec<T>) -> Result<Vec<T>> {
+        let max_pages = rule.pagination.as_ref().map_or(1, |(_, _, max)| *max);
+        let mut items = Vec::new();
+        let mut visited = HashSet::new();
+        let mut next = Some(self.resolve(url)?);
+        while let Some(page) = next.take() {
+            if visited.len() >= max_pages || !visited.insert(page.clone()) {
+                break;
+            }
+            let (found, following) = self.fetch_document(page.as_str(), |document, page| (parse(document, page), rule.next_page(document, page))).await?;
+            items.extend(found);
+            next = following;
+        }
+        Ok(items)
+    }
+
+    /// Télécharge `url` et applique `parse` au document (jamais gardé au‑delà d'un `await`)
+    async fn fetch_document<T>(&self, url: &str, parse: impl FnOnce(&Html, &Url) -> T) -> Result<T> {
+        let page = self.resolve(url)?;
+        info!("Récupération de la page {}: {}", self.rules.name, page);
+        let response = self.client.get(page.clone()).send().await.context("Erreur lors de la requête HTTP")?;
+        if !response.status().is_success() {
+            anyhow::bail!("Erreur HTTP: {}", response.status());
+        }
+        let html = response.text().await.context("Impossible de lire le contenu de la réponse")?;
+        Ok(parse(&Html::parse_document(&html), &page))
+    }
+
+    fn resolve(&self, url: &str) -> Result<Url> {
+        self.base_url.join(url).with_context(|| format!("URL invalide: {url}"))
+    }
+}
+
+impl SiteScraper for GenericScraper {
+    fn name(&self) -> &str {
+        &self.rules.name
+    }
+
+    /// `hosts`, ou l'hôte de `base_url`
+    fn domains(&self) -> Vec<String> {
+        self.hosts.clone()
+    }
+
+    fn list_seasons<'a>(&'a self, series_url: &'a str) -> LocalBoxFuture<'a, Result<Vec<Season>>> {
+        Box::pin(self.scrape_all(series_url))
+    }
+
+    fn list_episodes<'a>(&'a self, season_url: &'a str) -> LocalBoxFuture<'a, Result<Vec<Episode>>> {
+        Box::pin(self.scrape_episodes(season_url))
+    }
+
+    /// Premier lien réel déjà trouvé, sinon première URL de la page intermédiaire; sans
+    /// `download_page`, le lien lui‑même
+    fn resolve_download<'a>(&'a self, link: &'a DownloadLink) -> LocalBoxFuture<'a, Result<Option<String>>> {
+        Box::pin(async move {
+            if let Some(url) = link.actual_download_urls.first() {
+                return Ok(Some(url.clone()));
+            }
+            let Some(rule) = &self.download_page else {
+                return Ok(Some(link.url.clone()));
+            };
+            let found = self.fetch_document(&link.url, |document, page| rule.extract(document.root_element(), page)).await?;
+            Ok(found.into_iter().next().map(|l| l.url))
+        })
+    }
+}
+
+/// Saisons (nom, URL) d'une page
+fn season_entries(rule: &CompiledList, document: &Html, page: &Url) -> Vec<(String, String)> {
+    rule.entries(document)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, element)| {
+            let href = element.value().attr(&rule.attr).or_else(|| element.select(&rule.with_attr).next()?.value().attr(&rule.attr))?;
+            let url = page.join(href.trim()).ok()?;
+            Some((rule.entry_name(&element, || format!("Saison {}", i + 1)), url.to_string()))
+        })
+        .collect()
+}
+
+/// Épisodes d'une page: entrées ayant au moins un lien
+fn episode_entries(rule: &CompiledList, links: &CompiledLinks, document: &Html, page: &Url) -> Vec<Episode> {
+    rule.entries(document)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, element)| {
+            let download_links = links.extract(element, page);
+            (!download_links.is_empty()).then(|| Episode { name: rule.entry_name(&element, || format!("Épisode {}", i + 1)), download_links, is_new: false })
+        })
+        .collect()
+}
+
+/// `[scrapers] sites_dir`: dossier des descriptions de sites (`sites` par défaut)
+pub fn sites_dir_from_config(config: &AppConfig) -> PathBuf {
+    config
+        .scrapers
+        .as_ref()
+        .and_then(|s| s.sites_dir.clone())
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_SITES_DIR))
+}
+
+/// Compile chaque `.toml`, `.yaml` ou `.yml` de `dir`, dans l'ordre des noms; un fichier invalide
+/// est signalé et ignoré
+pub fn load_sites(dir: &Path) -> Vec<GenericScraper> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().and_then(|e| e.to_str()).is_some_and(|e| ["toml", "yaml", "yml"].contains(&e.to_ascii_lowercase().as_str())))
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| match SiteRules::load(&path).and_then(SiteRules::compile) {
+            Ok(site) => {
+                info!("Site décrit chargé: {} ({})", site.name(), path.display());
+                Some(site)
+            }
+            Err(e) => {
+                warn!("Description de site ignorée: {:#}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"
+        name = "Exemple"
+        base_url = "https://series.example.com/"
+
+        [seasons]
+        selectors = ["div.missing a", "a.season"]
+        name = "span"
+
+        [episodes]
+        selectors = ["li.episode"]
+        name = "b"
+        pagination = { next = "a.next", max_pages = 5 }
+
+        [links]
+        selector = "a"
+        quality = "small"
+        onclick = 'window\.location\.href="(?P<url>[^"]*fileid=(?P<file_id>[^&"]+)[^"]*)"'
+        filter = "download|dl"
+    "#;
+
+    #[test]
+    fn test_rules_compile_and_extract() {
+        let site = SiteRules::from_toml(RULES).unwrap().compile().unwrap();
+        assert_eq!(site.domains(), ["series.example.com"]);
+
+        let page = Url::parse("https://series.example.com/show/breaking-bad").unwrap();
+        let document = Html::parse_document(r#"<a class="season" href="season-1"><span>Season 1</span></a><a class="season" href="/s2">  </a>"#);
+        assert_eq!(season_entries(site.seasons.as_ref().unwrap(), &document, &page), [
+            ("Season 1".to_string(), "https://series.example.com/show/season-1".to_string()),
+            ("Saison 2".to_string(), "https://series.example.com/s2".to_string()),
+        ]);
+
+        let document = Html::parse_document(r#"
+            <li class="episode"><b>Episode 01 - Pilot</b>
+                <a onclick='window.location.href="/download.php?fileid=42&dkey=x"'><small>720p</small></a>
+                <a href="https://t.example.com/share">Partager</a>
+                <a href="/dl/ep1-480.mp4">480p</a>
+            </li>
+            <li class="episode"><b>Episode 02</b></li>
+            <a class="next" href="?page=2">Suivant</a>"#);
+        let episodes = episode_entries(&site.episodes, &site.links, &document, &page);
+        assert_eq!(episodes.len(), 1);
+        assert_eq!(episodes[0].name, "Episode 01 - Pilot");
+        let links = &episodes[0].download_links;
+        assert_eq!((links[0].url.as_str(), links[0].file_id.as_deref(), links[0].quality.as_str()), ("https://series.example.com/download.php?fileid=42&dkey=x", Some("42"), "720p"));
+        assert_eq!((links[1].url.as_str(), links[1].quality.as_str()), ("https://series.example.com/dl/ep1-480.mp4", "480p"));
+        assert_eq!(links.len(), 2);
+        assert_eq!(site.episodes.next_page(&document, &page).unwrap().as_str(), "https://series.example.com/show/breaking-bad?page=2");
+
+        // YAML, page unique et liens directs
+        let yaml = "name: Direct\nbase_url: https://files.example.com/\nepisodes:\n  selectors: [tr]\nlinks:\n  selector: a\n  direct: true\n";
+        let site = SiteRules::from_yaml(yaml).unwrap().compile().unwrap();
+        assert!(site.seasons.is_none());
+        let document = Html::parse_document("<table><tr><td><a href='e1.mkv'>e1</a></td></tr></table>");
+        let episodes = episode_entries(&site.episodes, &site.links, &document, &Url::parse("https://files.example.com/show/").unwrap());
+        assert_eq!(episodes[0].download_links[0].actual_download_urls, ["https://files.example.com/show/e1.mkv"]);
+
+        let broken = RULES.replace("\"li.episode\"", "\"li[\"");
+        assert!(SiteRules::from_toml(&broken).unwrap().compile().is_err());
+        let no_url = RULES.replace("(?P<url>", "(");
+        assert!(SiteRules::from_toml(&no_url).unwrap().compile().is_err());
+    }
+}
@@ -0,0 +1,251 @@
+//! Scraping incrémental d'une série déjà scrapée.
+//!
+//! `SiteScraper::list_seasons_since` reçoit la session enregistrée de la série: le scraper
+//! FZTV n'y relit que les saisons inconnues et la plus récente des connues, au plus grand
+//! numéro (la seule qui puisse encore gagner des épisodes), quel que soit l'ordre de la page;
+//! les autres sont reprises de la session sans requête (`reusable_seasons`). Puis
+//! `merge_with_previous` compare le résultat à la session: une saison aux mêmes épisodes (par
+//! nom) est reprise telle quelle (liens réels compris), une saison qui a changé garde les liens
+//! de ses anciens épisodes et marque les autres `is_new`, une saison inconnue est entièrement
+//! nouvelle. Les liens résolus au téléchargement d'une saison sont reportés dans la session
+//! (`record_resolved`): l'enrichissement et les téléchargements suivants ne les redemandent pas.
+use std::collections::HashMap;
+use crate::scrapers::episode::season_number;
+use crate::scrapers::fzscrape::fztv_scraper::{Episode, Season};
+
+/// Sort d'une saison par rapport à la session précédente
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeasonChange {
+    /// Mêmes épisodes: reprise de la session
+    Unchanged,
+    /// Épisodes ajoutés, retirés ou renommés (`before` dans la session)
+    Changed { before: usize },
+    /// Absente de la session
+    New,
+}
+
+/// Bilan d'une saison après fusion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeasonDiff {
+    pub name: String,
+    pub change: SeasonChange,
+    /// Épisodes marqués `is_new`
+    pub new_episodes: usize,
+}
+
+/// Bilan de la fusion, saison par saison
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScrapeDiff {
+    pub seasons: Vec<SeasonDiff>,
+}
+
+impl ScrapeDiff {
+    pub fn new_episodes(&self) -> usize {
+        self.seasons.iter().map(|s| s.new_episodes).sum()
+    }
+
+    /// Saisons nouvelles ou changées
+    pub fn changed_seasons(&self) -> usize {
+        self.seasons.iter().filter(|s| s.change != SeasonChange::Unchanged).count()
+    }
+}
+
+/// Saison de `previous` correspondant à la saison `name` d'URL `url`: par URL, puis par nom
+pub fn known_season<'a>(previous: &'a [Season], name: &str, url: &str) -> Option<&'a Season> {
+    previous.iter().find(|s| s.url == url).or_else(|| previous.iter().find(|s| s.name == name))
+}
+
+/// Pour chaque saison de la page (`(nom, URL)`), sa version de `previous` à reprendre sans
+/// requête, ou `None` pour la relire: saisons inconnues, plus récente des connues (plus grand
+/// numéro, d'après le nom) et connues sans numéro, dont on ne sait pas si elles sont closes
+pub fn reusable_seasons<'a>(previous: &'a [Season], listed: &[(String, String)]) -> Vec<Option<&'a Season>> {
+    let known: Vec<Option<&Season>> = listed.iter().map(|(name, url)| known_season(previous, name, url)).collect();
+    let latest = listed
+        .iter()
+        .zip(&known)
+        .filter(|(_, known)| known.is_some())
+        .filter_map(|((name, _), _)| season_number(name))
+        .max();
+    listed
+        .iter()
+        .zip(known)
+        .map(|((name, _), known)| match season_number(name) {
+            Some(number) if Some(number) != latest => known,
+            _ => None,
+        })
+        .collect()
+}
+
+/// Même liste d'épisodes, dans le même ordre
+fn same_episodes(a: &Season, b: &Season) -> bool {
+    a.episodes.iter().map(|e| &e.name).eq(b.episodes.iter().map(|e| &e.name))
+}
+
+/// Lien réel résolu au téléchargement d'un épisode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedLink {
+    pub episode: String,
+    /// `DownloadLink::url` du lien résolu
+    pub link: String,
+    pub url: String,
+}
+
+/// Reporte `resolved` dans les épisodes de la saison `season_url` de `seasons`; rend le nombre
+/// de liens complétés (ceux qui avaient déjà une URL réelle ne bougent pas)
+pub fn record_resolved(seasons: &mut [Season], season_url: &str, resolved: &[ResolvedLink]) -> usize {
+    let Some(season) = seasons.iter_mut().find(|s| s.url == season_url) else {
+        return 0;
+    };
+    let mut recorded = 0;
+    for resolved in resolved {
+        let link = season
+            .episodes
+            .iter_mut()
+            .filter(|e| e.name == resolved.episode)
+            .flat_map(|e| e.download_links.iter_mut())
+            .find(|l| l.url == resolved.link && l.actual_download_urls.is_empty());
+        if let Some(link) = link {
+            link.actual_download_urls.push(resolved.url.clone());
+            recorded += 1;
+        }
+    }
+    recorded
+}
+
+/// Fusionne `current` (scraping du jour) avec `previous` (session de la série); les saisons
+/// se retrouvent par URL, puis par nom, et les épisodes par nom. Les marques `is_new` d'un
+/// scraping antérieur sont effacées
+pub fn merge_with_previous(previous: &[Season], current: Vec<Season>) -> (Vec<Season>, ScrapeDiff) {
+    let mut diff = ScrapeDiff::default();
+
+    let seasons = current
+        .into_iter()
+        .map(|season| {
+            let (season, change) = match known_season(previous, &season.name, &season.url) {
+                Some(old) if same_episodes(old, &season) => {
+                    let mut kept = old.clone();
+                    kept.episodes.iter_mut().for_each(|e| e.is_new = false);
+                    (kept, SeasonChange::Unchanged)
+                }
+                Some(old) => {
+                    let old_episodes: HashMap<&str, &Episode> = old.episodes.iter().map(|e| (e.name.as_str(), e)).collect();
+                    let episodes = season
+                        .episodes
+                        .into_iter()
+                        .map(|episode| match old_episodes.get(episode.name.as_str()) {
+                            Some(old) => Episode { is_new: false, ..(*old).clone() },
+                            None => Episode { is_new: true, ..episode },
+                        })
+                        .collect();
+                    (Season { episodes, ..season }, SeasonChange::Changed { before: old.episodes.len() })
+                }
+                None => {
+                    let episodes = season.episodes.into_iter().map(|episode| Episode { is_new: true, ..episode }).collect();
+                    (Season { episodes, ..season }, SeasonChange::New)
+                }
+            };
+            let new_episodes = season.episodes.iter().filter(|e| e.is_new).count();
+            diff.seasons.push(SeasonDiff { name: season.name.clone(), change, new_episodes });
+            season
+        })
+        .collect();
+
+    tracing::info!(changed = diff.changed_seasons(), new_episodes = diff.new_episodes(), "Scraping comparé à la session précédente");
+    (seasons, diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scrapers::fzscrape::fztv_scraper::DownloadLink;
+
+    fn season(name: &str, episodes: &[(&str, Option<&str>)]) -> Season {
+        Season {
+            name: name.into(),
+            url: format!("https://fztvseries.mobi/{name}"),
+            episodes: episodes
+                .iter()
+                .map(|(episode, actual)| Episode {
+                    name: episode.to_string(),
+                    download_links: vec![DownloadLink {
+                        quality: "High MP4".into(),
+                        url: format!("episode.php?name={episode}"),
+                        file_id: None,
+                        dkey: None,
+                        actual_download_urls: actual.iter().map(|u| u.to_string()).collect(),
+                    }],
+                    is_new: false,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_merge_with_previous() {
+        let previous = vec![
+            season("s1", &[("Episode 1", Some("https://cdn/s1e1.mp4")), ("Episode 2", Some("https://cdn/s1e2.mp4"))]),
+            season("s2", &[("Episode 1", Some("https://cdn/s2e1.mp4"))]),
+        ];
+        let current = vec![
+            season("s1", &[("Episode 1", None), ("Episode 2", None)]),
+            season("s2", &[("Episode 1", None), ("Episode 2", None)]),
+            season("s3", &[("Episode 1", None)]),
+        ];
+        let (merged, diff) = merge_with_previous(&previous, current);
+
+        // saison inchangée: liens réels de la session
+        assert_eq!(merged[0].episodes[1].download_links[0].actual_download_urls, ["https://cdn/s1e2.mp4"]);
+        assert!(merged[0].episodes.iter().all(|e| !e.is_new));
+        // saison changée: ancien épisode gardé, le suivant marqué
+        assert_eq!(merged[1].episodes.iter().map(|e| e.is_new).collect::<Vec<_>>(), [false, true]);
+        assert_eq!(merged[1].episodes[0].download_links[0].actual_download_urls, ["https://cdn/s2e1.mp4"]);
+        assert!(merged[2].episodes[0].is_new);
+
+        assert_eq!(diff.seasons.iter().map(|s| s.change).collect::<Vec<_>>(), [
+            SeasonChange::Unchanged,
+            SeasonChange::Changed { before: 1 },
+            SeasonChange::New
+        ]);
+        assert_eq!((diff.changed_seasons(), diff.new_episodes()), (2, 2));
+
+        // même nombre d'épisodes, mais un épisode remplacé: saison changée
+        let renamed = vec![season("s1", &[("Episode 1", None), ("Episode 3", None)])];
+        let (merged_renamed, diff) = merge_with_previous(&previous, renamed);
+        assert_eq!(diff.seasons[0].change, SeasonChange::Changed { before: 2 });
+        assert_eq!(merged_renamed[0].episodes.iter().map(|e| e.is_new).collect::<Vec<_>>(), [false, true]);
+
+        // un nouveau passage sans changement efface les marques
+        let (again, diff) = merge_with_previous(&merged, merged.clone());
+        assert!(again.iter().flat_map(|s| &s.episodes).all(|e| !e.is_new));
+        assert_eq!(diff.new_episodes(), 0);
+    }
+
+    #[test]
+    fn test_record_resolved() {
+        let mut seasons = vec![season("s1", &[("Episode 1", Some("https://cdn/s1e1.mp4")), ("Episode 2", None)])];
+        let resolved = |episode: &str, url: &str| ResolvedLink {
+            episode: episode.into(),
+            link: format!("episode.php?name={episode}"),
+            url: url.into(),
+        };
+        let url = seasons[0].url.clone();
+
+        // déjà résolu: gardé; saison inconnue: rien
+        let count = record_resolved(&mut seasons, &url, &[resolved("Episode 1", "https://cdn/autre.mp4"), resolved("Episode 2", "https://cdn/s1e2.mp4")]);
+        assert_eq!(count, 1);
+        assert_eq!(seasons[0].episodes[0].download_links[0].actual_download_urls, ["https://cdn/s1e1.mp4"]);
+        assert_eq!(seasons[0].episodes[1].download_links[0].actual_download_urls, ["https://cdn/s1e2.mp4"]);
+        assert_eq!(record_resolved(&mut seasons, "https://fztvseries.mobi/s9", &[resolved("Episode 2", "x")]), 0);
+    }
+
+    #[test]
+    fn test_reusable_seasons_by_season_number() {
+        let previous = vec![season("Season 1", &[]), season("Season 2", &[]), season("Extras", &[])];
+        let listed = |names: &[&str]| names.iter().map(|n| (n.to_string(), format!("https://fztvseries.mobi/{n}"))).collect::<Vec<_>>();
+        let reused = |names: &[&str]| reusable_seasons(&previous, &listed(names)).iter().map(|s| s.map(|s| s.name.clone())).collect::<Vec<_>>();
+
+        // plus grand numéro relu quel que soit l'ordre; saison sans numéro et inconnue relues
+        assert_eq!(reused(&["Season 2", "Season 1", "Extras", "Season 3"]), [None, Some("Season 1".to_string()), None, None]);
+        assert_eq!(reused(&["Season 1", "Season 2"]), [Some("Season 1".to_string()), None]);
+    }
+}
@@ -0,0 +1,73 @@
+//! Utilitaires d'extraction d'URLs depuis une page HTML, partagés entre scrapers et downloader.
+use scraper::{Html, Selector};
+use url::Url;
+
+/// Sélecteurs (sélecteur, attribut) susceptibles de porter une URL téléchargeable
+const URL_SOURCES: &[(&str, &str)] = &[
+    ("input[type=\"text\"]", "value"),
+    ("a[href]", "href"),
+    ("video[src]", "src"),
+    ("audio[src]", "src"),
+    ("source[src]", "src"),
+];
+
+/// Indique si l'URL n'est pas un lien social/publicitaire connu des pages FZTV
+pub fn is_download_candidate(url: &str) -> bool {
+    !url.contains("t.me") && !url.contains("instagram") && !url.contains("fzmovies.live")
+}
+
+/// Collecte les URLs d'une page (inputs texte, liens, sources média), sans doublons et dans l'ordre.
+///
+/// Avec `base`, les liens relatifs sont résolus; sinon seules les URLs absolues `http(s)` sont gardées.
+pub fn extract_page_urls(document: &Html, base: Option<&Url>) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+
+    for (selector_str, attr) in URL_SOURCES {
+        let Ok(selector) = Selector::parse(selector_str) else { continue };
+        for element in document.select(&selector) {
+            let Some(raw) = element.value().attr(attr) else { continue };
+            let raw = raw.trim();
+            let resolved = if raw.starts_with("http") {
+                Some(raw.to_string())
+            } else {
+                base.and_then(|b| b.join(raw).ok())
+                    .filter(|u| u.scheme() == "http" || u.scheme() == "https")
+                    .map(|u| u.to_string())
+            };
+            if let Some(url) = resolved
+                && is_download_candidate(&url)
+                && !urls.contains(&url)
+            {
+                urls.push(url);
+            }
+        }
+    }
+
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_page_urls_filters_social_and_resolves() {
+        let html = Html::parse_document(r#"
+            <a href="https://t.me/channel">tg</a>
+            <a href="/files/a.mp4">a</a>
+            <input type="text" value="https://cdn.example.com/b.mp4">
+            <video src="https://cdn.example.com/b.mp4"></video>
+        "#);
+        let base = Url::parse("https://example.com/page").unwrap();
+
+        let urls = extract_page_urls(&html, Some(&base));
+        assert_eq!(urls, vec![
+            "https://cdn.example.com/b.mp4".to_string(),
+            "https://example.com/files/a.mp4".to_string(),
+        ]);
+
+        // Sans base, les liens relatifs sont ignorés
+        let urls = extract_page_urls(&html, None);
+        assert_eq!(urls, vec!["https://cdn.example.com/b.mp4".to_string()]);
+    }
+}
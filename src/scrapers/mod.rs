@@ -1,3 +1,19 @@
 pub mod fzscrape;
+pub mod links;
+pub mod browser_fallback;
+pub mod generic;
+pub mod site;
+pub mod registry;
+pub mod progress;
+pub mod cache;
+pub mod politeness;
+pub mod export;
+pub mod session;
+pub mod rotation;
+pub mod challenge;
+pub mod episode;
+pub mod incremental;
 
-pub use fzscrape::fztv_scraper::{FztvScraper, Season};
\ No newline at end of file
+pub use fzscrape::fztv_scraper::{FztvScraper, Season};
+pub use registry::ScraperRegistry;
+pub use site::SiteScraper;
\ No newline at end of file
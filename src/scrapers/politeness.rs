@@ -0,0 +1,141 @@
+//! Réglages de politesse du scraper: débit, concurrence, délai aléatoire.
+//!
+//! `ScraperOptions` (section `[scrapers]` de `scrapes.toml`) borne les requêtes simultanées
+//! (sémaphore de `fetch_page`, saisons traitées en parallèle), espace le départ de deux requêtes
+//! d'au moins `1 / requests_per_second`, et ajoute à chacune un délai aléatoire entre 0 et
+//! `jitter` pour ne pas frapper le site à intervalles réguliers. Les pages servies par le cache
+//! ne comptent pas.
+//!
+//! Une erreur transitoire (délai dépassé, connexion refusée, 5xx, 429) relance la page selon
+//! `retry` (délai exponentiel et gigue du téléchargeur, ou `Retry-After` du serveur).
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use crate::downloader::{AppConfig, RetryPolicy};
+
+/// Requêtes simultanées par défaut
+pub const DEFAULT_CONCURRENCY: usize = 10;
+/// Tentatives par page par défaut, première incluse
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// Débit et concurrence des requêtes d'un scraper
+#[derive(Debug, Clone)]
+pub struct ScraperOptions {
+    /// Requêtes lancées par seconde au plus (`None`: sans limite)
+    pub requests_per_second: Option<f64>,
+    /// Requêtes simultanées au plus
+    pub concurrency: usize,
+    /// Délai aléatoire maximal ajouté avant chaque requête
+    pub jitter: Duration,
+    /// Nouvelles tentatives d'une page après une erreur transitoire
+    pub retry: RetryPolicy,
+    /// Ouvrir chaque page scrapée dans le navigateur (débogage; désactivé par défaut)
+    pub debug_open_pages: bool,
+    /// Passer les vérifications anti‑robot (Cloudflare) dans le navigateur (activé par défaut)
+    pub solve_challenges: bool,
+}
+
+impl Default for ScraperOptions {
+    fn default() -> Self {
+        Self {
+            requests_per_second: None,
+            concurrency: DEFAULT_CONCURRENCY,
+            jitter: Duration::ZERO,
+            retry: RetryPolicy { max_attempts: DEFAULT_MAX_ATTEMPTS, ..Default::default() },
+            debug_open_pages: false,
+            solve_challenges: true,
+        }
+    }
+}
+
+/// Lit `[scrapers] requests_per_second`, `concurrency`, `jitter_ms`, `max_attempts`,
+/// `debug_open_pages` et `solve_challenges`
+pub fn scraper_options_from_config(config: &AppConfig) -> ScraperOptions {
+    let mut options = ScraperOptions::default();
+    if let Some(scrapers) = &config.scrapers {
+        options.requests_per_second = scrapers.requests_per_second.filter(|rps| *rps > 0.0);
+        if let Some(concurrency) = scrapers.concurrency {
+            options.concurrency = concurrency.max(1);
+        }
+        if let Some(ms) = scrapers.jitter_ms {
+            options.jitter = Duration::from_millis(ms);
+        }
+        if let Some(attempts) = scrapers.max_attempts {
+            options.retry.max_attempts = attempts.max(1);
+        }
+        options.debug_open_pages = scrapers.debug_open_pages.unwrap_or(false);
+        options.solve_challenges = scrapers.solve_challenges.unwrap_or(true);
+    }
+    options
+}
+
+/// Espace les départs de requêtes selon `ScraperOptions`
+#[derive(Debug)]
+pub(crate) struct Pacer {
+    interval: Option<Duration>,
+    jitter: Duration,
+    /// Premier instant libre pour la prochaine requête
+    next: Mutex<Option<Instant>>,
+}
+
+impl Pacer {
+    pub(crate) fn new(options: &ScraperOptions) -> Self {
+        Self {
+            interval: options.requests_per_second.map(|rps| Duration::from_secs_f64(1.0 / rps)),
+            jitter: options.jitter,
+            next: Mutex::new(None),
+        }
+    }
+
+    /// Attend le créneau de la prochaine requête (réservé avant d'attendre, dans l'ordre des
+    /// appels), plus le délai aléatoire
+    pub(crate) async fn wait(&self) {
+        let now = Instant::now();
+        let slot = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let slot = next.map_or(now, |n| n.max(now));
+            *next = self.interval.map(|interval| slot + interval);
+            slot
+        };
+        let delay = slot.saturating_duration_since(now) + self.jitter.mul_f64(random_unit());
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Tirage uniforme dans [0, 1] sans crate d'aléa, comme `RetryPolicy::delay`
+fn random_unit() -> f64 {
+    RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pacer_spaces_requests() {
+        let pacer = Pacer::new(&ScraperOptions { requests_per_second: Some(50.0), ..Default::default() });
+        let start = Instant::now();
+        for _ in 0..5 {
+            pacer.wait().await;
+        }
+        // premier départ immédiat, puis un toutes les 20 ms
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(80) && elapsed < Duration::from_secs(1), "{elapsed:?}");
+
+        let jittered = Pacer::new(&ScraperOptions { jitter: Duration::from_millis(30), ..Default::default() });
+        let start = Instant::now();
+        jittered.wait().await;
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        let unlimited = Pacer::new(&ScraperOptions::default());
+        let start = Instant::now();
+        for _ in 0..5 {
+            unlimited.wait().await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,131 @@
+//! Progression d'un scraping, émise par `FztvScraper::with_progress`.
+//!
+//! Le scraper envoie un `ScrapeEvent` à chaque étape (page demandée, saisons découvertes,
+//! saison terminée, lien résolu, page en échec); `ScrapeProgress::apply` les cumule pour
+//! l'onglet Scraper (barre de progression), `ScrapeEvent::describe` en fait une ligne de journal.
+//!
+//! Les pages abandonnées après leurs nouvelles tentatives sont aussi rendues avec les
+//! résultats, dans un `ScrapeReport`.
+use serde::{Deserialize, Serialize};
+
+/// Étape d'un scraping
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScrapeEvent {
+    /// Page en cours de récupération
+    Fetching { url: String },
+    /// Saisons trouvées sur la page de la série
+    SeasonsFound { count: usize },
+    /// Saison terminée (`episodes` vaut 0 si sa page a échoué)
+    SeasonScraped { name: String, episodes: usize },
+    /// Liens réels à résoudre par l'enrichissement
+    LinksToResolve { count: usize },
+    /// Lien d'un épisode résolu (`found`: une URL réelle a été trouvée)
+    LinkResolved { episode: String, found: bool },
+    /// Page abandonnée après ses tentatives
+    Failed(FetchFailure),
+}
+
+/// Page (ou lien) abandonnée
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchFailure {
+    pub url: String,
+    pub error: String,
+    /// Tentatives faites, première incluse
+    pub attempts: u32,
+}
+
+/// Échecs d'un scraping, rendus avec ses résultats
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScrapeReport {
+    pub failures: Vec<FetchFailure>,
+}
+
+impl ScrapeReport {
+    pub fn is_empty(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl ScrapeEvent {
+    /// Ligne de journal de l'événement
+    pub fn describe(&self) -> String {
+        match self {
+            ScrapeEvent::Fetching { url } => format!("Page: {url}"),
+            ScrapeEvent::SeasonsFound { count } => format!("{count} saison(s) trouvée(s)"),
+            ScrapeEvent::SeasonScraped { name, episodes } => format!("{name}: {episodes} épisode(s)"),
+            ScrapeEvent::LinksToResolve { count } => format!("{count} lien(s) à résoudre"),
+            ScrapeEvent::LinkResolved { episode, found: true } => format!("{episode}: lien trouvé"),
+            ScrapeEvent::LinkResolved { episode, found: false } => format!("{episode}: aucun lien"),
+            ScrapeEvent::Failed(failure) => format!("Échec après {} tentative(s): {} ({})", failure.attempts, failure.url, failure.error),
+        }
+    }
+}
+
+/// Cumul des événements d'un scraping
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScrapeProgress {
+    pub seasons_total: usize,
+    pub seasons_done: usize,
+    pub episodes: usize,
+    pub links_total: usize,
+    pub links_done: usize,
+    pub links_found: usize,
+    /// Pages et liens abandonnés
+    pub failures: Vec<FetchFailure>,
+    /// Dernière page demandée
+    pub current_url: Option<String>,
+}
+
+impl ScrapeProgress {
+    pub fn apply(&mut self, event: &ScrapeEvent) {
+        match event {
+            ScrapeEvent::Fetching { url } => self.current_url = Some(url.clone()),
+            ScrapeEvent::SeasonsFound { count } => self.seasons_total += count,
+            ScrapeEvent::SeasonScraped { episodes, .. } => {
+                self.seasons_done += 1;
+                self.episodes += episodes;
+            }
+            ScrapeEvent::LinksToResolve { count } => self.links_total += count,
+            ScrapeEvent::LinkResolved { found, .. } => {
+                self.links_done += 1;
+                self.links_found += usize::from(*found);
+            }
+            ScrapeEvent::Failed(failure) => self.failures.push(failure.clone()),
+        }
+    }
+
+    /// Avancement entre 0 et 1 (saisons puis liens); `None` tant que rien n'est dénombré
+    pub fn fraction(&self) -> Option<f32> {
+        let total = self.seasons_total + self.links_total;
+        (total > 0).then(|| ((self.seasons_done + self.links_done) as f32 / total as f32).min(1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_accumulates_events() {
+        let mut progress = ScrapeProgress::default();
+        assert_eq!(progress.fraction(), None);
+        let events = [
+            ScrapeEvent::Fetching { url: "https://fztvseries.mobi/v/serie.htm".into() },
+            ScrapeEvent::SeasonsFound { count: 2 },
+            ScrapeEvent::SeasonScraped { name: "Season 1".into(), episodes: 8 },
+            ScrapeEvent::SeasonScraped { name: "Season 2".into(), episodes: 0 },
+            ScrapeEvent::LinksToResolve { count: 2 },
+            ScrapeEvent::LinkResolved { episode: "Episode 1".into(), found: true },
+            ScrapeEvent::Failed(FetchFailure { url: "https://fztvseries.mobi/s2.htm".into(), error: "Erreur HTTP: 503".into(), attempts: 3 }),
+        ];
+        for event in &events {
+            progress.apply(event);
+        }
+        assert_eq!((progress.seasons_done, progress.episodes, progress.links_done, progress.links_found), (2, 8, 1, 1));
+        assert_eq!(progress.current_url.as_deref(), Some("https://fztvseries.mobi/v/serie.htm"));
+        assert_eq!(progress.fraction(), Some(0.75));
+        assert_eq!(events[3].describe(), "Season 2: 0 épisode(s)");
+        assert_eq!(progress.failures.len(), 1);
+        assert_eq!(events[6].describe(), "Échec après 3 tentative(s): https://fztvseries.mobi/s2.htm (Erreur HTTP: 503)");
+    }
+}
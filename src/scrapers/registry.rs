@@ -0,0 +1,123 @@
+//! Registre des scrapers, indexé par domaine.
+//!
+//! `for_url` retrouve le scraper d'une URL par son hôte, puis par ses domaines parents
+//! (`www.fztvseries.mobi` -> `fztvseries.mobi`). Les sites décrits par des règles
+//! (`generic`) sont enregistrés après les scrapers intégrés (FZTV, FZMovies) et l'emportent sur
+//! un même domaine.
+use std::collections::HashMap;
+use std::sync::Arc;
+use url::Url;
+use crate::downloader::AppConfig;
+use crate::scrapers::fzscrape::fzmovies_scraper::{self, FzmoviesScraper};
+use crate::scrapers::fzscrape::fztv_scraper::FztvScraper;
+use crate::scrapers::{cache, generic, politeness, rotation};
+use crate::scrapers::site::SiteScraper;
+
+/// Scrapers disponibles et domaine -> scraper
+#[derive(Default, Clone)]
+pub struct ScraperRegistry {
+    scrapers: Vec<Arc<dyn SiteScraper>>,
+    by_domain: HashMap<String, usize>,
+}
+
+/// Domaine normalisé: minuscules, sans `www.` ni point final
+fn normalize(domain: &str) -> String {
+    let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+    domain.strip_prefix("www.").map(str::to_string).unwrap_or(domain)
+}
+
+impl ScraperRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scraper FZTV `fztv`, FZMovies (`[scrapers] fzmovies_url`), puis les sites décrits de
+    /// `[scrapers] sites_dir`
+    pub fn from_config(config: &AppConfig, fztv: FztvScraper) -> Self {
+        let mut registry = Self::new()
+            .with_scraper(Arc::new(fztv))
+            .with_scraper(Arc::new(
+                FzmoviesScraper::new(fzmovies_scraper::fzmovies_url_from_config(config))
+                    .with_options(politeness::scraper_options_from_config(config))
+                    .with_cache(cache::page_cache_from_config(config))
+                    .with_rotation(rotation::rotation_from_config(config)),
+            ));
+        for site in generic::load_sites(&generic::sites_dir_from_config(config)) {
+            registry.register(Arc::new(site));
+        }
+        registry
+    }
+
+    /// Ajoute `scraper` pour chacun de ses domaines; un domaine déjà pris change de scraper
+    pub fn register(&mut self, scraper: Arc<dyn SiteScraper>) {
+        let index = self.scrapers.len();
+        for domain in scraper.domains() {
+            if let Some(previous) = self.by_domain.insert(normalize(&domain), index) {
+                tracing::info!(domain, previous = self.scrapers[previous].name(), now = scraper.name(), "Domaine repris par un autre scraper");
+            }
+        }
+        self.scrapers.push(scraper);
+    }
+
+    pub fn with_scraper(mut self, scraper: Arc<dyn SiteScraper>) -> Self {
+        self.register(scraper);
+        self
+    }
+
+    /// Scraper de l'hôte de `url` ou de son plus proche domaine parent enregistré
+    pub fn for_url(&self, url: &str) -> Option<Arc<dyn SiteScraper>> {
+        let host = normalize(Url::parse(url).ok()?.host_str()?);
+        let mut domain = host.as_str();
+        loop {
+            if let Some(&index) = self.by_domain.get(domain) {
+                return Some(self.scrapers[index].clone());
+            }
+            domain = domain.split_once('.')?.1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use futures::future::LocalBoxFuture;
+    use crate::scrapers::fzscrape::fztv_scraper::{DownloadLink, Episode, Season};
+
+    struct Fake(&'static str, &'static [&'static str]);
+
+    impl SiteScraper for Fake {
+        fn name(&self) -> &str {
+            self.0
+        }
+        fn domains(&self) -> Vec<String> {
+            self.1.iter().map(|d| d.to_string()).collect()
+        }
+        fn list_seasons<'a>(&'a self, _url: &'a str) -> LocalBoxFuture<'a, Result<Vec<Season>>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+        fn list_episodes<'a>(&'a self, _url: &'a str) -> LocalBoxFuture<'a, Result<Vec<Episode>>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+        fn resolve_download<'a>(&'a self, link: &'a DownloadLink) -> LocalBoxFuture<'a, Result<Option<String>>> {
+            Box::pin(async move { Ok(Some(link.url.clone())) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_dispatch_by_domain() {
+        let registry = ScraperRegistry::new()
+            .with_scraper(Arc::new(Fake("FZTV", &["www.fztvseries.mobi"])))
+            .with_scraper(Arc::new(Fake("Autre", &["example.com", "FZTVseries.mobi."])))
+            .with_scraper(Arc::new(Fake("Sous-domaine", &["cdn.example.com"])));
+        let name = |url: &str| registry.for_url(url).map(|s| s.name().to_string());
+        assert_eq!(name("https://fztvseries.mobi/series/1").as_deref(), Some("Autre"));
+        assert_eq!(name("https://media.cdn.example.com/a").as_deref(), Some("Sous-domaine"));
+        assert_eq!(name("https://www.example.com/").as_deref(), Some("Autre"));
+        assert_eq!(name("https://example.org/"), None);
+        assert_eq!(name("pas une url"), None);
+
+        let fake = Fake("Sans recherche", &[]);
+        assert!(fake.search("breaking bad").await.is_err());
+    }
+}
@@ -0,0 +1,254 @@
+//! Rotation des proxys et des user‑agents du scraper.
+//!
+//! `RotationPool` garde un client HTTP par proxy (un seul, direct, sans proxy) et les passe à
+//! tour de rôle à chaque requête de `FztvScraper::fetch_page`, avec le user‑agent suivant de la
+//! liste. Chaque proxy a son suivi de santé: après `MAX_CONSECUTIVE_FAILURES` échecs de suite
+//! (erreur réseau, 403, 407, 429: les signes d'un blocage), il est mis à l'écart le temps du
+//! `cooldown`, puis réessayé. Si tous le sont, celui qui revient le plus tôt sert quand même.
+//!
+//! Les clients partagent la session de cookies du scraper (celle du téléchargeur par défaut):
+//! `FztvScraper::with_rotation` et `with_cookie_jar` les lient à son stockage, pour que les
+//! cookies d'une vérification anti‑robot passée accompagnent aussi les requêtes des proxys.
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use anyhow::{Context, Result};
+use reqwest::{Client, StatusCode};
+use tokio::time::Instant;
+use crate::downloader::{AppConfig, CookieJar};
+
+/// Échecs de suite avant de mettre un proxy à l'écart
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+/// Mise à l'écart par défaut d'un proxy défaillant (5 min)
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(300);
+/// User‑agent des clients quand la liste est vide
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36";
+
+/// Client HTTP du scraper: délai de 30 s, cookies de `cookies`, `proxy` éventuel
+pub fn build_client(proxy: Option<&str>, cookies: &CookieJar) -> Result<Client> {
+    let mut builder = Client::builder()
+        .user_agent(DEFAULT_USER_AGENT)
+        .timeout(Duration::from_secs(30))
+        .cookie_provider(Arc::new(cookies.clone()));
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(reqwest::Proxy::all(proxy).with_context(|| format!("Proxy invalide: {proxy}"))?);
+    }
+    builder.build().context("Impossible de créer le client HTTP")
+}
+
+/// Chemin (proxy ou accès direct) choisi pour une requête
+#[derive(Debug, Clone)]
+pub struct Route {
+    /// Indice du proxy, à rendre avec `RotationPool::report`
+    pub index: usize,
+    pub client: Client,
+    /// User‑agent à poser sur la requête (`None`: celui du client)
+    pub user_agent: Option<String>,
+}
+
+/// Santé d'un proxy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyStats {
+    /// `None` pour l'accès direct
+    pub proxy: Option<String>,
+    pub requests: u32,
+    pub failures: u32,
+    /// Mis à l'écart en ce moment
+    pub benched: bool,
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    requests: u32,
+    failures: u32,
+    consecutive_failures: u32,
+    benched_until: Option<Instant>,
+}
+
+#[derive(Debug)]
+struct Proxy {
+    url: Option<String>,
+    client: Client,
+    /// Partagée avec les copies liées à une autre session de cookies
+    health: Arc<Mutex<Health>>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    proxies: Vec<Proxy>,
+    user_agents: Vec<String>,
+    cooldown: Duration,
+    next_proxy: AtomicUsize,
+    next_agent: AtomicUsize,
+}
+
+/// Proxys et user‑agents utilisés à tour de rôle (clonage bon marché, santé partagée)
+#[derive(Debug, Clone)]
+pub struct RotationPool(Arc<Inner>);
+
+impl RotationPool {
+    /// Un client par proxy de `proxies` (accès direct si la liste est vide), avec les cookies de
+    /// `cookies`
+    pub fn new(proxies: &[String], user_agents: Vec<String>, cooldown: Duration, cookies: &CookieJar) -> Result<Self> {
+        let urls: Vec<Option<&str>> = match proxies.is_empty() {
+            true => vec![None],
+            false => proxies.iter().map(|p| Some(p.as_str())).collect(),
+        };
+        let proxies = urls
+            .into_iter()
+            .map(|url| Ok(Proxy { url: url.map(str::to_string), client: build_client(url, cookies)?, health: Arc::default() }))
+            .collect::<Result<Vec<_>>>()?;
+        let user_agents = user_agents.into_iter().filter(|ua| !ua.trim().is_empty()).collect();
+        Ok(Self(Arc::new(Inner { proxies, user_agents, cooldown, next_proxy: AtomicUsize::new(0), next_agent: AtomicUsize::new(0) })))
+    }
+
+    /// Même rotation, santé des proxys partagée, avec des clients liés à `cookies` (un client
+    /// impossible à recréer garde son ancienne session)
+    pub fn with_cookie_jar(&self, cookies: &CookieJar) -> Self {
+        let inner = &self.0;
+        let proxies = inner
+            .proxies
+            .iter()
+            .map(|proxy| Proxy {
+                url: proxy.url.clone(),
+                client: build_client(proxy.url.as_deref(), cookies).unwrap_or_else(|e| {
+                    tracing::warn!(proxy = proxy.url.as_deref().unwrap_or("direct"), error = %format!("{e:#}"), "Client du proxy non recréé");
+                    proxy.client.clone()
+                }),
+                health: proxy.health.clone(),
+            })
+            .collect();
+        Self(Arc::new(Inner {
+            proxies,
+            user_agents: inner.user_agents.clone(),
+            cooldown: inner.cooldown,
+            next_proxy: AtomicUsize::new(0),
+            next_agent: AtomicUsize::new(0),
+        }))
+    }
+
+    /// Proxy suivant parmi ceux qui ne sont pas à l'écart, avec le user‑agent suivant
+    pub fn pick(&self) -> Route {
+        let inner = &self.0;
+        let count = inner.proxies.len();
+        let start = inner.next_proxy.fetch_add(1, Ordering::Relaxed) % count;
+        let now = Instant::now();
+        let benched_until = |index: usize| inner.proxies[index].health.lock().unwrap_or_else(|e| e.into_inner()).benched_until.filter(|until| *until > now);
+        let order = || (0..count).map(|offset| (start + offset) % count);
+        let index = order()
+            .find(|&index| benched_until(index).is_none())
+            .or_else(|| order().min_by_key(|&index| benched_until(index)))
+            .unwrap_or(start);
+        let user_agent = match inner.user_agents.is_empty() {
+            true => None,
+            false => Some(inner.user_agents[inner.next_agent.fetch_add(1, Ordering::Relaxed) % inner.user_agents.len()].clone()),
+        };
+        Route { index, client: inner.proxies[index].client.clone(), user_agent }
+    }
+
+    /// Issue d'une requête passée par le proxy `index`
+    pub fn report(&self, index: usize, success: bool) {
+        let Some(proxy) = self.0.proxies.get(index) else {
+            return;
+        };
+        let mut health = proxy.health.lock().unwrap_or_else(|e| e.into_inner());
+        health.requests += 1;
+        if success {
+            health.consecutive_failures = 0;
+            return;
+        }
+        health.failures += 1;
+        health.consecutive_failures += 1;
+        if health.consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+            health.consecutive_failures = 0;
+            health.benched_until = Some(Instant::now() + self.0.cooldown);
+            tracing::warn!(proxy = proxy.url.as_deref().unwrap_or("direct"), cooldown = ?self.0.cooldown, "Proxy mis à l'écart");
+        }
+    }
+
+    /// Santé de chaque proxy, dans l'ordre de la configuration
+    pub fn stats(&self) -> Vec<ProxyStats> {
+        let now = Instant::now();
+        self.0
+            .proxies
+            .iter()
+            .map(|proxy| {
+                let health = proxy.health.lock().unwrap_or_else(|e| e.into_inner());
+                ProxyStats {
+                    proxy: proxy.url.clone(),
+                    requests: health.requests,
+                    failures: health.failures,
+                    benched: health.benched_until.is_some_and(|until| until > now),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Le statut `status` trahit‑il un blocage du proxy (plutôt qu'une erreur du site)?
+pub fn is_blocked_status(status: StatusCode) -> bool {
+    matches!(status, StatusCode::FORBIDDEN | StatusCode::PROXY_AUTHENTICATION_REQUIRED | StatusCode::TOO_MANY_REQUESTS)
+}
+
+/// Rotation de `[scrapers] proxies`, `user_agents` et `proxy_cooldown_secs` (cookies du
+/// téléchargeur); `None` sans proxy ni user‑agent, ou si un proxy est invalide (le scraper garde
+/// alors son client direct)
+pub fn rotation_from_config(config: &AppConfig) -> Option<RotationPool> {
+    let scrapers = config.scrapers.as_ref()?;
+    let proxies = scrapers.proxies.clone().unwrap_or_default();
+    let user_agents = scrapers.user_agents.clone().unwrap_or_default();
+    if proxies.is_empty() && user_agents.is_empty() {
+        return None;
+    }
+    let cooldown = scrapers.proxy_cooldown_secs.map(Duration::from_secs).unwrap_or(DEFAULT_COOLDOWN);
+    RotationPool::new(&proxies, user_agents, cooldown, &CookieJar::global())
+        .inspect_err(|e| tracing::warn!(error = %format!("{e:#}"), "Rotation des proxys ignorée"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rotation_benches_failing_proxies() {
+        let proxies = vec!["http://127.0.0.1:9001".to_string(), "http://127.0.0.1:9002".to_string()];
+        let jar = CookieJar::in_memory();
+        let pool = RotationPool::new(&proxies, vec!["ua-1".into(), "ua-2".into(), "ua-3".into()], Duration::from_millis(100), &jar).unwrap();
+
+        let routes: Vec<_> = (0..4).map(|_| pool.pick()).collect();
+        assert_eq!(routes.iter().map(|r| r.index).collect::<Vec<_>>(), [0, 1, 0, 1]);
+        assert_eq!(routes.iter().map(|r| r.user_agent.as_deref().unwrap()).collect::<Vec<_>>(), ["ua-1", "ua-2", "ua-3", "ua-1"]);
+
+        // un succès remet le compte à zéro; trois échecs de suite écartent le proxy
+        for success in [false, false, true, false, false, false] {
+            pool.report(0, success);
+        }
+        let stats = pool.stats();
+        assert_eq!((stats[0].requests, stats[0].failures, stats[0].benched), (6, 5, true));
+        assert!(!stats[1].benched);
+        assert!((0..4).all(|_| pool.pick().index == 1));
+
+        // tous à l'écart: celui qui revient le plus tôt sert quand même
+        for _ in 0..3 {
+            pool.report(1, false);
+        }
+        assert!((0..2).all(|_| pool.pick().index == 0));
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(pool.stats().iter().all(|s| !s.benched));
+        let mut picked: Vec<_> = (0..2).map(|_| pool.pick().index).collect();
+        picked.sort();
+        assert_eq!(picked, [0, 1]);
+
+        // copie liée à d'autres cookies: même santé
+        let rebound = pool.with_cookie_jar(&CookieJar::in_memory());
+        pool.report(0, false);
+        assert_eq!(rebound.stats()[0].failures, pool.stats()[0].failures);
+
+        assert!(RotationPool::new(&["pas un proxy".to_string()], Vec::new(), DEFAULT_COOLDOWN, &jar).is_err());
+        let direct = RotationPool::new(&[], Vec::new(), DEFAULT_COOLDOWN, &jar).unwrap();
+        assert_eq!((direct.pick().index, direct.pick().user_agent), (0, None));
+        assert_eq!(direct.stats()[0].proxy, None);
+    }
+}
@@ -0,0 +1,163 @@
+//! Sessions de scraping enregistrées sur disque.
+//!
+//! Une session garde l'arbre des saisons (`Season` -> `Episode` -> `DownloadLink`, liens réels
+//! compris) avec l'URL de la série et ses dates, pour qu'un plantage ou un redémarrage ne fasse
+//! pas perdre un scraping ou un enrichissement. Une session par série:
+//! `<dossier>/<sha256 de l'URL, 16 caractères>.json`; la réenregistrer garde sa date de création.
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::downloader::AppConfig;
+use crate::scrapers::fzscrape::fztv_scraper::Season;
+
+/// Dossier des sessions par défaut
+pub const DEFAULT_SESSIONS_DIR: &str = ".scrapes-sessions";
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Résultats d'un scraping et leur provenance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrapeSession {
+    /// Identifiant dérivé de `source_url`
+    pub id: String,
+    /// Page de la série scrapée
+    pub source_url: String,
+    /// Premier enregistrement (secondes Unix)
+    pub created_at: u64,
+    /// Dernier enregistrement (secondes Unix)
+    pub updated_at: u64,
+    pub seasons: Vec<Season>,
+}
+
+impl ScrapeSession {
+    pub fn new(source_url: &str, seasons: Vec<Season>) -> Self {
+        let now = unix_now();
+        Self { id: session_id(source_url), source_url: source_url.to_string(), created_at: now, updated_at: now, seasons }
+    }
+
+    pub fn episode_count(&self) -> usize {
+        self.seasons.iter().map(|s| s.episodes.len()).sum()
+    }
+}
+
+/// Identifiant de la session de `source_url`
+pub fn session_id(source_url: &str) -> String {
+    Sha256::digest(source_url.trim().as_bytes()).iter().take(8).map(|b| format!("{b:02x}")).collect()
+}
+
+/// Sessions enregistrées, une par fichier JSON
+#[derive(Debug, Clone)]
+pub struct SessionStore {
+    dir: PathBuf,
+}
+
+impl SessionStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path_for(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    /// Enregistre `session` (fichier temporaire puis renommage); une session existante de la
+    /// même série garde sa date de création
+    pub fn save(&self, mut session: ScrapeSession) -> io::Result<ScrapeSession> {
+        fs::create_dir_all(&self.dir)?;
+        if let Some(previous) = self.load(&session.id) {
+            session.created_at = previous.created_at.min(session.created_at);
+        }
+        session.updated_at = unix_now();
+        let path = self.path_for(&session.id);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&session).map_err(io::Error::other)?)?;
+        fs::rename(&tmp, &path)?;
+        tracing::info!(id = %session.id, url = %session.source_url, seasons = session.seasons.len(), "Session de scraping enregistrée");
+        Ok(session)
+    }
+
+    /// Session `id`; `None` si absente ou illisible
+    pub fn load(&self, id: &str) -> Option<ScrapeSession> {
+        let content = fs::read_to_string(self.path_for(id)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Sessions lisibles du dossier, la plus récemment enregistrée d'abord
+    pub fn list(&self) -> Vec<ScrapeSession> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut sessions: Vec<ScrapeSession> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| serde_json::from_str(&fs::read_to_string(entry.path()).ok()?).ok())
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+        sessions
+    }
+
+    pub fn delete(&self, id: &str) -> io::Result<()> {
+        fs::remove_file(self.path_for(id))
+    }
+}
+
+/// Sessions de `[scrapers] sessions_dir` (`.scrapes-sessions` par défaut)
+pub fn session_store_from_config(config: &AppConfig) -> SessionStore {
+    let dir = config.scrapers.as_ref().and_then(|s| s.sessions_dir.clone()).unwrap_or_else(|| PathBuf::from(DEFAULT_SESSIONS_DIR));
+    SessionStore::new(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scrapers::fzscrape::fztv_scraper::{DownloadLink, Episode};
+
+    fn season(name: &str, actual: &[&str]) -> Season {
+        Season {
+            name: name.into(),
+            url: format!("https://fztvseries.mobi/{name}"),
+            episodes: vec![Episode {
+                name: "Episode 1".into(),
+                download_links: vec![DownloadLink {
+                    quality: "High MP4".into(),
+                    url: "episode.php?fileid=7".into(),
+                    file_id: Some("7".into()),
+                    dkey: None,
+                    actual_download_urls: actual.iter().map(|u| u.to_string()).collect(),
+                }],
+                is_new: false,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_session_store_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SessionStore::new(dir.path().join("sessions"));
+        assert!(store.list().is_empty());
+
+        let url = "https://fztvseries.mobi/v/serie.htm";
+        let first = store.save(ScrapeSession { created_at: 100, ..ScrapeSession::new(url, vec![season("s1", &[])]) }).unwrap();
+        assert_eq!(first.id, session_id(url));
+
+        // même série: remplacée, date de création conservée, liens réels gardés
+        let second = store.save(ScrapeSession::new(url, vec![season("s1", &["https://cdn/a.mp4"])])).unwrap();
+        assert_eq!((second.id.as_str(), second.created_at), (first.id.as_str(), 100));
+        let loaded = store.load(&second.id).unwrap();
+        assert_eq!(loaded.source_url, url);
+        assert_eq!(loaded.seasons[0].episodes[0].download_links[0].actual_download_urls, ["https://cdn/a.mp4"]);
+
+        let other = store.save(ScrapeSession::new("https://fztvseries.mobi/v/autre.htm", vec![season("s1", &[]), season("s2", &[])])).unwrap();
+        assert_eq!(other.episode_count(), 2);
+        assert_eq!(store.list().len(), 2);
+
+        store.delete(&first.id).unwrap();
+        assert_eq!(store.list().iter().map(|s| s.id.clone()).collect::<Vec<_>>(), [other.id]);
+        assert!(store.load(&first.id).is_none());
+    }
+}
@@ -0,0 +1,57 @@
+//! Interface commune des scrapers de sites.
+//!
+//! L'interface graphique (et une future ligne de commande) ne connaît que `SiteScraper`: le
+//! registre (`registry`) choisit l'implémentation d'après le domaine de l'URL. Les méthodes
+//! rendent des `LocalBoxFuture` pour rester utilisables derrière `dyn`: les documents HTML de
+//! `scraper` ne sont pas `Send`, et le scraper FZTV en garde entre deux `await`.
+use anyhow::Result;
+use futures::future::LocalBoxFuture;
+use serde::{Deserialize, Serialize};
+use crate::scrapers::fzscrape::fztv_scraper::{DownloadLink, Episode, Season};
+
+/// Série trouvée par une recherche
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub title: String,
+    /// Année de première diffusion, si la page l'indique
+    pub year: Option<u32>,
+    /// Page de la série, à passer à `list_seasons`
+    pub url: String,
+}
+
+/// Scraper d'un site de séries
+pub trait SiteScraper: Send + Sync {
+    /// Nom affiché du site
+    fn name(&self) -> &str;
+
+    /// Domaines servis (`fztvseries.mobi`); les sous‑domaines suivent
+    fn domains(&self) -> Vec<String>;
+
+    /// Séries dont le titre correspond à `query`; par défaut, le site n'a pas de recherche
+    fn search<'a>(&'a self, query: &'a str) -> LocalBoxFuture<'a, Result<Vec<SearchResult>>> {
+        let _ = query;
+        Box::pin(async move { Err(anyhow::anyhow!("Recherche non prise en charge par {}", self.name())) })
+    }
+
+    /// Saisons de la série `series_url`; leurs épisodes peuvent déjà être remplis
+    fn list_seasons<'a>(&'a self, series_url: &'a str) -> LocalBoxFuture<'a, Result<Vec<Season>>>;
+
+    /// Saisons de la série, `previous` étant sa session enregistrée: un site peut y reprendre
+    /// les saisons qui ne bougent plus au lieu de les relire; par défaut, `list_seasons`
+    fn list_seasons_since<'a>(&'a self, series_url: &'a str, previous: &'a [Season]) -> LocalBoxFuture<'a, Result<Vec<Season>>> {
+        let _ = previous;
+        self.list_seasons(series_url)
+    }
+
+    /// Épisodes de la saison `season_url`
+    fn list_episodes<'a>(&'a self, season_url: &'a str) -> LocalBoxFuture<'a, Result<Vec<Episode>>>;
+
+    /// Résout d'avance les liens réels des épisodes de `seasons` (`actual_download_urls`); par
+    /// défaut, rien: les liens sont résolus au téléchargement par `resolve_download`
+    fn enrich<'a>(&'a self, seasons: Vec<Season>) -> LocalBoxFuture<'a, Result<Vec<Season>>> {
+        Box::pin(async move { Ok(seasons) })
+    }
+
+    /// URL du média derrière `link` (`None` si la page n'en donne pas)
+    fn resolve_download<'a>(&'a self, link: &'a DownloadLink) -> LocalBoxFuture<'a, Result<Option<String>>>;
+}
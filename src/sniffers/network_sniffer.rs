@@ -10,6 +10,7 @@ use chromiumoxide_cdp::cdp::browser_protocol::network::{
 };
 use chromiumoxide_cdp::cdp::browser_protocol::page::NavigateParams;
 use futures::StreamExt;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::time::sleep;
@@ -30,6 +31,10 @@ pub struct NetworkEntry {
 pub struct NetworkSniffer {
     filter: Option<String>,
     captured_requests: Arc<Mutex<Vec<NetworkEntry>>>,
+    /// Fenêtre Chromium visible (onglet Sniffer) ou navigateur invisible (replis automatiques)
+    headless: bool,
+    /// Fichier JSON réécrit après chaque capture (`None` = pas d'export)
+    export_path: Option<PathBuf>,
 }
 
 impl NetworkSniffer {
@@ -38,9 +43,19 @@ impl NetworkSniffer {
         Self {
             filter,
             captured_requests: Arc::new(Mutex::new(Vec::new())),
+            headless: false,
+            export_path: Some(PathBuf::from("network_output.json")),
         }
     }
 
+    /// Navigateur invisible et sans export `network_output.json`, pour les captures lancées
+    /// automatiquement (repli du scraper)
+    pub fn headless(mut self) -> Self {
+        self.headless = true;
+        self.export_path = None;
+        self
+    }
+
     /// Lance le navigateur, navigue vers l'URL et capture toutes les requêtes réseau
     pub async fn sniff(&self, url: &str) -> Result<()> {
         // Réinitialiser les résultats
@@ -50,8 +65,9 @@ impl NetworkSniffer {
         }
 
         // Configuration du navigateur
-        let config = BrowserConfig::builder()
-            .with_head()
+        let builder = BrowserConfig::builder();
+        let builder = if self.headless { builder } else { builder.with_head() };
+        let config = builder
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to build browser config: {}", e))?;
 
@@ -165,7 +181,9 @@ impl NetworkSniffer {
         }
 
         // Exporter vers JSON
-        self.export_to_json("network_output.json").await?;
+        if let Some(path) = &self.export_path {
+            self.export_to_json(path).await?;
+        }
 
         // Fermer le navigateur
         browser.close().await?;
@@ -181,9 +199,12 @@ impl NetworkSniffer {
     }
 
     /// Exporte les résultats vers un fichier JSON
-    async fn export_to_json(&self, filename: &str) -> Result<()> {
-        let requests = self.captured_requests.lock().unwrap();
-        let json = serde_json::to_string_pretty(&*requests)?;
+    async fn export_to_json(&self, filename: &std::path::Path) -> Result<()> {
+        // Sérialiser puis relâcher le verrou avant l'écriture asynchrone
+        let json = {
+            let requests = self.captured_requests.lock().unwrap();
+            serde_json::to_string_pretty(&*requests)?
+        };
         tokio::fs::write(filename, json).await?;
         Ok(())
     }